@@ -0,0 +1,303 @@
+use std::{
+    env, fs,
+    io::Error as IoError,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error as ThisError;
+
+use crate::input::{ImputedCompilationData, ImputedEngineLimits};
+
+/// Steam app ids of games that load version 49 models, every other game is assumed to use version 48.
+const VERSION_49_APP_IDS: [i32; 2] = [620, 730];
+/// Steam app ids of games that load models with up to 256 bones, every other game loads up to 128.
+const LARGE_BONE_LIMIT_APP_IDS: [i32; 1] = [730];
+/// The most materials studiomdl lets a model use, which models of every game are made to fit.
+const DEFAULT_MATERIAL_LIMIT: usize = 32;
+
+#[derive(Debug, ThisError)]
+pub enum GameInfoError {
+    #[error("Failed To Read Game Info: {0}")]
+    FailedFileRead(#[from] IoError),
+    #[error("Game Info Is Missing The Game Name")]
+    MissingGameName,
+    #[error("Game Info Has An Unterminated String")]
+    UnterminatedString,
+}
+
+#[derive(Debug, ThisError)]
+pub enum PathTokenError {
+    #[error("Unknown Path Token {0}")]
+    UnknownToken(String),
+    #[error("Path Token Is Not Closed")]
+    UnterminatedToken,
+}
+
+/// Where a game loads models from, which model version it reads and how many bones and materials it loads.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct GameProfile {
+    pub name: String,
+    pub export_path: String,
+    pub target_version: i32,
+    pub engine_limits: ImputedEngineLimits,
+}
+
+/// Creates a profile from a mod directory or its gameinfo.txt, the models are exported to the models folder of the mod.
+pub fn detect_game_profile(path: &Path) -> Result<GameProfile, GameInfoError> {
+    let (game_directory, game_info_path) = if path.is_dir() {
+        (path, path.join("gameinfo.txt"))
+    } else {
+        (path.parent().unwrap_or(path), path.to_path_buf())
+    };
+
+    let tokens = tokenize_key_values(&fs::read_to_string(game_info_path)?)?;
+
+    let mut name = None;
+    let mut app_id = None;
+    for pair in tokens.windows(2) {
+        let (key, value) = (&pair[0], &pair[1]);
+
+        if name.is_none() && key.eq_ignore_ascii_case("game") {
+            name = Some(value.clone());
+        }
+
+        if app_id.is_none() && key.eq_ignore_ascii_case("SteamAppId") {
+            app_id = value.parse::<i32>().ok();
+        }
+    }
+
+    // Games installed through Steam are saved relative to the steamapps folder, so the profile works with other install locations.
+    let export_path = game_directory.join("models");
+    let export_path = match find_steamapps_directories()
+        .iter()
+        .find_map(|steamapps| export_path.strip_prefix(steamapps).ok().map(Path::to_path_buf))
+    {
+        Some(relative_path) => Path::new("$STEAMAPPS").join(relative_path),
+        None => export_path,
+    };
+
+    Ok(GameProfile {
+        name: name.ok_or(GameInfoError::MissingGameName)?,
+        export_path: export_path.to_string_lossy().to_string(),
+        target_version: match app_id {
+            Some(app_id) if VERSION_49_APP_IDS.contains(&app_id) => 49,
+            _ => 48,
+        },
+        engine_limits: ImputedEngineLimits {
+            bones: match app_id {
+                Some(app_id) if LARGE_BONE_LIMIT_APP_IDS.contains(&app_id) => 256,
+                _ => 128,
+            },
+            materials: DEFAULT_MATERIAL_LIMIT,
+        },
+    })
+}
+
+/// Expands the tokens in the export path of the project, the source files of the project are where the game of $GAMEDIR is searched from.
+pub fn expand_export_path(data: &ImputedCompilationData) -> Result<String, PathTokenError> {
+    let source_directory = data
+        .body_parts
+        .iter()
+        .flat_map(|body_part| &body_part.models)
+        .find(|model| !model.is_blank && !model.file_source.is_empty())
+        .and_then(|model| Path::new(&model.file_source).parent());
+
+    expand_path_tokens(&data.export_path, source_directory)
+}
+
+/// Replaces every $NAME or ${NAME} in the path with the folder the token stands for.
+/// $STEAMAPPS is the steamapps folder of the Steam library the rest of the path is in.
+/// $GAMEDIR is the game set in VPROJECT like the Source SDK tools use, without it the game is the closest folder above the source directory with a gameinfo.txt,
+/// like studiomdl finds the game from the folder of the QC file. Every other name is an environment variable, environment variables with the name of a token replace the token.
+pub fn expand_path_tokens(path: &str, source_directory: Option<&Path>) -> Result<String, PathTokenError> {
+    let mut expanded_path = String::with_capacity(path.len());
+    let mut characters = path.chars().peekable();
+
+    while let Some(character) = characters.next() {
+        if character != '$' {
+            expanded_path.push(character);
+            continue;
+        }
+
+        let mut name = String::new();
+        if characters.next_if_eq(&'{').is_some() {
+            loop {
+                match characters.next() {
+                    Some('}') => break,
+                    Some(character) => name.push(character),
+                    None => return Err(PathTokenError::UnterminatedToken),
+                }
+            }
+        } else {
+            while let Some(character) = characters.next_if(|character| character.is_ascii_alphanumeric() || *character == '_') {
+                name.push(character);
+            }
+        }
+
+        // A dollar sign that does not start a name is part of the path.
+        if name.is_empty() {
+            expanded_path.push('$');
+            continue;
+        }
+
+        let remaining_path = characters.clone().collect::<String>();
+        match resolve_path_token(&name, &remaining_path, source_directory) {
+            Some(value) => expanded_path.push_str(&value),
+            None => return Err(PathTokenError::UnknownToken(name)),
+        }
+    }
+
+    Ok(expanded_path)
+}
+
+fn resolve_path_token(name: &str, remaining_path: &str, source_directory: Option<&Path>) -> Option<String> {
+    if let Ok(value) = env::var(name) {
+        return Some(value);
+    }
+
+    match name {
+        "STEAMAPPS" => {
+            // The folder of the game is the first two folders after the token, like common and the name of the game.
+            let steamapps_directories = find_steamapps_directories();
+            let game_directory = Path::new(remaining_path.trim_start_matches(['/', '\\']))
+                .components()
+                .take(2)
+                .collect::<PathBuf>();
+            steamapps_directories
+                .iter()
+                .find(|steamapps_directory| steamapps_directory.join(&game_directory).is_dir())
+                .or(steamapps_directories.first())
+                .map(|directory| directory.to_string_lossy().to_string())
+        }
+        "GAMEDIR" => env::var("VPROJECT").ok().or_else(|| {
+            source_directory?
+                .ancestors()
+                .find(|directory| directory.join("gameinfo.txt").is_file())
+                .map(|directory| directory.to_string_lossy().to_string())
+        }),
+        _ => None,
+    }
+}
+
+/// The steamapps folders of every Steam library, starting with the library of Steam in the place it is installed to by default on each platform.
+/// Steam lists the other libraries, like ones on other drives, in the libraryfolders.vdf of its own library.
+fn find_steamapps_directories() -> Vec<PathBuf> {
+    let mut steam_directories = Vec::new();
+
+    if let Some(program_files) = env::var_os("ProgramFiles(x86)") {
+        steam_directories.push(PathBuf::from(program_files).join("Steam"));
+    }
+
+    if let Some(home_directory) = env::var_os("HOME").map(PathBuf::from) {
+        steam_directories.push(home_directory.join(".steam").join("steam"));
+        steam_directories.push(home_directory.join(".local").join("share").join("Steam"));
+        steam_directories.push(home_directory.join("Library").join("Application Support").join("Steam"));
+    }
+
+    let Some(steamapps_directory) = steam_directories
+        .into_iter()
+        .map(|steam_directory| steam_directory.join("steamapps"))
+        .find(|steamapps_directory| steamapps_directory.is_dir())
+    else {
+        return Vec::new();
+    };
+
+    let library_tokens = fs::read_to_string(steamapps_directory.join("libraryfolders.vdf"))
+        .ok()
+        .and_then(|library_text| tokenize_key_values(&library_text).ok())
+        .unwrap_or_default();
+
+    // The library of Steam is also listed, which can be through a link to it, so libraries are compared by where they lead.
+    let mut steamapps_directories = vec![steamapps_directory];
+    for pair in library_tokens.windows(2) {
+        if !pair[0].eq_ignore_ascii_case("path") {
+            continue;
+        }
+
+        let library_directory = PathBuf::from(pair[1].replace("\\\\", "\\")).join("steamapps");
+        let is_listed = steamapps_directories
+            .iter()
+            .any(|listed_directory| fs::canonicalize(listed_directory).ok() == fs::canonicalize(&library_directory).ok());
+        if library_directory.is_dir() && !is_listed {
+            steamapps_directories.push(library_directory);
+        }
+    }
+
+    steamapps_directories
+}
+
+/// Finds the materials folder of the game the export path is in, which is next to the models folder.
+pub fn find_materials_directory(export_path: &Path) -> Option<PathBuf> {
+    export_path
+        .ancestors()
+        .find(|path| path.file_name().is_some_and(|name| name.eq_ignore_ascii_case("models")))
+        .and_then(Path::parent)
+        .map(|game_directory| game_directory.join("materials"))
+}
+
+/// Whether the material has a VMT in one of the material paths, materials packed in VPK files are not found.
+pub fn material_exists(materials_directory: &Path, material_paths: &[String], material: &str) -> bool {
+    material_paths
+        .iter()
+        .any(|material_path| materials_directory.join(format!("{}{}.vmt", material_path, material)).is_file())
+}
+
+/// Whether the VMT of the material is drawn translucent, materials without a VMT in the material paths are not.
+pub fn material_is_translucent(materials_directory: &Path, material_paths: &[String], material: &str) -> bool {
+    let Some(material_text) = material_paths
+        .iter()
+        .find_map(|material_path| fs::read_to_string(materials_directory.join(format!("{}{}.vmt", material_path, material))).ok())
+    else {
+        return false;
+    };
+
+    let Ok(tokens) = tokenize_key_values(&material_text) else {
+        return false;
+    };
+
+    tokens
+        .windows(2)
+        .any(|pair| (pair[0].eq_ignore_ascii_case("$translucent") || pair[0].eq_ignore_ascii_case("$additive")) && pair[1].trim() == "1")
+}
+
+/// Splits a key values file into its keys and values, braces are dropped as only single values are looked up.
+fn tokenize_key_values(text: &str) -> Result<Vec<String>, GameInfoError> {
+    let mut tokens = Vec::new();
+
+    for line in text.lines() {
+        let mut characters = line.chars().peekable();
+
+        while let Some(character) = characters.next() {
+            match character {
+                '/' if characters.peek() == Some(&'/') => break,
+                '{' | '}' => {}
+                '"' => {
+                    let mut token = String::new();
+                    loop {
+                        match characters.next() {
+                            Some('"') => break,
+                            Some(character) => token.push(character),
+                            None => return Err(GameInfoError::UnterminatedString),
+                        }
+                    }
+                    tokens.push(token);
+                }
+                character if character.is_whitespace() => {}
+                character => {
+                    let mut token = String::from(character);
+                    while let Some(&next) = characters.peek() {
+                        if next.is_whitespace() || next == '"' || next == '{' || next == '}' {
+                            break;
+                        }
+                        token.push(next);
+                        characters.next();
+                    }
+                    tokens.push(token);
+                }
+            }
+        }
+    }
+
+    Ok(tokens)
+}
@@ -0,0 +1,117 @@
+//! Compiles the fixture projects in tests/golden and compares the written files against the expected dump checked in next to each project.
+//! Every folder in tests/golden with a project.json is a case, its source files are relative to the project like a saved project.
+//! A case without an expected dump fails, the dumps are only written when SOURCE_WRENCH_UPDATE_GOLDEN is set, review the changes before committing them.
+//! Run with: cargo test --features golden-tests
+
+use std::{env, fs, path::Path};
+
+use tauri::Manager;
+
+use crate::{compile_project, import::FileManager, plugin::PluginManager, project::open_project, read::dump_model_files, write::calculate_checksum};
+
+const EXPECTED_FILE_NAME: &str = "expected.txt";
+
+#[test]
+fn golden_files() {
+    let cases_directory = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("golden");
+    let update_golden = env::var_os("SOURCE_WRENCH_UPDATE_GOLDEN").is_some();
+
+    let mut case_paths = fs::read_dir(&cases_directory)
+        .expect("Golden Cases Directory Is Missing")
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.join("project.json").is_file())
+        .collect::<Vec<_>>();
+    case_paths.sort();
+
+    let mut failed_cases = Vec::new();
+    for case_path in case_paths {
+        let case_name = case_path.file_name().unwrap().to_string_lossy().to_string();
+        let dump = match compile_case(&case_path, &case_name) {
+            Ok(dump) => dump,
+            Err(error) => {
+                failed_cases.push(format!("{}: {}", case_name, error));
+                continue;
+            }
+        };
+
+        let expected_path = case_path.join(EXPECTED_FILE_NAME);
+        if update_golden {
+            fs::write(&expected_path, &dump).unwrap();
+            continue;
+        }
+
+        if !expected_path.is_file() {
+            failed_cases.push(format!(
+                "{}: Has no {}, run with SOURCE_WRENCH_UPDATE_GOLDEN set to write it",
+                case_name, EXPECTED_FILE_NAME
+            ));
+            continue;
+        }
+
+        let expected = fs::read_to_string(&expected_path).unwrap().replace("\r\n", "\n");
+        if let Some((line_index, (expected_line, compiled_line))) = expected
+            .lines()
+            .zip(dump.lines())
+            .enumerate()
+            .find(|(_, (expected_line, compiled_line))| expected_line != compiled_line)
+        {
+            failed_cases.push(format!(
+                "{}: Line {} was \"{}\" but is now \"{}\"",
+                case_name,
+                line_index + 1,
+                expected_line.trim(),
+                compiled_line.trim()
+            ));
+        } else if expected.lines().count() != dump.lines().count() {
+            failed_cases.push(format!(
+                "{}: Expected {} lines but the dump has {}",
+                case_name,
+                expected.lines().count(),
+                dump.lines().count()
+            ));
+        }
+    }
+
+    assert!(failed_cases.is_empty(), "Golden cases failed:\n{}", failed_cases.join("\n"));
+}
+
+/// Compiles the project of the case into a temporary folder and dumps the written files.
+/// The size and checksum of every file is added to the dump, so changes to data the dump does not show still fail the case.
+fn compile_case(case_path: &Path, case_name: &str) -> Result<String, String> {
+    let mut project = open_project(&case_path.join("project.json")).map_err(|error| error.to_string())?.project;
+    let export_path = env::temp_dir().join("source-wrench-golden").join(case_name);
+    let _ = fs::remove_dir_all(&export_path);
+    fs::create_dir_all(&export_path).map_err(|error| error.to_string())?;
+    project.export_path = export_path.to_string_lossy().to_string();
+
+    let app = tauri::test::mock_app();
+    app.manage(FileManager::default());
+    let file_manager = app.state::<FileManager>();
+
+    let source_files = project
+        .body_parts
+        .iter()
+        .flat_map(|body_part| body_part.models.iter().filter(|model| !model.is_blank))
+        .flat_map(|model| [&model.file_source, &model.flex_file_source])
+        .chain(project.animations.iter().map(|animation| &animation.file_source))
+        .filter(|source_file| !source_file.is_empty());
+    for source_file in source_files {
+        file_manager.load_file(source_file.clone()).map_err(|error| error.to_string())?;
+    }
+
+    let report = compile_project(project, false, &file_manager, &PluginManager::default()).ok_or("Compile Failed")?;
+    let model_file = report
+        .files
+        .iter()
+        .find(|file| file.name.ends_with(".mdl"))
+        .ok_or("Compile Wrote No Model File")?;
+    let mut dump = dump_model_files(&export_path.join(&model_file.name)).map_err(|error| error.to_string())?;
+
+    dump.push('\n');
+    for file in &report.files {
+        let data = fs::read(export_path.join(&file.name)).map_err(|error| error.to_string())?;
+        dump.push_str(&format!("{}: {} Bytes, Checksum {}\n", file.name, data.len(), calculate_checksum(&data)));
+    }
+
+    Ok(dump)
+}
@@ -1,22 +1,37 @@
 use std::{
-    io::Error,
+    cmp::Reverse,
+    collections::{HashMap, HashSet},
+    fs::{self, File},
+    io::{BufReader, Error, Read, Result as IoResult},
     path::{Path, PathBuf},
-    sync::{Arc, Mutex},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Condvar, Mutex,
+    },
+    thread,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 
 use indexmap::IndexMap;
-use serde::Serialize;
+use serde::{Serialize, Serializer};
 use thiserror::Error as ThisError;
 
 use crate::utilities::{
     logging::{log, LogLevel},
-    mathematics::{Quaternion, Vector2, Vector3},
+    mathematics::{Quaternion, Vector2, Vector3, Vector4},
+    progress::import_progress,
 };
 
+mod mdl;
 mod obj;
+mod push;
 mod smd;
 
+pub use mdl::decompile_mdl;
+use mdl::ParseMDLError;
 use obj::ParseOBJError;
+pub use push::PushedFileData;
+use push::{load_pushed_file, ParsePushedError};
 use smd::ParseSMDError;
 
 #[derive(Debug, Default, Serialize)]
@@ -45,6 +60,62 @@ pub struct ImportAnimation {
     pub channels: Vec<ImportChannel>,
 }
 
+/// A frame of an imported animation, used to inspect the animation before compiling.
+#[derive(Debug, Serialize)]
+pub struct ImportAnimationFrame {
+    pub frame_count: usize,
+    pub bones: Vec<ImportBonePose>,
+}
+
+/// The transform of a bone relative to its parent.
+#[derive(Debug, Serialize)]
+pub struct ImportBonePose {
+    pub name: String,
+    pub position: [f64; 3],
+    /// The roll, pitch and yaw in degrees.
+    pub rotation: [f64; 3],
+    /// Bones without keys in the animation stay in their bind pose.
+    pub animated: bool,
+}
+
+impl ImportFileData {
+    /// Finds the pose of every bone at the frame the same way animations are baked, a bone holds its last key until the next key.
+    /// Frames past the end of the animation show the last frame.
+    pub fn sample_animation(&self, animation_name: &str, frame: usize) -> Option<ImportAnimationFrame> {
+        let animation = self.animations.iter().find(|animation| animation.name == animation_name)?;
+        let frame = frame.min(animation.frame_count.saturating_sub(1));
+
+        let bones = self
+            .skeleton
+            .iter()
+            .enumerate()
+            .map(|(bone_index, bone)| {
+                let channel = animation.channels.iter().find(|channel| channel.bone == bone_index);
+                let position = channel
+                    .and_then(|channel| channel.position.iter().rfind(|keyframe| keyframe.frame <= frame))
+                    .map_or(bone.position, |keyframe| keyframe.value);
+                let rotation = channel
+                    .and_then(|channel| channel.rotation.iter().rfind(|keyframe| keyframe.frame <= frame))
+                    .map_or(bone.orientation, |keyframe| keyframe.value)
+                    .to_angles()
+                    .to_degrees();
+
+                ImportBonePose {
+                    name: bone.name.clone(),
+                    position: [position.x, position.y, position.z],
+                    rotation: [rotation.roll, rotation.pitch, rotation.yaw],
+                    animated: channel.is_some(),
+                }
+            })
+            .collect();
+
+        Some(ImportAnimationFrame {
+            frame_count: animation.frame_count,
+            bones,
+        })
+    }
+}
+
 #[derive(Debug, Default)]
 pub struct ImportChannel {
     pub bone: usize,
@@ -61,19 +132,51 @@ pub struct ImportKeyFrame<T> {
 #[derive(Debug, Default, Serialize)]
 pub struct ImportPart {
     pub name: String,
-    #[serde(skip_serializing)]
+    #[serde(rename = "vertex_count", serialize_with = "serialize_vertex_count")]
     pub vertices: Vec<ImportVertex>,
-    #[serde(skip_serializing)]
+    #[serde(rename = "triangle_counts", serialize_with = "serialize_triangle_counts")]
     pub polygons: IndexMap<String, Vec<Vec<usize>>>,
     #[serde(skip_serializing)]
     pub flexes: Vec<ImportFlex>,
 }
 
+/// Parts are sent to the window as statistics, parsers can repeat vertices so only unique vertices are counted like in a compiled mesh.
+fn serialize_vertex_count<S: Serializer>(vertices: &[ImportVertex], serializer: S) -> Result<S::Ok, S::Error> {
+    let unique_vertices = vertices
+        .iter()
+        .map(|vertex| {
+            [
+                vertex.position.x.to_bits(),
+                vertex.position.y.to_bits(),
+                vertex.position.z.to_bits(),
+                vertex.normal.x.to_bits(),
+                vertex.normal.y.to_bits(),
+                vertex.normal.z.to_bits(),
+                vertex.texture_coordinate.x.to_bits(),
+                vertex.texture_coordinate.y.to_bits(),
+            ]
+        })
+        .collect::<HashSet<_>>();
+
+    serializer.serialize_u64(unique_vertices.len() as u64)
+}
+
+/// The triangles of each material once the polygons are triangulated.
+fn serialize_triangle_counts<S: Serializer>(polygons: &IndexMap<String, Vec<Vec<usize>>>, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_map(
+        polygons
+            .iter()
+            .map(|(material, polygons)| (material, polygons.iter().map(|polygon| polygon.len().saturating_sub(2)).sum::<usize>())),
+    )
+}
+
 #[derive(Debug, Default)]
 pub struct ImportVertex {
     pub position: Vector3,
     pub normal: Vector3,
     pub texture_coordinate: Vector2,
+    /// The red, green, blue and alpha of the vertex from zero to one, when the source format stores a color.
+    pub color: Option<Vector4>,
     pub links: Vec<ImportLink>,
 }
 
@@ -83,12 +186,14 @@ pub struct ImportLink {
     pub weight: f64,
 }
 
+/// A frame of a vertex animation, named after the shape it makes when the source format names them.
 #[derive(Debug, Default)]
 pub struct ImportFlex {
     pub name: Option<String>,
     pub vertices: Vec<ImportFlexVertex>,
 }
 
+/// How far a vertex of the part moves in the frame, only the vertices that move are listed.
 #[derive(Debug, Default)]
 pub struct ImportFlexVertex {
     pub index: usize,
@@ -110,55 +215,405 @@ pub enum ParseError {
     FailedSMDFileParse(#[from] ParseSMDError),
     #[error("Failed To Parse OBJ File: {0}")]
     FailedOBJFileParse(#[from] ParseOBJError),
+    #[error("Failed To Parse MDL File: {0}")]
+    FailedMDLFileParse(#[from] ParseMDLError),
+    #[error("Failed To Read Pushed File: {0}")]
+    FailedPushedFileParse(#[from] ParsePushedError),
+}
+
+/// How the last import of a file went, failed imports are kept so the error can be shown next to the file.
+#[derive(Debug, Clone, Serialize)]
+pub struct FileStatus {
+    pub path: String,
+    pub loaded: bool,
+    pub size: u64,
+    pub parse_milliseconds: f64,
+    /// Seconds since the Unix epoch.
+    pub modified: Option<u64>,
+    pub error: Option<String>,
+    pub tolerant: bool,
 }
 
 #[derive(Debug, Default)]
 pub struct FileManager {
     pub files: Mutex<IndexMap<PathBuf, Arc<ImportFileData>>>,
+    /// The modification time of each file when it was imported, used to find files that changed on disk.
+    modified_times: Mutex<IndexMap<PathBuf, SystemTime>>,
+    statuses: Mutex<IndexMap<PathBuf, FileStatus>>,
+    /// How many times each file was loaded and not unloaded yet, a file is shared by everything that loads it.
+    load_counts: Mutex<IndexMap<PathBuf, usize>>,
+    /// Files parsed in the tolerant mode, which stays set when the file is unloaded so loading it again parses it the same way.
+    tolerant_files: Mutex<HashSet<PathBuf>>,
+    watching: AtomicBool,
+    import_queue: ImportQueue,
+}
+
+/// Limits how many files are imported at once, files waiting to be imported are started in the order they were queued.
+/// Files the user is working on are started first, so loading many files does not hold up the file being set up.
+#[derive(Debug, Default)]
+struct ImportQueue {
+    state: Mutex<ImportQueueState>,
+    changed: Condvar,
+}
+
+#[derive(Debug, Default)]
+struct ImportQueueState {
+    running: usize,
+    next_ticket: u64,
+    waiting: Vec<(PathBuf, u64)>,
+    /// The ticket of the last time each waiting file was prioritized, files prioritized last are started first.
+    priorities: HashMap<PathBuf, u64>,
+    /// A file prioritized before it was queued, only the file prioritized last is kept so files that are never imported are forgotten.
+    queued_priority: Option<(PathBuf, u64)>,
+}
+
+impl ImportQueue {
+    fn acquire(&self, file_path: &Path) -> ImportSlot<'_> {
+        let worker_count = thread::available_parallelism().map_or(1, |count| count.get());
+        let mut state = self.state.lock().unwrap();
+
+        let ticket = state.next_ticket;
+        state.next_ticket += 1;
+        state.waiting.push((file_path.to_path_buf(), ticket));
+
+        if state.queued_priority.as_ref().is_some_and(|(path, _)| path == file_path) {
+            let (path, priority) = state.queued_priority.take().unwrap();
+            state.priorities.insert(path, priority);
+        }
+
+        loop {
+            let next_ticket = state
+                .waiting
+                .iter()
+                .max_by_key(|(path, ticket)| (state.priorities.get(path).copied(), Reverse(*ticket)))
+                .map(|(_, ticket)| *ticket);
+
+            if state.running < worker_count && next_ticket == Some(ticket) {
+                break;
+            }
+
+            state = self.changed.wait(state).unwrap();
+        }
+
+        state.waiting.retain(|(_, waiting_ticket)| *waiting_ticket != ticket);
+        state.running += 1;
+
+        // The priority is kept while the same file is still waiting to be imported again.
+        if !state.waiting.iter().any(|(path, _)| path == file_path) {
+            state.priorities.remove(file_path);
+        }
+
+        ImportSlot { queue: self }
+    }
+
+    fn prioritize(&self, file_path: PathBuf) {
+        let mut state = self.state.lock().unwrap();
+        let ticket = state.next_ticket;
+        state.next_ticket += 1;
+
+        if state.waiting.iter().any(|(path, _)| *path == file_path) {
+            state.priorities.insert(file_path, ticket);
+            self.changed.notify_all();
+        } else {
+            state.queued_priority = Some((file_path, ticket));
+        }
+    }
+}
+
+/// Releases the place of the import in the queue once the import is done.
+struct ImportSlot<'a> {
+    queue: &'a ImportQueue,
+}
+
+impl Drop for ImportSlot<'_> {
+    fn drop(&mut self) {
+        let mut state = self.queue.state.lock().unwrap();
+        state.running -= 1;
+        self.queue.changed.notify_all();
+    }
 }
 
 impl FileManager {
     pub fn load_file(&self, path: String) -> Result<Arc<ImportFileData>, ParseError> {
         let file_path = PathBuf::from(path);
-        let mut files = self.files.lock().unwrap();
 
-        if let Some(file) = files.get(&file_path) {
+        if let Some(file) = self.files.lock().unwrap().get(&file_path) {
+            *self.load_counts.lock().unwrap().entry(file_path).or_default() += 1;
             return Ok(Arc::clone(file));
         }
 
-        if !file_path.try_exists()? {
-            return Err(ParseError::FileDoesNotExist);
-        }
+        // The files are not locked while importing so other files can be imported at the same time.
+        let (imported_file, modified_time) = self.import_file_with_status(&file_path)?;
+        let imported_file = Arc::new(imported_file);
 
-        let file_extension = file_path.extension().ok_or_else(|| ParseError::FileDoesNotHaveExtension)?;
+        // If the same file was imported at the same time, the file that finished first is kept so everyone shares it.
+        let mut files = self.files.lock().unwrap();
+        let file = Arc::clone(files.entry(file_path.clone()).or_insert(imported_file));
+        self.modified_times.lock().unwrap().entry(file_path.clone()).or_insert(modified_time);
+        *self.load_counts.lock().unwrap().entry(file_path.clone()).or_default() += 1;
+        self.set_loaded(&file_path, true);
+        Ok(file)
+    }
 
-        let imported_file = match file_extension.to_string_lossy().to_lowercase().as_str() {
-            "smd" => smd::load_smd(&file_path)?,
-            "obj" => obj::load_obj(&file_path)?,
-            _ => return Err(ParseError::UnsupportedFileFormat),
-        };
+    /// Imports a file again even if it did not change on disk, a file that failed to load is loaded once it imports.
+    pub fn reload_file(&self, path: String) -> Result<Arc<ImportFileData>, ParseError> {
+        let file_path = PathBuf::from(path);
+        let (imported_file, modified_time) = self.import_file_with_status(&file_path)?;
+        let imported_file = Arc::new(imported_file);
 
-        log(
-            format!(
-                "Loaded {} file: {}",
-                file_extension.to_string_lossy().to_uppercase(),
-                file_path.as_os_str().to_string_lossy()
-            ),
-            LogLevel::Verbose,
+        let mut files = self.files.lock().unwrap();
+        files.insert(file_path.clone(), Arc::clone(&imported_file));
+        self.modified_times.lock().unwrap().insert(file_path.clone(), modified_time);
+        self.load_counts.lock().unwrap().entry(file_path.clone()).or_insert(1);
+        self.set_loaded(&file_path, true);
+
+        Ok(imported_file)
+    }
+
+    /// Replaces the file with one sent by another program, the file is used instead of the file on disk until it is released.
+    /// Pushed files are not watched, as the program sends the file again when it changes.
+    pub fn push_file(&self, path: String, pushed_file: PushedFileData) -> Result<Arc<ImportFileData>, ParseError> {
+        let file_path = PathBuf::from(path);
+        let pushed_file = Arc::new(load_pushed_file(pushed_file)?);
+
+        let mut files = self.files.lock().unwrap();
+        files.insert(file_path.clone(), Arc::clone(&pushed_file));
+        self.modified_times.lock().unwrap().swap_remove(&file_path);
+        self.load_counts.lock().unwrap().entry(file_path.clone()).or_insert(1);
+        self.statuses.lock().unwrap().insert(
+            file_path.clone(),
+            FileStatus {
+                path: file_path.to_string_lossy().to_string(),
+                loaded: true,
+                size: 0,
+                parse_milliseconds: 0.0,
+                modified: SystemTime::now().duration_since(UNIX_EPOCH).ok().map(|modified| modified.as_secs()),
+                error: None,
+                tolerant: false,
+            },
         );
-        let file = Arc::new(imported_file);
-        files.insert(file_path, Arc::clone(&file));
-        Ok(file)
+
+        Ok(pushed_file)
     }
 
+    /// Releases one load of the file, the file is only removed once everything that loaded it has unloaded it.
     pub fn unload_file(&self, path: String) {
         let file_path = PathBuf::from(path);
         let mut files = self.files.lock().unwrap();
+        let mut load_counts = self.load_counts.lock().unwrap();
+
+        if let Some(load_count) = load_counts.get_mut(&file_path) {
+            if *load_count > 1 {
+                *load_count -= 1;
+                return;
+            }
+        }
+
+        load_counts.swap_remove(&file_path);
+        files.swap_remove(&file_path);
+        self.modified_times.lock().unwrap().swap_remove(&file_path);
+        self.statuses.lock().unwrap().swap_remove(&file_path);
+    }
+
+    /// Removes the file no matter how many times it was loaded.
+    pub fn release_file(&self, path: String) {
+        let file_path = PathBuf::from(path);
+        let mut files = self.files.lock().unwrap();
+        self.load_counts.lock().unwrap().swap_remove(&file_path);
         files.swap_remove(&file_path);
+        self.modified_times.lock().unwrap().swap_remove(&file_path);
+        self.statuses.lock().unwrap().swap_remove(&file_path);
+    }
+
+    pub fn release_all_files(&self) {
+        let mut files = self.files.lock().unwrap();
+        self.load_counts.lock().unwrap().clear();
+        files.clear();
+        self.modified_times.lock().unwrap().clear();
+        self.statuses.lock().unwrap().clear();
+    }
+
+    /// The status of every file that was loaded or failed to load, in the order they were first imported.
+    pub fn file_statuses(&self) -> Vec<FileStatus> {
+        self.statuses.lock().unwrap().values().cloned().collect()
+    }
+
+    fn import_file_with_status(&self, file_path: &Path) -> Result<(ImportFileData, SystemTime), ParseError> {
+        let tolerant = self.tolerant_files.lock().unwrap().contains(file_path);
+        let import_slot = self.import_queue.acquire(file_path);
+        let import_start = Instant::now();
+        let imported_file = import_existing_file(file_path, tolerant);
+        let parse_duration = import_start.elapsed();
+        drop(import_slot);
+
+        let metadata = fs::metadata(file_path).ok();
+        let status = FileStatus {
+            path: file_path.to_string_lossy().to_string(),
+            loaded: self.files.lock().unwrap().contains_key(file_path),
+            size: metadata.as_ref().map_or(0, |metadata| metadata.len()),
+            parse_milliseconds: parse_duration.as_secs_f64() * 1000.0,
+            modified: metadata
+                .and_then(|metadata| metadata.modified().ok())
+                .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+                .map(|modified| modified.as_secs()),
+            error: imported_file.as_ref().err().map(ToString::to_string),
+            tolerant,
+        };
+        self.statuses.lock().unwrap().insert(file_path.to_path_buf(), status);
+
+        imported_file
+    }
+
+    /// Moves the file ahead of the other files waiting to be imported, a file that is not waiting yet is moved ahead once it is.
+    pub fn prioritize_file(&self, path: String) {
+        let file_path = PathBuf::from(path);
+
+        if !self.files.lock().unwrap().contains_key(&file_path) {
+            self.import_queue.prioritize(file_path);
+        }
+    }
+
+    /// Only changes how the file is parsed the next time it is imported.
+    pub fn set_tolerant(&self, path: String, tolerant: bool) {
+        let file_path = PathBuf::from(path);
+        let mut tolerant_files = self.tolerant_files.lock().unwrap();
+
+        if tolerant {
+            tolerant_files.insert(file_path);
+        } else {
+            tolerant_files.remove(&file_path);
+        }
+    }
+
+    fn set_loaded(&self, file_path: &Path, loaded: bool) {
+        if let Some(status) = self.statuses.lock().unwrap().get_mut(file_path) {
+            status.loaded = loaded;
+        }
     }
 
     pub fn get_file(&self, path: &str) -> Option<Arc<ImportFileData>> {
         let file_path = Path::new(path);
         self.files.lock().unwrap().get(file_path).cloned()
     }
+
+    pub fn set_watching(&self, watching: bool) {
+        self.watching.store(watching, Ordering::Relaxed);
+    }
+
+    pub fn is_watching(&self) -> bool {
+        self.watching.load(Ordering::Relaxed)
+    }
+
+    /// Imports every loaded file again that changed on disk since it was imported.
+    /// A file is only imported once it has not changed for the debounce time, as exporters can take a while to write a file.
+    pub fn reload_changed_files(&self, debounce: Duration) -> Vec<(PathBuf, Arc<ImportFileData>)> {
+        let changed_files = self
+            .modified_times
+            .lock()
+            .unwrap()
+            .iter()
+            .filter_map(|(path, modified_time)| {
+                let current_modified_time = fs::metadata(path).and_then(|metadata| metadata.modified()).ok()?;
+
+                if current_modified_time == *modified_time || current_modified_time.elapsed().unwrap_or_default() < debounce {
+                    return None;
+                }
+
+                Some((path.clone(), current_modified_time))
+            })
+            .collect::<Vec<_>>();
+
+        let mut reloaded_files = Vec::new();
+        for (path, modified_time) in changed_files {
+            // The time is updated even if the import fails so a broken file is not imported again every check.
+            match self.modified_times.lock().unwrap().get_mut(&path) {
+                Some(previous_modified_time) => *previous_modified_time = modified_time,
+                None => continue, // The file was unloaded while checking.
+            }
+
+            match self.import_file_with_status(&path) {
+                Ok((file, _)) => {
+                    let file = Arc::new(file);
+                    self.files.lock().unwrap().insert(path.clone(), Arc::clone(&file));
+                    reloaded_files.push((path, file));
+                }
+                Err(error) => log(format!("Fail To Reload {}: {}!", path.to_string_lossy(), error), LogLevel::Error),
+            }
+        }
+
+        reloaded_files
+    }
+}
+
+fn import_existing_file(file_path: &Path, tolerant: bool) -> Result<(ImportFileData, SystemTime), ParseError> {
+    if !file_path.try_exists()? {
+        return Err(ParseError::FileDoesNotExist);
+    }
+
+    let modified_time = fs::metadata(file_path)?.modified()?;
+    Ok((import_file(file_path, tolerant)?, modified_time))
+}
+
+/// Only SMD and VTA files have a tolerant mode, the other formats are parsed the same either way.
+fn import_file(file_path: &Path, tolerant: bool) -> Result<ImportFileData, ParseError> {
+    let file_extension = file_path.extension().ok_or_else(|| ParseError::FileDoesNotHaveExtension)?;
+    let file_length = fs::metadata(file_path)?.len() as usize;
+    import_progress(&file_path.to_string_lossy(), 0, file_length);
+
+    let imported_file = match file_extension.to_string_lossy().to_lowercase().as_str() {
+        "smd" | "vta" => smd::load_smd(file_path, tolerant)?,
+        "obj" => obj::load_obj(file_path)?,
+        "mdl" => mdl::load_mdl(file_path)?,
+        _ => return Err(ParseError::UnsupportedFileFormat),
+    };
+
+    import_progress(&file_path.to_string_lossy(), file_length, file_length);
+    log(
+        format!(
+            "Loaded {} file: {}",
+            file_extension.to_string_lossy().to_uppercase(),
+            file_path.as_os_str().to_string_lossy()
+        ),
+        LogLevel::Verbose,
+    );
+
+    Ok(imported_file)
+}
+
+/// Opens a source file for reading, reporting how much of it has been read as it is parsed.
+fn open_import_file(file_path: &Path) -> IoResult<BufReader<ImportProgressReader<File>>> {
+    let file = File::open(file_path)?;
+    let length = file.metadata()?.len() as usize;
+
+    Ok(BufReader::new(ImportProgressReader {
+        reader: file,
+        path: file_path.to_string_lossy().to_string(),
+        read: 0,
+        length,
+        reported_percent: 0,
+    }))
+}
+
+struct ImportProgressReader<R: Read> {
+    reader: R,
+    path: String,
+    read: usize,
+    length: usize,
+    reported_percent: usize,
+}
+
+impl<R: Read> Read for ImportProgressReader<R> {
+    fn read(&mut self, buffer: &mut [u8]) -> IoResult<usize> {
+        let count = self.reader.read(buffer)?;
+        self.read += count;
+
+        // Progress is only reported once per percent so large files do not flood the window with events.
+        let percent = self.read * 100 / self.length.max(1);
+        if percent > self.reported_percent {
+            self.reported_percent = percent;
+            import_progress(&self.path, self.read, self.length);
+        }
+
+        Ok(count)
+    }
 }
@@ -0,0 +1,751 @@
+use std::path::{Path, PathBuf};
+
+use thiserror::Error as ThisError;
+
+use crate::{
+    input::{
+        ImputedAnimation, ImputedAnimationModifiers, ImputedBodyPart, ImputedCompilationData, ImputedEngineLimits, ImputedMaterialNormalization,
+        ImputedMeshLimits, ImputedModel, ImputedModelFlags, ImputedPolygonLimits, ImputedRotationEncoding, ImputedSequence, ImputedSequencePhase,
+        ImputedTextureCoordinates, ImputedWeldTolerances,
+    },
+    read::{FileReadError, FileReader},
+    utilities::{
+        logging::{log, LogLevel},
+        mathematics::{Angles, Quaternion, Vector3},
+    },
+    write::layout::{
+        ANIMATION_DESCRIPTION_SIZE, BODY_PART_SIZE, BONE_SIZE, MATERIAL_SIZE, MESH_BODY_PART_HEADER_SIZE, MESH_MESH_HEADER_SIZE, MESH_MODEL_HEADER_SIZE,
+        MESH_SIZE, MESH_STRIP_GROUP_HEADER_SIZE, MESH_STRIP_HEADER_SIZE, MESH_VERTEX_SIZE, MODEL_FILE_IDENTIFIER, MODEL_SIZE, SEQUENCE_DESCRIPTION_SIZE,
+        VERTEX_FILE_IDENTIFIER, VERTEX_FIXUP_SIZE, VERTEX_SIZE,
+    },
+};
+
+use super::{ImportAnimation, ImportBone, ImportChannel, ImportFileData, ImportKeyFrame, ImportLink, ImportPart, ImportVertex};
+
+#[derive(Debug, ThisError)]
+pub enum ParseMDLError {
+    #[error("Failed To Read File: {0}")]
+    FailedFileRead(#[from] FileReadError),
+    #[error("Model Version {0} Is Not Supported")]
+    UnsupportedVersion(i32),
+    #[error("Model Has No Vertex File")]
+    MissingVertexFile,
+    #[error("Model Has No Mesh File")]
+    MissingMeshFile,
+    #[error("Animation {0} Has An Invalid Value Encoding")]
+    InvalidAnimationValue(String),
+    #[error("Animation {0} Has More Frames Than Its Data Can Hold")]
+    AnimationFrameCountOutOfRange(String),
+    #[error("Model Vertex Is Out Of Bounds")]
+    VertexOutOfBounds,
+}
+
+const SUPPORTED_VERSIONS: [i32; 2] = [48, 49];
+
+/// A run of animation values covers at most 255 frames and takes at least 4 bytes, which bounds the frames the data after a section start can hold.
+const ANIMATION_VALUE_RUN_SIZE: usize = 4;
+const ANIMATION_VALUE_RUN_FRAMES: usize = u8::MAX as usize;
+
+const STRIP_IS_TRIANGLE_STRIP: u8 = 0x02;
+
+const MODEL_FORCE_OPAQUE: i32 = 0x00000004;
+const MODEL_TRANSLUCENT_TWO_PASS: i32 = 0x00000008;
+const MODEL_STATIC_PROP: i32 = 0x00000010;
+const MODEL_AMBIENT_BOOST: i32 = 0x00010000;
+const MODEL_DO_NOT_CAST_SHADOWS: i32 = 0x00020000;
+const MODEL_CAST_TEXTURE_SHADOWS: i32 = 0x00040000;
+
+const BONE_USED_BY_BONE_MERGE: i32 = 0x00040000;
+
+const ANIMATION_RAW_POSITION: u8 = 0x01;
+const ANIMATION_RAW_ROTATION: u8 = 0x02;
+const ANIMATION_ANIMATED_POSITION: u8 = 0x04;
+const ANIMATION_ANIMATED_ROTATION: u8 = 0x08;
+const ANIMATION_DELTA: u8 = 0x10;
+const ANIMATION_RAW_ROTATION_64: u8 = 0x20;
+
+/// The mesh file extensions in the order they are preferred.
+const MESH_FILE_EXTENSIONS: [&str; 3] = ["dx90.vtx", "vtx", "dx80.vtx"];
+
+struct ModelBone {
+    name: String,
+    parent: i32,
+    position: Vector3,
+    quaternion: Quaternion,
+    rotation: Angles,
+    position_scale: Vector3,
+    rotation_scale: Vector3,
+    flags: i32,
+}
+
+fn read_model_file(file_path: &Path) -> Result<FileReader, ParseMDLError> {
+    let reader = FileReader::from_file(file_path)?;
+
+    if reader.read_integer(0)? != MODEL_FILE_IDENTIFIER {
+        return Err(ParseMDLError::FailedFileRead(FileReadError::WrongIdentifier));
+    }
+
+    let version = reader.read_integer(4)?;
+    if !SUPPORTED_VERSIONS.contains(&version) {
+        return Err(ParseMDLError::UnsupportedVersion(version));
+    }
+
+    Ok(reader)
+}
+
+fn find_mesh_file(file_path: &Path) -> Result<PathBuf, ParseMDLError> {
+    for extension in MESH_FILE_EXTENSIONS {
+        let mesh_path = file_path.with_extension(extension);
+        if mesh_path.exists() {
+            return Ok(mesh_path);
+        }
+    }
+
+    Err(ParseMDLError::MissingMeshFile)
+}
+
+fn read_bones(reader: &FileReader) -> Result<Vec<ModelBone>, ParseMDLError> {
+    let bone_offset = reader.read_offset(160, 0)?;
+    let mut bones = Vec::new();
+
+    for bone_index in 0..reader.read_count(156)? {
+        let bone_base = bone_offset + bone_index * BONE_SIZE;
+        bones.push(ModelBone {
+            name: reader.read_string(bone_base, bone_base)?,
+            parent: reader.read_integer(bone_base + 4)?,
+            position: reader.read_vector3(bone_base + 32)?,
+            quaternion: reader.read_quaternion(bone_base + 44)?,
+            rotation: {
+                let rotation = reader.read_vector3(bone_base + 60)?;
+                Angles::new(rotation.x, rotation.y, rotation.z)
+            },
+            position_scale: reader.read_vector3(bone_base + 72)?,
+            rotation_scale: reader.read_vector3(bone_base + 84)?,
+            flags: reader.read_integer(bone_base + 160)?,
+        });
+    }
+
+    Ok(bones)
+}
+
+/// Loads a compiled model with its vertex and mesh files as a source file.
+pub fn load_mdl(file_path: &Path) -> Result<ImportFileData, ParseMDLError> {
+    let mdl_reader = read_model_file(file_path)?;
+
+    let vertex_path = file_path.with_extension("vvd");
+    if !vertex_path.exists() {
+        return Err(ParseMDLError::MissingVertexFile);
+    }
+    let vvd_reader = FileReader::from_file(&vertex_path)?;
+    if vvd_reader.read_integer(0)? != VERTEX_FILE_IDENTIFIER {
+        return Err(ParseMDLError::FailedFileRead(FileReadError::WrongIdentifier));
+    }
+
+    let vtx_reader = FileReader::from_file(&find_mesh_file(file_path)?)?;
+
+    let bones = read_bones(&mdl_reader)?;
+
+    let mut file_data = ImportFileData::default();
+
+    for bone in &bones {
+        file_data.skeleton.push(ImportBone {
+            name: bone.name.clone(),
+            parent: if bone.parent < 0 { None } else { Some(bone.parent as usize) },
+            position: bone.position,
+            orientation: bone.quaternion,
+        });
+    }
+
+    file_data.parts = read_parts(&mdl_reader, &vvd_reader, &vtx_reader)?;
+    file_data.animations = read_animations(&mdl_reader, &bones)?;
+
+    Ok(file_data)
+}
+
+fn read_material_paths(reader: &FileReader) -> Result<Vec<String>, ParseMDLError> {
+    let material_path_offset = reader.read_offset(216, 0)?;
+    let mut material_paths = Vec::new();
+
+    for material_path_index in 0..reader.read_count(212)? {
+        material_paths.push(reader.read_string(material_path_offset + material_path_index * 4, 0)?);
+    }
+
+    // A single root path is what gets written when no paths are set, so it is left out to keep the project the same.
+    if material_paths.len() == 1 && material_paths[0].is_empty() {
+        material_paths.clear();
+    }
+
+    Ok(material_paths)
+}
+
+fn read_model_flags(flags: i32) -> ImputedModelFlags {
+    ImputedModelFlags {
+        static_prop: flags & MODEL_STATIC_PROP != 0,
+        force_opaque: flags & MODEL_FORCE_OPAQUE != 0,
+        translucent_two_pass: flags & MODEL_TRANSLUCENT_TWO_PASS != 0,
+        ambient_boost: flags & MODEL_AMBIENT_BOOST != 0,
+        do_not_cast_shadows: flags & MODEL_DO_NOT_CAST_SHADOWS != 0,
+        cast_texture_shadows: flags & MODEL_CAST_TEXTURE_SHADOWS != 0,
+    }
+}
+
+fn read_materials(reader: &FileReader) -> Result<Vec<String>, ParseMDLError> {
+    let material_offset = reader.read_offset(208, 0)?;
+    let mut materials = Vec::new();
+
+    for material_index in 0..reader.read_count(204)? {
+        let material_base = material_offset + material_index * MATERIAL_SIZE;
+        materials.push(reader.read_string(material_base, material_base)?);
+    }
+
+    // Meshes reference skin slots, the first skin family maps those slots to materials.
+    let skin_offset = reader.read_offset(228, 0)?;
+    let mut skin_materials = Vec::new();
+    for skin_reference in 0..reader.read_count(220)? {
+        let material_index = reader.read_short(skin_offset + skin_reference * 2)? as usize;
+        skin_materials.push(materials.get(material_index).cloned().unwrap_or_default());
+    }
+
+    Ok(skin_materials)
+}
+
+/// Reads the vertices of the first level of detail, applying the fixup table if the file has one.
+fn read_vertices(reader: &FileReader) -> Result<Vec<ImportVertex>, ParseMDLError> {
+    let vertex_offset = reader.read_offset(56, 0)?;
+    let fixup_offset = reader.read_offset(52, 0)?;
+    let fixup_count = reader.read_count(48)?;
+
+    let mut vertex_ranges = Vec::new();
+    if fixup_count == 0 {
+        vertex_ranges.push((0, reader.read_count(16)?));
+    }
+
+    for fixup_index in 0..fixup_count {
+        let fixup_base = fixup_offset + fixup_index * VERTEX_FIXUP_SIZE;
+        if reader.read_integer(fixup_base)? >= 0 {
+            vertex_ranges.push((reader.read_count(fixup_base + 4)?, reader.read_count(fixup_base + 8)?));
+        }
+    }
+
+    let mut vertices = Vec::new();
+    for (start, count) in vertex_ranges {
+        for vertex_index in start..start + count {
+            let vertex_base = vertex_offset + vertex_index * VERTEX_SIZE;
+            let bone_count = reader.read_unsigned_byte(vertex_base + 15)? as usize;
+
+            let mut links = Vec::with_capacity(bone_count.min(3));
+            for link_index in 0..bone_count.min(3) {
+                links.push(ImportLink {
+                    bone: reader.read_unsigned_byte(vertex_base + 12 + link_index)? as usize,
+                    weight: reader.read_float(vertex_base + link_index * 4)? as f64,
+                });
+            }
+
+            vertices.push(ImportVertex {
+                position: reader.read_vector3(vertex_base + 16)?,
+                normal: reader.read_vector3(vertex_base + 28)?,
+                texture_coordinate: reader.read_vector2(vertex_base + 40)?,
+                color: None,
+                links,
+            });
+        }
+    }
+
+    Ok(vertices)
+}
+
+fn read_parts(mdl_reader: &FileReader, vvd_reader: &FileReader, vtx_reader: &FileReader) -> Result<Vec<ImportPart>, ParseMDLError> {
+    let materials = read_materials(mdl_reader)?;
+    let mut vertices = read_vertices(vvd_reader)?.into_iter().map(Some).collect::<Vec<_>>();
+    let mut parts = Vec::new();
+
+    let mdl_body_part_offset = mdl_reader.read_offset(236, 0)?;
+    let vtx_body_part_offset = vtx_reader.read_offset(32, 0)?;
+    for body_part_index in 0..mdl_reader.read_count(232)? {
+        let mdl_body_part_base = mdl_body_part_offset + body_part_index * BODY_PART_SIZE;
+        let vtx_body_part_base = vtx_body_part_offset + body_part_index * MESH_BODY_PART_HEADER_SIZE;
+
+        let mdl_model_offset = mdl_reader.read_offset(mdl_body_part_base + 12, mdl_body_part_base)?;
+        let vtx_model_offset = vtx_reader.read_offset(vtx_body_part_base + 4, vtx_body_part_base)?;
+        for model_index in 0..mdl_reader.read_count(mdl_body_part_base + 4)? {
+            let mdl_model_base = mdl_model_offset + model_index * MODEL_SIZE;
+            let vtx_model_base = vtx_model_offset + model_index * MESH_MODEL_HEADER_SIZE;
+
+            let model_vertex_count = mdl_reader.read_count(mdl_model_base + 80)?;
+            if model_vertex_count == 0 {
+                continue;
+            }
+
+            let model_vertex_start = mdl_reader.read_count(mdl_model_base + 84)? / VERTEX_SIZE;
+            let mut part = ImportPart {
+                name: mdl_reader.read_char_array(mdl_model_base, 64)?,
+                ..Default::default()
+            };
+
+            for vertex in vertices
+                .get_mut(model_vertex_start..model_vertex_start + model_vertex_count)
+                .ok_or(ParseMDLError::VertexOutOfBounds)?
+            {
+                part.vertices.push(vertex.take().ok_or(ParseMDLError::VertexOutOfBounds)?);
+            }
+
+            // Only the first level of detail is read, the others are generated from it.
+            let vtx_model_lod_base = vtx_reader.read_offset(vtx_model_base + 4, vtx_model_base)?;
+            let mdl_mesh_offset = mdl_reader.read_offset(mdl_model_base + 76, mdl_model_base)?;
+            let vtx_mesh_offset = vtx_reader.read_offset(vtx_model_lod_base + 4, vtx_model_lod_base)?;
+            for mesh_index in 0..mdl_reader.read_count(mdl_model_base + 72)? {
+                let mdl_mesh_base = mdl_mesh_offset + mesh_index * MESH_SIZE;
+                let vtx_mesh_base = vtx_mesh_offset + mesh_index * MESH_MESH_HEADER_SIZE;
+
+                let material = materials.get(mdl_reader.read_integer(mdl_mesh_base)? as usize).cloned().unwrap_or_default();
+                let mesh_vertex_start = mdl_reader.read_count(mdl_mesh_base + 12)?;
+                let polygons = part.polygons.entry(material).or_default();
+
+                let strip_group_offset = vtx_reader.read_offset(vtx_mesh_base + 4, vtx_mesh_base)?;
+                for strip_group_index in 0..vtx_reader.read_count(vtx_mesh_base)? {
+                    let strip_group_base = strip_group_offset + strip_group_index * MESH_STRIP_GROUP_HEADER_SIZE;
+                    let vertex_offset = vtx_reader.read_offset(strip_group_base + 4, strip_group_base)?;
+                    let index_offset = vtx_reader.read_offset(strip_group_base + 12, strip_group_base)?;
+                    let strip_offset = vtx_reader.read_offset(strip_group_base + 20, strip_group_base)?;
+
+                    let read_vertex = |index: usize| -> Result<usize, ParseMDLError> {
+                        let strip_group_vertex = vtx_reader.read_unsigned_short(index_offset + index * 2)? as usize;
+                        let mesh_vertex = vtx_reader.read_unsigned_short(vertex_offset + strip_group_vertex * MESH_VERTEX_SIZE + 4)? as usize;
+                        let part_vertex = mesh_vertex_start + mesh_vertex;
+
+                        if part_vertex >= model_vertex_count {
+                            return Err(ParseMDLError::VertexOutOfBounds);
+                        }
+
+                        Ok(part_vertex)
+                    };
+
+                    for strip_index in 0..vtx_reader.read_count(strip_group_base + 16)? {
+                        let strip_base = strip_offset + strip_index * MESH_STRIP_HEADER_SIZE;
+                        let index_count = vtx_reader.read_count(strip_base)?;
+                        let index_start = vtx_reader.read_count(strip_base + 4)?;
+                        let is_triangle_strip = vtx_reader.read_unsigned_byte(strip_base + 18)? & STRIP_IS_TRIANGLE_STRIP != 0;
+
+                        // The compiled triangles are wound the other way from source files.
+                        if is_triangle_strip {
+                            for triangle_index in 0..index_count.saturating_sub(2) {
+                                let mut triangle = [
+                                    read_vertex(index_start + triangle_index)?,
+                                    read_vertex(index_start + triangle_index + 1)?,
+                                    read_vertex(index_start + triangle_index + 2)?,
+                                ];
+
+                                if triangle[0] == triangle[1] || triangle[1] == triangle[2] || triangle[2] == triangle[0] {
+                                    continue;
+                                }
+
+                                if triangle_index % 2 == 1 {
+                                    triangle.swap(0, 1);
+                                }
+
+                                polygons.push(vec![triangle[2], triangle[1], triangle[0]]);
+                            }
+                            continue;
+                        }
+
+                        for triangle_index in (0..index_count - index_count % 3).step_by(3) {
+                            polygons.push(vec![
+                                read_vertex(index_start + triangle_index + 2)?,
+                                read_vertex(index_start + triangle_index + 1)?,
+                                read_vertex(index_start + triangle_index)?,
+                            ]);
+                        }
+                    }
+                }
+            }
+
+            parts.push(part);
+        }
+    }
+
+    Ok(parts)
+}
+
+/// Decodes a run length encoded animation value for a frame.
+fn read_animation_value(reader: &FileReader, value_base: usize, frame: usize, name: &str) -> Result<f64, ParseMDLError> {
+    let mut position = value_base;
+    let mut frame = frame;
+
+    loop {
+        let valid = reader.read_unsigned_byte(position)? as usize;
+        let total = reader.read_unsigned_byte(position + 1)? as usize;
+
+        if total == 0 || valid == 0 {
+            return Err(ParseMDLError::InvalidAnimationValue(name.to_string()));
+        }
+
+        if frame < total {
+            return Ok(reader.read_short(position + 2 + frame.min(valid - 1) * 2)? as f64);
+        }
+
+        frame -= total;
+        position += 2 + valid * 2;
+    }
+}
+
+fn read_animated_axes(reader: &FileReader, value_pointer: usize, frame: usize, name: &str) -> Result<Vector3, ParseMDLError> {
+    let mut axes = Vector3::default();
+
+    for axis in 0..3 {
+        let offset = reader.read_short(value_pointer + axis * 2)?;
+        if offset > 0 {
+            axes[axis] = read_animation_value(reader, value_pointer + offset as usize, frame, name)?;
+        }
+    }
+
+    Ok(axes)
+}
+
+fn read_animations(reader: &FileReader, bones: &[ModelBone]) -> Result<Vec<ImportAnimation>, ParseMDLError> {
+    let mut animations = Vec::new();
+
+    let animation_description_offset = reader.read_offset(184, 0)?;
+    for animation_description_index in 0..reader.read_count(180)? {
+        let animation_description_base = animation_description_offset + animation_description_index * ANIMATION_DESCRIPTION_SIZE;
+        let name = reader.read_string(animation_description_base + 4, animation_description_base)?;
+        let frame_count = reader.read_count(animation_description_base + 16)?.max(1);
+        let frames_per_section = reader.read_count(animation_description_base + 84)?;
+
+        let mut animation = ImportAnimation {
+            name: name.clone(),
+            frame_count,
+            channels: (0..bones.len()).map(|bone| ImportChannel { bone, ..Default::default() }).collect(),
+        };
+
+        if reader.read_integer(animation_description_base + 52)? != 0 {
+            log(
+                format!("Animation {} is stored in an animation block and was imported as the bind pose.", name),
+                LogLevel::Warn,
+            );
+            for (channel, bone) in animation.channels.iter_mut().zip(bones) {
+                channel.position.push(ImportKeyFrame {
+                    frame: 0,
+                    value: bone.position,
+                });
+                channel.rotation.push(ImportKeyFrame {
+                    frame: 0,
+                    value: bone.quaternion,
+                });
+            }
+            animations.push(animation);
+            continue;
+        }
+
+        // Each section starts with the first frame of the next, so the frame is looked up in the section it starts.
+        let section_starts = match frame_count.checked_div(frames_per_section) {
+            Some(full_sections) => {
+                let section_offset = reader.read_offset(animation_description_base + 80, animation_description_base)?;
+                let section_count = full_sections + 2;
+                if section_count > reader.capacity_for(section_offset, 8) {
+                    return Err(ParseMDLError::AnimationFrameCountOutOfRange(name));
+                }
+
+                let mut section_starts = Vec::with_capacity(section_count);
+                for section_index in 0..section_count {
+                    section_starts.push(reader.read_offset(section_offset + section_index * 8 + 4, animation_description_base)?);
+                }
+                section_starts
+            }
+            None => vec![reader.read_offset(animation_description_base + 56, animation_description_base)?],
+        };
+
+        for (section, section_start) in section_starts.iter().enumerate() {
+            let section_frame_count = match frames_per_section {
+                0 => frame_count,
+                _ => (frame_count - 1).saturating_sub(section * frames_per_section).min(frames_per_section) + 1,
+            };
+            if section_frame_count > reader.capacity_for(*section_start, ANIMATION_VALUE_RUN_SIZE) * ANIMATION_VALUE_RUN_FRAMES {
+                return Err(ParseMDLError::AnimationFrameCountOutOfRange(name));
+            }
+        }
+
+        for frame in 0..frame_count {
+            let (section_start, section_frame) = match frame.checked_div(frames_per_section) {
+                Some(section) => {
+                    let section = section.min(section_starts.len() - 1);
+                    (section_starts[section], frame - section * frames_per_section)
+                }
+                None => (section_starts[0], frame),
+            };
+
+            let mut positions = bones.iter().map(|bone| bone.position).collect::<Vec<_>>();
+            let mut rotations = bones.iter().map(|bone| bone.quaternion).collect::<Vec<_>>();
+
+            let mut animation_base = section_start;
+            loop {
+                let bone_index = reader.read_unsigned_byte(animation_base)? as usize;
+                let flags = reader.read_unsigned_byte(animation_base + 1)?;
+                let next_offset = reader.read_short(animation_base + 2)?;
+
+                if let Some(bone) = bones.get(bone_index) {
+                    let is_delta = flags & ANIMATION_DELTA != 0;
+                    let mut data_base = animation_base + 4;
+
+                    if is_delta {
+                        positions[bone_index] = Vector3::default();
+                        rotations[bone_index] = Quaternion::default();
+                    }
+
+                    if flags & ANIMATION_RAW_ROTATION_64 != 0 {
+                        rotations[bone_index] = reader.read_quaternion64(data_base)?;
+                        data_base += 8;
+                    } else if flags & ANIMATION_RAW_ROTATION != 0 {
+                        rotations[bone_index] = reader.read_quaternion48(data_base)?;
+                        data_base += 6;
+                    } else if flags & ANIMATION_ANIMATED_ROTATION != 0 {
+                        let values = read_animated_axes(reader, data_base, section_frame, &name)? * bone.rotation_scale;
+                        let mut angles = Angles::new(values.x, values.y, values.z);
+                        if !is_delta {
+                            angles = angles + bone.rotation;
+                        }
+                        rotations[bone_index] = angles.to_quaternion();
+                        data_base += 6;
+                    }
+
+                    if flags & ANIMATION_RAW_POSITION != 0 {
+                        positions[bone_index] = reader.read_vector48(data_base)?;
+                    } else if flags & ANIMATION_ANIMATED_POSITION != 0 {
+                        let mut position = read_animated_axes(reader, data_base, section_frame, &name)? * bone.position_scale;
+                        if !is_delta {
+                            position = position + bone.position;
+                        }
+                        positions[bone_index] = position;
+                    }
+                }
+
+                if next_offset <= 0 {
+                    break;
+                }
+                animation_base += next_offset as usize;
+            }
+
+            for (channel, (position, rotation)) in animation.channels.iter_mut().zip(positions.into_iter().zip(rotations)) {
+                channel.position.push(ImportKeyFrame { frame, value: position });
+                channel.rotation.push(ImportKeyFrame { frame, value: rotation });
+            }
+        }
+
+        animations.push(animation);
+    }
+
+    Ok(animations)
+}
+
+/// Creates a project that compiles the model again from the compiled files, using the model as the source file.
+pub fn decompile_mdl(file_path: &Path) -> Result<ImputedCompilationData, ParseMDLError> {
+    let reader = read_model_file(file_path)?;
+    let file_source = file_path.to_string_lossy().to_string();
+
+    // The second header holds the full internal name, the name in the header is cut off and not always the model path.
+    let (internal_name, max_eye_deflection) = match reader.read_integer(400)? {
+        0 => (reader.read_char_array(12, 64)?, 0.0),
+        _ => {
+            let second_header_offset = reader.read_offset(400, 0)?;
+            (
+                reader.read_string(second_header_offset + 20, second_header_offset)?,
+                reader.read_float(second_header_offset + 12)?,
+            )
+        }
+    };
+    let mut project = ImputedCompilationData {
+        model_name: internal_name.trim_end_matches(".mdl").to_string(),
+        export_path: file_path.parent().map(|path| path.to_string_lossy().to_string()).unwrap_or_default(),
+        target_version: reader.read_integer(4)?,
+        surface_property: reader.read_string(308, 0)?,
+        animation_block_size: 0,
+        write_report: false,
+        write_dx80_mesh: false,
+        write_software_mesh: false,
+        prefix_animation_names: false,
+        rotation_encoding: ImputedRotationEncoding::Quaternion64,
+        animation_section_frames: 30,
+        origin: [0.0; 3],
+        world_model: None,
+        gibs: None,
+        material_paths: read_material_paths(&reader)?,
+        material_renames: Vec::new(),
+        // Compiled models already store the cleaned up material names.
+        material_normalization: ImputedMaterialNormalization {
+            strip_directories: false,
+            merge_similar: true,
+        },
+        bone_attachments: Vec::new(),
+        procedural_bones: Vec::new(),
+        mouths: Vec::new(),
+        bone_flex_drivers: Vec::new(),
+        inverse_kinematic_chains: Vec::new(),
+        jiggle_chains: Vec::new(),
+        weight_lists: Vec::new(),
+        bone_conflicts: Vec::new(),
+        bone_merge_bones: read_bones(&reader)?
+            .into_iter()
+            .filter(|bone| bone.flags & BONE_USED_BY_BONE_MERGE != 0)
+            .map(|bone| bone.name)
+            .collect(),
+        mesh_limits: ImputedMeshLimits {
+            hardware_bones_per_strip: 53,
+            weights_per_vertex: 3,
+            vertex_cache_size: 16,
+            optimize_overdraw: true,
+            overdraw_threshold: 1.05,
+            mikktspace_tangents: false,
+        },
+        // The model compiled already, so its own bone and material counts fit the game it was made for.
+        engine_limits: ImputedEngineLimits {
+            bones: reader.read_count(156)?.max(128),
+            materials: reader.read_count(204)?.max(32),
+        },
+        weld_tolerances: ImputedWeldTolerances {
+            position: 0.00034526698,
+            normal_angle: 0.01,
+            texture_coordinate: 0.00001,
+        },
+        texture_coordinates: ImputedTextureCoordinates {
+            flip_v: false,
+            normalize: false,
+            reject_udim: false,
+        },
+        polygon_limits: ImputedPolygonLimits {
+            maximum_vertices: 64,
+            strict: false,
+        },
+        model_flags: read_model_flags(reader.read_integer(152)?),
+        // The engine uses a deflection of 30 degrees when none is set, a cosine outside of 0 to 1 is not a deflection that can compile again.
+        max_eye_deflection: if max_eye_deflection > 0.0 && max_eye_deflection < 1.0 {
+            (max_eye_deflection as f64).acos().to_degrees()
+        } else {
+            30.0
+        },
+        illumination_attachment: None,
+        normal_smoothing_angle: None,
+        seam_normal_tolerance: None,
+        bounding_box: None,
+        clipping_box: None,
+        body_parts: Vec::new(),
+        animations: Vec::new(),
+        sequences: Vec::new(),
+        transition_nodes: Vec::new(),
+        aim_matrices: Vec::new(),
+        physics: None,
+    };
+
+    if let Ok(mesh_path) = find_mesh_file(file_path) {
+        let mesh_reader = FileReader::from_file(&mesh_path)?;
+        project.mesh_limits = ImputedMeshLimits {
+            hardware_bones_per_strip: mesh_reader.read_unsigned_short(8)? as usize,
+            weights_per_vertex: mesh_reader.read_count(12)?,
+            vertex_cache_size: mesh_reader.read_count(4)?,
+            ..project.mesh_limits
+        };
+    }
+
+    let body_part_offset = reader.read_offset(236, 0)?;
+    for body_part_index in 0..reader.read_count(232)? {
+        let body_part_base = body_part_offset + body_part_index * BODY_PART_SIZE;
+        let mut body_part = ImputedBodyPart {
+            name: reader.read_string(body_part_base, body_part_base)?,
+            default_model: 0,
+            preview_model: None,
+            models: Vec::new(),
+        };
+
+        let model_offset = reader.read_offset(body_part_base + 12, body_part_base)?;
+        for model_index in 0..reader.read_count(body_part_base + 4)? {
+            let model_base = model_offset + model_index * MODEL_SIZE;
+            let name = reader.read_char_array(model_base, 64)?;
+            let is_blank = reader.read_count(model_base + 80)? == 0;
+
+            body_part.models.push(ImputedModel {
+                part_names: if is_blank { Vec::new() } else { vec![name.clone()] },
+                name,
+                is_blank,
+                file_source: if is_blank { String::new() } else { file_source.clone() },
+                materials: Vec::new(),
+                mirror: None,
+                flex_file_source: String::new(),
+            });
+        }
+
+        project.body_parts.push(body_part);
+    }
+
+    let mut animation_names = Vec::new();
+    let animation_description_offset = reader.read_offset(184, 0)?;
+    for animation_description_index in 0..reader.read_count(180)? {
+        let animation_description_base = animation_description_offset + animation_description_index * ANIMATION_DESCRIPTION_SIZE;
+        let name = reader.read_string(animation_description_base + 4, animation_description_base)?;
+        animation_names.push(name.clone());
+
+        project.animations.push(ImputedAnimation {
+            name: name.clone(),
+            file_source: file_source.clone(),
+            animation_name: name,
+            frame_range: None,
+            fps: reader.read_float(animation_description_base + 8)? as f64,
+            retarget: false,
+            modifiers: ImputedAnimationModifiers {
+                reverse: false,
+                speed: 1.0,
+                hold_start: 0,
+                hold_end: 0,
+            },
+            subtract: None,
+            motion: None,
+            inverse_kinematic_rules: Vec::new(),
+        });
+    }
+
+    let sequence_description_offset = reader.read_offset(192, 0)?;
+    for sequence_description_index in 0..reader.read_count(188)? {
+        let sequence_description_base = sequence_description_offset + sequence_description_index * SEQUENCE_DESCRIPTION_SIZE;
+        let animation_offset = reader.read_offset(sequence_description_base + 60, sequence_description_base)?;
+        let columns = reader.read_count(sequence_description_base + 68)?.max(1);
+        let rows = reader.read_count(sequence_description_base + 72)?.max(1);
+
+        let mut animations = Vec::with_capacity(rows.min(reader.capacity_for(animation_offset, columns * 2)));
+        for row in 0..rows {
+            let mut animation_row = Vec::with_capacity(columns.min(reader.capacity_for(animation_offset, 2)));
+            for column in 0..columns {
+                let animation_index = reader.read_short(animation_offset + (row * columns + column) * 2)? as usize;
+                animation_row.push(animation_names.get(animation_index).cloned().unwrap_or_default());
+            }
+            animations.push(animation_row);
+        }
+
+        let activity_modifier_offset = reader.read_offset(sequence_description_base + 184, sequence_description_base)?;
+        let mut activity_modifiers = Vec::new();
+        for activity_modifier_index in 0..reader.read_count(sequence_description_base + 188)? {
+            let activity_modifier_base = activity_modifier_offset + activity_modifier_index * 4;
+            activity_modifiers.push(reader.read_string(activity_modifier_base, activity_modifier_base)?);
+        }
+
+        let entry_phase = reader.read_float(sequence_description_base + 124)? as f64;
+        let exit_phase = reader.read_float(sequence_description_base + 128)? as f64;
+
+        project.sequences.push(ImputedSequence {
+            name: reader.read_string(sequence_description_base + 4, sequence_description_base)?,
+            animations,
+            weight_list: None,
+            auto_layers: Vec::new(),
+            bounding_box: None,
+            transition: None,
+            inverse_kinematic_locks: Vec::new(),
+            fade_in_time: reader.read_float(sequence_description_base + 104)? as f64,
+            fade_out_time: reader.read_float(sequence_description_base + 108)? as f64,
+            phase: (entry_phase != 0.0 || exit_phase != 0.0).then_some(ImputedSequencePhase {
+                entry: entry_phase,
+                exit: exit_phase,
+            }),
+            column_blend: None,
+            row_blend: None,
+            activity_modifiers,
+        });
+    }
+
+    Ok(project)
+}
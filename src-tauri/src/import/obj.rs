@@ -1,6 +1,5 @@
 use std::{
-    fs::File,
-    io::{BufRead, BufReader, Error},
+    io::{BufRead, Error},
     path::Path,
 };
 
@@ -9,10 +8,10 @@ use thiserror::Error as ThisError;
 
 use crate::utilities::{
     logging::{log, LogLevel},
-    mathematics::{Vector2, Vector3},
+    mathematics::{Vector2, Vector3, Vector4},
 };
 
-use super::{ImportAnimation, ImportBone, ImportChannel, ImportFileData, ImportKeyFrame, ImportLink, ImportPart, ImportVertex};
+use super::{open_import_file, ImportAnimation, ImportBone, ImportChannel, ImportFileData, ImportKeyFrame, ImportLink, ImportPart, ImportVertex};
 
 #[derive(Debug, ThisError)]
 pub enum ParseOBJError {
@@ -31,8 +30,7 @@ pub enum ParseOBJError {
 }
 
 pub fn load_obj(file_path: &Path) -> Result<ImportFileData, ParseOBJError> {
-    let file = File::open(file_path)?;
-    let file_buffer = BufReader::new(file);
+    let file_buffer = open_import_file(file_path)?;
     let lines = file_buffer.lines().map_while(Result::ok);
 
     let mut file_data = ImportFileData {
@@ -53,6 +51,7 @@ pub fn load_obj(file_path: &Path) -> Result<ImportFileData, ParseOBJError> {
     };
 
     let mut vertex_data = Vec::new();
+    let mut vertex_color_data = Vec::new();
     let mut texture_coordinate_data = Vec::new();
     let mut normal_data = Vec::new();
     let mut object_data = ImportPart::default();
@@ -90,6 +89,22 @@ pub fn load_obj(file_path: &Path) -> Result<ImportFileData, ParseOBJError> {
                 };
 
                 vertex_data.push(Vector3::new(x_position, y_position, z_position));
+
+                // Some exporters follow the position with a color, a single extra value is the weight of the position instead.
+                let color_arguments = line_arguments.collect::<Vec<_>>();
+                vertex_color_data.push(match color_arguments.len() {
+                    3 => {
+                        let mut color = [1.0; 4];
+                        for (channel, argument) in color_arguments.iter().enumerate() {
+                            color[channel] = match argument.parse::<f64>() {
+                                Ok(value) => value,
+                                Err(_) => return Err(ParseOBJError::FailedFloatParse(current_line_count)),
+                            };
+                        }
+                        Some(Vector4::new(color[0], color[1], color[2], color[3]))
+                    }
+                    _ => None,
+                });
             }
             Some("vt") => {
                 let u_texture_coordinate = match line_arguments.next() {
@@ -188,6 +203,7 @@ pub fn load_obj(file_path: &Path) -> Result<ImportFileData, ParseOBJError> {
                         position: vertex_data[vertex_index - 1],
                         normal: normal_data[normal_index - 1],
                         texture_coordinate: texture_coordinate_data[texture_coordinate_index - 1],
+                        color: vertex_color_data[vertex_index - 1],
                         links: vec![ImportLink { bone: 0, weight: 1.0 }],
                     });
                 }
@@ -0,0 +1,196 @@
+use indexmap::IndexMap;
+use serde::Deserialize;
+use thiserror::Error as ThisError;
+
+use crate::utilities::mathematics::{Quaternion, Vector2, Vector3};
+
+use super::{ImportAnimation, ImportBone, ImportChannel, ImportFileData, ImportKeyFrame, ImportLink, ImportPart, ImportVertex};
+
+#[derive(Debug, ThisError)]
+pub enum ParsePushedError {
+    #[error("Bone {0} Has A Parent That Does Not Exist Or Comes After It")]
+    BoneParentNotFound(String),
+    #[error("Animation {0} Has A Channel For A Bone That Does Not Exist")]
+    ChannelBoneNotFound(String),
+    #[error("Animation {0} Has A Key Frame After Its Last Frame")]
+    KeyFrameAfterLastFrame(String),
+    #[error("Part {0} Has A Vertex Linked To A Bone That Does Not Exist")]
+    LinkBoneNotFound(String),
+    #[error("Part {0} Has A Polygon With A Vertex That Does Not Exist")]
+    PolygonVertexNotFound(String),
+    #[error("Part {0} Has A Polygon With Less Than 3 Vertices")]
+    PolygonTooSmall(String),
+}
+
+/// A source file sent by another program instead of being read from disk, in the same layout as an imported file.
+/// Bones come before their children, rotations are quaternions ordered x, y, z, w.
+#[derive(Debug, Deserialize)]
+pub struct PushedFileData {
+    #[serde(default)]
+    skeleton: Vec<PushedBone>,
+    #[serde(default)]
+    animations: Vec<PushedAnimation>,
+    #[serde(default)]
+    parts: Vec<PushedPart>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PushedBone {
+    name: String,
+    parent: Option<usize>,
+    position: [f64; 3],
+    orientation: [f64; 4],
+}
+
+#[derive(Debug, Deserialize)]
+struct PushedAnimation {
+    name: String,
+    frame_count: usize,
+    channels: Vec<PushedChannel>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PushedChannel {
+    bone: usize,
+    /// The frame and position of each key.
+    position: Vec<(usize, [f64; 3])>,
+    /// The frame and rotation of each key.
+    rotation: Vec<(usize, [f64; 4])>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PushedPart {
+    name: String,
+    vertices: Vec<PushedVertex>,
+    materials: Vec<PushedMaterial>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PushedVertex {
+    position: [f64; 3],
+    normal: [f64; 3],
+    texture_coordinate: [f64; 2],
+    /// The bone and weight of each link.
+    links: Vec<(usize, f64)>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PushedMaterial {
+    material: String,
+    polygons: Vec<Vec<usize>>,
+}
+
+/// Checks the indices of the pushed file, as the compile expects every index of an imported file to be valid.
+pub fn load_pushed_file(pushed_file: PushedFileData) -> Result<ImportFileData, ParsePushedError> {
+    let bone_count = pushed_file.skeleton.len();
+
+    let mut skeleton = Vec::with_capacity(bone_count);
+    for (bone_index, bone) in pushed_file.skeleton.into_iter().enumerate() {
+        if bone.parent.is_some_and(|parent| parent >= bone_index) {
+            return Err(ParsePushedError::BoneParentNotFound(bone.name));
+        }
+
+        skeleton.push(ImportBone {
+            name: bone.name,
+            parent: bone.parent,
+            position: create_vector(bone.position),
+            orientation: create_quaternion(bone.orientation),
+        });
+    }
+
+    let mut animations = Vec::with_capacity(pushed_file.animations.len());
+    for animation in pushed_file.animations {
+        let mut channels = Vec::with_capacity(animation.channels.len());
+        for channel in animation.channels {
+            if channel.bone >= bone_count {
+                return Err(ParsePushedError::ChannelBoneNotFound(animation.name));
+            }
+
+            let mut key_frames = channel
+                .position
+                .iter()
+                .map(|(frame, _)| frame)
+                .chain(channel.rotation.iter().map(|(frame, _)| frame));
+            if key_frames.any(|frame| *frame >= animation.frame_count) {
+                return Err(ParsePushedError::KeyFrameAfterLastFrame(animation.name));
+            }
+
+            channels.push(ImportChannel {
+                bone: channel.bone,
+                position: channel
+                    .position
+                    .into_iter()
+                    .map(|(frame, value)| ImportKeyFrame {
+                        frame,
+                        value: create_vector(value),
+                    })
+                    .collect(),
+                rotation: channel
+                    .rotation
+                    .into_iter()
+                    .map(|(frame, value)| ImportKeyFrame {
+                        frame,
+                        value: create_quaternion(value),
+                    })
+                    .collect(),
+            });
+        }
+
+        animations.push(ImportAnimation {
+            name: animation.name,
+            frame_count: animation.frame_count,
+            channels,
+        });
+    }
+
+    let mut parts = Vec::with_capacity(pushed_file.parts.len());
+    for part in pushed_file.parts {
+        let vertex_count = part.vertices.len();
+
+        if part.vertices.iter().flat_map(|vertex| &vertex.links).any(|(bone, _)| *bone >= bone_count) {
+            return Err(ParsePushedError::LinkBoneNotFound(part.name));
+        }
+
+        let mut polygons: IndexMap<String, Vec<Vec<usize>>> = IndexMap::new();
+        for material in part.materials {
+            for polygon in material.polygons {
+                if polygon.len() < 3 {
+                    return Err(ParsePushedError::PolygonTooSmall(part.name));
+                }
+
+                if polygon.iter().any(|vertex| *vertex >= vertex_count) {
+                    return Err(ParsePushedError::PolygonVertexNotFound(part.name));
+                }
+
+                polygons.entry(material.material.clone()).or_default().push(polygon);
+            }
+        }
+
+        parts.push(ImportPart {
+            name: part.name,
+            vertices: part
+                .vertices
+                .into_iter()
+                .map(|vertex| ImportVertex {
+                    position: create_vector(vertex.position),
+                    normal: create_vector(vertex.normal),
+                    texture_coordinate: Vector2::new(vertex.texture_coordinate[0], vertex.texture_coordinate[1]),
+                    color: None,
+                    links: vertex.links.into_iter().map(|(bone, weight)| ImportLink { bone, weight }).collect(),
+                })
+                .collect(),
+            polygons,
+            flexes: Vec::new(),
+        });
+    }
+
+    Ok(ImportFileData { skeleton, animations, parts })
+}
+
+fn create_vector(value: [f64; 3]) -> Vector3 {
+    Vector3::new(value[0], value[1], value[2])
+}
+
+fn create_quaternion(value: [f64; 4]) -> Quaternion {
+    Quaternion::new(value[0], value[1], value[2], value[3])
+}
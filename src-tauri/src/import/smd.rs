@@ -1,15 +1,17 @@
-use std::{
-    fs::File,
-    io::{BufRead, BufReader},
-    path::Path,
-};
+use std::{io::BufRead, path::Path};
 
 use indexmap::IndexMap;
 use thiserror::Error as ThisError;
 
-use crate::utilities::mathematics::{Angles, Vector2, Vector3};
+use crate::utilities::{
+    logging::{log, LogLevel},
+    mathematics::{Angles, Vector2, Vector3},
+};
 
-use super::{ImportAnimation, ImportBone, ImportChannel, ImportFileData, ImportFlex, ImportFlexVertex, ImportKeyFrame, ImportLink, ImportPart, ImportVertex};
+use super::{
+    open_import_file, ImportAnimation, ImportBone, ImportChannel, ImportFileData, ImportFlex, ImportFlexVertex, ImportKeyFrame, ImportLink, ImportPart,
+    ImportVertex,
+};
 
 #[derive(Debug, ThisError)]
 pub enum ParseSMDError {
@@ -33,13 +35,37 @@ pub enum ParseSMDError {
     NoBindFrame,
     #[error("Not All Bones Specified")]
     MissingBoneBind,
+    #[error("Vertex {0} Is Not In The Base Frame")]
+    MissingBaseVertex(usize),
+    #[error("Base Frame Vertices Are Not Sequential")]
+    InvalidBaseVertexIndex,
+}
+
+/// The commands that start a block, tolerant parsing ends a block that is missing its end when the next block starts.
+const BLOCK_COMMANDS: [&str; 5] = ["version", "nodes", "skeleton", "triangles", "vertexanimation"];
+
+/// The text of a comment at the end of a line, exporters write the names of vertex animation frames there.
+fn line_comment(line: &str) -> Option<String> {
+    let start = line.find('#').map(|index| index + 1).or_else(|| line.find("//").map(|index| index + 2))?;
+    Some(line[start..].trim().to_string()).filter(|comment| !comment.is_empty())
 }
 
-pub fn load_smd(file_path: &Path) -> Result<ImportFileData, ParseSMDError> {
-    let file = File::open(file_path).expect("This should be checked before called!");
-    let file_buffer = BufReader::new(file);
-    let mut lines = file_buffer.lines().map_while(Result::ok);
+/// Loads SMD files and VTA files, which are SMD files with a vertex animation block.
+/// Tolerant parsing recovers from the quirks of old exporters with a warning where strict parsing stops with an error.
+pub fn load_smd(file_path: &Path, tolerant: bool) -> Result<ImportFileData, ParseSMDError> {
+    let file_buffer = open_import_file(file_path).expect("This should be checked before called!");
+    // Old exporters indent the lines with tabs, which hides the end of a block.
+    let mut lines = file_buffer
+        .lines()
+        .map_while(Result::ok)
+        .map(|line| if tolerant { line.trim().to_string() } else { line });
     let mut line_count = 0;
+    // A line that started a block before the previous block ended, which is parsed again as a command.
+    let mut pending_line: Option<String> = None;
+
+    let file_name = file_path.file_name().unwrap_or_default().to_string_lossy().to_string();
+    let warn = |message: &str, line_count: usize| log(format!("{} Line {}: {}!", file_name, line_count, message), LogLevel::Warn);
+    let starts_block = |line: &str| tolerant && BLOCK_COMMANDS.contains(&line.split_whitespace().next().unwrap_or_default());
 
     struct SplitAtWhitespace {
         input: String,
@@ -133,12 +159,13 @@ pub fn load_smd(file_path: &Path) -> Result<ImportFileData, ParseSMDError> {
 
     let mut nodes = Vec::new();
 
+    #[derive(Clone)]
     struct KeyFrame {
         position: Vector3,
         rotation: Angles,
     }
 
-    let mut frames = Vec::new();
+    let mut frames: Vec<IndexMap<usize, KeyFrame>> = Vec::new();
 
     struct Vertex {
         position: Vector3,
@@ -149,14 +176,52 @@ pub fn load_smd(file_path: &Path) -> Result<ImportFileData, ParseSMDError> {
 
     let mut triangles: IndexMap<String, Vec<[Vertex; 3]>> = IndexMap::new();
 
+    #[derive(Clone)]
     struct FlexVertex {
         position: Vector3,
         normal: Vector3,
     }
 
-    let mut flexes = Vec::new();
+    let mut flexes: Vec<IndexMap<usize, FlexVertex>> = Vec::new();
+
+    // Strict parsing only accepts the next frame, tolerant parsing repeats the last frame over gaps and adds the keys of a repeated frame to that frame.
+    fn select_frame<T: Clone + Default>(
+        frames: &mut Vec<T>,
+        time: usize,
+        tolerant: bool,
+        line_count: usize,
+        warn: &impl Fn(&str, usize),
+    ) -> Result<usize, ParseSMDError> {
+        if time == frames.len() {
+            frames.push(T::default());
+            return Ok(time);
+        }
+
+        if !tolerant {
+            return Err(ParseSMDError::InvalidFrameIndex(line_count));
+        }
 
-    while let Some(line) = lines.next() {
+        if time < frames.len() {
+            warn(&format!("Frame {} is repeated, the keys are added to the frame", time), line_count);
+            return Ok(time);
+        }
+
+        warn(
+            &format!("Frames {} to {} are missing, the previous frame is repeated", frames.len(), time - 1),
+            line_count,
+        );
+        while frames.len() < time {
+            frames.push(frames.last().cloned().unwrap_or_default());
+        }
+        frames.push(T::default());
+        Ok(time)
+    }
+
+    let mut current_frame = 0;
+    let mut current_flex = 0;
+    let mut frame_names = IndexMap::new();
+
+    while let Some(line) = pending_line.take().or_else(|| lines.next()) {
         line_count += 1;
 
         let mut line_arguments = SplitAtWhitespace::new(line);
@@ -185,6 +250,13 @@ pub fn load_smd(file_path: &Path) -> Result<ImportFileData, ParseSMDError> {
                         break;
                     }
 
+                    if starts_block(&line) {
+                        warn("The block before this line has no end", line_count);
+                        line_count -= 1;
+                        pending_line = Some(line);
+                        break;
+                    }
+
                     let mut line_arguments = SplitAtWhitespace::new(line);
 
                     let node_index = match line_arguments.next() {
@@ -227,6 +299,14 @@ pub fn load_smd(file_path: &Path) -> Result<ImportFileData, ParseSMDError> {
                         break;
                     }
 
+                    if starts_block(&line) {
+                        warn("The block before this line has no end", line_count);
+                        line_count -= 1;
+                        pending_line = Some(line);
+                        break;
+                    }
+
+                    let frame_name = if line.trim_start().starts_with("time") { line_comment(&line) } else { None };
                     let mut line_arguments = SplitAtWhitespace::new(line);
 
                     let node_index = match line_arguments.next() {
@@ -235,10 +315,10 @@ pub fn load_smd(file_path: &Path) -> Result<ImportFileData, ParseSMDError> {
                                 match line_arguments.next() {
                                     Some(time) => {
                                         let time = time.parse::<usize>().map_err(|_| ParseSMDError::FailedIntegerParse(line_count))?;
-                                        if time != frames.len() {
-                                            return Err(ParseSMDError::InvalidFrameIndex(line_count));
+                                        current_frame = select_frame(&mut frames, time, tolerant, line_count, &warn)?;
+                                        if let Some(frame_name) = frame_name {
+                                            frame_names.insert(current_frame, frame_name);
                                         }
-                                        frames.push(IndexMap::new());
                                         continue;
                                     }
                                     None => return Err(ParseSMDError::MissingArgument("Time", line_count)),
@@ -290,8 +370,8 @@ pub fn load_smd(file_path: &Path) -> Result<ImportFileData, ParseSMDError> {
                             .map_err(|_| ParseSMDError::FailedIntegerParse(line_count))?,
                     );
 
-                    let previous_frame = frames.last_mut().unwrap();
-                    previous_frame.insert(node_index, KeyFrame { position, rotation });
+                    let frame = frames.get_mut(current_frame).ok_or(ParseSMDError::InvalidFrameIndex(line_count))?;
+                    frame.insert(node_index, KeyFrame { position, rotation });
                 }
             }
             "triangles" => {
@@ -302,6 +382,13 @@ pub fn load_smd(file_path: &Path) -> Result<ImportFileData, ParseSMDError> {
                         break;
                     }
 
+                    if starts_block(&line) {
+                        warn("The block before this line has no end", line_count);
+                        line_count -= 1;
+                        pending_line = Some(line);
+                        break;
+                    }
+
                     let mut line_arguments = SplitAtWhitespace::new(line);
 
                     let material = match line_arguments.next() {
@@ -416,11 +503,26 @@ pub fn load_smd(file_path: &Path) -> Result<ImportFileData, ParseSMDError> {
                         Err(ParseSMDError::UnexpectedEndOfFile)
                     }
 
-                    let triangle = [
-                        parse_vertex(lines.by_ref(), &mut line_count, nodes.len())?,
-                        parse_vertex(lines.by_ref(), &mut line_count, nodes.len())?,
-                        parse_vertex(lines.by_ref(), &mut line_count, nodes.len())?,
-                    ];
+                    fn parse_triangle(
+                        lines: &mut impl Iterator<Item = String>,
+                        line_count: &mut usize,
+                        node_count: usize,
+                    ) -> Result<[Vertex; 3], ParseSMDError> {
+                        Ok([
+                            parse_vertex(lines, line_count, node_count)?,
+                            parse_vertex(lines, line_count, node_count)?,
+                            parse_vertex(lines, line_count, node_count)?,
+                        ])
+                    }
+
+                    let triangle = match parse_triangle(lines.by_ref(), &mut line_count, nodes.len()) {
+                        Ok(triangle) => triangle,
+                        Err(ParseSMDError::UnexpectedEndOfFile) if tolerant => {
+                            warn("The last triangle is incomplete and is left out", line_count);
+                            break;
+                        }
+                        Err(error) => return Err(error),
+                    };
 
                     let triangle_list = triangles.entry(material).or_default();
                     triangle_list.push(triangle);
@@ -434,6 +536,13 @@ pub fn load_smd(file_path: &Path) -> Result<ImportFileData, ParseSMDError> {
                         break;
                     }
 
+                    if starts_block(&line) {
+                        warn("The block before this line has no end", line_count);
+                        line_count -= 1;
+                        pending_line = Some(line);
+                        break;
+                    }
+
                     let mut line_arguments = SplitAtWhitespace::new(line);
 
                     let vertex_index = match line_arguments.next() {
@@ -442,10 +551,7 @@ pub fn load_smd(file_path: &Path) -> Result<ImportFileData, ParseSMDError> {
                                 match line_arguments.next() {
                                     Some(time) => {
                                         let time = time.parse::<usize>().map_err(|_| ParseSMDError::FailedIntegerParse(line_count))?;
-                                        if time != frames.len() {
-                                            return Err(ParseSMDError::InvalidFrameIndex(line_count));
-                                        }
-                                        flexes.push(IndexMap::new());
+                                        current_flex = select_frame(&mut flexes, time, tolerant, line_count, &warn)?;
                                         continue;
                                     }
                                     None => return Err(ParseSMDError::MissingArgument("Time", line_count)),
@@ -493,34 +599,57 @@ pub fn load_smd(file_path: &Path) -> Result<ImportFileData, ParseSMDError> {
                             .map_err(|_| ParseSMDError::FailedIntegerParse(line_count))?,
                     );
 
-                    let previous_flex = flexes.last_mut().unwrap();
-                    previous_flex.insert(vertex_index, FlexVertex { position, normal });
+                    let flex = flexes.get_mut(current_flex).ok_or(ParseSMDError::InvalidFrameIndex(line_count))?;
+                    flex.insert(vertex_index, FlexVertex { position, normal });
                 }
             }
+            _ if tolerant => warn(&format!("Unknown command {} is skipped", command), line_count),
             _ => return Err(ParseSMDError::UnknownCommand(command, line_count)),
         }
     }
 
+    // The first frame of a vertex animation is the base shape, the other frames only list the vertices they move.
     if !flexes.is_empty() {
+        let mut flexes = flexes.into_iter();
+        let mut base_frame = flexes.next().unwrap_or_default();
+        base_frame.sort_keys();
+
+        if base_frame.keys().enumerate().any(|(vertex_index, index)| vertex_index != *index) {
+            return Err(ParseSMDError::InvalidBaseVertexIndex);
+        }
+
         let mut flex_part = ImportPart {
             name: file_path.file_stem().unwrap().to_string_lossy().to_string(),
             ..Default::default()
         };
 
-        for flex in flexes {
-            let mut import_flex = ImportFlex::default();
+        for (flex_index, flex) in flexes.enumerate() {
+            let mut import_flex = ImportFlex {
+                name: frame_names.swap_remove(&(flex_index + 1)),
+                ..Default::default()
+            };
 
             for (index, vertex) in flex {
+                let base_vertex = base_frame.get(&index).ok_or(ParseSMDError::MissingBaseVertex(index))?;
                 import_flex.vertices.push(ImportFlexVertex {
                     index,
-                    position: vertex.position,
-                    normal: vertex.normal,
+                    position: vertex.position - base_vertex.position,
+                    normal: vertex.normal - base_vertex.normal,
                 });
             }
 
             flex_part.flexes.push(import_flex);
         }
 
+        flex_part.vertices = base_frame
+            .into_values()
+            .map(|vertex| ImportVertex {
+                position: vertex.position,
+                normal: vertex.normal,
+                ..Default::default()
+            })
+            .collect();
+
         return Ok(ImportFileData {
             parts: vec![flex_part],
             ..Default::default()
@@ -596,6 +725,7 @@ pub fn load_smd(file_path: &Path) -> Result<ImportFileData, ParseSMDError> {
                         position: vertex.position,
                         normal: vertex.normal,
                         texture_coordinate: vertex.texture_coordinate,
+                        color: None,
                         links: vertex.links.into_iter().map(|(bone, weight)| ImportLink { bone, weight }).collect(),
                     });
                 }
@@ -1,37 +1,574 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct ImputedCompilationData {
     pub model_name: String,
     pub export_path: String,
+    pub target_version: i32,
+    pub surface_property: String,
+    pub animation_block_size: usize,
+    pub write_report: bool,
+    pub write_dx80_mesh: bool,
+    pub write_software_mesh: bool,
+    pub prefix_animation_names: bool,
+    pub rotation_encoding: ImputedRotationEncoding,
+    /// Long animations are split into sections of this many frames, so the engine only loads the part that plays.
+    pub animation_section_frames: usize,
+    /// Moves the model so this point becomes its origin.
+    pub origin: [f64; 3],
+    /// Compiles a second model from the same sources, used for the world model of a weapon.
+    pub world_model: Option<ImputedWorldModel>,
+    pub gibs: Option<ImputedGibs>,
+    pub material_paths: Vec<String>,
+    pub material_renames: Vec<ImputedMaterialRename>,
+    #[serde(default)]
+    pub material_normalization: ImputedMaterialNormalization,
+    pub bone_attachments: Vec<ImputedBoneAttachment>,
+    pub procedural_bones: Vec<ImputedProceduralBone>,
+    pub mouths: Vec<ImputedMouth>,
+    pub bone_flex_drivers: Vec<ImputedBoneFlexDriver>,
+    pub inverse_kinematic_chains: Vec<ImputedInverseKinematicChain>,
+    pub jiggle_chains: Vec<ImputedJiggleChain>,
+    pub weight_lists: Vec<ImputedWeightList>,
+    /// How bones that differ between source files are merged, conflicts without a resolution keep the first source and are reported.
+    #[serde(default)]
+    pub bone_conflicts: Vec<ImputedBoneConflict>,
+    /// Bones other models are merged onto, like the hands of a view model. They are set up even when no vertex uses them.
+    #[serde(default)]
+    pub bone_merge_bones: Vec<String>,
+    pub mesh_limits: ImputedMeshLimits,
+    pub engine_limits: ImputedEngineLimits,
+    pub weld_tolerances: ImputedWeldTolerances,
+    pub texture_coordinates: ImputedTextureCoordinates,
+    pub polygon_limits: ImputedPolygonLimits,
+    pub model_flags: ImputedModelFlags,
+    /// How far in degrees the eyes can turn away from looking forward.
+    pub max_eye_deflection: f64,
+    /// The attachment the lighting of the model is sampled at instead of the center of its bounds.
+    pub illumination_attachment: Option<String>,
+    /// Recalculates the vertex normals, faces within this angle in degrees are smoothed together.
+    pub normal_smoothing_angle: Option<f64>,
+    /// Averages the normals on the open edges of different parts and models that meet within this distance, so seams are not lit apart.
+    #[serde(default)]
+    pub seam_normal_tolerance: Option<f64>,
+    /// Replaces the bounding box calculated from the meshes.
+    pub bounding_box: Option<ImputedBoundingBox>,
+    pub clipping_box: Option<ImputedBoundingBox>,
     pub body_parts: Vec<ImputedBodyPart>,
     pub animations: Vec<ImputedAnimation>,
     pub sequences: Vec<ImputedSequence>,
+    /// The names of the nodes sequences transition between.
+    pub transition_nodes: Vec<String>,
+    pub aim_matrices: Vec<ImputedAimMatrix>,
+    /// The collision model written to the physics file, no physics file is written when none.
+    pub physics: Option<ImputedPhysics>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ImputedWorldModel {
+    pub model_name: String,
+    pub origin: [f64; 3],
+    /// The sequences and body parts the world model keeps by name, everything is kept when a list is empty.
+    pub sequences: Vec<String>,
+    pub body_parts: Vec<String>,
+}
+
+/// How animation rotations that do not change are stored, the smaller encoding is less precise.
+/// Models of version 48 and 49 have no smaller encoding than 48 bits for animation rotations.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub enum ImputedRotationEncoding {
+    Quaternion64,
+    Quaternion48,
+}
+
+/// Makes the model a breakable prop that breaks into the models of a body part.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ImputedGibs {
+    /// The prop_data base the prop inherits from, like Wooden.Medium.
+    pub base: String,
+    /// Replaces the health of the base when above 0.
+    pub health: u32,
+    /// The physics impact damage table used when the prop hits something, like glass. Empty keeps the table of the base.
+    #[serde(default)]
+    pub damage_table: String,
+    /// The gib set from propdata.txt the prop also breaks into, like WoodChunks.
+    #[serde(default)]
+    pub breakable_model: String,
+    /// How many gibs of the gib set are spawned, 0 keeps the count of the base.
+    #[serde(default)]
+    pub breakable_count: u32,
+    #[serde(default)]
+    pub breakable_skin: u32,
+    /// The models the prop breaks into, the gibs compiled as separate models are added to these when the prop is compiled.
+    #[serde(default)]
+    pub gib_models: Vec<ImputedGibModel>,
+    pub body_part: String,
+    /// Compiles every model of the body part as its own model centered on its bounds, instead of keeping them in the prop.
+    pub separate_models: bool,
+}
+
+/// A model the prop breaks into, written as a break block of the collision model.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct ImputedGibModel {
+    /// The path of the model relative to the models folder.
+    pub model: String,
+    /// The seconds the gib stays before it fades out, 0 keeps it until it is removed.
+    pub fade_time: f64,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ImputedBoneConflict {
+    pub bone: String,
+    pub resolution: ImputedBoneResolution,
+}
+
+/// The hierarchy always comes from the first source with the bone, only the bind pose can be taken from an animation.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub enum ImputedBoneResolution {
+    /// Keeps the bind pose of the model files without reporting the animations that differ.
+    UseModel,
+    /// Uses the bind pose of the first animation that differs from the model files.
+    UseAnimation,
+    /// Animations that differ do not animate the bone.
+    Ignore,
+}
+
+/// The most bones and materials the engine branch of the target game loads in a model.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ImputedEngineLimits {
+    pub bones: usize,
+    pub materials: usize,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ImputedMeshLimits {
+    pub hardware_bones_per_strip: usize,
+    pub weights_per_vertex: usize,
+    pub vertex_cache_size: usize,
+    pub optimize_overdraw: bool,
+    pub overdraw_threshold: f64,
+    #[serde(default)]
+    pub mikktspace_tangents: bool,
+}
+
+/// How close vertices have to be to be welded into one, the normal angle is in degrees.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ImputedWeldTolerances {
+    pub position: f64,
+    pub normal_angle: f64,
+    pub texture_coordinate: f64,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ImputedTextureCoordinates {
+    /// Flips the V coordinate for formats that have the origin at the bottom of the texture.
+    pub flip_v: bool,
+    /// Moves every face into the first texture tile.
+    pub normalize: bool,
+    /// Errors on faces that are completely in a UDIM tile other than the first.
+    pub reject_udim: bool,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ImputedPolygonLimits {
+    pub maximum_vertices: usize,
+    pub strict: bool,
+}
+
+/// The flags in the model header that change how the engine draws the model.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ImputedModelFlags {
+    /// Marks a model with a single bone and no animation as a prop the engine can bake into the map.
+    #[serde(default)]
+    pub static_prop: bool,
+    /// Draws the model as opaque even when its materials are translucent.
+    pub force_opaque: bool,
+    /// Draws the opaque parts of a translucent model in a separate pass, which can not be used with forced opaque.
+    pub translucent_two_pass: bool,
+    pub ambient_boost: bool,
+    pub do_not_cast_shadows: bool,
+    /// Shadows use the alpha of the materials, which can not be used with no shadows.
+    pub cast_texture_shadows: bool,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ImputedBoundingBox {
+    pub minimum: [f64; 3],
+    pub maximum: [f64; 3],
+}
+
+/// A * in the source material matches any text, which replaces the * in the compiled material.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ImputedMaterialRename {
+    pub from: String,
+    pub to: String,
+}
+
+/// How the material names from the source files are cleaned up before they are renamed.
+/// Projects saved without it keep the material names as they are.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct ImputedMaterialNormalization {
+    /// Removes the folders exporters write in front of the texture name, like the absolute path of the texture.
+    pub strip_directories: bool,
+    /// Merges materials that only differ by case or by a texture extension like .tga into the first of them.
+    #[serde(default)]
+    pub merge_similar: bool,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ImputedBoneAttachment {
+    pub name: String,
+    pub bone: String,
+}
+
+/// A bone that is posed from the rotation of its control bone instead of being animated.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ImputedProceduralBone {
+    pub bone: String,
+    pub control_bone: String,
+    pub procedural: ImputedProcedural,
+}
+
+/// Angles are in degrees.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(tag = "type")]
+pub enum ImputedProcedural {
+    /// Blends between the transforms for when the axis of the control bone points along X+, X-, Y+, Y-, Z+ and Z-.
+    AxisInterpolation {
+        axis: ImputedAxis,
+        /// Added to the bind position of the bone, the same as the trigger positions.
+        positions: Box<[[f64; 3]; 6]>,
+        rotations: Box<[[f64; 3]; 6]>,
+    },
+    /// Blends between the transforms of the triggers the control bone rotation is closest to.
+    QuaternionInterpolation { triggers: Vec<ImputedQuaternionTrigger> },
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ImputedQuaternionTrigger {
+    pub tolerance: f64,
+    pub trigger: [f64; 3],
+    /// Added to the bind position of the bone.
+    pub position: [f64; 3],
+    pub rotation: [f64; 3],
+}
+
+/// A mouth used by lip sync to darken the inside of the mouth as it closes.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ImputedMouth {
+    pub bone: String,
+    /// The direction the mouth faces in the space of the bone.
+    pub forward: [f64; 3],
+    /// The flex description that opens the mouth.
+    pub flex: String,
+    /// The flex controller lip sync sets to open the mouth, the name of the flex is used when empty.
+    #[serde(default)]
+    pub flex_controller: String,
+}
+
+/// Drives flex controllers from the position of a bone relative to its parent.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ImputedBoneFlexDriver {
+    pub bone: String,
+    pub controls: Vec<ImputedBoneFlexControl>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ImputedBoneFlexControl {
+    /// The axis of the bone position that drives the flex controller.
+    pub component: ImputedAxis,
+    pub flex_controller: String,
+    /// The positions along the axis that set the flex controller to 0 and 1.
+    pub minimum: f64,
+    pub maximum: f64,
+}
+
+/// A leg or arm of three bones ending at the named bone, solved by moving the end bone onto its target.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ImputedInverseKinematicChain {
+    pub name: String,
+    pub bone: String,
+    /// The direction the middle joint bends towards in model space.
+    pub knee_direction: [f64; 3],
+}
+
+/// Makes every bone from the start bone down to the end bone a jiggle bone, the end bone marks the tip and does not jiggle itself.
+/// The stiffness and damping blend from the first value at the start of the chain to the second value at the tip.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ImputedJiggleChain {
+    pub start_bone: String,
+    pub end_bone: String,
+    pub tip_mass: f64,
+    pub stiffness: [f64; 2],
+    pub damping: [f64; 2],
+    /// How far in degrees each bone can bend away from its rest direction, zero leaves the bones unlimited.
+    pub angle_limit: f64,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ImputedWeightList {
+    pub name: String,
+    pub weights: Vec<ImputedBoneWeight>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ImputedBoneWeight {
+    pub bone: String,
+    pub weight: f64,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct ImputedBodyPart {
     pub name: String,
+    /// The model picked by the default body, which is reported to be set on the entity. The models keep their order.
+    #[serde(default)]
+    pub default_model: usize,
+    /// The model the bounding boxes are calculated from, every model is used when none.
+    #[serde(default)]
+    pub preview_model: Option<usize>,
     pub models: Vec<ImputedModel>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct ImputedModel {
     pub name: String,
     pub is_blank: bool,
     pub file_source: String,
     pub part_names: Vec<String>,
+    /// Only the polygons of these source materials are used, every material is used when empty.
+    pub materials: Vec<String>,
+    pub mirror: Option<ImputedMirror>,
+    /// A vertex animation of the model file, every frame after the base frame is a flex named after the frame.
+    /// The model has no flexes when empty.
+    #[serde(default)]
+    pub flex_file_source: String,
 }
 
-#[derive(Debug, Deserialize)]
+/// Mirrors the geometry of a model across an axis of the source file.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ImputedMirror {
+    pub axis: ImputedAxis,
+    /// Binds the vertices to the bones of the other side, found by swapping left and right in the bone names.
+    /// The vertices are mirrored in the space of their bones, so they follow the roll of the bones on the other side.
+    pub mirror_bones: bool,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+pub enum ImputedAxis {
+    X,
+    Y,
+    Z,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct ImputedAnimation {
     pub name: String,
     pub file_source: String,
     pub animation_name: String,
+    /// The first and last imported frame the animation uses, every frame is used when none.
+    pub frame_range: Option<[usize; 2]>,
+    pub fps: f64,
+    /// Applies the animation as changes from the rest pose of its source skeleton, for rigs with a different rest pose than the model.
+    pub retarget: bool,
+    pub modifiers: ImputedAnimationModifiers,
+    pub subtract: Option<ImputedAnimationSubtract>,
+    pub motion: Option<ImputedAnimationMotion>,
+    pub inverse_kinematic_rules: Vec<ImputedInverseKinematicRule>,
+}
+
+/// Changes applied to the frames of an animation after they are baked, in the order of the fields.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ImputedAnimationModifiers {
+    pub reverse: bool,
+    /// Scales the playback rate by resampling the frames, two plays the animation in half the frames. Must be from 0.01 to 100.
+    pub speed: f64,
+    /// Repeats the first and last frame this many times.
+    pub hold_start: usize,
+    pub hold_end: usize,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ImputedAnimationSubtract {
+    pub animation: String,
+    pub frame: usize,
+}
+
+/// Extracts the linear motion of the root bones so the animation moves the entity.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ImputedAnimationMotion {
+    pub x: bool,
+    pub y: bool,
+    pub z: bool,
+    /// The amount of pieces the motion is split into, each piece has its own velocity.
+    pub pieces: usize,
+    /// Removes the extracted motion from the root bones so the animation plays in place.
+    pub subtract: bool,
+}
+
+/// Locks an inverse kinematic chain in place over a range of frames of an animation.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ImputedInverseKinematicRule {
+    pub chain: String,
+    pub rule: ImputedInverseKinematicRuleType,
+    /// The frames the rule starts blending in, is fully applied, starts blending out and ends.
+    pub start: usize,
+    pub peak: usize,
+    pub tail: usize,
+    pub end: usize,
+    /// The frame the end bone touches the ground.
+    pub contact: usize,
+    pub height: f64,
+    pub radius: f64,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(tag = "type")]
+pub enum ImputedInverseKinematicRuleType {
+    /// Keeps the end bone where it is relative to another bone.
+    Touch { bone: String },
+    /// Plants the end bone on the ground.
+    Footstep,
+    /// Releases the chain from the previous rules.
+    Release,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct ImputedSequence {
     pub name: String,
     pub animations: Vec<Vec<String>>,
+    pub weight_list: Option<String>,
+    pub auto_layers: Vec<ImputedAutoLayer>,
+    /// Replaces the bounding box calculated from the animated meshes.
+    pub bounding_box: Option<ImputedBoundingBox>,
+    pub transition: Option<ImputedSequenceTransition>,
+    pub inverse_kinematic_locks: Vec<ImputedInverseKinematicLock>,
+    /// The seconds the sequence takes to blend in when it starts playing.
+    pub fade_in_time: f64,
+    /// The seconds the sequence takes to blend out when another sequence starts playing.
+    pub fade_out_time: f64,
+    pub phase: Option<ImputedSequencePhase>,
+    /// Blends the columns of the animations across a pose parameter.
+    pub column_blend: Option<ImputedSequenceBlend>,
+    /// Blends the rows of the animations across a pose parameter.
+    pub row_blend: Option<ImputedSequenceBlend>,
+    /// Picks this sequence over the other sequences of its activity when the entity has the same modifiers set.
+    #[serde(default)]
+    pub activity_modifiers: Vec<String>,
+}
+
+/// A pose parameter that picks between the animations of a sequence, single frame animations make a blend of static poses.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ImputedSequenceBlend {
+    pub parameter: String,
+    /// The value of the pose parameter at each column or row, the values must increase.
+    pub keys: Vec<f64>,
+}
+
+/// The cycles the sequence is at when it enters and leaves its transition nodes, both from zero to one.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ImputedSequencePhase {
+    pub entry: f64,
+    pub exit: f64,
+}
+
+/// The nodes a sequence moves from and to, a sequence with the same entry and exit node is the pose of that node.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ImputedSequenceTransition {
+    pub entry: String,
+    pub exit: String,
+    /// The sequence can also be played backwards to move from the exit node to the entry node.
+    pub reverse: bool,
+}
+
+/// Keeps an inverse kinematic chain where it was before the sequence was applied.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ImputedInverseKinematicLock {
+    pub chain: String,
+    pub position_weight: f64,
+    pub local_rotation_weight: f64,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ImputedAutoLayer {
+    pub sequence: String,
+    pub start: f64,
+    pub peak: f64,
+    pub tail: f64,
+    pub end: f64,
+    pub spline: bool,
+    pub cross_fade: bool,
+    pub no_blend: bool,
+}
+
+/// Generates a delta sequence that blends a set of bones to aim across a yaw and pitch range.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ImputedAimMatrix {
+    /// The name of the generated sequence.
+    pub name: String,
+    /// The animation the bones aim from, the aim is applied around the pose at the frame.
+    pub animation: String,
+    pub frame: usize,
+    /// How much of the aim each bone takes, the weights along a chain should add up to one.
+    pub weight_list: String,
+    pub yaw_parameter: String,
+    /// The yaw in degrees the matrix reaches to either side.
+    pub yaw_range: f64,
+    pub pitch_parameter: String,
+    /// The pitch in degrees the matrix reaches up and down.
+    pub pitch_range: f64,
+}
+
+/// A ragdoll of collision solids attached to bones, each solid is jointed to the solid of its closest parent bone.
+/// A single solid makes the collision model of a prop.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ImputedPhysics {
+    /// The mass of the whole model in kilograms, split between the solids by their volume.
+    pub total_mass: f64,
+    pub solids: Vec<ImputedPhysicsSolid>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ImputedPhysicsSolid {
+    pub bone: String,
+    pub shape: ImputedPhysicsShape,
+    /// The rotation limits of the joint to the parent solid around the X, Y and Z axis of the bone.
+    pub limits: [ImputedJointLimit; 3],
+}
+
+/// The shape of a solid in the space of its bone.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(tag = "type")]
+pub enum ImputedPhysicsShape {
+    Box {
+        minimum: [f64; 3],
+        maximum: [f64; 3],
+    },
+    /// A capsule from the bone along its X axis.
+    Capsule {
+        radius: f64,
+        length: f64,
+    },
+    /// The convex hull of the vertices in the source file that are mostly weighted to the bone.
+    Mesh {
+        file_source: String,
+        /// Splits a concave mesh into several convex hulls instead of wrapping it in one.
+        decomposition: Option<ImputedConvexDecomposition>,
+    },
+}
+
+/// The mesh is split into its connected pieces, which are merged by how little they grow until there are few enough hulls.
+/// Pieces that are still concave are then cut in two, the most concave first, until they are close to convex or there are no hulls left.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ImputedConvexDecomposition {
+    pub maximum_hulls: usize,
+    /// Each hull is simplified to at most this many vertices, keeping the vertices that stick out the most.
+    pub maximum_vertices: usize,
+}
+
+/// Angles are in degrees, a joint with the same minimum and maximum can not rotate around the axis.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ImputedJointLimit {
+    pub minimum: f64,
+    pub maximum: f64,
+    pub friction: f64,
 }
@@ -1,56 +1,549 @@
 // Prevents additional console window on Windows in release, DO NOT REMOVE!!
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
-use std::sync::Arc;
+use std::{collections::HashMap, fs, path::Path, sync::Arc, thread, time::Duration};
 
-use tauri::Manager;
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
 
+pub mod game;
 pub mod import;
 pub mod input;
+pub mod plugin;
 pub mod process;
+pub mod project;
+pub mod read;
+pub mod report;
+pub mod server;
 pub mod utilities;
 pub mod write;
 
-use import::{FileManager, ImportFileData};
-use input::ImputedCompilationData;
-use process::process;
-use utilities::logging::{log, LogLevel, LOGGER};
-use write::write_files;
+#[cfg(all(test, feature = "golden-tests"))]
+mod golden;
+
+use game::{detect_game_profile, expand_export_path, find_materials_directory, material_exists, GameProfile};
+use import::{decompile_mdl, FileManager, FileStatus, ImportAnimationFrame, ImportFileData};
+use input::{ImputedBodyPart, ImputedCompilationData, ImputedGibModel, ImputedModel, ImputedWorldModel};
+use plugin::{load_script_plugins, PluginManager};
+use process::{preview_materials, preview_skeleton, process, ProcessingDataError, SkeletonPreviewBone};
+use project::{find_relocated_files, open_project, save_project, OpenedProject};
+use read::{dump_model_files, verify_files};
+use report::CompileReport;
+use server::JobServer;
+use utilities::{
+    logging::{collect_warnings, export_log, log, LogLevel, LOGGER},
+    mathematics::BoundingBox,
+    progress::{clear_cancel, request_cancel},
+};
+use write::{write_files, FileWriteError, StagedFiles};
 
 // Learn more about Tauri commands at https://tauri.app/v1/guides/features/command
 #[tauri::command(async)]
-fn compile_model(data: ImputedCompilationData, file_manager: tauri::State<FileManager>) {
+fn compile_model(
+    data: ImputedCompilationData,
+    dry_run: bool,
+    file_manager: tauri::State<FileManager>,
+    plugin_manager: tauri::State<PluginManager>,
+    job_server: tauri::State<JobServer>,
+) -> Option<CompileReport> {
+    // Jobs share the cancel flag, warnings and listeners of the compile, so the window waits for a job that is compiling.
+    let _compiling = job_server.lock_compile();
+    compile_project(data, dry_run, &file_manager, &plugin_manager)
+}
+
+/// Compiles the model of the project with its world model and gibs, returning the report of the model.
+fn compile_project(
+    mut data: ImputedCompilationData,
+    dry_run: bool,
+    file_manager: &tauri::State<FileManager>,
+    plugin_manager: &PluginManager,
+) -> Option<CompileReport> {
+    clear_cancel();
+
+    // Projects are shared with the tokens in the export path, they are only replaced for this compile.
+    data.export_path = match expand_export_path(&data) {
+        Ok(export_path) => export_path,
+        Err(error) => {
+            log(format!("Fail To Expand Export Path: {}!", error), LogLevel::Error);
+            return None;
+        }
+    };
+
+    // Gibs compiled as their own models are taken out of the prop before it is compiled.
+    let gib_models = match data.gibs.as_ref().filter(|gibs| gibs.separate_models) {
+        Some(gibs) => match data.body_parts.iter().position(|body_part| body_part.name == gibs.body_part) {
+            Some(index) => data.body_parts.remove(index).models,
+            None => {
+                log(format!("Gib body part \"{}\" not found!", gibs.body_part), LogLevel::Error);
+                return None;
+            }
+        },
+        None => Vec::new(),
+    };
+
+    // The prop breaks into the gibs that are compiled from it, which are referenced by the names they are compiled with.
+    if let Some(gibs) = data.gibs.as_mut() {
+        for gib_model in gib_models.iter().filter(|model| !model.is_blank) {
+            if let Some(model) = normalize_model_name(&gib_model_name(&data.model_name, gib_model)) {
+                gibs.gib_models.push(ImputedGibModel { model, fade_time: 0.0 });
+            }
+        }
+    }
+
+    let mut compiled_models = vec![compile(&data, dry_run, file_manager, plugin_manager)?];
+
+    if let Some(world_model) = &data.world_model {
+        log("Compiling world model!", LogLevel::Info);
+        compiled_models.push(compile(&create_world_model_data(&data, world_model), dry_run, file_manager, plugin_manager)?);
+    }
+
+    for gib_model in gib_models.iter().filter(|model| !model.is_blank) {
+        log(format!("Compiling gib {}!", gib_model.name), LogLevel::Info);
+        compiled_models.push(compile(
+            &create_gib_data(&data, gib_model, file_manager),
+            dry_run,
+            file_manager,
+            plugin_manager,
+        )?);
+    }
+
+    // No file is replaced until every model is written, so a failed world model or gib leaves the files of the model untouched.
+    let mut staged_files = StagedFiles::default();
+    let mut reports = Vec::with_capacity(compiled_models.len());
+    for (report, model_staged_files) in compiled_models {
+        staged_files.append(model_staged_files);
+        reports.push(report);
+    }
+
+    if let Err(error) = staged_files.commit() {
+        log(format!("Fail To Write Files: {}!", error), LogLevel::Error);
+        return None;
+    }
+
+    for report in &reports {
+        finish_compile(&data, report);
+    }
+
+    let mut report = reports.remove(0);
+    report.additional_models = reports;
+    Some(report)
+}
+
+/// Processes and writes a single model to temporary files, returning the report of the written files and the files to commit.
+/// A dry run processes and writes the model in memory, reporting the differences to the files on disk without overwriting them.
+fn compile(
+    data: &ImputedCompilationData,
+    dry_run: bool,
+    file_manager: &tauri::State<FileManager>,
+    plugin_manager: &PluginManager,
+) -> Option<(CompileReport, StagedFiles)> {
+    let warnings = collect_warnings();
+
+    // Plugins run before the name is checked, so they can apply naming rules to it.
+    let mut data = data.clone();
+    if let Err(error) = plugin_manager.pre_process(&mut data) {
+        log(format!("Fail To Compile Model: {}!", error), LogLevel::Error);
+        return None;
+    }
+    let data = &data;
+
     if data.model_name.is_empty() {
         log("Model name is empty!", LogLevel::Error);
-        return;
+        return None;
     }
 
-    let mut model_name = data.model_name.clone();
-    if !model_name.ends_with(".mdl") {
-        model_name.push_str(".mdl");
-    }
+    let model_name = match normalize_model_name(&data.model_name) {
+        Some(name) => name,
+        None => {
+            log("Model name can not leave the export path!", LogLevel::Error);
+            return None;
+        }
+    };
+
+    // The files are written to the same path the internal name has relative to the models folder.
+    let file_name = model_name.trim_end_matches(".mdl").to_string();
 
     log(format!("Compiling model {}!", &model_name), LogLevel::Info);
 
-    let processed_data = match process(&data, &file_manager) {
+    let mut processed_data = match process(data, file_manager) {
         Ok(data) => data,
+        Err(ProcessingDataError::Cancelled) => {
+            log("Compile cancelled!", LogLevel::Info);
+            return None;
+        }
         Err(error) => {
             log(format!("Fail To Compile Model: {}!", error), LogLevel::Error);
-            return;
+            return None;
         }
     };
 
+    if let Err(error) = plugin_manager.post_process(data, &processed_data) {
+        log(format!("Fail To Compile Model: {}!", error), LogLevel::Error);
+        return None;
+    }
+
+    if let Err(error) = plugin_manager.pre_write(data, &mut processed_data) {
+        log(format!("Fail To Write Files: {}!", error), LogLevel::Error);
+        return None;
+    }
+
     log("Writing Files!", LogLevel::Info);
 
-    match write_files(data.model_name, model_name, processed_data, data.export_path) {
-        Ok(_) => {}
+    let (mut report, staged_files) = match write_files(file_name, model_name, data, processed_data, dry_run) {
+        Ok(written) => written,
+        Err(FileWriteError::Cancelled) => {
+            log("Compile cancelled!", LogLevel::Info);
+            return None;
+        }
         Err(error) => {
             log(format!("Fail To Write Files: {}!", error), LogLevel::Error);
-            return;
+            return None;
+        }
+    };
+    report.warnings = warnings.take();
+
+    Some((report, staged_files))
+}
+
+/// Reports a model after its files were committed, writing the compile report next to it when asked to.
+fn finish_compile(data: &ImputedCompilationData, report: &CompileReport) {
+    if report.dry_run {
+        log(
+            format!("Dry run of {} found {} differences!", report.model_name, report.differences.len()),
+            LogLevel::Info,
+        );
+        for difference in &report.differences {
+            log(format!("Dry Run: {}", difference), LogLevel::Info);
+        }
+        return;
+    }
+
+    log(format!("Model {} compiled successfully!", report.model_name), LogLevel::Info);
+
+    if data.write_report {
+        let file_name = report.model_name.trim_end_matches(".mdl");
+        let report_path = Path::new(&data.export_path).join(format!("{}.{}", file_name, "report.txt"));
+        if let Err(error) = report.write_to_file(&report_path) {
+            log(format!("Fail To Write Compile Report: {}!", error), LogLevel::Warn);
+        }
+    }
+
+    // Development builds always read back the output so writer offset bugs show up right away.
+    if tauri::is_dev() {
+        verify_output(report);
+    }
+}
+
+/// Creates the project of the world model, which shares the sources of the view model but has its own name, origin, sequences and body parts.
+fn create_world_model_data(data: &ImputedCompilationData, world_model: &ImputedWorldModel) -> ImputedCompilationData {
+    let mut world_model_data = data.clone();
+    world_model_data.model_name = world_model.model_name.clone();
+    world_model_data.origin = world_model.origin;
+    world_model_data.world_model = None;
+
+    if !world_model.sequences.is_empty() {
+        world_model_data.sequences.retain(|sequence| world_model.sequences.contains(&sequence.name));
+        world_model_data
+            .aim_matrices
+            .retain(|aim_matrix| world_model.sequences.contains(&aim_matrix.name));
+
+        // Auto layers can only play sequences the world model still has.
+        for sequence in &mut world_model_data.sequences {
+            sequence.auto_layers.retain(|auto_layer| world_model.sequences.contains(&auto_layer.sequence));
+        }
+    }
+
+    if !world_model.body_parts.is_empty() {
+        world_model_data.body_parts.retain(|body_part| world_model.body_parts.contains(&body_part.name));
+    }
+
+    world_model_data
+}
+
+/// Creates the project of a gib, which is named after the prop and only has the gib model centered on its bounds.
+fn create_gib_data(data: &ImputedCompilationData, gib_model: &ImputedModel, file_manager: &FileManager) -> ImputedCompilationData {
+    let mut gib_data = data.clone();
+    gib_data.model_name = gib_model_name(&data.model_name, gib_model);
+    gib_data.origin = calculate_gib_center(gib_model, file_manager);
+    gib_data.world_model = None;
+    gib_data.gibs = None;
+    gib_data.body_parts = vec![ImputedBodyPart {
+        name: gib_model.name.clone(),
+        default_model: 0,
+        preview_model: None,
+        models: vec![gib_model.clone()],
+    }];
+
+    // Gibs only need a pose to rest in, so only the first sequence and its animations are kept.
+    gib_data.sequences.truncate(1);
+    for sequence in &mut gib_data.sequences {
+        sequence.auto_layers.clear();
+        sequence.transition = None;
+    }
+    gib_data.animations.retain(|animation| {
+        data.sequences
+            .first()
+            .is_some_and(|sequence| sequence.animations.iter().flatten().any(|name| name == &animation.name))
+    });
+    gib_data.aim_matrices.clear();
+    gib_data.transition_nodes.clear();
+
+    gib_data
+}
+
+/// Gibs are named after the prop with the name of their model after it.
+fn gib_model_name(model_name: &str, gib_model: &ImputedModel) -> String {
+    format!("{}_{}", model_name.trim_end_matches(".mdl"), gib_model.name)
+}
+
+/// The center of the bounds of the parts a gib model uses, in the space of its source file.
+fn calculate_gib_center(gib_model: &ImputedModel, file_manager: &FileManager) -> [f64; 3] {
+    // A missing file is reported when the gib is processed.
+    let imported_file = match file_manager.get_file(&gib_model.file_source) {
+        Some(file) => file,
+        None => return [0.0; 3],
+    };
+
+    let mut bounds: Option<BoundingBox> = None;
+    for part in imported_file.parts.iter().filter(|part| gib_model.part_names.contains(&part.name)) {
+        for vertex in &part.vertices {
+            match &mut bounds {
+                Some(bounds) => bounds.add_point(vertex.position),
+                None => {
+                    bounds = Some(BoundingBox {
+                        minimum: vertex.position,
+                        maximum: vertex.position,
+                    })
+                }
+            }
+        }
+    }
+
+    match bounds {
+        Some(bounds) => {
+            let center = bounds.center();
+            [center.x, center.y, center.z]
+        }
+        None => [0.0; 3],
+    }
+}
+
+/// Converts a model name into the path relative to the models folder that the engine expects as the internal name.
+/// Returns none if the name would place the model outside of the export path.
+fn normalize_model_name(name: &str) -> Option<String> {
+    // The internal model name is read by the engine, which always expects forward slashes.
+    let mut model_name = name.replace('\\', "/").trim_start_matches('/').to_string();
+
+    // Names are often copied with the models folder included, but the engine already adds it.
+    if model_name.get(..7).is_some_and(|prefix| prefix.eq_ignore_ascii_case("models/")) {
+        model_name.drain(..7);
+    }
+
+    if model_name
+        .split('/')
+        .any(|component| component.is_empty() || component == "." || component == "..")
+    {
+        return None;
+    }
+
+    if !model_name.ends_with(".mdl") {
+        model_name.push_str(".mdl");
+    }
+
+    Some(model_name)
+}
+
+#[tauri::command]
+fn cancel_compile() {
+    log("Cancelling compile!", LogLevel::Info);
+    request_cancel();
+}
+
+#[tauri::command(async)]
+fn verify_model(report: CompileReport) -> bool {
+    verify_output(&report)
+}
+
+fn verify_output(report: &CompileReport) -> bool {
+    log("Verifying Files!", LogLevel::Info);
+
+    let mismatches = match verify_files(report) {
+        Ok(mismatches) => mismatches,
+        Err(error) => {
+            log(format!("Fail To Verify Files: {}!", error), LogLevel::Error);
+            return false;
+        }
+    };
+
+    for mismatch in &mismatches {
+        log(format!("Verification: {}", mismatch), LogLevel::Error);
+    }
+
+    if !mismatches.is_empty() {
+        return false;
+    }
+
+    log("Files match the compiled model!", LogLevel::Info);
+    true
+}
+
+#[tauri::command(async)]
+fn decompile_model(path: String) -> Option<ImputedCompilationData> {
+    let project = match decompile_mdl(Path::new(&path)) {
+        Ok(project) => project,
+        Err(error) => {
+            log(format!("Fail To Decompile Model: {}!", error), LogLevel::Error);
+            return None;
+        }
+    };
+
+    log(format!("Decompiled model {}!", project.model_name), LogLevel::Info);
+    Some(project)
+}
+
+/// The dump is written next to the model so it can be diffed against the dump of the same model from another compiler.
+#[tauri::command(async)]
+fn dump_model(path: String) -> Option<String> {
+    let model_path = Path::new(&path);
+    let dump = match dump_model_files(model_path) {
+        Ok(dump) => dump,
+        Err(error) => {
+            log(format!("Fail To Dump Model: {}!", error), LogLevel::Error);
+            return None;
+        }
+    };
+
+    let dump_path = model_path.with_extension("dump.txt");
+    if let Err(error) = fs::write(&dump_path, dump) {
+        log(format!("Fail To Write Dump File: {}!", error), LogLevel::Error);
+        return None;
+    }
+
+    log(format!("Dumped model to {}!", dump_path.display()), LogLevel::Info);
+    Some(dump_path.to_string_lossy().to_string())
+}
+
+#[tauri::command(async)]
+fn save_project_file(path: String, data: ImputedCompilationData) -> bool {
+    match save_project(&data, Path::new(&path)) {
+        Ok(()) => {
+            log(format!("Saved project {}!", path), LogLevel::Info);
+            true
+        }
+        Err(error) => {
+            log(format!("Fail To Save Project: {}!", error), LogLevel::Error);
+            false
+        }
+    }
+}
+
+#[tauri::command(async)]
+fn open_project_file(path: String) -> Option<OpenedProject> {
+    match open_project(Path::new(&path)) {
+        Ok(opened_project) => {
+            for missing_file in &opened_project.missing_files {
+                log(format!("Project source file {} not found!", missing_file), LogLevel::Warn);
+            }
+            Some(opened_project)
+        }
+        Err(error) => {
+            log(format!("Fail To Open Project: {}!", error), LogLevel::Error);
+            None
+        }
+    }
+}
+
+#[tauri::command(async)]
+fn relocate_files(directory: String, missing_files: Vec<String>) -> Option<HashMap<String, String>> {
+    match find_relocated_files(Path::new(&directory), &missing_files) {
+        Ok(relocated_files) => {
+            log(
+                format!("Found {} of {} missing files!", relocated_files.len(), missing_files.len()),
+                LogLevel::Info,
+            );
+            Some(relocated_files)
+        }
+        Err(error) => {
+            log(format!("Fail To Relocate Files: {}!", error), LogLevel::Error);
+            None
         }
     }
+}
+
+#[tauri::command(async)]
+fn inspect_skeleton(data: ImputedCompilationData, file_manager: tauri::State<FileManager>) -> Option<Vec<SkeletonPreviewBone>> {
+    match preview_skeleton(&data, &file_manager) {
+        Ok(skeleton) => Some(skeleton),
+        Err(error) => {
+            log(format!("Fail To Merge Skeleton: {}!", error), LogLevel::Error);
+            None
+        }
+    }
+}
 
-    log("Model compiled successfully!", LogLevel::Info);
+/// Errors are left for the compile to report, as the sources of other entries can still be loading when this is checked.
+#[tauri::command(async)]
+fn find_bone_conflicts(data: ImputedCompilationData, file_manager: tauri::State<FileManager>) -> Vec<SkeletonPreviewBone> {
+    preview_skeleton(&data, &file_manager)
+        .map(|skeleton| skeleton.into_iter().filter(|bone| !bone.conflicting_sources.is_empty()).collect())
+        .unwrap_or_default()
+}
+
+/// Materials are searched in the materials folder of the game the model is exported to, materials packed in VPK files are reported as missing.
+#[tauri::command(async)]
+fn find_missing_materials(data: ImputedCompilationData, file_manager: tauri::State<FileManager>) -> Option<Vec<String>> {
+    let export_path = match expand_export_path(&data) {
+        Ok(export_path) => export_path,
+        Err(error) => {
+            log(format!("Fail To Check Materials: {}!", error), LogLevel::Error);
+            return None;
+        }
+    };
+
+    let materials_directory = match find_materials_directory(Path::new(&export_path)) {
+        Some(directory) => directory,
+        None => {
+            log("Fail To Check Materials: The Export Path Is Not In A Models Folder!", LogLevel::Error);
+            return None;
+        }
+    };
+
+    let (materials, material_paths) = match preview_materials(&data, &file_manager) {
+        Ok(preview) => preview,
+        Err(error) => {
+            log(format!("Fail To Check Materials: {}!", error), LogLevel::Error);
+            return None;
+        }
+    };
+
+    let missing_materials = materials
+        .into_iter()
+        .filter(|material| !material_exists(&materials_directory, &material_paths, material))
+        .collect::<Vec<_>>();
+
+    for material in &missing_materials {
+        log(
+            format!("Material \"{}\" Was Not Found In {}!", material, materials_directory.display()),
+            LogLevel::Warn,
+        );
+    }
+
+    log(format!("Checked Materials, {} Missing!", missing_materials.len()), LogLevel::Info);
+    Some(missing_materials)
+}
+
+#[tauri::command(async)]
+fn detect_game(path: String) -> Option<GameProfile> {
+    match detect_game_profile(Path::new(&path)) {
+        Ok(profile) => {
+            log(format!("Detected game {}!", profile.name), LogLevel::Info);
+            Some(profile)
+        }
+        Err(error) => {
+            log(format!("Fail To Detect Game: {}!", error), LogLevel::Error);
+            None
+        }
+    }
 }
 
 #[tauri::command(async)]
@@ -69,16 +562,177 @@ fn unload_file(path: String, file_manager: tauri::State<FileManager>) {
     file_manager.unload_file(path);
 }
 
+#[tauri::command(async)]
+fn release_file(path: String, file_manager: tauri::State<FileManager>) {
+    file_manager.release_file(path);
+}
+
+#[tauri::command(async)]
+fn release_all_files(file_manager: tauri::State<FileManager>) {
+    file_manager.release_all_files();
+}
+
+#[tauri::command]
+fn set_tolerant_parsing(path: String, tolerant: bool, file_manager: tauri::State<FileManager>) {
+    file_manager.set_tolerant(path, tolerant);
+}
+
+/// The file has to be loaded, as the entry that inspects the animation loads its file.
+#[tauri::command(async)]
+fn inspect_animation_frame(path: String, animation: String, frame: usize, file_manager: tauri::State<FileManager>) -> Option<ImportAnimationFrame> {
+    let file = file_manager.get_file(&path)?;
+    let animation_frame = file.sample_animation(&animation, frame);
+
+    if animation_frame.is_none() {
+        log(format!("Animation \"{}\" Was Not Found In {}!", animation, path), LogLevel::Error);
+    }
+
+    animation_frame
+}
+
+#[tauri::command]
+fn prioritize_file(path: String, file_manager: tauri::State<FileManager>) {
+    file_manager.prioritize_file(path);
+}
+
+#[tauri::command]
+fn list_files(file_manager: tauri::State<FileManager>) -> Vec<FileStatus> {
+    file_manager.file_statuses()
+}
+
+/// Imports the file again and sends it to the window like a file that changed on disk, so every entry using it is updated.
+#[tauri::command(async)]
+fn reload_file(path: String, app: AppHandle, file_manager: tauri::State<FileManager>) -> bool {
+    match file_manager.reload_file(path.clone()) {
+        Ok(file) => {
+            let _ = app.emit("source-wrench-files-reloaded", vec![ReloadedFile { path, file }]);
+            true
+        }
+        Err(error) => {
+            log(format!("Fail To Reload {}: {}!", path, error), LogLevel::Error);
+            false
+        }
+    }
+}
+
+#[tauri::command]
+fn write_log_file(path: String) -> bool {
+    match export_log(Path::new(&path)) {
+        Ok(()) => true,
+        Err(error) => {
+            log(format!("Fail To Export Log: {}!", error), LogLevel::Error);
+            false
+        }
+    }
+}
+
+/// Returns the token programs authenticate with, none when the server was stopped or could not start.
+#[tauri::command]
+fn set_job_server(enabled: bool, port: u16, app: AppHandle, job_server: tauri::State<JobServer>) -> Option<String> {
+    if !enabled {
+        job_server.stop();
+        return None;
+    }
+
+    match job_server.start(app, port) {
+        Ok(token) => {
+            log(format!("Accepting Compile Jobs On Port {}!", port), LogLevel::Info);
+            Some(token)
+        }
+        Err(error) => {
+            log(format!("Fail To Start Job Server: {}!", error), LogLevel::Error);
+            None
+        }
+    }
+}
+
+#[tauri::command]
+fn set_file_watching(enabled: bool, file_manager: tauri::State<FileManager>) {
+    file_manager.set_watching(enabled);
+}
+
+/// How often loaded files are checked for changes, and how long a file has to be unchanged before it is imported again.
+const FILE_WATCH_INTERVAL: Duration = Duration::from_millis(500);
+
+#[derive(Clone, Serialize)]
+struct ReloadedFile {
+    path: String,
+    file: Arc<ImportFileData>,
+}
+
+/// Checks the loaded files for changes while watching is enabled and sends every file that was imported again to the window.
+fn watch_loaded_files(app: AppHandle) {
+    loop {
+        thread::sleep(FILE_WATCH_INTERVAL);
+
+        let file_manager = app.state::<FileManager>();
+        if !file_manager.is_watching() {
+            continue;
+        }
+
+        let reloaded_files = file_manager
+            .reload_changed_files(FILE_WATCH_INTERVAL)
+            .into_iter()
+            .map(|(path, file)| ReloadedFile {
+                path: path.to_string_lossy().to_string(),
+                file,
+            })
+            .collect::<Vec<_>>();
+
+        if reloaded_files.is_empty() {
+            continue;
+        }
+
+        log(format!("Reloaded {} changed files!", reloaded_files.len()), LogLevel::Info);
+        let _ = app.emit("source-wrench-files-reloaded", reloaded_files);
+    }
+}
+
 fn main() {
     tauri::Builder::default()
         .plugin(tauri_plugin_dialog::init())
         .manage(FileManager::default())
+        .manage(PluginManager::default())
+        .manage(JobServer::default())
         .setup(|app| {
             let window = app.get_webview_window("main");
             LOGGER.set(window.expect("Window Was Not Created!")).expect("LOGGER Was Already In Use?");
+            // Studios add their own checks by placing scripts in the plugins folder of the app config.
+            if let Ok(config_directory) = app.path().app_config_dir() {
+                if let Err(error) = load_script_plugins(&app.state::<PluginManager>(), &config_directory.join("plugins")) {
+                    log(format!("Fail To Load Plugins: {}!", error), LogLevel::Error);
+                }
+            }
+            let app_handle = app.app_handle().clone();
+            thread::spawn(move || watch_loaded_files(app_handle));
             Ok(())
         })
-        .invoke_handler(tauri::generate_handler![compile_model, load_file, unload_file])
+        .invoke_handler(tauri::generate_handler![
+            compile_model,
+            cancel_compile,
+            verify_model,
+            decompile_model,
+            dump_model,
+            save_project_file,
+            open_project_file,
+            relocate_files,
+            inspect_skeleton,
+            find_bone_conflicts,
+            find_missing_materials,
+            detect_game,
+            load_file,
+            unload_file,
+            release_file,
+            release_all_files,
+            list_files,
+            set_tolerant_parsing,
+            prioritize_file,
+            inspect_animation_frame,
+            reload_file,
+            set_file_watching,
+            set_job_server,
+            write_log_file
+        ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
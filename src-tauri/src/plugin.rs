@@ -0,0 +1,309 @@
+use std::{
+    fs,
+    io::{Error as IoError, ErrorKind, Read, Write},
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    sync::{Arc, RwLock},
+    thread,
+    time::{Duration, Instant},
+};
+
+use serde::Serialize;
+use serde_json::Error as JsonError;
+use thiserror::Error as ThisError;
+
+use crate::{
+    input::ImputedCompilationData,
+    process::ProcessedData,
+    utilities::{
+        logging::{log, LogLevel},
+        progress::is_cancelled,
+    },
+};
+
+#[derive(Debug, ThisError)]
+pub enum PluginError {
+    #[error("Plugin {0} Failed: {1}")]
+    Failed(String, String),
+    #[error("{0}")]
+    ScriptRejected(String),
+    #[error("Failed To Run Script: {0}")]
+    FailedScriptRun(#[from] IoError),
+    #[error("Script Returned Invalid Project: {0}")]
+    InvalidScriptProject(#[from] JsonError),
+    #[error("Script Did Not Finish Within {0} Seconds")]
+    ScriptTimedOut(u64),
+    #[error("Script Was Cancelled")]
+    ScriptCancelled,
+}
+
+/// How long a script can run for a stage before it is stopped.
+const SCRIPT_TIMEOUT: Duration = Duration::from_secs(60);
+/// How often a running script is checked for having exited, timed out or been cancelled.
+const SCRIPT_POLL_INTERVAL: Duration = Duration::from_millis(10);
+/// The extensions of the files Windows can run, as it has no executable permission.
+#[cfg(not(unix))]
+const SCRIPT_EXTENSIONS: [&str; 4] = ["exe", "bat", "cmd", "com"];
+
+/// Hooks into the stages of a compile, every hook returns the reason the compile has to stop as an error.
+pub trait CompilePlugin: Send + Sync {
+    fn name(&self) -> &str;
+
+    /// Called before the model is processed, the project can be changed to apply naming rules.
+    fn pre_process(&self, _input: &mut ImputedCompilationData) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// Called once the model is processed, to validate the processed data.
+    fn post_process(&self, _input: &ImputedCompilationData, _processed: &ProcessedData) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// Called right before the files are written, the processed data can still be changed.
+    fn pre_write(&self, _input: &ImputedCompilationData, _processed: &mut ProcessedData) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+/// The plugins every compile runs, in the order they were registered.
+#[derive(Default)]
+pub struct PluginManager {
+    plugins: RwLock<Vec<Arc<dyn CompilePlugin>>>,
+}
+
+impl PluginManager {
+    pub fn register(&self, plugin: Arc<dyn CompilePlugin>) {
+        log(format!("Loaded Plugin {}!", plugin.name()), LogLevel::Verbose);
+        self.plugins.write().unwrap().push(plugin);
+    }
+
+    pub fn pre_process(&self, input: &mut ImputedCompilationData) -> Result<(), PluginError> {
+        for plugin in self.plugins() {
+            plugin
+                .pre_process(input)
+                .map_err(|error| PluginError::Failed(plugin.name().to_string(), error))?;
+        }
+
+        Ok(())
+    }
+
+    pub fn post_process(&self, input: &ImputedCompilationData, processed: &ProcessedData) -> Result<(), PluginError> {
+        for plugin in self.plugins() {
+            plugin
+                .post_process(input, processed)
+                .map_err(|error| PluginError::Failed(plugin.name().to_string(), error))?;
+        }
+
+        Ok(())
+    }
+
+    pub fn pre_write(&self, input: &ImputedCompilationData, processed: &mut ProcessedData) -> Result<(), PluginError> {
+        for plugin in self.plugins() {
+            plugin
+                .pre_write(input, processed)
+                .map_err(|error| PluginError::Failed(plugin.name().to_string(), error))?;
+        }
+
+        Ok(())
+    }
+
+    // The plugins are copied out so a plugin registered during a compile does not wait on the compile.
+    fn plugins(&self) -> Vec<Arc<dyn CompilePlugin>> {
+        self.plugins.read().unwrap().clone()
+    }
+}
+
+/// Registers every file in the plugins folder that can be run as a script plugin.
+pub fn load_script_plugins(plugin_manager: &PluginManager, plugins_directory: &Path) -> Result<(), PluginError> {
+    if !plugins_directory.is_dir() {
+        return Ok(());
+    }
+
+    let mut script_paths = fs::read_dir(plugins_directory)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.is_file())
+        .collect::<Vec<_>>();
+    script_paths.sort();
+
+    for script_path in script_paths {
+        // Notes and files the system leaves in the folder would fail every compile if they were run.
+        if !is_script(&script_path) {
+            log(
+                format!("Skipping {} In The Plugins Folder As It Can Not Be Run!", script_path.display()),
+                LogLevel::Verbose,
+            );
+            continue;
+        }
+
+        plugin_manager.register(Arc::new(ScriptPlugin::new(script_path)));
+    }
+
+    Ok(())
+}
+
+#[cfg(unix)]
+fn is_script(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    fs::metadata(path).is_ok_and(|metadata| metadata.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_script(path: &Path) -> bool {
+    path.extension().is_some_and(|extension| {
+        SCRIPT_EXTENSIONS
+            .iter()
+            .any(|script_extension| extension.eq_ignore_ascii_case(script_extension))
+    })
+}
+
+/// The parts of the processed model that are sent to scripts, scripts can only check the processed model.
+#[derive(Debug, Serialize)]
+struct ScriptModelSummary<'a> {
+    model_name: &'a str,
+    bones: Vec<&'a str>,
+    materials: Vec<&'a str>,
+    material_paths: &'a [String],
+    body_parts: Vec<(&'a str, Vec<&'a str>)>,
+    animations: Vec<&'a str>,
+    sequences: Vec<&'a str>,
+}
+
+impl<'a> ScriptModelSummary<'a> {
+    fn new(input: &'a ImputedCompilationData, processed: &'a ProcessedData) -> Self {
+        Self {
+            model_name: &input.model_name,
+            bones: processed.bone_data.processed_bones.keys().map(String::as_str).collect(),
+            materials: processed.model_data.materials.iter().map(String::as_str).collect(),
+            material_paths: &processed.model_data.material_paths,
+            body_parts: processed
+                .model_data
+                .body_parts
+                .iter()
+                .map(|body_part| (body_part.name.as_str(), body_part.models.iter().map(|model| model.name.as_str()).collect()))
+                .collect(),
+            animations: processed
+                .animation_data
+                .processed_animations
+                .iter()
+                .map(|animation| animation.name.as_str())
+                .collect(),
+            sequences: processed.sequence_data.iter().map(|sequence| sequence.name.as_str()).collect(),
+        }
+    }
+}
+
+/// A program that is run for each stage with the stage as its argument and the data as JSON on its input.
+/// The compile stops when it exits with an error, with what it wrote to its error output as the reason.
+/// In the pre-process stage it gets the project and can write a changed project to its output.
+pub struct ScriptPlugin {
+    name: String,
+    path: PathBuf,
+}
+
+impl ScriptPlugin {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            name: path.file_name().unwrap_or_default().to_string_lossy().to_string(),
+            path,
+        }
+    }
+
+    fn run(&self, stage: &str, data: &[u8]) -> Result<Vec<u8>, PluginError> {
+        let mut child = Command::new(&self.path)
+            .arg(stage)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+
+        // The input is written and the outputs are read on their own threads, as the script can fill its output before it has read all of its input.
+        // The input is closed once written so the script knows all of the data was sent, scripts that do not read it can exit before.
+        let mut input = child.stdin.take().expect("Script Input Was Not Piped!");
+        let input_data = data.to_vec();
+        let input_writer = thread::spawn(move || match input.write_all(&input_data) {
+            Err(error) if error.kind() != ErrorKind::BrokenPipe => Err(error),
+            _ => Ok(()),
+        });
+        let output_reader = read_to_end_on_thread(child.stdout.take().expect("Script Output Was Not Piped!"));
+        let error_reader = read_to_end_on_thread(child.stderr.take().expect("Script Error Output Was Not Piped!"));
+
+        // The threads are left behind when the script is stopped, as programs started by the script can keep its outputs open.
+        let start_time = Instant::now();
+        let status = loop {
+            if let Some(status) = child.try_wait()? {
+                break status;
+            }
+
+            if is_cancelled() {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(PluginError::ScriptCancelled);
+            }
+
+            if start_time.elapsed() > SCRIPT_TIMEOUT {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(PluginError::ScriptTimedOut(SCRIPT_TIMEOUT.as_secs()));
+            }
+
+            thread::sleep(SCRIPT_POLL_INTERVAL);
+        };
+
+        input_writer.join().expect("Script Input Writer Panicked!")?;
+        let output = output_reader.join().expect("Script Output Reader Panicked!")?;
+        let error_output = error_reader.join().expect("Script Error Output Reader Panicked!")?;
+
+        let message = String::from_utf8_lossy(&error_output).trim().to_string();
+        if !status.success() {
+            let reason = if message.is_empty() { format!("Exited With {}", status) } else { message };
+            return Err(PluginError::ScriptRejected(reason));
+        }
+
+        if !message.is_empty() {
+            log(format!("Plugin {}: {}", self.name, message), LogLevel::Warn);
+        }
+
+        Ok(output)
+    }
+}
+
+fn read_to_end_on_thread(mut reader: impl Read + Send + 'static) -> thread::JoinHandle<Result<Vec<u8>, IoError>> {
+    thread::spawn(move || {
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data)?;
+        Ok(data)
+    })
+}
+
+impl CompilePlugin for ScriptPlugin {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn pre_process(&self, input: &mut ImputedCompilationData) -> Result<(), String> {
+        let output = serde_json::to_vec(input)
+            .map_err(PluginError::from)
+            .and_then(|data| self.run("pre-process", &data))
+            .map_err(|error| error.to_string())?;
+
+        // Scripts that only check the project do not have to write it back.
+        if !output.iter().all(u8::is_ascii_whitespace) {
+            *input = serde_json::from_slice(&output).map_err(|error| PluginError::from(error).to_string())?;
+        }
+
+        Ok(())
+    }
+
+    fn post_process(&self, input: &ImputedCompilationData, processed: &ProcessedData) -> Result<(), String> {
+        let summary = serde_json::to_vec(&ScriptModelSummary::new(input, processed)).map_err(|error| error.to_string())?;
+        self.run("post-process", &summary).map_err(|error| error.to_string())?;
+        Ok(())
+    }
+
+    fn pre_write(&self, input: &ImputedCompilationData, processed: &mut ProcessedData) -> Result<(), String> {
+        let summary = serde_json::to_vec(&ScriptModelSummary::new(input, processed)).map_err(|error| error.to_string())?;
+        self.run("pre-write", &summary).map_err(|error| error.to_string())?;
+        Ok(())
+    }
+}
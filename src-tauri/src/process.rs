@@ -1,31 +1,46 @@
+use std::{
+    collections::{hash_map::DefaultHasher, BTreeMap},
+    hash::{Hash, Hasher},
+    sync::{Arc, Mutex},
+};
+
 use bitflags::bitflags;
 use indexmap::{IndexMap, IndexSet};
+use serde::Serialize;
 use tauri::State;
 use thiserror::Error as ThisError;
 
 use crate::{
-    import::FileManager,
-    input::ImputedCompilationData,
+    import::{FileManager, ImportFileData},
+    input::{ImputedBoundingBox, ImputedCompilationData},
     utilities::{
         logging::{log, LogLevel},
-        mathematics::{Angles, BoundingBox, Matrix4, Vector2, Vector3, Vector4},
+        mathematics::{Angles, BoundingBox, Matrix4, Quaternion, Vector2, Vector3, Vector4},
+        progress::{is_cancelled, stage},
     },
 };
 
 mod animation;
 mod bones;
 mod mesh;
+mod physics;
 
-use animation::{process_animations, process_sequences, ProcessingAnimationError};
+use animation::{
+    calculate_sequence_bounding_boxes, process_animations, process_pose_parameters, process_sequences, process_transition_nodes, ProcessingAnimationError,
+};
 use bones::{process_bones, ProcessingBoneError};
-use mesh::{process_meshes, ProcessingMeshError};
+use mesh::{preview_materials as preview_mesh_materials, process_meshes, ProcessingMeshError};
+use physics::{process_physics, ProcessingPhysicsError};
 
 #[derive(Debug, Default)]
 pub struct ProcessedData {
     pub bone_data: ProcessedBoneData,
     pub animation_data: ProcessedAnimationData,
     pub sequence_data: Vec<ProcessedSequence>,
+    pub pose_parameters: Vec<ProcessedPoseParameter>,
+    pub transition_nodes: Vec<ProcessedTransitionNode>,
     pub model_data: ProcessedModelData,
+    pub physics_data: Option<ProcessedPhysicsData>,
 }
 
 #[derive(Debug, Default)]
@@ -33,11 +48,56 @@ pub struct ProcessedBoneData {
     pub processed_bones: IndexMap<String, ProcessedBone>,
     pub remapped_bones: IndexMap<String, Vec<ProcessedRemappedBone>>,
     pub sorted_bones_by_name: Vec<u8>,
+    pub attachments: Vec<ProcessedAttachment>,
+    pub illumination_attachment: Option<usize>,
+    pub mouths: Vec<ProcessedMouth>,
+    pub bone_flex_drivers: Vec<ProcessedBoneFlexDriver>,
+    pub inverse_kinematic_chains: Vec<ProcessedInverseKinematicChain>,
 }
 
 #[derive(Debug, Default)]
 pub struct ProcessedRemappedBone {
-    pub index: usize,
+    /// The index into the bone table, or none if the bone was removed.
+    pub index: Option<usize>,
+}
+
+#[derive(Debug, Default)]
+pub struct ProcessedAttachment {
+    pub name: String,
+    pub bone: usize,
+    pub position: Vector3,
+    pub rotation: Angles,
+}
+
+#[derive(Debug, Default)]
+pub struct ProcessedMouth {
+    pub bone: usize,
+    pub forward: Vector3,
+    pub flex: String,
+    pub flex_controller: String,
+}
+
+#[derive(Debug, Default)]
+pub struct ProcessedBoneFlexDriver {
+    pub bone: usize,
+    pub controls: Vec<ProcessedBoneFlexControl>,
+}
+
+#[derive(Debug, Default)]
+pub struct ProcessedBoneFlexControl {
+    pub component: usize,
+    pub flex_controller: String,
+    pub minimum: f64,
+    pub maximum: f64,
+}
+
+#[derive(Debug)]
+pub struct ProcessedInverseKinematicChain {
+    pub name: String,
+    /// The bones of the chain from the root to the end bone.
+    pub bones: [usize; 3],
+    /// The direction the middle joint bends towards in the local space of the root bone.
+    pub knee_direction: Vector3,
 }
 
 #[derive(Debug, Default)]
@@ -46,13 +106,78 @@ pub struct ProcessedBone {
     pub position: Vector3,
     pub rotation: Angles,
     pub flags: ProcessedBoneFlags,
+    pub procedural: Option<ProcessedProcedural>,
     pub pose: Matrix4,
+    /// The source files that have this bone with a different bind pose than the one used.
+    pub conflicting_sources: Vec<String>,
+    /// The local bind pose the bone was imported with.
+    pub source_pose: Matrix4,
+    /// The transforms from the imported bind pose to the compiled one, or none if the bone was left unchanged.
+    pub source_transform: Option<ProcessedSourceTransform>,
+}
+
+#[derive(Debug)]
+pub struct ProcessedSourceTransform {
+    /// Applied in the parent space of the bone.
+    pub pre: Matrix4,
+    /// Applied in the local space of the bone.
+    pub post: Matrix4,
+}
+
+#[derive(Debug)]
+pub enum ProcessedProcedural {
+    AxisInterpolation {
+        control: usize,
+        axis: usize,
+        positions: Box<[Vector3; 6]>,
+        rotations: Box<[Quaternion; 6]>,
+    },
+    QuaternionInterpolation {
+        control: usize,
+        triggers: Vec<ProcessedQuaternionTrigger>,
+    },
+    Jiggle(ProcessedJiggle),
+}
+
+/// A flexible jiggle bone that bends around its yaw and pitch like a spring.
+#[derive(Debug)]
+pub struct ProcessedJiggle {
+    /// The distance from the bone to its tip.
+    pub length: f64,
+    pub tip_mass: f64,
+    pub stiffness: f64,
+    pub damping: f64,
+    /// The angle in radians the bone can bend away from its rest direction.
+    pub angle_limit: Option<f64>,
+}
+
+#[derive(Debug)]
+pub struct ProcessedQuaternionTrigger {
+    pub inverse_tolerance: f64,
+    pub trigger: Quaternion,
+    pub position: Vector3,
+    pub rotation: Quaternion,
 }
 
 bitflags! {
-    #[derive(Debug, Default)]
+    #[derive(Debug, Default, Clone, Copy)]
     pub struct ProcessedBoneFlags: i32 {
-        const USED_BY_VERTEX = 0x00000400;
+        const ALWAYS_PROCEDURAL   = 0x00000004;
+        const USED_BY_ATTACHMENT  = 0x00000200;
+        /// Used by a vertex of the first level of detail, the other levels of detail follow in the next bits.
+        const USED_BY_VERTEX      = 0x00000400;
+        const USED_BY_VERTEX_MASK = 0x0003FC00;
+        const USED_BY_BONE_MERGE  = 0x00040000;
+        const USED_BY_ANYTHING    = 0x0007FF00;
+    }
+}
+
+impl ProcessedBoneFlags {
+    pub const MAX_LEVELS_OF_DETAIL: usize = 8;
+
+    pub fn used_by_vertex_at(level_of_detail: usize) -> Self {
+        debug_assert!(level_of_detail < Self::MAX_LEVELS_OF_DETAIL, "Level Of Detail Has No Flag!");
+        Self::from_bits_retain(Self::USED_BY_VERTEX.bits() << level_of_detail)
     }
 }
 
@@ -66,7 +191,49 @@ pub struct ProcessedAnimationData {
 pub struct ProcessedAnimation {
     pub name: String,
     pub frame_count: usize,
+    pub fps: f64,
+    pub is_delta: bool,
+    /// The frames in each section, zero when the animation is not split into sections.
+    pub frames_per_section: usize,
     pub sections: Vec<Vec<ProcessedAnimatedBoneData>>,
+    pub movements: Vec<ProcessedMovement>,
+    pub inverse_kinematic_rules: Vec<ProcessedInverseKinematicRule>,
+}
+
+/// A piece of the motion extracted from the root bones, the velocity changes linearly over the piece.
+#[derive(Debug)]
+pub struct ProcessedMovement {
+    pub end_frame: usize,
+    pub axes: [bool; 3],
+    pub start_velocity: f64,
+    pub end_velocity: f64,
+    pub direction: Vector3,
+    /// The position of the entity at the end frame relative to the first frame.
+    pub position: Vector3,
+}
+
+#[derive(Debug)]
+pub struct ProcessedInverseKinematicRule {
+    pub chain: usize,
+    pub rule: ProcessedInverseKinematicRuleType,
+    pub height: f64,
+    pub radius: f64,
+    /// The cycles the rule starts blending in, is fully applied, starts blending out and ends.
+    pub start: f64,
+    pub peak: f64,
+    pub tail: f64,
+    pub end: f64,
+    pub contact: f64,
+    pub start_frame: usize,
+    /// The target transform of the end bone for every frame from the start frame.
+    pub targets: Vec<(Vector3, Angles)>,
+}
+
+#[derive(Debug)]
+pub enum ProcessedInverseKinematicRuleType {
+    Touch { bone: usize },
+    Footstep,
+    Release,
 }
 
 #[derive(Debug, Default)]
@@ -80,35 +247,158 @@ pub struct ProcessedAnimatedBoneData {
 pub struct ProcessedSequence {
     pub name: String,
     pub animations: Vec<Vec<i16>>,
+    pub is_delta: bool,
+    pub weight_list: Vec<f64>,
+    pub auto_layers: Vec<ProcessedAutoLayer>,
+    /// The pose parameters that pick the column and the row of the blend grid.
+    pub parameters: [Option<ProcessedSequenceParameter>; 2],
+    pub bounding_box: BoundingBox,
+    pub transition: Option<ProcessedSequenceTransition>,
+    pub inverse_kinematic_rule_count: usize,
+    pub inverse_kinematic_locks: Vec<ProcessedInverseKinematicLock>,
+    pub fade_in_time: f64,
+    pub fade_out_time: f64,
+    pub entry_phase: f64,
+    pub exit_phase: f64,
+    pub activity_modifiers: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct ProcessedInverseKinematicLock {
+    pub chain: usize,
+    pub position_weight: f64,
+    pub local_rotation_weight: f64,
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ProcessedSequenceTransition {
+    pub entry_node: usize,
+    pub exit_node: usize,
+    pub reverse: bool,
+}
+
+#[derive(Debug, Default)]
+pub struct ProcessedTransitionNode {
+    pub name: String,
+    /// The number of the node to move to next to reach each node, zero if the node can not be reached.
+    pub next_nodes: Vec<u8>,
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct ProcessedSequenceParameter {
+    pub pose_parameter: usize,
+    pub start: f64,
+    pub end: f64,
+    /// The value of the pose parameter at each column or row of the blend grid.
+    pub keys: Vec<f64>,
+}
+
+#[derive(Debug, Default)]
+pub struct ProcessedPoseParameter {
+    pub name: String,
+    pub start: f64,
+    pub end: f64,
 }
 
 #[derive(Debug, Default)]
+pub struct ProcessedAutoLayer {
+    pub sequence: i16,
+    pub start: f64,
+    pub peak: f64,
+    pub tail: f64,
+    pub end: f64,
+    pub spline: bool,
+    pub cross_fade: bool,
+    pub no_blend: bool,
+}
+
+#[derive(Clone, Debug, Default)]
 pub struct ProcessedModelData {
     pub body_parts: Vec<ProcessedBodyPart>,
     pub bounding_box: BoundingBox,
+    pub clipping_box: BoundingBox,
     pub materials: IndexSet<String>,
+    pub material_paths: Vec<String>,
+    pub limits: ProcessedMeshLimits,
+    pub mesh_splits: Vec<ProcessedMeshSplit>,
+    /// The body value that shows the default model of every body part, to be set on the entity that uses the model.
+    pub default_body: usize,
+    /// The names of the flexes of every model, models with flexes of the same name share the flex.
+    pub flexes: IndexSet<String>,
 }
 
-#[derive(Debug, Default)]
+/// A material of a model that did not fit into a single mesh and was split by the vertex or hardware bone limit.
+#[derive(Clone, Debug, Default)]
+pub struct ProcessedMeshSplit {
+    pub model: String,
+    pub material: String,
+    pub mesh_count: usize,
+    pub vertex_count: usize,
+    pub triangle_count: usize,
+    pub vertex_limit_splits: usize,
+    pub hardware_bone_limit_splits: usize,
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ProcessedMeshLimits {
+    pub hardware_bones_per_strip: usize,
+    pub weights_per_vertex: usize,
+    pub vertex_cache_size: usize,
+    /// The allowed increase of vertex cache misses when sorting for overdraw, overdraw is not optimized without one.
+    pub overdraw_threshold: Option<f64>,
+    /// Generates the tangents with MikkTSpace, which matches the tangents most bakers make normal maps with.
+    pub mikktspace_tangents: bool,
+}
+
+#[derive(Clone, Debug, Default)]
 pub struct ProcessedBodyPart {
     pub name: String,
     pub models: Vec<ProcessedModel>,
+    pub default_model: usize,
+    /// The model the bounding boxes are calculated from, every model is used when none.
+    pub preview_model: Option<usize>,
 }
 
-#[derive(Debug, Default)]
+impl ProcessedBodyPart {
+    pub fn preview_models(&self) -> impl Iterator<Item = &ProcessedModel> {
+        self.models
+            .iter()
+            .enumerate()
+            .filter(|(model_index, _)| self.preview_model.is_none_or(|preview_model| preview_model == *model_index))
+            .map(|(_, model)| model)
+    }
+}
+
+#[derive(Clone, Debug, Default)]
 pub struct ProcessedModel {
     pub name: String,
     pub meshes: Vec<ProcessedMesh>,
 }
 
-#[derive(Debug, Default)]
+#[derive(Clone, Debug, Default)]
 pub struct ProcessedMesh {
     pub material: i32,
     pub vertex_data: Vec<ProcessedVertex>,
     pub strip_groups: Vec<ProcessedStripGroup>,
+    pub flexes: Vec<ProcessedFlex>,
 }
 
-#[derive(Debug, Default)]
+/// The vertices of a mesh that a flex moves, the flex is an index into the flexes of the model data.
+#[derive(Clone, Debug, Default)]
+pub struct ProcessedFlex {
+    pub flex: usize,
+    pub vertices: Vec<ProcessedFlexVertex>,
+}
+
+/// How far a vertex of the mesh moves when the flex is fully applied.
+#[derive(Clone, Debug, Default)]
+pub struct ProcessedFlexVertex {
+    pub vertex: usize,
+    pub position: Vector3,
+    pub normal: Vector3,
+}
+
+#[derive(Clone, Debug, Default)]
 pub struct ProcessedVertex {
     pub weights: [f32; 3],
     pub bones: [u8; 3],
@@ -119,7 +409,7 @@ pub struct ProcessedVertex {
     pub tangent: Vector4,
 }
 
-#[derive(Debug, Default)]
+#[derive(Clone, Debug, Default)]
 pub struct ProcessedStripGroup {
     pub vertices: Vec<ProcessedMeshVertex>,
     pub indices: Vec<u16>,
@@ -127,14 +417,14 @@ pub struct ProcessedStripGroup {
     pub is_flexed: bool,
 }
 
-#[derive(Debug, Default)]
+#[derive(Clone, Debug, Default)]
 pub struct ProcessedMeshVertex {
     pub bone_count: u8,
     pub vertex_index: u16,
     pub bones: [u8; 3],
 }
 
-#[derive(Debug, Default)]
+#[derive(Clone, Debug, Default)]
 pub struct ProcessedStrip {
     pub indices_count: i32,
     pub indices_offset: i32,
@@ -144,12 +434,52 @@ pub struct ProcessedStrip {
     pub hardware_bones: Vec<ProcessedHardwareBone>,
 }
 
-#[derive(Debug, Default)]
+#[derive(Clone, Debug, Default)]
 pub struct ProcessedHardwareBone {
     pub hardware_bone: i32,
     pub bone_table_bone: i32,
 }
 
+#[derive(Debug, Default)]
+pub struct ProcessedPhysicsData {
+    pub solids: Vec<ProcessedPhysicsSolid>,
+    pub constraints: Vec<ProcessedRagdollConstraint>,
+    pub total_mass: f64,
+}
+
+#[derive(Debug)]
+pub struct ProcessedPhysicsSolid {
+    pub bone: usize,
+    pub name: String,
+    pub mass: f64,
+    /// The convex pieces of the solid in the space of its bone.
+    pub hulls: Vec<ProcessedConvexHull>,
+}
+
+/// A closed convex shape, the triangles wind counterclockwise seen from outside.
+#[derive(Debug, Default)]
+pub struct ProcessedConvexHull {
+    pub points: Vec<Vector3>,
+    pub triangles: Vec<[usize; 3]>,
+}
+
+/// A joint between the solid of a bone and the solid of its closest parent bone.
+#[derive(Debug)]
+pub struct ProcessedRagdollConstraint {
+    pub parent: usize,
+    pub child: usize,
+    /// The limits around the X, Y and Z axis of the child bone.
+    pub limits: [ProcessedJointLimit; 3],
+}
+
+/// Angles are in degrees.
+#[derive(Debug, Clone, Copy)]
+pub struct ProcessedJointLimit {
+    pub minimum: f64,
+    pub maximum: f64,
+    pub friction: f64,
+}
+
 #[derive(Debug, ThisError)]
 pub enum ProcessingDataError {
     #[error("Model Has No Bones")]
@@ -158,57 +488,363 @@ pub enum ProcessingDataError {
     TooManySequences,
     #[error("Model Has No Sequences")]
     NoSequences,
+    #[error("Compile Was Cancelled")]
+    Cancelled,
+    #[error("Bounding Box Minimum Is Larger Than Its Maximum")]
+    InvalidBoundingBox,
+    #[error("Clipping Box Minimum Is Larger Than Its Maximum")]
+    InvalidClippingBox,
+    #[error("Model Flags {0} And {1} Can Not Both Be Enabled")]
+    ConflictingModelFlags(&'static str, &'static str),
+    #[error("Max Eye Deflection Of {0} Degrees Must Be Above 0 And Below 90 Degrees")]
+    InvalidMaxEyeDeflection(f64),
     #[error("Failed To Process Bone Data: {0}")]
     ProcessingBoneError(#[from] ProcessingBoneError),
     #[error("Failed To Process Animation Data: {0}")]
     ProcessingAnimationError(#[from] ProcessingAnimationError),
     #[error("Failed To Process Mesh Data: {0}")]
     ProcessingMeshError(#[from] ProcessingMeshError),
+    #[error("Failed To Process Physics Data: {0}")]
+    ProcessingPhysicsError(#[from] ProcessingPhysicsError),
 }
 
+/// The stages of a compile reported to the user, processing is every stage except for the last which writes the files.
+pub const COMPILE_STAGES: [&str; 6] = [
+    "Processing Bones",
+    "Processing Animations",
+    "Processing Sequences",
+    "Processing Meshes",
+    "Processing Physics",
+    "Writing Files",
+];
+
 pub const MAX_HARDWARE_BONES_PER_STRIP: usize = 53;
+/// Vertices and animations refer to bones with a byte.
+pub const MAX_BONES: usize = u8::MAX as usize + 1;
+/// Skin families refer to materials with a signed short.
+pub const MAX_MATERIALS: usize = i16::MAX as usize + 1;
+/// The vertex file stores a fixed amount of weights for every vertex.
+pub const MAX_WEIGHTS_PER_VERTEX: usize = 3;
+/// The largest vertex cache the vertex cache optimizer has scores for.
 pub const VERTEX_CACHE_SIZE: usize = 16;
 
 /// The tolerance for floating point numbers until they are considered equal.
 pub const FLOAT_TOLERANCE: f64 = f32::EPSILON as f64;
 
+/// A bone of the merged skeleton, used to show the skeleton before compiling.
+#[derive(Debug, Serialize)]
+pub struct SkeletonPreviewBone {
+    pub name: String,
+    pub parent: Option<usize>,
+    pub used_by_vertex: bool,
+    pub used_by_attachment: bool,
+    pub conflicting_sources: Vec<String>,
+}
+
+/// Flags the bones by what uses them now that the meshes are processed, the engine only sets up the bones used at the drawn level of detail.
+/// The flags from merging the skeletons are replaced, they count vertices of every part even when the part is not used.
+fn calculate_bone_usage(bone_data: &mut ProcessedBoneData, model_data: &ProcessedModelData) {
+    // Bone merging is marked from the input while merging the skeletons, so it is the only usage kept.
+    for bone in bone_data.processed_bones.values_mut() {
+        bone.flags
+            .remove(ProcessedBoneFlags::USED_BY_ANYTHING.difference(ProcessedBoneFlags::USED_BY_BONE_MERGE));
+    }
+
+    // Only the first level of detail is generated, so it is the only level with meshes.
+    let levels_of_detail = [model_data
+        .body_parts
+        .iter()
+        .flat_map(|body_part| &body_part.models)
+        .flat_map(|model| &model.meshes)];
+    for (level_of_detail, meshes) in levels_of_detail.into_iter().enumerate() {
+        let vertex_flag = ProcessedBoneFlags::used_by_vertex_at(level_of_detail);
+        for vertex in meshes.flat_map(|mesh| &mesh.vertex_data) {
+            for &bone in vertex.bones.iter().take(vertex.bone_count as usize) {
+                bone_data.processed_bones[bone as usize].flags.insert(vertex_flag);
+            }
+        }
+    }
+
+    for attachment in &bone_data.attachments {
+        bone_data.processed_bones[attachment.bone].flags.insert(ProcessedBoneFlags::USED_BY_ATTACHMENT);
+    }
+
+    // A bone is set up from its parent, so the parents of a used bone are used for the same things. Parents always come before their children.
+    for bone_index in (0..bone_data.processed_bones.len()).rev() {
+        let bone = &bone_data.processed_bones[bone_index];
+        if let Some(parent) = bone.parent {
+            let used_flags = bone.flags.intersection(ProcessedBoneFlags::USED_BY_ANYTHING);
+            bone_data.processed_bones[parent].flags.insert(used_flags);
+        }
+    }
+
+    let count_bones = |flags: ProcessedBoneFlags| bone_data.processed_bones.values().filter(|bone| bone.flags.intersects(flags)).count();
+    let used_bone_count = count_bones(ProcessedBoneFlags::USED_BY_ANYTHING);
+    debug_assert!(
+        bone_data
+            .processed_bones
+            .values()
+            .all(|bone| bone.parent.is_none_or(|parent| bone_data.processed_bones[parent]
+                .flags
+                .contains(bone.flags.intersection(ProcessedBoneFlags::USED_BY_ANYTHING)))),
+        "Used Bone Has A Parent That Is Not Used!"
+    );
+
+    log(
+        format!(
+            "{} Of {} Bones Are Used, {} By Vertices, {} By Attachments And {} By Bone Merging",
+            used_bone_count,
+            bone_data.processed_bones.len(),
+            count_bones(ProcessedBoneFlags::USED_BY_VERTEX_MASK),
+            count_bones(ProcessedBoneFlags::USED_BY_ATTACHMENT),
+            count_bones(ProcessedBoneFlags::USED_BY_BONE_MERGE)
+        ),
+        LogLevel::Verbose,
+    );
+
+    let unused_bones = bone_data
+        .processed_bones
+        .iter()
+        .filter(|(_, bone)| !bone.flags.intersects(ProcessedBoneFlags::USED_BY_ANYTHING))
+        .map(|(name, _)| name.as_str())
+        .collect::<Vec<_>>();
+    if !unused_bones.is_empty() {
+        log(format!("Bones Not Used By Anything: {}", unused_bones.join(", ")), LogLevel::Verbose);
+    }
+}
+
+/// Lists the materials of the model with the material paths they are searched in, used to check the materials before compiling.
+pub fn preview_materials(input: &ImputedCompilationData, file_manager: &State<FileManager>) -> Result<(IndexSet<String>, Vec<String>), ProcessingDataError> {
+    Ok(preview_mesh_materials(input, file_manager)?)
+}
+
+/// Merges the skeletons of every source file without processing the rest of the model.
+pub fn preview_skeleton(input: &ImputedCompilationData, file_manager: &State<FileManager>) -> Result<Vec<SkeletonPreviewBone>, ProcessingDataError> {
+    let processed_bone_data = process_bones(input, file_manager)?;
+
+    Ok(processed_bone_data
+        .processed_bones
+        .into_iter()
+        .map(|(name, bone)| SkeletonPreviewBone {
+            name,
+            parent: bone.parent,
+            used_by_vertex: bone.flags.contains(ProcessedBoneFlags::USED_BY_VERTEX),
+            used_by_attachment: bone.flags.contains(ProcessedBoneFlags::USED_BY_ATTACHMENT),
+            conflicting_sources: bone.conflicting_sources,
+        })
+        .collect())
+}
+
+/// The meshes of the last compile of a model with the hash of the input they were processed from and the source files they used.
+struct MeshCache {
+    key: u64,
+    source_files: Vec<Arc<ImportFileData>>,
+    model_data: ProcessedModelData,
+}
+
+/// The caches are kept per model name, so the view, world and gib models compiled together do not replace each other.
+static MESH_CACHE: Mutex<BTreeMap<String, MeshCache>> = Mutex::new(BTreeMap::new());
+
+/// Processes the meshes unless nothing they depend on changed since the last compile, like when only animations were edited.
+fn process_cached_meshes(
+    input: &ImputedCompilationData,
+    file_manager: &State<FileManager>,
+    processed_bone_data: &ProcessedBoneData,
+) -> Result<ProcessedModelData, ProcessingMeshError> {
+    let mut hasher = DefaultHasher::new();
+    serde_json::to_string(&(
+        &input.body_parts,
+        &input.material_paths,
+        &input.material_renames,
+        &input.material_normalization,
+        &input.mesh_limits,
+        &input.weld_tolerances,
+        &input.texture_coordinates,
+        &input.polygon_limits,
+        &input.engine_limits,
+        input.target_version,
+        input.normal_smoothing_angle,
+        input.seam_normal_tolerance,
+        input.origin,
+    ))
+    .unwrap_or_default()
+    .hash(&mut hasher);
+    // Vertices are weighted to the merged skeleton, which can change from files that have no meshes.
+    for (file_source, remapped_bones) in &processed_bone_data.remapped_bones {
+        file_source.hash(&mut hasher);
+        for remapped_bone in remapped_bones {
+            remapped_bone.index.hash(&mut hasher);
+        }
+    }
+
+    let source_files = input
+        .body_parts
+        .iter()
+        .flat_map(|body_part| &body_part.models)
+        .flat_map(|model| [&model.file_source, &model.flex_file_source])
+        .filter_map(|file_source| file_manager.get_file(file_source))
+        .collect::<Vec<_>>();
+    let key = hasher.finish();
+
+    // Reloading a file replaces it, so the same files are still loaded when every file is the one the meshes were processed from.
+    let mut mesh_cache = MESH_CACHE.lock().unwrap();
+    if let Some(cache) = mesh_cache.get(&input.model_name).filter(|cache| {
+        cache.key == key
+            && cache.source_files.len() == source_files.len()
+            && cache.source_files.iter().zip(&source_files).all(|(cached, loaded)| Arc::ptr_eq(cached, loaded))
+    }) {
+        log("Meshes are unchanged, reusing the processed meshes!", LogLevel::Verbose);
+        return Ok(cache.model_data.clone());
+    }
+
+    let model_data = process_meshes(input, file_manager, processed_bone_data)?;
+    mesh_cache.insert(
+        input.model_name.clone(),
+        MeshCache {
+            key,
+            source_files,
+            model_data: model_data.clone(),
+        },
+    );
+
+    Ok(model_data)
+}
+
+/// Converts a bounding box from the input, none if its minimum is larger than its maximum.
+fn create_bounding_box(imputed_bounding_box: &ImputedBoundingBox) -> Option<BoundingBox> {
+    let [minimum_x, minimum_y, minimum_z] = imputed_bounding_box.minimum;
+    let [maximum_x, maximum_y, maximum_z] = imputed_bounding_box.maximum;
+
+    let bounding_box = BoundingBox {
+        minimum: Vector3::new(minimum_x, minimum_y, minimum_z),
+        maximum: Vector3::new(maximum_x, maximum_y, maximum_z),
+    };
+
+    bounding_box.is_valid().then_some(bounding_box)
+}
+
+/// Reports the start of a compile stage, unless the compile was cancelled before it.
+fn start_stage(index: usize) -> Result<(), ProcessingDataError> {
+    if is_cancelled() {
+        return Err(ProcessingDataError::Cancelled);
+    }
+
+    log(COMPILE_STAGES[index], LogLevel::Debug);
+    stage(COMPILE_STAGES[index], index, COMPILE_STAGES.len());
+    Ok(())
+}
+
 pub fn process(input: &ImputedCompilationData, file_manager: &State<FileManager>) -> Result<ProcessedData, ProcessingDataError> {
     if input.sequences.is_empty() {
         return Err(ProcessingDataError::NoSequences);
     }
 
-    log("Processing Bones", LogLevel::Debug);
-    let processed_bone_data = process_bones(input, file_manager)?;
+    // The engine picks one of the conflicting flags, so the model would not be drawn the way it was set up.
+    if input.model_flags.force_opaque && input.model_flags.translucent_two_pass {
+        return Err(ProcessingDataError::ConflictingModelFlags("Force Opaque", "Translucent Two Pass"));
+    }
+
+    if input.model_flags.do_not_cast_shadows && input.model_flags.cast_texture_shadows {
+        return Err(ProcessingDataError::ConflictingModelFlags("Do Not Cast Shadows", "Cast Texture Shadows"));
+    }
+
+    // The deflection is stored as a cosine, which the engine treats as unset when it is 0.
+    if input.max_eye_deflection.is_nan() || input.max_eye_deflection <= 0.0 || input.max_eye_deflection >= 90.0 {
+        return Err(ProcessingDataError::InvalidMaxEyeDeflection(input.max_eye_deflection));
+    }
+
+    start_stage(0)?;
+    let mut processed_bone_data = process_bones(input, file_manager)?;
     log(format!("Model uses {} bones", processed_bone_data.processed_bones.len()), LogLevel::Verbose);
 
     if processed_bone_data.processed_bones.is_empty() {
         return Err(ProcessingDataError::NoBones);
     }
 
-    log("Processing Animations", LogLevel::Debug);
-    let processed_animation_data = process_animations(input, file_manager, &processed_bone_data)?;
+    start_stage(1)?;
+    let processed_animation_data = process_animations(input, file_manager, &processed_bone_data);
+    // Cancelling stops the animations early, which should not be reported as a failed animation.
+    if is_cancelled() {
+        return Err(ProcessingDataError::Cancelled);
+    }
+    let mut processed_animation_data = processed_animation_data?;
     log(
         format!("Model has {} animations", processed_animation_data.processed_animations.len()),
         LogLevel::Verbose,
     );
 
-    log("Processing Sequences", LogLevel::Debug);
-    let processed_sequences = process_sequences(input, &processed_animation_data.processed_animations)?;
+    start_stage(2)?;
+    let pose_parameters = process_pose_parameters(input);
+    let mut processed_sequences = process_sequences(input, &processed_animation_data.processed_animations, &processed_bone_data, &pose_parameters)?;
     log(format!("Model has {} sequences", processed_sequences.len()), LogLevel::Verbose);
 
     if processed_sequences.len() > i32::MAX as usize {
         return Err(ProcessingDataError::TooManySequences);
     }
 
-    log("Processing Mesh Data", LogLevel::Debug);
-    let processed_mesh = process_meshes(input, file_manager, &processed_bone_data)?;
+    let transition_nodes = process_transition_nodes(input, &processed_sequences)?;
+
+    // Animations are resolved by name in sequences, so only prefix them after sequences have been processed.
+    if input.prefix_animation_names {
+        for processed_animation in &mut processed_animation_data.processed_animations {
+            if !processed_animation.name.starts_with('@') {
+                processed_animation.name.insert(0, '@');
+            }
+        }
+    }
+
+    start_stage(3)?;
+    let mut processed_mesh = process_cached_meshes(input, file_manager, &processed_bone_data)?;
+    if is_cancelled() {
+        return Err(ProcessingDataError::Cancelled);
+    }
+
+    if let Some(bounding_box) = &input.bounding_box {
+        processed_mesh.bounding_box = create_bounding_box(bounding_box).ok_or(ProcessingDataError::InvalidBoundingBox)?;
+    }
+
+    if let Some(clipping_box) = &input.clipping_box {
+        processed_mesh.clipping_box = create_bounding_box(clipping_box).ok_or(ProcessingDataError::InvalidClippingBox)?;
+    }
+
+    calculate_sequence_bounding_boxes(
+        input,
+        &mut processed_sequences,
+        &processed_animation_data.processed_animations,
+        &processed_bone_data,
+        &processed_mesh,
+    )?;
+
+    // A model without meshes has no size of its own, so it is as large as its animations.
+    let has_vertices = processed_mesh
+        .body_parts
+        .iter()
+        .flat_map(|body_part| &body_part.models)
+        .flat_map(|model| &model.meshes)
+        .any(|mesh| !mesh.vertex_data.is_empty());
+
+    if input.bounding_box.is_none() && !has_vertices {
+        let mut bounding_box = BoundingBox::default();
+        for processed_sequence in &processed_sequences {
+            bounding_box.add_bounding_box(&processed_sequence.bounding_box);
+        }
+        processed_mesh.bounding_box = bounding_box;
+    }
     log(format!("Model has {} materials", processed_mesh.materials.len()), LogLevel::Verbose);
     log(format!("Model has {} body parts", processed_mesh.body_parts.len()), LogLevel::Verbose);
 
+    calculate_bone_usage(&mut processed_bone_data, &processed_mesh);
+
+    start_stage(4)?;
+    let physics_data = process_physics(input, file_manager, &processed_bone_data)?;
+
     Ok(ProcessedData {
         bone_data: processed_bone_data,
         animation_data: processed_animation_data,
         sequence_data: processed_sequences,
+        pose_parameters,
+        transition_nodes,
         model_data: processed_mesh,
+        physics_data,
     })
 }
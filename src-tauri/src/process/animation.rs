@@ -1,41 +1,136 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
 use indexmap::IndexMap;
+use rayon::prelude::*;
 use tauri::State;
 use thiserror::Error as ThisError;
 
 use crate::{
     import::{FileManager, ImportKeyFrame},
-    input::ImputedCompilationData,
+    input::{
+        ImputedAimMatrix, ImputedAnimation, ImputedAnimationMotion, ImputedCompilationData, ImputedInverseKinematicRule, ImputedInverseKinematicRuleType,
+        ImputedWeightList,
+    },
     utilities::{
         logging::{log, LogLevel},
-        mathematics::{Quaternion, Vector3},
+        mathematics::{BoundingBox, Matrix3, Matrix4, Quaternion, Vector3},
+        progress::{is_cancelled, progress},
     },
 };
 
-use super::{ProcessedAnimatedBoneData, ProcessedAnimation, ProcessedAnimationData, ProcessedBoneData, ProcessedSequence};
+use super::{
+    create_bounding_box, ProcessedAnimatedBoneData, ProcessedAnimation, ProcessedAnimationData, ProcessedAutoLayer, ProcessedBodyPart, ProcessedBoneData,
+    ProcessedInverseKinematicLock, ProcessedInverseKinematicRule, ProcessedInverseKinematicRuleType, ProcessedModelData, ProcessedMovement,
+    ProcessedPoseParameter, ProcessedSequence, ProcessedSequenceParameter, ProcessedSequenceTransition, ProcessedTransitionNode,
+};
 
 #[derive(Debug, ThisError)]
 pub enum ProcessingAnimationError {
+    #[error("Compile Was Cancelled")]
+    Cancelled,
     #[error("Animation File Source Not Loaded")]
     FileSourceNotLoaded,
     #[error("Animation Not Found: {0}")]
     AnimationNotFound(String),
     #[error("Model Has Too Many Animations")]
     TooManyAnimations,
-    #[error("Sequence Could Not Find Animation")]
-    SequenceAnimationNotFound,
+    #[error("Animation {0} Has An Invalid Frame Rate")]
+    InvalidFrameRate(String),
+    #[error("Animation {0} Has A Frame Range Outside Of Its Imported Frames")]
+    InvalidFrameRange(String),
+    #[error("Animation {0} Has A Speed Of {1} Outside Of {MINIMUM_ANIMATION_SPEED} To {MAXIMUM_ANIMATION_SPEED}")]
+    InvalidSpeed(String, f64),
+    #[error("Animation Sections Must Have At Least {0} Frames")]
+    InvalidSectionFrameCount(usize),
+    #[error("Sequence {0} Could Not Find Animation {1}")]
+    SequenceAnimationNotFound(String, String),
+    #[error("Sequence {0} Has No Animations")]
+    SequenceHasNoAnimations(String),
+    #[error("Sequence {0} Has A Negative Fade Time")]
+    InvalidSequenceFadeTime(String),
+    #[error("Sequence {0} Has A Phase Outside Of Zero To One")]
+    InvalidSequencePhase(String),
+    #[error("Sequence {0} Has An Empty Activity Modifier")]
+    EmptyActivityModifier(String),
+    #[error("Sequence {0} Has {1} Blend Keys For {2} Animations")]
+    SequenceBlendKeyCountMismatch(String, usize, usize),
+    #[error("Sequence {0} Needs At Least Two Increasing Blend Keys")]
+    InvalidSequenceBlendKeys(String),
+    #[error("Sequence {0} Blend Row {1} Has {2} Animations But Expected {3}")]
+    SequenceBlendGridNotRectangular(String, usize, usize, usize),
+    #[error("Subtract Animation Not Found: {0}")]
+    SubtractAnimationNotFound(String),
+    #[error("Subtract Frame {0} Is Out Of Range For Animation {1}")]
+    SubtractFrameOutOfRange(usize, String),
+    #[error("Weight List Not Found: {0}")]
+    WeightListNotFound(String),
+    #[error("Weight List {0} Could Not Find Bone {1}")]
+    WeightListBoneNotFound(String, String),
+    #[error("Auto Layer Sequence Not Found: {0}")]
+    AutoLayerSequenceNotFound(String),
+    #[error("Sequence {0} Can Not Auto Layer Itself")]
+    AutoLayerSelfReference(String),
+    #[error("Auto Layer {1} On Sequence {0} Has An Invalid Range")]
+    InvalidAutoLayerRange(String, String),
+    #[error("Bounding Box Of Sequence {0} Has A Minimum Larger Than Its Maximum")]
+    InvalidSequenceBoundingBox(String),
+    #[error("Aim Matrix {0} Could Not Find Animation {1}")]
+    AimMatrixAnimationNotFound(String, String),
+    #[error("Aim Matrix Frame {0} Is Out Of Range For Animation {1}")]
+    AimMatrixFrameOutOfRange(usize, String),
+    #[error("Aim Matrix {0} Has An Invalid Range")]
+    InvalidAimMatrixRange(String),
+    #[error("Aim Matrix {0} Uses The Same Pose Parameter For Yaw And Pitch")]
+    AimMatrixSharedParameter(String),
+    #[error("Aim Matrix {0} Has The Same Name As Another Sequence")]
+    DuplicateAimMatrixName(String),
+    #[error("Aim Matrix {0} Generates Animation {1} Which Already Exists")]
+    AimMatrixAnimationConflict(String, String),
+    #[error("Sequence {0} Could Not Find Transition Node {1}")]
+    TransitionNodeNotFound(String, String),
+    #[error("Duplicate Transition Node: {0}")]
+    DuplicateTransitionNode(String),
+    #[error("Model Has Too Many Transition Nodes")]
+    TooManyTransitionNodes,
+    #[error("Animation {0} Extracts Motion Without Any Axes")]
+    MotionHasNoAxes(String),
+    #[error("Animation {0} Can Not Split Its Motion Into {1} Pieces")]
+    InvalidMotionPieces(String, usize),
+    #[error("Animation {0} Has No Animated Root Bone To Extract Motion From")]
+    MotionRootBoneNotFound(String),
+    #[error("Animation {0} Could Not Find Inverse Kinematic Chain {1}")]
+    InverseKinematicRuleChainNotFound(String, String),
+    #[error("Inverse Kinematic Rule For Chain {1} On Animation {0} Has An Invalid Range")]
+    InvalidInverseKinematicRuleRange(String, String),
+    #[error("Inverse Kinematic Rule On Animation {0} Could Not Find Bone {1}")]
+    InverseKinematicRuleBoneNotFound(String, String),
+    #[error("Sequence {0} Blends Animations With Different Inverse Kinematic Rule Counts")]
+    SequenceInverseKinematicRuleCountMismatch(String),
+    #[error("Sequence {0} Could Not Find Inverse Kinematic Chain {1}")]
+    SequenceInverseKinematicChainNotFound(String, String),
 }
 
+/// Sections share their last frame with the next section, so a section needs at least one frame of its own.
+const MINIMUM_SECTION_FRAMES: usize = 2;
+
+/// The range of speeds an animation can be resampled at, slower speeds would create more frames than the engine can hold.
+const MINIMUM_ANIMATION_SPEED: f64 = 0.01;
+const MAXIMUM_ANIMATION_SPEED: f64 = 100.0;
+
+/// The names of the rows and columns of an aim matrix, from the negative to the positive end of the pitch and yaw ranges.
+const AIM_PITCH_NAMES: [&str; 3] = ["up", "center", "down"];
+const AIM_YAW_NAMES: [&str; 3] = ["right", "center", "left"];
+
 pub fn process_animations(
     input: &ImputedCompilationData,
     import: &State<FileManager>,
     bone_table: &ProcessedBoneData,
 ) -> Result<ProcessedAnimationData, ProcessingAnimationError> {
-    struct ChannelData {
-        position: Vec<Vector3>,
-        rotation: Vec<Quaternion>,
+    if input.animation_section_frames < MINIMUM_SECTION_FRAMES {
+        return Err(ProcessingAnimationError::InvalidSectionFrameCount(MINIMUM_SECTION_FRAMES));
     }
 
-    let mut processed_animations = Vec::new();
+    let mut used_animations = Vec::new();
     for imputed_animation in &input.animations {
         // Check if the animation is used in any sequence.
         if !input.sequences.iter().any(|sequence| {
@@ -44,102 +139,38 @@ pub fn process_animations(
                 .iter()
                 .any(|row| row.iter().any(|animation| animation == &imputed_animation.name))
         }) {
-            log(format!("Animation \"{}\" Not Used!", imputed_animation.name), LogLevel::Warn);
+            // Aim matrices only sample the pose of their animation, so the animation itself is not needed.
+            if !input.aim_matrices.iter().any(|aim_matrix| aim_matrix.animation == imputed_animation.name) {
+                log(format!("Animation \"{}\" Not Used!", imputed_animation.name), LogLevel::Warn);
+            }
             continue;
         }
 
-        // Gather imported animation data.
-        let imported_file = match import.get_file(&imputed_animation.file_source) {
-            Some(file) => file,
-            None => {
-                return Err(ProcessingAnimationError::FileSourceNotLoaded);
-            }
-        };
-        let remapped_bones = match bone_table.remapped_bones.get(&imputed_animation.file_source) {
-            Some(remapped_bones) => remapped_bones,
-            None => {
-                return Err(ProcessingAnimationError::FileSourceNotLoaded);
-            }
-        };
-        let imported_animation = match imported_file.animations.iter().find(|anim| anim.name == imputed_animation.animation_name) {
-            Some(imported_animation) => imported_animation,
-            None => {
-                return Err(ProcessingAnimationError::AnimationNotFound(imputed_animation.animation_name.clone()));
-            }
-        };
-
-        let mut animation_channels = IndexMap::new();
-
-        for channel in &imported_animation.channels {
-            let mapped_bone = &remapped_bones[channel.bone];
-            if animation_channels.contains_key(&mapped_bone.index) {
-                continue;
-            }
-
-            let bone = &bone_table.processed_bones[mapped_bone.index];
-
-            animation_channels.insert(
-                mapped_bone.index,
-                ChannelData {
-                    position: bake_channel_keyframes(&channel.position, imported_animation.frame_count, bone.position),
-                    rotation: bake_channel_keyframes(&channel.rotation, imported_animation.frame_count, bone.rotation.to_quaternion()),
-                },
-            );
+        if !imputed_animation.fps.is_finite() || imputed_animation.fps <= 0.0 {
+            return Err(ProcessingAnimationError::InvalidFrameRate(imputed_animation.name.clone()));
         }
 
-        let frame_count = imported_animation.frame_count;
-
-        // TODO: Implement animation processing.
-        // TODO: Add a check if the position data is going to be out of bounds.
-
-        // Split animation into sections
-        let frames_per_sections = 30; // TODO: Make this configurable.
-        let animation_section_split_threshold = 120; // TODO: Make this configurable.
-
-        let section_count = if frame_count >= animation_section_split_threshold {
-            (frame_count / frames_per_sections) + 2
-        } else {
-            1
-        };
-        let section_frame_count = if frame_count >= animation_section_split_threshold {
-            frames_per_sections
-        } else {
-            frame_count
-        };
-
-        let mut processed_animation = ProcessedAnimation {
-            name: imputed_animation.name.clone(),
-            frame_count,
-            sections: Vec::with_capacity(section_count),
-        };
-
-        for section in 0..section_count {
-            let section_frame_start = (section * section_frame_count).min(frame_count - 1);
-            let section_frame_end = ((section + 1) * section_frame_count).min(frame_count - 1);
-
-            let mut section_data = Vec::new();
-            for (index_bone, channel_data) in &animation_channels {
-                let bone = &bone_table.processed_bones[*index_bone];
-                let mut position = Vec::new();
-                let mut rotation = Vec::new();
-
-                // TODO: If animation is delta then skip subtracting from bone
-                for frame in section_frame_start..=section_frame_end {
-                    position.push(channel_data.position[frame] - bone.position);
-                    rotation.push(channel_data.rotation[frame].to_angles().clean() - bone.rotation);
-                }
+        used_animations.push(imputed_animation);
+    }
 
-                section_data.push(ProcessedAnimatedBoneData {
-                    bone: (*index_bone).try_into().unwrap(),
-                    position,
-                    rotation,
-                });
+    // Baking and splitting each animation does not depend on the others, so they are processed in parallel.
+    let completed_animations = AtomicUsize::new(0);
+    let mut processed_animations = used_animations
+        .par_iter()
+        .map(|imputed_animation| {
+            if is_cancelled() {
+                return Err(ProcessingAnimationError::Cancelled);
             }
 
-            processed_animation.sections.push(section_data);
-        }
+            let processed_animation = process_animation(imputed_animation, input, import, bone_table);
+            let completed = completed_animations.fetch_add(1, Ordering::Relaxed) + 1;
+            progress("Processing Animations", completed, used_animations.len());
+            processed_animation
+        })
+        .collect::<Result<Vec<_>, _>>()?;
 
-        processed_animations.push(processed_animation);
+    for imputed_aim_matrix in &input.aim_matrices {
+        processed_animations.extend(create_aim_animations(imputed_aim_matrix, input, import, bone_table)?);
     }
 
     let mut animation_scales = vec![(Vector3::default(), Vector3::default()); bone_table.processed_bones.len()];
@@ -180,6 +211,615 @@ pub fn process_animations(
     })
 }
 
+/// Bakes an animation to every frame and splits it into sections relative to the bind pose or the subtract pose.
+fn process_animation(
+    imputed_animation: &ImputedAnimation,
+    input: &ImputedCompilationData,
+    import: &State<FileManager>,
+    bone_table: &ProcessedBoneData,
+) -> Result<ProcessedAnimation, ProcessingAnimationError> {
+    let (frame_count, mut animation_channels) = bake_animation_channels(imputed_animation, input, import, bone_table)?;
+
+    // The rules are sampled before the motion is extracted, so the targets include the motion of the entity.
+    let inverse_kinematic_rules = imputed_animation
+        .inverse_kinematic_rules
+        .iter()
+        .map(|imputed_rule| create_inverse_kinematic_rule(imputed_animation, imputed_rule, frame_count, &animation_channels, bone_table))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let movements = match &imputed_animation.motion {
+        Some(motion) => extract_motion(imputed_animation, motion, frame_count, &mut animation_channels, bone_table)?,
+        None => Vec::new(),
+    };
+
+    // Gather the pose the animation is relative to if it is a delta animation.
+    let subtract_pose = match &imputed_animation.subtract {
+        Some(subtract) => {
+            let subtract_animation = input
+                .animations
+                .iter()
+                .find(|animation| animation.name == subtract.animation)
+                .ok_or_else(|| ProcessingAnimationError::SubtractAnimationNotFound(subtract.animation.clone()))?;
+
+            let (subtract_frame_count, subtract_channels) = bake_animation_channels(subtract_animation, input, import, bone_table)?;
+
+            if subtract.frame >= subtract_frame_count {
+                return Err(ProcessingAnimationError::SubtractFrameOutOfRange(subtract.frame, subtract.animation.clone()));
+            }
+
+            let mut subtract_pose = IndexMap::new();
+            for (index_bone, channel_data) in subtract_channels {
+                subtract_pose.insert(
+                    index_bone,
+                    (channel_data.position[subtract.frame], channel_data.rotation[subtract.frame].to_angles().clean()),
+                );
+            }
+
+            Some(subtract_pose)
+        }
+        None => None,
+    };
+
+    // TODO: Implement animation processing.
+    // TODO: Add a check if the position data is going to be out of bounds.
+
+    // Split animation into sections, only animations that are at least four sections long are split.
+    let frames_per_section = input.animation_section_frames;
+    let is_split = frame_count >= frames_per_section * 4;
+
+    let section_count = if is_split { (frame_count / frames_per_section) + 2 } else { 1 };
+    let section_frame_count = if is_split { frames_per_section } else { frame_count };
+
+    let mut processed_animation = ProcessedAnimation {
+        name: imputed_animation.name.clone(),
+        frame_count,
+        fps: imputed_animation.fps,
+        is_delta: subtract_pose.is_some(),
+        frames_per_section: if is_split { frames_per_section } else { 0 },
+        sections: Vec::with_capacity(section_count),
+        movements,
+        inverse_kinematic_rules,
+    };
+
+    for section in 0..section_count {
+        let section_frame_start = (section * section_frame_count).min(frame_count - 1);
+        let section_frame_end = ((section + 1) * section_frame_count).min(frame_count - 1);
+
+        let mut section_data = Vec::new();
+        for (index_bone, channel_data) in &animation_channels {
+            let bone = &bone_table.processed_bones[*index_bone];
+            let mut position = Vec::new();
+            let mut rotation = Vec::new();
+
+            let (base_position, base_rotation) = match &subtract_pose {
+                Some(subtract_pose) => subtract_pose.get(index_bone).copied().unwrap_or((bone.position, bone.rotation)),
+                None => (bone.position, bone.rotation),
+            };
+
+            for frame in section_frame_start..=section_frame_end {
+                position.push(channel_data.position[frame] - base_position);
+                rotation.push(channel_data.rotation[frame].to_angles().clean() - base_rotation);
+            }
+
+            section_data.push(ProcessedAnimatedBoneData {
+                bone: (*index_bone).try_into().unwrap(),
+                position,
+                rotation,
+            });
+        }
+
+        processed_animation.sections.push(section_data);
+    }
+
+    Ok(processed_animation)
+}
+
+/// Splits the motion of the first animated root bone into pieces of constant velocity, optionally removing it from the root bones.
+fn extract_motion(
+    imputed_animation: &ImputedAnimation,
+    motion: &ImputedAnimationMotion,
+    frame_count: usize,
+    animation_channels: &mut IndexMap<usize, ChannelData>,
+    bone_table: &ProcessedBoneData,
+) -> Result<Vec<ProcessedMovement>, ProcessingAnimationError> {
+    let axes = [motion.x, motion.y, motion.z];
+
+    if !axes.iter().any(|axis| *axis) {
+        return Err(ProcessingAnimationError::MotionHasNoAxes(imputed_animation.name.clone()));
+    }
+
+    // Every piece needs at least one frame to move over.
+    if motion.pieces == 0 || motion.pieces >= frame_count {
+        return Err(ProcessingAnimationError::InvalidMotionPieces(imputed_animation.name.clone(), motion.pieces));
+    }
+
+    let root_bones = animation_channels
+        .keys()
+        .copied()
+        .filter(|bone_index| bone_table.processed_bones[*bone_index].parent.is_none())
+        .collect::<Vec<_>>();
+
+    let motion_bone = *root_bones
+        .first()
+        .ok_or_else(|| ProcessingAnimationError::MotionRootBoneNotFound(imputed_animation.name.clone()))?;
+
+    let motion_positions = &animation_channels[&motion_bone].position;
+    let motion_track = motion_positions
+        .iter()
+        .map(|position| {
+            let mut offset = *position - motion_positions[0];
+            for axis in 0..3 {
+                if !axes[axis] {
+                    offset[axis] = 0.0;
+                }
+            }
+            offset
+        })
+        .collect::<Vec<_>>();
+
+    let last_frame = frame_count - 1;
+    let mut movements = Vec::with_capacity(motion.pieces);
+    let mut extracted_track = vec![Vector3::default(); frame_count];
+    let mut start_frame = 0;
+
+    for piece in 1..=motion.pieces {
+        let end_frame = piece * last_frame / motion.pieces;
+        let piece_frames = (end_frame - start_frame) as f64;
+        let start_position = motion_track[start_frame];
+        let piece_motion = motion_track[end_frame] - start_position;
+        let distance = piece_motion.magnitude();
+        let velocity = distance / piece_frames * imputed_animation.fps;
+
+        for (frame, extracted) in extracted_track.iter_mut().enumerate().take(end_frame + 1).skip(start_frame) {
+            *extracted = start_position + piece_motion * ((frame - start_frame) as f64 / piece_frames);
+        }
+
+        movements.push(ProcessedMovement {
+            end_frame,
+            axes,
+            start_velocity: velocity,
+            end_velocity: velocity,
+            direction: if distance > f64::EPSILON {
+                piece_motion.normalize()
+            } else {
+                Vector3::default()
+            },
+            position: motion_track[end_frame],
+        });
+
+        start_frame = end_frame;
+    }
+
+    if motion.subtract {
+        for root_bone in root_bones {
+            let channel = animation_channels.get_mut(&root_bone).unwrap();
+            for (position, extracted) in channel.position.iter_mut().zip(&extracted_track) {
+                *position = *position - *extracted;
+            }
+        }
+    }
+
+    Ok(movements)
+}
+
+/// Resolves an inverse kinematic rule and samples where the end bone of its chain is over the frames of the rule.
+fn create_inverse_kinematic_rule(
+    imputed_animation: &ImputedAnimation,
+    imputed_rule: &ImputedInverseKinematicRule,
+    frame_count: usize,
+    animation_channels: &IndexMap<usize, ChannelData>,
+    bone_table: &ProcessedBoneData,
+) -> Result<ProcessedInverseKinematicRule, ProcessingAnimationError> {
+    let chain = bone_table
+        .inverse_kinematic_chains
+        .iter()
+        .position(|chain| chain.name == imputed_rule.chain)
+        .ok_or_else(|| ProcessingAnimationError::InverseKinematicRuleChainNotFound(imputed_animation.name.clone(), imputed_rule.chain.clone()))?;
+
+    let range = [imputed_rule.start, imputed_rule.peak, imputed_rule.tail, imputed_rule.end];
+    if range.windows(2).any(|pair| pair[0] > pair[1])
+        || imputed_rule.end >= frame_count
+        || !(imputed_rule.start..=imputed_rule.end).contains(&imputed_rule.contact)
+    {
+        return Err(ProcessingAnimationError::InvalidInverseKinematicRuleRange(
+            imputed_animation.name.clone(),
+            imputed_rule.chain.clone(),
+        ));
+    }
+
+    let rule = match &imputed_rule.rule {
+        ImputedInverseKinematicRuleType::Touch { bone } => ProcessedInverseKinematicRuleType::Touch {
+            bone: bone_table
+                .processed_bones
+                .get_index_of(bone)
+                .ok_or_else(|| ProcessingAnimationError::InverseKinematicRuleBoneNotFound(imputed_animation.name.clone(), bone.clone()))?,
+        },
+        ImputedInverseKinematicRuleType::Footstep => ProcessedInverseKinematicRuleType::Footstep,
+        ImputedInverseKinematicRuleType::Release => ProcessedInverseKinematicRuleType::Release,
+    };
+
+    let last_frame = frame_count - 1;
+    let to_cycle = |frame: usize| if last_frame == 0 { 0.0 } else { frame as f64 / last_frame as f64 };
+
+    // The engine interpolates towards the next frame, so the frame after the end is sampled too.
+    let end_bone = bone_table.inverse_kinematic_chains[chain].bones[2];
+    let targets = match rule {
+        ProcessedInverseKinematicRuleType::Release => Vec::new(),
+        ProcessedInverseKinematicRuleType::Touch { bone } => (imputed_rule.start..=(imputed_rule.end + 1).min(last_frame))
+            .map(|frame| {
+                let (end_pose, end_rotation) = calculate_animated_pose(end_bone, frame, animation_channels, bone_table);
+                let (touch_pose, touch_rotation) = calculate_animated_pose(bone, frame, animation_channels, bone_table);
+                let inverse_touch_rotation = Quaternion::new(-touch_rotation.x, -touch_rotation.y, -touch_rotation.z, touch_rotation.w);
+                (
+                    touch_pose.transpose().transform(end_pose.transform(Vector3::default())),
+                    (inverse_touch_rotation * end_rotation).to_angles(),
+                )
+            })
+            .collect(),
+        ProcessedInverseKinematicRuleType::Footstep => (imputed_rule.start..=(imputed_rule.end + 1).min(last_frame))
+            .map(|frame| {
+                let (end_pose, end_rotation) = calculate_animated_pose(end_bone, frame, animation_channels, bone_table);
+                (end_pose.transform(Vector3::default()), end_rotation.to_angles())
+            })
+            .collect(),
+    };
+
+    Ok(ProcessedInverseKinematicRule {
+        chain,
+        rule,
+        height: imputed_rule.height,
+        radius: imputed_rule.radius,
+        start: to_cycle(imputed_rule.start),
+        peak: to_cycle(imputed_rule.peak),
+        tail: to_cycle(imputed_rule.tail),
+        end: to_cycle(imputed_rule.end),
+        contact: to_cycle(imputed_rule.contact),
+        start_frame: imputed_rule.start,
+        targets,
+    })
+}
+
+/// Calculates the model space pose and rotation of a bone at a frame of the baked channels.
+fn calculate_animated_pose(
+    bone: usize,
+    frame: usize,
+    animation_channels: &IndexMap<usize, ChannelData>,
+    bone_table: &ProcessedBoneData,
+) -> (Matrix4, Quaternion) {
+    let processed_bone = &bone_table.processed_bones[bone];
+
+    let (position, rotation) = match animation_channels.get(&bone) {
+        Some(channel_data) => (channel_data.position[frame], channel_data.rotation[frame]),
+        None => (processed_bone.position, processed_bone.rotation.to_quaternion()),
+    };
+
+    let local_pose = Matrix4::new(position, rotation.to_matrix());
+
+    match processed_bone.parent {
+        Some(parent) => {
+            let (parent_pose, parent_rotation) = calculate_animated_pose(parent, frame, animation_channels, bone_table);
+            (parent_pose * local_pose, parent_rotation * rotation)
+        }
+        None => (local_pose, rotation),
+    }
+}
+
+/// Names the animations of an aim matrix, row by row.
+fn aim_matrix_animation_names(aim_matrix_name: &str) -> Vec<String> {
+    AIM_PITCH_NAMES
+        .iter()
+        .flat_map(|pitch| AIM_YAW_NAMES.iter().map(move |yaw| format!("{}_{}_{}", aim_matrix_name, pitch, yaw)))
+        .collect()
+}
+
+/// Creates the single frame delta animations of an aim matrix.
+/// Every weighted bone is turned by its share of the yaw and pitch around the up and side axes of the model.
+fn create_aim_animations(
+    imputed_aim_matrix: &ImputedAimMatrix,
+    input: &ImputedCompilationData,
+    import: &State<FileManager>,
+    bone_table: &ProcessedBoneData,
+) -> Result<Vec<ProcessedAnimation>, ProcessingAnimationError> {
+    let ranges = [imputed_aim_matrix.yaw_range, imputed_aim_matrix.pitch_range];
+    if ranges.iter().any(|range| !range.is_finite() || *range <= 0.0 || *range >= 180.0) {
+        return Err(ProcessingAnimationError::InvalidAimMatrixRange(imputed_aim_matrix.name.clone()));
+    }
+
+    if imputed_aim_matrix.yaw_parameter == imputed_aim_matrix.pitch_parameter {
+        return Err(ProcessingAnimationError::AimMatrixSharedParameter(imputed_aim_matrix.name.clone()));
+    }
+
+    let center_animation = input
+        .animations
+        .iter()
+        .find(|animation| animation.name == imputed_aim_matrix.animation)
+        .ok_or_else(|| ProcessingAnimationError::AimMatrixAnimationNotFound(imputed_aim_matrix.name.clone(), imputed_aim_matrix.animation.clone()))?;
+
+    if !center_animation.fps.is_finite() || center_animation.fps <= 0.0 {
+        return Err(ProcessingAnimationError::InvalidFrameRate(center_animation.name.clone()));
+    }
+
+    let (frame_count, animation_channels) = bake_animation_channels(center_animation, input, import, bone_table)?;
+    if imputed_aim_matrix.frame >= frame_count {
+        return Err(ProcessingAnimationError::AimMatrixFrameOutOfRange(
+            imputed_aim_matrix.frame,
+            center_animation.name.clone(),
+        ));
+    }
+
+    let imputed_weight_list = input
+        .weight_lists
+        .iter()
+        .find(|weight_list| weight_list.name == imputed_aim_matrix.weight_list)
+        .ok_or_else(|| ProcessingAnimationError::WeightListNotFound(imputed_aim_matrix.weight_list.clone()))?;
+
+    // Unlike sequence weight lists the weights are not inherited, children already follow the turn of their parents.
+    let mut aim_weights = Vec::with_capacity(imputed_weight_list.weights.len());
+    for imputed_bone_weight in &imputed_weight_list.weights {
+        let bone_index = bone_table
+            .processed_bones
+            .get_index_of(&imputed_bone_weight.bone)
+            .ok_or_else(|| ProcessingAnimationError::WeightListBoneNotFound(imputed_weight_list.name.clone(), imputed_bone_weight.bone.clone()))?;
+
+        let weight = imputed_bone_weight.weight.clamp(0.0, 1.0);
+        if weight > 0.0 && !aim_weights.iter().any(|&(index, _)| index == bone_index) {
+            aim_weights.push((bone_index, weight));
+        }
+    }
+
+    // The rotation of every bone in model space at the frame that is aimed from.
+    let mut world_rotations: Vec<Matrix3> = Vec::with_capacity(bone_table.processed_bones.len());
+    for (bone_index, bone) in bone_table.processed_bones.values().enumerate() {
+        let local_rotation = match animation_channels.get(&bone_index) {
+            Some(channel_data) => channel_data.rotation[imputed_aim_matrix.frame].to_matrix(),
+            None => bone.rotation.to_matrix(),
+        };
+
+        let world_rotation = match bone.parent {
+            Some(parent_index) => world_rotations[parent_index] * local_rotation,
+            None => local_rotation,
+        };
+        world_rotations.push(world_rotation);
+    }
+
+    let yaw_range = imputed_aim_matrix.yaw_range.to_radians();
+    let pitch_range = imputed_aim_matrix.pitch_range.to_radians();
+
+    let mut aim_animations = Vec::with_capacity(AIM_PITCH_NAMES.len() * AIM_YAW_NAMES.len());
+    for (animation_index, animation_name) in aim_matrix_animation_names(&imputed_aim_matrix.name).into_iter().enumerate() {
+        if input.animations.iter().any(|animation| animation.name == animation_name) {
+            return Err(ProcessingAnimationError::AimMatrixAnimationConflict(
+                imputed_aim_matrix.name.clone(),
+                animation_name,
+            ));
+        }
+
+        let yaw = ((animation_index % AIM_YAW_NAMES.len()) as f64 - 1.0) * yaw_range;
+        let pitch = ((animation_index / AIM_YAW_NAMES.len()) as f64 - 1.0) * pitch_range;
+
+        let mut section_data = Vec::with_capacity(aim_weights.len());
+        for &(bone_index, weight) in &aim_weights {
+            // Turning around a model axis is the same as turning around that axis moved into the space of the bone.
+            let yaw_axis = Vector3::new(0.0, 0.0, 1.0) * world_rotations[bone_index];
+            let pitch_axis = Vector3::new(0.0, 1.0, 0.0) * world_rotations[bone_index];
+            let rotation = Quaternion::from_axis_angle(yaw_axis, yaw * weight) * Quaternion::from_axis_angle(pitch_axis, pitch * weight);
+
+            section_data.push(ProcessedAnimatedBoneData {
+                bone: bone_index.try_into().unwrap(),
+                position: vec![Vector3::default()],
+                rotation: vec![rotation.to_angles().clean()],
+            });
+        }
+
+        aim_animations.push(ProcessedAnimation {
+            name: animation_name,
+            frame_count: 1,
+            fps: center_animation.fps,
+            is_delta: true,
+            frames_per_section: 0,
+            sections: vec![section_data],
+            movements: Vec::new(),
+            inverse_kinematic_rules: Vec::new(),
+        });
+    }
+
+    Ok(aim_animations)
+}
+
+/// Creates the per bone weights of a weight list, bones not listed inherit the weight of their parent.
+fn create_weight_list(imputed_weight_list: &ImputedWeightList, bone_table: &ProcessedBoneData) -> Result<Vec<f64>, ProcessingAnimationError> {
+    let mut listed_weights = IndexMap::with_capacity(imputed_weight_list.weights.len());
+
+    for imputed_bone_weight in &imputed_weight_list.weights {
+        let bone_index = match bone_table.processed_bones.get_index_of(&imputed_bone_weight.bone) {
+            Some(index) => index,
+            None => {
+                return Err(ProcessingAnimationError::WeightListBoneNotFound(
+                    imputed_weight_list.name.clone(),
+                    imputed_bone_weight.bone.clone(),
+                ))
+            }
+        };
+
+        listed_weights.insert(bone_index, imputed_bone_weight.weight.clamp(0.0, 1.0));
+    }
+
+    let mut weight_list: Vec<f64> = Vec::with_capacity(bone_table.processed_bones.len());
+
+    // Parents always come before their children in the bone table.
+    for (bone_index, bone) in bone_table.processed_bones.values().enumerate() {
+        let weight = match listed_weights.get(&bone_index) {
+            Some(weight) => *weight,
+            None => bone.parent.map_or(0.0, |parent_index| weight_list[parent_index]),
+        };
+
+        weight_list.push(weight);
+    }
+
+    Ok(weight_list)
+}
+
+struct ChannelData {
+    position: Vec<Vector3>,
+    rotation: Vec<Quaternion>,
+}
+
+/// Bakes the imported animation channels of an imputed animation onto the bone table.
+fn bake_animation_channels(
+    imputed_animation: &ImputedAnimation,
+    input: &ImputedCompilationData,
+    import: &State<FileManager>,
+    bone_table: &ProcessedBoneData,
+) -> Result<(usize, IndexMap<usize, ChannelData>), ProcessingAnimationError> {
+    let imported_file = match import.get_file(&imputed_animation.file_source) {
+        Some(file) => file,
+        None => {
+            return Err(ProcessingAnimationError::FileSourceNotLoaded);
+        }
+    };
+    let remapped_bones = match bone_table.remapped_bones.get(&imputed_animation.file_source) {
+        Some(remapped_bones) => remapped_bones,
+        None => {
+            return Err(ProcessingAnimationError::FileSourceNotLoaded);
+        }
+    };
+    let imported_animation = match imported_file.animations.iter().find(|anim| anim.name == imputed_animation.animation_name) {
+        Some(imported_animation) => imported_animation,
+        None => {
+            return Err(ProcessingAnimationError::AnimationNotFound(imputed_animation.animation_name.clone()));
+        }
+    };
+
+    let mut animation_channels = IndexMap::new();
+
+    for channel in &imported_animation.channels {
+        let mapped_bone_index = match remapped_bones[channel.bone].index {
+            Some(index) => index,
+            None => continue,
+        };
+
+        if animation_channels.contains_key(&mapped_bone_index) {
+            continue;
+        }
+
+        let bone = &bone_table.processed_bones[mapped_bone_index];
+
+        // Root bones were moved by the origin, so their imported positions are moved the same way.
+        let origin = match bone.parent {
+            Some(_) => Vector3::default(),
+            None => Vector3::new(input.origin[0], input.origin[1], input.origin[2]),
+        };
+
+        if imputed_animation.retarget {
+            // The change from the rest pose of the source skeleton is applied to the bind pose, so rigs with a different rest pose keep the shape of the model.
+            let source_bone = &imported_file.skeleton[channel.bone];
+            let inverse_source_rotation = Quaternion::new(
+                -source_bone.orientation.x,
+                -source_bone.orientation.y,
+                -source_bone.orientation.z,
+                source_bone.orientation.w,
+            );
+            let bind_rotation = bone.rotation.to_quaternion();
+
+            animation_channels.insert(
+                mapped_bone_index,
+                ChannelData {
+                    position: bake_channel_keyframes(&channel.position, imported_animation.frame_count, source_bone.position)
+                        .into_iter()
+                        .map(|position| bone.position + (position - source_bone.position))
+                        .collect(),
+                    rotation: bake_channel_keyframes(&channel.rotation, imported_animation.frame_count, source_bone.orientation)
+                        .into_iter()
+                        .map(|rotation| bind_rotation * (inverse_source_rotation * rotation))
+                        .collect(),
+                },
+            );
+            continue;
+        }
+
+        animation_channels.insert(
+            mapped_bone_index,
+            ChannelData {
+                position: bake_channel_keyframes(&channel.position, imported_animation.frame_count, bone.position + origin)
+                    .into_iter()
+                    .map(|position| position - origin)
+                    .collect(),
+                rotation: bake_channel_keyframes(&channel.rotation, imported_animation.frame_count, bone.rotation.to_quaternion()),
+            },
+        );
+    }
+
+    // Only the selected frames are used, so a single long take can be split into several animations.
+    let (frame_start, frame_count) = match imputed_animation.frame_range {
+        Some([start, end]) if start <= end && end < imported_animation.frame_count => (start, end - start + 1),
+        Some(_) => return Err(ProcessingAnimationError::InvalidFrameRange(imputed_animation.name.clone())),
+        None => (0, imported_animation.frame_count),
+    };
+
+    if frame_count != imported_animation.frame_count {
+        for channel_data in animation_channels.values_mut() {
+            channel_data.position = channel_data.position[frame_start..frame_start + frame_count].to_vec();
+            channel_data.rotation = channel_data.rotation[frame_start..frame_start + frame_count].to_vec();
+        }
+    }
+
+    let modifiers = &imputed_animation.modifiers;
+    if !(MINIMUM_ANIMATION_SPEED..=MAXIMUM_ANIMATION_SPEED).contains(&modifiers.speed) {
+        return Err(ProcessingAnimationError::InvalidSpeed(imputed_animation.name.clone(), modifiers.speed));
+    }
+
+    // The frames are resampled at the scaled rate, keeping the first and last frame.
+    let resampled_frame_count = ((frame_count - 1) as f64 / modifiers.speed).round() as usize + 1;
+    let frame_scale = if resampled_frame_count > 1 {
+        (frame_count - 1) as f64 / (resampled_frame_count - 1) as f64
+    } else {
+        0.0
+    };
+
+    for channel_data in animation_channels.values_mut() {
+        if modifiers.reverse {
+            channel_data.position.reverse();
+            channel_data.rotation.reverse();
+        }
+
+        if resampled_frame_count != frame_count {
+            let (positions, rotations) = (&channel_data.position, &channel_data.rotation);
+            let sample = |frame: usize| {
+                let time = frame as f64 * frame_scale;
+                let (previous, next) = (time.floor() as usize, (time.ceil() as usize).min(frame_count - 1));
+                let fraction = time - previous as f64;
+
+                // Rotations are blended along the shortest path, so the sign of the next rotation is matched to the previous.
+                let (from, mut to) = (rotations[previous], rotations[next]);
+                if from.x * to.x + from.y * to.y + from.z * to.z + from.w * to.w < 0.0 {
+                    to = Quaternion::new(-to.x, -to.y, -to.z, -to.w);
+                }
+                let rotation = Quaternion::new(
+                    from.x + (to.x - from.x) * fraction,
+                    from.y + (to.y - from.y) * fraction,
+                    from.z + (to.z - from.z) * fraction,
+                    from.w + (to.w - from.w) * fraction,
+                )
+                .normalize();
+
+                (positions[previous] + (positions[next] - positions[previous]) * fraction, rotation)
+            };
+            let (positions, rotations) = (0..resampled_frame_count).map(sample).unzip();
+            channel_data.position = positions;
+            channel_data.rotation = rotations;
+        }
+
+        let (first_position, last_position) = (channel_data.position[0], channel_data.position[resampled_frame_count - 1]);
+        let (first_rotation, last_rotation) = (channel_data.rotation[0], channel_data.rotation[resampled_frame_count - 1]);
+        channel_data.position.splice(0..0, vec![first_position; modifiers.hold_start]);
+        channel_data.position.extend(vec![last_position; modifiers.hold_end]);
+        channel_data.rotation.splice(0..0, vec![first_rotation; modifiers.hold_start]);
+        channel_data.rotation.extend(vec![last_rotation; modifiers.hold_end]);
+    }
+
+    Ok((modifiers.hold_start + resampled_frame_count + modifiers.hold_end, animation_channels))
+}
+
 /// Convert channel keyframes to a continuous set of values.
 fn bake_channel_keyframes<T: Copy>(channel: &[ImportKeyFrame<T>], frame_count: usize, default: T) -> Vec<T> {
     let mut baked_channel = Vec::with_capacity(frame_count);
@@ -201,15 +841,134 @@ fn bake_channel_keyframes<T: Copy>(channel: &[ImportKeyFrame<T>], frame_count: u
     baked_channel
 }
 
-pub fn process_sequences(input: &ImputedCompilationData, animations: &[ProcessedAnimation]) -> Result<Vec<ProcessedSequence>, ProcessingAnimationError> {
+/// Creates the pose parameters the sequences and aim matrices blend with, a parameter shared by several blends covers all of their ranges.
+pub fn process_pose_parameters(input: &ImputedCompilationData) -> Vec<ProcessedPoseParameter> {
+    let mut pose_parameters: Vec<ProcessedPoseParameter> = Vec::new();
+
+    let sequence_ranges = input
+        .sequences
+        .iter()
+        .flat_map(|sequence| [&sequence.column_blend, &sequence.row_blend])
+        .flatten()
+        .filter(|blend| !blend.keys.is_empty())
+        .map(|blend| {
+            let start = blend.keys.iter().copied().fold(f64::INFINITY, f64::min);
+            let end = blend.keys.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+            (&blend.parameter, start, end)
+        });
+
+    let aim_matrix_ranges = input.aim_matrices.iter().flat_map(|imputed_aim_matrix| {
+        [
+            (&imputed_aim_matrix.yaw_parameter, -imputed_aim_matrix.yaw_range, imputed_aim_matrix.yaw_range),
+            (
+                &imputed_aim_matrix.pitch_parameter,
+                -imputed_aim_matrix.pitch_range,
+                imputed_aim_matrix.pitch_range,
+            ),
+        ]
+    });
+
+    for (name, start, end) in sequence_ranges.chain(aim_matrix_ranges) {
+        match pose_parameters.iter_mut().find(|pose_parameter| &pose_parameter.name == name) {
+            Some(pose_parameter) => {
+                pose_parameter.start = pose_parameter.start.min(start);
+                pose_parameter.end = pose_parameter.end.max(end);
+            }
+            None => pose_parameters.push(ProcessedPoseParameter {
+                name: name.clone(),
+                start,
+                end,
+            }),
+        }
+    }
+
+    pose_parameters
+}
+
+/// Spreads the values of a pose parameter evenly across the columns or rows of a blend grid.
+fn even_pose_keys(start: f64, end: f64, count: usize) -> Vec<f64> {
+    if count < 2 {
+        return vec![start; count];
+    }
+
+    (0..count).map(|index| start + (end - start) * index as f64 / (count - 1) as f64).collect()
+}
+
+pub fn process_sequences(
+    input: &ImputedCompilationData,
+    animations: &[ProcessedAnimation],
+    bone_table: &ProcessedBoneData,
+    pose_parameters: &[ProcessedPoseParameter],
+) -> Result<Vec<ProcessedSequence>, ProcessingAnimationError> {
     let mut processed_sequences = Vec::with_capacity(input.sequences.len());
 
+    let mut weight_lists = IndexMap::with_capacity(input.weight_lists.len());
+    for imputed_weight_list in &input.weight_lists {
+        weight_lists.insert(imputed_weight_list.name.clone(), create_weight_list(imputed_weight_list, bone_table)?);
+    }
+
     for input_sequence in &input.sequences {
+        let column_count = input_sequence.animations.first().map_or(0, |row| row.len());
+        if column_count == 0 {
+            return Err(ProcessingAnimationError::SequenceHasNoAnimations(input_sequence.name.clone()));
+        }
+
+        if !(input_sequence.fade_in_time >= 0.0 && input_sequence.fade_out_time >= 0.0) {
+            return Err(ProcessingAnimationError::InvalidSequenceFadeTime(input_sequence.name.clone()));
+        }
+
+        let (entry_phase, exit_phase) = input_sequence.phase.as_ref().map_or((0.0, 0.0), |phase| (phase.entry, phase.exit));
+        if !(0.0..=1.0).contains(&entry_phase) || !(0.0..=1.0).contains(&exit_phase) {
+            return Err(ProcessingAnimationError::InvalidSequencePhase(input_sequence.name.clone()));
+        }
+
+        for (row_index, row_value) in input_sequence.animations.iter().enumerate() {
+            if row_value.len() != column_count {
+                return Err(ProcessingAnimationError::SequenceBlendGridNotRectangular(
+                    input_sequence.name.clone(),
+                    row_index,
+                    row_value.len(),
+                    column_count,
+                ));
+            }
+        }
+
         let mut processed_sequence = ProcessedSequence {
             name: input_sequence.name.clone(),
-            animations: vec![vec![0; input_sequence.animations[0].len()]; input_sequence.animations.len()],
+            animations: vec![vec![0; column_count]; input_sequence.animations.len()],
+            weight_list: match &input_sequence.weight_list {
+                Some(weight_list_name) => match weight_lists.get(weight_list_name) {
+                    Some(weight_list) => weight_list.clone(),
+                    None => return Err(ProcessingAnimationError::WeightListNotFound(weight_list_name.clone())),
+                },
+                None => vec![1.0; bone_table.processed_bones.len()],
+            },
+            fade_in_time: input_sequence.fade_in_time,
+            fade_out_time: input_sequence.fade_out_time,
+            entry_phase,
+            exit_phase,
+            ..Default::default()
         };
 
+        for activity_modifier in &input_sequence.activity_modifiers {
+            if activity_modifier.is_empty() {
+                return Err(ProcessingAnimationError::EmptyActivityModifier(input_sequence.name.clone()));
+            }
+
+            if processed_sequence.activity_modifiers.contains(activity_modifier) {
+                log(
+                    format!(
+                        "Sequence \"{}\" Has Activity Modifier \"{}\" More Than Once! Skipping!",
+                        input_sequence.name, activity_modifier
+                    ),
+                    LogLevel::Warn,
+                );
+                continue;
+            }
+
+            processed_sequence.activity_modifiers.push(activity_modifier.clone());
+        }
+
         for (row_index, row_value) in input_sequence.animations.iter().enumerate() {
             for (column_index, column_value) in row_value.iter().enumerate() {
                 let animation = animations.iter().position(|animation| animation.name == *column_value);
@@ -217,16 +976,386 @@ pub fn process_sequences(input: &ImputedCompilationData, animations: &[Processed
                 let animation_index = match animation {
                     Some(index) => index,
                     None => {
-                        return Err(ProcessingAnimationError::SequenceAnimationNotFound);
+                        return Err(ProcessingAnimationError::SequenceAnimationNotFound(
+                            input_sequence.name.clone(),
+                            column_value.clone(),
+                        ));
                     }
                 };
 
                 processed_sequence.animations[row_index][column_index] = animation_index.try_into().unwrap();
+                processed_sequence.is_delta |= animations[animation_index].is_delta;
+            }
+        }
+
+        // The engine samples every blended animation at the same cycle, so differing frame counts stretch the shorter animations.
+        let first_frame_count = animations[processed_sequence.animations[0][0] as usize].frame_count;
+        if let Some(mismatched_animation) = processed_sequence
+            .animations
+            .iter()
+            .flatten()
+            .map(|&animation_index| &animations[animation_index as usize])
+            .find(|animation| animation.frame_count != first_frame_count)
+        {
+            log(
+                format!(
+                    "Sequence \"{}\" Blends Animations With Different Frame Counts ({} And {} On \"{}\")! Playback Will Be Resampled!",
+                    input_sequence.name, first_frame_count, mismatched_animation.frame_count, mismatched_animation.name
+                ),
+                LogLevel::Warn,
+            );
+        }
+
+        // The engine reads the same amount of rules from every blended animation.
+        processed_sequence.inverse_kinematic_rule_count = animations[processed_sequence.animations[0][0] as usize].inverse_kinematic_rules.len();
+        if processed_sequence
+            .animations
+            .iter()
+            .flatten()
+            .any(|&animation_index| animations[animation_index as usize].inverse_kinematic_rules.len() != processed_sequence.inverse_kinematic_rule_count)
+        {
+            return Err(ProcessingAnimationError::SequenceInverseKinematicRuleCountMismatch(input_sequence.name.clone()));
+        }
+
+        for input_lock in &input_sequence.inverse_kinematic_locks {
+            let chain = bone_table
+                .inverse_kinematic_chains
+                .iter()
+                .position(|chain| chain.name == input_lock.chain)
+                .ok_or_else(|| ProcessingAnimationError::SequenceInverseKinematicChainNotFound(input_sequence.name.clone(), input_lock.chain.clone()))?;
+
+            processed_sequence.inverse_kinematic_locks.push(ProcessedInverseKinematicLock {
+                chain,
+                position_weight: input_lock.position_weight,
+                local_rotation_weight: input_lock.local_rotation_weight,
+            });
+        }
+
+        let blend_sizes = [column_count, input_sequence.animations.len()];
+        for (parameter_index, input_blend) in [&input_sequence.column_blend, &input_sequence.row_blend].into_iter().enumerate() {
+            let Some(input_blend) = input_blend else {
+                continue;
+            };
+
+            if input_blend.keys.len() != blend_sizes[parameter_index] {
+                return Err(ProcessingAnimationError::SequenceBlendKeyCountMismatch(
+                    input_sequence.name.clone(),
+                    input_blend.keys.len(),
+                    blend_sizes[parameter_index],
+                ));
+            }
+
+            // The engine searches the keys in order to find the two animations to blend between.
+            if input_blend.keys.len() < 2 || input_blend.keys.windows(2).any(|pair| pair[0] >= pair[1]) {
+                return Err(ProcessingAnimationError::InvalidSequenceBlendKeys(input_sequence.name.clone()));
+            }
+
+            processed_sequence.parameters[parameter_index] = Some(ProcessedSequenceParameter {
+                pose_parameter: pose_parameters
+                    .iter()
+                    .position(|pose_parameter| pose_parameter.name == input_blend.parameter)
+                    .unwrap(),
+                start: input_blend.keys[0],
+                end: input_blend.keys[input_blend.keys.len() - 1],
+                keys: input_blend.keys.clone(),
+            });
+        }
+
+        if let Some(input_transition) = &input_sequence.transition {
+            let find_node = |node_name: &String| {
+                input
+                    .transition_nodes
+                    .iter()
+                    .position(|transition_node| transition_node == node_name)
+                    .ok_or_else(|| ProcessingAnimationError::TransitionNodeNotFound(input_sequence.name.clone(), node_name.clone()))
+            };
+
+            processed_sequence.transition = Some(ProcessedSequenceTransition {
+                entry_node: find_node(&input_transition.entry)?,
+                exit_node: find_node(&input_transition.exit)?,
+                reverse: input_transition.reverse,
+            });
+        }
+
+        for input_auto_layer in &input_sequence.auto_layers {
+            if input_auto_layer.sequence == input_sequence.name {
+                return Err(ProcessingAnimationError::AutoLayerSelfReference(input_sequence.name.clone()));
+            }
+
+            // Aim matrices are added after the sequences of the input, so they can be layered by name too.
+            let sequence_index = match input
+                .sequences
+                .iter()
+                .map(|sequence| &sequence.name)
+                .chain(input.aim_matrices.iter().map(|aim_matrix| &aim_matrix.name))
+                .position(|sequence_name| *sequence_name == input_auto_layer.sequence)
+            {
+                Some(index) => index,
+                None => return Err(ProcessingAnimationError::AutoLayerSequenceNotFound(input_auto_layer.sequence.clone())),
+            };
+
+            // The range is in cycles of the sequence and must ramp up then down in order.
+            let range = [input_auto_layer.start, input_auto_layer.peak, input_auto_layer.tail, input_auto_layer.end];
+            if range.iter().any(|value| !(0.0..=1.0).contains(value)) || range.windows(2).any(|pair| pair[0] > pair[1]) {
+                return Err(ProcessingAnimationError::InvalidAutoLayerRange(
+                    input_sequence.name.clone(),
+                    input_auto_layer.sequence.clone(),
+                ));
             }
+
+            processed_sequence.auto_layers.push(ProcessedAutoLayer {
+                sequence: sequence_index.try_into().unwrap(),
+                start: input_auto_layer.start,
+                peak: input_auto_layer.peak,
+                tail: input_auto_layer.tail,
+                end: input_auto_layer.end,
+                spline: input_auto_layer.spline,
+                cross_fade: input_auto_layer.cross_fade,
+                no_blend: input_auto_layer.no_blend,
+            });
         }
 
         processed_sequences.push(processed_sequence);
     }
 
+    for imputed_aim_matrix in &input.aim_matrices {
+        if processed_sequences.iter().any(|sequence| sequence.name == imputed_aim_matrix.name) {
+            return Err(ProcessingAnimationError::DuplicateAimMatrixName(imputed_aim_matrix.name.clone()));
+        }
+
+        // The aim animations were generated while processing animations, so they always exist.
+        let animation_indices = aim_matrix_animation_names(&imputed_aim_matrix.name)
+            .iter()
+            .map(|animation_name| {
+                animations
+                    .iter()
+                    .position(|animation| animation.name == *animation_name)
+                    .unwrap()
+                    .try_into()
+                    .unwrap()
+            })
+            .collect::<Vec<i16>>();
+
+        let sequence_parameter = |name: &String, range: f64, count: usize| {
+            Some(ProcessedSequenceParameter {
+                pose_parameter: pose_parameters.iter().position(|pose_parameter| &pose_parameter.name == name).unwrap(),
+                start: -range,
+                end: range,
+                keys: even_pose_keys(-range, range, count),
+            })
+        };
+
+        processed_sequences.push(ProcessedSequence {
+            name: imputed_aim_matrix.name.clone(),
+            animations: animation_indices.chunks(AIM_YAW_NAMES.len()).map(|row| row.to_vec()).collect(),
+            is_delta: true,
+            weight_list: vec![1.0; bone_table.processed_bones.len()],
+            parameters: [
+                sequence_parameter(&imputed_aim_matrix.yaw_parameter, imputed_aim_matrix.yaw_range, AIM_YAW_NAMES.len()),
+                sequence_parameter(&imputed_aim_matrix.pitch_parameter, imputed_aim_matrix.pitch_range, AIM_PITCH_NAMES.len()),
+            ],
+            fade_in_time: 0.2,
+            fade_out_time: 0.2,
+            ..Default::default()
+        });
+    }
+
     Ok(processed_sequences)
 }
+
+/// Creates the transition graph between the nodes of the sequences.
+/// Nodes that are not connected by a sequence are reached by going through the nodes in between.
+pub fn process_transition_nodes(
+    input: &ImputedCompilationData,
+    sequences: &[ProcessedSequence],
+) -> Result<Vec<ProcessedTransitionNode>, ProcessingAnimationError> {
+    // Nodes are numbered from one in a byte, zero is used for no node.
+    if input.transition_nodes.len() > u8::MAX as usize {
+        return Err(ProcessingAnimationError::TooManyTransitionNodes);
+    }
+
+    for (node_index, node_name) in input.transition_nodes.iter().enumerate() {
+        if input.transition_nodes[..node_index].contains(node_name) {
+            return Err(ProcessingAnimationError::DuplicateTransitionNode(node_name.clone()));
+        }
+    }
+
+    let node_count = input.transition_nodes.len();
+    let mut next_nodes = vec![vec![0u8; node_count]; node_count];
+    let mut used_nodes = vec![false; node_count];
+    for transition in sequences.iter().filter_map(|sequence| sequence.transition) {
+        used_nodes[transition.entry_node] = true;
+        used_nodes[transition.exit_node] = true;
+
+        if transition.entry_node == transition.exit_node {
+            continue;
+        }
+
+        next_nodes[transition.entry_node][transition.exit_node] = (transition.exit_node + 1) as u8;
+        if transition.reverse {
+            next_nodes[transition.exit_node][transition.entry_node] = (transition.entry_node + 1) as u8;
+        }
+    }
+
+    // Each pass only extends the paths found in the previous pass, so the shortest path to a node is taken.
+    loop {
+        let previous_next_nodes = next_nodes.clone();
+        let mut found_path = false;
+
+        for from_node in 0..node_count {
+            for to_node in 0..node_count {
+                if from_node == to_node || previous_next_nodes[from_node][to_node] != 0 {
+                    continue;
+                }
+
+                if let Some(through_node) =
+                    (0..node_count).find(|&through_node| previous_next_nodes[through_node][to_node] != 0 && previous_next_nodes[from_node][through_node] != 0)
+                {
+                    next_nodes[from_node][to_node] = previous_next_nodes[from_node][through_node];
+                    found_path = true;
+                }
+            }
+        }
+
+        if !found_path {
+            break;
+        }
+    }
+
+    let mut transition_nodes = Vec::with_capacity(node_count);
+    for ((node_name, next_nodes), used) in input.transition_nodes.iter().zip(next_nodes).zip(used_nodes) {
+        if !used {
+            log(format!("Transition Node \"{}\" Not Used By Any Sequence!", node_name), LogLevel::Warn);
+        }
+
+        transition_nodes.push(ProcessedTransitionNode {
+            name: node_name.clone(),
+            next_nodes,
+        });
+    }
+
+    Ok(transition_nodes)
+}
+
+/// Sets the bounding box of every sequence, sequences without one in the input get the extents of the meshes moved by their animations.
+pub fn calculate_sequence_bounding_boxes(
+    input: &ImputedCompilationData,
+    sequences: &mut [ProcessedSequence],
+    animations: &[ProcessedAnimation],
+    bone_table: &ProcessedBoneData,
+    model_data: &ProcessedModelData,
+) -> Result<(), ProcessingAnimationError> {
+    let bone_bounds = calculate_bone_bounds(bone_table, model_data);
+
+    let animation_bounds = animations
+        .par_iter()
+        .map(|animation| calculate_animation_bounds(animation, bone_table, &bone_bounds))
+        .collect::<Vec<_>>();
+
+    // Generated sequences come after the sequences of the input and have no bounding box of their own.
+    for (sequence_index, sequence) in sequences.iter_mut().enumerate() {
+        if let Some(bounding_box) = input
+            .sequences
+            .get(sequence_index)
+            .and_then(|input_sequence| input_sequence.bounding_box.as_ref())
+        {
+            sequence.bounding_box =
+                create_bounding_box(bounding_box).ok_or_else(|| ProcessingAnimationError::InvalidSequenceBoundingBox(sequence.name.clone()))?;
+            continue;
+        }
+
+        // Delta animations are only offsets from another pose, so they are given the size of the model.
+        if sequence.is_delta {
+            sequence.bounding_box = model_data.bounding_box;
+            continue;
+        }
+
+        let mut bounding_box = None;
+        for &animation_index in sequence.animations.iter().flatten() {
+            if let Some(animation_bounding_box) = &animation_bounds[animation_index as usize] {
+                extend_bounds(&mut bounding_box, animation_bounding_box.minimum);
+                extend_bounds(&mut bounding_box, animation_bounding_box.maximum);
+            }
+        }
+
+        sequence.bounding_box = bounding_box.unwrap_or(model_data.bounding_box);
+    }
+
+    Ok(())
+}
+
+/// Creates the bounds of the vertices weighted to each bone relative to the bone, every weighted bone is used so the bounds are never too small.
+/// Only the preview models of the body parts are used.
+fn calculate_bone_bounds(bone_table: &ProcessedBoneData, model_data: &ProcessedModelData) -> Vec<Option<BoundingBox>> {
+    let inverse_poses = bone_table.processed_bones.values().map(|bone| bone.pose.transpose()).collect::<Vec<_>>();
+    let mut bone_bounds = vec![None; bone_table.processed_bones.len()];
+
+    for mesh in model_data
+        .body_parts
+        .iter()
+        .flat_map(ProcessedBodyPart::preview_models)
+        .flat_map(|model| &model.meshes)
+    {
+        for vertex in &mesh.vertex_data {
+            for &bone in vertex.bones.iter().take(vertex.bone_count as usize) {
+                extend_bounds(&mut bone_bounds[bone as usize], inverse_poses[bone as usize].transform(vertex.position));
+            }
+        }
+    }
+
+    // Without any vertices the bones themselves are used.
+    if bone_bounds.iter().all(Option::is_none) {
+        bone_bounds.fill(Some(BoundingBox::default()));
+    }
+
+    bone_bounds
+}
+
+/// Moves the bone bounds to every frame of the animation and combines them.
+fn calculate_animation_bounds(animation: &ProcessedAnimation, bone_table: &ProcessedBoneData, bone_bounds: &[Option<BoundingBox>]) -> Option<BoundingBox> {
+    let mut bounding_box = None;
+    let mut bone_matrices = vec![Matrix4::identity(); bone_table.processed_bones.len()];
+
+    for section in &animation.sections {
+        let mut animated_bones = vec![None; bone_table.processed_bones.len()];
+        for animated_bone in section {
+            animated_bones[animated_bone.bone as usize] = Some(animated_bone);
+        }
+
+        let frame_count = section.first().map_or(1, |animated_bone| animated_bone.position.len());
+        for frame in 0..frame_count {
+            for (bone_index, bone) in bone_table.processed_bones.values().enumerate() {
+                let (position, rotation) = match animated_bones[bone_index] {
+                    Some(animated_bone) => (bone.position + animated_bone.position[frame], bone.rotation + animated_bone.rotation[frame]),
+                    None => (bone.position, bone.rotation),
+                };
+
+                let local_matrix = Matrix4::new(position, rotation.to_matrix());
+                bone_matrices[bone_index] = match bone.parent {
+                    Some(parent_index) => bone_matrices[parent_index] * local_matrix,
+                    None => local_matrix,
+                };
+
+                if let Some(bone_bounding_box) = &bone_bounds[bone_index] {
+                    for corner in bone_bounding_box.corners() {
+                        extend_bounds(&mut bounding_box, bone_matrices[bone_index].transform(corner));
+                    }
+                }
+            }
+        }
+    }
+
+    bounding_box
+}
+
+fn extend_bounds(bounds: &mut Option<BoundingBox>, point: Vector3) {
+    match bounds {
+        Some(bounding_box) => bounding_box.add_point(point),
+        None => {
+            *bounds = Some(BoundingBox {
+                minimum: point,
+                maximum: point,
+            })
+        }
+    }
+}
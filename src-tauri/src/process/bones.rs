@@ -1,28 +1,92 @@
+use std::collections::HashSet;
+
 use indexmap::IndexMap;
 use tauri::State;
 use thiserror::Error as ThisError;
 
 use crate::{
-    import::{FileManager, ImportPart},
-    input::ImputedCompilationData,
-    process::ProcessedRemappedBone,
+    import::{FileManager, ImportBone, ImportPart},
+    input::{ImputedBoneResolution, ImputedCompilationData, ImputedProcedural},
+    process::{ProcessedRemappedBone, MAX_BONES},
     utilities::{
         logging::{log, LogLevel},
-        mathematics::Matrix4,
+        mathematics::{Angles, Matrix4, Vector3},
     },
 };
 
-use super::{ProcessedBone, ProcessedBoneData, ProcessedBoneFlags};
+use super::{
+    ProcessedAttachment, ProcessedBone, ProcessedBoneData, ProcessedBoneFlags, ProcessedBoneFlexControl, ProcessedBoneFlexDriver,
+    ProcessedInverseKinematicChain, ProcessedJiggle, ProcessedMouth, ProcessedProcedural, ProcessedQuaternionTrigger, ProcessedSourceTransform,
+};
 
 #[derive(Debug, ThisError)]
 pub enum ProcessingBoneError {
     #[error("Animation File Source Not Loaded")]
     FileSourceNotLoaded,
-    #[error("Model Has Too Many Bone")]
-    TooManyBones,
+    #[error("Bone Limit Must Be Between 1 And {1}: {0}")]
+    InvalidBoneLimit(usize, usize),
+    #[error("Model Has {0} Bones But The Limit Is {1}")]
+    TooManyBones(usize, usize),
+    #[error("Attachment Bone Not Found: {0}")]
+    AttachmentBoneNotFound(String),
+    #[error("Attachment Bone {0} Has No Parent")]
+    AttachmentBoneHasNoParent(String),
+    #[error("Attachment Bone {0} Has Child Bones")]
+    AttachmentBoneHasChildren(String),
+    #[error("Attachment Bone {0} Is Used By Vertices")]
+    AttachmentBoneUsedByVertex(String),
+    #[error("Illumination Attachment Not Found: {0}")]
+    IlluminationAttachmentNotFound(String),
+    #[error("Procedural Bone Not Found: {0}")]
+    ProceduralBoneNotFound(String),
+    #[error("Procedural Bone {0} Is Already Procedural")]
+    DuplicateProceduralBone(String),
+    #[error("Procedural Bone {0} Can Not Control Itself")]
+    ProceduralBoneControlsItself(String),
+    #[error("Procedural Bone {0} Has No Triggers")]
+    ProceduralBoneHasNoTriggers(String),
+    #[error("Procedural Bone {0} Has A Trigger Tolerance That Is Not Above 0")]
+    InvalidTriggerTolerance(String),
+    #[error("Mouth Bone Not Found: {0}")]
+    MouthBoneNotFound(String),
+    #[error("Mouth On Bone {0} Has No Forward Direction")]
+    InvalidMouthForward(String),
+    #[error("Mouth On Bone {0} Has No Flex")]
+    MouthHasNoFlex(String),
+    #[error("Bone Flex Driver Bone Not Found: {0}")]
+    BoneFlexDriverBoneNotFound(String),
+    #[error("Bone {0} Already Has A Bone Flex Driver")]
+    DuplicateBoneFlexDriver(String),
+    #[error("Bone Flex Driver On Bone {0} Has No Controls")]
+    BoneFlexDriverHasNoControls(String),
+    #[error("Bone Flex Driver On Bone {0} Has A Control Without A Flex Controller")]
+    BoneFlexControlHasNoController(String),
+    #[error("Bone Flex Driver On Bone {0} Has An Invalid Range For Flex Controller {1}")]
+    InvalidBoneFlexControlRange(String, String),
+    #[error("Inverse Kinematic Chain {0} Could Not Find Bone {1}")]
+    InverseKinematicChainBoneNotFound(String, String),
+    #[error("Inverse Kinematic Chain {0} Needs Two Parents Above Its Bone")]
+    InverseKinematicChainTooShort(String),
+    #[error("Duplicate Inverse Kinematic Chain: {0}")]
+    DuplicateInverseKinematicChain(String),
+    #[error("Jiggle Chain Bone Not Found: {0}")]
+    JiggleChainBoneNotFound(String),
+    #[error("Jiggle Chain End Bone {1} Is Not Below Start Bone {0}")]
+    JiggleChainNotConnected(String, String),
+    #[error("Jiggle Chain Starting At {0} Has Invalid Settings")]
+    InvalidJiggleChain(String),
+    #[error("Bone Merge Bone Not Found: {0}")]
+    BoneMergeBoneNotFound(String),
+    #[error("Attachment Bone {0} Is Marked For Bone Merge")]
+    AttachmentBoneIsBoneMerged(String),
 }
 
 pub fn process_bones(input: &ImputedCompilationData, import: &State<FileManager>) -> Result<ProcessedBoneData, ProcessingBoneError> {
+    let bone_limit = input.engine_limits.bones;
+    if !(1..=MAX_BONES).contains(&bone_limit) {
+        return Err(ProcessingBoneError::InvalidBoneLimit(bone_limit, MAX_BONES));
+    }
+
     let mut source_bone_table: IndexMap<String, ProcessedBone> = IndexMap::new();
     let mut remapped_files = IndexMap::new();
 
@@ -39,16 +103,29 @@ pub fn process_bones(input: &ImputedCompilationData, import: &State<FileManager>
             for (import_bone_index, import_bone) in imported_file.skeleton.iter().enumerate() {
                 let bone_flags = create_bone_flags(import_bone_index, &imported_file.parts);
 
+                let global_parent_name = find_parent_name(&source_bone_table, &import_bone.name);
+                let import_parent_name = import_bone.parent.map(|parent_index| imported_file.skeleton[parent_index].name.clone());
+
                 if let Some((global_bone_index, _, global_bone)) = source_bone_table.get_full_mut(&import_bone.name) {
                     global_bone.flags.insert(bone_flags);
-                    remapped_bones.push(ProcessedRemappedBone { index: global_bone_index });
+
+                    // Model files always keep the first bind pose, so only unresolved conflicts are reported.
+                    if let Some(conflict) = find_bone_conflict(global_bone, global_parent_name, import_bone, import_parent_name) {
+                        if find_bone_resolution(input, &import_bone.name).is_none() {
+                            report_bone_conflict(global_bone, &import_bone.name, &imputed_model.file_source, conflict);
+                        }
+                    }
+
+                    remapped_bones.push(ProcessedRemappedBone {
+                        index: Some(global_bone_index),
+                    });
                     continue;
                 }
 
-                let processed_parent = import_bone.parent.map(|parent_index| remapped_bones[parent_index].index);
+                let processed_parent = import_bone.parent.and_then(|parent_index| remapped_bones[parent_index].index);
 
                 remapped_bones.push(ProcessedRemappedBone {
-                    index: source_bone_table.len(),
+                    index: Some(source_bone_table.len()),
                 });
                 source_bone_table.insert(
                     import_bone.name.clone(),
@@ -56,6 +133,7 @@ pub fn process_bones(input: &ImputedCompilationData, import: &State<FileManager>
                         parent: processed_parent,
                         position: import_bone.position,
                         rotation: import_bone.orientation.to_angles().normalize(),
+                        source_pose: Matrix4::new(import_bone.position, import_bone.orientation.to_matrix()),
                         flags: bone_flags,
                         ..Default::default()
                     },
@@ -66,6 +144,9 @@ pub fn process_bones(input: &ImputedCompilationData, import: &State<FileManager>
         }
     }
 
+    // Only the first animation that differs gives its bind pose to a bone resolved to use the animation.
+    let mut animation_posed_bones = HashSet::new();
+
     for imputed_animation in &input.animations {
         if remapped_files.contains_key(&imputed_animation.file_source) {
             continue;
@@ -78,15 +159,37 @@ pub fn process_bones(input: &ImputedCompilationData, import: &State<FileManager>
         let mut remapped_bones = Vec::with_capacity(imported_file.skeleton.len());
 
         for import_bone in &imported_file.skeleton {
-            if let Some(global_bone_index) = source_bone_table.get_index_of(&import_bone.name) {
-                remapped_bones.push(ProcessedRemappedBone { index: global_bone_index });
+            let global_parent_name = find_parent_name(&source_bone_table, &import_bone.name);
+            let import_parent_name = import_bone.parent.map(|parent_index| imported_file.skeleton[parent_index].name.clone());
+
+            if let Some((global_bone_index, _, global_bone)) = source_bone_table.get_full_mut(&import_bone.name) {
+                let conflict = find_bone_conflict(global_bone, global_parent_name, import_bone, import_parent_name);
+
+                let index = match (conflict, find_bone_resolution(input, &import_bone.name)) {
+                    (None, _) | (Some(_), Some(ImputedBoneResolution::UseModel)) => Some(global_bone_index),
+                    (Some(_), Some(ImputedBoneResolution::UseAnimation)) => {
+                        if animation_posed_bones.insert(import_bone.name.clone()) {
+                            global_bone.position = import_bone.position;
+                            global_bone.rotation = import_bone.orientation.to_angles().normalize();
+                            global_bone.source_pose = Matrix4::new(import_bone.position, import_bone.orientation.to_matrix());
+                        }
+                        Some(global_bone_index)
+                    }
+                    (Some(_), Some(ImputedBoneResolution::Ignore)) => None,
+                    (Some(conflict), None) => {
+                        report_bone_conflict(global_bone, &import_bone.name, &imputed_animation.file_source, conflict);
+                        Some(global_bone_index)
+                    }
+                };
+
+                remapped_bones.push(ProcessedRemappedBone { index });
                 continue;
             }
 
-            let processed_parent = import_bone.parent.map(|parent_index| remapped_bones[parent_index].index);
+            let processed_parent = import_bone.parent.and_then(|parent_index| remapped_bones[parent_index].index);
 
             remapped_bones.push(ProcessedRemappedBone {
-                index: source_bone_table.len(),
+                index: Some(source_bone_table.len()),
             });
             source_bone_table.insert(
                 import_bone.name.clone(),
@@ -94,6 +197,7 @@ pub fn process_bones(input: &ImputedCompilationData, import: &State<FileManager>
                     parent: processed_parent,
                     position: import_bone.position,
                     rotation: import_bone.orientation.to_angles().normalize(),
+                    source_pose: Matrix4::new(import_bone.position, import_bone.orientation.to_matrix()),
                     ..Default::default()
                 },
             );
@@ -104,14 +208,43 @@ pub fn process_bones(input: &ImputedCompilationData, import: &State<FileManager>
 
     log(format!("Model uses {} source bones.", source_bone_table.len()), LogLevel::Debug);
 
+    let attachments = convert_bones_to_attachments(input, &mut source_bone_table, &mut remapped_files)?;
+
+    let illumination_attachment = match &input.illumination_attachment {
+        Some(name) => Some(
+            attachments
+                .iter()
+                .position(|attachment| &attachment.name == name)
+                .ok_or_else(|| ProcessingBoneError::IlluminationAttachmentNotFound(name.clone()))?,
+        ),
+        None => None,
+    };
+
+    create_procedural_bones(input, &mut source_bone_table)?;
+
+    create_jiggle_chains(input, &mut source_bone_table)?;
+
+    let mouths = create_mouths(input, &source_bone_table)?;
+
+    let bone_flex_drivers = create_bone_flex_drivers(input, &source_bone_table)?;
+
+    tag_bone_merge_bones(input, &mut source_bone_table)?;
+
     // TODO: Tag bones from input data
 
     // TODO: Enforce skeleton hierarchy
 
     // TODO: Collapse bones
 
-    if source_bone_table.len() > (i8::MAX as usize) + 1 {
-        return Err(ProcessingBoneError::TooManyBones);
+    if source_bone_table.len() > bone_limit {
+        report_bone_budget(import, &source_bone_table, &remapped_files, bone_limit);
+        return Err(ProcessingBoneError::TooManyBones(source_bone_table.len(), bone_limit));
+    }
+
+    // Moving the root bones moves every bone below them, the meshes and animations are moved the same way.
+    let origin = Vector3::new(input.origin[0], input.origin[1], input.origin[2]);
+    for bone in source_bone_table.values_mut().filter(|bone| bone.parent.is_none()) {
+        bone.position = bone.position - origin;
     }
 
     // Build bone pose matrices
@@ -124,7 +257,23 @@ pub fn process_bones(input: &ImputedCompilationData, import: &State<FileManager>
         };
     }
 
-    let mut sorted_bones_by_name: Vec<u8> = (0..source_bone_table.len() as u8).collect();
+    // Record how bones were moved away from their imported bind pose
+    for bone in source_bone_table.values_mut() {
+        let post = bone.source_pose.transpose() * Matrix4::new(bone.position, bone.rotation.to_matrix());
+
+        if is_identity_transform(&post) {
+            continue;
+        }
+
+        bone.source_transform = Some(ProcessedSourceTransform {
+            pre: Matrix4::identity(),
+            post,
+        });
+    }
+
+    let inverse_kinematic_chains = create_inverse_kinematic_chains(input, &source_bone_table)?;
+
+    let mut sorted_bones_by_name: Vec<u8> = (0..source_bone_table.len()).map(|bone_index| bone_index as u8).collect();
     sorted_bones_by_name.sort_by(|from, to| {
         let bone_from = source_bone_table.get_index(*from as usize).unwrap().0;
         let bone_to = source_bone_table.get_index(*to as usize).unwrap().0;
@@ -135,9 +284,470 @@ pub fn process_bones(input: &ImputedCompilationData, import: &State<FileManager>
         processed_bones: source_bone_table,
         remapped_bones: remapped_files,
         sorted_bones_by_name,
+        attachments,
+        illumination_attachment,
+        mouths,
+        bone_flex_drivers,
+        inverse_kinematic_chains,
+    })
+}
+
+/// Logs the bones past the bone limit and the bones with the least vertex influence, which lose the least when collapsed into their parents.
+fn report_bone_budget(
+    import: &State<FileManager>,
+    source_bone_table: &IndexMap<String, ProcessedBone>,
+    remapped_files: &IndexMap<String, Vec<ProcessedRemappedBone>>,
+    bone_limit: usize,
+) {
+    let bones_past_limit = source_bone_table.keys().skip(bone_limit).cloned().collect::<Vec<_>>();
+    log(
+        format!("Bones Past The Limit Of {}: {}", bone_limit, bones_past_limit.join(", ")),
+        LogLevel::Error,
+    );
+
+    let mut influences = vec![0.0; source_bone_table.len()];
+    for (file_source, remapped_bones) in remapped_files {
+        let Some(imported_file) = import.get_file(file_source) else {
+            continue;
+        };
+
+        for link in imported_file.parts.iter().flat_map(|part| &part.vertices).flat_map(|vertex| &vertex.links) {
+            if let Some(bone_index) = remapped_bones[link.bone].index {
+                influences[bone_index] += link.weight;
+            }
+        }
+    }
+
+    // Root bones have no parent to collapse into.
+    let mut candidates = (0..source_bone_table.len())
+        .filter(|&bone_index| source_bone_table[bone_index].parent.is_some())
+        .collect::<Vec<_>>();
+    candidates.sort_by(|&from, &to| influences[from].total_cmp(&influences[to]));
+
+    let candidates = candidates
+        .iter()
+        .take(source_bone_table.len() - bone_limit)
+        .map(|&bone_index| format!("{} ({:.2})", source_bone_table.get_index(bone_index).unwrap().0, influences[bone_index]))
+        .collect::<Vec<_>>();
+    log(format!("Collapse Candidates By Vertex Influence: {}", candidates.join(", ")), LogLevel::Warn);
+}
+
+/// Removes the bones marked as attachments from the bone table, keeping their transform relative to their parent.
+fn convert_bones_to_attachments(
+    input: &ImputedCompilationData,
+    source_bone_table: &mut IndexMap<String, ProcessedBone>,
+    remapped_files: &mut IndexMap<String, Vec<ProcessedRemappedBone>>,
+) -> Result<Vec<ProcessedAttachment>, ProcessingBoneError> {
+    let mut attachments: Vec<ProcessedAttachment> = Vec::with_capacity(input.bone_attachments.len());
+
+    for imputed_attachment in &input.bone_attachments {
+        let (bone_index, _, bone) = match source_bone_table.get_full(&imputed_attachment.bone) {
+            Some(bone) => bone,
+            None => return Err(ProcessingBoneError::AttachmentBoneNotFound(imputed_attachment.bone.clone())),
+        };
+
+        let parent_index = match bone.parent {
+            Some(parent) => parent,
+            None => return Err(ProcessingBoneError::AttachmentBoneHasNoParent(imputed_attachment.bone.clone())),
+        };
+
+        if source_bone_table.values().any(|other_bone| other_bone.parent == Some(bone_index)) {
+            return Err(ProcessingBoneError::AttachmentBoneHasChildren(imputed_attachment.bone.clone()));
+        }
+
+        // Attachment bones are removed from the bone table, so other models could not merge onto them.
+        if input.bone_merge_bones.contains(&imputed_attachment.bone) {
+            return Err(ProcessingBoneError::AttachmentBoneIsBoneMerged(imputed_attachment.bone.clone()));
+        }
+
+        if bone.flags.contains(ProcessedBoneFlags::USED_BY_VERTEX) {
+            return Err(ProcessingBoneError::AttachmentBoneUsedByVertex(imputed_attachment.bone.clone()));
+        }
+
+        let (_, removed_bone) = source_bone_table.shift_remove_index(bone_index).unwrap();
+
+        // Every bone after the removed bone has shifted down by one.
+        let shift_index = |index: usize| if index > bone_index { index - 1 } else { index };
+
+        for processed_bone in source_bone_table.values_mut() {
+            processed_bone.parent = processed_bone.parent.map(shift_index);
+        }
+
+        for remapped_bones in remapped_files.values_mut() {
+            for remapped_bone in remapped_bones {
+                remapped_bone.index = match remapped_bone.index {
+                    Some(index) if index == bone_index => None,
+                    index => index.map(shift_index),
+                };
+            }
+        }
+
+        for attachment in &mut attachments {
+            attachment.bone = shift_index(attachment.bone);
+        }
+
+        let parent_index = shift_index(parent_index);
+        source_bone_table[parent_index].flags.insert(ProcessedBoneFlags::USED_BY_ATTACHMENT);
+
+        attachments.push(ProcessedAttachment {
+            name: imputed_attachment.name.clone(),
+            bone: parent_index,
+            position: removed_bone.position,
+            rotation: removed_bone.rotation,
+        });
+    }
+
+    Ok(attachments)
+}
+
+/// Finds the bones of the mouths, this is done after the attachments are removed so the bone indices are final.
+fn create_mouths(input: &ImputedCompilationData, source_bone_table: &IndexMap<String, ProcessedBone>) -> Result<Vec<ProcessedMouth>, ProcessingBoneError> {
+    let mut mouths = Vec::with_capacity(input.mouths.len());
+
+    for imputed_mouth in &input.mouths {
+        let bone = source_bone_table
+            .get_index_of(&imputed_mouth.bone)
+            .ok_or_else(|| ProcessingBoneError::MouthBoneNotFound(imputed_mouth.bone.clone()))?;
+
+        let forward = Vector3::new(imputed_mouth.forward[0], imputed_mouth.forward[1], imputed_mouth.forward[2]);
+        if !forward.is_finite() || forward.magnitude() < f64::EPSILON {
+            return Err(ProcessingBoneError::InvalidMouthForward(imputed_mouth.bone.clone()));
+        }
+
+        if imputed_mouth.flex.is_empty() {
+            return Err(ProcessingBoneError::MouthHasNoFlex(imputed_mouth.bone.clone()));
+        }
+
+        mouths.push(ProcessedMouth {
+            bone,
+            forward: forward.normalize(),
+            flex: imputed_mouth.flex.clone(),
+            flex_controller: if imputed_mouth.flex_controller.is_empty() {
+                imputed_mouth.flex.clone()
+            } else {
+                imputed_mouth.flex_controller.clone()
+            },
+        });
+    }
+
+    Ok(mouths)
+}
+
+/// Finds the bones of the bone flex drivers and checks the ranges of their controls.
+fn create_bone_flex_drivers(
+    input: &ImputedCompilationData,
+    source_bone_table: &IndexMap<String, ProcessedBone>,
+) -> Result<Vec<ProcessedBoneFlexDriver>, ProcessingBoneError> {
+    let mut bone_flex_drivers: Vec<ProcessedBoneFlexDriver> = Vec::with_capacity(input.bone_flex_drivers.len());
+
+    for imputed_bone_flex_driver in &input.bone_flex_drivers {
+        let bone = source_bone_table
+            .get_index_of(&imputed_bone_flex_driver.bone)
+            .ok_or_else(|| ProcessingBoneError::BoneFlexDriverBoneNotFound(imputed_bone_flex_driver.bone.clone()))?;
+
+        if bone_flex_drivers.iter().any(|bone_flex_driver| bone_flex_driver.bone == bone) {
+            return Err(ProcessingBoneError::DuplicateBoneFlexDriver(imputed_bone_flex_driver.bone.clone()));
+        }
+
+        if imputed_bone_flex_driver.controls.is_empty() {
+            return Err(ProcessingBoneError::BoneFlexDriverHasNoControls(imputed_bone_flex_driver.bone.clone()));
+        }
+
+        let mut controls = Vec::with_capacity(imputed_bone_flex_driver.controls.len());
+        for imputed_control in &imputed_bone_flex_driver.controls {
+            if imputed_control.flex_controller.is_empty() {
+                return Err(ProcessingBoneError::BoneFlexControlHasNoController(imputed_bone_flex_driver.bone.clone()));
+            }
+
+            // The engine divides by the range, so an empty range can not be mapped to the flex controller.
+            let range = imputed_control.maximum - imputed_control.minimum;
+            if !range.is_finite() || range.abs() < f64::EPSILON {
+                return Err(ProcessingBoneError::InvalidBoneFlexControlRange(
+                    imputed_bone_flex_driver.bone.clone(),
+                    imputed_control.flex_controller.clone(),
+                ));
+            }
+
+            controls.push(ProcessedBoneFlexControl {
+                component: imputed_control.component as usize,
+                flex_controller: imputed_control.flex_controller.clone(),
+                minimum: imputed_control.minimum,
+                maximum: imputed_control.maximum,
+            });
+        }
+
+        bone_flex_drivers.push(ProcessedBoneFlexDriver { bone, controls });
+    }
+
+    Ok(bone_flex_drivers)
+}
+
+/// Creates the inverse kinematic chains from their end bone and its two parents, this needs the bone poses to be built.
+fn create_inverse_kinematic_chains(
+    input: &ImputedCompilationData,
+    source_bone_table: &IndexMap<String, ProcessedBone>,
+) -> Result<Vec<ProcessedInverseKinematicChain>, ProcessingBoneError> {
+    let mut inverse_kinematic_chains: Vec<ProcessedInverseKinematicChain> = Vec::with_capacity(input.inverse_kinematic_chains.len());
+
+    for imputed_chain in &input.inverse_kinematic_chains {
+        if inverse_kinematic_chains.iter().any(|chain| chain.name == imputed_chain.name) {
+            return Err(ProcessingBoneError::DuplicateInverseKinematicChain(imputed_chain.name.clone()));
+        }
+
+        let end_bone = source_bone_table
+            .get_index_of(&imputed_chain.bone)
+            .ok_or_else(|| ProcessingBoneError::InverseKinematicChainBoneNotFound(imputed_chain.name.clone(), imputed_chain.bone.clone()))?;
+
+        let middle_bone = source_bone_table[end_bone]
+            .parent
+            .ok_or_else(|| ProcessingBoneError::InverseKinematicChainTooShort(imputed_chain.name.clone()))?;
+
+        let root_bone = source_bone_table[middle_bone]
+            .parent
+            .ok_or_else(|| ProcessingBoneError::InverseKinematicChainTooShort(imputed_chain.name.clone()))?;
+
+        // The engine rotates the knee direction by the root bone, so it is moved out of model space.
+        let inverse_root_pose = source_bone_table[root_bone].pose.transpose();
+        let knee_direction = Vector3::new(
+            imputed_chain.knee_direction[0],
+            imputed_chain.knee_direction[1],
+            imputed_chain.knee_direction[2],
+        );
+
+        inverse_kinematic_chains.push(ProcessedInverseKinematicChain {
+            name: imputed_chain.name.clone(),
+            bones: [root_bone, middle_bone, end_bone],
+            knee_direction: inverse_root_pose.transform(knee_direction) - inverse_root_pose.transform(Vector3::default()),
+        });
+    }
+
+    Ok(inverse_kinematic_chains)
+}
+
+/// Sets up the procedural bones, this is done after the attachments are removed so the bone indices are final.
+fn create_procedural_bones(input: &ImputedCompilationData, source_bone_table: &mut IndexMap<String, ProcessedBone>) -> Result<(), ProcessingBoneError> {
+    let degrees_to_angles = |angles: [f64; 3]| Angles::new(angles[0], angles[1], angles[2]).to_radians();
+    let to_vector = |vector: [f64; 3]| Vector3::new(vector[0], vector[1], vector[2]);
+
+    for imputed_procedural_bone in &input.procedural_bones {
+        let bone_index = source_bone_table
+            .get_index_of(&imputed_procedural_bone.bone)
+            .ok_or_else(|| ProcessingBoneError::ProceduralBoneNotFound(imputed_procedural_bone.bone.clone()))?;
+
+        let control = source_bone_table
+            .get_index_of(&imputed_procedural_bone.control_bone)
+            .ok_or_else(|| ProcessingBoneError::ProceduralBoneNotFound(imputed_procedural_bone.control_bone.clone()))?;
+
+        if control == bone_index {
+            return Err(ProcessingBoneError::ProceduralBoneControlsItself(imputed_procedural_bone.bone.clone()));
+        }
+
+        let bone = &mut source_bone_table[bone_index];
+
+        if bone.procedural.is_some() {
+            return Err(ProcessingBoneError::DuplicateProceduralBone(imputed_procedural_bone.bone.clone()));
+        }
+
+        let procedural = match &imputed_procedural_bone.procedural {
+            ImputedProcedural::AxisInterpolation { axis, positions, rotations } => ProcessedProcedural::AxisInterpolation {
+                control,
+                axis: *axis as usize,
+                positions: Box::new(positions.map(|position| bone.position + to_vector(position))),
+                rotations: Box::new(rotations.map(|rotation| degrees_to_angles(rotation).to_quaternion())),
+            },
+            ImputedProcedural::QuaternionInterpolation { triggers } => {
+                if triggers.is_empty() {
+                    return Err(ProcessingBoneError::ProceduralBoneHasNoTriggers(imputed_procedural_bone.bone.clone()));
+                }
+
+                let mut processed_triggers = Vec::with_capacity(triggers.len());
+                for trigger in triggers {
+                    if trigger.tolerance <= 0.0 || trigger.tolerance.is_nan() {
+                        return Err(ProcessingBoneError::InvalidTriggerTolerance(imputed_procedural_bone.bone.clone()));
+                    }
+
+                    processed_triggers.push(ProcessedQuaternionTrigger {
+                        inverse_tolerance: 1.0 / trigger.tolerance.to_radians(),
+                        trigger: degrees_to_angles(trigger.trigger).to_quaternion(),
+                        position: bone.position + to_vector(trigger.position),
+                        rotation: degrees_to_angles(trigger.rotation).to_quaternion(),
+                    });
+                }
+
+                ProcessedProcedural::QuaternionInterpolation {
+                    control,
+                    triggers: processed_triggers,
+                }
+            }
+        };
+
+        bone.procedural = Some(procedural);
+        bone.flags.insert(ProcessedBoneFlags::ALWAYS_PROCEDURAL);
+    }
+
+    Ok(())
+}
+
+/// Turns the bones of the jiggle chains into jiggle bones, each reaching to the next bone in the chain.
+fn create_jiggle_chains(input: &ImputedCompilationData, source_bone_table: &mut IndexMap<String, ProcessedBone>) -> Result<(), ProcessingBoneError> {
+    for imputed_jiggle_chain in &input.jiggle_chains {
+        let find_bone = |name: &String| {
+            source_bone_table
+                .get_index_of(name)
+                .ok_or_else(|| ProcessingBoneError::JiggleChainBoneNotFound(name.clone()))
+        };
+        let start_bone = find_bone(&imputed_jiggle_chain.start_bone)?;
+        let end_bone = find_bone(&imputed_jiggle_chain.end_bone)?;
+
+        let settings = [imputed_jiggle_chain.tip_mass, imputed_jiggle_chain.angle_limit]
+            .into_iter()
+            .chain(imputed_jiggle_chain.stiffness)
+            .chain(imputed_jiggle_chain.damping);
+        if settings.into_iter().any(|setting| setting.is_nan() || setting < 0.0) || imputed_jiggle_chain.angle_limit > 180.0 {
+            return Err(ProcessingBoneError::InvalidJiggleChain(imputed_jiggle_chain.start_bone.clone()));
+        }
+
+        // The chain is walked up from the tip, so it is collected in reverse.
+        let mut chain = vec![end_bone];
+        while chain[chain.len() - 1] != start_bone {
+            match source_bone_table[chain[chain.len() - 1]].parent {
+                Some(parent) => chain.push(parent),
+                None => {
+                    return Err(ProcessingBoneError::JiggleChainNotConnected(
+                        imputed_jiggle_chain.start_bone.clone(),
+                        imputed_jiggle_chain.end_bone.clone(),
+                    ))
+                }
+            }
+        }
+        chain.reverse();
+
+        if chain.len() < 2 {
+            return Err(ProcessingBoneError::InvalidJiggleChain(imputed_jiggle_chain.start_bone.clone()));
+        }
+
+        let jiggle_count = chain.len() - 1;
+        for (chain_index, pair) in chain.windows(2).enumerate() {
+            let (bone_index, tip_index) = (pair[0], pair[1]);
+            let blend = if jiggle_count > 1 {
+                chain_index as f64 / (jiggle_count - 1) as f64
+            } else {
+                0.0
+            };
+            let interpolate = |range: [f64; 2]| range[0] + (range[1] - range[0]) * blend;
+
+            let length = source_bone_table[tip_index].position.magnitude();
+            let bone = &mut source_bone_table[bone_index];
+            if bone.procedural.is_some() {
+                return Err(ProcessingBoneError::DuplicateProceduralBone(
+                    source_bone_table.get_index(bone_index).unwrap().0.clone(),
+                ));
+            }
+
+            bone.procedural = Some(ProcessedProcedural::Jiggle(ProcessedJiggle {
+                length,
+                tip_mass: imputed_jiggle_chain.tip_mass,
+                stiffness: interpolate(imputed_jiggle_chain.stiffness),
+                damping: interpolate(imputed_jiggle_chain.damping),
+                angle_limit: (imputed_jiggle_chain.angle_limit > 0.0).then(|| imputed_jiggle_chain.angle_limit.to_radians()),
+            }));
+        }
+    }
+
+    Ok(())
+}
+
+/// How far apart bind poses of the same bone can be before the source files are considered to disagree.
+const BIND_POSE_TOLERANCE: f64 = 0.001;
+
+#[derive(Clone, Copy, Debug)]
+enum BoneConflict {
+    Parent,
+    BindPose,
+}
+
+fn find_parent_name(source_bone_table: &IndexMap<String, ProcessedBone>, bone_name: &str) -> Option<String> {
+    let parent_index = source_bone_table.get(bone_name)?.parent?;
+    source_bone_table.get_index(parent_index).map(|(parent_name, _)| parent_name.clone())
+}
+
+fn find_bone_resolution(input: &ImputedCompilationData, bone_name: &str) -> Option<ImputedBoneResolution> {
+    input
+        .bone_conflicts
+        .iter()
+        .find(|bone_conflict| bone_conflict.bone == bone_name)
+        .map(|bone_conflict| bone_conflict.resolution)
+}
+
+/// Compares a bone of a source file with the same bone already in the bone table.
+fn find_bone_conflict(
+    global_bone: &ProcessedBone,
+    global_parent_name: Option<String>,
+    import_bone: &ImportBone,
+    import_parent_name: Option<String>,
+) -> Option<BoneConflict> {
+    if global_parent_name != import_parent_name {
+        return Some(BoneConflict::Parent);
+    }
+
+    let position_difference = (global_bone.position - import_bone.position).magnitude();
+
+    let global_rotation = global_bone.rotation.to_quaternion();
+    let rotation_dot = global_rotation.x * import_bone.orientation.x
+        + global_rotation.y * import_bone.orientation.y
+        + global_rotation.z * import_bone.orientation.z
+        + global_rotation.w * import_bone.orientation.w;
+
+    if position_difference <= BIND_POSE_TOLERANCE && 1.0 - rotation_dot.abs() <= BIND_POSE_TOLERANCE {
+        return None;
+    }
+
+    Some(BoneConflict::BindPose)
+}
+
+/// Records the source file on the bone so the conflict can be resolved.
+fn report_bone_conflict(global_bone: &mut ProcessedBone, bone_name: &str, file_source: &str, conflict: BoneConflict) {
+    if global_bone.conflicting_sources.iter().any(|source| source == file_source) {
+        return;
+    }
+
+    let difference = match conflict {
+        BoneConflict::Parent => "parent",
+        BoneConflict::BindPose => "bind pose",
+    };
+
+    log(
+        format!(
+            "Bone {} in {} has a different {} than the first source with it.",
+            bone_name, file_source, difference
+        ),
+        LogLevel::Warn,
+    );
+    global_bone.conflicting_sources.push(file_source.to_string());
+}
+
+fn is_identity_transform(transform: &Matrix4) -> bool {
+    let identity = Matrix4::identity();
+
+    transform.entries.iter().zip(identity.entries.iter()).all(|(row, identity_row)| {
+        row.iter()
+            .zip(identity_row.iter())
+            .all(|(entry, identity_entry)| (entry - identity_entry).abs() <= BIND_POSE_TOLERANCE)
     })
 }
 
+/// Marks the bones other models merge onto, the bone usage keeps them set up when no vertex uses them.
+fn tag_bone_merge_bones(input: &ImputedCompilationData, source_bone_table: &mut IndexMap<String, ProcessedBone>) -> Result<(), ProcessingBoneError> {
+    for bone_name in &input.bone_merge_bones {
+        let bone = source_bone_table
+            .get_mut(bone_name)
+            .ok_or_else(|| ProcessingBoneError::BoneMergeBoneNotFound(bone_name.clone()))?;
+        bone.flags.insert(ProcessedBoneFlags::USED_BY_BONE_MERGE);
+    }
+
+    Ok(())
+}
+
 fn create_bone_flags(bone_index: usize, import_parts: &[ImportPart]) -> ProcessedBoneFlags {
     let mut flags = ProcessedBoneFlags::default();
 
@@ -1,23 +1,30 @@
 use core::f64;
+use std::{
+    collections::{HashMap, HashSet},
+    sync::atomic::{AtomicUsize, Ordering},
+};
 
 use indexmap::{IndexMap, IndexSet};
 use kdtree::{distance::squared_euclidean, KdTree};
+use rayon::prelude::*;
 use tauri::State;
 use thiserror::Error as ThisError;
 
 use crate::{
-    import::{FileManager, ImportPart, ImportVertex},
-    input::ImputedCompilationData,
+    import::{FileManager, ImportBone, ImportFileData, ImportLink, ImportVertex},
+    input::{ImputedCompilationData, ImputedMaterialRename, ImputedModel},
     process::{
-        ProcessedHardwareBone, ProcessedMeshVertex, ProcessedStrip, ProcessedStripGroup, ProcessedVertex, MAX_HARDWARE_BONES_PER_STRIP, VERTEX_CACHE_SIZE,
+        ProcessedFlex, ProcessedFlexVertex, ProcessedHardwareBone, ProcessedMeshLimits, ProcessedMeshVertex, ProcessedStrip, ProcessedStripGroup,
+        ProcessedVertex, MAX_HARDWARE_BONES_PER_STRIP, MAX_MATERIALS, MAX_WEIGHTS_PER_VERTEX, VERTEX_CACHE_SIZE,
     },
     utilities::{
         logging::{log, LogLevel},
-        mathematics::{BoundingBox, Vector2, Vector3, Vector4},
+        mathematics::{BoundingBox, Matrix4, Vector2, Vector3, Vector4},
+        progress::{is_cancelled, progress},
     },
 };
 
-use super::{ProcessedBodyPart, ProcessedBoneData, ProcessedMesh, ProcessedModel, ProcessedModelData, ProcessedRemappedBone, FLOAT_TOLERANCE};
+use super::{ProcessedBodyPart, ProcessedBoneData, ProcessedMesh, ProcessedMeshSplit, ProcessedModel, ProcessedModelData, ProcessedRemappedBone};
 
 #[derive(Debug, ThisError)]
 pub enum ProcessingMeshError {
@@ -27,24 +34,87 @@ pub enum ProcessingMeshError {
     PartNotFound(String),
     #[error("Face Was Incomplete")]
     IncompleteFace,
-    #[error("Model Has Too Many Materials")]
-    TooManyMaterials,
+    #[error("Part {0} Has A Polygon With {1} Vertices Which Is Over The Limit Of {2}")]
+    PolygonTooLarge(String, usize, usize),
+    #[error("Normal Smoothing Angle Must Be Between 0 And 180: {0}")]
+    InvalidSmoothingAngle(f64),
+    #[error("Seam Normal Tolerance Must Be A Positive Distance: {0}")]
+    InvalidSeamNormalTolerance(f64),
+    #[error("Part {0} Has Texture Coordinates In UDIM Tile {1}")]
+    UdimTextureCoordinates(String, i64),
+    #[error("Weld Tolerances Must Not Be Negative")]
+    NegativeWeldTolerance,
+    #[error("Weld Normal Angle Must Be Between 0 And 180: {0}")]
+    InvalidWeldNormalAngle(f64),
+    #[error("Polygon Vertex Limit Must Be At Least 3: {0}")]
+    InvalidPolygonLimit(usize),
+    #[error("Material Limit Must Be Between 1 And {1}: {0}")]
+    InvalidMaterialLimit(usize, usize),
+    #[error("Model Has {0} Materials But The Limit Is {1}")]
+    TooManyMaterials(usize, usize),
     #[error("Model Has Too Many Body Parts")]
     TooManyBodyParts,
+    #[error("Default Model {1} Of Body Part {0} Does Not Exist")]
+    DefaultModelNotFound(String, usize),
+    #[error("Preview Model {1} Of Body Part {0} Does Not Exist")]
+    PreviewModelNotFound(String, usize),
+    #[error("Hardware Bones Per Strip Must Be Between {1} And {2}: {0}")]
+    InvalidHardwareBonesPerStrip(usize, usize, usize),
+    #[error("Weights Per Vertex Must Be Between 1 And {1}: {0}")]
+    InvalidWeightsPerVertex(usize, usize),
+    #[error("Vertex Cache Size Must Be Between 3 And {1}: {0}")]
+    InvalidVertexCacheSize(usize, usize),
+    #[error("Overdraw Threshold Must Be At Least 1: {0}")]
+    InvalidOverdrawThreshold(f64),
+    #[error("Flex File Source Not Loaded")]
+    FlexFileSourceNotLoaded,
+    #[error("Flex File Of Model {0} Has No Flexes")]
+    NoFlexes(String),
 }
 
-#[derive(Debug, Default)]
+#[derive(Clone, Debug, Default)]
 struct WeightLink {
     bone: u8,
     weight: f64,
 }
 
-#[derive(Debug, Default)]
+#[derive(Clone, Debug, Default)]
 struct TriangleVertex {
     position: Vector3,
     normal: Vector3,
     texture_coordinate: Vector2,
     links: Vec<WeightLink>,
+    flex_deltas: Vec<FlexDelta>,
+}
+
+/// How far a vertex moves when a flex is fully applied, the flex is an index into the flexes of the model data.
+#[derive(Debug, Default, Clone, Copy)]
+struct FlexDelta {
+    flex: usize,
+    position: Vector3,
+    normal: Vector3,
+}
+
+const MAX_REPORTED_BAD_VERTICES: usize = 16;
+/// Model versions with a vertex color stream in the vertex file, none of the versions that can be written have one.
+const VERTEX_COLOR_VERSIONS: [i32; 0] = [];
+/// The extensions exporters leave on material names from the texture file the material was made from.
+const MATERIAL_FILE_EXTENSIONS: [&str; 9] = ["tga", "bmp", "png", "jpg", "jpeg", "psd", "dds", "vtf", "vmt"];
+
+/// The weld tolerances in the form they are compared in.
+#[derive(Debug, Clone, Copy)]
+struct WeldTolerances {
+    position_squared: f64,
+    normal_cosine: f64,
+    texture_coordinate: f64,
+}
+
+/// The imported file a model is made from, with its bones and flexes mapped to the model.
+struct ModelSource<'a> {
+    imported_file: &'a ImportFileData,
+    mapped_bones: &'a [ProcessedRemappedBone],
+    /// The flex deltas of every vertex of every part of the imported file, empty when the model has no flexes.
+    flex_deltas: Vec<Vec<Vec<FlexDelta>>>,
 }
 
 #[derive(Debug)]
@@ -53,6 +123,8 @@ struct TriangleList {
     vertex_tree: KdTree<f64, usize, [f64; 3]>,
     tangents: Vec<Vector4>,
     triangles: Vec<[usize; 3]>,
+    /// The part every triangle was made from, this is only in order with the triangles until they are optimized.
+    triangle_parts: Vec<usize>,
 }
 
 impl Default for TriangleList {
@@ -62,6 +134,7 @@ impl Default for TriangleList {
             vertex_tree: KdTree::new(3),
             tangents: Vec::new(),
             triangles: Vec::new(),
+            triangle_parts: Vec::new(),
         }
     }
 }
@@ -71,12 +144,67 @@ pub fn process_meshes(
     import: &State<FileManager>,
     processed_bone_data: &ProcessedBoneData,
 ) -> Result<ProcessedModelData, ProcessingMeshError> {
-    let mut processed_model_data = ProcessedModelData::default();
+    let limits = process_mesh_limits(input)?;
+    let material_limit = input.engine_limits.materials;
+    if !(1..=MAX_MATERIALS).contains(&material_limit) {
+        return Err(ProcessingMeshError::InvalidMaterialLimit(material_limit, MAX_MATERIALS));
+    }
 
-    let mut bounding_box = BoundingBox::default();
+    let weld_tolerances = process_weld_tolerances(input)?;
+
+    if input.polygon_limits.maximum_vertices < 3 {
+        return Err(ProcessingMeshError::InvalidPolygonLimit(input.polygon_limits.maximum_vertices));
+    }
+
+    if let Some(smoothing_angle) = input.normal_smoothing_angle {
+        if !(0.0..=180.0).contains(&smoothing_angle) {
+            return Err(ProcessingMeshError::InvalidSmoothingAngle(smoothing_angle));
+        }
+    }
+
+    if let Some(seam_tolerance) = input.seam_normal_tolerance {
+        if !(seam_tolerance >= 0.0 && seam_tolerance.is_finite()) {
+            return Err(ProcessingMeshError::InvalidSeamNormalTolerance(seam_tolerance));
+        }
+    }
+
+    let mut processed_model_data = ProcessedModelData { limits, ..Default::default() };
+
+    for material_path in &input.material_paths {
+        let material_path = create_material_directory(material_path);
+
+        if processed_model_data.material_paths.contains(&material_path) {
+            log(format!("Duplicate Material Path \"{}\"! Skipping!", material_path), LogLevel::Warn);
+            continue;
+        }
+
+        processed_model_data.material_paths.push(material_path);
+    }
+
+    // Triangle lists are created in order so material indices do not depend on which thread finishes first.
+    let mut pending_models = Vec::new();
+    let mut warned_vertex_colors = false;
+    let mut merged_materials = HashSet::new();
     for imputed_body_part in &input.body_parts {
+        // A body part without models has nothing to pick, so only the first model is allowed as its default.
+        if imputed_body_part.default_model > 0 && imputed_body_part.default_model >= imputed_body_part.models.len() {
+            return Err(ProcessingMeshError::DefaultModelNotFound(
+                imputed_body_part.name.clone(),
+                imputed_body_part.default_model,
+            ));
+        }
+
+        if let Some(preview_model) = imputed_body_part
+            .preview_model
+            .filter(|preview_model| *preview_model >= imputed_body_part.models.len())
+        {
+            return Err(ProcessingMeshError::PreviewModelNotFound(imputed_body_part.name.clone(), preview_model));
+        }
+
         let mut processed_body_part = ProcessedBodyPart {
             name: imputed_body_part.name.clone(),
+            default_model: imputed_body_part.default_model,
+            preview_model: imputed_body_part.preview_model,
             ..Default::default()
         };
 
@@ -103,11 +231,42 @@ pub fn process_meshes(
                 }
             };
 
+            if !warned_vertex_colors
+                && !VERTEX_COLOR_VERSIONS.contains(&input.target_version)
+                && imported_file.parts.iter().flat_map(|part| &part.vertices).any(|vertex| vertex.color.is_some())
+            {
+                log(
+                    format!(
+                        "Model {} Has Vertex Colors But Version {} Has No Vertex Color Stream! Skipping Colors!",
+                        imputed_model.name, input.target_version
+                    ),
+                    LogLevel::Warn,
+                );
+                warned_vertex_colors = true;
+            }
+
+            let flex_deltas = if imputed_model.flex_file_source.is_empty() {
+                Vec::new()
+            } else {
+                let flex_file = import
+                    .get_file(&imputed_model.flex_file_source)
+                    .ok_or(ProcessingMeshError::FlexFileSourceNotLoaded)?;
+                match_flex_vertices(imputed_model, &imported_file, &flex_file, &mut processed_model_data.flexes, &weld_tolerances)?
+            };
+
+            let model_source = ModelSource {
+                imported_file: &imported_file,
+                mapped_bones: processed_bone_data.remapped_bones[&imputed_model.file_source].as_slice(),
+                flex_deltas,
+            };
+
             let triangle_lists = create_triangle_lists(
-                &imputed_model.part_names,
-                &imported_file.parts,
+                imputed_model,
+                &model_source,
                 &mut processed_model_data.materials,
-                processed_bone_data.remapped_bones[&imputed_model.file_source].as_slice(),
+                &mut merged_materials,
+                &weld_tolerances,
+                input,
             )?;
 
             if triangle_lists.is_empty() {
@@ -116,141 +275,1022 @@ pub fn process_meshes(
                 continue;
             }
 
-            let mut bad_vertex_count = 0;
-            let mut culled_vertex_count = 0;
-            let mut face_count = 0;
-            let mut vertex_count = 0;
-            let mut indices_count = 0;
-            for (material_index, mut triangle_list) in triangle_lists {
-                reorder_triangle_vertex_order(&mut triangle_list);
-                sort_vertices_by_hardware_bones(&mut triangle_list);
-                optimize_vertex_cache(&mut triangle_list);
-                optimize_overdraw(&mut triangle_list);
-                bad_vertex_count += calculate_vertex_tangents(&mut triangle_list);
-                culled_vertex_count += cull_weight_links(&mut triangle_list);
-                let meshes = convert_to_meshes(material_index, triangle_list, &mut bounding_box);
-                face_count += meshes.1;
-                vertex_count += meshes.2;
-                indices_count += meshes.3;
-                processed_model.meshes.extend(meshes.0);
-            }
+            pending_models.push((processed_model_data.body_parts.len(), processed_body_part.models.len(), triangle_lists));
+            processed_body_part.models.push(processed_model);
+        }
 
-            if bad_vertex_count > 0 {
-                log(format!("{} Had {} Bad Vertices!", imputed_model.name, bad_vertex_count), LogLevel::Warn);
-            }
+        processed_model_data.body_parts.push(processed_body_part);
+    }
 
-            if culled_vertex_count > 0 {
-                log(
-                    format!("{} Had {} Weight Culled Vertices!", imputed_model.name, culled_vertex_count),
-                    LogLevel::Warn,
-                );
+    if let Some(seam_tolerance) = input.seam_normal_tolerance {
+        let smoothed_vertex_count = smooth_seam_normals(&mut pending_models, seam_tolerance);
+        log(format!("Smoothed {} Seam Vertex Normals", smoothed_vertex_count), LogLevel::Verbose);
+    }
+
+    let model_count = pending_models.len();
+    let completed_models = AtomicUsize::new(0);
+    let optimized_models = pending_models
+        .into_par_iter()
+        .map(|(body_part_index, model_index, triangle_lists)| {
+            // The remaining models are left empty once cancelled, the compile is stopped after this stage.
+            if is_cancelled() {
+                return (body_part_index, model_index, (Vec::new(), BoundingBox::default(), Vec::new()));
             }
 
-            log(
-                format!(
-                    "{} has {} faces, {} vertices and {} indices",
-                    imputed_model.name, face_count, vertex_count, indices_count
-                ),
-                LogLevel::Verbose,
-            );
+            let model_name = &input.body_parts[body_part_index].models[model_index].name;
+            let optimized_model = optimize_model(model_name, triangle_lists, &processed_model_data.materials, &limits);
+            let completed = completed_models.fetch_add(1, Ordering::Relaxed) + 1;
+            progress("Optimizing Meshes", completed, model_count);
+            (body_part_index, model_index, optimized_model)
+        })
+        .collect::<Vec<_>>();
 
-            processed_body_part.models.push(processed_model);
+    let mut bounding_box = BoundingBox::default();
+    for (body_part_index, model_index, (meshes, model_bounding_box, mesh_splits)) in optimized_models {
+        processed_model_data.body_parts[body_part_index].models[model_index].meshes = meshes;
+        processed_model_data.mesh_splits.extend(mesh_splits);
+
+        if input.body_parts[body_part_index]
+            .preview_model
+            .is_none_or(|preview_model| preview_model == model_index)
+        {
+            bounding_box.add_bounding_box(&model_bounding_box);
         }
+    }
 
-        processed_model_data.body_parts.push(processed_body_part);
+    processed_model_data.default_body = body_value(&processed_model_data.body_parts, |body_part| body_part.default_model);
+    if processed_model_data.default_body > 0 {
+        log(format!("Default Body Is {}", processed_model_data.default_body), LogLevel::Info);
+    }
+
+    if processed_model_data.body_parts.iter().any(|body_part| body_part.preview_model.is_some()) {
+        let preview_body = body_value(&processed_model_data.body_parts, |body_part| body_part.preview_model.unwrap_or_default());
+        log(format!("Preview Body Is {}", preview_body), LogLevel::Info);
     }
 
     if processed_model_data.body_parts.len() > i32::MAX as usize {
         return Err(ProcessingMeshError::TooManyBodyParts);
     }
 
-    if processed_model_data.materials.len() > (i16::MAX as usize + 1) {
-        return Err(ProcessingMeshError::TooManyMaterials);
+    if processed_model_data.materials.len() > material_limit {
+        report_material_budget(&processed_model_data, material_limit);
+        return Err(ProcessingMeshError::TooManyMaterials(processed_model_data.materials.len(), material_limit));
     }
 
     // TODO: Check if bounding box is too large
 
-    processed_model_data.bounding_box = bounding_box; // TODO: Overwrite this with input bounding box.
+    processed_model_data.bounding_box = bounding_box;
 
     Ok(processed_model_data)
 }
 
+/// The body value that shows the picked model of every body part.
+/// Each body part picks its model with the body divided by the model counts of the body parts before it, like the base written for it.
+fn body_value(processed_body_parts: &[ProcessedBodyPart], picked_model: impl Fn(&ProcessedBodyPart) -> usize) -> usize {
+    let mut base = 1;
+    let mut body = 0;
+    for processed_body_part in processed_body_parts {
+        body += picked_model(processed_body_part) * base;
+        base *= processed_body_part.models.len();
+    }
+
+    body
+}
+
+/// Optimizes the triangle lists of a model and splits them into meshes, this is the slow part of processing a model.
+fn optimize_model(
+    model_name: &str,
+    triangle_lists: IndexMap<usize, TriangleList>,
+    materials: &IndexSet<String>,
+    limits: &ProcessedMeshLimits,
+) -> (Vec<ProcessedMesh>, BoundingBox, Vec<ProcessedMeshSplit>) {
+    let mut meshes = Vec::new();
+    let mut bounding_box = BoundingBox::default();
+    let mut mesh_splits = Vec::new();
+
+    let mut bad_vertices = Vec::new();
+    let mut culled_vertex_count = 0;
+    let mut face_count = 0;
+    let mut vertex_count = 0;
+    let mut indices_count = 0;
+    let mut unused_vertex_count = 0;
+    for (material_index, mut triangle_list) in triangle_lists {
+        unused_vertex_count += remove_unused_vertices(&mut triangle_list);
+        reorder_triangle_vertex_order(&mut triangle_list);
+        optimize_vertex_cache(&mut triangle_list, limits.vertex_cache_size);
+        if let Some(overdraw_threshold) = limits.overdraw_threshold {
+            optimize_overdraw(&mut triangle_list, limits.vertex_cache_size, overdraw_threshold);
+        }
+        if limits.mikktspace_tangents {
+            bad_vertices.extend(calculate_mikktspace_tangents(&mut triangle_list));
+        } else {
+            bad_vertices.extend(calculate_vertex_tangents(&mut triangle_list));
+        }
+        culled_vertex_count += cull_weight_links(&mut triangle_list, limits.weights_per_vertex);
+        sort_vertices_by_hardware_bones(&mut triangle_list, limits.hardware_bones_per_strip);
+        let converted_meshes = convert_to_meshes(material_index, triangle_list, &mut bounding_box, limits.hardware_bones_per_strip);
+        face_count += converted_meshes.1;
+        vertex_count += converted_meshes.2;
+        indices_count += converted_meshes.3;
+
+        let (vertex_limit_splits, hardware_bone_limit_splits) = converted_meshes.4;
+        if vertex_limit_splits + hardware_bone_limit_splits > 0 {
+            let mesh_split = ProcessedMeshSplit {
+                model: model_name.to_string(),
+                material: materials[material_index].clone(),
+                mesh_count: converted_meshes.0.len(),
+                vertex_count: converted_meshes.0.iter().map(|mesh| mesh.vertex_data.len()).sum(),
+                triangle_count: converted_meshes.1,
+                vertex_limit_splits,
+                hardware_bone_limit_splits,
+            };
+            report_mesh_split(&mesh_split, limits.hardware_bones_per_strip);
+            mesh_splits.push(mesh_split);
+        }
+
+        meshes.extend(converted_meshes.0);
+    }
+
+    if unused_vertex_count > 0 {
+        log(
+            format!("{} Had {} Unused Vertices! Removing!", model_name, unused_vertex_count),
+            LogLevel::Verbose,
+        );
+    }
+
+    if !bad_vertices.is_empty() {
+        log(format!("{} Had {} Bad Vertices!", model_name, bad_vertices.len()), LogLevel::Warn);
+
+        // Only the first few are listed so a broken model does not flood the log.
+        for position in bad_vertices.iter().take(MAX_REPORTED_BAD_VERTICES) {
+            log(
+                format!("{} Has A Bad Vertex At ({:.3}, {:.3}, {:.3})", model_name, position.x, position.y, position.z),
+                LogLevel::Verbose,
+            );
+        }
+    }
+
+    if culled_vertex_count > 0 {
+        log(format!("{} Had {} Weight Culled Vertices!", model_name, culled_vertex_count), LogLevel::Warn);
+    }
+
+    log(
+        format!(
+            "{} has {} faces, {} vertices and {} indices",
+            model_name, face_count, vertex_count, indices_count
+        ),
+        LogLevel::Verbose,
+    );
+
+    (meshes, bounding_box, mesh_splits)
+}
+
+/// Logs why a material was split into more than one mesh, splitting it in the source file keeps the meshes under the limits.
+fn report_mesh_split(mesh_split: &ProcessedMeshSplit, hardware_bones_per_strip: usize) {
+    log(
+        format!(
+            "{} Material \"{}\" Was Split Into {} Meshes! ({} Vertices, {} Triangles)",
+            mesh_split.model, mesh_split.material, mesh_split.mesh_count, mesh_split.vertex_count, mesh_split.triangle_count
+        ),
+        LogLevel::Warn,
+    );
+
+    if mesh_split.vertex_limit_splits > 0 {
+        log(
+            format!(
+                "{} Material \"{}\" Split {} Times For Having More Than {} Vertices In A Mesh!",
+                mesh_split.model,
+                mesh_split.material,
+                mesh_split.vertex_limit_splits,
+                u16::MAX as usize + 1
+            ),
+            LogLevel::Verbose,
+        );
+    }
+
+    if mesh_split.hardware_bone_limit_splits > 0 {
+        log(
+            format!(
+                "{} Material \"{}\" Split {} Times For Using More Than {} Bones In A Mesh!",
+                mesh_split.model, mesh_split.material, mesh_split.hardware_bone_limit_splits, hardware_bones_per_strip
+            ),
+            LogLevel::Verbose,
+        );
+    }
+}
+
+/// Lists the materials and material paths the meshes would be written with, without processing the meshes.
+pub fn preview_materials(input: &ImputedCompilationData, import: &State<FileManager>) -> Result<(IndexSet<String>, Vec<String>), ProcessingMeshError> {
+    let mut material_table = IndexSet::new();
+
+    for imputed_model in input.body_parts.iter().flat_map(|body_part| &body_part.models) {
+        if imputed_model.is_blank {
+            continue;
+        }
+
+        let imported_file = import.get_file(&imputed_model.file_source).ok_or(ProcessingMeshError::FileSourceNotLoaded)?;
+
+        for imputed_part_name in &imputed_model.part_names {
+            let import_part = match imported_file.parts.iter().find(|part| part.name == *imputed_part_name) {
+                Some(part) => part,
+                None => return Err(ProcessingMeshError::PartNotFound(imputed_part_name.clone())),
+            };
+
+            for material in import_part.polygons.keys() {
+                if !imputed_model.materials.is_empty() && !imputed_model.materials.contains(material) {
+                    continue;
+                }
+
+                let (material_name, _) = resolve_material_name(material, &material_table, input);
+                material_table.insert(material_name);
+            }
+        }
+    }
+
+    let mut material_directories = Vec::new();
+    for material_path in &input.material_paths {
+        let material_directory = create_material_directory(material_path);
+        if !material_directories.contains(&material_directory) {
+            material_directories.push(material_directory);
+        }
+    }
+
+    if material_directories.is_empty() {
+        material_directories.push(String::new());
+    }
+
+    Ok((material_table, material_directories))
+}
+
+/// Normalizes a material path into the directory the engine appends the material names to.
+fn create_material_directory(material_path: &str) -> String {
+    let normalized_path = normalize_material_path(material_path);
+
+    // The engine appends the material name directly to the path, so every path except the root needs a trailing slash.
+    if normalized_path.is_empty() {
+        normalized_path
+    } else {
+        format!("{}/", normalized_path)
+    }
+}
+
+/// Normalizes and renames a material of a source file into the name it is written with.
+/// A material that only differs from a material in the table by case or extension takes the name from the table, the name it had is also returned.
+fn resolve_material_name(material: &str, material_table: &IndexSet<String>, input: &ImputedCompilationData) -> (String, Option<String>) {
+    let mut material_name = normalize_material_path(material);
+
+    if input.material_normalization.strip_directories {
+        material_name = material_name.rsplit('/').next().unwrap_or_default().to_string();
+    }
+
+    if let Some(renamed_material) = rename_material(&material_name, &input.material_renames) {
+        material_name = renamed_material;
+    }
+
+    // The first spelling of the material is kept, so every part using it shares one material slot.
+    if input.material_normalization.merge_similar {
+        let merge_key = material_merge_key(&material_name);
+        if let Some(existing_material) = material_table
+            .iter()
+            .find(|existing_material| material_merge_key(existing_material) == merge_key)
+        {
+            if *existing_material != material_name {
+                return (existing_material.clone(), Some(material_name));
+            }
+        }
+    }
+
+    (material_name, None)
+}
+
 /// Combines parts into triangle lists for each material.
 fn create_triangle_lists(
-    part_names: &[String],
-    parts: &[ImportPart],
+    imputed_model: &ImputedModel,
+    model_source: &ModelSource,
     material_table: &mut IndexSet<String>,
-    mapped_bones: &[ProcessedRemappedBone],
+    merged_materials: &mut HashSet<String>,
+    weld_tolerances: &WeldTolerances,
+    input: &ImputedCompilationData,
 ) -> Result<IndexMap<usize, TriangleList>, ProcessingMeshError> {
+    let imported_file = model_source.imported_file;
+    let polygon_limits = &input.polygon_limits;
     let mut triangle_lists: IndexMap<usize, TriangleList> = IndexMap::new();
+    let mut added_triangles: HashSet<(usize, [usize; 3])> = HashSet::new();
 
-    for imputed_part_name in part_names {
-        let import_part = match parts.iter().find(|part| part.name == *imputed_part_name) {
+    let mirror_axis = imputed_model.mirror.as_ref().map(|mirror| mirror.axis as usize);
+    let origin = Vector3::new(input.origin[0], input.origin[1], input.origin[2]);
+    let bone_links = match &imputed_model.mirror {
+        Some(mirror) if mirror.mirror_bones => mirror_bone_links(&imported_file.skeleton, model_source.mapped_bones),
+        _ => (0..imported_file.skeleton.len()).collect(),
+    };
+    let mirror_transforms = mirror_axis.map(|axis| (axis, mirror_bone_transforms(&imported_file.skeleton, &bone_links, axis)));
+
+    for (part_index, imputed_part_name) in imputed_model.part_names.iter().enumerate() {
+        let (import_part_index, import_part) = match imported_file.parts.iter().enumerate().find(|(_, part)| part.name == *imputed_part_name) {
             Some(part) => part,
             None => return Err(ProcessingMeshError::PartNotFound(imputed_part_name.clone())),
         };
 
+        let mut oversized_polygon_count = 0;
+        let mut degenerate_triangle_count = 0;
+        let mut duplicate_triangle_count = 0;
+
+        // The whole part is triangulated first so normals can be smoothed across materials.
+        let mut part_triangles = Vec::new();
+        let mut triangle_texture_offsets = Vec::new();
         for (material, faces) in &import_part.polygons {
-            let material_index = material_table.insert_full(material.clone()).0;
+            if !imputed_model.materials.is_empty() && !imputed_model.materials.contains(material) {
+                continue;
+            }
+
+            let (material_name, merged_name) = resolve_material_name(material, material_table, input);
+            if let Some(merged_name) = merged_name.filter(|merged_name| merged_materials.insert(merged_name.clone())) {
+                log(
+                    format!(
+                        "Material \"{}\" Only Differs From \"{}\" By Case Or Extension! Merging!",
+                        merged_name, material_name
+                    ),
+                    LogLevel::Warn,
+                );
+            }
 
-            let triangle_list = triangle_lists.entry(material_index).or_default();
+            let material_index = material_table.insert_full(material_name).0;
+
+            triangle_lists.entry(material_index).or_default();
 
             for face in faces {
                 if face.len() < 3 {
                     return Err(ProcessingMeshError::IncompleteFace);
                 }
 
-                let triangulated_face = triangulate_face(face, &import_part.vertices);
-
-                for mut triangle in triangulated_face {
-                    for vertex_index in &mut triangle {
-                        let import_vertex = &import_part.vertices[*vertex_index];
+                // Large polygons are fan triangulated as the best fan search is quadratic on the vertex count.
+                let triangulated_face = if face.len() > polygon_limits.maximum_vertices {
+                    if polygon_limits.strict {
+                        return Err(ProcessingMeshError::PolygonTooLarge(
+                            import_part.name.clone(),
+                            face.len(),
+                            polygon_limits.maximum_vertices,
+                        ));
+                    }
 
-                        let mut mapped_links = Vec::with_capacity(import_vertex.links.len());
+                    oversized_polygon_count += 1;
+                    fan_triangulate_face(face, 0)
+                } else {
+                    triangulate_face(face, &import_part.vertices)
+                };
+
+                let face_tile = find_texture_tile(face, &import_part.vertices);
+
+                if input.texture_coordinates.reject_udim {
+                    if let Some((tile_u, tile_v)) = face_tile {
+                        if (tile_u, tile_v) != (0, 0) && (0..10).contains(&tile_u) && tile_v >= 0 {
+                            return Err(ProcessingMeshError::UdimTextureCoordinates(
+                                import_part.name.clone(),
+                                1001 + tile_u + tile_v * 10,
+                            ));
+                        }
+                    }
+                }
 
-                        for link in &import_vertex.links {
-                            let mapped_bone = &mapped_bones[link.bone];
-                            mapped_links.push(WeightLink {
-                                bone: mapped_bone.index.try_into().unwrap(),
-                                weight: link.weight,
+                // The whole face is moved by the same amount so the texture stays continuous across it.
+                let texture_offset = match (input.texture_coordinates.normalize, face_tile) {
+                    (true, Some((tile_u, tile_v))) => Vector2::new(tile_u as f64, tile_v as f64),
+                    (true, None) => {
+                        let minimum = face
+                            .iter()
+                            .map(|&index| import_part.vertices[index].texture_coordinate)
+                            .fold(Vector2::new(f64::INFINITY, f64::INFINITY), |minimum, coordinate| {
+                                Vector2::new(minimum.x.min(coordinate.x), minimum.y.min(coordinate.y))
                             });
-                        }
+                        Vector2::new(minimum.x.floor(), minimum.y.floor())
+                    }
+                    (false, _) => Vector2::default(),
+                };
 
-                        let triangle_vertex = TriangleVertex {
-                            position: import_vertex.position,
-                            normal: import_vertex.normal.normalize(),
-                            texture_coordinate: import_vertex.texture_coordinate,
-                            links: mapped_links,
-                        };
-
-                        let neighbors = triangle_list
-                            .vertex_tree
-                            .within(&triangle_vertex.position.as_slice(), FLOAT_TOLERANCE, &squared_euclidean)
-                            .unwrap();
-
-                        if let Some(&(_, index)) = neighbors.iter().find(|(_, &i)| vertex_equals(&triangle_vertex, &triangle_list.vertices[i])) {
-                            *vertex_index = *index;
-                            continue;
-                        }
+                for triangle in triangulated_face {
+                    if is_degenerate_triangle(&triangle, &import_part.vertices) {
+                        degenerate_triangle_count += 1;
+                        continue;
+                    }
+
+                    part_triangles.push((material_index, triangle));
+                    triangle_texture_offsets.push(texture_offset);
+                }
+            }
+        }
 
-                        triangle_list
-                            .vertex_tree
-                            .add(triangle_vertex.position.as_slice(), triangle_list.vertices.len())
-                            .unwrap();
+        let smoothed_normals = input
+            .normal_smoothing_angle
+            .map(|smoothing_angle| calculate_smoothed_normals(&import_part.vertices, &part_triangles, smoothing_angle, weld_tolerances));
+
+        for (triangle_index, (material_index, mut triangle)) in part_triangles.into_iter().enumerate() {
+            let triangle_list = &mut triangle_lists[&material_index];
+
+            for (corner_index, vertex_index) in triangle.iter_mut().enumerate() {
+                let import_vertex = &import_part.vertices[*vertex_index];
+
+                let mut mapped_links = Vec::with_capacity(import_vertex.links.len());
+
+                for link in &import_vertex.links {
+                    let mapped_bone = &model_source.mapped_bones[bone_links[link.bone]];
+                    mapped_links.push(WeightLink {
+                        bone: mapped_bone.index.expect("Removed Bones Are Not Used By Vertices").try_into().unwrap(),
+                        weight: link.weight,
+                    });
+                }
+
+                let normal = match &smoothed_normals {
+                    Some(normals) => normals[triangle_index][corner_index],
+                    None => import_vertex.normal.normalize(),
+                };
+
+                let mut texture_coordinate = import_vertex.texture_coordinate - triangle_texture_offsets[triangle_index];
+                if input.texture_coordinates.flip_v {
+                    texture_coordinate.y = 1.0 - texture_coordinate.y;
+                }
 
-                        *vertex_index = triangle_list.vertices.len();
-                        triangle_list.vertices.push(triangle_vertex);
+                let mut triangle_vertex = TriangleVertex {
+                    position: import_vertex.position,
+                    normal,
+                    texture_coordinate,
+                    links: mapped_links,
+                    flex_deltas: model_source
+                        .flex_deltas
+                        .get(import_part_index)
+                        .map_or_else(Vec::new, |part_deltas| part_deltas[*vertex_index].clone()),
+                };
+
+                if let Some((axis, mirror_transforms)) = &mirror_transforms {
+                    let (position, normal) = mirror_vertex(&triangle_vertex, &import_vertex.links, mirror_transforms, *axis);
+                    triangle_vertex.position = position;
+                    triangle_vertex.normal = normal;
+
+                    for flex_delta in &mut triangle_vertex.flex_deltas {
+                        flex_delta.position = mirror_direction(flex_delta.position, &import_vertex.links, mirror_transforms, *axis);
+                        flex_delta.normal = mirror_direction(flex_delta.normal, &import_vertex.links, mirror_transforms, *axis);
                     }
+                }
+
+                triangle_vertex.position = triangle_vertex.position - origin;
 
-                    triangle_list.triangles.push(triangle);
+                let neighbors = triangle_list
+                    .vertex_tree
+                    .within(&triangle_vertex.position.as_slice(), weld_tolerances.position_squared, &squared_euclidean)
+                    .unwrap();
+
+                if let Some(&(_, index)) = neighbors
+                    .iter()
+                    .find(|(_, &i)| vertex_equals(&triangle_vertex, &triangle_list.vertices[i], weld_tolerances))
+                {
+                    *vertex_index = *index;
+                    continue;
                 }
+
+                triangle_list
+                    .vertex_tree
+                    .add(triangle_vertex.position.as_slice(), triangle_list.vertices.len())
+                    .unwrap();
+
+                *vertex_index = triangle_list.vertices.len();
+                triangle_list.vertices.push(triangle_vertex);
+            }
+
+            // Mirroring turns the triangles inside out, so the winding is flipped back.
+            if mirror_axis.is_some() {
+                triangle.swap(1, 2);
+            }
+
+            // Corners can be welded into one vertex, the vertices left unused by this are removed before optimizing.
+            if triangle[0] == triangle[1] || triangle[1] == triangle[2] || triangle[2] == triangle[0] {
+                degenerate_triangle_count += 1;
+                continue;
+            }
+
+            // The triangle is rotated to start at its lowest index so the same face is found whatever corner it starts at.
+            let lowest_corner = (0..3).min_by_key(|&corner| triangle[corner]).unwrap();
+            let rotated_triangle = [0, 1, 2].map(|corner| triangle[(lowest_corner + corner) % 3]);
+            if !added_triangles.insert((material_index, rotated_triangle)) {
+                duplicate_triangle_count += 1;
+                continue;
             }
+
+            triangle_list.triangles.push(triangle);
+            triangle_list.triangle_parts.push(part_index);
+        }
+
+        if degenerate_triangle_count > 0 || duplicate_triangle_count > 0 {
+            log(
+                format!(
+                    "Part {} Had {} Degenerate And {} Duplicate Triangles! Removing!",
+                    import_part.name, degenerate_triangle_count, duplicate_triangle_count
+                ),
+                LogLevel::Warn,
+            );
+        }
+
+        if oversized_polygon_count > 0 {
+            log(
+                format!(
+                    "Part {} Had {} Polygons Over {} Vertices! Fan Triangulating!",
+                    import_part.name, oversized_polygon_count, polygon_limits.maximum_vertices
+                ),
+                LogLevel::Warn,
+            );
         }
     }
 
     Ok(triangle_lists)
 }
 
+/// Matches the vertices of the model file to the base frame of its flex file by position, as the frames of the flex file only know the vertices of the base frame.
+/// Returns the flex deltas of every vertex of every part of the model file, the flexes are added to the flex table by name.
+fn match_flex_vertices(
+    imputed_model: &ImputedModel,
+    imported_file: &ImportFileData,
+    flex_file: &ImportFileData,
+    flex_table: &mut IndexSet<String>,
+    weld_tolerances: &WeldTolerances,
+) -> Result<Vec<Vec<Vec<FlexDelta>>>, ProcessingMeshError> {
+    let flex_part = match flex_file.parts.iter().find(|part| !part.flexes.is_empty()) {
+        Some(part) => part,
+        None => return Err(ProcessingMeshError::NoFlexes(imputed_model.name.clone())),
+    };
+
+    let mut base_deltas = vec![Vec::new(); flex_part.vertices.len()];
+    for (frame_index, import_flex) in flex_part.flexes.iter().enumerate() {
+        let flex_name = import_flex.name.clone().unwrap_or_else(|| format!("flex_{}", frame_index + 1));
+        let flex = flex_table.insert_full(flex_name).0;
+
+        // Vertices listed in a frame without moving are left out of the flex.
+        for flex_vertex in &import_flex.vertices {
+            if flex_vertex.position.magnitude() == 0.0 && flex_vertex.normal.magnitude() == 0.0 {
+                continue;
+            }
+
+            base_deltas[flex_vertex.index].push(FlexDelta {
+                flex,
+                position: flex_vertex.position,
+                normal: flex_vertex.normal,
+            });
+        }
+    }
+
+    let mut base_tree = KdTree::new(3);
+    for (base_index, base_vertex) in flex_part.vertices.iter().enumerate() {
+        base_tree.add(base_vertex.position.as_slice(), base_index).unwrap();
+    }
+
+    // Formats like SMD repeat a vertex for every face, so the base vertex with the closest normal is picked.
+    let mut matched_base_vertices = vec![false; flex_part.vertices.len()];
+    let part_deltas = imported_file
+        .parts
+        .iter()
+        .map(|import_part| {
+            import_part
+                .vertices
+                .iter()
+                .map(|import_vertex| {
+                    let neighbors = base_tree
+                        .within(&import_vertex.position.as_slice(), weld_tolerances.position_squared, &squared_euclidean)
+                        .unwrap();
+
+                    for &(_, &base_index) in &neighbors {
+                        matched_base_vertices[base_index] = true;
+                    }
+
+                    neighbors
+                        .iter()
+                        .map(|&(_, &base_index)| base_index)
+                        .max_by(|&first, &second| {
+                            let first_alignment = flex_part.vertices[first].normal.dot(import_vertex.normal);
+                            let second_alignment = flex_part.vertices[second].normal.dot(import_vertex.normal);
+                            first_alignment.total_cmp(&second_alignment)
+                        })
+                        .map_or_else(Vec::new, |base_index| base_deltas[base_index].clone())
+                })
+                .collect()
+        })
+        .collect();
+
+    let unmatched_count = matched_base_vertices.iter().filter(|matched| !**matched).count();
+    if unmatched_count > 0 {
+        log(
+            format!(
+                "{} Vertices Of The Flex File Of Model {} Are Not On The Model! Skipping!",
+                unmatched_count, imputed_model.name
+            ),
+            LogLevel::Warn,
+        );
+    }
+
+    Ok(part_deltas)
+}
+
+/// Calculates a normal for every corner of the triangles from the faces that share its position.
+/// Faces are only smoothed together when the angle between them is within the smoothing angle, this also
+/// smooths formats like SMD where every triangle has its own copy of a vertex.
+fn calculate_smoothed_normals(
+    vertices: &[ImportVertex],
+    triangles: &[(usize, [usize; 3])],
+    smoothing_angle: f64,
+    weld_tolerances: &WeldTolerances,
+) -> Vec<[Vector3; 3]> {
+    let smoothing_threshold = smoothing_angle.to_radians().cos();
+
+    // The cross product is not normalized so larger faces have more influence on the normal.
+    let face_normals = triangles
+        .iter()
+        .map(|(_, triangle)| {
+            let position0 = vertices[triangle[0]].position;
+            (vertices[triangle[1]].position - position0).cross(vertices[triangle[2]].position - position0)
+        })
+        .collect::<Vec<_>>();
+
+    let mut position_tree: KdTree<f64, usize, [f64; 3]> = KdTree::new(3);
+    let mut position_faces: Vec<Vec<usize>> = Vec::new();
+    let mut corner_positions = Vec::with_capacity(triangles.len());
+
+    for (triangle_index, (_, triangle)) in triangles.iter().enumerate() {
+        let mut positions = [0; 3];
+
+        for (corner_index, &vertex_index) in triangle.iter().enumerate() {
+            let position = vertices[vertex_index].position.as_slice();
+            let neighbors = position_tree.within(&position, weld_tolerances.position_squared, &squared_euclidean).unwrap();
+
+            let position_index = match neighbors.first() {
+                Some(&(_, &index)) => index,
+                None => {
+                    position_tree.add(position, position_faces.len()).unwrap();
+                    position_faces.push(Vec::new());
+                    position_faces.len() - 1
+                }
+            };
+
+            if !position_faces[position_index].contains(&triangle_index) {
+                position_faces[position_index].push(triangle_index);
+            }
+
+            positions[corner_index] = position_index;
+        }
+
+        corner_positions.push(positions);
+    }
+
+    corner_positions
+        .iter()
+        .enumerate()
+        .map(|(triangle_index, positions)| {
+            let face_direction = face_normals[triangle_index].normalize();
+
+            let triangle = triangles[triangle_index].1;
+
+            std::array::from_fn(|corner_index| {
+                let mut normal = Vector3::default();
+
+                for &face_index in &position_faces[positions[corner_index]] {
+                    if face_index == triangle_index || face_normals[face_index].normalize().dot(face_direction) >= smoothing_threshold {
+                        normal = normal + face_normals[face_index];
+                    }
+                }
+
+                // Faces without an area have no direction, so the imported normal is kept for them.
+                if normal.magnitude() < f64::EPSILON {
+                    return vertices[triangle[corner_index]].normal.normalize();
+                }
+
+                normal.normalize()
+            })
+        })
+        .collect()
+}
+
+/// Averages the normals of the vertices on the open edges of different parts and models that meet within the tolerance.
+/// Parts are only welded when their vertices are equal, so the seam between a head and a body keeps two normals that light it apart.
+/// Returns how many vertices had their normal changed.
+fn smooth_seam_normals(models: &mut [(usize, usize, IndexMap<usize, TriangleList>)], tolerance: f64) -> usize {
+    let tolerance_squared = tolerance * tolerance;
+
+    // Every vertex is mapped to a shared position, vertices within the tolerance of each other map to the first one.
+    let mut position_tree: KdTree<f64, usize, [f64; 3]> = KdTree::new(3);
+    let mut position_count = 0;
+    let mut vertex_positions = Vec::with_capacity(models.len());
+    for (_, _, triangle_lists) in models.iter() {
+        let mut model_positions = Vec::with_capacity(triangle_lists.len());
+
+        for triangle_list in triangle_lists.values() {
+            let mut list_positions = Vec::with_capacity(triangle_list.vertices.len());
+
+            for vertex in &triangle_list.vertices {
+                let position = vertex.position.as_slice();
+                let neighbors = position_tree.within(&position, tolerance_squared, &squared_euclidean).unwrap();
+
+                let position_index = match neighbors.first() {
+                    Some(&(_, &index)) => index,
+                    None => {
+                        position_tree.add(position, position_count).unwrap();
+                        position_count += 1;
+                        position_count - 1
+                    }
+                };
+
+                list_positions.push(position_index);
+            }
+
+            model_positions.push(list_positions);
+        }
+
+        vertex_positions.push(model_positions);
+    }
+
+    // An edge that only one triangle of a part uses is on the border of the part, materials of a part are counted together.
+    let mut position_vertices: Vec<IndexSet<(usize, usize, usize)>> = vec![IndexSet::new(); position_count];
+    let mut position_sources: Vec<HashSet<(usize, usize)>> = vec![HashSet::new(); position_count];
+    for (model_index, (_, _, triangle_lists)) in models.iter().enumerate() {
+        let positions = &vertex_positions[model_index];
+        let edge_key = |list_index: usize, part: usize, from: usize, to: usize| {
+            let from = positions[list_index][from];
+            let to = positions[list_index][to];
+            (part, from.min(to), from.max(to))
+        };
+
+        let mut edge_counts: HashMap<(usize, usize, usize), usize> = HashMap::new();
+        for (list_index, triangle_list) in triangle_lists.values().enumerate() {
+            for (triangle, &part) in triangle_list.triangles.iter().zip(&triangle_list.triangle_parts) {
+                for corner in 0..3 {
+                    *edge_counts
+                        .entry(edge_key(list_index, part, triangle[corner], triangle[(corner + 1) % 3]))
+                        .or_default() += 1;
+                }
+            }
+        }
+
+        for (list_index, triangle_list) in triangle_lists.values().enumerate() {
+            for (triangle, &part) in triangle_list.triangles.iter().zip(&triangle_list.triangle_parts) {
+                for corner in 0..3 {
+                    let edge = [triangle[corner], triangle[(corner + 1) % 3]];
+                    if edge_counts[&edge_key(list_index, part, edge[0], edge[1])] != 1 {
+                        continue;
+                    }
+
+                    for vertex_index in edge {
+                        let position_index = positions[list_index][vertex_index];
+                        position_vertices[position_index].insert((model_index, list_index, vertex_index));
+                        position_sources[position_index].insert((model_index, part));
+                    }
+                }
+            }
+        }
+    }
+
+    let mut smoothed_vertex_count = 0;
+    for (vertices, sources) in position_vertices.iter().zip(&position_sources) {
+        if sources.len() < 2 {
+            continue;
+        }
+
+        let normal = vertices.iter().fold(Vector3::default(), |normal, &(model_index, list_index, vertex_index)| {
+            normal + models[model_index].2.get_index(list_index).unwrap().1.vertices[vertex_index].normal
+        });
+
+        // Opposite normals cancel out, the vertices keep their own normals instead of having no direction.
+        if normal.magnitude() < f64::EPSILON {
+            continue;
+        }
+
+        let normal = normal.normalize();
+        for &(model_index, list_index, vertex_index) in vertices {
+            models[model_index].2.get_index_mut(list_index).unwrap().1.vertices[vertex_index].normal = normal;
+        }
+
+        smoothed_vertex_count += vertices.len();
+    }
+
+    smoothed_vertex_count
+}
+
+/// Maps every bone of a file to the bone on the other side, bones without one map to themselves.
+fn mirror_bone_links(skeleton: &[ImportBone], mapped_bones: &[ProcessedRemappedBone]) -> Vec<usize> {
+    skeleton
+        .iter()
+        .enumerate()
+        .map(|(bone_index, bone)| {
+            mirror_bone_name(&bone.name)
+                .and_then(|mirrored_name| skeleton.iter().position(|mirrored_bone| mirrored_bone.name == mirrored_name))
+                // A bone no vertex used before mirroring can be removed from the bone table.
+                .filter(|&mirrored_index| mapped_bones[mirrored_index].index.is_some())
+                .unwrap_or(bone_index)
+        })
+        .collect()
+}
+
+/// Finds the transform that moves a vertex bound to each bone over to the bone it is mirrored onto.
+/// Vertices keep their place relative to the bone on the other side, so they follow its roll even when it is not an exact mirror of the original bone.
+/// Bones mirrored onto themselves are reflected across the axis of the file.
+fn mirror_bone_transforms(skeleton: &[ImportBone], bone_links: &[usize], axis: usize) -> Vec<Matrix4> {
+    let world_reflection = reflection_matrix(axis);
+    let bind_poses = skeleton
+        .iter()
+        .map(|bone| {
+            let mut pose = Matrix4::new(bone.position, bone.orientation.to_matrix());
+            let mut parent = bone.parent;
+            let mut depth = 0;
+            while let Some(parent_index) = parent {
+                // A broken hierarchy with a loop would never reach the root.
+                if depth > skeleton.len() {
+                    break;
+                }
+                let parent_bone = &skeleton[parent_index];
+                pose = Matrix4::new(parent_bone.position, parent_bone.orientation.to_matrix()) * pose;
+                parent = parent_bone.parent;
+                depth += 1;
+            }
+            pose
+        })
+        .collect::<Vec<_>>();
+
+    bone_links
+        .iter()
+        .enumerate()
+        .map(|(bone_index, &mirrored_index)| {
+            if bone_index == mirrored_index {
+                return world_reflection;
+            }
+
+            let source_pose = bind_poses[bone_index];
+            let mirrored_pose = bind_poses[mirrored_index];
+
+            // The reflected bone needs one of its own axes flipped to stay a rotation, the one closest to the bone on the other side is used.
+            let local_reflection = (0..3)
+                .map(reflection_matrix)
+                .max_by(|first, second| {
+                    rotation_similarity(&(world_reflection * source_pose * *first), &mirrored_pose)
+                        .total_cmp(&rotation_similarity(&(world_reflection * source_pose * *second), &mirrored_pose))
+                })
+                .unwrap();
+
+            // The transpose keeps the translation in its last row, which would leak into the product.
+            let mut inverse_source_pose = source_pose.transpose();
+            inverse_source_pose.entries[3] = [0.0, 0.0, 0.0, 1.0];
+
+            mirrored_pose * local_reflection * inverse_source_pose
+        })
+        .collect()
+}
+
+/// Mirrors the position and normal of a vertex by blending the mirror transforms of the bones it is bound to.
+fn mirror_vertex(vertex: &TriangleVertex, links: &[ImportLink], mirror_transforms: &[Matrix4], axis: usize) -> (Vector3, Vector3) {
+    let normal = mirror_direction(vertex.normal, links, mirror_transforms, axis).normalize();
+    let total_weight = links.iter().map(|link| link.weight).sum::<f64>();
+    if links.is_empty() || total_weight <= f64::EPSILON {
+        let mut position = vertex.position;
+        position[axis] = -position[axis];
+        return (position, normal);
+    }
+
+    let mut position = Vector3::default();
+    for link in links {
+        let weight = link.weight / total_weight;
+        position = position + mirror_transforms[link.bone].transform(vertex.position) * weight;
+    }
+
+    (position, normal)
+}
+
+/// Mirrors a direction of a vertex the same way as the vertex, the length of the direction is kept.
+fn mirror_direction(direction: Vector3, links: &[ImportLink], mirror_transforms: &[Matrix4], axis: usize) -> Vector3 {
+    let total_weight = links.iter().map(|link| link.weight).sum::<f64>();
+    if links.is_empty() || total_weight <= f64::EPSILON {
+        let mut mirrored_direction = direction;
+        mirrored_direction[axis] = -mirrored_direction[axis];
+        return mirrored_direction;
+    }
+
+    let mut mirrored_direction = Vector3::default();
+    for link in links {
+        let transform = &mirror_transforms[link.bone];
+        let weight = link.weight / total_weight;
+        mirrored_direction = mirrored_direction + (transform.transform(direction) - transform.transform(Vector3::default())) * weight;
+    }
+
+    mirrored_direction
+}
+
+/// Creates a transform that flips a single axis.
+fn reflection_matrix(axis: usize) -> Matrix4 {
+    let mut reflection = Matrix4::identity();
+    reflection.entries[axis][axis] = -1.0;
+    reflection
+}
+
+/// Measures how closely the rotations of two transforms line up, equal rotations give 3.
+fn rotation_similarity(first: &Matrix4, second: &Matrix4) -> f64 {
+    (0..3)
+        .flat_map(|row| (0..3).map(move |column| first.entries[row][column] * second.entries[row][column]))
+        .sum()
+}
+
+/// Swaps left and right in a bone name, both as words like "Left" and as single letters between separators like "L_Hand".
+fn mirror_bone_name(name: &str) -> Option<String> {
+    const SIDE_WORDS: [(&str, &str); 3] = [("Left", "Right"), ("left", "right"), ("LEFT", "RIGHT")];
+
+    for (left, right) in SIDE_WORDS {
+        if name.contains(left) {
+            return Some(name.replace(left, right));
+        }
+
+        if name.contains(right) {
+            return Some(name.replace(right, left));
+        }
+    }
+
+    let mut mirrored = false;
+    let mirrored_name = name
+        .split_inclusive(['_', '.', ' ', '-'])
+        .map(|token| {
+            let (letter, separator) = token.split_at(token.trim_end_matches(['_', '.', ' ', '-']).len());
+            let mirrored_letter = match letter {
+                "L" => "R",
+                "R" => "L",
+                "l" => "r",
+                "r" => "l",
+                _ => return token.to_string(),
+            };
+            mirrored = true;
+            format!("{}{}", mirrored_letter, separator)
+        })
+        .collect::<String>();
+
+    mirrored.then_some(mirrored_name)
+}
+
+/// Finds the texture tile a face is completely inside of, faces on the edge of a tile count as inside of it.
+fn find_texture_tile(face: &[usize], vertices: &[ImportVertex]) -> Option<(i64, i64)> {
+    let first_coordinate = vertices[face[0]].texture_coordinate;
+    let mut tile = (first_coordinate.x.floor(), first_coordinate.y.floor());
+
+    for &index in face {
+        let coordinate = vertices[index].texture_coordinate;
+        tile = (tile.0.min(coordinate.x.floor()), tile.1.min(coordinate.y.floor()));
+    }
+
+    let inside_tile = face.iter().all(|&index| {
+        let coordinate = vertices[index].texture_coordinate;
+        coordinate.x <= tile.0 + 1.0 && coordinate.y <= tile.1 + 1.0
+    });
+
+    if !inside_tile || !tile.0.is_finite() || !tile.1.is_finite() {
+        return None;
+    }
+
+    Some((tile.0 as i64, tile.1 as i64))
+}
+
+/// Checks if a triangle has no area, or has a position that is not a number.
+fn is_degenerate_triangle(triangle: &[usize; 3], vertices: &[ImportVertex]) -> bool {
+    let position0 = vertices[triangle[0]].position;
+    let area = (vertices[triangle[1]].position - position0)
+        .cross(vertices[triangle[2]].position - position0)
+        .magnitude();
+
+    // A not a number area fails every comparison, so it is checked for explicitly.
+    area <= f64::EPSILON || area.is_nan()
+}
+
+/// Converts a material path from a source file to the forward slash separated form the engine expects.
+fn normalize_material_path(path: &str) -> String {
+    path.replace('\\', "/").trim_matches('/').to_string()
+}
+
+/// Materials that only differ by case or by the extension of their texture are the same material to the engine.
+fn material_merge_key(material_name: &str) -> String {
+    let lowercase_name = material_name.to_lowercase();
+
+    match lowercase_name.rsplit_once('.') {
+        Some((name, extension)) if MATERIAL_FILE_EXTENSIONS.contains(&extension) => name.to_string(),
+        _ => lowercase_name,
+    }
+}
+
+/// Finds the first rename that matches the whole material name.
+fn rename_material(material_name: &str, renames: &[ImputedMaterialRename]) -> Option<String> {
+    for rename in renames {
+        let from = normalize_material_path(&rename.from);
+
+        match from.split_once('*') {
+            None if from == material_name => return Some(normalize_material_path(&rename.to)),
+            Some((prefix, suffix))
+                if material_name.len() >= prefix.len() + suffix.len() && material_name.starts_with(prefix) && material_name.ends_with(suffix) =>
+            {
+                let matched_text = &material_name[prefix.len()..material_name.len() - suffix.len()];
+                return Some(normalize_material_path(&rename.to.replacen('*', matched_text, 1)));
+            }
+            _ => {}
+        }
+    }
+
+    None
+}
+
 /// Triangulates a face into a triangles.
 fn triangulate_face(face: &[usize], vertices: &[ImportVertex]) -> Vec<[usize; 3]> {
     if face.len() == 3 {
@@ -263,8 +1303,6 @@ fn triangulate_face(face: &[usize], vertices: &[ImportVertex]) -> Vec<[usize; 3]
 
     // TODO: Implement a better triangulation algorithm.
 
-    let mut triangles = Vec::new();
-
     let index_count = face.len();
     let mut minimum_distance = f64::MAX;
     let mut minimum_index = 0;
@@ -291,28 +1329,33 @@ fn triangulate_face(face: &[usize], vertices: &[ImportVertex]) -> Vec<[usize; 3]
         }
     }
 
+    fan_triangulate_face(face, minimum_index)
+}
+
+/// Triangulates a face as a fan around the vertex at the center index.
+fn fan_triangulate_face(face: &[usize], center_index: usize) -> Vec<[usize; 3]> {
+    let index_count = face.len();
+    let mut triangles = Vec::with_capacity(index_count - 2);
+
     for triangle_build_index in 1..index_count - 1 {
         triangles.push([
-            face[minimum_index],
-            face[(minimum_index + triangle_build_index) % index_count],
-            face[(minimum_index + triangle_build_index + 1) % index_count],
+            face[center_index],
+            face[(center_index + triangle_build_index) % index_count],
+            face[(center_index + triangle_build_index + 1) % index_count],
         ]);
     }
 
     triangles
 }
 
-/// Compares two triangle vertices for equality.
-fn vertex_equals(from: &TriangleVertex, to: &TriangleVertex) -> bool {
-    if (from.normal.x - to.normal.x).abs() > FLOAT_TOLERANCE
-        || (from.normal.y - to.normal.y).abs() > FLOAT_TOLERANCE
-        || (from.normal.z - to.normal.z).abs() > FLOAT_TOLERANCE
-    {
+/// Compares two triangle vertices for equality, the positions are already known to be within the tolerance.
+fn vertex_equals(from: &TriangleVertex, to: &TriangleVertex, weld_tolerances: &WeldTolerances) -> bool {
+    if from.normal.dot(to.normal) < weld_tolerances.normal_cosine {
         return false;
     }
 
-    if (from.texture_coordinate.x - to.texture_coordinate.x).abs() > FLOAT_TOLERANCE
-        || (from.texture_coordinate.y - to.texture_coordinate.y).abs() > FLOAT_TOLERANCE
+    if (from.texture_coordinate.x - to.texture_coordinate.x).abs() > weld_tolerances.texture_coordinate
+        || (from.texture_coordinate.y - to.texture_coordinate.y).abs() > weld_tolerances.texture_coordinate
     {
         return false;
     }
@@ -330,9 +1373,55 @@ fn vertex_equals(from: &TriangleVertex, to: &TriangleVertex) -> bool {
         return false;
     }
 
+    if from.flex_deltas.len() != to.flex_deltas.len() {
+        return false;
+    }
+
+    if from.flex_deltas.iter().zip(to.flex_deltas.iter()).any(|(from_delta, to_delta)| {
+        let position_difference = from_delta.position - to_delta.position;
+        let normal_difference = from_delta.normal - to_delta.normal;
+        from_delta.flex != to_delta.flex
+            || position_difference.dot(position_difference) > weld_tolerances.position_squared
+            || normal_difference.dot(normal_difference) > weld_tolerances.position_squared
+    }) {
+        return false;
+    }
+
     true
 }
 
+/// Removes the vertices that no triangle uses, returns how many were removed.
+fn remove_unused_vertices(triangle_list: &mut TriangleList) -> usize {
+    let mut used_vertices = vec![false; triangle_list.vertices.len()];
+    for &vertex_index in triangle_list.triangles.iter().flatten() {
+        used_vertices[vertex_index] = true;
+    }
+
+    let unused_vertex_count = used_vertices.iter().filter(|&&used| !used).count();
+    if unused_vertex_count == 0 {
+        return 0;
+    }
+
+    let mut remapped_indices = Vec::with_capacity(used_vertices.len());
+    let mut kept_vertex_count = 0;
+    for &used in &used_vertices {
+        remapped_indices.push(kept_vertex_count);
+        kept_vertex_count += used as usize;
+    }
+
+    let mut used_vertices = used_vertices.into_iter();
+    triangle_list.vertices.retain(|_| used_vertices.next().unwrap());
+
+    for triangle in &mut triangle_list.triangles {
+        *triangle = triangle.map(|index| remapped_indices[index]);
+    }
+
+    // The tree is only used for welding, it is emptied so it can not point at moved vertices.
+    triangle_list.vertex_tree = KdTree::new(3);
+
+    unused_vertex_count
+}
+
 /// Reorders the triangle vertex order to be clockwise.
 fn reorder_triangle_vertex_order(triangle_list: &mut TriangleList) {
     // TODO: Actually implement this function if a file format has a clockwise format.
@@ -341,14 +1430,160 @@ fn reorder_triangle_vertex_order(triangle_list: &mut TriangleList) {
     }
 }
 
-/// Sorts the vertices to decrease the amount of strips.
-fn sort_vertices_by_hardware_bones(_triangle_list: &mut TriangleList) {
-    // TODO: Implement this function.
+/// Logs the materials past the material limit and the materials used by the fewest triangles, which are the cheapest to merge.
+fn report_material_budget(processed_model_data: &ProcessedModelData, material_limit: usize) {
+    let materials_past_limit = processed_model_data.materials.iter().skip(material_limit).cloned().collect::<Vec<_>>();
+    log(
+        format!("Materials Past The Limit Of {}: {}", material_limit, materials_past_limit.join(", ")),
+        LogLevel::Error,
+    );
+
+    let mut triangle_counts = vec![0; processed_model_data.materials.len()];
+    for mesh in processed_model_data
+        .body_parts
+        .iter()
+        .flat_map(|body_part| &body_part.models)
+        .flat_map(|model| &model.meshes)
+    {
+        triangle_counts[mesh.material as usize] += mesh.strip_groups.iter().map(|strip_group| strip_group.indices.len() / 3).sum::<usize>();
+    }
+
+    let mut candidates = (0..processed_model_data.materials.len()).collect::<Vec<_>>();
+    candidates.sort_by_key(|&material_index| triangle_counts[material_index]);
+
+    let candidates = candidates
+        .iter()
+        .take(processed_model_data.materials.len() - material_limit)
+        .map(|&material_index| {
+            format!(
+                "{} ({} Triangles)",
+                processed_model_data.materials[material_index], triangle_counts[material_index]
+            )
+        })
+        .collect::<Vec<_>>();
+    log(format!("Merge Candidates By Triangle Count: {}", candidates.join(", ")), LogLevel::Warn);
+}
+
+/// Validates the imputed mesh limits against what the output format supports.
+fn process_mesh_limits(input: &ImputedCompilationData) -> Result<ProcessedMeshLimits, ProcessingMeshError> {
+    let imputed_limits = &input.mesh_limits;
+
+    if !(1..=MAX_WEIGHTS_PER_VERTEX).contains(&imputed_limits.weights_per_vertex) {
+        return Err(ProcessingMeshError::InvalidWeightsPerVertex(
+            imputed_limits.weights_per_vertex,
+            MAX_WEIGHTS_PER_VERTEX,
+        ));
+    }
+
+    // Every hardware bone of a triangle has to fit into a strip.
+    let minimum_hardware_bones = imputed_limits.weights_per_vertex * 3;
+    if !(minimum_hardware_bones..=MAX_HARDWARE_BONES_PER_STRIP).contains(&imputed_limits.hardware_bones_per_strip) {
+        return Err(ProcessingMeshError::InvalidHardwareBonesPerStrip(
+            imputed_limits.hardware_bones_per_strip,
+            minimum_hardware_bones,
+            MAX_HARDWARE_BONES_PER_STRIP,
+        ));
+    }
+
+    if !(3..=VERTEX_CACHE_SIZE).contains(&imputed_limits.vertex_cache_size) {
+        return Err(ProcessingMeshError::InvalidVertexCacheSize(imputed_limits.vertex_cache_size, VERTEX_CACHE_SIZE));
+    }
+
+    // A threshold below one would require the overdraw order to have fewer cache misses than the vertex cache order.
+    if imputed_limits.optimize_overdraw && !(imputed_limits.overdraw_threshold >= 1.0 && imputed_limits.overdraw_threshold.is_finite()) {
+        return Err(ProcessingMeshError::InvalidOverdrawThreshold(imputed_limits.overdraw_threshold));
+    }
+
+    Ok(ProcessedMeshLimits {
+        hardware_bones_per_strip: imputed_limits.hardware_bones_per_strip,
+        weights_per_vertex: imputed_limits.weights_per_vertex,
+        vertex_cache_size: imputed_limits.vertex_cache_size,
+        overdraw_threshold: imputed_limits.optimize_overdraw.then_some(imputed_limits.overdraw_threshold),
+        mikktspace_tangents: imputed_limits.mikktspace_tangents,
+    })
+}
+
+/// Validates the imputed weld tolerances and converts them to the form they are compared in.
+fn process_weld_tolerances(input: &ImputedCompilationData) -> Result<WeldTolerances, ProcessingMeshError> {
+    let imputed_tolerances = &input.weld_tolerances;
+
+    if !(imputed_tolerances.position >= 0.0 && imputed_tolerances.texture_coordinate >= 0.0) {
+        return Err(ProcessingMeshError::NegativeWeldTolerance);
+    }
+
+    if !(0.0..=180.0).contains(&imputed_tolerances.normal_angle) {
+        return Err(ProcessingMeshError::InvalidWeldNormalAngle(imputed_tolerances.normal_angle));
+    }
+
+    // The vertex tree compares squared distances, and the cosine is lowered slightly so equal normals still weld with no angle.
+    Ok(WeldTolerances {
+        position_squared: imputed_tolerances.position * imputed_tolerances.position,
+        normal_cosine: imputed_tolerances.normal_angle.to_radians().cos() - 1e-9,
+        texture_coordinate: imputed_tolerances.texture_coordinate,
+    })
+}
+
+/// Sorts the triangles into clusters that share hardware bones to decrease the amount of strips.
+/// Triangles keep their order inside a cluster, so this runs after the cache optimizations without undoing them.
+fn sort_vertices_by_hardware_bones(triangle_list: &mut TriangleList, hardware_bones_per_strip: usize) {
+    let triangle_bones = triangle_list
+        .triangles
+        .iter()
+        .map(|triangle| {
+            triangle
+                .iter()
+                .flat_map(|&index| triangle_list.vertices[index].links.iter().map(|link| link.bone))
+                .collect::<IndexSet<u8>>()
+        })
+        .collect::<Vec<_>>();
+
+    let mut remaining_triangles = (0..triangle_list.triangles.len()).collect::<Vec<_>>();
+    let mut sorted_triangles = Vec::with_capacity(remaining_triangles.len());
+
+    while !remaining_triangles.is_empty() {
+        let mut cluster_bones = IndexSet::new();
+
+        loop {
+            // Every triangle that fits in the cluster without new bones is taken, the triangle that adds the least bones is added next.
+            let mut next_triangle: Option<(usize, usize)> = None;
+            remaining_triangles.retain(|&triangle_index| {
+                let new_bone_count = triangle_bones[triangle_index].difference(&cluster_bones).count();
+
+                if new_bone_count == 0 {
+                    sorted_triangles.push(triangle_index);
+                    return false;
+                }
+
+                if cluster_bones.len() + new_bone_count <= hardware_bones_per_strip
+                    && next_triangle.is_none_or(|(_, least_new_bones)| new_bone_count < least_new_bones)
+                {
+                    next_triangle = Some((triangle_index, new_bone_count));
+                }
+
+                true
+            });
+
+            match next_triangle {
+                Some((triangle_index, _)) => cluster_bones.extend(triangle_bones[triangle_index].iter().copied()),
+                None => break,
+            }
+        }
+
+        // A triangle with more bones than a strip can hold would never fit, it is left for the mesh conversion to handle.
+        if cluster_bones.is_empty() {
+            sorted_triangles.append(&mut remaining_triangles);
+        }
+    }
+
+    triangle_list.triangles = sorted_triangles
+        .into_iter()
+        .map(|triangle_index| triangle_list.triangles[triangle_index])
+        .collect();
 }
 
 /// Sorts the indices to decrease the amount of cache misses.
 /// Implementation of https://github.com/zeux/meshoptimizer/blob/master/src/vcacheoptimizer.cpp
-fn optimize_vertex_cache(triangle_list: &mut TriangleList) {
+fn optimize_vertex_cache(triangle_list: &mut TriangleList, cache_size: usize) {
     const VERTEX_VALENCE_SIZE: usize = 8;
 
     struct VertexScoreTable {
@@ -461,7 +1696,7 @@ fn optimize_vertex_cache(triangle_list: &mut TriangleList) {
         }
 
         std::mem::swap(&mut cache, &mut cache_new);
-        cache_count = if cache_write > VERTEX_CACHE_SIZE { VERTEX_CACHE_SIZE } else { cache_write };
+        cache_count = if cache_write > cache_size { cache_size } else { cache_write };
 
         for vertex_index in 0..3 {
             let neighbors = &mut adjacency.data[adjacency.offsets[vertex_index]..];
@@ -486,7 +1721,7 @@ fn optimize_vertex_cache(triangle_list: &mut TriangleList) {
                 continue;
             }
 
-            let cache_position = if cache_value >= VERTEX_CACHE_SIZE { None } else { Some(cache_value) };
+            let cache_position = if cache_value >= cache_size { None } else { Some(cache_value) };
             let score = calculate_vertex_score(cache_position, adjacency.counts[cache_value]);
             let score_difference = score - vertex_scores[cache_value];
 
@@ -522,39 +1757,41 @@ fn optimize_vertex_cache(triangle_list: &mut TriangleList) {
 
 /// Sorts the indices to decrease the amount of overdraw.
 /// Implementation of https://github.com/zeux/meshoptimizer/blob/master/src/overdrawoptimizer.cpp
-fn optimize_overdraw(triangle_list: &mut TriangleList) {
-    // TODO: Configure threshold to work well with source or make it a parameter.
-    let threshold = 1.05;
-
+/// The threshold is how many more vertex cache misses the new order is allowed to have, 1.05 allows 5% more.
+fn optimize_overdraw(triangle_list: &mut TriangleList, cache_size: usize, threshold: f64) {
     let triangle_count = triangle_list.triangles.len();
+    if triangle_count == 0 {
+        return;
+    }
+
     let indices = triangle_list.triangles.drain(..).flatten().collect::<Vec<_>>();
 
-    fn update_cache(a: usize, b: usize, c: usize, cache_timestamps: &mut [usize], timestamp: &mut usize) -> usize {
+    let update_cache = |a: usize, b: usize, c: usize, cache_timestamps: &mut [usize], timestamp: &mut usize| -> usize {
         let mut cache_misses = 0;
 
-        if *timestamp - cache_timestamps[a] > VERTEX_CACHE_SIZE {
+        if *timestamp - cache_timestamps[a] > cache_size {
             cache_timestamps[a] = *timestamp;
             *timestamp += 1;
             cache_misses += 1;
         }
 
-        if *timestamp - cache_timestamps[b] > VERTEX_CACHE_SIZE {
+        if *timestamp - cache_timestamps[b] > cache_size {
             cache_timestamps[b] = *timestamp;
             *timestamp += 1;
             cache_misses += 1;
         }
 
-        if *timestamp - cache_timestamps[c] > VERTEX_CACHE_SIZE {
+        if *timestamp - cache_timestamps[c] > cache_size {
             cache_timestamps[c] = *timestamp;
             *timestamp += 1;
             cache_misses += 1;
         }
 
         cache_misses
-    }
+    };
 
     let mut cache_timestamps = vec![0; triangle_list.vertices.len()];
-    let mut timestamp = VERTEX_CACHE_SIZE + 1;
+    let mut timestamp = cache_size + 1;
     let mut hard_clusters = vec![0; indices.len() / 3];
     let mut hard_cluster_count = 0;
     for triangle_index in 0..triangle_count {
@@ -584,7 +1821,7 @@ fn optimize_overdraw(triangle_list: &mut TriangleList) {
 
         debug_assert!(start < end);
 
-        timestamp += VERTEX_CACHE_SIZE + 1;
+        timestamp += cache_size + 1;
 
         let mut cluster_misses = 0;
         for cache_vertex in start..end {
@@ -600,7 +1837,7 @@ fn optimize_overdraw(triangle_list: &mut TriangleList) {
         soft_clusters[soft_cluster_count] = start;
         soft_cluster_count += 1;
 
-        timestamp += VERTEX_CACHE_SIZE + 1;
+        timestamp += cache_size + 1;
 
         let mut running_misses = 0;
         let mut running_faces = 0;
@@ -617,7 +1854,7 @@ fn optimize_overdraw(triangle_list: &mut TriangleList) {
                 soft_clusters[soft_cluster_count] = cache_vertex + 1;
                 soft_cluster_count += 1;
 
-                timestamp += VERTEX_CACHE_SIZE + 1;
+                timestamp += cache_size + 1;
 
                 running_misses = 0;
                 running_faces = 0;
@@ -667,7 +1904,8 @@ fn optimize_overdraw(triangle_list: &mut TriangleList) {
             let position10 = position1 - position0;
             let position20 = position2 - position0;
 
-            let normal = position10.cross(position20);
+            // The triangles are clockwise at this point, so the cross product is flipped to face outwards.
+            let normal = position20.cross(position10);
 
             let area = normal.magnitude();
 
@@ -692,7 +1930,8 @@ fn optimize_overdraw(triangle_list: &mut TriangleList) {
     let mut sort_keys = vec![0; cluster_count];
     let mut sort_order = vec![0; cluster_count];
 
-    let mut sort_data_max = f64::MIN;
+    // Starts above zero so clusters that all sit on the mesh centroid do not divide by zero.
+    let mut sort_data_max: f64 = 1e-3;
 
     for sort_data_value in &sort_data {
         sort_data_max = sort_data_max.max(sort_data_value.abs());
@@ -757,81 +1996,205 @@ fn optimize_overdraw(triangle_list: &mut TriangleList) {
     debug_assert!(offset == indices.len());
 }
 
-/// Calculates the tangents for each vertex.
-fn calculate_vertex_tangents(triangle_list: &mut TriangleList) -> usize {
+/// Calculates the tangents for each vertex from the texture coordinate directions of the faces around it.
+/// This is not a MikkTSpace implementation, normal maps baked with MikkTSpace should be compiled with the MikkTSpace tangents.
+/// Face tangents are projected onto the plane of the vertex normal and weighted by the angle of the corner.
+/// Returns the positions of the vertices that had no usable tangent.
+fn calculate_vertex_tangents(triangle_list: &mut TriangleList) -> Vec<Vector3> {
     let mut tangents = vec![Vector3::default(); triangle_list.vertices.len()];
     let mut bi_tangents = vec![Vector3::default(); triangle_list.vertices.len()];
 
+    let project = |vector: Vector3, normal: Vector3| (vector - normal * normal.dot(vector)).normalize();
+
     for face in &triangle_list.triangles {
-        let edge1 = triangle_list.vertices[face[1]].position - triangle_list.vertices[face[0]].position;
-        let edge2 = triangle_list.vertices[face[2]].position - triangle_list.vertices[face[0]].position;
-        let delta_uv1 = triangle_list.vertices[face[1]].texture_coordinate - triangle_list.vertices[face[0]].texture_coordinate;
-        let delta_uv2 = triangle_list.vertices[face[2]].texture_coordinate - triangle_list.vertices[face[0]].texture_coordinate;
+        let vertices = face.map(|index| &triangle_list.vertices[index]);
 
-        let denominator = delta_uv1.x * delta_uv2.y - delta_uv2.x * delta_uv1.y;
+        let edge1 = vertices[1].position - vertices[0].position;
+        let edge2 = vertices[2].position - vertices[0].position;
+        let delta_uv1 = vertices[1].texture_coordinate - vertices[0].texture_coordinate;
+        let delta_uv2 = vertices[2].texture_coordinate - vertices[0].texture_coordinate;
 
-        if denominator.abs() < f64::EPSILON {
-            for vertex_index in 0..3 {
-                tangents[face[vertex_index]] = Vector3::new(1.0, 0.0, 0.0);
-                bi_tangents[face[vertex_index]] = Vector3::new(0.0, 1.0, 0.0);
-            }
+        let signed_area = delta_uv1.x * delta_uv2.y - delta_uv2.x * delta_uv1.y;
+
+        // Faces without a texture area have no tangent direction and are left out.
+        if signed_area.abs() < f64::EPSILON {
             continue;
         }
 
-        let area = 1.0 / denominator;
-
-        let tangent = Vector3::new(
-            area * (delta_uv2.y * edge1.x - delta_uv1.y * edge2.x),
-            area * (delta_uv2.y * edge1.y - delta_uv1.y * edge2.y),
-            area * (delta_uv2.y * edge1.z - delta_uv1.y * edge2.z),
-        );
+        // Only the direction is used, so the area is reduced to its sign to keep tiny faces from overflowing.
+        let orientation = signed_area.signum();
+        let face_tangent = (edge1 * delta_uv2.y - edge2 * delta_uv1.y) * orientation;
+        let face_bi_tangent = (edge2 * delta_uv1.x - edge1 * delta_uv2.x) * orientation;
 
-        let bi_tangent = Vector3::new(
-            area * (delta_uv1.x * edge2.x - delta_uv2.x * edge1.x),
-            area * (delta_uv1.x * edge2.y - delta_uv2.x * edge1.y),
-            area * (delta_uv1.x * edge2.z - delta_uv2.x * edge1.z),
-        );
+        for corner in 0..3 {
+            let normal = vertices[corner].normal;
+            let next_edge = project(vertices[(corner + 1) % 3].position - vertices[corner].position, normal);
+            let previous_edge = project(vertices[(corner + 2) % 3].position - vertices[corner].position, normal);
+            let corner_angle = next_edge.dot(previous_edge).clamp(-1.0, 1.0).acos();
 
-        for vertex_index in 0..3 {
-            tangents[face[vertex_index]] = tangents[face[vertex_index]] + tangent;
-            bi_tangents[face[vertex_index]] = bi_tangents[face[vertex_index]] + bi_tangent;
+            tangents[face[corner]] = tangents[face[corner]] + project(face_tangent, normal) * corner_angle;
+            bi_tangents[face[corner]] = bi_tangents[face[corner]] + project(face_bi_tangent, normal) * corner_angle;
         }
     }
 
     triangle_list.tangents.reserve(triangle_list.vertices.len());
-    let mut bad_vertex_count = 0;
-    for index in 0..triangle_list.vertices.len() {
-        let normalized_tangent = tangents[index].normalize();
-        let normalized_bi_tangent = bi_tangents[index].normalize();
+    let mut bad_vertices = Vec::new();
+    for (index, vertex) in triangle_list.vertices.iter().enumerate() {
+        // The tangent is bad when no face around the vertex had a texture direction, or the directions cancelled out.
+        let mut normalized_tangent = project(tangents[index], vertex.normal);
+        if normalized_tangent.magnitude() < 0.5 {
+            bad_vertices.push(vertex.position);
+            normalized_tangent = surface_tangent(vertex.normal);
+        }
+
+        let cross_product = vertex.normal.cross(normalized_tangent);
+        let sign = if cross_product.dot(bi_tangents[index]) < 0.0 { -1.0 } else { 1.0 };
+
+        triangle_list
+            .tangents
+            .push(Vector4::new(normalized_tangent.x, normalized_tangent.y, normalized_tangent.z, sign));
+    }
+
+    bad_vertices
+}
+
+/// Any direction on the surface is better than none for a vertex without a tangent.
+fn surface_tangent(normal: Vector3) -> Vector3 {
+    let axis = if normal.x.abs() < 0.9 {
+        Vector3::new(1.0, 0.0, 0.0)
+    } else {
+        Vector3::new(0.0, 1.0, 0.0)
+    };
+
+    (axis - normal * normal.dot(axis)).normalize()
+}
 
-        let cross_product = triangle_list.vertices[index].normal.cross(normalized_tangent);
-        let sign = if cross_product.dot(normalized_bi_tangent) < 0.0 { -1.0 } else { 1.0 };
+/// The triangles of a triangle list as MikkTSpace sees them, with the tangent it generates for each corner.
+struct MikkTSpaceGeometry<'a> {
+    triangle_list: &'a TriangleList,
+    corner_tangents: Vec<[f32; 4]>,
+}
+
+impl MikkTSpaceGeometry<'_> {
+    fn vertex(&self, face: usize, corner: usize) -> &TriangleVertex {
+        &self.triangle_list.vertices[self.triangle_list.triangles[face][corner]]
+    }
+}
+
+impl mikktspace::Geometry for MikkTSpaceGeometry<'_> {
+    fn num_faces(&self) -> usize {
+        self.triangle_list.triangles.len()
+    }
+
+    fn num_vertices_of_face(&self, _face: usize) -> usize {
+        3
+    }
+
+    fn position(&self, face: usize, corner: usize) -> [f32; 3] {
+        let position = self.vertex(face, corner).position;
+        [position.x as f32, position.y as f32, position.z as f32]
+    }
+
+    fn normal(&self, face: usize, corner: usize) -> [f32; 3] {
+        let normal = self.vertex(face, corner).normal;
+        [normal.x as f32, normal.y as f32, normal.z as f32]
+    }
+
+    fn tex_coord(&self, face: usize, corner: usize) -> [f32; 2] {
+        let texture_coordinate = self.vertex(face, corner).texture_coordinate;
+        [texture_coordinate.x as f32, texture_coordinate.y as f32]
+    }
+
+    fn set_tangent_encoded(&mut self, tangent: [f32; 4], face: usize, corner: usize) {
+        self.corner_tangents[face * 3 + corner] = tangent;
+    }
+}
 
-        let vertex_tangent = Vector4::new(normalized_tangent.x, normalized_tangent.y, normalized_tangent.z, sign);
+/// Calculates the tangents with MikkTSpace, so normal maps baked with it shade the same on split or mirrored texture coordinates.
+/// MikkTSpace generates a tangent for each corner, so vertices whose corners got different tangents are split.
+/// Returns the positions of the vertices that had no usable tangent.
+fn calculate_mikktspace_tangents(triangle_list: &mut TriangleList) -> Vec<Vector3> {
+    // MikkTSpace makes up a tangent for the vertices without a texture direction, so they are found before it runs.
+    let mut has_texture_direction = vec![false; triangle_list.vertices.len()];
+    for face in &triangle_list.triangles {
+        let [first, second, third] = face.map(|index| triangle_list.vertices[index].texture_coordinate);
+        let delta_uv1 = second - first;
+        let delta_uv2 = third - first;
 
-        triangle_list.tangents.push(vertex_tangent);
+        if (delta_uv1.x * delta_uv2.y - delta_uv2.x * delta_uv1.y).abs() >= f64::EPSILON {
+            for &index in face {
+                has_texture_direction[index] = true;
+            }
+        }
+    }
 
-        // This is what source considers a bad vertex.
-        // TODO: Find a better way to calculate vertex tangents to not have bad vertices.
-        let tangent_dot = normalized_tangent.dot(triangle_list.vertices[index].normal);
-        if !(-0.95..=0.95).contains(&tangent_dot) {
-            bad_vertex_count += 1;
+    let bad_vertices = triangle_list
+        .vertices
+        .iter()
+        .zip(&has_texture_direction)
+        .filter(|(_, has_texture_direction)| !**has_texture_direction)
+        .map(|(vertex, _)| vertex.position)
+        .collect();
+
+    let mut geometry = MikkTSpaceGeometry {
+        triangle_list,
+        corner_tangents: vec![[0.0; 4]; triangle_list.triangles.len() * 3],
+    };
+    mikktspace::generate_tangents(&mut geometry);
+    let corner_tangents = geometry.corner_tangents;
+
+    // The tangents each vertex was given with the vertex that holds them, the first is always held by the vertex itself.
+    let mut vertex_tangents: Vec<Vec<([f32; 4], usize)>> = vec![Vec::new(); triangle_list.vertices.len()];
+    triangle_list.tangents = vec![Vector4::default(); triangle_list.vertices.len()];
+    for face_index in 0..triangle_list.triangles.len() {
+        for corner in 0..3 {
+            let vertex_index = triangle_list.triangles[face_index][corner];
+            let corner_tangent = corner_tangents[face_index * 3 + corner];
+
+            let known_vertex = vertex_tangents[vertex_index]
+                .iter()
+                .find(|(known, _)| *known == corner_tangent)
+                .map(|(_, known_vertex)| *known_vertex);
+            let tangent_vertex = match known_vertex {
+                Some(known_vertex) => known_vertex,
+                None => {
+                    let tangent_vertex = if vertex_tangents[vertex_index].is_empty() {
+                        vertex_index
+                    } else {
+                        triangle_list.vertices.push(triangle_list.vertices[vertex_index].clone());
+                        triangle_list.tangents.push(Vector4::default());
+                        triangle_list.vertices.len() - 1
+                    };
+                    vertex_tangents[vertex_index].push((corner_tangent, tangent_vertex));
+
+                    let [x, y, z, sign] = corner_tangent.map(f64::from);
+                    triangle_list.tangents[tangent_vertex] = if Vector3::new(x, y, z).magnitude() < 0.5 {
+                        let fallback = surface_tangent(triangle_list.vertices[vertex_index].normal);
+                        Vector4::new(fallback.x, fallback.y, fallback.z, 1.0)
+                    } else {
+                        Vector4::new(x, y, z, sign)
+                    };
+                    tangent_vertex
+                }
+            };
+            triangle_list.triangles[face_index][corner] = tangent_vertex;
         }
     }
-    bad_vertex_count
+
+    bad_vertices
 }
 
-/// Culls the weight links to a maximum of 3.
-fn cull_weight_links(triangle_list: &mut TriangleList) -> usize {
+/// Culls the weight links to the maximum weights per vertex.
+fn cull_weight_links(triangle_list: &mut TriangleList, weights_per_vertex: usize) -> usize {
     let mut culled_vertex_count = 0;
 
     for vertex in &mut triangle_list.vertices {
-        if vertex.links.len() <= 3 {
+        if vertex.links.len() <= weights_per_vertex {
             continue;
         }
 
         vertex.links.sort_by(|a, b| b.weight.partial_cmp(&a.weight).unwrap());
-        vertex.links.truncate(3);
+        vertex.links.truncate(weights_per_vertex);
         culled_vertex_count += 1;
     }
 
@@ -839,7 +2202,13 @@ fn cull_weight_links(triangle_list: &mut TriangleList) -> usize {
 }
 
 /// Converts a triangle list into a list of processed meshes.
-fn convert_to_meshes(material_index: usize, triangle_list: TriangleList, bounding_box: &mut BoundingBox) -> (Vec<ProcessedMesh>, usize, usize, usize) {
+/// The last value is how many times the list was split by the vertex limit and by the hardware bone limit.
+fn convert_to_meshes(
+    material_index: usize,
+    triangle_list: TriangleList,
+    bounding_box: &mut BoundingBox,
+    hardware_bones_per_strip: usize,
+) -> (Vec<ProcessedMesh>, usize, usize, usize, (usize, usize)) {
     let mut processed_meshes = Vec::new();
 
     let mut processed_mesh = ProcessedMesh {
@@ -850,6 +2219,8 @@ fn convert_to_meshes(material_index: usize, triangle_list: TriangleList, boundin
     let mut triangle_count = 0;
     let mut vertex_count = 0;
     let mut indices_count = 0;
+    let mut vertex_limit_splits = 0;
+    let mut hardware_bone_limit_splits = 0;
 
     let mut processed_strip_group = ProcessedStripGroup::default();
     let mut processed_strip = ProcessedStrip::default();
@@ -873,9 +2244,15 @@ fn convert_to_meshes(material_index: usize, triangle_list: TriangleList, boundin
 
         let unique_new_hardware_bones = new_hardware_bone_count.iter().collect::<IndexSet<_>>();
 
-        if processed_strip_group.vertices.len() + unique_new_vertices.len() > (u16::MAX as usize + 1)
-            || hardware_bones.len() + unique_new_hardware_bones.len() > MAX_HARDWARE_BONES_PER_STRIP
-        {
+        let exceeds_vertex_limit = processed_strip_group.vertices.len() + unique_new_vertices.len() > (u16::MAX as usize + 1);
+        let exceeds_hardware_bone_limit = hardware_bones.len() + unique_new_hardware_bones.len() > hardware_bones_per_strip;
+        if exceeds_vertex_limit || exceeds_hardware_bone_limit {
+            if exceeds_vertex_limit {
+                vertex_limit_splits += 1;
+            } else {
+                hardware_bone_limit_splits += 1;
+            }
+
             processed_strip_group.strips.push(processed_strip);
             processed_mesh.strip_groups.push(processed_strip_group);
             processed_meshes.push(processed_mesh);
@@ -987,6 +2364,26 @@ fn convert_to_meshes(material_index: usize, triangle_list: TriangleList, boundin
             mapped_indices.insert(index, processed_strip_group.vertices.len());
             processed_strip.indices_count += 1;
 
+            for flex_delta in &vertex_data.flex_deltas {
+                let flex_index = match processed_mesh.flexes.iter().position(|processed_flex| processed_flex.flex == flex_delta.flex) {
+                    Some(flex_index) => flex_index,
+                    None => {
+                        processed_mesh.flexes.push(ProcessedFlex {
+                            flex: flex_delta.flex,
+                            ..Default::default()
+                        });
+                        processed_mesh.flexes.len() - 1
+                    }
+                };
+
+                processed_mesh.flexes[flex_index].vertices.push(ProcessedFlexVertex {
+                    vertex: processed_mesh.vertex_data.len(),
+                    position: flex_delta.position,
+                    normal: flex_delta.normal,
+                });
+                processed_strip_group.is_flexed = true;
+            }
+
             processed_strip_group.vertices.push(processed_mesh_vertex);
             processed_mesh.vertex_data.push(processed_vertex);
             processed_strip.vertex_count += 1;
@@ -1002,5 +2399,148 @@ fn convert_to_meshes(material_index: usize, triangle_list: TriangleList, boundin
     processed_mesh.strip_groups.push(processed_strip_group);
     processed_meshes.push(processed_mesh);
 
-    (processed_meshes, triangle_count, vertex_count, indices_count)
+    (
+        processed_meshes,
+        triangle_count,
+        vertex_count,
+        indices_count,
+        (vertex_limit_splits, hardware_bone_limit_splits),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utilities::mathematics::Quaternion;
+
+    /// Adds a quad of two triangles with its own vertices, wound clockwise when looking at it against its normal.
+    fn add_quad(triangle_list: &mut TriangleList, center: Vector3, normal: Vector3, size: f64) {
+        let helper = if normal.x.abs() < 0.5 {
+            Vector3::new(1.0, 0.0, 0.0)
+        } else {
+            Vector3::new(0.0, 1.0, 0.0)
+        };
+        let right = (helper - normal * normal.dot(helper)).normalize() * size;
+        let up = normal.cross(right);
+
+        let first_vertex = triangle_list.vertices.len();
+        for corner in [center - right + up, center + right + up, center + right - up, center - right - up] {
+            triangle_list.vertices.push(TriangleVertex {
+                position: corner,
+                normal,
+                ..Default::default()
+            });
+        }
+
+        triangle_list.triangles.push([first_vertex, first_vertex + 1, first_vertex + 2]);
+        triangle_list.triangles.push([first_vertex, first_vertex + 2, first_vertex + 3]);
+    }
+
+    fn add_cube(triangle_list: &mut TriangleList) {
+        for normal in [
+            Vector3::new(1.0, 0.0, 0.0),
+            Vector3::new(-1.0, 0.0, 0.0),
+            Vector3::new(0.0, 1.0, 0.0),
+            Vector3::new(0.0, -1.0, 0.0),
+            Vector3::new(0.0, 0.0, 1.0),
+            Vector3::new(0.0, 0.0, -1.0),
+        ] {
+            add_quad(triangle_list, normal, normal, 1.0);
+        }
+    }
+
+    fn sorted_triangles(triangle_list: &TriangleList) -> Vec<[usize; 3]> {
+        let mut triangles = triangle_list.triangles.clone();
+        triangles.sort();
+        triangles
+    }
+
+    #[test]
+    fn optimize_overdraw_keeps_every_triangle() {
+        let mut triangle_list = TriangleList::default();
+        add_quad(&mut triangle_list, Vector3::new(0.0, 0.0, 0.1), Vector3::new(0.0, 0.0, 1.0), 0.1);
+        add_cube(&mut triangle_list);
+        let triangles = sorted_triangles(&triangle_list);
+
+        optimize_overdraw(&mut triangle_list, 16, 1.05);
+
+        assert_eq!(sorted_triangles(&triangle_list), triangles);
+        assert!(triangle_list.triangles.iter().flatten().all(|index| *index < triangle_list.vertices.len()));
+    }
+
+    #[test]
+    fn optimize_overdraw_draws_occluded_clusters_last() {
+        let mut triangle_list = TriangleList::default();
+        add_quad(&mut triangle_list, Vector3::new(0.0, 0.0, 0.1), Vector3::new(0.0, 0.0, 1.0), 0.1);
+        add_cube(&mut triangle_list);
+        let inner_triangles = triangle_list.triangles[..2].to_vec();
+
+        optimize_overdraw(&mut triangle_list, 16, 1.05);
+
+        assert_eq!(triangle_list.triangles[triangle_list.triangles.len() - 2..], inner_triangles);
+    }
+
+    #[test]
+    fn optimize_overdraw_keeps_an_order_without_occluders() {
+        let mut triangle_list = TriangleList::default();
+        add_cube(&mut triangle_list);
+        let triangles = triangle_list.triangles.clone();
+
+        optimize_overdraw(&mut triangle_list, 16, 1.05);
+
+        assert_eq!(triangle_list.triangles, triangles);
+    }
+
+    #[test]
+    fn optimize_overdraw_keeps_an_empty_list_empty() {
+        let mut triangle_list = TriangleList::default();
+
+        optimize_overdraw(&mut triangle_list, 16, 1.05);
+
+        assert!(triangle_list.triangles.is_empty());
+    }
+
+    fn side_bones(right_roll: f64) -> Vec<ImportBone> {
+        vec![
+            ImportBone {
+                name: "Hand_L".to_string(),
+                parent: None,
+                position: Vector3::new(10.0, 0.0, 0.0),
+                orientation: Quaternion::default(),
+            },
+            ImportBone {
+                name: "Hand_R".to_string(),
+                parent: None,
+                position: Vector3::new(-10.0, 0.0, 0.0),
+                orientation: Quaternion::from_axis_angle(Vector3::new(0.0, 0.0, 1.0), f64::consts::PI)
+                    * Quaternion::from_axis_angle(Vector3::new(1.0, 0.0, 0.0), right_roll),
+            },
+        ]
+    }
+
+    fn mirror_bound_vertex(skeleton: &[ImportBone]) -> Vector3 {
+        let mirror_transforms = mirror_bone_transforms(skeleton, &[1, 0], 0);
+        let vertex = TriangleVertex {
+            position: Vector3::new(12.0, 1.0, 0.0),
+            normal: Vector3::new(0.0, 1.0, 0.0),
+            ..Default::default()
+        };
+
+        mirror_vertex(&vertex, &[ImportLink { bone: 0, weight: 1.0 }], &mirror_transforms, 0).0
+    }
+
+    #[test]
+    fn mirror_vertex_reflects_across_mirrored_bones() {
+        let position = mirror_bound_vertex(&side_bones(0.0));
+
+        assert!((position - Vector3::new(-12.0, 1.0, 0.0)).magnitude() < 1e-9);
+    }
+
+    #[test]
+    fn mirror_vertex_follows_the_roll_of_the_other_bone() {
+        let roll = 30.0_f64.to_radians();
+        let position = mirror_bound_vertex(&side_bones(roll));
+
+        assert!((position - Vector3::new(-12.0, roll.cos(), -roll.sin())).magnitude() < 1e-9);
+    }
 }
@@ -0,0 +1,866 @@
+use std::{
+    collections::{HashMap, HashSet},
+    f64::consts::PI,
+};
+
+use tauri::State;
+use thiserror::Error as ThisError;
+
+use crate::{
+    import::{FileManager, ImportPart, ImportVertex},
+    input::{ImputedCompilationData, ImputedPhysics, ImputedPhysicsShape},
+    utilities::{
+        logging::{log, LogLevel},
+        mathematics::{BoundingBox, Vector3},
+    },
+};
+
+use super::{ProcessedBoneData, ProcessedConvexHull, ProcessedJointLimit, ProcessedPhysicsData, ProcessedPhysicsSolid, ProcessedRagdollConstraint};
+
+#[derive(Debug, ThisError)]
+pub enum ProcessingPhysicsError {
+    #[error("Physics Has No Solids")]
+    NoSolids,
+    #[error("Physics Total Mass Must Be Above 0")]
+    InvalidTotalMass,
+    #[error("Physics Solid Bone Not Found: {0}")]
+    SolidBoneNotFound(String),
+    #[error("Bone {0} Already Has A Physics Solid")]
+    DuplicateSolid(String),
+    #[error("Physics Solid On Bone {0} Has An Invalid Shape")]
+    InvalidShape(String),
+    #[error("Physics Solid On Bone {0} Has An Invalid Joint Limit")]
+    InvalidJointLimit(String),
+    #[error("Physics File Source Not Loaded: {0}")]
+    FileSourceNotLoaded(String),
+    #[error("Physics Solid On Bone {0} Has Invalid Decomposition Settings")]
+    InvalidDecomposition(String),
+    #[error("Physics Solid On Bone {0} Has No Volume")]
+    SolidHasNoVolume(String),
+    #[error("Physics Solid On Bone {0} Has {1} Triangles But The Limit Is {2}")]
+    SolidTooComplex(String, usize, usize),
+    #[error("Physics Solids On Bones {0} And {1} Both Have No Parent Solid, A Ragdoll Can Only Have One Root")]
+    MultipleRootSolids(String, String),
+}
+
+/// The triangles of each hull of a solid are numbered with 12 bits in the physics file.
+const MAX_SOLID_TRIANGLES: usize = 4096;
+/// The segments around a capsule and the rings of each of its caps.
+const CAPSULE_SEGMENTS: usize = 12;
+const CAPSULE_RINGS: usize = 3;
+/// How far a polygon of a collision mesh can be from the surface of its hulls before it is reported.
+const COLLISION_SURFACE_TOLERANCE: f64 = 0.01;
+/// Polygons of a collision mesh with less area than this are reported as degenerate.
+const COLLISION_AREA_TOLERANCE: f64 = 0.000001;
+/// How deep the surface of a decomposed piece can be inside of its hull, relative to the size of the mesh, before the piece is split.
+const CONCAVITY_TOLERANCE: f64 = 0.01;
+/// The cuts tried across a concave piece along each axis, the piece is split into this many slices.
+const CONCAVITY_CUTS: usize = 8;
+
+/// The triangles of a piece of a collision mesh.
+type MeshPiece = Vec<[Vector3; 3]>;
+
+pub fn process_physics(
+    input: &ImputedCompilationData,
+    file_manager: &State<FileManager>,
+    processed_bone_data: &ProcessedBoneData,
+) -> Result<Option<ProcessedPhysicsData>, ProcessingPhysicsError> {
+    let imputed_physics = match &input.physics {
+        Some(physics) => physics,
+        None => return Ok(None),
+    };
+
+    if imputed_physics.solids.is_empty() {
+        return Err(ProcessingPhysicsError::NoSolids);
+    }
+
+    if imputed_physics.total_mass.is_nan() || imputed_physics.total_mass <= 0.0 {
+        return Err(ProcessingPhysicsError::InvalidTotalMass);
+    }
+
+    let origin = Vector3::new(input.origin[0], input.origin[1], input.origin[2]);
+    let mut solids = Vec::with_capacity(imputed_physics.solids.len());
+    let mut limits = Vec::with_capacity(imputed_physics.solids.len());
+    for imputed_solid in &imputed_physics.solids {
+        let bone = processed_bone_data
+            .processed_bones
+            .get_index_of(&imputed_solid.bone)
+            .ok_or_else(|| ProcessingPhysicsError::SolidBoneNotFound(imputed_solid.bone.clone()))?;
+
+        if solids.iter().any(|solid: &ProcessedPhysicsSolid| solid.bone == bone) {
+            return Err(ProcessingPhysicsError::DuplicateSolid(imputed_solid.bone.clone()));
+        }
+
+        for limit in &imputed_solid.limits {
+            if limit.minimum.is_nan()
+                || limit.maximum.is_nan()
+                || limit.minimum > limit.maximum
+                || limit.minimum < -180.0
+                || limit.maximum > 180.0
+                || limit.friction.is_nan()
+                || limit.friction < 0.0
+            {
+                return Err(ProcessingPhysicsError::InvalidJointLimit(imputed_solid.bone.clone()));
+            }
+        }
+
+        let hulls: Vec<ProcessedConvexHull> = match &imputed_solid.shape {
+            ImputedPhysicsShape::Box { minimum, maximum } => {
+                if (0..3).any(|axis| minimum[axis].is_nan() || maximum[axis].is_nan() || minimum[axis] >= maximum[axis]) {
+                    return Err(ProcessingPhysicsError::InvalidShape(imputed_solid.bone.clone()));
+                }
+
+                let corners = (0..8)
+                    .map(|corner| {
+                        let pick = |axis: usize| if corner & (1 << axis) == 0 { minimum[axis] } else { maximum[axis] };
+                        Vector3::new(pick(0), pick(1), pick(2))
+                    })
+                    .collect::<Vec<_>>();
+                create_convex_hull(&corners).into_iter().collect()
+            }
+            ImputedPhysicsShape::Capsule { radius, length } => {
+                if radius.is_nan() || length.is_nan() || *radius <= 0.0 || *length < 0.0 {
+                    return Err(ProcessingPhysicsError::InvalidShape(imputed_solid.bone.clone()));
+                }
+
+                create_convex_hull(&create_capsule_points(*radius, *length)).into_iter().collect()
+            }
+            ImputedPhysicsShape::Mesh { file_source, decomposition } => {
+                let imported_file = file_manager
+                    .get_file(file_source)
+                    .ok_or_else(|| ProcessingPhysicsError::FileSourceNotLoaded(file_source.clone()))?;
+
+                // Vertices are moved like the meshes of the model, then into the space of the bone.
+                let bone_transform = processed_bone_data.processed_bones[bone].pose.transpose();
+                let is_bone_vertex = |vertex: &ImportVertex| {
+                    vertex
+                        .links
+                        .iter()
+                        .max_by(|first, second| first.weight.total_cmp(&second.weight))
+                        .is_some_and(|link| imported_file.skeleton[link.bone].name == imputed_solid.bone)
+                };
+
+                let hulls = match decomposition {
+                    Some(decomposition) => {
+                        if decomposition.maximum_hulls == 0 || decomposition.maximum_vertices < 4 {
+                            return Err(ProcessingPhysicsError::InvalidDecomposition(imputed_solid.bone.clone()));
+                        }
+
+                        let pieces = find_connected_pieces(&imported_file.parts, is_bone_vertex)
+                            .into_iter()
+                            .map(|piece| {
+                                piece
+                                    .into_iter()
+                                    .map(|triangle| triangle.map(|position| bone_transform.transform(position - origin)))
+                                    .collect()
+                            })
+                            .collect();
+                        let hulls = decompose_pieces(pieces, decomposition.maximum_hulls)
+                            .into_iter()
+                            .map(|hull| simplify_convex_hull(hull, decomposition.maximum_vertices))
+                            .collect::<Vec<_>>();
+                        log(
+                            format!("Physics solid on bone {} was decomposed into {} hulls", imputed_solid.bone, hulls.len()),
+                            LogLevel::Verbose,
+                        );
+                        hulls
+                    }
+                    None => {
+                        let points = imported_file
+                            .parts
+                            .iter()
+                            .flat_map(|part| &part.vertices)
+                            .filter(|vertex| is_bone_vertex(vertex))
+                            .map(|vertex| bone_transform.transform(vertex.position - origin))
+                            .collect::<Vec<_>>();
+                        create_convex_hull(&points).into_iter().collect()
+                    }
+                };
+
+                validate_collision_mesh(
+                    &imputed_solid.bone,
+                    &imported_file.parts,
+                    is_bone_vertex,
+                    |position| bone_transform.transform(position - origin),
+                    &hulls,
+                );
+                hulls
+            }
+        };
+
+        if hulls.is_empty() {
+            return Err(ProcessingPhysicsError::SolidHasNoVolume(imputed_solid.bone.clone()));
+        }
+
+        if let Some(hull) = hulls.iter().find(|hull| hull.triangles.len() > MAX_SOLID_TRIANGLES) {
+            return Err(ProcessingPhysicsError::SolidTooComplex(
+                imputed_solid.bone.clone(),
+                hull.triangles.len(),
+                MAX_SOLID_TRIANGLES,
+            ));
+        }
+
+        solids.push(ProcessedPhysicsSolid {
+            bone,
+            name: imputed_solid.bone.clone(),
+            mass: 0.0,
+            hulls,
+        });
+        limits.push(imputed_solid.limits.each_ref().map(|limit| ProcessedJointLimit {
+            minimum: limit.minimum,
+            maximum: limit.maximum,
+            friction: limit.friction,
+        }));
+    }
+
+    distribute_mass(imputed_physics, &mut solids);
+
+    // Each solid is jointed to the solid of its closest parent bone, which leaves a single solid as the root of the ragdoll.
+    let mut constraints = Vec::new();
+    let mut root_solid: Option<usize> = None;
+    for (solid_index, solid) in solids.iter().enumerate() {
+        let mut parent_bone = processed_bone_data.processed_bones[solid.bone].parent;
+        let parent_solid = loop {
+            match parent_bone {
+                Some(bone) => match solids.iter().position(|solid| solid.bone == bone) {
+                    Some(parent_solid) => break Some(parent_solid),
+                    None => parent_bone = processed_bone_data.processed_bones[bone].parent,
+                },
+                None => break None,
+            }
+        };
+
+        match parent_solid {
+            Some(parent) => constraints.push(ProcessedRagdollConstraint {
+                parent,
+                child: solid_index,
+                limits: limits[solid_index],
+            }),
+            None => {
+                if let Some(root_solid) = root_solid {
+                    return Err(ProcessingPhysicsError::MultipleRootSolids(solids[root_solid].name.clone(), solid.name.clone()));
+                }
+                root_solid = Some(solid_index);
+            }
+        }
+    }
+
+    log(
+        format!("Physics has {} solids and {} constraints", solids.len(), constraints.len()),
+        LogLevel::Verbose,
+    );
+
+    Ok(Some(ProcessedPhysicsData {
+        solids,
+        constraints,
+        total_mass: imputed_physics.total_mass,
+    }))
+}
+
+/// Splits the total mass between the solids by their volume, so denser parts of the model need a separate physics model.
+fn distribute_mass(imputed_physics: &ImputedPhysics, solids: &mut [ProcessedPhysicsSolid]) {
+    let total_volume = solids.iter().map(|solid| solid.volume()).sum::<f64>();
+
+    for solid in solids {
+        solid.mass = imputed_physics.total_mass * solid.volume() / total_volume;
+    }
+}
+
+/// The points of a capsule from the origin along the X axis, the caps are rings of points that close in on the poles.
+fn create_capsule_points(radius: f64, length: f64) -> Vec<Vector3> {
+    let mut points = Vec::new();
+
+    for (center, direction) in [(0.0, -1.0), (length, 1.0)] {
+        points.push(Vector3::new(center + direction * radius, 0.0, 0.0));
+
+        for ring in 0..CAPSULE_RINGS {
+            let latitude = ring as f64 / CAPSULE_RINGS as f64 * PI / 2.0;
+            let ring_radius = radius * latitude.cos();
+
+            for segment in 0..CAPSULE_SEGMENTS {
+                let longitude = segment as f64 / CAPSULE_SEGMENTS as f64 * PI * 2.0;
+                points.push(Vector3::new(
+                    center + direction * radius * latitude.sin(),
+                    ring_radius * longitude.cos(),
+                    ring_radius * longitude.sin(),
+                ));
+            }
+        }
+    }
+
+    points
+}
+
+impl ProcessedPhysicsSolid {
+    pub fn volume(&self) -> f64 {
+        self.hulls.iter().map(|hull| hull.volume()).sum()
+    }
+
+    pub fn center_of_mass(&self) -> Vector3 {
+        let weighted_center = self
+            .hulls
+            .iter()
+            .fold(Vector3::default(), |sum, hull| sum + hull.center_of_mass() * hull.volume());
+        weighted_center / self.volume()
+    }
+}
+
+impl ProcessedConvexHull {
+    pub fn volume(&self) -> f64 {
+        self.triangles
+            .iter()
+            .map(|&[a, b, c]| self.points[a].dot(self.points[b].cross(self.points[c])) / 6.0)
+            .sum()
+    }
+
+    /// How far the point is outside of the hull, negative when the point is inside.
+    pub fn distance_outside(&self, point: Vector3) -> f64 {
+        self.triangles
+            .iter()
+            .map(|&[a, b, c]| {
+                let (a, b, c) = (self.points[a], self.points[b], self.points[c]);
+                (b - a).cross(c - a).normalize().dot(point - a)
+            })
+            .fold(f64::MIN, f64::max)
+    }
+
+    /// How far the point is from the surface of the hull along the direction, either forwards or backwards.
+    pub fn distance_to_surface(&self, point: Vector3, direction: Vector3) -> f64 {
+        let mut forwards = f64::INFINITY;
+        let mut backwards = f64::INFINITY;
+        for &[a, b, c] in &self.triangles {
+            let (a, b, c) = (self.points[a], self.points[b], self.points[c]);
+            let normal = (b - a).cross(c - a).normalize();
+            let inside = -normal.dot(point - a);
+            let speed = normal.dot(direction);
+            if speed > 0.0 {
+                forwards = forwards.min(inside / speed);
+            } else if speed < 0.0 {
+                backwards = backwards.min(inside / -speed);
+            }
+        }
+        forwards.min(backwards).max(0.0)
+    }
+
+    /// The center of mass of the hull when it has the same density everywhere.
+    pub fn center_of_mass(&self) -> Vector3 {
+        let mut weighted_center = Vector3::default();
+        let mut volume = 0.0;
+
+        for &[a, b, c] in &self.triangles {
+            let (a, b, c) = (self.points[a], self.points[b], self.points[c]);
+            let tetrahedron_volume = a.dot(b.cross(c)) / 6.0;
+            weighted_center = weighted_center + (a + b + c) / 4.0 * tetrahedron_volume;
+            volume += tetrahedron_volume;
+        }
+
+        weighted_center / volume
+    }
+}
+
+/// Builds the convex hull of the points by growing a tetrahedron one point at a time, none if the points have no volume.
+pub fn create_convex_hull(points: &[Vector3]) -> Option<ProcessedConvexHull> {
+    let first = *points.first()?;
+    let (second_index, extent) = points
+        .iter()
+        .map(|point| (*point - first).magnitude())
+        .enumerate()
+        .max_by(|(_, first), (_, second)| first.total_cmp(second))?;
+
+    if !extent.is_finite() || extent <= 0.0 {
+        return None;
+    }
+
+    // Points closer than this to a triangle are treated as on it, so flat faces do not split into slivers.
+    let tolerance = extent * 1e-6;
+
+    let second = points[second_index];
+    let line = (second - first).normalize();
+    let (third_index, line_distance) = points
+        .iter()
+        .map(|point| (*point - first).cross(line).magnitude())
+        .enumerate()
+        .max_by(|(_, first), (_, second)| first.total_cmp(second))?;
+    if line_distance <= tolerance {
+        return None;
+    }
+
+    let normal = (second - first).cross(points[third_index] - first).normalize();
+    let (fourth_index, plane_distance) = points
+        .iter()
+        .map(|point| normal.dot(*point - first).abs())
+        .enumerate()
+        .max_by(|(_, first), (_, second)| first.total_cmp(second))?;
+    if plane_distance <= tolerance {
+        return None;
+    }
+
+    let center = (first + second + points[third_index] + points[fourth_index]) / 4.0;
+    let distance_to_plane = |triangle: &[usize; 3], point: Vector3| {
+        let [a, b, c] = triangle.map(|index| points[index]);
+        (b - a).cross(c - a).normalize().dot(point - a)
+    };
+
+    let mut triangles = vec![
+        [0, second_index, third_index],
+        [0, second_index, fourth_index],
+        [0, third_index, fourth_index],
+        [second_index, third_index, fourth_index],
+    ];
+
+    // Triangles wind counterclockwise seen from outside the hull.
+    for triangle in &mut triangles {
+        if distance_to_plane(triangle, center) > 0.0 {
+            triangle.swap(1, 2);
+        }
+    }
+
+    for (point_index, &point) in points.iter().enumerate() {
+        let visible = triangles
+            .iter()
+            .map(|triangle| distance_to_plane(triangle, point) > tolerance)
+            .collect::<Vec<_>>();
+        if !visible.contains(&true) {
+            continue;
+        }
+
+        // The edges are kept in order so the same points always build the same hull.
+        let visible_edges = triangles
+            .iter()
+            .zip(&visible)
+            .filter(|(_, &visible)| visible)
+            .flat_map(|(triangle, _)| (0..3).map(|corner| (triangle[corner], triangle[(corner + 1) % 3])))
+            .collect::<Vec<_>>();
+        let visible_edge_set = visible_edges.iter().copied().collect::<HashSet<_>>();
+
+        // The edges between the visible and hidden triangles are connected to the point, which replaces the visible triangles.
+        let mut visible = visible.into_iter();
+        triangles.retain(|_| !visible.next().unwrap());
+        for (start, end) in visible_edges {
+            if !visible_edge_set.contains(&(end, start)) {
+                triangles.push([start, end, point_index]);
+            }
+        }
+    }
+
+    // Only the points on the hull are kept.
+    let mut hull = ProcessedConvexHull::default();
+    let mut remapped_points = vec![None; points.len()];
+    for triangle in &mut triangles {
+        for index in triangle.iter_mut() {
+            *index = *remapped_points[*index].get_or_insert_with(|| {
+                hull.points.push(points[*index]);
+                hull.points.len() - 1
+            });
+        }
+    }
+    hull.triangles = triangles;
+
+    Some(hull)
+}
+
+fn find_root(roots: &mut [usize], mut index: usize) -> usize {
+    while roots[index] != index {
+        roots[index] = roots[roots[index]];
+        index = roots[index];
+    }
+    index
+}
+
+fn join_roots(roots: &mut [usize], first: usize, second: usize) {
+    let (first_root, second_root) = (find_root(roots, first), find_root(roots, second));
+    roots[first_root.max(second_root)] = first_root.min(second_root);
+}
+
+/// Groups the polygons of the vertices that pass the filter into the pieces of the mesh that are connected, as triangles.
+/// Vertices at the same position are connected, as source files repeat the vertices of each polygon.
+/// A vertex that is not on a triangle of the piece is kept as a triangle with the vertex as every corner.
+fn find_connected_pieces(parts: &[ImportPart], is_piece_vertex: impl Fn(&ImportVertex) -> bool) -> Vec<MeshPiece> {
+    let positions = parts.iter().flat_map(|part| &part.vertices).map(|vertex| vertex.position).collect::<Vec<_>>();
+    let included = parts.iter().flat_map(|part| &part.vertices).map(&is_piece_vertex).collect::<Vec<_>>();
+    let mut roots = (0..positions.len()).collect::<Vec<_>>();
+
+    let mut welded_positions = HashMap::new();
+    for (index, position) in positions.iter().enumerate().filter(|&(index, _)| included[index]) {
+        let key = [position.x.to_bits(), position.y.to_bits(), position.z.to_bits()];
+        let welded = *welded_positions.entry(key).or_insert(index);
+        join_roots(&mut roots, welded, index);
+    }
+
+    let mut polygons = Vec::new();
+    let mut part_offset = 0;
+    for part in parts {
+        for polygon in part.polygons.values().flatten() {
+            let corners = polygon
+                .iter()
+                .map(|corner| part_offset + corner)
+                .filter(|&corner| included[corner])
+                .collect::<Vec<_>>();
+            for pair in corners.windows(2) {
+                join_roots(&mut roots, pair[0], pair[1]);
+            }
+            polygons.push(corners);
+        }
+        part_offset += part.vertices.len();
+    }
+
+    // Pieces are kept in the order of their first vertex, so the same mesh always decomposes the same way.
+    let mut piece_indices = HashMap::new();
+    let mut pieces: Vec<MeshPiece> = Vec::new();
+    for index in (0..positions.len()).filter(|&index| included[index]) {
+        let root = find_root(&mut roots, index);
+        piece_indices.entry(root).or_insert_with(|| {
+            pieces.push(Vec::new());
+            pieces.len() - 1
+        });
+    }
+
+    let mut on_triangle = vec![false; positions.len()];
+    for corners in polygons.iter().filter(|corners| corners.len() >= 3) {
+        let piece = piece_indices[&find_root(&mut roots, corners[0])];
+        for index in 1..corners.len() - 1 {
+            pieces[piece].push([corners[0], corners[index], corners[index + 1]].map(|corner| positions[corner]));
+        }
+        for &corner in corners {
+            on_triangle[corner] = true;
+        }
+    }
+
+    for index in (0..positions.len()).filter(|&index| included[index] && !on_triangle[index]) {
+        let piece = piece_indices[&find_root(&mut roots, index)];
+        pieces[piece].push([positions[index]; 3]);
+    }
+
+    pieces
+}
+
+/// Hulls each piece and merges the pieces until none are flat and there are no more than the maximum hulls.
+/// The pieces merged are the ones whose bounds grow the least, so pieces that are close together are merged first.
+/// Pieces that are still concave are then split while there are hulls left, see [`split_concave_pieces`].
+fn decompose_pieces(pieces: Vec<MeshPiece>, maximum_hulls: usize) -> Vec<ProcessedConvexHull> {
+    let piece_bounds = |piece: &MeshPiece| BoundingBox::from_points(piece.iter().flatten());
+    let piece_hull = |piece: &MeshPiece| create_convex_hull(&piece.iter().flatten().copied().collect::<Vec<_>>());
+
+    let mesh_bounds = BoundingBox::from_points(pieces.iter().flatten().flatten());
+    let mut pieces = pieces
+        .into_iter()
+        .map(|piece| {
+            let hull = piece_hull(&piece);
+            let bounds = piece_bounds(&piece);
+            (piece, bounds, hull)
+        })
+        .collect::<Vec<_>>();
+
+    let bounds_volume = |bounds: &BoundingBox| {
+        let size = bounds.size();
+        size.x * size.y * size.z
+    };
+    let merge_cost = |first: &BoundingBox, second: &BoundingBox| {
+        let mut merged = *first;
+        merged.add_bounding_box(second);
+        bounds_volume(&merged) - bounds_volume(first) - bounds_volume(second)
+    };
+
+    while pieces.len() > 1 {
+        let flat_piece = pieces.iter().position(|(_, _, hull)| hull.is_none());
+        if flat_piece.is_none() && pieces.len() <= maximum_hulls {
+            break;
+        }
+
+        let candidates = match flat_piece {
+            Some(flat_piece) => (0..pieces.len())
+                .filter(|&other| other != flat_piece)
+                .map(|other| (flat_piece, other))
+                .collect::<Vec<_>>(),
+            None => (0..pieces.len())
+                .flat_map(|first| (first + 1..pieces.len()).map(move |second| (first, second)))
+                .collect(),
+        };
+        let (first, second) = candidates
+            .into_iter()
+            .min_by(|&(first_a, second_a), &(first_b, second_b)| {
+                merge_cost(&pieces[first_a].1, &pieces[second_a].1).total_cmp(&merge_cost(&pieces[first_b].1, &pieces[second_b].1))
+            })
+            .unwrap();
+
+        let (first, second) = (first.min(second), first.max(second));
+        let (removed_triangles, _, _) = pieces.remove(second);
+        let piece = &mut pieces[first];
+        piece.0.extend(removed_triangles);
+        piece.1 = piece_bounds(&piece.0);
+        piece.2 = piece_hull(&piece.0);
+    }
+
+    let pieces = pieces.into_iter().filter_map(|(piece, _, hull)| Some((piece, hull?))).collect();
+    let tolerance = mesh_bounds.size().magnitude() * CONCAVITY_TOLERANCE;
+    split_concave_pieces(pieces, maximum_hulls, tolerance)
+}
+
+/// Splits the piece with the deepest point inside of its hull in two until every piece is within the tolerance or there are no hulls left.
+/// Each piece is cut by the plane along an axis that gives the smallest hulls, which are tried through the deepest point and across the piece.
+fn split_concave_pieces(mut pieces: Vec<(MeshPiece, ProcessedConvexHull)>, maximum_hulls: usize, tolerance: f64) -> Vec<ProcessedConvexHull> {
+    // The corners and center of each triangle are moved along its normal, points on the surface of the hull can not move one way.
+    let concavity = |piece: &MeshPiece, hull: &ProcessedConvexHull| {
+        piece
+            .iter()
+            .filter_map(|&[a, b, c]| {
+                let normal = (b - a).cross(c - a);
+                if normal.magnitude() <= 0.0 {
+                    return None;
+                }
+                let normal = normal.normalize();
+                Some([a, b, c, (a + b + c) / 3.0].map(|point| (point, hull.distance_to_surface(point, normal))))
+            })
+            .flatten()
+            .max_by(|(_, first), (_, second)| first.total_cmp(second))
+    };
+
+    let mut concave_pieces = pieces.iter().map(|(piece, hull)| concavity(piece, hull)).collect::<Vec<_>>();
+    while pieces.len() < maximum_hulls {
+        let deepest = concave_pieces
+            .iter()
+            .enumerate()
+            .filter_map(|(index, concave)| concave.map(|(corner, depth)| (index, corner, depth)))
+            .filter(|&(_, _, depth)| depth > tolerance)
+            .max_by(|(_, _, first), (_, _, second)| first.total_cmp(second));
+        let Some((index, corner, _)) = deepest else {
+            break;
+        };
+
+        let piece = &pieces[index].0;
+        let bounds = BoundingBox::from_points(piece.iter().flatten());
+        let cuts = (0..3).flat_map(|axis| {
+            let through_corner = (axis, corner[axis]);
+            let across = (1..CONCAVITY_CUTS).map(move |cut| {
+                (
+                    axis,
+                    bounds.minimum[axis] + (bounds.maximum[axis] - bounds.minimum[axis]) * cut as f64 / CONCAVITY_CUTS as f64,
+                )
+            });
+            std::iter::once(through_corner).chain(across)
+        });
+
+        let best_split = cuts
+            .filter_map(|(axis, value)| {
+                let (below, above) = split_piece(piece, axis, value);
+                let below_hull = create_convex_hull(&below.iter().flatten().copied().collect::<Vec<_>>())?;
+                let above_hull = create_convex_hull(&above.iter().flatten().copied().collect::<Vec<_>>())?;
+                Some([(below, below_hull), (above, above_hull)])
+            })
+            .min_by(|first, second| {
+                let volume = |split: &[(MeshPiece, ProcessedConvexHull); 2]| split.iter().map(|(_, hull)| hull.volume()).sum::<f64>();
+                volume(first).total_cmp(&volume(second))
+            });
+
+        // A piece that can not be cut into two solid pieces is kept whole.
+        let Some([below, above]) = best_split else {
+            concave_pieces[index] = None;
+            continue;
+        };
+
+        concave_pieces[index] = concavity(&below.0, &below.1);
+        concave_pieces.push(concavity(&above.0, &above.1));
+        pieces[index] = below;
+        pieces.push(above);
+    }
+
+    pieces.into_iter().map(|(_, hull)| hull).collect()
+}
+
+/// Cuts the triangles of the piece at the value along the axis into the triangles below and above it.
+fn split_piece(piece: &MeshPiece, axis: usize, value: f64) -> (MeshPiece, MeshPiece) {
+    let mut below = Vec::new();
+    let mut above = Vec::new();
+
+    for triangle in piece {
+        let sides = triangle.map(|corner| corner[axis] - value);
+        if sides.iter().all(|&side| side <= 0.0) {
+            below.push(*triangle);
+            continue;
+        }
+        if sides.iter().all(|&side| side >= 0.0) {
+            above.push(*triangle);
+            continue;
+        }
+
+        let mut below_polygon = Vec::new();
+        let mut above_polygon = Vec::new();
+        for corner in 0..3 {
+            let next = (corner + 1) % 3;
+            if sides[corner] <= 0.0 {
+                below_polygon.push(triangle[corner]);
+            }
+            if sides[corner] >= 0.0 {
+                above_polygon.push(triangle[corner]);
+            }
+            if sides[corner] * sides[next] < 0.0 {
+                let crossing = triangle[corner] + (triangle[next] - triangle[corner]) * (sides[corner] / (sides[corner] - sides[next]));
+                below_polygon.push(crossing);
+                above_polygon.push(crossing);
+            }
+        }
+
+        for (polygon, triangles) in [(below_polygon, &mut below), (above_polygon, &mut above)] {
+            for index in 1..polygon.len() - 1 {
+                triangles.push([polygon[0], polygon[index], polygon[index + 1]]);
+            }
+        }
+    }
+
+    (below, above)
+}
+
+/// Rebuilds the hull from its points that stick out the most, starting from the points furthest along each axis.
+fn simplify_convex_hull(hull: ProcessedConvexHull, maximum_vertices: usize) -> ProcessedConvexHull {
+    if hull.points.len() <= maximum_vertices {
+        return hull;
+    }
+
+    let mut selected = Vec::new();
+    for axis in 0..3 {
+        for extreme in [
+            hull.points.iter().min_by(|first, second| first[axis].total_cmp(&second[axis])),
+            hull.points.iter().max_by(|first, second| first[axis].total_cmp(&second[axis])),
+        ]
+        .into_iter()
+        .flatten()
+        {
+            if selected.len() < maximum_vertices && !selected.iter().any(|point: &Vector3| (*point - *extreme).magnitude() == 0.0) {
+                selected.push(*extreme);
+            }
+        }
+    }
+
+    let mut simplified = create_convex_hull(&selected);
+    while selected.len() < maximum_vertices {
+        let furthest = hull
+            .points
+            .iter()
+            .map(|point| {
+                (
+                    point,
+                    simplified.as_ref().map_or(f64::INFINITY, |simplified| simplified.distance_outside(*point)),
+                )
+            })
+            .max_by(|(_, first), (_, second)| first.total_cmp(second));
+
+        match furthest {
+            Some((point, distance)) if distance > 0.0 => selected.push(*point),
+            _ => break,
+        }
+        simplified = create_convex_hull(&selected);
+    }
+
+    simplified.unwrap_or(hull)
+}
+
+/// Checks that the polygons of a collision mesh form a closed surface that lies on its hulls, and logs every polygon that fails.
+/// A failing mesh still compiles, as the hulls only cover the mesh, but the solid will not match the source in the engine.
+fn validate_collision_mesh(
+    bone_name: &str,
+    parts: &[ImportPart],
+    is_collision_vertex: impl Fn(&ImportVertex) -> bool,
+    to_bone_space: impl Fn(Vector3) -> Vector3,
+    hulls: &[ProcessedConvexHull],
+) {
+    // Polygons are named by their part and their index in it, corners at the same position are welded.
+    let mut welded_positions = HashMap::new();
+    let mut positions = Vec::new();
+    let mut polygons = Vec::new();
+    for part in parts {
+        for (polygon_index, polygon) in part.polygons.values().flatten().enumerate() {
+            if !polygon.iter().all(|&corner| is_collision_vertex(&part.vertices[corner])) {
+                continue;
+            }
+
+            let corners = polygon
+                .iter()
+                .map(|&corner| {
+                    let position = part.vertices[corner].position;
+                    let key = [position.x.to_bits(), position.y.to_bits(), position.z.to_bits()];
+                    *welded_positions.entry(key).or_insert_with(|| {
+                        positions.push(to_bone_space(position));
+                        positions.len() - 1
+                    })
+                })
+                .collect::<Vec<_>>();
+            polygons.push((format!("{}#{}", part.name, polygon_index), corners));
+        }
+    }
+
+    let polygon_edges = |corners: &[usize]| {
+        (0..corners.len())
+            .map(|corner| (corners[corner], corners[(corner + 1) % corners.len()]))
+            .filter(|(start, end)| start != end)
+            .collect::<Vec<_>>()
+    };
+
+    let mut edge_counts = HashMap::new();
+    for (_, corners) in &polygons {
+        for edge in polygon_edges(corners) {
+            *edge_counts.entry(edge).or_insert(0) += 1;
+        }
+    }
+
+    let mut degenerate_polygons = Vec::new();
+    let mut open_polygons = Vec::new();
+    let mut non_manifold_polygons = Vec::new();
+    let mut off_surface_polygons = Vec::new();
+    let mut signed_volume = 0.0;
+    for (name, corners) in &polygons {
+        let corner_positions = corners.iter().map(|&corner| positions[corner]).collect::<Vec<_>>();
+        let area_normal = (1..corner_positions.len().saturating_sub(1)).fold(Vector3::default(), |sum, corner| {
+            sum + (corner_positions[corner] - corner_positions[0]).cross(corner_positions[corner + 1] - corner_positions[0])
+        });
+        signed_volume += (1..corner_positions.len().saturating_sub(1))
+            .map(|corner| corner_positions[0].dot(corner_positions[corner].cross(corner_positions[corner + 1])) / 6.0)
+            .sum::<f64>();
+
+        if area_normal.magnitude() * 0.5 < COLLISION_AREA_TOLERANCE {
+            degenerate_polygons.push(name.as_str());
+            continue;
+        }
+
+        let edges = polygon_edges(corners);
+        if edges.iter().any(|&(start, end)| !edge_counts.contains_key(&(end, start))) {
+            open_polygons.push(name.as_str());
+        }
+
+        // An edge used twice in the same direction is shared by more than two polygons or by polygons that wind opposite ways.
+        if edges
+            .iter()
+            .any(|&(start, end)| edge_counts[&(start, end)] > 1 || edge_counts.get(&(end, start)).is_some_and(|&count| count > 1))
+        {
+            non_manifold_polygons.push(name.as_str());
+        }
+
+        // The center of a polygon inside its hull is only on the surface when the whole polygon is on a face of the hull.
+        let center = corner_positions.iter().fold(Vector3::default(), |sum, position| sum + *position) / corner_positions.len() as f64;
+        let surface_distance = hulls.iter().map(|hull| hull.distance_outside(center).abs()).fold(f64::INFINITY, f64::min);
+        if surface_distance > COLLISION_SURFACE_TOLERANCE {
+            off_surface_polygons.push(name.as_str());
+        }
+    }
+
+    let report = |problem: &str, failed: &[&str]| {
+        if !failed.is_empty() {
+            log(
+                format!("Physics Solid On Bone {} Has {} {}: {}", bone_name, failed.len(), problem, failed.join(", ")),
+                LogLevel::Warn,
+            );
+        }
+    };
+
+    report("Degenerate Polygons", &degenerate_polygons);
+    report("Polygons With Open Edges", &open_polygons);
+    report("Non Manifold Polygons", &non_manifold_polygons);
+    report("Concave Polygons Not On Its Hulls", &off_surface_polygons);
+
+    // The volume is only meaningful when the mesh is closed.
+    if open_polygons.is_empty() && non_manifold_polygons.is_empty() && !polygons.is_empty() && signed_volume <= 0.0 {
+        log(
+            format!("Physics Solid On Bone {} Has A Mesh With No Volume Or Polygons Facing Inwards!", bone_name),
+            LogLevel::Warn,
+        );
+    }
+}
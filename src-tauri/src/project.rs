@@ -0,0 +1,223 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    io::Error as IoError,
+    path::{Component, Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Error as JsonError;
+use thiserror::Error as ThisError;
+
+use crate::{
+    input::{ImputedCompilationData, ImputedPhysicsShape},
+    utilities::logging::{log, LogLevel},
+};
+
+/// The version of the project file format, increased when a saved project can no longer be read as it is.
+/// Projects saved before the format had a version are version 0, they only miss fields that have defaults.
+const PROJECT_FORMAT_VERSION: u32 = 1;
+
+#[derive(Debug, ThisError)]
+pub enum ProjectError {
+    #[error("Failed To Access Project File: {0}")]
+    FailedFileAccess(#[from] IoError),
+    #[error("Failed To Parse Project: {0}")]
+    InvalidProject(#[from] JsonError),
+    #[error("Project Path Has No Directory")]
+    NoProjectDirectory,
+    #[error("Project Format Version {0} Is Newer Than The Supported Version {PROJECT_FORMAT_VERSION}")]
+    NewerProjectFormat(u32),
+}
+
+/// The project as it is written to the file, with the format version next to the fields of the project.
+#[derive(Deserialize, Serialize)]
+struct ProjectFile {
+    #[serde(default)]
+    format_version: u32,
+    #[serde(flatten)]
+    project: ImputedCompilationData,
+}
+
+/// A project read from a file with its source files resolved, and the source files that are not where the project says they are.
+#[derive(Debug, Serialize)]
+pub struct OpenedProject {
+    pub project: ImputedCompilationData,
+    pub missing_files: Vec<String>,
+}
+
+/// Writes the project with its source files relative to the project file, so the project still works when its folder is moved or shared.
+pub fn save_project(project: &ImputedCompilationData, project_path: &Path) -> Result<(), ProjectError> {
+    let project_directory = project_path.parent().ok_or(ProjectError::NoProjectDirectory)?;
+    let mut portable_project = project.clone();
+
+    for source_file in source_file_paths(&mut portable_project) {
+        *source_file = make_relative_path(source_file, project_directory);
+    }
+
+    let project_file = ProjectFile {
+        format_version: PROJECT_FORMAT_VERSION,
+        project: portable_project,
+    };
+
+    fs::write(project_path, serde_json::to_string_pretty(&project_file)?)?;
+
+    Ok(())
+}
+
+/// Reads a project and resolves its relative source files against the folder of the project file.
+pub fn open_project(project_path: &Path) -> Result<OpenedProject, ProjectError> {
+    let project_directory = project_path.parent().ok_or(ProjectError::NoProjectDirectory)?;
+    let project_file: ProjectFile = serde_json::from_str(&fs::read_to_string(project_path)?)?;
+
+    if project_file.format_version > PROJECT_FORMAT_VERSION {
+        return Err(ProjectError::NewerProjectFormat(project_file.format_version));
+    }
+
+    let mut project = project_file.project;
+
+    let mut missing_files = Vec::new();
+    for source_file in source_file_paths(&mut project) {
+        *source_file = resolve_relative_path(source_file, project_directory);
+
+        if !Path::new(source_file.as_str()).is_file() && !missing_files.contains(source_file) {
+            missing_files.push(source_file.clone());
+        }
+    }
+
+    Ok(OpenedProject { project, missing_files })
+}
+
+/// Searches the folder and the folders in it for files with the same name as the missing files, the first file found is used.
+/// Folders in it that can not be read are skipped, and folders linked more than once are only searched once so link cycles end.
+pub fn find_relocated_files(search_directory: &Path, missing_files: &[String]) -> Result<HashMap<String, String>, ProjectError> {
+    let mut relocated_files = HashMap::new();
+    let mut searched_directories = HashSet::new();
+    let mut directories = vec![search_directory.to_path_buf()];
+
+    while let Some(directory) = directories.pop() {
+        if !searched_directories.insert(directory.canonicalize().unwrap_or_else(|_| directory.clone())) {
+            continue;
+        }
+
+        let entries = match fs::read_dir(&directory) {
+            Ok(entries) => entries,
+            Err(error) if directory == search_directory => return Err(error.into()),
+            Err(error) => {
+                log(
+                    format!("Failed To Search Folder \"{}\": {}! Skipping!", directory.display(), error),
+                    LogLevel::Warn,
+                );
+                continue;
+            }
+        };
+
+        for entry in entries.flatten() {
+            let path = entry.path();
+
+            if path.is_dir() {
+                directories.push(path);
+                continue;
+            }
+
+            for missing_file in missing_files {
+                if relocated_files.contains_key(missing_file) || Path::new(missing_file).file_name() != path.file_name() {
+                    continue;
+                }
+
+                relocated_files.insert(missing_file.clone(), path.to_string_lossy().to_string());
+            }
+        }
+    }
+
+    Ok(relocated_files)
+}
+
+/// Every source file the project loads once, used to load the files of a project that is not open in the window.
+pub fn list_source_files(project: &ImputedCompilationData) -> Vec<String> {
+    let mut project = project.clone();
+    let mut source_files: Vec<String> = Vec::new();
+
+    for source_file in source_file_paths(&mut project) {
+        if !source_files.contains(source_file) {
+            source_files.push(source_file.clone());
+        }
+    }
+
+    source_files
+}
+
+/// Every source file the project loads, blank models and unset files are left out.
+fn source_file_paths(project: &mut ImputedCompilationData) -> Vec<&mut String> {
+    let mut source_files = Vec::new();
+
+    for body_part in &mut project.body_parts {
+        for model in body_part.models.iter_mut().filter(|model| !model.is_blank) {
+            source_files.push(&mut model.file_source);
+            source_files.push(&mut model.flex_file_source);
+        }
+    }
+
+    for animation in &mut project.animations {
+        source_files.push(&mut animation.file_source);
+    }
+
+    if let Some(physics) = &mut project.physics {
+        for solid in &mut physics.solids {
+            if let ImputedPhysicsShape::Mesh { file_source, .. } = &mut solid.shape {
+                source_files.push(file_source);
+            }
+        }
+    }
+
+    source_files.retain(|source_file| !source_file.is_empty());
+    source_files
+}
+
+/// The path relative to the directory with forward slashes, so it is read the same on every platform.
+/// Paths that do not share a root with the directory, like files on another drive, are kept as they are.
+fn make_relative_path(path: &str, directory: &Path) -> String {
+    let path_components = Path::new(path).components().collect::<Vec<_>>();
+    let directory_components = directory.components().collect::<Vec<_>>();
+
+    if !Path::new(path).is_absolute() || path_components.first() != directory_components.first() {
+        return path.to_string();
+    }
+
+    let shared_components = path_components
+        .iter()
+        .zip(&directory_components)
+        .take_while(|(path_component, directory_component)| path_component == directory_component)
+        .count();
+
+    let parent_components = directory_components.len() - shared_components;
+    let remaining_components = path_components[shared_components..]
+        .iter()
+        .map(|component| component.as_os_str().to_string_lossy().to_string());
+
+    vec![String::from(".."); parent_components]
+        .into_iter()
+        .chain(remaining_components)
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Joins a relative path to the directory without leaving parent directory components in it, absolute paths are kept as they are.
+fn resolve_relative_path(path: &str, directory: &Path) -> String {
+    if Path::new(path).is_absolute() {
+        return path.to_string();
+    }
+
+    let mut resolved_path = PathBuf::new();
+    for component in directory.join(path).components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                resolved_path.pop();
+            }
+            component => resolved_path.push(component),
+        }
+    }
+
+    resolved_path.to_string_lossy().to_string()
+}
@@ -0,0 +1,476 @@
+use std::{fs, io::Error as IoError, path::Path};
+
+use half::f16;
+use thiserror::Error as ThisError;
+
+use crate::{
+    report::CompileReport,
+    utilities::mathematics::{Quaternion, Vector2, Vector3},
+    write::{calculate_checksum, layout::VERTEX_SIZE},
+};
+
+mod dump;
+mod mesh;
+mod model;
+mod vertex;
+
+use dump::{dump_mesh, dump_model, dump_vertex};
+use mesh::MeshFileSummary;
+use model::ModelFileSummary;
+use vertex::VertexFileSummary;
+
+#[derive(Debug, ThisError)]
+pub enum FileReadError {
+    #[error("Failed To Read File: {0}")]
+    FailedFileRead(#[from] IoError),
+    #[error("Read At {0} Is Out Of Bounds")]
+    OutOfBounds(i64),
+    #[error("File Has Wrong Identifier")]
+    WrongIdentifier,
+    #[error("File Version {0} Is Not Supported")]
+    UnsupportedVersion(i32),
+    #[error("Count At {0} Is Negative")]
+    NegativeCount(usize),
+    #[error("String At {0} Is Not Terminated")]
+    UnterminatedString(usize),
+    #[error("Report Has No {0} File")]
+    MissingFile(&'static str),
+}
+
+/// A reader over a whole file, every read takes an absolute position because the formats are made of offsets.
+pub struct FileReader {
+    data: Vec<u8>,
+}
+
+impl FileReader {
+    pub fn from_file(path: &Path) -> Result<Self, FileReadError> {
+        Ok(Self { data: fs::read(path)? })
+    }
+
+    pub fn from_bytes(data: Vec<u8>) -> Self {
+        Self { data }
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Computes the checksum of the file the way it was computed when written, with the checksum itself still zero.
+    pub fn checksum(&self) -> i32 {
+        let checksum_range = 8..12;
+        calculate_checksum(
+            self.data
+                .iter()
+                .enumerate()
+                .map(|(index, byte)| if checksum_range.contains(&index) { &0 } else { byte }),
+        )
+    }
+
+    fn read_bytes<const N: usize>(&self, position: usize) -> Result<[u8; N], FileReadError> {
+        match self.data.get(position..position + N) {
+            Some(bytes) => Ok(bytes.try_into().unwrap()),
+            None => Err(FileReadError::OutOfBounds(position as i64)),
+        }
+    }
+
+    pub fn read_unsigned_byte(&self, position: usize) -> Result<u8, FileReadError> {
+        Ok(u8::from_le_bytes(self.read_bytes(position)?))
+    }
+
+    pub fn read_short(&self, position: usize) -> Result<i16, FileReadError> {
+        Ok(i16::from_le_bytes(self.read_bytes(position)?))
+    }
+
+    pub fn read_unsigned_short(&self, position: usize) -> Result<u16, FileReadError> {
+        Ok(u16::from_le_bytes(self.read_bytes(position)?))
+    }
+
+    pub fn read_integer(&self, position: usize) -> Result<i32, FileReadError> {
+        Ok(i32::from_le_bytes(self.read_bytes(position)?))
+    }
+
+    pub fn read_float(&self, position: usize) -> Result<f32, FileReadError> {
+        Ok(f32::from_le_bytes(self.read_bytes(position)?))
+    }
+
+    pub fn read_unsigned_long(&self, position: usize) -> Result<u64, FileReadError> {
+        Ok(u64::from_le_bytes(self.read_bytes(position)?))
+    }
+
+    pub fn read_vector2(&self, position: usize) -> Result<Vector2, FileReadError> {
+        Ok(Vector2::new(self.read_float(position)? as f64, self.read_float(position + 4)? as f64))
+    }
+
+    pub fn read_vector3(&self, position: usize) -> Result<Vector3, FileReadError> {
+        Ok(Vector3::new(
+            self.read_float(position)? as f64,
+            self.read_float(position + 4)? as f64,
+            self.read_float(position + 8)? as f64,
+        ))
+    }
+
+    pub fn read_quaternion(&self, position: usize) -> Result<Quaternion, FileReadError> {
+        Ok(Quaternion::new(
+            self.read_float(position)? as f64,
+            self.read_float(position + 4)? as f64,
+            self.read_float(position + 8)? as f64,
+            self.read_float(position + 12)? as f64,
+        ))
+    }
+
+    pub fn read_quaternion64(&self, position: usize) -> Result<Quaternion, FileReadError> {
+        let value = self.read_unsigned_long(position)?;
+        let x = ((value & 0x1FFFFF) as f64 - 1048576.0) / 1048576.5;
+        let y = (((value >> 21) & 0x1FFFFF) as f64 - 1048576.0) / 1048576.5;
+        let z = (((value >> 42) & 0x1FFFFF) as f64 - 1048576.0) / 1048576.5;
+        let w = (1.0 - x * x - y * y - z * z).max(0.0).sqrt();
+        Ok(Quaternion::new(x, y, z, if value >> 63 == 1 { -w } else { w }))
+    }
+
+    pub fn read_quaternion48(&self, position: usize) -> Result<Quaternion, FileReadError> {
+        let x = (self.read_unsigned_short(position)? as f64 - 32768.0) / 32768.0;
+        let y = (self.read_unsigned_short(position + 2)? as f64 - 32768.0) / 32768.0;
+        let packed = self.read_unsigned_short(position + 4)?;
+        let z = ((packed & 0x7FFF) as f64 - 16384.0) / 16384.0;
+        let w = (1.0 - x * x - y * y - z * z).max(0.0).sqrt();
+        Ok(Quaternion::new(x, y, z, if packed >> 15 == 1 { -w } else { w }))
+    }
+
+    pub fn read_vector48(&self, position: usize) -> Result<Vector3, FileReadError> {
+        Ok(Vector3::new(
+            f16::from_le_bytes(self.read_bytes(position)?).to_f64(),
+            f16::from_le_bytes(self.read_bytes(position + 2)?).to_f64(),
+            f16::from_le_bytes(self.read_bytes(position + 4)?).to_f64(),
+        ))
+    }
+
+    /// The most elements of the size that fit between the offset and the end of the file, used to bound allocations by counts read from the file.
+    pub fn capacity_for(&self, offset: usize, element_size: usize) -> usize {
+        self.data.len().saturating_sub(offset) / element_size.max(1)
+    }
+
+    /// Reads an integer that is used as an array size.
+    pub fn read_count(&self, position: usize) -> Result<usize, FileReadError> {
+        let count = self.read_integer(position)?;
+
+        if count < 0 {
+            return Err(FileReadError::NegativeCount(position));
+        }
+
+        Ok(count as usize)
+    }
+
+    /// Reads an integer offset and resolves it against the base it was written relative to.
+    pub fn read_offset(&self, position: usize, base: usize) -> Result<usize, FileReadError> {
+        let offset = base as i64 + self.read_integer(position)? as i64;
+
+        if offset < 0 || offset as usize > self.data.len() {
+            return Err(FileReadError::OutOfBounds(offset));
+        }
+
+        Ok(offset as usize)
+    }
+
+    pub fn read_char_array(&self, position: usize, length: usize) -> Result<String, FileReadError> {
+        let bytes = match self.data.get(position..position + length) {
+            Some(bytes) => bytes,
+            None => return Err(FileReadError::OutOfBounds(position as i64)),
+        };
+
+        let end = bytes.iter().position(|byte| *byte == 0).unwrap_or(length);
+        Ok(String::from_utf8_lossy(&bytes[..end]).into_owned())
+    }
+
+    /// Reads a string from the string table using an offset stored at the position.
+    pub fn read_string(&self, position: usize, base: usize) -> Result<String, FileReadError> {
+        let start = self.read_offset(position, base)?;
+
+        match self.data[start..].iter().position(|byte| *byte == 0) {
+            Some(end) => Ok(String::from_utf8_lossy(&self.data[start..start + end]).into_owned()),
+            None => Err(FileReadError::UnterminatedString(start)),
+        }
+    }
+}
+
+/// Reads back the files listed in the report and compares them against the report, which is built from the processed data.
+/// Returns every mismatch that was found, an empty list means the files match.
+pub fn verify_files(report: &CompileReport) -> Result<Vec<String>, FileReadError> {
+    let export_path = Path::new(&report.export_path);
+    let find_file = |extension: &'static str| match report.files.iter().find(|file| file.name.ends_with(extension)) {
+        Some(file) => Ok(export_path.join(&file.name)),
+        None => Err(FileReadError::MissingFile(extension)),
+    };
+
+    let mdl_reader = FileReader::from_file(&find_file(".mdl")?)?;
+    let model = ModelFileSummary::read(&mdl_reader)?;
+    let vvd_reader = FileReader::from_file(&find_file(".vvd")?)?;
+    let vertex = VertexFileSummary::read(&vvd_reader)?;
+    let vtx_reader = FileReader::from_file(&find_file(".vtx")?)?;
+    let mesh = MeshFileSummary::read(&vtx_reader)?;
+
+    let mut mismatches = Vec::new();
+    let mut check = |matches: bool, message: String| {
+        if !matches {
+            mismatches.push(message);
+        }
+    };
+
+    check(
+        model.length == mdl_reader.len(),
+        format!("MDL length is {} but the file is {} bytes.", model.length, mdl_reader.len()),
+    );
+    check(
+        model.checksum == report.checksum && model.checksum == mdl_reader.checksum(),
+        format!(
+            "MDL checksum is {} but {} was reported and the file sums to {}.",
+            model.checksum,
+            report.checksum,
+            mdl_reader.checksum()
+        ),
+    );
+    check(
+        model.checksum == vertex.checksum && model.checksum == mesh.checksum,
+        format!("Checksums differ, MDL {} VVD {} VTX {}.", model.checksum, vertex.checksum, mesh.checksum),
+    );
+
+    check(
+        model.bones.len() == report.bone_count,
+        format!("MDL has {} bones but {} were processed.", model.bones.len(), report.bone_count),
+    );
+    for (bone_index, (bone_name, bone_parent)) in model.bones.iter().enumerate() {
+        check(
+            *bone_parent < bone_index as i32,
+            format!("Bone {} has parent {} which is not before it.", bone_name, bone_parent),
+        );
+    }
+
+    check(
+        model.materials == report.materials,
+        format!("MDL materials {:?} do not match processed materials {:?}.", model.materials, report.materials),
+    );
+
+    check(
+        model.animations.len() == report.animations.len(),
+        format!("MDL has {} animations but {} were processed.", model.animations.len(), report.animations.len()),
+    );
+    for ((name, frame_count), animation) in model.animations.iter().zip(&report.animations) {
+        check(
+            *name == animation.name && *frame_count == animation.frame_count,
+            format!(
+                "MDL animation {} with {} frames does not match {} with {} frames.",
+                name, frame_count, animation.name, animation.frame_count
+            ),
+        );
+    }
+
+    check(
+        model.body_parts.len() == report.body_parts.len() && mesh.body_parts.len() == report.body_parts.len(),
+        format!(
+            "MDL has {} body parts and VTX has {} but {} were processed.",
+            model.body_parts.len(),
+            mesh.body_parts.len(),
+            report.body_parts.len()
+        ),
+    );
+    for ((model_body_part, mesh_body_part), report_body_part) in model.body_parts.iter().zip(&mesh.body_parts).zip(&report.body_parts) {
+        check(
+            model_body_part.name == report_body_part.name,
+            format!("MDL body part {} does not match {}.", model_body_part.name, report_body_part.name),
+        );
+        check(
+            model_body_part.models.len() == report_body_part.models.len() && mesh_body_part.len() == report_body_part.models.len(),
+            format!("Body part {} has a different model count in the MDL or VTX.", report_body_part.name),
+        );
+
+        for ((model_model, mesh_model), report_model) in model_body_part.models.iter().zip(mesh_body_part).zip(&report_body_part.models) {
+            // Model names are stored in a fixed size array, so long names are cut off.
+            let expected_name = report_model.name.bytes().take(64).collect::<Vec<_>>();
+            check(
+                model_model.name.as_bytes() == expected_name,
+                format!("MDL model {} does not match {}.", model_model.name, report_model.name),
+            );
+            check(
+                model_model.mesh_vertex_count == model_model.vertex_count,
+                format!(
+                    "Model {} has {} vertices but its meshes have {}.",
+                    report_model.name, model_model.vertex_count, model_model.mesh_vertex_count
+                ),
+            );
+
+            if let Some(level_of_detail) = report_model.levels_of_detail.first() {
+                check(
+                    model_model.vertex_count == level_of_detail.vertex_count,
+                    format!(
+                        "MDL model {} has {} vertices but {} were processed.",
+                        report_model.name, model_model.vertex_count, level_of_detail.vertex_count
+                    ),
+                );
+                check(
+                    mesh_model.first() == Some(&level_of_detail.triangle_count),
+                    format!(
+                        "VTX model {} has {:?} triangles but {} were processed.",
+                        report_model.name,
+                        mesh_model.first(),
+                        level_of_detail.triangle_count
+                    ),
+                );
+            }
+        }
+    }
+
+    let model_vertex_count = model
+        .body_parts
+        .iter()
+        .flat_map(|body_part| &body_part.models)
+        .map(|model| model.vertex_count)
+        .sum::<usize>();
+    check(
+        vertex.vertex_count == model_vertex_count,
+        format!("VVD has {} vertices but the MDL models have {}.", vertex.vertex_count, model_vertex_count),
+    );
+    check(
+        vertex.vertex_data_size == vertex.vertex_count * VERTEX_SIZE,
+        format!(
+            "VVD vertex data is {} bytes but {} vertices need {}.",
+            vertex.vertex_data_size,
+            vertex.vertex_count,
+            vertex.vertex_count * VERTEX_SIZE
+        ),
+    );
+    check(
+        vertex.data_end <= vvd_reader.len(),
+        format!("VVD data ends at {} but the file is {} bytes.", vertex.data_end, vvd_reader.len()),
+    );
+
+    Ok(mismatches)
+}
+
+/// Writes a model file and the vertex and mesh files next to it as indented text, for comparing against the output of other compilers.
+/// Files next to the model that do not exist are left out of the dump.
+pub fn dump_model_files(path: &Path) -> Result<String, FileReadError> {
+    // Only the file names are written so dumps of the same model compiled to different folders are the same.
+    let file_name = |path: &Path| path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+    let mut dump = format!("{}\n", file_name(path));
+    dump.push_str(&dump_model(&FileReader::from_file(path)?)?);
+
+    let vertex_path = path.with_extension("vvd");
+    if vertex_path.is_file() {
+        dump.push_str(&format!("\n{}\n", file_name(&vertex_path)));
+        dump.push_str(&dump_vertex(&FileReader::from_file(&vertex_path)?)?);
+    }
+
+    if let Some(mesh_path) = ["dx90.vtx", "vtx"]
+        .iter()
+        .map(|extension| path.with_extension(extension))
+        .find(|mesh_path| mesh_path.is_file())
+    {
+        dump.push_str(&format!("\n{}\n", file_name(&mesh_path)));
+        dump.push_str(&dump_mesh(&FileReader::from_file(&mesh_path)?)?);
+    }
+
+    Ok(dump)
+}
+
+/// Compares a compiled model with the model file already on disk, for dry runs that do not overwrite it.
+/// Returns every structural difference that was found, a missing file is reported as a new model.
+pub fn compare_model_file(path: &Path, data: Vec<u8>) -> Result<Vec<String>, FileReadError> {
+    if !path.exists() {
+        return Ok(vec![format!("{} does not exist yet and would be created.", path.display())]);
+    }
+
+    let existing = ModelFileSummary::read(&FileReader::from_file(path)?)?;
+    let compiled = ModelFileSummary::read(&FileReader::from_bytes(data))?;
+
+    let mut differences = Vec::new();
+
+    let existing_bones = existing.bones.iter().map(|(name, _)| name).collect::<Vec<_>>();
+    let compiled_bones = compiled.bones.iter().map(|(name, _)| name).collect::<Vec<_>>();
+    for bone in compiled_bones.iter().filter(|bone| !existing_bones.contains(bone)) {
+        differences.push(format!("Bone {} is added.", bone));
+    }
+    for bone in existing_bones.iter().filter(|bone| !compiled_bones.contains(bone)) {
+        differences.push(format!("Bone {} is removed.", bone));
+    }
+    for (bone_name, parent) in &compiled.bones {
+        let parent_name = |bones: &[(String, i32)], parent: i32| bones.get(parent as usize).map(|(name, _)| name.clone());
+        if let Some((_, existing_parent)) = existing.bones.iter().find(|(name, _)| name == bone_name) {
+            let (existing_parent_name, compiled_parent_name) = (parent_name(&existing.bones, *existing_parent), parent_name(&compiled.bones, *parent));
+            if existing_parent_name != compiled_parent_name {
+                differences.push(format!(
+                    "Bone {} parent changes from {:?} to {:?}.",
+                    bone_name, existing_parent_name, compiled_parent_name
+                ));
+            }
+        }
+    }
+
+    if existing.sequence_count != compiled.sequence_count {
+        differences.push(format!(
+            "Sequence count changes from {} to {}.",
+            existing.sequence_count, compiled.sequence_count
+        ));
+    }
+
+    for (name, frame_count) in &compiled.animations {
+        match existing.animations.iter().find(|(existing_name, _)| existing_name == name) {
+            Some((_, existing_frame_count)) if existing_frame_count != frame_count => differences.push(format!(
+                "Animation {} frame count changes from {} to {}.",
+                name, existing_frame_count, frame_count
+            )),
+            Some(_) => {}
+            None => differences.push(format!("Animation {} is added.", name)),
+        }
+    }
+    for (name, _) in existing
+        .animations
+        .iter()
+        .filter(|(name, _)| !compiled.animations.iter().any(|(compiled_name, _)| compiled_name == name))
+    {
+        differences.push(format!("Animation {} is removed.", name));
+    }
+
+    if existing.materials != compiled.materials {
+        differences.push(format!("Materials change from {:?} to {:?}.", existing.materials, compiled.materials));
+    }
+
+    for body_part in &compiled.body_parts {
+        let existing_body_part = match existing.body_parts.iter().find(|existing_body_part| existing_body_part.name == body_part.name) {
+            Some(existing_body_part) => existing_body_part,
+            None => {
+                differences.push(format!("Body part {} is added.", body_part.name));
+                continue;
+            }
+        };
+
+        for model in &body_part.models {
+            match existing_body_part.models.iter().find(|existing_model| existing_model.name == model.name) {
+                Some(existing_model) if existing_model.vertex_count != model.vertex_count => differences.push(format!(
+                    "Model {} vertex count changes from {} to {}.",
+                    model.name, existing_model.vertex_count, model.vertex_count
+                )),
+                Some(_) => {}
+                None => differences.push(format!("Model {} is added to body part {}.", model.name, body_part.name)),
+            }
+        }
+        for model in existing_body_part
+            .models
+            .iter()
+            .filter(|existing_model| !body_part.models.iter().any(|model| model.name == existing_model.name))
+        {
+            differences.push(format!("Model {} is removed from body part {}.", model.name, body_part.name));
+        }
+    }
+    for body_part in existing
+        .body_parts
+        .iter()
+        .filter(|existing_body_part| !compiled.body_parts.iter().any(|body_part| body_part.name == existing_body_part.name))
+    {
+        differences.push(format!("Body part {} is removed.", body_part.name));
+    }
+
+    Ok(differences)
+}
@@ -0,0 +1,348 @@
+use std::fmt::{Display, Write};
+
+use crate::{
+    utilities::mathematics::Vector3,
+    write::layout::{
+        ANIMATION_DESCRIPTION_SIZE, ATTACHMENT_SIZE, BODY_PART_SIZE, BONE_SIZE, FLEX_SIZE, HITBOX_SET_SIZE, HITBOX_SIZE, MATERIAL_SIZE,
+        MESH_BODY_PART_HEADER_SIZE, MESH_MODEL_HEADER_SIZE, MESH_MODEL_LOD_HEADER_SIZE, MESH_SIZE, MODEL_FILE_IDENTIFIER, MODEL_SIZE,
+        SEQUENCE_DESCRIPTION_SIZE, VERTEX_FILE_IDENTIFIER,
+    },
+};
+
+use super::{FileReadError, FileReader};
+
+/// Text written one field per line, with the fields of every structure indented under it so dumps can be compared line by line.
+struct FileDump {
+    text: String,
+    depth: usize,
+}
+
+impl FileDump {
+    fn new() -> Self {
+        Self { text: String::new(), depth: 0 }
+    }
+
+    fn field(&mut self, name: &str, value: impl Display) {
+        let _ = writeln!(self.text, "{}{}: {}", "    ".repeat(self.depth), name, value);
+    }
+
+    fn vector(&mut self, name: &str, value: Vector3) {
+        self.field(name, format_args!("{} {} {}", value.x, value.y, value.z));
+    }
+
+    fn begin(&mut self, name: impl Display) {
+        let _ = writeln!(self.text, "{}{}", "    ".repeat(self.depth), name);
+        self.depth += 1;
+    }
+
+    fn end(&mut self) {
+        self.depth -= 1;
+    }
+}
+
+/// Writes every header field of a model file and the structures it points to as indented text.
+/// The fields are named after their meaning instead of the engine names, the offsets are kept so the text can be matched to a hex view.
+pub fn dump_model(reader: &FileReader) -> Result<String, FileReadError> {
+    if reader.read_integer(0)? != MODEL_FILE_IDENTIFIER {
+        return Err(FileReadError::WrongIdentifier);
+    }
+
+    let mut dump = FileDump::new();
+
+    dump.begin("Header");
+    dump.field("Version", reader.read_integer(4)?);
+    dump.field("Checksum", reader.read_integer(8)?);
+    dump.field("Name", reader.read_char_array(12, 64)?);
+    dump.field("Length", reader.read_integer(76)?);
+    dump.vector("Eye Position", reader.read_vector3(80)?);
+    dump.vector("Illumination Position", reader.read_vector3(92)?);
+    dump.vector("Hull Minimum", reader.read_vector3(104)?);
+    dump.vector("Hull Maximum", reader.read_vector3(116)?);
+    dump.vector("View Minimum", reader.read_vector3(128)?);
+    dump.vector("View Maximum", reader.read_vector3(140)?);
+    dump.field("Flags", format_args!("{:#010X}", reader.read_integer(152)?));
+    for (name, count_position) in [
+        ("Bones", 156),
+        ("Bone Controllers", 164),
+        ("Hitbox Sets", 172),
+        ("Animations", 180),
+        ("Sequences", 188),
+        ("Materials", 204),
+        ("Material Paths", 212),
+        ("Skin References", 220),
+        ("Skin Families", 224),
+        ("Body Parts", 232),
+        ("Attachments", 240),
+        ("Nodes", 248),
+        ("Flex Descriptions", 260),
+        ("Flex Controllers", 268),
+        ("Flex Rules", 276),
+        ("Inverse Kinematic Chains", 284),
+        ("Mouths", 292),
+        ("Pose Parameters", 300),
+        ("Inverse Kinematic Locks", 320),
+        ("Include Models", 336),
+        ("Animation Blocks", 352),
+        ("Flex Controller Interfaces", 384),
+    ] {
+        dump.field(
+            &format!("{} Count", name),
+            format_args!("{} At {}", reader.read_integer(count_position)?, reader.read_integer(count_position + 4)?),
+        );
+    }
+    dump.field("Surface Property", reader.read_string(308, 0)?);
+    dump.field("Key Values Size", reader.read_integer(316)?);
+    dump.field("Mass", reader.read_float(328)?);
+    dump.field("Contents", format_args!("{:#010X}", reader.read_integer(332)?));
+    dump.field("Directional Light Dot", reader.read_unsigned_byte(376)?);
+    dump.field("Root Level Of Detail", reader.read_unsigned_byte(377)?);
+    dump.field("Allowed Root Levels Of Detail", reader.read_unsigned_byte(378)?);
+    dump.field("Second Header Offset", reader.read_integer(400)?);
+    dump.end();
+
+    let bone_offset = reader.read_offset(160, 0)?;
+    for bone_index in 0..reader.read_count(156)? {
+        let bone_base = bone_offset + bone_index * BONE_SIZE;
+        dump.begin(format_args!("Bone {} At {}", bone_index, bone_base));
+        dump.field("Name", reader.read_string(bone_base, bone_base)?);
+        dump.field("Parent", reader.read_integer(bone_base + 4)?);
+        dump.vector("Position", reader.read_vector3(bone_base + 32)?);
+        dump.vector("Rotation", reader.read_vector3(bone_base + 60)?);
+        dump.vector("Position Scale", reader.read_vector3(bone_base + 72)?);
+        dump.vector("Rotation Scale", reader.read_vector3(bone_base + 84)?);
+        dump.field("Flags", format_args!("{:#010X}", reader.read_integer(bone_base + 160)?));
+        dump.field("Procedural Type", reader.read_integer(bone_base + 164)?);
+        dump.field("Physics Bone", reader.read_integer(bone_base + 172)?);
+        dump.field("Surface Property", reader.read_string(bone_base + 176, bone_base)?);
+        dump.field("Contents", format_args!("{:#010X}", reader.read_integer(bone_base + 180)?));
+        dump.end();
+    }
+
+    let hitbox_set_offset = reader.read_offset(176, 0)?;
+    for hitbox_set_index in 0..reader.read_count(172)? {
+        let hitbox_set_base = hitbox_set_offset + hitbox_set_index * HITBOX_SET_SIZE;
+        dump.begin(format_args!("Hitbox Set {} At {}", hitbox_set_index, hitbox_set_base));
+        dump.field("Name", reader.read_string(hitbox_set_base, hitbox_set_base)?);
+
+        let hitbox_offset = reader.read_offset(hitbox_set_base + 8, hitbox_set_base)?;
+        for hitbox_index in 0..reader.read_count(hitbox_set_base + 4)? {
+            let hitbox_base = hitbox_offset + hitbox_index * HITBOX_SIZE;
+            dump.begin(format_args!("Hitbox {} At {}", hitbox_index, hitbox_base));
+            dump.field("Bone", reader.read_integer(hitbox_base)?);
+            dump.field("Group", reader.read_integer(hitbox_base + 4)?);
+            dump.vector("Minimum", reader.read_vector3(hitbox_base + 8)?);
+            dump.vector("Maximum", reader.read_vector3(hitbox_base + 20)?);
+            dump.end();
+        }
+
+        dump.end();
+    }
+
+    let animation_description_offset = reader.read_offset(184, 0)?;
+    for animation_description_index in 0..reader.read_count(180)? {
+        let animation_description_base = animation_description_offset + animation_description_index * ANIMATION_DESCRIPTION_SIZE;
+        dump.begin(format_args!("Animation {} At {}", animation_description_index, animation_description_base));
+        dump.field("Name", reader.read_string(animation_description_base + 4, animation_description_base)?);
+        dump.field("Frame Rate", reader.read_float(animation_description_base + 8)?);
+        dump.field("Flags", format_args!("{:#010X}", reader.read_integer(animation_description_base + 12)?));
+        dump.field("Frame Count", reader.read_integer(animation_description_base + 16)?);
+        dump.field("Movement Count", reader.read_integer(animation_description_base + 20)?);
+        dump.field("Animation Offset", reader.read_integer(animation_description_base + 56)?);
+        dump.field("Section Frames", reader.read_integer(animation_description_base + 84)?);
+        dump.end();
+    }
+
+    let sequence_description_offset = reader.read_offset(192, 0)?;
+    for sequence_description_index in 0..reader.read_count(188)? {
+        let sequence_description_base = sequence_description_offset + sequence_description_index * SEQUENCE_DESCRIPTION_SIZE;
+        dump.begin(format_args!("Sequence {} At {}", sequence_description_index, sequence_description_base));
+        dump.field("Name", reader.read_string(sequence_description_base + 4, sequence_description_base)?);
+        dump.field("Activity Name", reader.read_string(sequence_description_base + 8, sequence_description_base)?);
+        dump.field("Flags", format_args!("{:#010X}", reader.read_integer(sequence_description_base + 12)?));
+        dump.field("Activity", reader.read_integer(sequence_description_base + 16)?);
+        dump.field("Activity Weight", reader.read_integer(sequence_description_base + 20)?);
+        dump.field("Event Count", reader.read_integer(sequence_description_base + 24)?);
+        dump.vector("Minimum", reader.read_vector3(sequence_description_base + 32)?);
+        dump.vector("Maximum", reader.read_vector3(sequence_description_base + 44)?);
+        dump.field("Blend Count", reader.read_integer(sequence_description_base + 56)?);
+        dump.field(
+            "Blend Size",
+            format_args!(
+                "{} {}",
+                reader.read_integer(sequence_description_base + 68)?,
+                reader.read_integer(sequence_description_base + 72)?
+            ),
+        );
+        dump.field("Fade In Time", reader.read_float(sequence_description_base + 104)?);
+        dump.field("Fade Out Time", reader.read_float(sequence_description_base + 108)?);
+        dump.field("Entry Node", reader.read_integer(sequence_description_base + 112)?);
+        dump.field("Exit Node", reader.read_integer(sequence_description_base + 116)?);
+
+        let activity_modifier_offset = reader.read_offset(sequence_description_base + 184, sequence_description_base)?;
+        for activity_modifier_index in 0..reader.read_count(sequence_description_base + 188)? {
+            let activity_modifier_base = activity_modifier_offset + activity_modifier_index * 4;
+            dump.field("Activity Modifier", reader.read_string(activity_modifier_base, activity_modifier_base)?);
+        }
+        dump.end();
+    }
+
+    let material_offset = reader.read_offset(208, 0)?;
+    for material_index in 0..reader.read_count(204)? {
+        let material_base = material_offset + material_index * MATERIAL_SIZE;
+        dump.begin(format_args!("Material {} At {}", material_index, material_base));
+        dump.field("Name", reader.read_string(material_base, material_base)?);
+        dump.field("Flags", format_args!("{:#010X}", reader.read_integer(material_base + 4)?));
+        dump.end();
+    }
+
+    let material_path_offset = reader.read_offset(216, 0)?;
+    for material_path_index in 0..reader.read_count(212)? {
+        dump.field(
+            &format!("Material Path {}", material_path_index),
+            reader.read_string(material_path_offset + material_path_index * 4, 0)?,
+        );
+    }
+
+    // Every skin family is a row of material indices, one for every skin reference.
+    let skin_reference_count = reader.read_count(220)?;
+    let skin_offset = reader.read_offset(228, 0)?;
+    for skin_family_index in 0..reader.read_count(224)? {
+        let skin_base = skin_offset + skin_family_index * skin_reference_count * 2;
+        let materials = (0..skin_reference_count)
+            .map(|skin_reference_index| reader.read_short(skin_base + skin_reference_index * 2).map(|material| material.to_string()))
+            .collect::<Result<Vec<_>, _>>()?;
+        dump.field(&format!("Skin Family {}", skin_family_index), materials.join(" "));
+    }
+
+    let body_part_offset = reader.read_offset(236, 0)?;
+    for body_part_index in 0..reader.read_count(232)? {
+        let body_part_base = body_part_offset + body_part_index * BODY_PART_SIZE;
+        dump.begin(format_args!("Body Part {} At {}", body_part_index, body_part_base));
+        dump.field("Name", reader.read_string(body_part_base, body_part_base)?);
+        dump.field("Base", reader.read_integer(body_part_base + 8)?);
+
+        let model_offset = reader.read_offset(body_part_base + 12, body_part_base)?;
+        for model_index in 0..reader.read_count(body_part_base + 4)? {
+            let model_base = model_offset + model_index * MODEL_SIZE;
+            dump.begin(format_args!("Model {} At {}", model_index, model_base));
+            dump.field("Name", reader.read_char_array(model_base, 64)?);
+            dump.field("Bounding Radius", reader.read_float(model_base + 68)?);
+            dump.field("Vertex Count", reader.read_integer(model_base + 80)?);
+            dump.field("Vertex Offset", reader.read_integer(model_base + 84)?);
+            dump.field("Tangent Offset", reader.read_integer(model_base + 88)?);
+
+            let mesh_offset = reader.read_offset(model_base + 76, model_base)?;
+            for mesh_index in 0..reader.read_count(model_base + 72)? {
+                let mesh_base = mesh_offset + mesh_index * MESH_SIZE;
+                dump.begin(format_args!("Mesh {} At {}", mesh_index, mesh_base));
+                dump.field("Material", reader.read_integer(mesh_base)?);
+                dump.field("Vertex Count", reader.read_integer(mesh_base + 8)?);
+                dump.field("Vertex Offset", reader.read_integer(mesh_base + 12)?);
+                dump.field("Flex Count", reader.read_integer(mesh_base + 16)?);
+                dump.vector("Center", reader.read_vector3(mesh_base + 36)?);
+
+                let flex_offset = reader.read_offset(mesh_base + 20, mesh_base)?;
+                for flex_index in 0..reader.read_count(mesh_base + 16)? {
+                    let flex_base = flex_offset + flex_index * FLEX_SIZE;
+                    dump.begin(format_args!("Flex {} At {}", flex_index, flex_base));
+                    dump.field("Flex Description", reader.read_integer(flex_base)?);
+                    dump.field("Vertex Animation Count", reader.read_integer(flex_base + 20)?);
+                    dump.end();
+                }
+
+                dump.end();
+            }
+
+            dump.end();
+        }
+
+        dump.end();
+    }
+
+    let attachment_offset = reader.read_offset(244, 0)?;
+    for attachment_index in 0..reader.read_count(240)? {
+        let attachment_base = attachment_offset + attachment_index * ATTACHMENT_SIZE;
+        dump.begin(format_args!("Attachment {} At {}", attachment_index, attachment_base));
+        dump.field("Name", reader.read_string(attachment_base, attachment_base)?);
+        dump.field("Flags", format_args!("{:#010X}", reader.read_integer(attachment_base + 4)?));
+        dump.field("Bone", reader.read_integer(attachment_base + 8)?);
+        dump.end();
+    }
+
+    Ok(dump.text)
+}
+
+/// Writes the header of a vertex file as indented text.
+pub fn dump_vertex(reader: &FileReader) -> Result<String, FileReadError> {
+    if reader.read_integer(0)? != VERTEX_FILE_IDENTIFIER {
+        return Err(FileReadError::WrongIdentifier);
+    }
+
+    let mut dump = FileDump::new();
+
+    dump.begin("Header");
+    dump.field("Version", reader.read_integer(4)?);
+    dump.field("Checksum", reader.read_integer(8)?);
+    dump.field("Level Of Detail Count", reader.read_integer(12)?);
+    for level_of_detail in 0..8 {
+        dump.field(
+            &format!("Level Of Detail {} Vertex Count", level_of_detail),
+            reader.read_integer(16 + level_of_detail * 4)?,
+        );
+    }
+    dump.field("Fixup Count", reader.read_integer(48)?);
+    dump.field("Fixup Offset", reader.read_integer(52)?);
+    dump.field("Vertex Offset", reader.read_integer(56)?);
+    dump.field("Tangent Offset", reader.read_integer(60)?);
+    dump.end();
+
+    Ok(dump.text)
+}
+
+/// Writes the header of a mesh file and the levels of detail of every model as indented text.
+pub fn dump_mesh(reader: &FileReader) -> Result<String, FileReadError> {
+    let version = reader.read_integer(0)?;
+    if version != 7 {
+        return Err(FileReadError::UnsupportedVersion(version));
+    }
+
+    let mut dump = FileDump::new();
+
+    dump.begin("Header");
+    dump.field("Version", version);
+    dump.field("Vertex Cache Size", reader.read_integer(4)?);
+    dump.field("Maximum Bones Per Strip", reader.read_unsigned_short(8)?);
+    dump.field("Maximum Bones Per Triangle", reader.read_unsigned_short(10)?);
+    dump.field("Maximum Bones Per Vertex", reader.read_integer(12)?);
+    dump.field("Checksum", reader.read_integer(16)?);
+    dump.field("Level Of Detail Count", reader.read_integer(20)?);
+    dump.field("Material Replacement Offset", reader.read_integer(24)?);
+    dump.end();
+
+    let body_part_offset = reader.read_offset(32, 0)?;
+    for body_part_index in 0..reader.read_count(28)? {
+        let body_part_base = body_part_offset + body_part_index * MESH_BODY_PART_HEADER_SIZE;
+        dump.begin(format_args!("Body Part {} At {}", body_part_index, body_part_base));
+
+        let model_offset = reader.read_offset(body_part_base + 4, body_part_base)?;
+        for model_index in 0..reader.read_count(body_part_base)? {
+            let model_base = model_offset + model_index * MESH_MODEL_HEADER_SIZE;
+            dump.begin(format_args!("Model {} At {}", model_index, model_base));
+
+            let model_lod_offset = reader.read_offset(model_base + 4, model_base)?;
+            for model_lod_index in 0..reader.read_count(model_base)? {
+                let model_lod_base = model_lod_offset + model_lod_index * MESH_MODEL_LOD_HEADER_SIZE;
+                dump.begin(format_args!("Level Of Detail {} At {}", model_lod_index, model_lod_base));
+                dump.field("Mesh Count", reader.read_integer(model_lod_base)?);
+                dump.field("Switch Point", reader.read_float(model_lod_base + 8)?);
+                dump.end();
+            }
+
+            dump.end();
+        }
+
+        dump.end();
+    }
+
+    Ok(dump.text)
+}
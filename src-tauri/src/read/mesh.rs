@@ -0,0 +1,63 @@
+use crate::write::layout::{
+    MESH_BODY_PART_HEADER_SIZE, MESH_MESH_HEADER_SIZE, MESH_MODEL_HEADER_SIZE, MESH_MODEL_LOD_HEADER_SIZE, MESH_STRIP_GROUP_HEADER_SIZE,
+};
+
+use super::{FileReadError, FileReader};
+
+/// The parts of a mesh file that are compared against the compile report.
+#[derive(Debug, Default)]
+pub struct MeshFileSummary {
+    pub checksum: i32,
+    /// The triangle count of every level of detail for every model in every body part.
+    pub body_parts: Vec<Vec<Vec<usize>>>,
+}
+
+impl MeshFileSummary {
+    pub fn read(reader: &FileReader) -> Result<Self, FileReadError> {
+        let version = reader.read_integer(0)?;
+        if version != 7 {
+            return Err(FileReadError::UnsupportedVersion(version));
+        }
+
+        let mut summary = Self {
+            checksum: reader.read_integer(16)?,
+            body_parts: Vec::new(),
+        };
+
+        let body_part_offset = reader.read_offset(32, 0)?;
+        for body_part_index in 0..reader.read_count(28)? {
+            let body_part_base = body_part_offset + body_part_index * MESH_BODY_PART_HEADER_SIZE;
+            let mut body_part = Vec::new();
+
+            let model_offset = reader.read_offset(body_part_base + 4, body_part_base)?;
+            for model_index in 0..reader.read_count(body_part_base)? {
+                let model_base = model_offset + model_index * MESH_MODEL_HEADER_SIZE;
+                let mut model = Vec::new();
+
+                let model_lod_offset = reader.read_offset(model_base + 4, model_base)?;
+                for model_lod_index in 0..reader.read_count(model_base)? {
+                    let model_lod_base = model_lod_offset + model_lod_index * MESH_MODEL_LOD_HEADER_SIZE;
+                    let mut triangle_count = 0;
+
+                    let mesh_offset = reader.read_offset(model_lod_base + 4, model_lod_base)?;
+                    for mesh_index in 0..reader.read_count(model_lod_base)? {
+                        let mesh_base = mesh_offset + mesh_index * MESH_MESH_HEADER_SIZE;
+
+                        let strip_group_offset = reader.read_offset(mesh_base + 4, mesh_base)?;
+                        for strip_group_index in 0..reader.read_count(mesh_base)? {
+                            triangle_count += reader.read_count(strip_group_offset + strip_group_index * MESH_STRIP_GROUP_HEADER_SIZE + 8)? / 3;
+                        }
+                    }
+
+                    model.push(triangle_count);
+                }
+
+                body_part.push(model);
+            }
+
+            summary.body_parts.push(body_part);
+        }
+
+        Ok(summary)
+    }
+}
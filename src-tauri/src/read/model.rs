@@ -0,0 +1,97 @@
+use crate::write::layout::{ANIMATION_DESCRIPTION_SIZE, BODY_PART_SIZE, BONE_SIZE, MATERIAL_SIZE, MESH_SIZE, MODEL_FILE_IDENTIFIER, MODEL_SIZE};
+
+use super::{FileReadError, FileReader};
+
+/// The parts of a model file that are compared against the compile report.
+#[derive(Debug, Default)]
+pub struct ModelFileSummary {
+    pub checksum: i32,
+    pub length: usize,
+    pub bones: Vec<(String, i32)>,
+    pub animations: Vec<(String, usize)>,
+    pub sequence_count: usize,
+    pub materials: Vec<String>,
+    pub body_parts: Vec<ModelFileBodyPartSummary>,
+}
+
+#[derive(Debug, Default)]
+pub struct ModelFileBodyPartSummary {
+    pub name: String,
+    pub models: Vec<ModelFileModelSummary>,
+}
+
+#[derive(Debug, Default)]
+pub struct ModelFileModelSummary {
+    pub name: String,
+    pub vertex_count: usize,
+    pub mesh_vertex_count: usize,
+}
+
+impl ModelFileSummary {
+    pub fn read(reader: &FileReader) -> Result<Self, FileReadError> {
+        if reader.read_integer(0)? != MODEL_FILE_IDENTIFIER {
+            return Err(FileReadError::WrongIdentifier);
+        }
+
+        let mut summary = Self {
+            checksum: reader.read_integer(8)?,
+            length: reader.read_count(76)?,
+            ..Default::default()
+        };
+
+        let bone_offset = reader.read_offset(160, 0)?;
+        for bone_index in 0..reader.read_count(156)? {
+            let bone_base = bone_offset + bone_index * BONE_SIZE;
+            summary
+                .bones
+                .push((reader.read_string(bone_base, bone_base)?, reader.read_integer(bone_base + 4)?));
+        }
+
+        let animation_description_offset = reader.read_offset(184, 0)?;
+        for animation_description_index in 0..reader.read_count(180)? {
+            let animation_description_base = animation_description_offset + animation_description_index * ANIMATION_DESCRIPTION_SIZE;
+            summary.animations.push((
+                reader.read_string(animation_description_base + 4, animation_description_base)?,
+                reader.read_count(animation_description_base + 16)?,
+            ));
+        }
+
+        summary.sequence_count = reader.read_count(188)?;
+
+        let material_offset = reader.read_offset(208, 0)?;
+        for material_index in 0..reader.read_count(204)? {
+            let material_base = material_offset + material_index * MATERIAL_SIZE;
+            summary.materials.push(reader.read_string(material_base, material_base)?);
+        }
+
+        let body_part_offset = reader.read_offset(236, 0)?;
+        for body_part_index in 0..reader.read_count(232)? {
+            let body_part_base = body_part_offset + body_part_index * BODY_PART_SIZE;
+            let mut body_part = ModelFileBodyPartSummary {
+                name: reader.read_string(body_part_base, body_part_base)?,
+                models: Vec::new(),
+            };
+
+            let model_offset = reader.read_offset(body_part_base + 12, body_part_base)?;
+            for model_index in 0..reader.read_count(body_part_base + 4)? {
+                let model_base = model_offset + model_index * MODEL_SIZE;
+                let mut model = ModelFileModelSummary {
+                    name: reader.read_char_array(model_base, 64)?,
+                    vertex_count: reader.read_count(model_base + 80)?,
+                    mesh_vertex_count: 0,
+                };
+
+                let mesh_offset = reader.read_offset(model_base + 76, model_base)?;
+                for mesh_index in 0..reader.read_count(model_base + 72)? {
+                    model.mesh_vertex_count += reader.read_count(mesh_offset + mesh_index * MESH_SIZE + 8)?;
+                }
+
+                body_part.models.push(model);
+            }
+
+            summary.body_parts.push(body_part);
+        }
+
+        Ok(summary)
+    }
+}
@@ -0,0 +1,31 @@
+use crate::write::layout::{TANGENT_SIZE, VERTEX_FILE_IDENTIFIER};
+
+use super::{FileReadError, FileReader};
+
+/// The parts of a vertex file that are compared against the model file.
+#[derive(Debug, Default)]
+pub struct VertexFileSummary {
+    pub checksum: i32,
+    pub vertex_count: usize,
+    pub vertex_data_size: usize,
+    pub data_end: usize,
+}
+
+impl VertexFileSummary {
+    pub fn read(reader: &FileReader) -> Result<Self, FileReadError> {
+        if reader.read_integer(0)? != VERTEX_FILE_IDENTIFIER {
+            return Err(FileReadError::WrongIdentifier);
+        }
+
+        let vertex_count = reader.read_count(16)?;
+        let vertex_offset = reader.read_offset(56, 0)?;
+        let tangent_offset = reader.read_offset(60, 0)?;
+
+        Ok(Self {
+            checksum: reader.read_integer(8)?,
+            vertex_count,
+            vertex_data_size: tangent_offset.saturating_sub(vertex_offset),
+            data_end: tangent_offset + vertex_count * TANGENT_SIZE,
+        })
+    }
+}
@@ -0,0 +1,176 @@
+use std::{
+    fmt::{self, Display, Formatter},
+    fs,
+    io::Error as IoError,
+    path::Path,
+};
+
+use serde::{Deserialize, Serialize};
+
+/// A summary of a compiled model, similar to the statistics studiomdl prints after a compile.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct CompileReport {
+    pub model_name: String,
+    pub export_path: String,
+    pub checksum: i32,
+    pub bone_count: usize,
+    pub body_parts: Vec<ReportBodyPart>,
+    /// The body value the entity needs to show the default model of every body part.
+    pub default_body: usize,
+    pub materials: Vec<String>,
+    pub animations: Vec<ReportAnimation>,
+    pub files: Vec<ReportFile>,
+    pub mesh_splits: Vec<ReportMeshSplit>,
+    pub warnings: Vec<String>,
+    pub flag_issues: Vec<ReportFlagIssue>,
+    /// Dry runs do not write the files, the differences to the files already on disk are reported instead.
+    pub dry_run: bool,
+    pub differences: Vec<String>,
+    /// The reports of the world model and the gibs compiled along with the model.
+    #[serde(default)]
+    pub additional_models: Vec<CompileReport>,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct ReportBodyPart {
+    pub name: String,
+    pub models: Vec<ReportModel>,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct ReportModel {
+    pub name: String,
+    pub levels_of_detail: Vec<ReportLevelOfDetail>,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct ReportLevelOfDetail {
+    pub vertex_count: usize,
+    pub triangle_count: usize,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct ReportAnimation {
+    pub name: String,
+    pub frame_count: usize,
+    pub data_size: usize,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct ReportFile {
+    pub name: String,
+    pub size: usize,
+}
+
+/// A material that was split into more meshes than one, so it can be split at the source instead.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct ReportMeshSplit {
+    pub model: String,
+    pub material: String,
+    pub mesh_count: usize,
+    pub vertex_count: usize,
+    pub triangle_count: usize,
+    pub vertex_limit_splits: usize,
+    pub hardware_bone_limit_splits: usize,
+}
+
+/// A likely misconfiguration of the model flags found by comparing them against the contents of the model.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct ReportFlagIssue {
+    pub message: String,
+    pub fix: String,
+    /// The model flags the fix changes and the values it changes them to, empty when the fix is not a flag change.
+    pub flag_changes: Vec<(String, bool)>,
+}
+
+impl CompileReport {
+    pub fn write_to_file(&self, path: &Path) -> Result<(), IoError> {
+        fs::write(path, self.to_string())
+    }
+}
+
+impl Display for CompileReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Compile Report For {}", self.model_name)?;
+        writeln!(f, "Exported To {}", self.export_path)?;
+        writeln!(f, "Checksum: {:08X}", self.checksum)?;
+        writeln!(f)?;
+        writeln!(f, "Bones: {}", self.bone_count)?;
+        writeln!(f)?;
+
+        writeln!(f, "Body Parts: {}", self.body_parts.len())?;
+        writeln!(f, "Default Body: {}", self.default_body)?;
+        for body_part in &self.body_parts {
+            writeln!(f, "  {}: {} models", body_part.name, body_part.models.len())?;
+            for model in &body_part.models {
+                writeln!(f, "    {}", model.name)?;
+                for (level_of_detail_index, level_of_detail) in model.levels_of_detail.iter().enumerate() {
+                    writeln!(
+                        f,
+                        "      LOD {}: {} vertices, {} triangles",
+                        level_of_detail_index, level_of_detail.vertex_count, level_of_detail.triangle_count
+                    )?;
+                }
+            }
+        }
+        writeln!(f)?;
+
+        writeln!(f, "Materials: {}", self.materials.len())?;
+        for material in &self.materials {
+            writeln!(f, "  {}", material)?;
+        }
+        writeln!(f)?;
+
+        writeln!(f, "Animations: {}", self.animations.len())?;
+        for animation in &self.animations {
+            writeln!(f, "  {}: {} frames, {} bytes", animation.name, animation.frame_count, animation.data_size)?;
+        }
+        writeln!(f)?;
+
+        writeln!(f, "Files: {}", self.files.len())?;
+        for file in &self.files {
+            writeln!(f, "  {}: {} bytes", file.name, file.size)?;
+        }
+        writeln!(f)?;
+
+        writeln!(f, "Mesh Splits: {}", self.mesh_splits.len())?;
+        for mesh_split in &self.mesh_splits {
+            writeln!(
+                f,
+                "  {} {}: {} meshes, {} vertices, {} triangles, {} vertex limit splits, {} bone limit splits",
+                mesh_split.model,
+                mesh_split.material,
+                mesh_split.mesh_count,
+                mesh_split.vertex_count,
+                mesh_split.triangle_count,
+                mesh_split.vertex_limit_splits,
+                mesh_split.hardware_bone_limit_splits
+            )?;
+        }
+        writeln!(f)?;
+
+        writeln!(f, "Warnings: {}", self.warnings.len())?;
+        for warning in &self.warnings {
+            writeln!(f, "  {}", warning)?;
+        }
+
+        if self.dry_run {
+            writeln!(f)?;
+            writeln!(f, "Differences: {}", self.differences.len())?;
+            for difference in &self.differences {
+                writeln!(f, "  {}", difference)?;
+            }
+        }
+
+        // The other models write their own reports next to their files.
+        if !self.additional_models.is_empty() {
+            writeln!(f)?;
+            writeln!(f, "Additional Models: {}", self.additional_models.len())?;
+            for additional_model in &self.additional_models {
+                writeln!(f, "  {}: {} warnings", additional_model.model_name, additional_model.warnings.len())?;
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,400 @@
+//! Accepts compile jobs from other programs, like an exporter in a modeling program, on a local socket.
+//! Each line sent to the socket is a request as JSON, every line sent back is a message as JSON, both are tagged with their type.
+//! While a job compiles its logs and progress are sent, the last message of a job has the compile report.
+//! Source files can also be pushed straight from the program, they are used instead of the file on disk with the same path.
+//! Any page open in a browser can send requests to a local port, so connections that send an HTTP request are closed.
+//! Every connection has to authenticate with the token of the server first, the token is written to a file only the user can read.
+
+use std::{
+    fs::{self, OpenOptions},
+    io::{BufRead, BufReader, Error as IoError, ErrorKind, Write},
+    net::{Ipv4Addr, TcpListener, TcpStream},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        mpsc::{self, Sender},
+        Arc, Mutex, MutexGuard,
+    },
+    thread,
+    time::Duration,
+};
+
+use rayon::ThreadPoolBuilder;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::{
+    compile_project,
+    import::{FileManager, PushedFileData},
+    input::ImputedCompilationData,
+    plugin::PluginManager,
+    project::{list_source_files, open_project},
+    report::CompileReport,
+    utilities::{
+        logging::{add_log_listener, log, log_scope, set_log_scope, LogEntry, LogLevel},
+        progress::{add_progress_listener, ProgressEvent},
+    },
+    ReloadedFile,
+};
+
+/// How often the server checks if it was stopped while waiting for connections.
+const ACCEPT_INTERVAL: Duration = Duration::from_millis(100);
+
+/// The name of the file in the app config folder that the token of the running server is written to.
+const TOKEN_FILE_NAME: &str = "job-server-token";
+const TOKEN_SIZE: usize = 16;
+
+/// Every job gets its own log scope, so only the logs of its compile are sent to it.
+static NEXT_JOB: AtomicU64 = AtomicU64::new(1);
+
+/// The job server that is running, only one server runs at a time.
+#[derive(Default)]
+pub struct JobServer {
+    running: Mutex<Option<Arc<AtomicBool>>>,
+    /// Jobs share the cancel flag and warnings of the compile, so only one job compiles at a time.
+    compiling: Arc<Mutex<()>>,
+}
+
+impl JobServer {
+    /// Starts accepting jobs on the port of the local machine, a server that was already running is stopped.
+    /// Returns the token connections authenticate with, a new token is made every time the server starts.
+    pub fn start(&self, app: AppHandle, port: u16) -> Result<String, IoError> {
+        self.stop();
+
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, port))?;
+        listener.set_nonblocking(true)?;
+
+        let token = create_token()?;
+        let token_path = token_file_path(&app)?;
+        write_token_file(&token_path, &token)?;
+        log(format!("Wrote The Job Server Token To {}!", token_path.display()), LogLevel::Info);
+
+        let running = Arc::new(AtomicBool::new(true));
+        *self.running.lock().unwrap() = Some(Arc::clone(&running));
+
+        let compiling = Arc::clone(&self.compiling);
+        let server_token = token.clone();
+        thread::spawn(move || accept_jobs(app, listener, running, compiling, server_token));
+
+        Ok(token)
+    }
+
+    /// Jobs that are already compiling are finished.
+    pub fn stop(&self) {
+        if let Some(running) = self.running.lock().unwrap().take() {
+            running.store(false, Ordering::Relaxed);
+            log("Stopped Accepting Compile Jobs!", LogLevel::Info);
+        }
+    }
+
+    /// Waits for the job that is compiling, compiles from the window hold this so they do not run at the same time as a job.
+    pub fn lock_compile(&self) -> MutexGuard<'_, ()> {
+        self.compiling.lock().unwrap_or_else(|error| error.into_inner())
+    }
+}
+
+/// The type of a request picks how the rest of the line is read, so a request that is not valid is rejected with its own error.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ServerRequest {
+    Authenticate { token: String },
+    PushFile(PushedFile),
+    Compile(CompileJob),
+}
+
+#[derive(Debug, Deserialize)]
+struct PushedFile {
+    path: String,
+    file: PushedFileData,
+}
+
+/// A job either has the project in it or the path of a saved project.
+#[derive(Debug, Deserialize)]
+struct CompileJob {
+    #[serde(default)]
+    project: Option<Box<ImputedCompilationData>>,
+    #[serde(default)]
+    project_path: Option<String>,
+    #[serde(default)]
+    dry_run: bool,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "type")]
+enum JobMessage {
+    Log { entry: LogEntry },
+    Progress { event: String, progress: ProgressEvent },
+    Finished { report: Option<Box<CompileReport>> },
+    Pushed { path: String },
+    Authenticated,
+    Rejected { reason: String },
+}
+
+/// The token is random bytes from the system written as hex, so it can be sent in a line of JSON.
+fn create_token() -> Result<String, IoError> {
+    let mut bytes = [0; TOKEN_SIZE];
+    getrandom::getrandom(&mut bytes).map_err(|error| IoError::other(error.to_string()))?;
+    Ok(bytes.iter().map(|byte| format!("{:02x}", byte)).collect())
+}
+
+fn token_file_path(app: &AppHandle) -> Result<PathBuf, IoError> {
+    let config_directory = app
+        .path()
+        .app_config_dir()
+        .map_err(|error| IoError::new(ErrorKind::NotFound, error.to_string()))?;
+    Ok(config_directory.join(TOKEN_FILE_NAME))
+}
+
+/// The old file is removed first, so the new file is always created with only the user able to read it.
+fn write_token_file(path: &Path, token: &str) -> Result<(), IoError> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    match fs::remove_file(path) {
+        Err(error) if error.kind() != ErrorKind::NotFound => return Err(error),
+        _ => {}
+    }
+
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+
+        options.mode(0o600);
+    }
+
+    options.open(path)?.write_all(token.as_bytes())
+}
+
+/// Compares every byte, so how long the comparison takes does not tell how much of the token was right.
+fn is_token(sent_token: &str, token: &str) -> bool {
+    sent_token.len() == token.len()
+        && sent_token
+            .bytes()
+            .zip(token.bytes())
+            .fold(0, |difference, (sent, byte)| difference | (sent ^ byte))
+            == 0
+}
+
+fn accept_jobs(app: AppHandle, listener: TcpListener, running: Arc<AtomicBool>, compiling: Arc<Mutex<()>>, token: String) {
+    while running.load(Ordering::Relaxed) {
+        match listener.accept() {
+            Ok((stream, _)) => {
+                let app = app.clone();
+                let compiling = Arc::clone(&compiling);
+                let token = token.clone();
+                thread::spawn(move || {
+                    if let Err(error) = handle_connection(&app, stream, &compiling, &token) {
+                        log(format!("Job Connection Closed: {}!", error), LogLevel::Verbose);
+                    }
+                });
+            }
+            Err(error) if error.kind() == ErrorKind::WouldBlock => thread::sleep(ACCEPT_INTERVAL),
+            Err(error) => log(format!("Fail To Accept Job Connection: {}!", error), LogLevel::Warn),
+        }
+    }
+}
+
+fn handle_connection(app: &AppHandle, stream: TcpStream, compiling: &Mutex<()>, token: &str) -> Result<(), IoError> {
+    // The listener does not block, but the connections wait for their jobs.
+    stream.set_nonblocking(false)?;
+    let mut writer = stream.try_clone()?;
+    let mut authenticated = false;
+
+    for line in BufReader::new(stream).lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        if is_http_request(&line) {
+            log("Closed A Job Connection That Sent An HTTP Request!", LogLevel::Warn);
+            return Ok(());
+        }
+
+        let request = serde_json::from_str::<ServerRequest>(&line);
+
+        // Nothing is done for a connection before it authenticates, and a connection with the wrong token is closed.
+        if !authenticated {
+            match request {
+                Ok(ServerRequest::Authenticate { token: sent_token }) if is_token(&sent_token, token) => {
+                    authenticated = true;
+                    send_message(&mut writer, &JobMessage::Authenticated)?;
+                    continue;
+                }
+                _ => {
+                    log("Closed A Job Connection That Did Not Authenticate!", LogLevel::Warn);
+                    return send_message(
+                        &mut writer,
+                        &JobMessage::Rejected {
+                            reason: String::from("Connection Did Not Authenticate"),
+                        },
+                    );
+                }
+            }
+        }
+
+        let job = match request {
+            Ok(ServerRequest::Authenticate { .. }) => {
+                send_message(
+                    &mut writer,
+                    &JobMessage::Rejected {
+                        reason: String::from("Connection Is Already Authenticated"),
+                    },
+                )?;
+                continue;
+            }
+            Ok(ServerRequest::Compile(job)) => job,
+            Ok(ServerRequest::PushFile(push_file)) => {
+                send_message(&mut writer, &receive_file(app, push_file))?;
+                continue;
+            }
+            Err(error) => {
+                send_message(&mut writer, &JobMessage::Rejected { reason: error.to_string() })?;
+                continue;
+            }
+        };
+
+        let data = match (job.project, job.project_path) {
+            (Some(project), _) => *project,
+            (None, Some(project_path)) => match open_project(Path::new(&project_path)) {
+                Ok(opened_project) => opened_project.project,
+                Err(error) => {
+                    send_message(&mut writer, &JobMessage::Rejected { reason: error.to_string() })?;
+                    continue;
+                }
+            },
+            (None, None) => {
+                send_message(
+                    &mut writer,
+                    &JobMessage::Rejected {
+                        reason: String::from("Job Has No Project Or Project Path"),
+                    },
+                )?;
+                continue;
+            }
+        };
+
+        let _compiling = compiling.lock().unwrap_or_else(|error| error.into_inner());
+        run_job(app, data, job.dry_run, &mut writer)?;
+    }
+
+    Ok(())
+}
+
+/// A request line of HTTP is a method, a target and the version of HTTP, which a line of JSON can not be.
+fn is_http_request(line: &str) -> bool {
+    let words = line.split_whitespace().collect::<Vec<_>>();
+    match words.as_slice() {
+        [method, _, version] => method.chars().all(|character| character.is_ascii_uppercase()) && version.starts_with("HTTP/"),
+        _ => false,
+    }
+}
+
+/// Entries using the path are updated like the file changed on disk.
+fn receive_file(app: &AppHandle, pushed_file: PushedFile) -> JobMessage {
+    match app.state::<FileManager>().push_file(pushed_file.path.clone(), pushed_file.file) {
+        Ok(file) => {
+            log(format!("Received file {}!", pushed_file.path), LogLevel::Info);
+            let _ = app.emit(
+                "source-wrench-files-reloaded",
+                vec![ReloadedFile {
+                    path: pushed_file.path.clone(),
+                    file,
+                }],
+            );
+            JobMessage::Pushed { path: pushed_file.path }
+        }
+        Err(error) => JobMessage::Rejected { reason: error.to_string() },
+    }
+}
+
+/// Compiles the project on another thread and sends its logs and progress until it is done.
+/// Logs from the window, like files it imports while the job compiles, are not part of the job and are not sent.
+fn run_job(app: &AppHandle, data: ImputedCompilationData, dry_run: bool, writer: &mut TcpStream) -> Result<(), IoError> {
+    let (sender, receiver) = mpsc::channel();
+    let job = NEXT_JOB.fetch_add(1, Ordering::Relaxed);
+    let job_running = Arc::new(AtomicBool::new(true));
+
+    // The listeners are removed with the first log or progress after the job is done.
+    let log_sender = sender.clone();
+    let log_job_running = Arc::clone(&job_running);
+    add_log_listener(move |entry| {
+        log_job_running.load(Ordering::Relaxed) && (log_scope() != Some(job) || log_sender.send(JobMessage::Log { entry: entry.clone() }).is_ok())
+    });
+    let progress_sender = sender.clone();
+    let progress_job_running = Arc::clone(&job_running);
+    add_progress_listener(move |event, progress| {
+        progress_job_running.load(Ordering::Relaxed)
+            && (log_scope() != Some(job)
+                || progress_sender
+                    .send(JobMessage::Progress {
+                        event: event.to_string(),
+                        progress: progress.clone(),
+                    })
+                    .is_ok())
+    });
+
+    let app = app.clone();
+    let compile_thread = thread::spawn(move || {
+        set_log_scope(Some(job));
+        // Parts of the compile run on the thread pool, so the job gets a pool whose threads log for it too.
+        match ThreadPoolBuilder::new().start_handler(move |_| set_log_scope(Some(job))).build() {
+            Ok(thread_pool) => thread_pool.install(|| compile_job(&app, data, dry_run, sender)),
+            Err(error) => {
+                log(format!("Fail To Create The Job Thread Pool: {}!", error), LogLevel::Warn);
+                compile_job(&app, data, dry_run, sender);
+            }
+        }
+    });
+
+    // A closed connection does not stop the compile, so the compile is waited on before the next job can take the compile lock.
+    let mut sent = Ok(());
+    for message in &receiver {
+        let finished = matches!(message, JobMessage::Finished { .. });
+        sent = send_message(writer, &message);
+
+        if finished || sent.is_err() {
+            break;
+        }
+    }
+
+    drop(receiver);
+    if compile_thread.join().is_err() {
+        log("Compile Job Panicked!", LogLevel::Error);
+    }
+    job_running.store(false, Ordering::Relaxed);
+
+    sent
+}
+
+/// The source files are loaded for the job and unloaded after, files the window has loaded stay loaded.
+fn compile_job(app: &AppHandle, data: ImputedCompilationData, dry_run: bool, sender: Sender<JobMessage>) {
+    let file_manager = app.state::<FileManager>();
+    let plugin_manager = app.state::<PluginManager>();
+
+    let mut loaded_files = Vec::new();
+    for source_file in list_source_files(&data) {
+        match file_manager.load_file(source_file.clone()) {
+            Ok(_) => loaded_files.push(source_file),
+            Err(error) => log(format!("Fail To Load File {}: {}!", source_file, error), LogLevel::Error),
+        }
+    }
+
+    log(format!("Compiling Job {}!", data.model_name), LogLevel::Info);
+    let report = compile_project(data, dry_run, &file_manager, &plugin_manager);
+
+    for loaded_file in loaded_files {
+        file_manager.unload_file(loaded_file);
+    }
+
+    let _ = sender.send(JobMessage::Finished { report: report.map(Box::new) });
+}
+
+fn send_message(writer: &mut TcpStream, message: &JobMessage) -> Result<(), IoError> {
+    let mut line = serde_json::to_string(message)?;
+    line.push('\n');
+    writer.write_all(line.as_bytes())
+}
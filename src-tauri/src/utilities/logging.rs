@@ -1,10 +1,18 @@
 use std::{
+    cell::Cell,
+    collections::VecDeque,
     fmt::{self, Display, Formatter},
-    sync::OnceLock,
+    fs,
+    io::Error as IoError,
+    panic::Location,
+    path::{Component, Path},
+    sync::{Arc, Mutex, OnceLock},
+    time::{SystemTime, UNIX_EPOCH},
 };
 
 use serde::Serialize;
 use tauri::{Emitter, WebviewWindow};
+use thiserror::Error as ThisError;
 
 #[derive(Clone, Serialize)]
 pub enum LogLevel {
@@ -30,26 +38,216 @@ impl Display for LogLevel {
     }
 }
 
+/// The part of the compiler a log came from.
+#[derive(Clone, Serialize)]
+pub enum LogSource {
+    Application,
+    Import,
+    Process,
+    Write,
+}
+
+impl LogSource {
+    /// Finds the source from the module that the log was called in.
+    fn from_file(file: &str) -> Self {
+        for component in Path::new(file).components() {
+            let Component::Normal(name) = component else {
+                continue;
+            };
+
+            match name.to_str().map(|name| name.trim_end_matches(".rs")) {
+                Some("import") => return LogSource::Import,
+                Some("process") => return LogSource::Process,
+                Some("write") => return LogSource::Write,
+                _ => {}
+            }
+        }
+
+        LogSource::Application
+    }
+}
+
+impl Display for LogSource {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let output = match self {
+            LogSource::Application => "APPLICATION",
+            LogSource::Import => "IMPORT",
+            LogSource::Process => "PROCESS",
+            LogSource::Write => "WRITE",
+        };
+        write!(f, "{}", output)
+    }
+}
+
+#[track_caller]
 pub fn log<T: Into<String>>(message: T, level: LogLevel) {
-    let log_message = message.into();
+    let entry = LogEntry::new(level, LogSource::from_file(Location::caller().file()), message.into());
     if tauri::is_dev() {
-        println!("[{}] {}", level, log_message);
+        println!("{}", entry);
     }
     if let Some(window) = LOGGER.get() {
-        let _ = window.emit("source-wrench-log", LogEvent::new(level, log_message));
+        let _ = window.emit("source-wrench-log", entry.clone());
+    }
+    if let Ok(mut listeners) = LOG_LISTENERS.lock() {
+        listeners.retain(|listener| listener(&entry));
+    }
+    if let Ok(mut history) = LOG_HISTORY.lock() {
+        if history.len() == LOG_HISTORY_CAPACITY {
+            history.pop_front();
+        }
+        history.push_back(entry);
     }
 }
 
 #[derive(Clone, Serialize)]
-struct LogEvent {
+pub struct LogEntry {
+    /// Milliseconds since the unix epoch.
+    timestamp: u64,
     level: LogLevel,
+    source: LogSource,
     message: String,
 }
 
-impl LogEvent {
-    fn new(level: LogLevel, message: String) -> Self {
-        Self { level, message }
+impl LogEntry {
+    fn new(level: LogLevel, source: LogSource, message: String) -> Self {
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |duration| duration.as_millis() as u64);
+
+        Self {
+            timestamp,
+            level,
+            source,
+            message,
+        }
+    }
+}
+
+impl Display for LogEntry {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}] [{}] [{}] {}", format_timestamp(self.timestamp), self.level, self.source, self.message)
     }
 }
 
+/// Formats milliseconds since the unix epoch as a UTC date and time.
+fn format_timestamp(timestamp: u64) -> String {
+    let milliseconds = timestamp % 1000;
+    let seconds = timestamp / 1000;
+    let time_of_day = seconds % 86400;
+
+    // Converts the days since the epoch to a civil date, with years starting in March so leap days are last.
+    let days = (seconds / 86400) as i64 + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days.rem_euclid(146097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}.{:03}",
+        year,
+        month,
+        day,
+        time_of_day / 3600,
+        time_of_day % 3600 / 60,
+        time_of_day % 60,
+        milliseconds
+    )
+}
+
+#[derive(Debug, ThisError)]
+pub enum LogExportError {
+    #[error("Failed To Write Log File: {0}")]
+    FailedFileWrite(#[from] IoError),
+    #[error("Failed To Serialize Log: {0}")]
+    FailedSerialization(#[from] serde_json::Error),
+}
+
+/// Writes the kept logs, as json when the file has a json extension and as text otherwise.
+pub fn export_log(path: &Path) -> Result<(), LogExportError> {
+    let history = match LOG_HISTORY.lock() {
+        Ok(history) => history.clone(),
+        Err(_) => VecDeque::new(),
+    };
+
+    let is_json = path.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("json"));
+
+    let contents = if is_json {
+        serde_json::to_string_pretty(&history)?
+    } else {
+        history.iter().map(|entry| format!("{}\n", entry)).collect()
+    };
+
+    fs::write(path, contents)?;
+
+    Ok(())
+}
+
 pub static LOGGER: OnceLock<WebviewWindow> = OnceLock::new();
+
+/// The most logs kept for exporting, the oldest logs are dropped so long sessions do not keep growing.
+const LOG_HISTORY_CAPACITY: usize = 100_000;
+
+/// The latest logs since the application started, kept for exporting.
+static LOG_HISTORY: Mutex<VecDeque<LogEntry>> = Mutex::new(VecDeque::new());
+
+/// Called with every log, a listener is removed once it returns false.
+static LOG_LISTENERS: Mutex<Vec<LogListener>> = Mutex::new(Vec::new());
+
+type LogListener = Box<dyn Fn(&LogEntry) -> bool + Send>;
+
+thread_local! {
+    /// The compile job the logs of the thread belong to, listeners check it to only take the logs of their own job.
+    static LOG_SCOPE: Cell<Option<u64>> = const { Cell::new(None) };
+}
+
+pub fn set_log_scope(scope: Option<u64>) {
+    LOG_SCOPE.with(|log_scope| log_scope.set(scope));
+}
+
+pub fn log_scope() -> Option<u64> {
+    LOG_SCOPE.with(Cell::get)
+}
+
+pub fn add_log_listener(listener: impl Fn(&LogEntry) -> bool + Send + 'static) {
+    if let Ok(mut listeners) = LOG_LISTENERS.lock() {
+        listeners.push(Box::new(listener));
+    }
+}
+
+/// Collects the warnings logged while it is alive, used for the report of a single compile.
+pub struct WarningCollector {
+    warnings: Arc<Mutex<Vec<String>>>,
+}
+
+impl WarningCollector {
+    pub fn take(&self) -> Vec<String> {
+        match self.warnings.lock() {
+            Ok(mut warnings) => std::mem::take(&mut *warnings),
+            Err(_) => Vec::new(),
+        }
+    }
+}
+
+/// Starts collecting the warnings that are logged, the listener is removed with the next log after the collector is dropped.
+pub fn collect_warnings() -> WarningCollector {
+    let warnings = Arc::new(Mutex::new(Vec::new()));
+
+    let listener_warnings = Arc::downgrade(&warnings);
+    add_log_listener(move |entry| {
+        let Some(warnings) = listener_warnings.upgrade() else {
+            return false;
+        };
+
+        if let LogLevel::Warn = entry.level {
+            if let Ok(mut warnings) = warnings.lock() {
+                warnings.push(entry.message.clone());
+            }
+        }
+
+        true
+    });
+
+    WarningCollector { warnings }
+}
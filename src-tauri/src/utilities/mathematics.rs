@@ -13,6 +13,20 @@ pub struct BoundingBox {
 }
 
 impl BoundingBox {
+    /// The bounds of the points, which is not valid when there are no points.
+    pub fn from_points<'a>(points: impl IntoIterator<Item = &'a Vector3>) -> Self {
+        let mut bounding_box = Self {
+            minimum: Vector3::new(f64::MAX, f64::MAX, f64::MAX),
+            maximum: Vector3::new(f64::MIN, f64::MIN, f64::MIN),
+        };
+
+        for point in points {
+            bounding_box.add_point(*point);
+        }
+
+        bounding_box
+    }
+
     pub fn is_valid(&self) -> bool {
         self.minimum.x <= self.maximum.x && self.minimum.y <= self.maximum.y && self.minimum.z <= self.maximum.z
     }
@@ -33,9 +47,32 @@ impl BoundingBox {
         self.maximum.z = self.maximum.z.max(point.z);
     }
 
+    pub fn add_bounding_box(&mut self, other: &BoundingBox) {
+        self.add_point(other.minimum);
+        self.add_point(other.maximum);
+    }
+
     pub fn center(&self) -> Vector3 {
         (self.minimum + self.maximum) * 0.5
     }
+
+    pub fn size(&self) -> Vector3 {
+        self.maximum - self.minimum
+    }
+
+    pub fn corners(&self) -> [Vector3; 8] {
+        let (minimum, maximum) = (self.minimum, self.maximum);
+        [
+            Vector3::new(minimum.x, minimum.y, minimum.z),
+            Vector3::new(maximum.x, minimum.y, minimum.z),
+            Vector3::new(minimum.x, maximum.y, minimum.z),
+            Vector3::new(maximum.x, maximum.y, minimum.z),
+            Vector3::new(minimum.x, minimum.y, maximum.z),
+            Vector3::new(maximum.x, minimum.y, maximum.z),
+            Vector3::new(minimum.x, maximum.y, maximum.z),
+            Vector3::new(maximum.x, maximum.y, maximum.z),
+        ]
+    }
 }
 
 pub fn clamp<T: PartialOrd>(value: T, minimum: T, maximum: T) -> T {
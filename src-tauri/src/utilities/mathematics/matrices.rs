@@ -92,6 +92,14 @@ impl Matrix4 {
         }
     }
 
+    pub fn transform(&self, point: Vector3) -> Vector3 {
+        Vector3::new(
+            self.entries[0][0] * point.x + self.entries[0][1] * point.y + self.entries[0][2] * point.z + self.entries[0][3],
+            self.entries[1][0] * point.x + self.entries[1][1] * point.y + self.entries[1][2] * point.z + self.entries[1][3],
+            self.entries[2][0] * point.x + self.entries[2][1] * point.y + self.entries[2][2] * point.z + self.entries[2][3],
+        )
+    }
+
     pub fn transpose(&self) -> Self {
         let translation = Vector3::new(self.entries[0][3], self.entries[1][3], self.entries[2][3]);
         let row_x = Vector3::new(self.entries[0][0], self.entries[1][0], self.entries[2][0]);
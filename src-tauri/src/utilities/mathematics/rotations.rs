@@ -1,9 +1,9 @@
 use std::{
     f64::consts::{FRAC_PI_2, PI},
-    ops::{Add, Index, Sub},
+    ops::{Add, Index, Mul, Sub},
 };
 
-use super::Matrix3;
+use super::{Matrix3, Vector3};
 
 /// Euler angles in radians. Roll, Pitch, Yaw
 #[derive(Clone, Copy, Debug, Default)]
@@ -166,6 +166,12 @@ impl Quaternion {
     pub fn new(x: f64, y: f64, z: f64, w: f64) -> Self {
         Self { x, y, z, w }
     }
+
+    /// Creates a rotation of an angle in radians around a normalized axis.
+    pub fn from_axis_angle(axis: Vector3, angle: f64) -> Self {
+        let half_sin = (angle / 2.0).sin();
+        Self::new(axis.x * half_sin, axis.y * half_sin, axis.z * half_sin, (angle / 2.0).cos())
+    }
 }
 
 impl Quaternion {
@@ -224,3 +230,16 @@ impl Quaternion {
         Self::new(self.x / mag, self.y / mag, self.z / mag, self.w / mag)
     }
 }
+
+impl Mul for Quaternion {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        Self::new(
+            self.w * rhs.x + self.x * rhs.w + self.y * rhs.z - self.z * rhs.y,
+            self.w * rhs.y - self.x * rhs.z + self.y * rhs.w + self.z * rhs.x,
+            self.w * rhs.z + self.x * rhs.y - self.y * rhs.x + self.z * rhs.w,
+            self.w * rhs.w - self.x * rhs.x - self.y * rhs.y - self.z * rhs.z,
+        )
+    }
+}
@@ -1,2 +1,3 @@
 pub mod logging;
 pub mod mathematics;
+pub mod progress;
@@ -0,0 +1,87 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Mutex,
+};
+
+use serde::Serialize;
+use tauri::Emitter;
+
+use super::logging::LOGGER;
+
+/// Set when the user asks for the running compile to stop, stages check it between items.
+static CANCEL_REQUESTED: AtomicBool = AtomicBool::new(false);
+
+pub fn request_cancel() {
+    CANCEL_REQUESTED.store(true, Ordering::Relaxed);
+}
+
+pub fn clear_cancel() {
+    CANCEL_REQUESTED.store(false, Ordering::Relaxed);
+}
+
+pub fn is_cancelled() -> bool {
+    CANCEL_REQUESTED.load(Ordering::Relaxed)
+}
+
+/// Reports which stage of the compile is running out of all the stages.
+pub fn stage(name: &str, index: usize, count: usize) {
+    emit(
+        "source-wrench-stage",
+        ProgressEvent {
+            stage: name.to_string(),
+            completed: index,
+            total: count,
+        },
+    );
+}
+
+/// Reports how many items of a stage are done, stages that run in parallel may report out of order.
+pub fn progress(stage: &str, completed: usize, total: usize) {
+    emit(
+        "source-wrench-progress",
+        ProgressEvent {
+            stage: stage.to_string(),
+            completed,
+            total,
+        },
+    );
+}
+
+/// Reports how many bytes of a file have been imported.
+pub fn import_progress(path: &str, completed: usize, total: usize) {
+    emit(
+        "source-wrench-import-progress",
+        ProgressEvent {
+            stage: path.to_string(),
+            completed,
+            total,
+        },
+    );
+}
+
+fn emit(event: &str, payload: ProgressEvent) {
+    if let Ok(mut listeners) = PROGRESS_LISTENERS.lock() {
+        listeners.retain(|listener| listener(event, &payload));
+    }
+    if let Some(window) = LOGGER.get() {
+        let _ = window.emit(event, payload);
+    }
+}
+
+/// Called with the name and payload of every progress event, a listener is removed once it returns false.
+static PROGRESS_LISTENERS: Mutex<Vec<ProgressListener>> = Mutex::new(Vec::new());
+
+type ProgressListener = Box<dyn Fn(&str, &ProgressEvent) -> bool + Send>;
+
+pub fn add_progress_listener(listener: impl Fn(&str, &ProgressEvent) -> bool + Send + 'static) {
+    if let Ok(mut listeners) = PROGRESS_LISTENERS.lock() {
+        listeners.push(Box::new(listener));
+    }
+}
+
+#[derive(Clone, Serialize)]
+pub struct ProgressEvent {
+    stage: String,
+    completed: usize,
+    total: usize,
+}
@@ -1,29 +1,57 @@
-use std::{fs::write, mem::size_of};
+use std::{
+    fs::{self, File},
+    io::{BufWriter, Error as IoError, Read, Write},
+    mem::size_of,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
+};
 
 use half::f16;
 use indexmap::IndexMap;
+use rayon::prelude::*;
 use thiserror::Error as ThisError;
 
 use crate::{
-    process::{ProcessedAnimationData, ProcessedBodyPart, ProcessedData, FLOAT_TOLERANCE, MAX_HARDWARE_BONES_PER_STRIP, VERTEX_CACHE_SIZE},
-    utilities::mathematics::{clamp, Angles, Quaternion, Vector2, Vector3, Vector4},
+    game::{find_materials_directory, material_is_translucent},
+    input::{ImputedCompilationData, ImputedGibs, ImputedModelFlags, ImputedRotationEncoding},
+    process::{
+        ProcessedAnimation, ProcessedAnimationData, ProcessedBodyPart, ProcessedData, ProcessedInverseKinematicRuleType, ProcessedPhysicsData,
+        ProcessedProcedural, COMPILE_STAGES, FLOAT_TOLERANCE, MAX_HARDWARE_BONES_PER_STRIP,
+    },
+    read::compare_model_file,
+    report::{CompileReport, ReportAnimation, ReportBodyPart, ReportFile, ReportFlagIssue, ReportLevelOfDetail, ReportMeshSplit, ReportModel},
+    utilities::{
+        logging::{log, LogLevel},
+        mathematics::{clamp, Angles, BoundingBox, Matrix4, Quaternion, Vector2, Vector3, Vector4},
+        progress::{is_cancelled, progress, stage},
+    },
 };
 
+pub mod layout;
 mod mesh;
 mod model;
+mod physics;
 mod vertex;
 
+use layout::{TANGENT_SIZE, VERTEX_SIZE};
 use mesh::{
     MeshFileBodyPartHeader, MeshFileBoneStateChangeHeader, MeshFileHeader, MeshFileMaterialReplacementListHeader, MeshFileMeshHeader, MeshFileModelHeader,
     MeshFileModelLODHeader, MeshFileStripFlags, MeshFileStripGroupHeader, MeshFileStripGroupHeaderFlags, MeshFileStripHeader, MeshFileVertexHeader,
 };
 
 use model::{
-    ModelFileAnimation, ModelFileAnimationData, ModelFileAnimationDescription, ModelFileAnimationEncoding, ModelFileAnimationEncodingHeader,
-    ModelFileAnimationSection, ModelFileAnimationValue, ModelFileBodyPart, ModelFileBone, ModelFileBoneFlags, ModelFileHeader, ModelFileHitboxSet,
-    ModelFileMaterial, ModelFileMesh, ModelFileModel, ModelFileSecondHeader, ModelFileSequenceDescription,
+    ModelFileAnimation, ModelFileAnimationData, ModelFileAnimationDescription, ModelFileAnimationDescriptionFlags, ModelFileAnimationEncoding,
+    ModelFileAnimationEncodingHeader, ModelFileAnimationSection, ModelFileAnimationValue, ModelFileAttachment, ModelFileAutoLayer, ModelFileAutoLayerFlags,
+    ModelFileAxisInterpolationBone, ModelFileBodyPart, ModelFileBone, ModelFileBoneFlags, ModelFileBoneFlexDriver, ModelFileBoneFlexDriverControl,
+    ModelFileBoneProceduralType, ModelFileCompressedInverseKinematicError, ModelFileFlex, ModelFileFlexController, ModelFileFlexDescription,
+    ModelFileFlexOperation, ModelFileFlexRule, ModelFileHeader, ModelFileHeaderContents, ModelFileHeaderFlags, ModelFileHitboxSet,
+    ModelFileInverseKinematicChain, ModelFileInverseKinematicLink, ModelFileInverseKinematicLock, ModelFileInverseKinematicRule, ModelFileJiggleBone,
+    ModelFileJiggleBoneFlags, ModelFileMaterial, ModelFileMesh, ModelFileModel, ModelFileMouth, ModelFileMovement, ModelFileMovementFlags,
+    ModelFilePoseParameter, ModelFileQuaternionInterpolationBone, ModelFileQuaternionInterpolationTrigger, ModelFileSecondHeader, ModelFileSequenceDescription,
+    ModelFileSequenceDescriptionFlags, ModelFileSourceBoneTransform, ModelFileVertexAnimation,
 };
 
+use physics::{PhysicsFileHeader, PhysicsFileLedge, PhysicsFileSolid};
 use vertex::{VertexFileHeader, VertexFileVertex};
 
 pub const MAX_LOD_COUNT: usize = 8;
@@ -36,17 +64,162 @@ pub enum FileWriteError {
     KeyvaluesToLarge,
     #[error("Offset Provided Is Too Large To Write To File")]
     OffsetToLarge,
+    #[error("Section {1} Of Animation {0} Is Too Large To Write To File, Use Fewer Frames Per Section")]
+    AnimationSectionToLarge(String, usize),
+    #[error("Failed To Write File: {0}")]
+    FailedFileWrite(#[from] IoError),
+    #[error("Model Version {0} Is Not Supported")]
+    UnsupportedVersion(i32),
+    #[error("Keyvalue {0} Has A Quote In Its Value {1}, Keyvalues Can Not Escape Quotes")]
+    KeyvalueHasQuote(String, String),
+    #[error("Compile Was Cancelled")]
+    Cancelled,
+}
+
+/// The model versions that share the layout written by the model writer, so the target version only changes the header version.
+/// 48 is used by the Source 2013 branches and 49 by the Portal 2 and CS:GO branches, 44 and 45 use other layouts that are not written.
+const SUPPORTED_MODEL_VERSIONS: [i32; 2] = [48, 49];
+
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// The model and animation block files both start with an identifier and a version, followed by the checksum.
+const HEADER_CHECKSUM_INDEX: usize = 8;
+
+/// The lookup table for the CRC32 polynomial that studiomdl uses for the model checksum.
+const CHECKSUM_TABLE: [u32; 256] = {
+    let mut table = [0; 256];
+    let mut index = 0;
+    while index < 256 {
+        let mut value = index as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            value = if value & 1 == 1 { 0xEDB88320 ^ (value >> 1) } else { value >> 1 };
+            bit += 1;
+        }
+        table[index] = value;
+        index += 1;
+    }
+    table
+};
+
+/// Computes the CRC32 of the bytes, which the engine compares between the model files.
+pub fn calculate_checksum<'a>(bytes: impl IntoIterator<Item = &'a u8>) -> i32 {
+    let mut crc = u32::MAX;
+
+    for byte in bytes {
+        crc = CHECKSUM_TABLE[((crc ^ *byte as u32) & 0xFF) as usize] ^ (crc >> 8);
+    }
+
+    (crc ^ u32::MAX) as i32
+}
+
+/// A byte buffer stored in fixed size chunks so growing it never copies the data already written.
+#[derive(Debug, Default)]
+pub struct ChunkedBuffer {
+    chunks: Vec<Vec<u8>>,
+    length: usize,
+}
+
+impl ChunkedBuffer {
+    pub fn len(&self) -> usize {
+        self.length
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.length == 0
+    }
+
+    pub fn extend_from_slice(&mut self, mut bytes: &[u8]) {
+        self.length += bytes.len();
+
+        while !bytes.is_empty() {
+            let chunk = match self.chunks.last_mut() {
+                Some(chunk) if chunk.len() < CHUNK_SIZE => chunk,
+                _ => {
+                    self.chunks.push(Vec::with_capacity(CHUNK_SIZE));
+                    self.chunks.last_mut().unwrap()
+                }
+            };
+
+            let count = bytes.len().min(CHUNK_SIZE - chunk.len());
+            chunk.extend_from_slice(&bytes[..count]);
+            bytes = &bytes[count..];
+        }
+    }
+
+    pub fn push(&mut self, byte: u8) {
+        self.extend_from_slice(&[byte]);
+    }
+
+    pub fn pad(&mut self, count: usize) {
+        for _ in 0..count {
+            self.push(0);
+        }
+    }
+
+    /// Overwrites already written bytes starting at the index, the bytes may cross a chunk boundary.
+    pub fn overwrite(&mut self, index: usize, bytes: &[u8]) {
+        debug_assert!(
+            index + bytes.len() <= self.length,
+            "Overwrite Is Out Of Bounds! index: {} bytes.len(): {} self.length: {}",
+            index,
+            bytes.len(),
+            self.length
+        );
+
+        for (byte_index, byte) in bytes.iter().enumerate() {
+            let position = index + byte_index;
+            self.chunks[position / CHUNK_SIZE][position % CHUNK_SIZE] = *byte;
+        }
+    }
+
+    pub fn checksum(&self) -> i32 {
+        calculate_checksum(self.chunks.iter().flatten())
+    }
+
+    /// Compares the buffer to a file a chunk at a time, files of another length are never read.
+    pub fn matches_file(&self, path: &Path) -> Result<bool, IoError> {
+        let mut file = File::open(path)?;
+        if file.metadata()?.len() != self.length as u64 {
+            return Ok(false);
+        }
+
+        let mut file_chunk = vec![0; CHUNK_SIZE];
+        for chunk in &self.chunks {
+            let file_chunk = &mut file_chunk[..chunk.len()];
+            file.read_exact(file_chunk)?;
+            if file_chunk != chunk.as_slice() {
+                return Ok(false);
+            }
+        }
+
+        Ok(true)
+    }
+
+    pub fn to_vec(&self) -> Vec<u8> {
+        self.chunks.concat()
+    }
+
+    pub fn write_to_file(&self, path: &Path) -> Result<(), IoError> {
+        let mut file = BufWriter::new(File::create(path)?);
+
+        for chunk in &self.chunks {
+            file.write_all(chunk)?;
+        }
+
+        file.flush()
+    }
 }
 
 #[derive(Debug, Default)]
 pub struct FileWriter {
-    pub data: Vec<u8>,
+    pub data: ChunkedBuffer,
     string_table: IndexMap<String, Vec<(usize, usize)>>,
 }
 
 impl FileWriter {
     pub fn write_unsigned_byte(&mut self, value: u8) {
-        self.data.extend(value.to_le_bytes());
+        self.data.extend_from_slice(&value.to_le_bytes());
     }
 
     pub fn write_unsigned_byte_array(&mut self, values: &[u8]) {
@@ -56,15 +229,15 @@ impl FileWriter {
     }
 
     pub fn write_short(&mut self, value: i16) {
-        self.data.extend(value.to_le_bytes());
+        self.data.extend_from_slice(&value.to_le_bytes());
     }
 
     pub fn write_unsigned_short(&mut self, value: u16) {
-        self.data.extend(value.to_le_bytes());
+        self.data.extend_from_slice(&value.to_le_bytes());
     }
 
     pub fn write_integer(&mut self, value: i32) {
-        self.data.extend(value.to_le_bytes());
+        self.data.extend_from_slice(&value.to_le_bytes());
     }
 
     pub fn write_integer_array(&mut self, values: &[i32]) {
@@ -74,7 +247,7 @@ impl FileWriter {
     }
 
     pub fn write_float(&mut self, value: f32) {
-        self.data.extend(value.to_le_bytes());
+        self.data.extend_from_slice(&value.to_le_bytes());
     }
 
     pub fn write_float_array(&mut self, values: &[f32]) {
@@ -84,13 +257,13 @@ impl FileWriter {
     }
 
     pub fn write_unsigned_long(&mut self, value: u64) {
-        self.data.extend(value.to_le_bytes());
+        self.data.extend_from_slice(&value.to_le_bytes());
     }
 
     pub fn write_char_array(&mut self, value: &str, length: usize) {
         let mut bytes = value.as_bytes().to_vec();
         bytes.resize(length, 0);
-        self.data.extend(bytes);
+        self.data.extend_from_slice(&bytes);
     }
 
     pub fn write_vector2(&mut self, value: Vector2) {
@@ -124,6 +297,14 @@ impl FileWriter {
         self.write_float(value.yaw as f32);
     }
 
+    pub fn write_matrix3x4(&mut self, value: Matrix4) {
+        for row in &value.entries[..3] {
+            for entry in row {
+                self.write_float(*entry as f32);
+            }
+        }
+    }
+
     pub fn write_string_to_table(&mut self, base: usize, value: &str) {
         let string_offset = self.write_integer_index();
 
@@ -176,7 +357,7 @@ impl FileWriter {
 
         let bytes = (offset as i32).to_le_bytes();
 
-        self.data[index..index + bytes.len()].clone_from_slice(&bytes as &[u8]);
+        self.data.overwrite(index, &bytes);
         Ok(())
     }
 
@@ -187,7 +368,7 @@ impl FileWriter {
 
         let bytes = (offset as i16).to_le_bytes();
 
-        self.data[index..index + bytes.len()].clone_from_slice(&bytes as &[u8]);
+        self.data.overwrite(index, &bytes);
         Ok(())
     }
 
@@ -201,17 +382,28 @@ impl FileWriter {
     }
 
     pub fn write_quaternion64(&mut self, value: Quaternion) {
-        let x = clamp((value.x * 1048576.0) as u64 + 1048576, 0, 2097151);
-        let y = clamp((value.y * 1048576.0) as u64 + 1048576, 0, 2097151);
-        let z = clamp((value.z * 1048576.0) as u64 + 1048576, 0, 2097151);
+        // The components are offset before converting, as negative floats convert to zero.
+        let x = clamp((value.x * 1048576.5 + 1048576.0).round() as i64, 0, 2097151) as u64;
+        let y = clamp((value.y * 1048576.5 + 1048576.0).round() as i64, 0, 2097151) as u64;
+        let z = clamp((value.z * 1048576.5 + 1048576.0).round() as i64, 0, 2097151) as u64;
         let w = (value.w < 0.0) as u64;
         self.write_unsigned_long((w << 63) | (z << 42) | (y << 21) | x);
     }
 
+    pub fn write_quaternion48(&mut self, value: Quaternion) {
+        let x = clamp((value.x * 32768.0 + 32768.0).round() as i32, 0, 65535) as u16;
+        let y = clamp((value.y * 32768.0 + 32768.0).round() as i32, 0, 65535) as u16;
+        let z = clamp((value.z * 16384.0 + 16384.0).round() as i32, 0, 32767) as u16;
+        let w = (value.w < 0.0) as u16;
+        self.write_unsigned_short(x);
+        self.write_unsigned_short(y);
+        self.write_unsigned_short((w << 15) | z);
+    }
+
     pub fn write_vector48(&mut self, value: Vector3) {
-        self.data.extend(f16::from_f64(value.x).to_le_bytes());
-        self.data.extend(f16::from_f64(value.y).to_le_bytes());
-        self.data.extend(f16::from_f64(value.z).to_le_bytes());
+        self.data.extend_from_slice(&f16::from_f64(value.x).to_le_bytes());
+        self.data.extend_from_slice(&f16::from_f64(value.y).to_le_bytes());
+        self.data.extend_from_slice(&f16::from_f64(value.z).to_le_bytes());
     }
 
     pub fn write_array_size(&mut self, size: usize) -> Result<(), FileWriteError> {
@@ -241,7 +433,7 @@ impl FileWriter {
 
         let padding = alignment - remainder;
 
-        self.data.resize(self.data.len() + padding, 0);
+        self.data.pad(padding);
     }
 }
 
@@ -249,20 +441,61 @@ pub trait WriteToWriter {
     fn write(&mut self, writer: &mut FileWriter) -> Result<(), FileWriteError>;
 }
 
-pub fn write_files(file_name: String, model_name: String, processed_data: ProcessedData, export_path: String) -> Result<(), FileWriteError> {
+pub fn write_files(
+    file_name: String,
+    model_name: String,
+    input: &ImputedCompilationData,
+    processed_data: ProcessedData,
+    dry_run: bool,
+) -> Result<(CompileReport, StagedFiles), FileWriteError> {
+    if !SUPPORTED_MODEL_VERSIONS.contains(&input.target_version) {
+        return Err(FileWriteError::UnsupportedVersion(input.target_version));
+    }
+
+    if is_cancelled() {
+        return Err(FileWriteError::Cancelled);
+    }
+    stage(COMPILE_STAGES[COMPILE_STAGES.len() - 1], COMPILE_STAGES.len() - 1, COMPILE_STAGES.len());
+
+    // The engine loads the animation block file from the game directory, so it is placed next to the model path.
+    let animation_block_file_name = match input.animation_block_size {
+        0 => String::new(),
+        _ => format!("models/{}.ani", model_name.trim_end_matches(".mdl")),
+    };
+
     let mut mdl_header = ModelFileHeader {
-        version: 48,
-        checksum: 69420,
-        bounding_box: processed_data.model_data.bounding_box, // TODO: If the model has no mesh use sequence bounding box.
-        illumination_position: processed_data.model_data.bounding_box.center(), // TODO: If input, use the input value.
+        version: input.target_version,
+        bounding_box: processed_data.model_data.bounding_box,
+        clipping_box: processed_data.model_data.clipping_box,
+        // The illumination position is relative to the illumination attachment when there is one.
+        illumination_position: match processed_data.bone_data.illumination_attachment {
+            Some(_) => Vector3::default(),
+            None => processed_data.model_data.bounding_box.center(), // TODO: If input, use the input value.
+        },
+        flags: create_header_flags(&input.model_flags),
         second_header: ModelFileSecondHeader {
-            name: model_name,
+            name: model_name.clone(),
+            // The attachment index starts at 1, as 0 means the model has no illumination attachment.
+            illumination_position_attachment_index: processed_data.bone_data.illumination_attachment.map_or(0, |attachment| attachment as i32 + 1),
+            max_eye_deflection: input.max_eye_deflection.to_radians().cos() as f32,
             ..Default::default()
         },
+        surface_properties: input.surface_property.clone(),
+        animation_block_file_name,
+        keyvalues: input.gibs.as_ref().map(create_prop_data_keyvalues).transpose()?.unwrap_or_default(),
         ..Default::default()
     };
 
     for (bone_index, (bone_name, processed_bone)) in processed_data.bone_data.processed_bones.into_iter().enumerate() {
+        if let Some(source_transform) = &processed_bone.source_transform {
+            mdl_header.second_header.source_bone_transforms.push(ModelFileSourceBoneTransform {
+                write_base: 0,
+                name: bone_name.clone(),
+                pre_transform: source_transform.pre,
+                post_transform: source_transform.post,
+            });
+        }
+
         let bone = ModelFileBone {
             name: bone_name,
             parent: match processed_bone.parent {
@@ -276,6 +509,8 @@ pub fn write_files(file_name: String, model_name: String, processed_data: Proces
             animation_rotation_scale: processed_data.animation_data.animation_scales[bone_index].1,
             pose: processed_bone.pose.transpose(),
             flags: ModelFileBoneFlags::from_bits_truncate(processed_bone.flags.bits()),
+            procedural_type: processed_bone.procedural.map(convert_procedural),
+            surface_properties: input.surface_property.clone(),
             ..Default::default()
         };
         mdl_header.bones.push(bone);
@@ -283,46 +518,278 @@ pub fn write_files(file_name: String, model_name: String, processed_data: Proces
 
     mdl_header.sorted_bone_table_by_name = processed_data.bone_data.sorted_bones_by_name;
 
+    if let Some(physics_data) = &processed_data.physics_data {
+        mdl_header.mass = physics_data.total_mass as f32;
+        for (solid_index, solid) in physics_data.solids.iter().enumerate() {
+            mdl_header.bones[solid.bone].physics_index = solid_index as i32;
+        }
+    }
+
+    for processed_attachment in processed_data.bone_data.attachments {
+        mdl_header.local_attachments.push(ModelFileAttachment {
+            name: processed_attachment.name,
+            bone: processed_attachment.bone as i32,
+            local: Matrix4::new(processed_attachment.position, processed_attachment.rotation.to_matrix()),
+            ..Default::default()
+        });
+    }
+
+    // Flexes of the meshes refer to their flex description by index, every flex is set by a flex controller of the same name.
+    let flex_descriptions = processed_data
+        .model_data
+        .flexes
+        .iter()
+        .map(|flex| add_flex_description(&mut mdl_header, flex.clone(), flex.clone()) as i32)
+        .collect::<Vec<_>>();
+
+    // Mouths refer to their flex by index, so every flex used by a mouth gets a flex description.
+    for processed_mouth in processed_data.bone_data.mouths {
+        let flex_description = add_flex_description(&mut mdl_header, processed_mouth.flex, processed_mouth.flex_controller);
+
+        mdl_header.mouths.push(ModelFileMouth {
+            bone: processed_mouth.bone as i32,
+            forward: processed_mouth.forward,
+            flex_description: flex_description as i32,
+        });
+    }
+
+    // Bone flex drivers map the bone position from 0 to 1, so the flex controllers they drive use the same range.
+    for processed_bone_flex_driver in processed_data.bone_data.bone_flex_drivers {
+        let mut bone_flex_driver = ModelFileBoneFlexDriver {
+            bone: processed_bone_flex_driver.bone as i32,
+            ..Default::default()
+        };
+
+        for processed_control in processed_bone_flex_driver.controls {
+            let flex_controller = match mdl_header
+                .flex_controllers
+                .iter()
+                .position(|flex_controller| flex_controller.name == processed_control.flex_controller)
+            {
+                Some(index) => index,
+                None => {
+                    mdl_header.flex_controllers.push(ModelFileFlexController {
+                        controller_type: String::from("default"),
+                        name: processed_control.flex_controller,
+                        minimum: 0.0,
+                        maximum: 1.0,
+                        ..Default::default()
+                    });
+                    mdl_header.flex_controllers.len() - 1
+                }
+            };
+
+            bone_flex_driver.controls.push(ModelFileBoneFlexDriverControl {
+                bone_component: processed_control.component as i32,
+                flex_controller: flex_controller as i32,
+                minimum: processed_control.minimum as f32,
+                maximum: processed_control.maximum as f32,
+            });
+        }
+
+        mdl_header.second_header.bone_flex_drivers.push(bone_flex_driver);
+    }
+
+    // Only the root link carries the knee direction, the engine ignores it on the other links.
+    for processed_chain in processed_data.bone_data.inverse_kinematic_chains {
+        mdl_header.inverse_kinematic_chains.push(ModelFileInverseKinematicChain {
+            name: processed_chain.name,
+            links: processed_chain
+                .bones
+                .iter()
+                .enumerate()
+                .map(|(link_index, &bone)| ModelFileInverseKinematicLink {
+                    bone: bone as i32,
+                    knee_direction: if link_index == 0 {
+                        processed_chain.knee_direction
+                    } else {
+                        Vector3::default()
+                    },
+                })
+                .collect(),
+            ..Default::default()
+        });
+    }
+
     mdl_header.hitbox_sets.push(ModelFileHitboxSet {
         name: String::from("default"),
         ..Default::default()
     });
 
-    write_animations(processed_data.animation_data, &mut mdl_header);
+    write_animations(processed_data.animation_data, input.rotation_encoding, &mut mdl_header)?;
+
+    for transition_node in processed_data.transition_nodes {
+        mdl_header.local_node_names.push(transition_node.name);
+        mdl_header.local_nodes.push(transition_node.next_nodes);
+    }
+
+    for processed_pose_parameter in processed_data.pose_parameters {
+        mdl_header.local_pose_parameters.push(ModelFilePoseParameter {
+            name: processed_pose_parameter.name,
+            start: processed_pose_parameter.start as f32,
+            end: processed_pose_parameter.end as f32,
+            ..Default::default()
+        });
+    }
 
     for processed_sequence in processed_data.sequence_data {
-        let sequence_description = ModelFileSequenceDescription {
+        let mut sequence_description = ModelFileSequenceDescription {
             name: processed_sequence.name,
-            fade_in_time: 0.2,
-            fade_out_time: 0.2,
-            blend_size: [processed_sequence.animations.len() as i32, processed_sequence.animations[0].len() as i32],
+            fade_in_time: processed_sequence.fade_in_time as f32,
+            fade_out_time: processed_sequence.fade_out_time as f32,
+            entry_phase: processed_sequence.entry_phase as f32,
+            exit_phase: processed_sequence.exit_phase as f32,
+            // Animations are stored row by row, so the first blend size is the column count.
+            blend_size: [processed_sequence.animations[0].len() as i32, processed_sequence.animations.len() as i32],
             animations: processed_sequence.animations.into_iter().flatten().collect(),
-            weight_list: vec![1.0; mdl_header.bones.len()],
+            weight_list: processed_sequence.weight_list.iter().map(|&weight| weight as f32).collect(),
+            bounding_box: processed_sequence.bounding_box,
+            activity_modifiers: processed_sequence.activity_modifiers,
             ..Default::default()
         };
 
+        if processed_sequence.is_delta {
+            sequence_description.flags.insert(ModelFileSequenceDescriptionFlags::DELTA);
+        }
+
+        // Node numbers start at one, zero is used for sequences that are not part of the transition graph.
+        if let Some(transition) = processed_sequence.transition {
+            sequence_description.local_entry_node = transition.entry_node as i32 + 1;
+            sequence_description.local_exit_node = transition.exit_node as i32 + 1;
+            sequence_description.reverse_transition = transition.reverse;
+        }
+
+        sequence_description.inversive_kinematic_count = processed_sequence.inverse_kinematic_rule_count as i32;
+        for processed_lock in processed_sequence.inverse_kinematic_locks {
+            sequence_description.inversive_kinematics_locks.push(ModelFileInverseKinematicLock {
+                chain: processed_lock.chain as i32,
+                position_weight: processed_lock.position_weight as f32,
+                local_rotation_weight: processed_lock.local_rotation_weight as f32,
+            });
+        }
+
+        for (parameter_index, parameter) in processed_sequence.parameters.iter().enumerate() {
+            if let Some(parameter) = parameter {
+                sequence_description.parameters[parameter_index] = parameter.pose_parameter as i32;
+                sequence_description.parameters_start[parameter_index] = parameter.start as f32;
+                sequence_description.parameters_end[parameter_index] = parameter.end as f32;
+            }
+        }
+
+        // The keys of the columns are followed by the keys of the rows, an axis without a parameter has a single key.
+        if processed_sequence.parameters.iter().any(Option::is_some) {
+            for (parameter_index, parameter) in processed_sequence.parameters.iter().enumerate() {
+                match parameter {
+                    Some(parameter) => sequence_description.pose_keys.extend(parameter.keys.iter().map(|&key| key as f32)),
+                    None => sequence_description
+                        .pose_keys
+                        .extend(vec![0.0; sequence_description.blend_size[parameter_index] as usize]),
+                }
+            }
+        }
+
+        for processed_auto_layer in processed_sequence.auto_layers {
+            let mut auto_layer = ModelFileAutoLayer {
+                sequence: processed_auto_layer.sequence,
+                start: processed_auto_layer.start as f32,
+                peak: processed_auto_layer.peak as f32,
+                tail: processed_auto_layer.tail as f32,
+                end: processed_auto_layer.end as f32,
+                ..Default::default()
+            };
+
+            if processed_auto_layer.spline {
+                auto_layer.flags.insert(ModelFileAutoLayerFlags::SPLINE);
+            }
+
+            if processed_auto_layer.cross_fade {
+                auto_layer.flags.insert(ModelFileAutoLayerFlags::CROSS_FADE);
+            }
+
+            if processed_auto_layer.no_blend {
+                auto_layer.flags.insert(ModelFileAutoLayerFlags::NO_BLEND);
+            }
+
+            sequence_description.auto_layers.push(auto_layer);
+        }
+
         mdl_header.local_sequence_descriptions.push(sequence_description);
     }
 
     let mut vvd_header = VertexFileHeader {
         version: 4,
-        checksum: 69420,
         lod_count: 1,
         ..Default::default()
     };
     let mut vtx_header = MeshFileHeader {
         version: 7,
-        vertex_cache_size: VERTEX_CACHE_SIZE as i32,
-        max_bones_per_strip: MAX_HARDWARE_BONES_PER_STRIP as u16,
-        max_bones_per_triangle: 9,
-        max_bones_per_vertex: 3,
-        checksum: 69420,
+        vertex_cache_size: processed_data.model_data.limits.vertex_cache_size as i32,
+        max_bones_per_strip: processed_data.model_data.limits.hardware_bones_per_strip as u16,
+        max_bones_per_triangle: (processed_data.model_data.limits.weights_per_vertex * 3) as u16,
+        max_bones_per_vertex: processed_data.model_data.limits.weights_per_vertex as i32,
+        ..Default::default()
+    };
+
+    let mut report = CompileReport {
+        model_name,
+        export_path: input.export_path.clone(),
+        bone_count: mdl_header.bones.len(),
+        dry_run,
+        materials: processed_data.model_data.materials.iter().cloned().collect(),
+        default_body: processed_data.model_data.default_body,
+        mesh_splits: processed_data
+            .model_data
+            .mesh_splits
+            .iter()
+            .map(|mesh_split| ReportMeshSplit {
+                model: mesh_split.model.clone(),
+                material: mesh_split.material.clone(),
+                mesh_count: mesh_split.mesh_count,
+                vertex_count: mesh_split.vertex_count,
+                triangle_count: mesh_split.triangle_count,
+                vertex_limit_splits: mesh_split.vertex_limit_splits,
+                hardware_bone_limit_splits: mesh_split.hardware_bone_limit_splits,
+            })
+            .collect(),
         ..Default::default()
     };
 
-    mdl_header.material_paths.push(String::from("\\"));
+    for processed_body_part in &processed_data.model_data.body_parts {
+        report.body_parts.push(ReportBodyPart {
+            name: processed_body_part.name.clone(),
+            models: processed_body_part
+                .models
+                .iter()
+                .map(|processed_model| ReportModel {
+                    name: processed_model.name.clone(),
+                    // TODO: Report every level of detail once they are generated.
+                    levels_of_detail: vec![ReportLevelOfDetail {
+                        vertex_count: processed_model.meshes.iter().map(|mesh| mesh.vertex_data.len()).sum(),
+                        triangle_count: processed_model
+                            .meshes
+                            .iter()
+                            .flat_map(|mesh| &mesh.strip_groups)
+                            .map(|strip_group| strip_group.indices.len() / 3)
+                            .sum(),
+                    }],
+                })
+                .collect(),
+        });
+    }
+
+    // The engine joins material paths with forward slashes on every platform, so no separator is needed for the root.
+    mdl_header.material_paths = processed_data.model_data.material_paths;
+    if mdl_header.material_paths.is_empty() {
+        mdl_header.material_paths.push(String::new());
+    }
 
-    write_body_parts(processed_data.model_data.body_parts, &mut mdl_header, &mut vtx_header, &mut vvd_header);
+    write_body_parts(
+        processed_data.model_data.body_parts,
+        &flex_descriptions,
+        &mut mdl_header,
+        &mut vtx_header,
+        &mut vvd_header,
+    );
 
     for processed_material in processed_data.model_data.materials {
         let material = ModelFileMaterial {
@@ -334,238 +801,927 @@ pub fn write_files(file_name: String, model_name: String, processed_data: Proces
 
     mdl_header.material_replacements.push((0..mdl_header.materials.len() as i16).collect());
 
+    report.flag_issues = audit_header_flags(&mdl_header, input, processed_data.physics_data.is_some());
+
+    // The other variants share the mesh data, the software variant only differs by skinning the strips on the CPU.
+    let mut mesh_variants = vec![("dx90.vtx", true)];
+    if input.write_dx80_mesh {
+        mesh_variants.push(("dx80.vtx", true));
+    }
+    if input.write_software_mesh {
+        mesh_variants.push(("sw.vtx", false));
+    }
+
+    let file_count = 2 + mesh_variants.len() + if input.animation_block_size > 0 { 1 } else { 0 } + if processed_data.physics_data.is_some() { 1 } else { 0 };
+
+    // Nothing has been written yet, so a cancel here has no temporary files to remove.
+    if is_cancelled() {
+        return Err(FileWriteError::Cancelled);
+    }
+
+    // Each file is written out before the next is built so only one file buffer is held at a time, besides the animation blocks.
+    let export_path = Path::new(&input.export_path);
+
+    // Files are written to temporary paths first, so a failed compile never leaves a mix of old and partially written files.
+    let mut staged_files = StagedFiles::default();
+
+    // Model names can have folders in them, which are created under the export path like the engine expects.
+    if let Some(model_directory) = export_path.join(&file_name).parent().filter(|_| !dry_run) {
+        fs::create_dir_all(model_directory)?;
+    }
+
+    // The animation block file is kept until the model is written, as it needs the checksum of the model.
+    let mut ani_writer = None;
+    if input.animation_block_size > 0 {
+        let mut writer = FileWriter::default();
+        mdl_header.write_animation_blocks(&mut writer, input.animation_block_size * 1024)?;
+        log(
+            format!("Animations were split into {} animation blocks", mdl_header.animation_blocks.len() - 1),
+            LogLevel::Verbose,
+        );
+        ani_writer = Some(writer);
+    }
+
     let mut mdl_writer = FileWriter::default();
-    let mut vvd_writer = FileWriter::default();
-    let mut vtx_writer = FileWriter::default();
     mdl_header.write(&mut mdl_writer)?;
+
+    // Like studiomdl the checksum is taken over the whole model file while its checksum is still zero.
+    let checksum = mdl_writer.data.checksum();
+    mdl_writer.data.overwrite(HEADER_CHECKSUM_INDEX, &checksum.to_le_bytes());
+    vvd_header.checksum = checksum;
+    vtx_header.checksum = checksum;
+    report.checksum = checksum;
+    log(format!("Model checksum is {}", checksum), LogLevel::Verbose);
+
+    if let Some(mut ani_writer) = ani_writer {
+        ani_writer.data.overwrite(HEADER_CHECKSUM_INDEX, &checksum.to_le_bytes());
+        output_file(
+            &mut report,
+            &mut staged_files,
+            export_path,
+            format!("{}.{}", file_name, "ani"),
+            &ani_writer.data,
+        )?;
+        progress("Writing Files", report.files.len(), file_count);
+    }
+
+    for animation_description in &mdl_header.local_animation_descriptions {
+        report.animations.push(ReportAnimation {
+            name: animation_description.name.clone(),
+            frame_count: animation_description.frame_count as usize,
+            data_size: animation_description.data_size,
+        });
+    }
+    drop(mdl_header);
+    output_file(
+        &mut report,
+        &mut staged_files,
+        export_path,
+        format!("{}.{}", file_name, "mdl"),
+        &mdl_writer.data,
+    )?;
+    if dry_run {
+        let model_path = export_path.join(format!("{}.{}", file_name, "mdl"));
+        match compare_model_file(&model_path, mdl_writer.data.to_vec()) {
+            Ok(differences) => report.differences.extend(differences),
+            Err(error) => log(format!("Fail To Compare Model File: {}!", error), LogLevel::Warn),
+        }
+    }
+    progress("Writing Files", report.files.len(), file_count);
+    drop(mdl_writer);
+
+    let mut vvd_writer = FileWriter::default();
     vvd_header.write(&mut vvd_writer)?;
-    vtx_header.write(&mut vtx_writer)?;
+    drop(vvd_header);
+    output_file(
+        &mut report,
+        &mut staged_files,
+        export_path,
+        format!("{}.{}", file_name, "vvd"),
+        &vvd_writer.data,
+    )?;
+    progress("Writing Files", report.files.len(), file_count);
+    drop(vvd_writer);
+
+    for (extension, hardware_skinned) in mesh_variants {
+        vtx_header.set_hardware_skinned(hardware_skinned);
+
+        let mut vtx_writer = FileWriter::default();
+        vtx_header.write(&mut vtx_writer)?;
+        output_file(
+            &mut report,
+            &mut staged_files,
+            export_path,
+            format!("{}.{}", file_name, extension),
+            &vtx_writer.data,
+        )?;
+        progress("Writing Files", report.files.len(), file_count);
+    }
 
-    // FIXME: This is a temporary solution to write the files.
-    let _ = write(format!("{}/{}.{}", export_path, file_name, "mdl"), mdl_writer.data);
-    let _ = write(format!("{}/{}.{}", export_path, file_name, "vvd"), vvd_writer.data);
-    let _ = write(format!("{}/{}.{}", export_path, file_name, "dx90.vtx"), vtx_writer.data);
+    if let Some(physics_data) = processed_data.physics_data {
+        let mut phy_header = create_physics_file(physics_data, &input.surface_property, input.gibs.as_ref())?;
+        phy_header.checksum = checksum;
+
+        let mut phy_writer = FileWriter::default();
+        phy_header.write(&mut phy_writer)?;
+        output_file(
+            &mut report,
+            &mut staged_files,
+            export_path,
+            format!("{}.{}", file_name, "phy"),
+            &phy_writer.data,
+        )?;
+        progress("Writing Files", report.files.len(), file_count);
+    }
 
-    Ok(())
+    // Files an earlier compile wrote that this compile did not are removed, so the engine does not load them with a mismatched checksum.
+    for extension in ["ani", "dx80.vtx", "sw.vtx", "phy"] {
+        let name = format!("{}.{}", file_name, extension);
+        let path = export_path.join(&name);
+        if report.files.iter().any(|file| file.name == name) || !path.exists() {
+            continue;
+        }
+
+        if dry_run {
+            report.differences.push(format!("{} is no longer written and is removed.", name));
+        } else {
+            staged_files.remove(path);
+        }
+    }
+
+    Ok((report, staged_files))
 }
 
-fn write_animations(animations: ProcessedAnimationData, header: &mut ModelFileHeader) {
-    for processed_animation in animations.processed_animations {
-        let mut animation_description = ModelFileAnimationDescription {
-            name: processed_animation.name,
-            fps: 30.0,
-            frame_count: processed_animation.frame_count as i32,
-            // TODO: frames_per_section should use the imported frame count.
-            frames_per_section: if processed_animation.sections.len() > 1 { 30 } else { 0 },
-            animation_sections: Vec::with_capacity(processed_animation.sections.len()),
-            ..Default::default()
-        };
+/// Files written next to the files they replace, which are removed again unless every file of the model was written.
+/// The files are only moved into place by a commit, so the files of several models can be replaced together.
+#[derive(Default)]
+pub struct StagedFiles {
+    /// The temporary path and the final path of each file.
+    files: Vec<(PathBuf, PathBuf)>,
+    /// Files of an earlier compile that are no longer written, which are removed by the commit.
+    removed_files: Vec<PathBuf>,
+}
 
-        for mut section in processed_animation.sections {
-            let mut animation_section = ModelFileAnimationSection {
-                animation_data: Vec::with_capacity(section.len()),
-                ..Default::default()
-            };
+impl StagedFiles {
+    fn write(&mut self, path: PathBuf, data: &ChunkedBuffer) -> Result<(), IoError> {
+        let temporary_path = with_added_extension(&path, "tmp");
 
-            section.sort_by(|to, from| to.bone.cmp(&from.bone));
-
-            for animation_bone_data in section {
-                let scale = animations.animation_scales[animation_bone_data.bone as usize].0;
-                let mut scaled_position_axis = [
-                    Vec::with_capacity(animation_bone_data.position.len()),
-                    Vec::with_capacity(animation_bone_data.position.len()),
-                    Vec::with_capacity(animation_bone_data.position.len()),
-                ];
-                for position in &animation_bone_data.position {
-                    for axis in 0..3 {
-                        scaled_position_axis[axis].push(if position[axis].abs() > FLOAT_TOLERANCE {
-                            (position[axis] / scale[axis]) as i16
-                        } else {
-                            0
-                        });
-                    }
-                }
+        // The file is tracked before it is written, so a failed write still removes what was written of it.
+        self.files.push((temporary_path.clone(), path));
+        data.write_to_file(&temporary_path)
+    }
 
-                let scale = animations.animation_scales[animation_bone_data.bone as usize].1;
-                let mut scaled_rotation_axis = [
-                    Vec::with_capacity(animation_bone_data.rotation.len()),
-                    Vec::with_capacity(animation_bone_data.rotation.len()),
-                    Vec::with_capacity(animation_bone_data.rotation.len()),
-                ];
-                for rotation in &animation_bone_data.rotation {
-                    for axis in 0..3 {
-                        scaled_rotation_axis[axis].push(if rotation[axis].abs() > FLOAT_TOLERANCE {
-                            (rotation[axis] / scale[axis]) as i16
-                        } else {
-                            0
-                        });
-                    }
+    fn remove(&mut self, path: PathBuf) {
+        self.removed_files.push(path);
+    }
+
+    /// Takes over the files staged for another model.
+    pub fn append(&mut self, mut other: StagedFiles) {
+        self.files.append(&mut other.files);
+        self.removed_files.append(&mut other.removed_files);
+    }
+
+    /// Moves the temporary files over the files they replace and removes the files that are no longer written.
+    /// The old files are moved to backups first, so when any rename fails every old file is put back as it was.
+    pub fn commit(mut self) -> Result<(), IoError> {
+        let mut backups = Vec::new();
+        let mut committed_files = Vec::new();
+
+        if let Err(error) = self.replace_files(&mut backups, &mut committed_files) {
+            for path in committed_files {
+                let _ = fs::remove_file(path);
+            }
+
+            for (path, backup_path) in backups.iter().rev() {
+                if let Err(error) = fs::rename(backup_path, path) {
+                    log(
+                        format!("Fail To Restore {} From {}: {}!", path.display(), backup_path.display(), error),
+                        LogLevel::Error,
+                    );
                 }
+            }
 
-                fn encode_run_length(values: &[i16]) -> Vec<ModelFileAnimationEncoding> {
-                    let mut encoding = Vec::new();
-
-                    let mut current_total = 0;
-                    let mut current_valid = Vec::new();
-
-                    for value in values {
-                        // Check if the current header is full.
-                        if current_total == u8::MAX {
-                            encoding.push(ModelFileAnimationEncoding::Header(ModelFileAnimationEncodingHeader {
-                                total: current_total,
-                                valid: current_valid.len() as u8,
-                            }));
-                            encoding.extend(current_valid.into_iter().map(ModelFileAnimationEncoding::Value));
-                            current_total = 0;
-                            current_valid = Vec::new();
-                        }
+            return Err(error);
+        }
 
-                        // Check if the current header is empty.
-                        if current_valid.is_empty() {
-                            current_total += 1;
-                            current_valid.push(*value);
-                            continue;
-                        }
+        for (_, backup_path) in backups {
+            let _ = fs::remove_file(backup_path);
+        }
 
-                        // Check if the previous value is the same as the current value.
-                        if current_valid[current_valid.len() - 1] == *value {
-                            current_total += 1;
-                            continue;
-                        }
+        self.files.clear();
+        Ok(())
+    }
 
-                        // If the current value is not the same as the previous value and the values length is not equal to the total.
-                        if current_valid.len() as u8 != current_total {
-                            encoding.push(ModelFileAnimationEncoding::Header(ModelFileAnimationEncodingHeader {
-                                total: current_total,
-                                valid: current_valid.len() as u8,
-                            }));
-                            encoding.extend(current_valid.into_iter().map(ModelFileAnimationEncoding::Value));
-
-                            current_total = 1;
-                            current_valid = vec![*value];
-                            continue;
-                        }
+    fn replace_files(&self, backups: &mut Vec<(PathBuf, PathBuf)>, committed_files: &mut Vec<PathBuf>) -> Result<(), IoError> {
+        for path in self.files.iter().map(|(_, path)| path).chain(&self.removed_files) {
+            if !path.exists() {
+                continue;
+            }
 
-                        current_total += 1;
-                        current_valid.push(*value);
-                    }
+            let backup_path = with_added_extension(path, "bak");
+            fs::rename(path, &backup_path)?;
+            backups.push((path.clone(), backup_path));
+        }
 
-                    encoding.push(ModelFileAnimationEncoding::Header(ModelFileAnimationEncodingHeader {
-                        total: current_total,
-                        valid: current_valid.len() as u8,
-                    }));
-                    encoding.extend(current_valid.into_iter().map(ModelFileAnimationEncoding::Value));
+        for (temporary_path, path) in &self.files {
+            fs::rename(temporary_path, path)?;
+            committed_files.push(path.clone());
+        }
+
+        Ok(())
+    }
+}
+
+fn with_added_extension(path: &Path, extension: &str) -> PathBuf {
+    let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(".");
+    file_name.push(extension);
+    path.with_file_name(file_name)
+}
+
+impl Drop for StagedFiles {
+    fn drop(&mut self) {
+        for (temporary_path, _) in &self.files {
+            let _ = fs::remove_file(temporary_path);
+        }
+    }
+}
+
+/// Stages a file to be written to the export path and adds it to the report.
+/// A dry run leaves the file on disk untouched and reports how the size of the file would change instead.
+fn output_file(
+    report: &mut CompileReport,
+    staged_files: &mut StagedFiles,
+    export_path: &Path,
+    name: String,
+    data: &ChunkedBuffer,
+) -> Result<(), FileWriteError> {
+    let path = export_path.join(&name);
+
+    if report.dry_run {
+        if let Ok(metadata) = fs::metadata(&path) {
+            let size_delta = data.len() as i64 - metadata.len() as i64;
+            if size_delta != 0 {
+                report.differences.push(format!(
+                    "{} changes from {} bytes to {} bytes ({:+}).",
+                    name,
+                    metadata.len(),
+                    data.len(),
+                    size_delta
+                ));
+            }
+        }
+    } else if data.matches_file(&path).unwrap_or(false) {
+        // Files that did not change are not written again, so the engine does not reload them.
+        log(format!("{} is unchanged, skipping!", name), LogLevel::Verbose);
+    } else {
+        staged_files.write(path, data)?;
+    }
+
+    report.files.push(ReportFile { name, size: data.len() });
+    Ok(())
+}
+
+/// Creates the prop_data block the engine reads from the model keyvalues to make the prop breakable.
+/// Only the keys that are set are written, every other key is taken from the base in propdata.txt.
+fn create_prop_data_keyvalues(gibs: &ImputedGibs) -> Result<String, FileWriteError> {
+    let mut prop_data = vec![("base", gibs.base.clone())];
+
+    if gibs.health > 0 {
+        prop_data.push(("health", gibs.health.to_string()));
+    }
+
+    if !gibs.damage_table.is_empty() {
+        prop_data.push(("damage_table", gibs.damage_table.clone()));
+    }
+
+    if !gibs.breakable_model.is_empty() {
+        prop_data.push(("breakable_model", gibs.breakable_model.clone()));
+
+        if gibs.breakable_count > 0 {
+            prop_data.push(("breakable_count", gibs.breakable_count.to_string()));
+        }
+
+        prop_data.push(("breakable_skin", gibs.breakable_skin.to_string()));
+    }
+
+    let mut keyvalues = String::from("mdlkeyvalue\n{\n\tprop_data\n\t{\n");
+    for (key, value) in prop_data {
+        check_keyvalue(key, &value)?;
+        keyvalues.push_str(&format!("\t\t\"{}\" \"{}\"\n", key, value));
+    }
+    keyvalues.push_str("\t}\n}\n");
+
+    Ok(keyvalues)
+}
+
+/// The engine reads keyvalues without escape sequences, so a quote in a value would end its string early.
+fn check_keyvalue(key: &str, value: &str) -> Result<(), FileWriteError> {
+    if value.contains('"') {
+        return Err(FileWriteError::KeyvalueHasQuote(key.to_string(), value.to_string()));
+    }
+
+    Ok(())
+}
+
+/// The physics engine works in meters with Z pointing down, while models are in inches with Z pointing up.
+const METERS_PER_INCH: f64 = 0.0254;
+
+fn convert_to_physics_space(point: Vector3) -> Vector3 {
+    Vector3::new(point.x, -point.z, point.y) * METERS_PER_INCH
+}
+
+/// Converts the solids into the space of the physics engine and describes them and their constraints in the keyvalues.
+/// The models a breakable prop breaks into are also listed in the keyvalues, as the engine reads them from the collision model.
+fn create_physics_file(physics_data: ProcessedPhysicsData, surface_property: &str, gibs: Option<&ImputedGibs>) -> Result<PhysicsFileHeader, FileWriteError> {
+    let mut phy_header = PhysicsFileHeader::default();
+    let root_name = physics_data
+        .solids
+        .iter()
+        .enumerate()
+        .find(|(solid_index, _)| !physics_data.constraints.iter().any(|constraint| constraint.child == *solid_index))
+        .map(|(_, solid)| solid.name.clone())
+        .unwrap_or_default();
+
+    for (solid_index, solid) in physics_data.solids.iter().enumerate() {
+        phy_header.keyvalues.push_str(&format!(
+            "solid {{\n\"index\" \"{}\"\n\"name\" \"{}\"\n\"mass\" \"{:.6}\"\n\"surfaceprop\" \"{}\"\n",
+            solid_index, solid.name, solid.mass, surface_property
+        ));
+        phy_header.keyvalues.push_str(&format!(
+            "\"damping\" \"0.000000\"\n\"rotdamping\" \"0.000000\"\n\"inertia\" \"1.000000\"\n\"volume\" \"{:.6}\"\n}}\n",
+            solid.volume()
+        ));
+
+        let ledges = solid
+            .hulls
+            .iter()
+            .map(|hull| PhysicsFileLedge {
+                points: hull.points.iter().map(|point| convert_to_physics_space(*point)).collect(),
+                triangles: hull.triangles.clone(),
+            })
+            .collect::<Vec<_>>();
+
+        // The inertia and drag are taken from the box around the solid, the engine only uses them as a starting point.
+        let size = BoundingBox::from_points(ledges.iter().flat_map(|ledge| &ledge.points)).size();
+        phy_header.solids.push(PhysicsFileSolid {
+            ledges,
+            mass_center: convert_to_physics_space(solid.center_of_mass()),
+            rotation_inertia: Vector3::new(
+                size.y * size.y + size.z * size.z,
+                size.x * size.x + size.z * size.z,
+                size.x * size.x + size.y * size.y,
+            ) / 12.0,
+            drag_axis_areas: Vector3::new(size.y * size.z, size.x * size.z, size.x * size.y),
+        });
+    }
+
+    for constraint in &physics_data.constraints {
+        phy_header.keyvalues.push_str(&format!(
+            "ragdollconstraint {{\n\"parent\" \"{}\"\n\"child\" \"{}\"\n",
+            constraint.parent, constraint.child
+        ));
+        for (axis, limit) in ["x", "y", "z"].into_iter().zip(constraint.limits) {
+            phy_header.keyvalues.push_str(&format!(
+                "\"{0}min\" \"{1:.6}\"\n\"{0}max\" \"{2:.6}\"\n\"{0}friction\" \"{3:.6}\"\n",
+                axis, limit.minimum, limit.maximum, limit.friction
+            ));
+        }
+        phy_header.keyvalues.push_str("}\n");
+    }
+
+    // The model is concave when it is made of more than one convex piece.
+    let is_concave = physics_data.solids.len() > 1 || physics_data.solids.iter().any(|solid| solid.hulls.len() > 1);
+    phy_header.keyvalues.push_str(&format!(
+        "editparams {{\n\"rootname\" \"{}\"\n\"totalmass\" \"{:.6}\"\n\"concave\" \"{}\"\n}}\n",
+        root_name, physics_data.total_mass, is_concave as i32
+    ));
+
+    for gib_model in gibs.iter().flat_map(|gibs| &gibs.gib_models) {
+        // The engine accepts forward slashes on every platform, so the path is written with them.
+        let model = gib_model.model.replace('\\', "/");
+        check_keyvalue("model", &model)?;
+        phy_header
+            .keyvalues
+            .push_str(&format!("break {{\n\"model\" \"{}\"\n\"fadetime\" \"{:.6}\"\n}}\n", model, gib_model.fade_time));
+    }
+
+    Ok(phy_header)
+}
+
+fn create_header_flags(model_flags: &ImputedModelFlags) -> ModelFileHeaderFlags {
+    let mut flags = ModelFileHeaderFlags::empty();
+    flags.set(ModelFileHeaderFlags::STATIC_PROP, model_flags.static_prop);
+    flags.set(ModelFileHeaderFlags::FORCE_OPAQUE, model_flags.force_opaque);
+    flags.set(ModelFileHeaderFlags::FORCE_TRANSLUCENT, model_flags.translucent_two_pass);
+    flags.set(ModelFileHeaderFlags::AMBIENT_BOOST, model_flags.ambient_boost);
+    flags.set(ModelFileHeaderFlags::DO_NOT_CAST_SHADOWS, model_flags.do_not_cast_shadows);
+    flags.set(ModelFileHeaderFlags::CAST_TEXTURE_SHADOWS, model_flags.cast_texture_shadows);
+    flags
+}
+
+/// Compares the header flags and contents against the model data and warns about likely misconfigurations.
+/// The issues that are fixed by changing the model flags carry the changes, so they can be applied from the report.
+fn audit_header_flags(header: &ModelFileHeader, input: &ImputedCompilationData, has_collision: bool) -> Vec<ReportFlagIssue> {
+    let mut issues = Vec::new();
+    let mut issue = |message: String, fix: &str, flag_changes: &[(&str, bool)]| {
+        log(format!("Flag Audit: {} Fix: {}", message, fix), LogLevel::Warn);
+        issues.push(ReportFlagIssue {
+            message,
+            fix: fix.to_string(),
+            flag_changes: flag_changes.iter().map(|(flag, enabled)| (flag.to_string(), *enabled)).collect(),
+        });
+    };
+
+    let is_animated = header.bones.len() > 1 || header.local_animation_descriptions.iter().any(|animation| animation.frame_count > 1);
+    let flags = header.flags;
+
+    if !is_animated && !flags.contains(ModelFileHeaderFlags::STATIC_PROP) {
+        issue(
+            String::from("Model has a single bone and no animation but is not a static prop."),
+            "Enable Static Prop.",
+            &[("static_prop", true)],
+        );
+    }
+
+    if is_animated && flags.contains(ModelFileHeaderFlags::STATIC_PROP) {
+        issue(
+            String::from("Model is a static prop but has multiple bones or animation, the engine will not animate it."),
+            "Disable Static Prop.",
+            &[("static_prop", false)],
+        );
+    }
+
+    if flags.contains(ModelFileHeaderFlags::STATIC_PROP) && !has_collision {
+        issue(
+            String::from("Model is a static prop but has no collision model, players and objects pass through it."),
+            "Add a collision solid in the physics menu.",
+            &[],
+        );
+    }
+
+    if input.gibs.is_some() && !has_collision {
+        issue(
+            String::from("Model breaks into gibs but has no collision model, it can not be hit or broken."),
+            "Add a collision solid in the physics menu.",
+            &[],
+        );
+    }
+
+    if flags.contains(ModelFileHeaderFlags::FORCE_OPAQUE | ModelFileHeaderFlags::FORCE_TRANSLUCENT) {
+        issue(
+            String::from("Model is forced both opaque and translucent."),
+            "Disable Translucent Two Pass.",
+            &[("translucent_two_pass", false)],
+        );
+    }
+
+    // Materials are only checked when the export path is in a game, materials packed in VPK files are seen as opaque.
+    if let Some(materials_directory) = find_materials_directory(Path::new(&input.export_path)) {
+        let translucent_materials = header
+            .materials
+            .iter()
+            .map(|material| &material.name)
+            .filter(|material| material_is_translucent(&materials_directory, &header.material_paths, material))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        if !translucent_materials.is_empty() && flags.contains(ModelFileHeaderFlags::FORCE_OPAQUE) {
+            issue(
+                format!(
+                    "Model is forced opaque but has translucent materials {}, they are drawn opaque.",
+                    translucent_materials.join(", ")
+                ),
+                "Disable Force Opaque.",
+                &[("force_opaque", false)],
+            );
+        }
+
+        if translucent_materials.is_empty() && flags.contains(ModelFileHeaderFlags::FORCE_TRANSLUCENT) {
+            issue(
+                String::from("Model draws its opaque parts in a separate pass but has no translucent materials."),
+                "Disable Translucent Two Pass.",
+                &[("translucent_two_pass", false)],
+            );
+        }
+
+        if !translucent_materials.is_empty()
+            && translucent_materials.len() < header.materials.len()
+            && !flags.intersects(ModelFileHeaderFlags::FORCE_OPAQUE | ModelFileHeaderFlags::FORCE_TRANSLUCENT)
+        {
+            issue(
+                String::from("Model mixes opaque and translucent materials, the opaque parts are sorted with the translucent parts."),
+                "Enable Translucent Two Pass.",
+                &[("translucent_two_pass", true)],
+            );
+        }
+    }
+
+    if flags.intersects(ModelFileHeaderFlags::HAS_SHADOW_LOD | ModelFileHeaderFlags::USE_SHADOW_LOD_MATERIALS) {
+        issue(
+            String::from("Model uses shadow LOD flags but has no shadow LOD."),
+            "Disable HAS_SHADOW_LOD and USE_SHADOW_LOD_MATERIALS.",
+            &[],
+        );
+    }
+
+    if header.contents.contains(ModelFileHeaderContents::GRATE) && header.contents.contains(ModelFileHeaderContents::SOLID) {
+        issue(
+            String::from("Model contents are both solid and grate, grates let bullets and visibility through."),
+            "Remove SOLID or GRATE from contents.",
+            &[],
+        );
+    }
+
+    if header.contents.contains(ModelFileHeaderContents::LADDER) && !header.contents.contains(ModelFileHeaderContents::SOLID) {
+        issue(
+            String::from("Model contents are a ladder but not solid, players can not climb it."),
+            "Add SOLID to contents.",
+            &[],
+        );
+    }
+
+    issues
+}
+
+fn write_animations(
+    animations: ProcessedAnimationData,
+    rotation_encoding: ImputedRotationEncoding,
+    header: &mut ModelFileHeader,
+) -> Result<(), FileWriteError> {
+    // Encoding is independent for each animation, so the animations are encoded in parallel and then added in order.
+    let completed_animations = AtomicUsize::new(0);
+    let animation_count = animations.processed_animations.len();
+    header.local_animation_descriptions = animations
+        .processed_animations
+        .into_par_iter()
+        .map(|processed_animation| {
+            let animation_description = encode_animation(processed_animation, &animations.animation_scales, rotation_encoding);
+            let completed = completed_animations.fetch_add(1, Ordering::Relaxed) + 1;
+            progress("Encoding Animations", completed, animation_count);
+            animation_description
+        })
+        .collect::<Result<_, _>>()?;
 
-                    encoding
+    Ok(())
+}
+
+/// Scales the animation values to integers and run length encodes them for each section of the animation.
+fn encode_animation(
+    processed_animation: ProcessedAnimation,
+    animation_scales: &[(Vector3, Vector3)],
+    rotation_encoding: ImputedRotationEncoding,
+) -> Result<ModelFileAnimationDescription, FileWriteError> {
+    let mut animation_description = ModelFileAnimationDescription {
+        name: processed_animation.name,
+        fps: processed_animation.fps as f32,
+        frame_count: processed_animation.frame_count as i32,
+        frames_per_section: processed_animation.frames_per_section as i32,
+        animation_sections: Vec::with_capacity(processed_animation.sections.len()),
+        ..Default::default()
+    };
+
+    if processed_animation.is_delta {
+        animation_description.flags.insert(ModelFileAnimationDescriptionFlags::DELTA);
+    }
+
+    for processed_movement in processed_animation.movements {
+        let mut flags = ModelFileMovementFlags::empty();
+        for (axis, flag) in [
+            ModelFileMovementFlags::LINEAR_X,
+            ModelFileMovementFlags::LINEAR_Y,
+            ModelFileMovementFlags::LINEAR_Z,
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            flags.set(flag, processed_movement.axes[axis]);
+        }
+
+        animation_description.movements.push(ModelFileMovement {
+            end_frame: processed_movement.end_frame as i32,
+            flags,
+            start_velocity: processed_movement.start_velocity as f32,
+            end_velocity: processed_movement.end_velocity as f32,
+            angle: 0.0,
+            direction: processed_movement.direction,
+            position: processed_movement.position,
+        });
+    }
+
+    for processed_rule in processed_animation.inverse_kinematic_rules {
+        let (rule_type, bone) = match processed_rule.rule {
+            ProcessedInverseKinematicRuleType::Touch { bone } => (1, bone as i32),
+            ProcessedInverseKinematicRuleType::Footstep => (3, -1),
+            ProcessedInverseKinematicRuleType::Release => (4, -1),
+        };
+
+        animation_description.inverse_kinematic_rules.push(ModelFileInverseKinematicRule {
+            rule_type,
+            chain: processed_rule.chain as i32,
+            bone,
+            slot: processed_rule.chain as i32,
+            height: processed_rule.height as f32,
+            radius: processed_rule.radius as f32,
+            start: processed_rule.start as f32,
+            peak: processed_rule.peak as f32,
+            tail: processed_rule.tail as f32,
+            end: processed_rule.end as f32,
+            contact: processed_rule.contact as f32,
+            start_frame: processed_rule.start_frame as i32,
+            compressed_error: encode_inverse_kinematic_targets(&processed_rule.targets),
+            ..Default::default()
+        });
+    }
+
+    for (section_index, mut section) in processed_animation.sections.into_iter().enumerate() {
+        let mut animation_section = ModelFileAnimationSection {
+            animation_data: Vec::with_capacity(section.len()),
+            ..Default::default()
+        };
+
+        section.sort_by(|to, from| to.bone.cmp(&from.bone));
+
+        for animation_bone_data in section {
+            let scale = animation_scales[animation_bone_data.bone as usize].0;
+            let mut scaled_position_axis = [
+                Vec::with_capacity(animation_bone_data.position.len()),
+                Vec::with_capacity(animation_bone_data.position.len()),
+                Vec::with_capacity(animation_bone_data.position.len()),
+            ];
+            for position in &animation_bone_data.position {
+                for axis in 0..3 {
+                    scaled_position_axis[axis].push(if position[axis].abs() > FLOAT_TOLERANCE {
+                        (position[axis] / scale[axis]) as i16
+                    } else {
+                        0
+                    });
                 }
+            }
 
-                let encoded_position_axis = [
-                    encode_run_length(&scaled_position_axis[0]),
-                    encode_run_length(&scaled_position_axis[1]),
-                    encode_run_length(&scaled_position_axis[2]),
-                ];
-
-                let mut position = None;
-
-                // Handle single frame
-                if encoded_position_axis[0].len() == 2 && encoded_position_axis[1].len() == 2 && encoded_position_axis[2].len() == 2 {
-                    match (&encoded_position_axis[0][1], &encoded_position_axis[1][1], &encoded_position_axis[2][1]) {
-                        (ModelFileAnimationEncoding::Value(x), ModelFileAnimationEncoding::Value(y), ModelFileAnimationEncoding::Value(z)) => {
-                            if *x != 0 || *y != 0 || *z != 0 {
-                                position = Some(ModelFileAnimationData::Single(animation_bone_data.position[0]));
-                            }
-                        }
-                        _ => {
-                            unreachable!("All the values should be ModelFileAnimationEncoding::Value");
-                        }
-                    }
+            let scale = animation_scales[animation_bone_data.bone as usize].1;
+            let mut scaled_rotation_axis = [
+                Vec::with_capacity(animation_bone_data.rotation.len()),
+                Vec::with_capacity(animation_bone_data.rotation.len()),
+                Vec::with_capacity(animation_bone_data.rotation.len()),
+            ];
+            for rotation in &animation_bone_data.rotation {
+                for axis in 0..3 {
+                    scaled_rotation_axis[axis].push(if rotation[axis].abs() > FLOAT_TOLERANCE {
+                        (rotation[axis] / scale[axis]) as i16
+                    } else {
+                        0
+                    });
                 }
+            }
 
-                // Handle multiple frame
-                if encoded_position_axis[0].len() > 2 || encoded_position_axis[1].len() > 2 || encoded_position_axis[2].len() > 2 {
-                    let mut animation_axis = ModelFileAnimationValue::default();
+            let encoded_position_axis = [
+                encode_run_length(&scaled_position_axis[0]),
+                encode_run_length(&scaled_position_axis[1]),
+                encode_run_length(&scaled_position_axis[2]),
+            ];
 
-                    let [x_encoded, y_encoded, z_encoded] = encoded_position_axis;
+            let mut position = None;
 
-                    if x_encoded.len() > 2 {
-                        animation_axis.values[0] = Some(x_encoded);
+            // Handle single frame
+            if encoded_position_axis[0].len() == 2 && encoded_position_axis[1].len() == 2 && encoded_position_axis[2].len() == 2 {
+                match (&encoded_position_axis[0][1], &encoded_position_axis[1][1], &encoded_position_axis[2][1]) {
+                    (ModelFileAnimationEncoding::Value(x), ModelFileAnimationEncoding::Value(y), ModelFileAnimationEncoding::Value(z)) => {
+                        if *x != 0 || *y != 0 || *z != 0 {
+                            position = Some(ModelFileAnimationData::Single(animation_bone_data.position[0]));
+                        }
                     }
-
-                    if y_encoded.len() > 2 {
-                        animation_axis.values[1] = Some(y_encoded);
+                    _ => {
+                        unreachable!("All the values should be ModelFileAnimationEncoding::Value");
                     }
+                }
+            }
 
-                    if z_encoded.len() > 2 {
-                        animation_axis.values[2] = Some(z_encoded);
-                    }
+            // Handle multiple frame
+            if encoded_position_axis[0].len() > 2 || encoded_position_axis[1].len() > 2 || encoded_position_axis[2].len() > 2 {
+                let mut animation_axis = ModelFileAnimationValue::default();
 
-                    position = Some(ModelFileAnimationData::Array(animation_axis));
+                let [x_encoded, y_encoded, z_encoded] = encoded_position_axis;
+
+                if x_encoded.len() > 2 {
+                    animation_axis.values[0] = Some(x_encoded);
                 }
 
-                let encoded_rotation_axis = [
-                    encode_run_length(&scaled_rotation_axis[0]),
-                    encode_run_length(&scaled_rotation_axis[1]),
-                    encode_run_length(&scaled_rotation_axis[2]),
-                ];
-
-                let mut rotation = None;
-
-                // Handle single frame
-                if encoded_rotation_axis[0].len() == 2 && encoded_rotation_axis[1].len() == 2 && encoded_rotation_axis[2].len() == 2 {
-                    match (&encoded_rotation_axis[0][1], &encoded_rotation_axis[1][1], &encoded_rotation_axis[2][1]) {
-                        (ModelFileAnimationEncoding::Value(x), ModelFileAnimationEncoding::Value(y), ModelFileAnimationEncoding::Value(z)) => {
-                            if *x != 0 || *y != 0 || *z != 0 {
-                                rotation = Some(ModelFileAnimationData::Single(animation_bone_data.rotation[0]));
-                            }
-                        }
-                        _ => {
-                            unreachable!("All the values should be ModelFileAnimationEncoding::Value");
-                        }
-                    }
+                if y_encoded.len() > 2 {
+                    animation_axis.values[1] = Some(y_encoded);
                 }
 
-                // Handle multiple frame
-                if encoded_rotation_axis[0].len() > 2 || encoded_rotation_axis[1].len() > 2 || encoded_rotation_axis[2].len() > 2 {
-                    let mut animation_axis = ModelFileAnimationValue::default();
+                if z_encoded.len() > 2 {
+                    animation_axis.values[2] = Some(z_encoded);
+                }
 
-                    let [x_encoded, y_encoded, z_encoded] = encoded_rotation_axis;
+                position = Some(ModelFileAnimationData::Array(animation_axis));
+            }
 
-                    if x_encoded.len() > 2 {
-                        animation_axis.values[0] = Some(x_encoded);
-                    }
+            let encoded_rotation_axis = [
+                encode_run_length(&scaled_rotation_axis[0]),
+                encode_run_length(&scaled_rotation_axis[1]),
+                encode_run_length(&scaled_rotation_axis[2]),
+            ];
 
-                    if y_encoded.len() > 2 {
-                        animation_axis.values[1] = Some(y_encoded);
-                    }
+            let mut rotation = None;
 
-                    if z_encoded.len() > 2 {
-                        animation_axis.values[2] = Some(z_encoded);
+            // Handle single frame
+            if encoded_rotation_axis[0].len() == 2 && encoded_rotation_axis[1].len() == 2 && encoded_rotation_axis[2].len() == 2 {
+                match (&encoded_rotation_axis[0][1], &encoded_rotation_axis[1][1], &encoded_rotation_axis[2][1]) {
+                    (ModelFileAnimationEncoding::Value(x), ModelFileAnimationEncoding::Value(y), ModelFileAnimationEncoding::Value(z)) => {
+                        if *x != 0 || *y != 0 || *z != 0 {
+                            rotation = Some(ModelFileAnimationData::Single(animation_bone_data.rotation[0]));
+                        }
+                    }
+                    _ => {
+                        unreachable!("All the values should be ModelFileAnimationEncoding::Value");
                     }
+                }
+            }
+
+            // Handle multiple frame
+            if encoded_rotation_axis[0].len() > 2 || encoded_rotation_axis[1].len() > 2 || encoded_rotation_axis[2].len() > 2 {
+                let mut animation_axis = ModelFileAnimationValue::default();
+
+                let [x_encoded, y_encoded, z_encoded] = encoded_rotation_axis;
 
-                    rotation = Some(ModelFileAnimationData::Array(animation_axis));
+                if x_encoded.len() > 2 {
+                    animation_axis.values[0] = Some(x_encoded);
                 }
 
-                if position.is_none() && rotation.is_none() {
-                    continue;
+                if y_encoded.len() > 2 {
+                    animation_axis.values[1] = Some(y_encoded);
                 }
 
-                animation_section.animation_data.push(ModelFileAnimation {
-                    bone: animation_bone_data.bone,
-                    position,
-                    rotation,
-                    ..Default::default()
-                });
+                if z_encoded.len() > 2 {
+                    animation_axis.values[2] = Some(z_encoded);
+                }
+
+                rotation = Some(ModelFileAnimationData::Array(animation_axis));
             }
 
-            animation_description.animation_sections.push(animation_section);
+            if position.is_none() && rotation.is_none() {
+                continue;
+            }
+
+            let animation = ModelFileAnimation {
+                delta: processed_animation.is_delta,
+                bone: animation_bone_data.bone,
+                position,
+                rotation,
+                compact_rotation: matches!(rotation_encoding, ImputedRotationEncoding::Quaternion48),
+                ..Default::default()
+            };
+
+            // The data of each bone is found from the bone before it with a short offset, which limits how large it can be.
+            if animation.size() > i16::MAX as usize {
+                return Err(FileWriteError::AnimationSectionToLarge(animation_description.name, section_index));
+            }
+
+            animation_section.animation_data.push(animation);
         }
 
-        header.local_animation_descriptions.push(animation_description);
+        animation_description.animation_sections.push(animation_section);
     }
+
+    Ok(animation_description)
+}
+
+/// Compresses the targets of an inverse kinematic rule, each channel is scaled to fit its largest value.
+fn encode_inverse_kinematic_targets(targets: &[(Vector3, Angles)]) -> Option<ModelFileCompressedInverseKinematicError> {
+    if targets.is_empty() {
+        return None;
+    }
+
+    let mut compressed_error = ModelFileCompressedInverseKinematicError::default();
+
+    for channel in 0..6 {
+        let values = targets
+            .iter()
+            .map(|(position, rotation)| if channel < 3 { position[channel] } else { rotation[channel - 3] })
+            .collect::<Vec<_>>();
+
+        let maximum = values.iter().fold(0.0, |maximum: f64, value| maximum.max(value.abs()));
+        if maximum <= FLOAT_TOLERANCE {
+            continue;
+        }
+
+        let scale = maximum / ((i16::MAX as f64) + 1.0);
+        compressed_error.scales[channel] = scale as f32;
+        compressed_error.values[channel] = Some(encode_run_length(&values.iter().map(|value| (value / scale) as i16).collect::<Vec<_>>()));
+    }
+
+    Some(compressed_error)
+}
+
+/// Compresses the values of a channel into runs, repeated values at the end of a run are not stored.
+fn encode_run_length(values: &[i16]) -> Vec<ModelFileAnimationEncoding> {
+    let mut encoding = Vec::new();
+
+    let mut current_total = 0;
+    let mut current_valid = Vec::new();
+
+    for value in values {
+        // Check if the current header is full.
+        if current_total == u8::MAX {
+            encoding.push(ModelFileAnimationEncoding::Header(ModelFileAnimationEncodingHeader {
+                total: current_total,
+                valid: current_valid.len() as u8,
+            }));
+            encoding.extend(current_valid.into_iter().map(ModelFileAnimationEncoding::Value));
+            current_total = 0;
+            current_valid = Vec::new();
+        }
+
+        // Check if the current header is empty.
+        if current_valid.is_empty() {
+            current_total += 1;
+            current_valid.push(*value);
+            continue;
+        }
+
+        // Check if the previous value is the same as the current value.
+        if current_valid[current_valid.len() - 1] == *value {
+            current_total += 1;
+            continue;
+        }
+
+        // If the current value is not the same as the previous value and the values length is not equal to the total.
+        if current_valid.len() as u8 != current_total {
+            encoding.push(ModelFileAnimationEncoding::Header(ModelFileAnimationEncodingHeader {
+                total: current_total,
+                valid: current_valid.len() as u8,
+            }));
+            encoding.extend(current_valid.into_iter().map(ModelFileAnimationEncoding::Value));
+
+            current_total = 1;
+            current_valid = vec![*value];
+            continue;
+        }
+
+        current_total += 1;
+        current_valid.push(*value);
+    }
+
+    encoding.push(ModelFileAnimationEncoding::Header(ModelFileAnimationEncodingHeader {
+        total: current_total,
+        valid: current_valid.len() as u8,
+    }));
+    encoding.extend(current_valid.into_iter().map(ModelFileAnimationEncoding::Value));
+
+    encoding
+}
+
+/// Finds the flex description of a flex or adds it, the flex description is bound to its flex controller with a flex rule as lip sync and faces only set the flex controllers.
+fn add_flex_description(mdl_header: &mut ModelFileHeader, flex: String, flex_controller: String) -> usize {
+    let flex_description = match mdl_header.flex_descriptions.iter().position(|flex_description| flex_description.name == flex) {
+        Some(index) => index,
+        None => {
+            mdl_header.flex_descriptions.push(ModelFileFlexDescription {
+                name: flex,
+                ..Default::default()
+            });
+            mdl_header.flex_descriptions.len() - 1
+        }
+    };
+
+    let flex_controller = match mdl_header
+        .flex_controllers
+        .iter()
+        .position(|existing_flex_controller| existing_flex_controller.name == flex_controller)
+    {
+        Some(index) => index,
+        None => {
+            mdl_header.flex_controllers.push(ModelFileFlexController {
+                controller_type: String::from("default"),
+                name: flex_controller,
+                minimum: 0.0,
+                maximum: 1.0,
+                ..Default::default()
+            });
+            mdl_header.flex_controllers.len() - 1
+        }
+    };
+
+    if !mdl_header
+        .flex_rules
+        .iter()
+        .any(|flex_rule| flex_rule.flex_description == flex_description as i32)
+    {
+        mdl_header.flex_rules.push(ModelFileFlexRule {
+            flex_description: flex_description as i32,
+            operations: vec![ModelFileFlexOperation::FetchController(flex_controller as i32)],
+            ..Default::default()
+        });
+    }
+
+    flex_description
 }
 
 fn write_body_parts(
     processed_body_parts: Vec<ProcessedBodyPart>,
+    flex_descriptions: &[i32],
     header: &mut ModelFileHeader,
     mesh_header: &mut MeshFileHeader,
     vertex_header: &mut VertexFileHeader,
@@ -591,8 +1747,8 @@ fn write_body_parts(
                 name: processed_model.name,
                 meshes: Vec::with_capacity(processed_model.meshes.len()),
                 vertex_count: processed_model.meshes.iter().map(|mesh| mesh.vertex_data.len()).sum::<usize>() as i32,
-                vertex_offset: (vertex_header.vertices.len() * 48) as i32, // FIXME: Add a check for this.
-                tangent_offset: (vertex_header.tangents.len() * 16) as i32, // FIXME: Add a check for this.
+                vertex_offset: (vertex_header.vertices.len() * VERTEX_SIZE) as i32, // FIXME: Add a check for this.
+                tangent_offset: (vertex_header.tangents.len() * TANGENT_SIZE) as i32, // FIXME: Add a check for this.
                 ..Default::default()
             };
 
@@ -603,6 +1759,24 @@ fn write_body_parts(
             for processed_mesh in processed_model.meshes {
                 let model_mesh = ModelFileMesh {
                     material: processed_mesh.material,
+                    flexes: processed_mesh
+                        .flexes
+                        .into_iter()
+                        .map(|processed_flex| ModelFileFlex {
+                            flex_description: flex_descriptions[processed_flex.flex],
+                            targets: [0.0, 1.0, 10.0, 11.0],
+                            vertex_animations: processed_flex
+                                .vertices
+                                .into_iter()
+                                .map(|processed_flex_vertex| ModelFileVertexAnimation {
+                                    index: processed_flex_vertex.vertex.try_into().unwrap(),
+                                    position_delta: processed_flex_vertex.position,
+                                    normal_delta: processed_flex_vertex.normal,
+                                })
+                                .collect(),
+                            ..Default::default()
+                        })
+                        .collect(),
                     vertex_count: processed_mesh.vertex_data.len() as i32,
                     vertex_offset: vertex_count as i32,
                     mesh_identifier: mesh_id,
@@ -614,8 +1788,6 @@ fn write_body_parts(
                 vertex_count += processed_mesh.vertex_data.len();
 
                 for processed_vertex in processed_mesh.vertex_data {
-                    // let mut uv_fix = vertex.texture_coordinate; // FIXME: This should be in the mesh processing stage.
-                    // uv_fix.y = 1.0 - uv_fix.y;
                     vertex_header.vertices.push(VertexFileVertex {
                         weights: processed_vertex.weights,
                         bones: processed_vertex.bones,
@@ -631,7 +1803,13 @@ fn write_body_parts(
 
                 for processed_strip_group in processed_mesh.strip_groups {
                     let mut mesh_strip_group_header = MeshFileStripGroupHeader {
-                        flags: MeshFileStripGroupHeaderFlags::IS_HARDWARE_SKINNED,
+                        flags: if processed_strip_group.is_flexed {
+                            MeshFileStripGroupHeaderFlags::IS_HARDWARE_SKINNED
+                                | MeshFileStripGroupHeaderFlags::IS_FLEXED
+                                | MeshFileStripGroupHeaderFlags::IS_DELTA_FLEXED
+                        } else {
+                            MeshFileStripGroupHeaderFlags::IS_HARDWARE_SKINNED
+                        },
                         indices: processed_strip_group.indices,
                         ..Default::default()
                     };
@@ -693,3 +1871,54 @@ fn write_body_parts(
     mesh_header.material_replacement_lists.push(MeshFileMaterialReplacementListHeader::default());
     vertex_header.lod_vertex_count = [vertex_header.vertices.len() as i32; MAX_LOD_COUNT];
 }
+
+fn convert_procedural(procedural: ProcessedProcedural) -> ModelFileBoneProceduralType {
+    match procedural {
+        ProcessedProcedural::AxisInterpolation {
+            control,
+            axis,
+            positions,
+            rotations,
+        } => ModelFileBoneProceduralType::AxisInterpolation(Box::new(ModelFileAxisInterpolationBone {
+            control: control as i32,
+            axis: axis as i32,
+            positions: *positions,
+            quaternions: *rotations,
+        })),
+        ProcessedProcedural::QuaternionInterpolation { control, triggers } => {
+            ModelFileBoneProceduralType::QuaternionInterpolation(ModelFileQuaternionInterpolationBone {
+                write_base: 0,
+                control: control as i32,
+                triggers: triggers
+                    .into_iter()
+                    .map(|trigger| ModelFileQuaternionInterpolationTrigger {
+                        inverse_tolerance: trigger.inverse_tolerance as f32,
+                        trigger: trigger.trigger,
+                        position: trigger.position,
+                        quaternion: trigger.rotation,
+                    })
+                    .collect(),
+                trigger_offset: 0,
+            })
+        }
+        ProcessedProcedural::Jiggle(jiggle) => {
+            let mut flags = ModelFileJiggleBoneFlags::IS_FLEXIBLE;
+            if jiggle.angle_limit.is_some() {
+                flags.insert(ModelFileJiggleBoneFlags::HAS_ANGLE_CONSTRAINT);
+            }
+
+            ModelFileBoneProceduralType::Jiggle(Box::new(ModelFileJiggleBone {
+                flags,
+                length: jiggle.length as f32,
+                tip_mass: jiggle.tip_mass as f32,
+                yaw_stiffness: jiggle.stiffness as f32,
+                yaw_damping: jiggle.damping as f32,
+                pitch_stiffness: jiggle.stiffness as f32,
+                pitch_damping: jiggle.damping as f32,
+                along_stiffness: jiggle.stiffness as f32,
+                along_damping: jiggle.damping as f32,
+                angle_limit: jiggle.angle_limit.unwrap_or_default() as f32,
+            }))
+        }
+    }
+}
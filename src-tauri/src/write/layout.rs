@@ -0,0 +1,30 @@
+//! The identifiers and the sizes of the fixed size structures of the compiled files.
+//! The writers check every structure they write against its size, and the readers step through arrays of the structures with them.
+
+pub const MODEL_FILE_IDENTIFIER: i32 = (84 << 24) + (83 << 16) + (68 << 8) + 73;
+pub const VERTEX_FILE_IDENTIFIER: i32 = (86 << 24) + (83 << 16) + (68 << 8) + 73;
+
+pub const BONE_SIZE: usize = 216;
+pub const ATTACHMENT_SIZE: usize = 92;
+pub const HITBOX_SET_SIZE: usize = 12;
+pub const HITBOX_SIZE: usize = 68;
+pub const ANIMATION_DESCRIPTION_SIZE: usize = 100;
+pub const SEQUENCE_DESCRIPTION_SIZE: usize = 212;
+pub const MATERIAL_SIZE: usize = 64;
+pub const BODY_PART_SIZE: usize = 16;
+pub const MODEL_SIZE: usize = 148;
+pub const MESH_SIZE: usize = 116;
+pub const FLEX_SIZE: usize = 60;
+pub const VERTEX_ANIMATION_SIZE: usize = 16;
+
+pub const VERTEX_SIZE: usize = 48;
+pub const TANGENT_SIZE: usize = 16;
+pub const VERTEX_FIXUP_SIZE: usize = 12;
+
+pub const MESH_BODY_PART_HEADER_SIZE: usize = 8;
+pub const MESH_MODEL_HEADER_SIZE: usize = 8;
+pub const MESH_MODEL_LOD_HEADER_SIZE: usize = 12;
+pub const MESH_MESH_HEADER_SIZE: usize = 9;
+pub const MESH_STRIP_GROUP_HEADER_SIZE: usize = 25;
+pub const MESH_STRIP_HEADER_SIZE: usize = 27;
+pub const MESH_VERTEX_SIZE: usize = 9;
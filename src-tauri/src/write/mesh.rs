@@ -1,4 +1,10 @@
-use super::{FileWriteError, FileWriter, WriteToWriter};
+use super::{
+    layout::{
+        MESH_BODY_PART_HEADER_SIZE, MESH_MESH_HEADER_SIZE, MESH_MODEL_HEADER_SIZE, MESH_MODEL_LOD_HEADER_SIZE, MESH_STRIP_GROUP_HEADER_SIZE,
+        MESH_STRIP_HEADER_SIZE, MESH_VERTEX_SIZE,
+    },
+    FileWriteError, FileWriter, WriteToWriter,
+};
 use bitflags::bitflags;
 
 #[derive(Debug, Default)]
@@ -155,6 +161,23 @@ impl WriteToWriter for MeshFileHeader {
     }
 }
 
+impl MeshFileHeader {
+    /// Marks every strip group as skinned by the hardware or by the CPU.
+    pub fn set_hardware_skinned(&mut self, hardware_skinned: bool) {
+        for body_part in &mut self.body_parts {
+            for model in &mut body_part.models {
+                for model_lod in &mut model.model_lods {
+                    for mesh in &mut model_lod.meshes {
+                        for strip_group in &mut mesh.strip_groups {
+                            strip_group.flags.set(MeshFileStripGroupHeaderFlags::IS_HARDWARE_SKINNED, hardware_skinned);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
 #[derive(Debug, Default)]
 pub struct MeshFileMaterialReplacementListHeader {
     pub write_base: usize,
@@ -205,6 +228,11 @@ impl WriteToWriter for MeshFileBodyPartHeader {
         self.write_base = writer.data.len();
         writer.write_array_size(self.models.len())?;
         self.model_offset = writer.write_integer_index();
+        debug_assert_eq!(
+            writer.data.len() - self.write_base,
+            MESH_BODY_PART_HEADER_SIZE,
+            "Body Part Header Does Not Match Its Size!"
+        );
         Ok(())
     }
 }
@@ -227,6 +255,11 @@ impl WriteToWriter for MeshFileModelHeader {
         self.write_base = writer.data.len();
         writer.write_array_size(self.model_lods.len())?;
         self.model_lod_offset = writer.write_integer_index();
+        debug_assert_eq!(
+            writer.data.len() - self.write_base,
+            MESH_MODEL_HEADER_SIZE,
+            "Model Header Does Not Match Its Size!"
+        );
         Ok(())
     }
 }
@@ -251,6 +284,11 @@ impl WriteToWriter for MeshFileModelLODHeader {
         writer.write_array_size(self.meshes.len())?;
         self.mesh_offset = writer.write_integer_index();
         writer.write_float(self.switch_point);
+        debug_assert_eq!(
+            writer.data.len() - self.write_base,
+            MESH_MODEL_LOD_HEADER_SIZE,
+            "Model LOD Header Does Not Match Its Size!"
+        );
         Ok(())
     }
 }
@@ -275,6 +313,11 @@ impl WriteToWriter for MeshFileMeshHeader {
         writer.write_array_size(self.strip_groups.len())?;
         self.strip_group_offset = writer.write_integer_index();
         writer.write_unsigned_byte(self.flags.bits());
+        debug_assert_eq!(
+            writer.data.len() - self.write_base,
+            MESH_MESH_HEADER_SIZE,
+            "Mesh Header Does Not Match Its Size!"
+        );
         Ok(())
     }
 }
@@ -315,6 +358,11 @@ impl WriteToWriter for MeshFileStripGroupHeader {
         writer.write_array_size(self.strips.len())?;
         self.strip_offset = writer.write_integer_index();
         writer.write_unsigned_byte(self.flags.bits());
+        debug_assert_eq!(
+            writer.data.len() - self.write_base,
+            MESH_STRIP_GROUP_HEADER_SIZE,
+            "Strip Group Header Does Not Match Its Size!"
+        );
         Ok(())
     }
 }
@@ -351,10 +399,12 @@ pub struct MeshFileVertexHeader {
 
 impl WriteToWriter for MeshFileVertexHeader {
     fn write(&mut self, writer: &mut FileWriter) -> Result<(), FileWriteError> {
+        let write_base = writer.data.len();
         writer.write_unsigned_byte_array(&[0, 1, 2]);
         writer.write_unsigned_byte(self.bone_count);
         writer.write_unsigned_short(self.vertex_index);
         writer.write_unsigned_byte_array(self.bone_weight_bones.as_ref());
+        debug_assert_eq!(writer.data.len() - write_base, MESH_VERTEX_SIZE, "Vertex Does Not Match Its Size!");
         Ok(())
     }
 }
@@ -383,6 +433,11 @@ impl WriteToWriter for MeshFileStripHeader {
         writer.write_unsigned_byte(self.flags.bits());
         writer.write_array_size(self.bone_state_changes.len())?;
         self.bone_state_change_offset = writer.write_integer_index();
+        debug_assert_eq!(
+            writer.data.len() - self.write_base,
+            MESH_STRIP_HEADER_SIZE,
+            "Strip Header Does Not Match Its Size!"
+        );
         Ok(())
     }
 }
@@ -2,7 +2,13 @@ use crate::utilities::mathematics::{Angles, BoundingBox, Matrix4, Quaternion, Ve
 
 use bitflags::bitflags;
 
-use super::{FileWriteError, FileWriter, WriteToWriter};
+use super::{
+    layout::{
+        ANIMATION_DESCRIPTION_SIZE, ATTACHMENT_SIZE, BODY_PART_SIZE, BONE_SIZE, FLEX_SIZE, HITBOX_SET_SIZE, HITBOX_SIZE, MATERIAL_SIZE, MESH_SIZE,
+        MODEL_FILE_IDENTIFIER, MODEL_SIZE, SEQUENCE_DESCRIPTION_SIZE, VERTEX_ANIMATION_SIZE,
+    },
+    FileWriteError, FileWriter, WriteToWriter,
+};
 
 #[derive(Debug)]
 pub struct ModelFileHeader {
@@ -33,22 +39,23 @@ pub struct ModelFileHeader {
     pub material_replacement_offset: usize,
     pub body_parts: Vec<ModelFileBodyPart>,
     pub body_part_offset: usize,
-    pub local_attachments: Vec<()>,
+    pub local_attachments: Vec<ModelFileAttachment>,
     pub local_attachment_offset: usize,
-    pub local_nodes: Vec<()>,
+    pub local_node_names: Vec<String>,
+    pub local_nodes: Vec<Vec<u8>>,
     pub local_node_offset: usize,
     pub local_node_names_offset: usize,
-    pub flex_descriptions: Vec<()>,
+    pub flex_descriptions: Vec<ModelFileFlexDescription>,
     pub flex_description_offset: usize,
-    pub flex_controllers: Vec<()>,
+    pub flex_controllers: Vec<ModelFileFlexController>,
     pub flex_controller_offset: usize,
-    pub flex_rules: Vec<()>,
+    pub flex_rules: Vec<ModelFileFlexRule>,
     pub flex_rule_offset: usize,
-    pub inverse_kinematic_chains: Vec<()>,
+    pub inverse_kinematic_chains: Vec<ModelFileInverseKinematicChain>,
     pub inverse_kinematic_chain_offset: usize,
-    pub mouths: Vec<()>,
+    pub mouths: Vec<ModelFileMouth>,
     pub mouth_offset: usize,
-    pub local_pose_parameters: Vec<()>,
+    pub local_pose_parameters: Vec<ModelFilePoseParameter>,
     pub local_pose_parameters_offset: usize,
     pub surface_properties: String,
     pub keyvalues: String,
@@ -59,7 +66,7 @@ pub struct ModelFileHeader {
     pub include_models: Vec<()>,
     pub include_model_offset: usize,
     pub animation_block_file_name: String,
-    pub animation_blocks: Vec<()>,
+    pub animation_blocks: Vec<ModelFileAnimationBlock>,
     pub animation_block_offset: usize,
     pub sorted_bone_table_by_name: Vec<u8>,
     pub sorted_bone_table_by_name_index: usize,
@@ -84,7 +91,7 @@ impl Default for ModelFileHeader {
             illumination_position: Default::default(),
             bounding_box: Default::default(),
             clipping_box: Default::default(),
-            flags: ModelFileHeaderFlags::FORCE_OPAQUE,
+            flags: Default::default(),
             bones: Default::default(),
             bone_offset: Default::default(),
             bone_controllers: Default::default(),
@@ -105,6 +112,7 @@ impl Default for ModelFileHeader {
             body_part_offset: Default::default(),
             local_attachments: Default::default(),
             local_attachment_offset: Default::default(),
+            local_node_names: Default::default(),
             local_nodes: Default::default(),
             local_node_offset: Default::default(),
             local_node_names_offset: Default::default(),
@@ -241,13 +249,18 @@ impl WriteToWriter for ModelFileHeader {
             bone.write(writer)?;
         }
         writer.align(4);
-        // TODO: Write Bone Procedurals
+
+        for bone in &mut self.bones {
+            bone.write_procedural(writer)?;
+        }
 
         writer.write_to_integer_offset(self.bone_controller_offset, writer.data.len())?;
         // TODO: Write Bone Controllers
 
         writer.write_to_integer_offset(self.local_attachment_offset, writer.data.len())?;
-        // TODO: Write Attachments
+        for attachment in &mut self.local_attachments {
+            attachment.write(writer)?;
+        }
 
         writer.write_to_integer_offset(self.hitbox_set_offset, writer.data.len())?;
         for hitbox_set in &mut self.hitbox_sets {
@@ -275,32 +288,60 @@ impl WriteToWriter for ModelFileHeader {
             writer.align(16);
             animation_description.write_animations(writer)?;
             writer.align(4);
+            animation_description.write_movements(writer)?;
+            animation_description.write_inverse_kinematic_rules(writer)?;
+            writer.align(4);
         }
-        // TODO: Write Local Animation Description IK errors, Local Hierarchy, Movement, Bone Save Frames
+        // TODO: Write Local Animation Description IK errors, Local Hierarchy, Bone Save Frames
 
         writer.write_to_integer_offset(self.local_sequence_description_offset, writer.data.len())?;
         for sequence_description in &mut self.local_sequence_descriptions {
             sequence_description.write(writer)?;
         }
 
-        // TODO: Write Local Sequence Descriptions Pose Keys, events, auto layers, auto layer rules, sequence group, local activity modifier
+        // TODO: Write Local Sequence Descriptions events, auto layer rules, sequence group
+
+        for sequence_description in &mut self.local_sequence_descriptions {
+            sequence_description.write_pose_keys(writer)?;
+        }
+
+        for sequence_description in &mut self.local_sequence_descriptions {
+            sequence_description.write_auto_layers(writer)?;
+        }
 
         for sequence_description in &mut self.local_sequence_descriptions {
             sequence_description.write_bone_weights(writer)?;
         }
 
-        // TODO: Write Local Sequence Descriptions ik locks
+        for sequence_description in &mut self.local_sequence_descriptions {
+            sequence_description.write_inverse_kinematic_locks(writer)?;
+        }
 
         for sequence_description in &mut self.local_sequence_descriptions {
             sequence_description.write_animations(writer)?;
         }
         writer.align(4);
 
+        for sequence_description in &mut self.local_sequence_descriptions {
+            sequence_description.write_activity_modifiers(writer)?;
+        }
+
         writer.write_to_integer_offset(self.local_node_names_offset, writer.data.len())?;
-        // TODO: Write Local Node Names
+        debug_assert!(
+            self.local_node_names.len() == self.local_nodes.len(),
+            "Node Name Count Does Not Match Node Count! self.local_node_names.len(): {} self.local_nodes.len(): {}",
+            self.local_node_names.len(),
+            self.local_nodes.len()
+        );
+        for node_name in &self.local_node_names {
+            writer.write_string_to_table(0, node_name);
+        }
 
         writer.write_to_integer_offset(self.local_node_offset, writer.data.len())?;
-        // TODO: Write Local Nodes
+        for node in &self.local_nodes {
+            writer.write_unsigned_byte_array(node);
+        }
+        writer.align(4);
 
         writer.write_to_integer_offset(self.body_part_offset, writer.data.len())?;
         for body_part in &mut self.body_parts {
@@ -316,37 +357,65 @@ impl WriteToWriter for ModelFileHeader {
         }
         writer.align(4);
 
-        // TODO: Write Body Parts Eyeballs, flexes
+        for body_part in &mut self.body_parts {
+            body_part.write_mesh_flexes(writer)?;
+        }
+
+        // TODO: Write Body Parts Eyeballs
 
         writer.write_to_integer_offset(self.flex_description_offset, writer.data.len())?;
-        // TODO: Write Flex Descriptions
+        for flex_description in &mut self.flex_descriptions {
+            flex_description.write(writer)?;
+        }
 
         writer.write_to_integer_offset(self.flex_controller_offset, writer.data.len())?;
-        // TODO: Write Flex Controllers
+        for flex_controller in &mut self.flex_controllers {
+            flex_controller.write(writer)?;
+        }
 
         writer.write_to_integer_offset(self.flex_rule_offset, writer.data.len())?;
-        // TODO: Write Flex Rules
+        for flex_rule in &mut self.flex_rules {
+            flex_rule.write(writer)?;
+        }
+
+        for flex_rule in &mut self.flex_rules {
+            flex_rule.write_operations(writer)?;
+        }
 
         writer.write_to_integer_offset(self.flex_flex_controller_remap_offset, writer.data.len())?;
         // TODO: Write Flex Controller Remaps
 
         writer.write_to_integer_offset(self.inverse_kinematic_chain_offset, writer.data.len())?;
-        // TODO: Write Inverse Kinematic Chains
+        for inverse_kinematic_chain in &mut self.inverse_kinematic_chains {
+            inverse_kinematic_chain.write(writer)?;
+        }
+
+        for inverse_kinematic_chain in &mut self.inverse_kinematic_chains {
+            inverse_kinematic_chain.write_links(writer)?;
+        }
 
         writer.write_to_integer_offset(self.local_inverse_kinematics_auto_play_lock_offset, writer.data.len())?;
         // TODO: Write Local Inverse Kinematic Auto Play Locks
 
         writer.write_to_integer_offset(self.mouth_offset, writer.data.len())?;
-        // TODO: Write Mouths
+        for mouth in &mut self.mouths {
+            mouth.write(writer)?;
+        }
 
         writer.write_to_integer_offset(self.local_pose_parameters_offset, writer.data.len())?;
-        // TODO: Write Local Pose Parameters
+        for pose_parameter in &mut self.local_pose_parameters {
+            pose_parameter.write(writer)?;
+        }
 
         writer.write_to_integer_offset(self.include_model_offset, writer.data.len())?;
         // TODO: Write Include Models
 
-        writer.write_to_integer_offset(self.animation_block_offset, writer.data.len())?;
-        // TODO: Write Animation Blocks
+        if !self.animation_blocks.is_empty() {
+            writer.write_to_integer_offset(self.animation_block_offset, writer.data.len())?;
+            for animation_block in &mut self.animation_blocks {
+                animation_block.write(writer)?;
+            }
+        }
 
         writer.write_to_integer_offset(self.material_offset, writer.data.len())?;
         for material in &mut self.materials {
@@ -382,6 +451,43 @@ impl WriteToWriter for ModelFileHeader {
     }
 }
 
+impl ModelFileHeader {
+    /// Writes the animation data into an animation block file, starting a new block once a block reaches the block size.
+    /// This has to be called before the header is written so the animation descriptions know which block their data is in.
+    pub fn write_animation_blocks(&mut self, writer: &mut FileWriter, block_size: usize) -> Result<(), FileWriteError> {
+        debug_assert!(block_size > 0, "Animation block size is zero!");
+        writer.write_integer(ModelFileHeaderIdentifier::Animation.to_integer());
+        writer.write_integer(self.version);
+        writer.write_integer(self.checksum);
+        writer.write_char_array("Model Compiled With Source Wrench!", 64);
+        let file_length_index = writer.write_integer_index();
+        writer.align(16);
+
+        // The first block is reserved to mark animations that are stored in the model file.
+        self.animation_blocks.clear();
+        self.animation_blocks.push(ModelFileAnimationBlock::default());
+
+        let mut block_start = writer.data.len();
+        for animation_description in &mut self.local_animation_descriptions {
+            animation_description.write_animation_block(writer, self.animation_blocks.len() as i32, block_start)?;
+            writer.align(16);
+
+            if writer.data.len() - block_start >= block_size {
+                self.animation_blocks.push(ModelFileAnimationBlock::new(block_start, writer.data.len())?);
+                block_start = writer.data.len();
+            }
+        }
+
+        if writer.data.len() > block_start {
+            self.animation_blocks.push(ModelFileAnimationBlock::new(block_start, writer.data.len())?);
+        }
+
+        writer.write_to_integer_offset(file_length_index, writer.data.len())?;
+
+        Ok(())
+    }
+}
+
 #[derive(Debug, Default)]
 #[allow(dead_code)]
 pub enum ModelFileHeaderIdentifier {
@@ -393,7 +499,7 @@ pub enum ModelFileHeaderIdentifier {
 impl ModelFileHeaderIdentifier {
     pub fn to_integer(&self) -> i32 {
         match self {
-            ModelFileHeaderIdentifier::Studio => (84 << 24) + (83 << 16) + (68 << 8) + 73,
+            ModelFileHeaderIdentifier::Studio => MODEL_FILE_IDENTIFIER,
             ModelFileHeaderIdentifier::Animation => (71 << 24) + (65 << 16) + (68 << 8) + 73,
         }
     }
@@ -430,7 +536,7 @@ bitflags! {
 #[derive(Debug, Default)]
 pub struct ModelFileSecondHeader {
     pub write_base: usize,
-    pub source_bone_transforms: Vec<()>,
+    pub source_bone_transforms: Vec<ModelFileSourceBoneTransform>,
     pub source_bone_transform_offset: usize,
     pub illumination_position_attachment_index: i32,
     pub max_eye_deflection: f32,
@@ -438,7 +544,7 @@ pub struct ModelFileSecondHeader {
     pub linear_bones: Option<()>,
     pub linear_bone_index: usize,
     pub name: String,
-    pub bone_flex_drivers: Vec<()>,
+    pub bone_flex_drivers: Vec<ModelFileBoneFlexDriver>,
     pub bone_flex_driver_offset: usize,
 }
 
@@ -461,8 +567,11 @@ impl WriteToWriter for ModelFileSecondHeader {
 
 impl ModelFileSecondHeader {
     fn write_source_bone_transforms(&mut self, writer: &mut FileWriter) -> Result<(), FileWriteError> {
-        writer.write_to_integer_offset(self.source_bone_transform_offset, writer.data.len())?;
-        // TODO: Write Source Bone Transforms
+        writer.write_to_integer_offset(self.source_bone_transform_offset, writer.data.len() - self.write_base)?;
+        for source_bone_transform in &mut self.source_bone_transforms {
+            source_bone_transform.write(writer)?;
+        }
+
         Ok(())
     }
 
@@ -471,8 +580,93 @@ impl ModelFileSecondHeader {
         Ok(())
     }
 
-    fn write_bone_flex_driver(&mut self, _writer: &mut FileWriter) -> Result<(), FileWriteError> {
-        // TODO: Write Bone Flex Driver
+    fn write_bone_flex_driver(&mut self, writer: &mut FileWriter) -> Result<(), FileWriteError> {
+        writer.write_to_integer_offset(self.bone_flex_driver_offset, writer.data.len() - self.write_base)?;
+        for bone_flex_driver in &mut self.bone_flex_drivers {
+            bone_flex_driver.write(writer)?;
+        }
+
+        for bone_flex_driver in &mut self.bone_flex_drivers {
+            bone_flex_driver.write_controls(writer)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ModelFileBoneFlexDriver {
+    pub write_base: usize,
+    pub bone: i32,
+    pub controls: Vec<ModelFileBoneFlexDriverControl>,
+    pub control_offset: usize,
+}
+
+impl WriteToWriter for ModelFileBoneFlexDriver {
+    fn write(&mut self, writer: &mut FileWriter) -> Result<(), FileWriteError> {
+        self.write_base = writer.data.len();
+        debug_assert!(self.bone >= 0, "Bone Flex Driver Bone Is Negative! self.bone: {}", self.bone);
+        writer.write_integer(self.bone);
+        writer.write_array_size(self.controls.len())?;
+        self.control_offset = writer.write_integer_index();
+        writer.write_integer_array(&[0; 3]);
+
+        Ok(())
+    }
+}
+
+impl ModelFileBoneFlexDriver {
+    fn write_controls(&mut self, writer: &mut FileWriter) -> Result<(), FileWriteError> {
+        writer.write_to_integer_offset(self.control_offset, writer.data.len() - self.write_base)?;
+
+        for control in &mut self.controls {
+            control.write(writer)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ModelFileBoneFlexDriverControl {
+    /// The axis of the bone position, 0 for X, 1 for Y and 2 for Z.
+    pub bone_component: i32,
+    pub flex_controller: i32,
+    pub minimum: f32,
+    pub maximum: f32,
+}
+
+impl WriteToWriter for ModelFileBoneFlexDriverControl {
+    fn write(&mut self, writer: &mut FileWriter) -> Result<(), FileWriteError> {
+        writer.write_integer(self.bone_component);
+        debug_assert!(
+            self.flex_controller >= 0,
+            "Bone Flex Driver Flex Controller Is Negative! self.flex_controller: {}",
+            self.flex_controller
+        );
+        writer.write_integer(self.flex_controller);
+        writer.write_float(self.minimum);
+        writer.write_float(self.maximum);
+
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub struct ModelFileSourceBoneTransform {
+    pub write_base: usize,
+    pub name: String,
+    pub pre_transform: Matrix4,
+    pub post_transform: Matrix4,
+}
+
+impl WriteToWriter for ModelFileSourceBoneTransform {
+    fn write(&mut self, writer: &mut FileWriter) -> Result<(), FileWriteError> {
+        self.write_base = writer.data.len();
+        writer.write_string_to_table(self.write_base, &self.name);
+        writer.write_matrix3x4(self.pre_transform);
+        writer.write_matrix3x4(self.post_transform);
+
         Ok(())
     }
 }
@@ -533,20 +727,7 @@ impl WriteToWriter for ModelFileBone {
         writer.write_angles(self.rotation);
         writer.write_vector3(self.animation_position_scale);
         writer.write_vector3(self.animation_rotation_scale);
-        writer.write_float_array(&[
-            self.pose.entries[0][0] as f32,
-            self.pose.entries[0][1] as f32,
-            self.pose.entries[0][2] as f32,
-            self.pose.entries[0][3] as f32,
-            self.pose.entries[1][0] as f32,
-            self.pose.entries[1][1] as f32,
-            self.pose.entries[1][2] as f32,
-            self.pose.entries[1][3] as f32,
-            self.pose.entries[2][0] as f32,
-            self.pose.entries[2][1] as f32,
-            self.pose.entries[2][2] as f32,
-            self.pose.entries[2][3] as f32,
-        ]);
+        writer.write_matrix3x4(self.pose);
         writer.write_quaternion(self.alignment);
         writer.write_integer(self.flags.bits());
         writer.write_integer(self.procedural_type.as_ref().map_or(0, |procedural| procedural.to_integer()));
@@ -555,6 +736,7 @@ impl WriteToWriter for ModelFileBone {
         writer.write_string_to_table(self.write_base, &self.surface_properties);
         writer.write_integer(self.contents.bits());
         writer.write_integer_array(&[0; 8]);
+        debug_assert_eq!(writer.data.len() - self.write_base, BONE_SIZE, "Bone Does Not Match Its Size!");
 
         Ok(())
     }
@@ -593,26 +775,186 @@ bitflags! {
     }
 }
 
+impl ModelFileBone {
+    fn write_procedural(&mut self, writer: &mut FileWriter) -> Result<(), FileWriteError> {
+        let procedural_type = match &mut self.procedural_type {
+            Some(procedural_type) => procedural_type,
+            None => return Ok(()),
+        };
+
+        writer.write_to_integer_offset(self.procedural_offset, writer.data.len() - self.write_base)?;
+
+        match procedural_type {
+            ModelFileBoneProceduralType::AxisInterpolation(axis_interpolation) => axis_interpolation.write(writer)?,
+            ModelFileBoneProceduralType::QuaternionInterpolation(quaternion_interpolation) => quaternion_interpolation.write(writer)?,
+            ModelFileBoneProceduralType::Jiggle(jiggle) => jiggle.write(writer)?,
+            _ => {}
+        }
+
+        Ok(())
+    }
+}
+
 #[derive(Debug)]
 #[allow(dead_code)]
 pub enum ModelFileBoneProceduralType {
-    // TODO: Add Structure Values To Enum Options.
-    AxisInterpolation,
-    QuaternionInterpolation,
+    // TODO: Add Structure Values To The Remaining Enum Options.
+    AxisInterpolation(Box<ModelFileAxisInterpolationBone>),
+    QuaternionInterpolation(ModelFileQuaternionInterpolationBone),
     AimAtBone,
     AimAtAttachment,
-    Jiggle,
+    Jiggle(Box<ModelFileJiggleBone>),
 }
 
 impl ModelFileBoneProceduralType {
     fn to_integer(&self) -> i32 {
         match self {
-            Self::AxisInterpolation => 1,
-            Self::QuaternionInterpolation => 2,
+            Self::AxisInterpolation(_) => 1,
+            Self::QuaternionInterpolation(_) => 2,
             Self::AimAtBone => 3,
             Self::AimAtAttachment => 4,
-            Self::Jiggle => 5,
+            Self::Jiggle(_) => 5,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ModelFileAxisInterpolationBone {
+    pub control: i32,
+    pub axis: i32,
+    pub positions: [Vector3; 6],
+    pub quaternions: [Quaternion; 6],
+}
+
+impl WriteToWriter for ModelFileAxisInterpolationBone {
+    fn write(&mut self, writer: &mut FileWriter) -> Result<(), FileWriteError> {
+        writer.write_integer(self.control);
+        writer.write_integer(self.axis);
+        for position in self.positions {
+            writer.write_vector3(position);
+        }
+        for quaternion in self.quaternions {
+            writer.write_quaternion(quaternion);
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub struct ModelFileQuaternionInterpolationBone {
+    pub write_base: usize,
+    pub control: i32,
+    pub triggers: Vec<ModelFileQuaternionInterpolationTrigger>,
+    pub trigger_offset: usize,
+}
+
+impl WriteToWriter for ModelFileQuaternionInterpolationBone {
+    fn write(&mut self, writer: &mut FileWriter) -> Result<(), FileWriteError> {
+        self.write_base = writer.data.len();
+        writer.write_integer(self.control);
+        writer.write_array_size(self.triggers.len())?;
+        self.trigger_offset = writer.write_integer_index();
+
+        writer.write_to_integer_offset(self.trigger_offset, writer.data.len() - self.write_base)?;
+        for trigger in &mut self.triggers {
+            trigger.write(writer)?;
         }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub struct ModelFileQuaternionInterpolationTrigger {
+    pub inverse_tolerance: f32,
+    pub trigger: Quaternion,
+    pub position: Vector3,
+    pub quaternion: Quaternion,
+}
+
+impl WriteToWriter for ModelFileQuaternionInterpolationTrigger {
+    fn write(&mut self, writer: &mut FileWriter) -> Result<(), FileWriteError> {
+        writer.write_float(self.inverse_tolerance);
+        writer.write_quaternion(self.trigger);
+        writer.write_vector3(self.position);
+        writer.write_quaternion(self.quaternion);
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ModelFileJiggleBone {
+    pub flags: ModelFileJiggleBoneFlags,
+    pub length: f32,
+    pub tip_mass: f32,
+    pub yaw_stiffness: f32,
+    pub yaw_damping: f32,
+    pub pitch_stiffness: f32,
+    pub pitch_damping: f32,
+    pub along_stiffness: f32,
+    pub along_damping: f32,
+    pub angle_limit: f32,
+}
+
+impl WriteToWriter for ModelFileJiggleBone {
+    fn write(&mut self, writer: &mut FileWriter) -> Result<(), FileWriteError> {
+        writer.write_integer(self.flags.bits());
+        writer.write_float(self.length);
+        writer.write_float(self.tip_mass);
+        writer.write_float(self.yaw_stiffness);
+        writer.write_float(self.yaw_damping);
+        writer.write_float(self.pitch_stiffness);
+        writer.write_float(self.pitch_damping);
+        writer.write_float(self.along_stiffness);
+        writer.write_float(self.along_damping);
+        writer.write_float(self.angle_limit);
+        // The yaw and pitch constraints, the base spring and the boing settings are not used.
+        writer.write_float_array(&[0.0; 8]);
+        writer.write_float_array(&[0.0; 12]);
+        writer.write_float_array(&[0.0; 5]);
+
+        Ok(())
+    }
+}
+
+bitflags! {
+    #[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+    pub struct ModelFileJiggleBoneFlags: i32 {
+        const IS_FLEXIBLE = 0x01;
+        const HAS_ANGLE_CONSTRAINT = 0x10;
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ModelFileAttachment {
+    pub write_base: usize,
+    pub name: String,
+    pub flags: ModelFileAttachmentFlags,
+    pub bone: i32,
+    pub local: Matrix4,
+}
+
+impl WriteToWriter for ModelFileAttachment {
+    fn write(&mut self, writer: &mut FileWriter) -> Result<(), FileWriteError> {
+        self.write_base = writer.data.len();
+        writer.write_string_to_table(self.write_base, &self.name);
+        writer.write_integer(self.flags.bits());
+        debug_assert!(self.bone >= 0, "Attachment Bone Is Negative! self.bone: {}", self.bone);
+        writer.write_integer(self.bone);
+        writer.write_matrix3x4(self.local);
+        writer.write_integer_array(&[0; 8]);
+        debug_assert_eq!(writer.data.len() - self.write_base, ATTACHMENT_SIZE, "Attachment Does Not Match Its Size!");
+
+        Ok(())
+    }
+}
+
+bitflags! {
+    #[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+    pub struct ModelFileAttachmentFlags: i32 {
+        const WORLD_ALIGN = 0x10000;
     }
 }
 
@@ -641,6 +983,7 @@ impl WriteToWriter for ModelFileHitboxSet {
         writer.write_string_to_table(self.write_base, &self.name);
         writer.write_array_size(self.hitboxes.len())?;
         self.hitbox_offset = writer.write_integer_index();
+        debug_assert_eq!(writer.data.len() - self.write_base, HITBOX_SET_SIZE, "Hitbox Set Does Not Match Its Size!");
 
         Ok(())
     }
@@ -679,6 +1022,35 @@ impl WriteToWriter for ModelFileHitBox {
             None => writer.write_integer(0),
         }
         writer.write_integer_array(&[0; 8]);
+        debug_assert_eq!(writer.data.len() - self.write_base, HITBOX_SIZE, "Hitbox Does Not Match Its Size!");
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ModelFileAnimationBlock {
+    pub data_start: i32,
+    pub data_end: i32,
+}
+
+impl ModelFileAnimationBlock {
+    fn new(data_start: usize, data_end: usize) -> Result<Self, FileWriteError> {
+        if data_end > i32::MAX as usize {
+            return Err(FileWriteError::OffsetToLarge);
+        }
+
+        Ok(Self {
+            data_start: data_start as i32,
+            data_end: data_end as i32,
+        })
+    }
+}
+
+impl WriteToWriter for ModelFileAnimationBlock {
+    fn write(&mut self, writer: &mut FileWriter) -> Result<(), FileWriteError> {
+        writer.write_integer(self.data_start);
+        writer.write_integer(self.data_end);
 
         Ok(())
     }
@@ -691,12 +1063,14 @@ pub struct ModelFileAnimationDescription {
     pub fps: f32,
     pub flags: ModelFileAnimationDescriptionFlags,
     pub frame_count: i32,
-    pub movements: Vec<()>,
+    pub movements: Vec<ModelFileMovement>,
     pub movement_offset: usize,
     pub animation_block: i32,
     pub animation_sections: Vec<ModelFileAnimationSection>,
     pub animation_offset: usize,
-    pub inverse_kinematic_rules: Vec<()>,
+    pub block_animation_offset: usize,
+    pub data_size: usize,
+    pub inverse_kinematic_rules: Vec<ModelFileInverseKinematicRule>,
     pub inverse_kinematic_rule_offset: usize,
     pub local_hierarchy: Vec<()>,
     pub local_hierarchy_offset: usize,
@@ -741,12 +1115,49 @@ impl WriteToWriter for ModelFileAnimationDescription {
         writer.write_array_size_short(self.zero_frames.len())?;
         self.zero_frame_offset = writer.write_integer_index();
         writer.write_integer(0);
+        debug_assert_eq!(
+            writer.data.len() - self.write_base,
+            ANIMATION_DESCRIPTION_SIZE,
+            "Animation Description Does Not Match Its Size!"
+        );
 
         Ok(())
     }
 }
 
 impl ModelFileAnimationDescription {
+    fn write_inverse_kinematic_rules(&mut self, writer: &mut FileWriter) -> Result<(), FileWriteError> {
+        if self.inverse_kinematic_rules.is_empty() {
+            return Ok(());
+        }
+
+        writer.write_to_integer_offset(self.inverse_kinematic_rule_offset, writer.data.len() - self.write_base)?;
+
+        for inverse_kinematic_rule in &mut self.inverse_kinematic_rules {
+            inverse_kinematic_rule.write(writer)?;
+        }
+
+        for inverse_kinematic_rule in &mut self.inverse_kinematic_rules {
+            inverse_kinematic_rule.write_compressed_error(writer)?;
+        }
+
+        Ok(())
+    }
+
+    fn write_movements(&mut self, writer: &mut FileWriter) -> Result<(), FileWriteError> {
+        if self.movements.is_empty() {
+            return Ok(());
+        }
+
+        writer.write_to_integer_offset(self.movement_offset, writer.data.len() - self.write_base)?;
+
+        for movement in &mut self.movements {
+            movement.write(writer)?;
+        }
+
+        Ok(())
+    }
+
     fn write_sections(&mut self, writer: &mut FileWriter) -> Result<(), FileWriteError> {
         if self.animation_sections.len() == 1 {
             return Ok(());
@@ -762,11 +1173,25 @@ impl ModelFileAnimationDescription {
     }
 
     fn write_animations(&mut self, writer: &mut FileWriter) -> Result<(), FileWriteError> {
+        if self.animation_block > 0 {
+            writer.write_to_integer_offset(self.animation_offset, self.block_animation_offset)?;
+
+            if self.animation_sections.len() > 1 {
+                for section in &mut self.animation_sections {
+                    writer.write_to_integer_offset(section.animation_index, section.block_animation_offset)?;
+                }
+            }
+
+            return Ok(());
+        }
+
+        let data_start = writer.data.len();
         writer.write_to_integer_offset(self.animation_offset, writer.data.len() - self.write_base)?;
 
         if self.animation_sections.len() == 1 {
             let section = &mut self.animation_sections[0];
             section.write_animation(writer, true, self.write_base)?;
+            self.data_size = writer.data.len() - data_start;
             return Ok(());
         }
 
@@ -774,6 +1199,23 @@ impl ModelFileAnimationDescription {
             section.write_animation(writer, false, self.write_base)?;
         }
 
+        self.data_size = writer.data.len() - data_start;
+        Ok(())
+    }
+
+    /// Writes the animation data to an animation block, the offsets are relative to the start of the block.
+    fn write_animation_block(&mut self, writer: &mut FileWriter, block: i32, block_start: usize) -> Result<(), FileWriteError> {
+        self.animation_block = block;
+        self.block_animation_offset = writer.data.len() - block_start;
+
+        for section in &mut self.animation_sections {
+            section.animation_block = block;
+            section.block_animation_offset = writer.data.len() - block_start;
+            section.write_animation(writer, true, block_start)?;
+        }
+
+        self.data_size = writer.data.len() - block_start - self.block_animation_offset;
+
         Ok(())
     }
 }
@@ -788,32 +1230,179 @@ bitflags! {
 }
 
 #[derive(Debug, Default)]
-pub struct ModelFileAnimationSection {
-    pub write_base: usize,
-    pub animation_block: i32,
-    pub animation_index: usize,
-    pub animation_data: Vec<ModelFileAnimation>,
+pub struct ModelFileMovement {
+    pub end_frame: i32,
+    pub flags: ModelFileMovementFlags,
+    pub start_velocity: f32,
+    pub end_velocity: f32,
+    pub angle: f32,
+    pub direction: Vector3,
+    pub position: Vector3,
 }
 
-impl WriteToWriter for ModelFileAnimationSection {
+impl WriteToWriter for ModelFileMovement {
     fn write(&mut self, writer: &mut FileWriter) -> Result<(), FileWriteError> {
-        self.write_base = writer.data.len();
-        writer.write_integer(self.animation_block);
-        self.animation_index = writer.write_integer_index();
+        debug_assert!(self.end_frame > 0, "Movement End Frame Is Not Positive! self.end_frame: {}", self.end_frame);
+        writer.write_integer(self.end_frame);
+        writer.write_integer(self.flags.bits());
+        writer.write_float(self.start_velocity);
+        writer.write_float(self.end_velocity);
+        writer.write_float(self.angle);
+        writer.write_vector3(self.direction);
+        writer.write_vector3(self.position);
 
         Ok(())
     }
 }
 
-impl ModelFileAnimationSection {
-    fn write_animation(&mut self, writer: &mut FileWriter, single: bool, animation_description_index: usize) -> Result<(), FileWriteError> {
-        if !single {
-            writer.write_to_integer_offset(self.animation_index, writer.data.len() - animation_description_index)?;
-        }
+bitflags! {
+    #[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+    pub struct ModelFileMovementFlags: i32 {
+        const LINEAR_X = 0x0040;
+        const LINEAR_Y = 0x0080;
+        const LINEAR_Z = 0x0100;
+    }
+}
 
-        if self.animation_data.is_empty() {
-            let mut animation = ModelFileAnimation {
-                bone: 255,
+#[derive(Debug, Default)]
+pub struct ModelFileInverseKinematicRule {
+    pub write_base: usize,
+    pub rule_type: i32,
+    pub chain: i32,
+    pub bone: i32,
+    pub slot: i32,
+    pub height: f32,
+    pub radius: f32,
+    pub start: f32,
+    pub peak: f32,
+    pub tail: f32,
+    pub end: f32,
+    pub contact: f32,
+    pub start_frame: i32,
+    pub compressed_error: Option<ModelFileCompressedInverseKinematicError>,
+    pub compressed_error_offset: usize,
+}
+
+impl WriteToWriter for ModelFileInverseKinematicRule {
+    fn write(&mut self, writer: &mut FileWriter) -> Result<(), FileWriteError> {
+        self.write_base = writer.data.len();
+        writer.write_integer(0);
+        writer.write_integer(self.rule_type);
+        debug_assert!(self.chain >= 0, "Inverse Kinematic Rule Chain Is Negative! self.chain: {}", self.chain);
+        writer.write_integer(self.chain);
+        writer.write_integer(self.bone);
+        writer.write_integer(self.slot);
+        writer.write_float(self.height);
+        writer.write_float(self.radius);
+        writer.write_float(0.0);
+        writer.write_vector3(Vector3::default());
+        writer.write_quaternion(Quaternion::default());
+        self.compressed_error_offset = writer.write_integer_index();
+        writer.write_integer(0);
+        writer.write_integer(self.start_frame);
+        writer.write_integer(0);
+        writer.write_float(self.start);
+        writer.write_float(self.peak);
+        writer.write_float(self.tail);
+        writer.write_float(self.end);
+        writer.write_float(0.0);
+        writer.write_float(self.contact);
+        writer.write_float(0.0);
+        writer.write_float(0.0);
+        writer.write_integer_array(&[0; 3]);
+        writer.write_integer(0);
+        writer.write_integer_array(&[0; 7]);
+
+        Ok(())
+    }
+}
+
+impl ModelFileInverseKinematicRule {
+    fn write_compressed_error(&mut self, writer: &mut FileWriter) -> Result<(), FileWriteError> {
+        if let Some(compressed_error) = &mut self.compressed_error {
+            writer.write_to_integer_offset(self.compressed_error_offset, writer.data.len() - self.write_base)?;
+            compressed_error.write(writer)?;
+            compressed_error.write_data(writer)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The target of an inverse kinematic rule for every frame, stored as three position and three rotation channels.
+#[derive(Debug, Default)]
+pub struct ModelFileCompressedInverseKinematicError {
+    pub write_base: usize,
+    pub scales: [f32; 6],
+    pub offsets: [usize; 6],
+    pub values: [Option<Vec<ModelFileAnimationEncoding>>; 6],
+}
+
+impl WriteToWriter for ModelFileCompressedInverseKinematicError {
+    fn write(&mut self, writer: &mut FileWriter) -> Result<(), FileWriteError> {
+        self.write_base = writer.data.len();
+        writer.write_float_array(&self.scales);
+
+        for channel in 0..6 {
+            self.offsets[channel] = writer.write_short_index();
+        }
+
+        Ok(())
+    }
+}
+
+impl ModelFileCompressedInverseKinematicError {
+    fn write_data(&mut self, writer: &mut FileWriter) -> Result<(), FileWriteError> {
+        for channel in 0..6 {
+            if let Some(values) = &self.values[channel] {
+                writer.write_to_short_offset(self.offsets[channel], writer.data.len() - self.write_base)?;
+
+                for value in values {
+                    match value {
+                        ModelFileAnimationEncoding::Header(header) => {
+                            writer.write_unsigned_byte(header.valid);
+                            writer.write_unsigned_byte(header.total);
+                        }
+                        ModelFileAnimationEncoding::Value(value) => {
+                            writer.write_short(*value);
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ModelFileAnimationSection {
+    pub write_base: usize,
+    pub animation_block: i32,
+    pub animation_index: usize,
+    pub block_animation_offset: usize,
+    pub animation_data: Vec<ModelFileAnimation>,
+}
+
+impl WriteToWriter for ModelFileAnimationSection {
+    fn write(&mut self, writer: &mut FileWriter) -> Result<(), FileWriteError> {
+        self.write_base = writer.data.len();
+        writer.write_integer(self.animation_block);
+        self.animation_index = writer.write_integer_index();
+
+        Ok(())
+    }
+}
+
+impl ModelFileAnimationSection {
+    fn write_animation(&mut self, writer: &mut FileWriter, single: bool, animation_description_index: usize) -> Result<(), FileWriteError> {
+        if !single {
+            writer.write_to_integer_offset(self.animation_index, writer.data.len() - animation_description_index)?;
+        }
+
+        if self.animation_data.is_empty() {
+            let mut animation = ModelFileAnimation {
+                bone: 255,
                 ..Default::default()
             };
             animation.write(writer)?;
@@ -836,6 +1425,7 @@ bitflags! {
     #[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
     struct ModelFileAnimationFlags: u8 {
         const RAW_POSITION = 0x01;
+        const RAW_ROTATION_48 = 0x02;
         const ANIMATED_POSITION = 0x04;
         const ANIMATED_ROTATION = 0x08;
         const DELTA = 0x10;
@@ -850,6 +1440,8 @@ pub struct ModelFileAnimation {
     pub bone: u8,
     pub rotation: Option<ModelFileAnimationData<Angles>>,
     pub position: Option<ModelFileAnimationData<Vector3>>,
+    /// Writes a single rotation as a Quaternion48 instead of a Quaternion64.
+    pub compact_rotation: bool,
     pub next_offset: usize,
 }
 
@@ -867,6 +1459,7 @@ impl WriteToWriter for ModelFileAnimation {
 
         if let Some(data) = &self.rotation {
             match data {
+                ModelFileAnimationData::Single(_) if self.compact_rotation => flags |= ModelFileAnimationFlags::RAW_ROTATION_48,
                 ModelFileAnimationData::Single(_) => flags |= ModelFileAnimationFlags::RAW_ROTATION,
                 ModelFileAnimationData::Array(_) => flags |= ModelFileAnimationFlags::ANIMATED_ROTATION,
             }
@@ -884,6 +1477,9 @@ impl WriteToWriter for ModelFileAnimation {
 
         if let Some(data) = &mut self.rotation {
             match data {
+                ModelFileAnimationData::Single(value) if self.compact_rotation => {
+                    writer.write_quaternion48(value.to_quaternion());
+                }
                 ModelFileAnimationData::Single(value) => {
                     writer.write_quaternion64(value.to_quaternion());
                 }
@@ -916,6 +1512,28 @@ impl WriteToWriter for ModelFileAnimation {
     }
 }
 
+impl ModelFileAnimation {
+    /// The size of the animation data of the bone once written.
+    pub fn size(&self) -> usize {
+        let value_size = |value: &ModelFileAnimationValue| 6 + value.values.iter().flatten().map(|values| values.len() * 2).sum::<usize>();
+
+        let rotation_size = match &self.rotation {
+            Some(ModelFileAnimationData::Single(_)) if self.compact_rotation => 6,
+            Some(ModelFileAnimationData::Single(_)) => 8,
+            Some(ModelFileAnimationData::Array(value)) => value_size(value),
+            None => 0,
+        };
+
+        let position_size = match &self.position {
+            Some(ModelFileAnimationData::Single(_)) => 6,
+            Some(ModelFileAnimationData::Array(value)) => value_size(value),
+            None => 0,
+        };
+
+        4 + rotation_size + position_size
+    }
+}
+
 #[derive(Debug)]
 pub enum ModelFileAnimationData<T> {
     Single(T),
@@ -999,15 +1617,17 @@ pub struct ModelFileSequenceDescription {
     pub local_entry_node: i32,
     pub local_exit_node: i32,
     pub reverse_transition: bool,
+    pub entry_phase: f32,
+    pub exit_phase: f32,
     pub inversive_kinematic_count: i32,
-    pub auto_layers: Vec<()>,
+    pub auto_layers: Vec<ModelFileAutoLayer>,
     pub auto_layer_offset: usize,
     pub weight_list: Vec<f32>,
     pub weight_list_offset: usize,
-    #[allow(dead_code)]
-    pub pose_keys: Vec<f32>, // TODO: What the fuck are these for? Is the name correct for this? Seems fine if they don't exist but don't trust that.
+    /// The pose parameter value at each column then each row of the blend grid, the engine spreads the blends evenly without them.
+    pub pose_keys: Vec<f32>,
     pub pose_key_index: usize,
-    pub inversive_kinematics_locks: Vec<()>,
+    pub inversive_kinematics_locks: Vec<ModelFileInverseKinematicLock>,
     pub inversive_kinematics_lock_offset: usize,
     pub keyvalues: String,
     pub pose_cycle: i32,
@@ -1038,6 +1658,8 @@ impl Default for ModelFileSequenceDescription {
             local_entry_node: Default::default(),
             local_exit_node: Default::default(),
             reverse_transition: Default::default(),
+            entry_phase: Default::default(),
+            exit_phase: Default::default(),
             inversive_kinematic_count: Default::default(),
             auto_layers: Default::default(),
             auto_layer_offset: Default::default(),
@@ -1071,6 +1693,12 @@ impl WriteToWriter for ModelFileSequenceDescription {
         writer.write_array_size(self.animations.len())?;
         self.animation_offset = writer.write_integer_index();
         writer.write_integer(0);
+        debug_assert!(
+            self.blend_size.iter().all(|&size| size > 0) && (self.blend_size[0] * self.blend_size[1]) as usize == self.animations.len(),
+            "Blend Size Does Not Match Animation Count! self.blend_size: {:?} self.animations.len(): {}",
+            self.blend_size,
+            self.animations.len()
+        );
         writer.write_integer_array(&self.blend_size);
         writer.write_integer_array(&self.parameters);
         writer.write_float_array(&self.parameters_start);
@@ -1081,8 +1709,8 @@ impl WriteToWriter for ModelFileSequenceDescription {
         writer.write_integer(self.local_entry_node);
         writer.write_integer(self.local_exit_node);
         writer.write_integer(self.reverse_transition as i32);
-        writer.write_float(0.0);
-        writer.write_float(0.0);
+        writer.write_float(self.entry_phase);
+        writer.write_float(self.exit_phase);
         writer.write_float(0.0);
         writer.write_integer(0);
         writer.write_integer(0);
@@ -1099,15 +1727,54 @@ impl WriteToWriter for ModelFileSequenceDescription {
         }
         writer.write_integer(self.keyvalues.len() as i32);
         writer.write_integer(self.pose_cycle);
-        writer.write_array_size(self.activity_modifiers.len())?;
         self.activity_modifier_offset = writer.write_integer_index();
+        writer.write_array_size(self.activity_modifiers.len())?;
         writer.write_integer_array(&[0; 5]);
+        debug_assert_eq!(
+            writer.data.len() - self.write_base,
+            SEQUENCE_DESCRIPTION_SIZE,
+            "Sequence Description Does Not Match Its Size!"
+        );
 
         Ok(())
     }
 }
 
 impl ModelFileSequenceDescription {
+    fn write_inverse_kinematic_locks(&mut self, writer: &mut FileWriter) -> Result<(), FileWriteError> {
+        writer.write_to_integer_offset(self.inversive_kinematics_lock_offset, writer.data.len() - self.write_base)?;
+
+        for inverse_kinematic_lock in &mut self.inversive_kinematics_locks {
+            inverse_kinematic_lock.write(writer)?;
+        }
+
+        Ok(())
+    }
+
+    fn write_auto_layers(&mut self, writer: &mut FileWriter) -> Result<(), FileWriteError> {
+        writer.write_to_integer_offset(self.auto_layer_offset, writer.data.len() - self.write_base)?;
+
+        for auto_layer in &mut self.auto_layers {
+            auto_layer.write(writer)?;
+        }
+
+        Ok(())
+    }
+
+    fn write_pose_keys(&mut self, writer: &mut FileWriter) -> Result<(), FileWriteError> {
+        if self.pose_keys.is_empty() {
+            return Ok(());
+        }
+
+        writer.write_to_integer_offset(self.pose_key_index, writer.data.len() - self.write_base)?;
+
+        for pose_key in &self.pose_keys {
+            writer.write_float(*pose_key);
+        }
+
+        Ok(())
+    }
+
     fn write_bone_weights(&mut self, writer: &mut FileWriter) -> Result<(), FileWriteError> {
         writer.write_to_integer_offset(self.weight_list_offset, writer.data.len() - self.write_base)?;
 
@@ -1127,6 +1794,22 @@ impl ModelFileSequenceDescription {
 
         Ok(())
     }
+
+    /// Every activity modifier is only the offset to its name from where the modifier is written.
+    fn write_activity_modifiers(&mut self, writer: &mut FileWriter) -> Result<(), FileWriteError> {
+        if self.activity_modifiers.is_empty() {
+            return Ok(());
+        }
+
+        writer.write_to_integer_offset(self.activity_modifier_offset, writer.data.len() - self.write_base)?;
+
+        for activity_modifier in &self.activity_modifiers {
+            let activity_modifier_base = writer.data.len();
+            writer.write_string_to_table(activity_modifier_base, activity_modifier);
+        }
+
+        Ok(())
+    }
 }
 
 bitflags! {
@@ -1146,6 +1829,273 @@ bitflags! {
     }
 }
 
+#[derive(Debug, Default)]
+pub struct ModelFileInverseKinematicLock {
+    pub chain: i32,
+    pub position_weight: f32,
+    pub local_rotation_weight: f32,
+}
+
+impl WriteToWriter for ModelFileInverseKinematicLock {
+    fn write(&mut self, writer: &mut FileWriter) -> Result<(), FileWriteError> {
+        debug_assert!(self.chain >= 0, "Inverse Kinematic Lock Chain Is Negative! self.chain: {}", self.chain);
+        writer.write_integer(self.chain);
+        writer.write_float(self.position_weight);
+        writer.write_float(self.local_rotation_weight);
+        writer.write_integer(0);
+        writer.write_integer_array(&[0; 4]);
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ModelFileAutoLayer {
+    pub sequence: i16,
+    pub pose: i16,
+    pub flags: ModelFileAutoLayerFlags,
+    pub start: f32,
+    pub peak: f32,
+    pub tail: f32,
+    pub end: f32,
+}
+
+impl WriteToWriter for ModelFileAutoLayer {
+    fn write(&mut self, writer: &mut FileWriter) -> Result<(), FileWriteError> {
+        debug_assert!(self.sequence >= 0, "Auto Layer Sequence Is Negative! self.sequence: {}", self.sequence);
+        writer.write_short(self.sequence);
+        writer.write_short(self.pose);
+        writer.write_integer(self.flags.bits());
+        debug_assert!(
+            self.start <= self.peak && self.peak <= self.tail && self.tail <= self.end,
+            "Auto Layer Range Is Not In Order! start: {} peak: {} tail: {} end: {}",
+            self.start,
+            self.peak,
+            self.tail,
+            self.end
+        );
+        writer.write_float(self.start);
+        writer.write_float(self.peak);
+        writer.write_float(self.tail);
+        writer.write_float(self.end);
+
+        Ok(())
+    }
+}
+
+bitflags! {
+    #[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+    pub struct ModelFileAutoLayerFlags: i32 {
+        const POST       = 0x0010;
+        const SPLINE     = 0x0040;
+        const CROSS_FADE = 0x0080;
+        const NO_BLEND   = 0x0200;
+        const LOCAL      = 0x1000;
+        const POSE       = 0x4000;
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ModelFilePoseParameter {
+    pub write_base: usize,
+    pub name: String,
+    pub flags: i32,
+    pub start: f32,
+    pub end: f32,
+    pub loop_range: f32,
+}
+
+#[derive(Debug, Default)]
+pub struct ModelFileFlexDescription {
+    pub write_base: usize,
+    pub name: String,
+}
+
+impl WriteToWriter for ModelFileFlexDescription {
+    fn write(&mut self, writer: &mut FileWriter) -> Result<(), FileWriteError> {
+        self.write_base = writer.data.len();
+        writer.write_string_to_table(self.write_base, &self.name);
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ModelFileFlexController {
+    pub write_base: usize,
+    pub controller_type: String,
+    pub name: String,
+    pub minimum: f32,
+    pub maximum: f32,
+}
+
+impl WriteToWriter for ModelFileFlexController {
+    fn write(&mut self, writer: &mut FileWriter) -> Result<(), FileWriteError> {
+        self.write_base = writer.data.len();
+        writer.write_string_to_table(self.write_base, &self.controller_type);
+        writer.write_string_to_table(self.write_base, &self.name);
+        // The engine fills in the global index of the controller when the model is loaded.
+        writer.write_integer(0);
+        writer.write_float(self.minimum);
+        writer.write_float(self.maximum);
+
+        Ok(())
+    }
+}
+
+/// Sets the weight of a flex description from the flex controllers each frame.
+#[derive(Debug, Default)]
+pub struct ModelFileFlexRule {
+    pub write_base: usize,
+    pub flex_description: i32,
+    pub operations: Vec<ModelFileFlexOperation>,
+    pub operation_offset: usize,
+}
+
+impl WriteToWriter for ModelFileFlexRule {
+    fn write(&mut self, writer: &mut FileWriter) -> Result<(), FileWriteError> {
+        self.write_base = writer.data.len();
+        debug_assert!(
+            self.flex_description >= 0,
+            "Flex Rule Flex Description Is Negative! self.flex_description: {}",
+            self.flex_description
+        );
+        writer.write_integer(self.flex_description);
+        writer.write_array_size(self.operations.len())?;
+        self.operation_offset = writer.write_integer_index();
+
+        Ok(())
+    }
+}
+
+impl ModelFileFlexRule {
+    fn write_operations(&mut self, writer: &mut FileWriter) -> Result<(), FileWriteError> {
+        writer.write_to_integer_offset(self.operation_offset, writer.data.len() - self.write_base)?;
+
+        for operation in &mut self.operations {
+            operation.write(writer)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The operations of a flex rule are run on a stack, the value left on top is the weight of the flex description.
+#[derive(Debug)]
+#[allow(dead_code)]
+pub enum ModelFileFlexOperation {
+    Constant(f32),
+    FetchController(i32),
+}
+
+impl WriteToWriter for ModelFileFlexOperation {
+    fn write(&mut self, writer: &mut FileWriter) -> Result<(), FileWriteError> {
+        match self {
+            Self::Constant(value) => {
+                writer.write_integer(1);
+                writer.write_float(*value);
+            }
+            Self::FetchController(flex_controller) => {
+                writer.write_integer(2);
+                debug_assert!(
+                    *flex_controller >= 0,
+                    "Flex Operation Flex Controller Is Negative! flex_controller: {}",
+                    flex_controller
+                );
+                writer.write_integer(*flex_controller);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ModelFileInverseKinematicChain {
+    pub write_base: usize,
+    pub name: String,
+    pub links: Vec<ModelFileInverseKinematicLink>,
+    pub link_offset: usize,
+}
+
+impl WriteToWriter for ModelFileInverseKinematicChain {
+    fn write(&mut self, writer: &mut FileWriter) -> Result<(), FileWriteError> {
+        self.write_base = writer.data.len();
+        writer.write_string_to_table(self.write_base, &self.name);
+        writer.write_integer(0);
+        writer.write_array_size(self.links.len())?;
+        self.link_offset = writer.write_integer_index();
+
+        Ok(())
+    }
+}
+
+impl ModelFileInverseKinematicChain {
+    fn write_links(&mut self, writer: &mut FileWriter) -> Result<(), FileWriteError> {
+        writer.write_to_integer_offset(self.link_offset, writer.data.len() - self.write_base)?;
+
+        for link in &mut self.links {
+            link.write(writer)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ModelFileInverseKinematicLink {
+    pub bone: i32,
+    pub knee_direction: Vector3,
+}
+
+impl WriteToWriter for ModelFileInverseKinematicLink {
+    fn write(&mut self, writer: &mut FileWriter) -> Result<(), FileWriteError> {
+        debug_assert!(self.bone >= 0, "Inverse Kinematic Link Bone Is Negative! self.bone: {}", self.bone);
+        writer.write_integer(self.bone);
+        writer.write_vector3(self.knee_direction);
+        writer.write_vector3(Vector3::default());
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ModelFileMouth {
+    pub bone: i32,
+    pub forward: Vector3,
+    pub flex_description: i32,
+}
+
+impl WriteToWriter for ModelFileMouth {
+    fn write(&mut self, writer: &mut FileWriter) -> Result<(), FileWriteError> {
+        debug_assert!(self.bone >= 0, "Mouth Bone Is Negative! self.bone: {}", self.bone);
+        writer.write_integer(self.bone);
+        writer.write_vector3(self.forward);
+        debug_assert!(
+            self.flex_description >= 0,
+            "Mouth Flex Description Is Negative! self.flex_description: {}",
+            self.flex_description
+        );
+        writer.write_integer(self.flex_description);
+
+        Ok(())
+    }
+}
+
+impl WriteToWriter for ModelFilePoseParameter {
+    fn write(&mut self, writer: &mut FileWriter) -> Result<(), FileWriteError> {
+        self.write_base = writer.data.len();
+        writer.write_string_to_table(self.write_base, &self.name);
+        writer.write_integer(self.flags);
+        debug_assert!(self.start < self.end, "Pose Parameter Range Is Empty! start: {} end: {}", self.start, self.end);
+        writer.write_float(self.start);
+        writer.write_float(self.end);
+        writer.write_float(self.loop_range);
+
+        Ok(())
+    }
+}
+
 #[derive(Debug, Default)]
 pub struct ModelFileBodyPart {
     pub write_base: usize,
@@ -1163,6 +2113,7 @@ impl WriteToWriter for ModelFileBodyPart {
         writer.write_array_size(self.models.len())?;
         writer.write_integer(self.base);
         self.model_offset = writer.write_integer_index();
+        debug_assert_eq!(writer.data.len() - self.write_base, BODY_PART_SIZE, "Body Part Does Not Match Its Size!");
 
         Ok(())
     }
@@ -1192,6 +2143,14 @@ impl ModelFileBodyPart {
 
         Ok(())
     }
+
+    fn write_mesh_flexes(&mut self, writer: &mut FileWriter) -> Result<(), FileWriteError> {
+        for mesh in self.models.iter_mut().flat_map(|model| &mut model.meshes) {
+            mesh.write_flexes(writer)?;
+        }
+
+        Ok(())
+    }
 }
 
 #[derive(Debug, Default)]
@@ -1226,6 +2185,7 @@ impl WriteToWriter for ModelFileModel {
         writer.write_integer(0);
         writer.write_integer(0);
         writer.write_integer_array(&[0; 8]);
+        debug_assert_eq!(writer.data.len() - self.write_base, MODEL_SIZE, "Model Does Not Match Its Size!");
 
         Ok(())
     }
@@ -1240,7 +2200,7 @@ pub struct ModelFileMesh {
     pub model_index: usize,
     pub vertex_count: i32,
     pub vertex_offset: i32,
-    pub flexes: Vec<()>,
+    pub flexes: Vec<ModelFileFlex>,
     pub flex_offset: usize,
     pub is_eye_mesh: bool,
     pub eye_index: i32,
@@ -1265,12 +2225,94 @@ impl WriteToWriter for ModelFileMesh {
         writer.write_integer(0);
         writer.write_integer_array(&self.vertex_lod_count);
         writer.write_integer_array(&[0; 8]);
+        debug_assert_eq!(writer.data.len() - self.write_base, MESH_SIZE, "Mesh Does Not Match Its Size!");
+
+        Ok(())
+    }
+}
+
+impl ModelFileMesh {
+    fn write_flexes(&mut self, writer: &mut FileWriter) -> Result<(), FileWriteError> {
+        writer.write_to_integer_offset(self.flex_offset, writer.data.len() - self.write_base)?;
+
+        for flex in &mut self.flexes {
+            flex.write(writer)?;
+        }
+
+        for flex in &mut self.flexes {
+            flex.write_vertex_animations(writer)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ModelFileFlex {
+    pub write_base: usize,
+    pub flex_description: i32,
+    /// The flex weights where the flex starts, fully applies, stops fully applying and stops.
+    pub targets: [f32; 4],
+    pub vertex_animations: Vec<ModelFileVertexAnimation>,
+    pub vertex_animation_offset: usize,
+}
+
+impl WriteToWriter for ModelFileFlex {
+    fn write(&mut self, writer: &mut FileWriter) -> Result<(), FileWriteError> {
+        self.write_base = writer.data.len();
+
+        writer.write_integer(self.flex_description);
+        writer.write_float_array(&self.targets);
+        writer.write_array_size(self.vertex_animations.len())?;
+        self.vertex_animation_offset = writer.write_integer_index();
+        // The flex is paired with itself, so every side of the vertices gets the same weight.
+        writer.write_integer(self.flex_description);
+        writer.write_unsigned_byte(0);
+        writer.write_unsigned_byte_array(&[0; 3]);
+        writer.write_integer_array(&[0; 6]);
+        debug_assert_eq!(writer.data.len() - self.write_base, FLEX_SIZE, "Flex Does Not Match Its Size!");
 
         Ok(())
     }
 }
 
-impl ModelFileMesh {}
+impl ModelFileFlex {
+    fn write_vertex_animations(&mut self, writer: &mut FileWriter) -> Result<(), FileWriteError> {
+        writer.write_to_integer_offset(self.vertex_animation_offset, writer.data.len() - self.write_base)?;
+
+        for vertex_animation in &mut self.vertex_animations {
+            vertex_animation.write(writer)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ModelFileVertexAnimation {
+    pub index: u16,
+    pub position_delta: Vector3,
+    pub normal_delta: Vector3,
+}
+
+impl WriteToWriter for ModelFileVertexAnimation {
+    fn write(&mut self, writer: &mut FileWriter) -> Result<(), FileWriteError> {
+        let write_base = writer.data.len();
+
+        writer.write_unsigned_short(self.index);
+        writer.write_unsigned_byte(255);
+        writer.write_unsigned_byte(127);
+        writer.write_vector48(self.position_delta);
+        writer.write_vector48(self.normal_delta);
+        debug_assert_eq!(
+            writer.data.len() - write_base,
+            VERTEX_ANIMATION_SIZE,
+            "Vertex Animation Does Not Match Its Size!"
+        );
+
+        Ok(())
+    }
+}
 
 #[derive(Debug, Default)]
 pub struct ModelFileMaterial {
@@ -1289,6 +2331,7 @@ impl WriteToWriter for ModelFileMaterial {
         writer.write_integer(0);
         writer.write_integer(0);
         writer.write_integer_array(&[0; 10]);
+        debug_assert_eq!(writer.data.len() - self.write_base, MATERIAL_SIZE, "Material Does Not Match Its Size!");
 
         Ok(())
     }
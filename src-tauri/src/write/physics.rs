@@ -0,0 +1,214 @@
+use std::collections::HashMap;
+
+use crate::utilities::mathematics::{BoundingBox, Vector3};
+
+use super::{FileWriteError, FileWriter, WriteToWriter};
+
+#[derive(Debug, Default)]
+pub struct PhysicsFileHeader {
+    pub checksum: i32,
+    pub solids: Vec<PhysicsFileSolid>,
+    /// The text describing the solids and the constraints between them.
+    pub keyvalues: String,
+}
+
+const PHYSICS_FILE_HEADER_SIZE: i32 = 16;
+
+impl WriteToWriter for PhysicsFileHeader {
+    fn write(&mut self, writer: &mut FileWriter) -> Result<(), FileWriteError> {
+        writer.write_integer(PHYSICS_FILE_HEADER_SIZE);
+        writer.write_integer(0);
+        writer.write_array_size(self.solids.len())?;
+        writer.write_integer(self.checksum);
+
+        for solid in &mut self.solids {
+            solid.write(writer)?;
+        }
+
+        writer.write_null_terminated_string(&self.keyvalues);
+
+        Ok(())
+    }
+}
+
+/// A solid made of convex pieces in the space of the physics engine, which is in meters with Z pointing down.
+#[derive(Debug, Default)]
+pub struct PhysicsFileSolid {
+    pub ledges: Vec<PhysicsFileLedge>,
+    pub mass_center: Vector3,
+    /// The rotational inertia of the solid around each axis for a mass of one.
+    pub rotation_inertia: Vector3,
+    pub drag_axis_areas: Vector3,
+}
+
+/// A convex piece of a solid.
+#[derive(Debug, Default)]
+pub struct PhysicsFileLedge {
+    pub points: Vec<Vector3>,
+    /// The triangles wind counterclockwise seen from outside the piece.
+    pub triangles: Vec<[usize; 3]>,
+}
+
+const COLLISION_MODEL_IDENTIFIER: i32 = (89 << 24) + (72 << 16) + (80 << 8) + 86;
+const COLLISION_MODEL_VERSION: i16 = 0x100;
+const COMPACT_SURFACE_IDENTIFIER: i32 = (83 << 24) + (80 << 16) + (86 << 8) + 73;
+
+impl WriteToWriter for PhysicsFileSolid {
+    fn write(&mut self, writer: &mut FileWriter) -> Result<(), FileWriteError> {
+        debug_assert!(!self.ledges.is_empty(), "Physics Solid Has No Ledges!");
+
+        let size_index = writer.write_integer_index();
+        let solid_start = writer.data.len();
+        writer.write_integer(COLLISION_MODEL_IDENTIFIER);
+        writer.write_short(COLLISION_MODEL_VERSION);
+        writer.write_short(0); // The model is made of convex polygons.
+        let surface_size_index = writer.write_integer_index();
+        writer.write_vector3(self.drag_axis_areas);
+        writer.write_integer(0); // The axis map is not used.
+
+        let upper_limit_radius = self
+            .ledges
+            .iter()
+            .flat_map(|ledge| &ledge.points)
+            .map(|point| (*point - self.mass_center).magnitude())
+            .fold(0.0, f64::max);
+
+        let surface_start = writer.data.len();
+        writer.write_vector3(self.mass_center);
+        writer.write_vector3(self.rotation_inertia);
+        writer.write_float(upper_limit_radius as f32);
+        // The maximum surface deviation is in the low byte, the size is written into the upper bytes later.
+        let surface_byte_size_index = writer.write_integer_index();
+        let ledge_tree_index = writer.write_integer_index();
+        writer.write_integer(0);
+        writer.write_integer(0);
+        writer.write_integer(COMPACT_SURFACE_IDENTIFIER);
+
+        let mut ledge_starts = Vec::with_capacity(self.ledges.len());
+        for ledge in &mut self.ledges {
+            ledge_starts.push(writer.data.len());
+            ledge.write(writer)?;
+        }
+
+        writer.write_to_integer_offset(ledge_tree_index, writer.data.len() - surface_start)?;
+        let mut ledge_indices = (0..self.ledges.len()).collect::<Vec<_>>();
+        self.write_ledge_tree_node(writer, &mut ledge_indices, &ledge_starts)?;
+
+        let surface_size = writer.data.len() - surface_start;
+        writer.write_to_integer_offset(surface_size_index, surface_size)?;
+        writer.write_to_integer_offset(surface_byte_size_index, surface_size << 8)?;
+        writer.write_to_integer_offset(size_index, writer.data.len() - solid_start)?;
+
+        Ok(())
+    }
+}
+
+impl PhysicsFileSolid {
+    /// Writes a node bounding the ledges with a sphere, the ledges are split in half along their longest axis until each node has one ledge.
+    /// The left child directly follows its parent node, while the parent points to its right child.
+    fn write_ledge_tree_node(&self, writer: &mut FileWriter, ledges: &mut [usize], ledge_starts: &[usize]) -> Result<(), FileWriteError> {
+        let bounds = BoundingBox::from_points(ledges.iter().flat_map(|&ledge| &self.ledges[ledge].points));
+        let center = bounds.center();
+        let radius = ledges
+            .iter()
+            .flat_map(|&ledge| &self.ledges[ledge].points)
+            .map(|point| (*point - center).magnitude())
+            .fold(0.0, f64::max);
+
+        let node_start = writer.data.len();
+        let right_node_index = writer.write_integer_index();
+        match ledges {
+            [ledge] => writer.write_negative_offset(node_start - ledge_starts[*ledge])?,
+            _ => writer.write_integer(0),
+        }
+        writer.write_vector3(center);
+        writer.write_float(radius as f32);
+        // The half size of the box around the ledges relative to the radius, in steps of 1/250.
+        for axis in 0..3 {
+            let box_size = (bounds.size()[axis] * 0.5 / radius.max(f64::EPSILON) * 250.0).ceil() + 1.0;
+            writer.write_unsigned_byte(box_size.min(u8::MAX as f64) as u8);
+        }
+        writer.write_unsigned_byte(0);
+
+        if ledges.len() > 1 {
+            let size = bounds.size();
+            let axis = (0..3).max_by(|&first, &second| size[first].total_cmp(&size[second])).unwrap_or_default();
+            let ledge_center = |ledge: usize| BoundingBox::from_points(&self.ledges[ledge].points).center()[axis];
+            ledges.sort_by(|&first, &second| ledge_center(first).total_cmp(&ledge_center(second)));
+
+            let (left, right) = ledges.split_at_mut(ledges.len() / 2);
+            self.write_ledge_tree_node(writer, left, ledge_starts)?;
+            writer.write_to_integer_offset(right_node_index, writer.data.len() - node_start)?;
+            self.write_ledge_tree_node(writer, right, ledge_starts)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl WriteToWriter for PhysicsFileLedge {
+    fn write(&mut self, writer: &mut FileWriter) -> Result<(), FileWriteError> {
+        debug_assert!(!self.triangles.is_empty(), "Physics Ledge Has No Triangles!");
+        debug_assert!(
+            self.triangles.len() <= 4096,
+            "Physics Ledge Has Too Many Triangles! self.triangles.len(): {}",
+            self.triangles.len()
+        );
+        debug_assert!(
+            self.points.len() <= u16::MAX as usize,
+            "Physics Ledge Has Too Many Points! self.points.len(): {}",
+            self.points.len()
+        );
+
+        let ledge_start = writer.data.len();
+        let point_offset_index = writer.write_integer_index();
+        writer.write_integer(0); // Client data.
+                                 // The ledge is compact and has no children, its size is in steps of 16 bytes.
+        writer.write_integer((1 << 2) | ((1 + self.triangles.len() as i32) << 8));
+        writer.write_array_size_short(self.triangles.len())?;
+        writer.write_short(0);
+
+        // Each edge points to the same edge going the other way in the neighboring triangle, relative in steps of 4 bytes.
+        let edge_position = |triangle: usize, corner: usize| (triangle * 4 + 1 + corner) as i32;
+        let edges = self
+            .triangles
+            .iter()
+            .enumerate()
+            .flat_map(|(triangle_index, triangle)| {
+                (0..3).map(move |corner| ((triangle[corner], triangle[(corner + 1) % 3]), edge_position(triangle_index, corner)))
+            })
+            .collect::<HashMap<_, _>>();
+
+        let normals = self
+            .triangles
+            .iter()
+            .map(|&[a, b, c]| (self.points[b] - self.points[a]).cross(self.points[c] - self.points[a]).normalize())
+            .collect::<Vec<_>>();
+
+        for (triangle_index, triangle) in self.triangles.iter().enumerate() {
+            // The pierce triangle is the one facing most away from the triangle.
+            let pierce_index = normals
+                .iter()
+                .enumerate()
+                .min_by(|(_, first), (_, second)| normals[triangle_index].dot(**first).total_cmp(&normals[triangle_index].dot(**second)))
+                .map(|(index, _)| index)
+                .unwrap_or_default();
+            writer.write_integer((triangle_index | (pierce_index << 12)) as i32);
+
+            for corner in 0..3 {
+                let (start, end) = (triangle[corner], triangle[(corner + 1) % 3]);
+                let opposite = edges.get(&(end, start)).copied().unwrap_or_else(|| edge_position(triangle_index, corner));
+                let relative_opposite = opposite - edge_position(triangle_index, corner);
+                writer.write_integer(start as i32 | ((relative_opposite & 0x7FFF) << 16));
+            }
+        }
+
+        writer.write_to_integer_offset(point_offset_index, writer.data.len() - ledge_start)?;
+        for point in &self.points {
+            writer.write_vector3(*point);
+            writer.write_float(0.0);
+        }
+
+        Ok(())
+    }
+}
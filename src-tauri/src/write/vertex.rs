@@ -1,6 +1,9 @@
 use crate::utilities::mathematics::{Vector2, Vector3, Vector4};
 
-use super::{FileWriteError, FileWriter, WriteToWriter, MAX_LOD_COUNT};
+use super::{
+    layout::{VERTEX_FILE_IDENTIFIER, VERTEX_FIXUP_SIZE, VERTEX_SIZE},
+    FileWriteError, FileWriter, WriteToWriter, MAX_LOD_COUNT,
+};
 
 #[derive(Debug, Default)]
 pub struct VertexFileHeader {
@@ -16,8 +19,6 @@ pub struct VertexFileHeader {
     pub tangent_offset: usize,
 }
 
-const VERTEX_FILE_IDENTIFIER: i32 = (86 << 24) + (83 << 16) + (68 << 8) + 73;
-
 impl WriteToWriter for VertexFileHeader {
     fn write(&mut self, writer: &mut FileWriter) -> Result<(), FileWriteError> {
         writer.write_integer(VERTEX_FILE_IDENTIFIER);
@@ -96,6 +97,7 @@ pub struct VertexFileFixup {
 
 impl WriteToWriter for VertexFileFixup {
     fn write(&mut self, writer: &mut FileWriter) -> Result<(), FileWriteError> {
+        let write_base = writer.data.len();
         debug_assert!(self.lod > 0, "LOD Is Less Than 1! self.lod: {}", self.lod);
         debug_assert!(
             self.lod <= MAX_LOD_COUNT as i32,
@@ -108,6 +110,7 @@ impl WriteToWriter for VertexFileFixup {
         writer.write_integer(self.vertex_index);
         debug_assert!(self.vertex_count > 0, "Vertex Count Is Less Than 1! self.vertex_count: {}", self.vertex_count);
         writer.write_integer(self.vertex_count);
+        debug_assert_eq!(writer.data.len() - write_base, VERTEX_FIXUP_SIZE, "Vertex Fixup Does Not Match Its Size!");
 
         Ok(())
     }
@@ -125,6 +128,7 @@ pub struct VertexFileVertex {
 
 impl WriteToWriter for VertexFileVertex {
     fn write(&mut self, writer: &mut FileWriter) -> Result<(), FileWriteError> {
+        let write_base = writer.data.len();
         debug_assert!(
             (self.weights.iter().sum::<f32>() - 1.0).abs() < 1e-6,
             "Vertex Weight Sum Not Equal To 1!: self.weights: {:?} sum: {}",
@@ -147,6 +151,7 @@ impl WriteToWriter for VertexFileVertex {
             self.texture_coordinate
         );
         writer.write_vector2(self.texture_coordinate);
+        debug_assert_eq!(writer.data.len() - write_base, VERTEX_SIZE, "Vertex Does Not Match Its Size!");
 
         Ok(())
     }
@@ -1,94 +1,1110 @@
 import { invoke } from '@tauri-apps/api/core';
-import { documentDir } from '@tauri-apps/api/path';
-import { open } from '@tauri-apps/plugin-dialog';
-import { createSignal, Show, type Component } from 'solid-js';
-import { createStore } from 'solid-js/store';
-import { AnimationEntryProperties } from './components/AnimationEntry';
+import { open, save } from '@tauri-apps/plugin-dialog';
+import { batch, createMemo, createSignal, For, Show, type Component } from 'solid-js';
+import { createStore, reconcile } from 'solid-js/store';
+import { AimMatrixEntryProperties } from './components/AimMatrixEntry';
+import AimMatrixMenu from './components/AimMatrixMenu';
+import { AnimationEntryProperties, AnimationInverseKinematicRule, AnimationMotion } from './components/AnimationEntry';
 import AnimationMenu from './components/AnimationMenu';
+import { AttachmentEntryProperties } from './components/AttachmentEntry';
+import AttachmentMenu from './components/AttachmentMenu';
 import { BodyPartEntryProperties } from './components/BodyPartEntry';
 import BodyPartMenu from './components/BodyPartMenu';
+import { type BodyPartModelEntryProperties, type ModelMirror } from './components/BodyPartModelEntry';
+import BoneConflicts, { type BoneConflict } from './components/BoneConflicts';
+import { BoneFlexDriverEntryProperties } from './components/BoneFlexDriverEntry';
+import BoneFlexDriverMenu from './components/BoneFlexDriverMenu';
+import BoundingBoxInput, { type BoundingBox } from './components/BoundingBoxInput';
+import { defaultDialogPath, onModelFileLoaded, onModelFilesReloaded } from './components/FileOperations';
+import GameProfiles, { type EngineLimits } from './components/GameProfiles';
+import GibsInput, { type Gibs } from './components/GibsInput';
+import { copyState, createHistory } from './components/History';
+import { InverseKinematicChainEntryProperties } from './components/InverseKinematicChainEntry';
+import InverseKinematicChainMenu from './components/InverseKinematicChainMenu';
+import { JiggleChainEntryProperties } from './components/JiggleChainEntry';
+import JiggleChainMenu from './components/JiggleChainMenu';
 import Logging from './components/Logging';
-import { SequenceEntryProperties } from './components/SequenceEntry';
-import SequenceMenu from './components/SequenceMenu';
+import MaterialMenu, { createNewMaterialPath, type MaterialNormalization } from './components/MaterialMenu';
+import { MaterialPathEntryProperties } from './components/MaterialPathEntry';
+import { MaterialRenameEntryProperties } from './components/MaterialRenameEntry';
+import MissingFiles from './components/MissingFiles';
+import ModelFlagsInput, { type ModelFlags } from './components/ModelFlagsInput';
+import { MouthEntryProperties } from './components/MouthEntry';
+import MouthMenu from './components/MouthMenu';
+import PhysicsMenu from './components/PhysicsMenu';
+import { PhysicsSolidEntryProperties } from './components/PhysicsSolidEntry';
+import { type Procedural, type ProceduralBoneEntryProperties } from './components/ProceduralBoneEntry';
+import ProceduralBoneMenu from './components/ProceduralBoneMenu';
+import RecentFiles, { addRecentFile } from './components/RecentFiles';
+import NewProject, { type ProjectTemplate } from './components/ProjectTemplates';
+import Report, { type CompileReport } from './components/Report';
+import {
+    SequenceEntryProperties,
+    type SequenceBlend,
+    type SequenceInverseKinematicLock,
+    type SequencePhase,
+    type SequenceTransition,
+} from './components/SequenceEntry';
+import SequenceMenu, { createSequenceData } from './components/SequenceMenu';
+import SourceFiles from './components/SourceFiles';
+import Skeleton, { type SkeletonBone } from './components/Skeleton';
+import { loadUserDefaults, resetUserDefaults, saveUserDefaults, type RotationEncoding } from './components/UserDefaults';
+import { WeightListEntryProperties } from './components/WeightListEntry';
+import WeightListMenu from './components/WeightListMenu';
+import WorldModelInput, { type WorldModel } from './components/WorldModelInput';
 
 type ImputedCompilationData = {
     model_name: string;
     export_path: string;
+    target_version: number;
+    surface_property: string;
+    animation_block_size: number;
+    animation_section_frames: number;
+    write_report: boolean;
+    write_dx80_mesh: boolean;
+    write_software_mesh: boolean;
+    prefix_animation_names: boolean;
+    rotation_encoding: RotationEncoding;
+    origin: [number, number, number];
+    world_model: WorldModel | null;
+    gibs: Gibs | null;
+    material_paths: string[];
+    material_renames: {
+        from: string;
+        to: string;
+    }[];
+    material_normalization: MaterialNormalization;
+    bone_attachments: {
+        name: string;
+        bone: string;
+    }[];
+    procedural_bones: {
+        bone: string;
+        control_bone: string;
+        procedural: Procedural;
+    }[];
+    mouths: MouthEntryProperties['data'][];
+    bone_flex_drivers: BoneFlexDriverEntryProperties['data'][];
+    inverse_kinematic_chains: InverseKinematicChainEntryProperties['data'][];
+    jiggle_chains: JiggleChainEntryProperties['data'][];
+    weight_lists: {
+        name: string;
+        weights: {
+            bone: string;
+            weight: number;
+        }[];
+    }[];
+    bone_conflicts: BoneConflict[];
+    bone_merge_bones: string[];
+    mesh_limits: {
+        hardware_bones_per_strip: number;
+        weights_per_vertex: number;
+        vertex_cache_size: number;
+        optimize_overdraw: boolean;
+        overdraw_threshold: number;
+        mikktspace_tangents: boolean;
+    };
+    engine_limits: EngineLimits;
+    weld_tolerances: {
+        position: number;
+        normal_angle: number;
+        texture_coordinate: number;
+    };
+    texture_coordinates: {
+        flip_v: boolean;
+        normalize: boolean;
+        reject_udim: boolean;
+    };
+    polygon_limits: {
+        maximum_vertices: number;
+        strict: boolean;
+    };
+    model_flags: ModelFlags;
+    max_eye_deflection: number;
+    illumination_attachment: string | null;
+    normal_smoothing_angle: number | null;
+    seam_normal_tolerance: number | null;
+    bounding_box: BoundingBox | null;
+    clipping_box: BoundingBox | null;
     body_parts: {
         name: string;
+        default_model: number;
+        preview_model: number | null;
         models: {
             name: string;
             is_blank: boolean;
             file_source: string;
             part_names: string[];
+            materials: string[];
+            mirror: ModelMirror | null;
+            flex_file_source: string;
         }[];
     }[];
     animations: {
         name: string;
         file_source: string;
         animation_name: string;
+        frame_range: [number, number] | null;
+        fps: number;
+        retarget: boolean;
+        modifiers: {
+            reverse: boolean;
+            speed: number;
+            hold_start: number;
+            hold_end: number;
+        };
+        subtract: {
+            animation: string;
+            frame: number;
+        } | null;
+        motion: AnimationMotion | null;
+        inverse_kinematic_rules: AnimationInverseKinematicRule[];
     }[];
     sequences: {
         name: string;
         animations: string[][];
+        weight_list: string | null;
+        auto_layers: {
+            sequence: string;
+            start: number;
+            peak: number;
+            tail: number;
+            end: number;
+            spline: boolean;
+            cross_fade: boolean;
+            no_blend: boolean;
+        }[];
+        bounding_box: BoundingBox | null;
+        transition: SequenceTransition | null;
+        inverse_kinematic_locks: SequenceInverseKinematicLock[];
+        fade_in_time: number;
+        fade_out_time: number;
+        phase: SequencePhase | null;
+        column_blend: SequenceBlend | null;
+        row_blend: SequenceBlend | null;
+        activity_modifiers: string[];
     }[];
+    transition_nodes: string[];
+    aim_matrices: AimMatrixEntryProperties['data'][];
+    physics: {
+        total_mass: number;
+        solids: PhysicsSolidEntryProperties['data'][];
+    } | null;
 };
 
+// The fields of the compilation data that each menu edits, every other field is edited in the compilation menu.
+const menuFields: Record<string, (keyof ImputedCompilationData)[]> = {
+    'Body-Part-Menu': ['body_parts'],
+    'Material-Menu': ['material_paths', 'material_renames', 'material_normalization'],
+    'Animation-Menu': ['animations'],
+    'Sequence-Menu': ['sequences', 'transition_nodes'],
+    'Weight-List-Menu': ['weight_lists'],
+    'Aim-Matrix-Menu': ['aim_matrices'],
+    'Attachment-Menu': ['bone_attachments'],
+    'Procedural-Bone-Menu': ['procedural_bones'],
+    'Mouth-Menu': ['mouths'],
+    'Bone-Flex-Driver-Menu': ['bone_flex_drivers'],
+    'Inverse-Kinematic-Chain-Menu': ['inverse_kinematic_chains'],
+    'Jiggle-Chain-Menu': ['jiggle_chains'],
+    'Physics-Menu': ['physics'],
+    'Skeleton-Menu': ['bone_merge_bones'],
+};
+
+// Opened entries count down so they never share an identifier with the entries the menus count up from 0.
+let openedEntryIdentifierGenerator = -1;
+
 const App: Component = () => {
     const [modelExportPath, setModelExportPath] = createSignal('');
     const [modelName, setModelName] = createSignal('');
+    const userDefaults = loadUserDefaults();
+    const [targetVersion, setTargetVersion] = createSignal(userDefaults.target_version);
+    const [surfaceProperty, setSurfaceProperty] = createSignal(userDefaults.surface_property);
+    const [prefixAnimationNames, setPrefixAnimationNames] = createSignal(userDefaults.prefix_animation_names);
+    const [rotationEncoding, setRotationEncoding] = createSignal(userDefaults.rotation_encoding);
+    const [hardwareBonesPerStrip, setHardwareBonesPerStrip] = createSignal(userDefaults.hardware_bones_per_strip);
+    const [boneLimit, setBoneLimit] = createSignal(userDefaults.bone_limit);
+    const [materialLimit, setMaterialLimit] = createSignal(userDefaults.material_limit);
+    const [weightsPerVertex, setWeightsPerVertex] = createSignal(userDefaults.weights_per_vertex);
+    const [vertexCacheSize, setVertexCacheSize] = createSignal(userDefaults.vertex_cache_size);
+    const [optimizeOverdraw, setOptimizeOverdraw] = createSignal(userDefaults.optimize_overdraw);
+    const [overdrawThreshold, setOverdrawThreshold] = createSignal(userDefaults.overdraw_threshold);
+    const [mikktspaceTangents, setMikktspaceTangents] = createSignal(userDefaults.mikktspace_tangents);
+    const [weldPositionTolerance, setWeldPositionTolerance] = createSignal(userDefaults.weld_position_tolerance);
+    const [weldNormalAngle, setWeldNormalAngle] = createSignal(userDefaults.weld_normal_angle);
+    const [weldTextureCoordinateTolerance, setWeldTextureCoordinateTolerance] = createSignal(userDefaults.weld_texture_coordinate_tolerance);
+    const [flipTextureV, setFlipTextureV] = createSignal(userDefaults.flip_texture_v);
+    const [normalizeTextureCoordinates, setNormalizeTextureCoordinates] = createSignal(userDefaults.normalize_texture_coordinates);
+    const [rejectUdimTextureCoordinates, setRejectUdimTextureCoordinates] = createSignal(userDefaults.reject_udim_texture_coordinates);
+    const [maximumPolygonVertices, setMaximumPolygonVertices] = createSignal(userDefaults.maximum_polygon_vertices);
+    const [strictPolygonLimit, setStrictPolygonLimit] = createSignal(userDefaults.strict_polygon_limit);
+    const [animationBlockSize, setAnimationBlockSize] = createSignal(userDefaults.animation_block_size);
+    const [animationSectionFrames, setAnimationSectionFrames] = createSignal(userDefaults.animation_section_frames);
+    const [writeReport, setWriteReport] = createSignal(userDefaults.write_report);
+    const [writeDx80Mesh, setWriteDx80Mesh] = createSignal(userDefaults.write_dx80_mesh);
+    const [writeSoftwareMesh, setWriteSoftwareMesh] = createSignal(userDefaults.write_software_mesh);
+    const [modelFlags, setModelFlags] = createSignal<ModelFlags>(userDefaults.model_flags);
+    const [maxEyeDeflection, setMaxEyeDeflection] = createSignal(30);
+    const [illuminationAttachment, setIlluminationAttachment] = createSignal<string | null>(null);
+    const [normalSmoothingAngle, setNormalSmoothingAngle] = createSignal<number | null>(null);
+    const [seamNormalTolerance, setSeamNormalTolerance] = createSignal<number | null>(null);
+    const [boundingBox, setBoundingBox] = createSignal<BoundingBox | null>(null);
+    const [clippingBox, setClippingBox] = createSignal<BoundingBox | null>(null);
+    const [origin, setOrigin] = createSignal<[number, number, number]>([0, 0, 0]);
+    const [worldModel, setWorldModel] = createSignal<WorldModel | null>(null);
+    const [gibs, setGibs] = createSignal<Gibs | null>(null);
+    const [compileReport, setCompileReport] = createSignal<CompileReport | null>(null);
+    const [skeletonBones, setSkeletonBones] = createSignal<SkeletonBone[] | null>(null);
+    const [boneConflicts, setBoneConflicts] = createSignal<BoneConflict[]>([]);
+    const [boneMergeBones, setBoneMergeBones] = createSignal<string[]>([]);
+    const [pendingBoneConflicts, setPendingBoneConflicts] = createSignal<SkeletonBone[]>([]);
+    const [missingMaterials, setMissingMaterials] = createSignal<string[] | null>(null);
     const [modelCompiling, setModelCompiling] = createSignal(false);
+    const [watchSourceFiles, setWatchSourceFiles] = createSignal(false);
+    const [recompileOnChange, setRecompileOnChange] = createSignal(false);
+    const [acceptCompileJobs, setAcceptCompileJobs] = createSignal(false);
+    const [compileJobPort, setCompileJobPort] = createSignal(27150);
+    const [compileJobToken, setCompileJobToken] = createSignal('');
+    const [missingFiles, setMissingFiles] = createSignal<string[]>([]);
+    const [compiledData, setCompiledData] = createSignal<ImputedCompilationData | null>(null);
+    const [materialPathEntries, setMaterialPathEntries] = createStore<MaterialPathEntryProperties[]>([]);
+    const [materialRenameEntries, setMaterialRenameEntries] = createStore<MaterialRenameEntryProperties[]>([]);
+    const [materialNormalization, setMaterialNormalization] = createSignal<MaterialNormalization>({ strip_directories: false, merge_similar: true });
+    const [attachmentEntries, setAttachmentEntries] = createStore<AttachmentEntryProperties[]>([]);
+    const [proceduralBoneEntries, setProceduralBoneEntries] = createStore<ProceduralBoneEntryProperties[]>([]);
+    const [mouthEntries, setMouthEntries] = createStore<MouthEntryProperties[]>([]);
+    const [boneFlexDriverEntries, setBoneFlexDriverEntries] = createStore<BoneFlexDriverEntryProperties[]>([]);
+    const [inverseKinematicChainEntries, setInverseKinematicChainEntries] = createStore<InverseKinematicChainEntryProperties[]>([]);
+    const [jiggleChainEntries, setJiggleChainEntries] = createStore<JiggleChainEntryProperties[]>([]);
     const [bodyPartEntries, setBodyPartEntries] = createStore<BodyPartEntryProperties[]>([]);
     const [animationEntries, setAnimationEntries] = createStore<AnimationEntryProperties[]>([]);
     const [sequenceEntries, setSequenceEntries] = createStore<SequenceEntryProperties[]>([]);
+    const [transitionNodes, setTransitionNodes] = createSignal<string[]>([]);
+    const [weightListEntries, setWeightListEntries] = createStore<WeightListEntryProperties[]>([]);
+    const [aimMatrixEntries, setAimMatrixEntries] = createStore<AimMatrixEntryProperties[]>([]);
+    const [physicsTotalMass, setPhysicsTotalMass] = createSignal(100);
+    const [physicsSolidEntries, setPhysicsSolidEntries] = createStore<PhysicsSolidEntryProperties[]>([]);
 
-    const compileModel = async () => {
-        setModelCompiling(true);
+    const editHistory = createHistory(
+        () => ({
+            modelExportPath: modelExportPath(),
+            modelName: modelName(),
+            targetVersion: targetVersion(),
+            surfaceProperty: surfaceProperty(),
+            prefixAnimationNames: prefixAnimationNames(),
+            rotationEncoding: rotationEncoding(),
+            hardwareBonesPerStrip: hardwareBonesPerStrip(),
+            boneLimit: boneLimit(),
+            materialLimit: materialLimit(),
+            weightsPerVertex: weightsPerVertex(),
+            vertexCacheSize: vertexCacheSize(),
+            optimizeOverdraw: optimizeOverdraw(),
+            overdrawThreshold: overdrawThreshold(),
+            mikktspaceTangents: mikktspaceTangents(),
+            weldPositionTolerance: weldPositionTolerance(),
+            weldNormalAngle: weldNormalAngle(),
+            weldTextureCoordinateTolerance: weldTextureCoordinateTolerance(),
+            flipTextureV: flipTextureV(),
+            normalizeTextureCoordinates: normalizeTextureCoordinates(),
+            rejectUdimTextureCoordinates: rejectUdimTextureCoordinates(),
+            maximumPolygonVertices: maximumPolygonVertices(),
+            strictPolygonLimit: strictPolygonLimit(),
+            animationBlockSize: animationBlockSize(),
+            animationSectionFrames: animationSectionFrames(),
+            writeReport: writeReport(),
+            writeDx80Mesh: writeDx80Mesh(),
+            writeSoftwareMesh: writeSoftwareMesh(),
+            modelFlags: modelFlags(),
+            maxEyeDeflection: maxEyeDeflection(),
+            illuminationAttachment: illuminationAttachment(),
+            normalSmoothingAngle: normalSmoothingAngle(),
+            seamNormalTolerance: seamNormalTolerance(),
+            boundingBox: boundingBox(),
+            clippingBox: clippingBox(),
+            origin: origin(),
+            worldModel: worldModel(),
+            gibs: gibs(),
+            materialPathEntries: copyState(materialPathEntries),
+            materialRenameEntries: copyState(materialRenameEntries),
+            materialNormalization: materialNormalization(),
+            attachmentEntries: copyState(attachmentEntries),
+            proceduralBoneEntries: copyState(proceduralBoneEntries),
+            mouthEntries: copyState(mouthEntries),
+            boneFlexDriverEntries: copyState(boneFlexDriverEntries),
+            inverseKinematicChainEntries: copyState(inverseKinematicChainEntries),
+            jiggleChainEntries: copyState(jiggleChainEntries),
+            bodyPartEntries: copyState(bodyPartEntries),
+            animationEntries: copyState(animationEntries),
+            sequenceEntries: copyState(sequenceEntries),
+            transitionNodes: [...transitionNodes()],
+            weightListEntries: copyState(weightListEntries),
+            boneConflicts: boneConflicts(),
+            boneMergeBones: boneMergeBones(),
+            aimMatrixEntries: copyState(aimMatrixEntries),
+            physicsTotalMass: physicsTotalMass(),
+            physicsSolidEntries: copyState(physicsSolidEntries),
+        }),
+        (state) => {
+            setModelExportPath(state.modelExportPath);
+            setModelName(state.modelName);
+            setTargetVersion(state.targetVersion);
+            setSurfaceProperty(state.surfaceProperty);
+            setPrefixAnimationNames(state.prefixAnimationNames);
+            setRotationEncoding(state.rotationEncoding);
+            setHardwareBonesPerStrip(state.hardwareBonesPerStrip);
+            setBoneLimit(state.boneLimit);
+            setMaterialLimit(state.materialLimit);
+            setWeightsPerVertex(state.weightsPerVertex);
+            setVertexCacheSize(state.vertexCacheSize);
+            setOptimizeOverdraw(state.optimizeOverdraw);
+            setOverdrawThreshold(state.overdrawThreshold);
+            setMikktspaceTangents(state.mikktspaceTangents);
+            setWeldPositionTolerance(state.weldPositionTolerance);
+            setWeldNormalAngle(state.weldNormalAngle);
+            setWeldTextureCoordinateTolerance(state.weldTextureCoordinateTolerance);
+            setFlipTextureV(state.flipTextureV);
+            setNormalizeTextureCoordinates(state.normalizeTextureCoordinates);
+            setRejectUdimTextureCoordinates(state.rejectUdimTextureCoordinates);
+            setMaximumPolygonVertices(state.maximumPolygonVertices);
+            setStrictPolygonLimit(state.strictPolygonLimit);
+            setAnimationBlockSize(state.animationBlockSize);
+            setAnimationSectionFrames(state.animationSectionFrames);
+            setWriteReport(state.writeReport);
+            setWriteDx80Mesh(state.writeDx80Mesh);
+            setWriteSoftwareMesh(state.writeSoftwareMesh);
+            setModelFlags(state.modelFlags);
+            setMaxEyeDeflection(state.maxEyeDeflection);
+            setIlluminationAttachment(state.illuminationAttachment);
+            setNormalSmoothingAngle(state.normalSmoothingAngle);
+            setSeamNormalTolerance(state.seamNormalTolerance);
+            setBoundingBox(state.boundingBox);
+            setClippingBox(state.clippingBox);
+            setOrigin(state.origin);
+            setWorldModel(state.worldModel);
+            setGibs(state.gibs);
+            // Entries are matched by identifier so the entries that still exist keep their loaded files.
+            setMaterialPathEntries(reconcile(state.materialPathEntries, { key: 'identifier' }));
+            setMaterialRenameEntries(reconcile(state.materialRenameEntries, { key: 'identifier' }));
+            setMaterialNormalization(state.materialNormalization);
+            setAttachmentEntries(reconcile(state.attachmentEntries, { key: 'identifier' }));
+            setProceduralBoneEntries(reconcile(state.proceduralBoneEntries, { key: 'identifier' }));
+            setMouthEntries(reconcile(state.mouthEntries, { key: 'identifier' }));
+            setBoneFlexDriverEntries(reconcile(state.boneFlexDriverEntries, { key: 'identifier' }));
+            setInverseKinematicChainEntries(reconcile(state.inverseKinematicChainEntries, { key: 'identifier' }));
+            setJiggleChainEntries(reconcile(state.jiggleChainEntries, { key: 'identifier' }));
+            setBodyPartEntries(reconcile(state.bodyPartEntries, { key: 'identifier' }));
+            setAnimationEntries(reconcile(state.animationEntries, { key: 'identifier' }));
+            setSequenceEntries(reconcile(state.sequenceEntries, { key: 'identifier' }));
+            setTransitionNodes(state.transitionNodes);
+            setWeightListEntries(reconcile(state.weightListEntries, { key: 'identifier' }));
+            setBoneConflicts(state.boneConflicts);
+            setBoneMergeBones(state.boneMergeBones);
+            setAimMatrixEntries(reconcile(state.aimMatrixEntries, { key: 'identifier' }));
+            setPhysicsTotalMass(state.physicsTotalMass);
+            setPhysicsSolidEntries(reconcile(state.physicsSolidEntries, { key: 'identifier' }));
+        },
+    );
 
-        const data: ImputedCompilationData = {
-            model_name: modelName(),
-            export_path: modelExportPath(),
-            body_parts: bodyPartEntries.map((bodyPart) => ({
-                name: bodyPart.data.name,
-                models: bodyPart.data.models.map((model) => ({
-                    name: model.data.name,
-                    is_blank: model.data.blank,
-                    file_source: model.data.file_source,
-                    part_names: model.data.part_names.filter((part) => part !== null),
-                })),
+    const saveCompilationDefaults = () => {
+        saveUserDefaults({
+            ...loadUserDefaults(),
+            target_version: targetVersion(),
+            surface_property: surfaceProperty(),
+            prefix_animation_names: prefixAnimationNames(),
+            rotation_encoding: rotationEncoding(),
+            hardware_bones_per_strip: hardwareBonesPerStrip(),
+            bone_limit: boneLimit(),
+            material_limit: materialLimit(),
+            weights_per_vertex: weightsPerVertex(),
+            vertex_cache_size: vertexCacheSize(),
+            optimize_overdraw: optimizeOverdraw(),
+            overdraw_threshold: overdrawThreshold(),
+            mikktspace_tangents: mikktspaceTangents(),
+            weld_position_tolerance: weldPositionTolerance(),
+            weld_normal_angle: weldNormalAngle(),
+            weld_texture_coordinate_tolerance: weldTextureCoordinateTolerance(),
+            flip_texture_v: flipTextureV(),
+            normalize_texture_coordinates: normalizeTextureCoordinates(),
+            reject_udim_texture_coordinates: rejectUdimTextureCoordinates(),
+            maximum_polygon_vertices: maximumPolygonVertices(),
+            strict_polygon_limit: strictPolygonLimit(),
+            animation_block_size: animationBlockSize(),
+            animation_section_frames: animationSectionFrames(),
+            write_report: writeReport(),
+            write_dx80_mesh: writeDx80Mesh(),
+            write_software_mesh: writeSoftwareMesh(),
+            model_flags: modelFlags(),
+        });
+    };
+
+    const resetCompilationDefaults = () => {
+        resetUserDefaults();
+        const defaults = loadUserDefaults();
+        setTargetVersion(defaults.target_version);
+        setSurfaceProperty(defaults.surface_property);
+        setPrefixAnimationNames(defaults.prefix_animation_names);
+        setRotationEncoding(defaults.rotation_encoding);
+        setHardwareBonesPerStrip(defaults.hardware_bones_per_strip);
+        setBoneLimit(defaults.bone_limit);
+        setMaterialLimit(defaults.material_limit);
+        setWeightsPerVertex(defaults.weights_per_vertex);
+        setVertexCacheSize(defaults.vertex_cache_size);
+        setOptimizeOverdraw(defaults.optimize_overdraw);
+        setOverdrawThreshold(defaults.overdraw_threshold);
+        setMikktspaceTangents(defaults.mikktspace_tangents);
+        setWeldPositionTolerance(defaults.weld_position_tolerance);
+        setWeldNormalAngle(defaults.weld_normal_angle);
+        setWeldTextureCoordinateTolerance(defaults.weld_texture_coordinate_tolerance);
+        setFlipTextureV(defaults.flip_texture_v);
+        setNormalizeTextureCoordinates(defaults.normalize_texture_coordinates);
+        setRejectUdimTextureCoordinates(defaults.reject_udim_texture_coordinates);
+        setMaximumPolygonVertices(defaults.maximum_polygon_vertices);
+        setStrictPolygonLimit(defaults.strict_polygon_limit);
+        setAnimationBlockSize(defaults.animation_block_size);
+        setAnimationSectionFrames(defaults.animation_section_frames);
+        setWriteReport(defaults.write_report);
+        setWriteDx80Mesh(defaults.write_dx80_mesh);
+        setWriteSoftwareMesh(defaults.write_software_mesh);
+        setModelFlags(defaults.model_flags);
+    };
+
+    // The dump is written next to the model by the backend, the path is only logged.
+    const dumpModel = async () => {
+        const selectedFile = await open({
+            defaultPath: await defaultDialogPath(),
+            directory: false,
+            filters: [{ extensions: ['mdl', 'MDL'], name: 'Compiled Models' }],
+            multiple: false,
+            title: 'Select Model To Dump',
+        });
+
+        if (selectedFile === null) {
+            return;
+        }
+
+        await invoke<string | null>('dump_model', { path: selectedFile });
+    };
+
+    // A path skips the file dialog, which is used to open recent projects again.
+    const decompileModel = async (path?: string) => {
+        const selectedFile =
+            path ??
+            (await open({
+                defaultPath: await defaultDialogPath(),
+                directory: false,
+                filters: [{ extensions: ['mdl', 'MDL'], name: 'Compiled Models' }],
+                multiple: false,
+                title: 'Select Model To Decompile',
+            }));
+
+        if (selectedFile === null) {
+            return;
+        }
+
+        const project = await invoke<ImputedCompilationData | null>('decompile_model', { path: selectedFile });
+
+        if (project === null) {
+            return;
+        }
+
+        addRecentFile('projects', selectedFile);
+
+        // The model can now be selected as the source file of body part models and animations.
+        setModelExportPath(project.export_path);
+        setModelName(project.model_name);
+        setTargetVersion(project.target_version);
+        setSurfaceProperty(project.surface_property);
+        setHardwareBonesPerStrip(project.mesh_limits.hardware_bones_per_strip);
+        setBoneLimit(project.engine_limits.bones);
+        setMaterialLimit(project.engine_limits.materials);
+        setWeightsPerVertex(project.mesh_limits.weights_per_vertex);
+        setVertexCacheSize(project.mesh_limits.vertex_cache_size);
+        // Compiled models already store the texture coordinates the way the engine expects them.
+        setFlipTextureV(project.texture_coordinates.flip_v);
+        setModelFlags(project.model_flags);
+        setMaxEyeDeflection(project.max_eye_deflection);
+        setMaterialPathEntries(project.material_paths.map((path) => createNewMaterialPath(setMaterialPathEntries, path)));
+
+        // The decompiled project is only written to a file when the user picks where to save it.
+        const projectFile = await save({
+            defaultPath: selectedFile.replace(/\.mdl$/i, '.project.json'),
+            filters: [{ extensions: ['json'], name: 'Projects' }],
+            title: 'Save Decompiled Project',
+        });
+
+        if (projectFile !== null && (await invoke<boolean>('save_project_file', { path: projectFile, data: project }))) {
+            addRecentFile('projects', projectFile);
+        }
+    };
+
+    const createOpenedBodyPartModels = (models: ImputedCompilationData['body_parts'][number]['models']) => {
+        const [modelEntries, setModelEntries] = createStore<BodyPartModelEntryProperties[]>([]);
+        setModelEntries(
+            models.map((model, identifier) => ({
+                identifier,
+                setBodyPartModels: setModelEntries,
+                data: {
+                    name: model.name,
+                    blank: model.is_blank,
+                    file_source: model.file_source,
+                    part_names: model.part_names,
+                    materials: model.materials,
+                    mirror: model.mirror,
+                    flex_file_source: model.flex_file_source ?? '',
+                },
             })),
-            animations: animationEntries.map((animation) => ({
-                name: animation.data.name,
-                file_source: animation.data.file_source,
-                animation_name: animation.data.source_animation,
+        );
+        return modelEntries;
+    };
+
+    // Every setting is replaced, the entries load their source files again once they are shown.
+    const applyProject = (project: ImputedCompilationData) => {
+        batch(() => {
+            setModelExportPath(project.export_path);
+            setModelName(project.model_name);
+            setTargetVersion(project.target_version);
+            setSurfaceProperty(project.surface_property);
+            setPrefixAnimationNames(project.prefix_animation_names);
+            setRotationEncoding(project.rotation_encoding);
+            setHardwareBonesPerStrip(project.mesh_limits.hardware_bones_per_strip);
+            setBoneLimit(project.engine_limits.bones);
+            setMaterialLimit(project.engine_limits.materials);
+            setWeightsPerVertex(project.mesh_limits.weights_per_vertex);
+            setVertexCacheSize(project.mesh_limits.vertex_cache_size);
+            setOptimizeOverdraw(project.mesh_limits.optimize_overdraw);
+            setOverdrawThreshold(project.mesh_limits.overdraw_threshold);
+            setMikktspaceTangents(project.mesh_limits.mikktspace_tangents ?? false);
+            setWeldPositionTolerance(project.weld_tolerances.position);
+            setWeldNormalAngle(project.weld_tolerances.normal_angle);
+            setWeldTextureCoordinateTolerance(project.weld_tolerances.texture_coordinate);
+            setFlipTextureV(project.texture_coordinates.flip_v);
+            setNormalizeTextureCoordinates(project.texture_coordinates.normalize);
+            setRejectUdimTextureCoordinates(project.texture_coordinates.reject_udim);
+            setMaximumPolygonVertices(project.polygon_limits.maximum_vertices);
+            setStrictPolygonLimit(project.polygon_limits.strict);
+            setAnimationBlockSize(project.animation_block_size);
+            setAnimationSectionFrames(project.animation_section_frames);
+            setWriteReport(project.write_report);
+            setWriteDx80Mesh(project.write_dx80_mesh);
+            setWriteSoftwareMesh(project.write_software_mesh);
+            setModelFlags(project.model_flags);
+            setMaxEyeDeflection(project.max_eye_deflection);
+            setIlluminationAttachment(project.illumination_attachment);
+            setNormalSmoothingAngle(project.normal_smoothing_angle);
+            setSeamNormalTolerance(project.seam_normal_tolerance);
+            setBoundingBox(project.bounding_box);
+            setClippingBox(project.clipping_box);
+            setOrigin(project.origin);
+            setWorldModel(project.world_model);
+            setGibs(project.gibs && { ...project.gibs, gib_models: project.gibs.gib_models ?? [] });
+            setMaterialPathEntries(project.material_paths.map((path) => createNewMaterialPath(setMaterialPathEntries, path)));
+            setMaterialRenameEntries(
+                project.material_renames.map((data) => ({ identifier: openedEntryIdentifierGenerator--, setMaterialRenameEntries, data })),
+            );
+            setMaterialNormalization(project.material_normalization);
+            setAttachmentEntries(project.bone_attachments.map((data) => ({ identifier: openedEntryIdentifierGenerator--, setAttachmentEntries, data })));
+            setProceduralBoneEntries(
+                project.procedural_bones.map((data) => ({ identifier: openedEntryIdentifierGenerator--, setProceduralBoneEntries, data })),
+            );
+            setMouthEntries(
+                project.mouths.map((mouth) => ({
+                    identifier: openedEntryIdentifierGenerator--,
+                    setMouthEntries,
+                    data: { ...mouth, flex_controller: mouth.flex_controller ?? '' },
+                })),
+            );
+            setBoneFlexDriverEntries(
+                project.bone_flex_drivers.map((data) => ({ identifier: openedEntryIdentifierGenerator--, setBoneFlexDriverEntries, data })),
+            );
+            setInverseKinematicChainEntries(
+                project.inverse_kinematic_chains.map((data) => ({ identifier: openedEntryIdentifierGenerator--, setInverseKinematicChainEntries, data })),
+            );
+            setJiggleChainEntries(project.jiggle_chains.map((data) => ({ identifier: openedEntryIdentifierGenerator--, setJiggleChainEntries, data })));
+            setWeightListEntries(project.weight_lists.map((data) => ({ identifier: openedEntryIdentifierGenerator--, setWeightListEntries, data })));
+            setBoneConflicts(project.bone_conflicts);
+            setBoneMergeBones(project.bone_merge_bones);
+            setBodyPartEntries(
+                project.body_parts.map((bodyPart) => ({
+                    identifier: openedEntryIdentifierGenerator--,
+                    setBodyParts: setBodyPartEntries,
+                    // The opened models are identified by their index, so the picked model indices are also their identifiers.
+                    data: {
+                        name: bodyPart.name,
+                        default_model: bodyPart.default_model,
+                        preview_model: bodyPart.preview_model,
+                        models: createOpenedBodyPartModels(bodyPart.models),
+                    },
+                })),
+            );
+            setAnimationEntries(
+                project.animations.map((animation) => ({
+                    identifier: openedEntryIdentifierGenerator--,
+                    setAnimationEntries,
+                    data: {
+                        name: animation.name,
+                        file_source: animation.file_source,
+                        source_animation: animation.animation_name,
+                        frame_range: animation.frame_range,
+                        fps: animation.fps,
+                        retarget: animation.retarget,
+                        reverse: animation.modifiers.reverse,
+                        speed: animation.modifiers.speed,
+                        hold_start: animation.modifiers.hold_start,
+                        hold_end: animation.modifiers.hold_end,
+                        subtract_animation: animation.subtract?.animation ?? '',
+                        subtract_frame: animation.subtract?.frame ?? 0,
+                        motion: animation.motion,
+                        inverse_kinematic_rules: animation.inverse_kinematic_rules,
+                    },
+                })),
+            );
+            setSequenceEntries(
+                project.sequences.map((sequence) => ({
+                    identifier: openedEntryIdentifierGenerator--,
+                    setSequenceEntries,
+                    data: { ...sequence, weight_list: sequence.weight_list ?? '' },
+                })),
+            );
+            setTransitionNodes(project.transition_nodes);
+            setAimMatrixEntries(project.aim_matrices.map((data) => ({ identifier: openedEntryIdentifierGenerator--, setAimMatrixEntries, data })));
+            setPhysicsTotalMass(project.physics?.total_mass ?? 100);
+            setPhysicsSolidEntries(
+                (project.physics?.solids ?? []).map((data) => ({ identifier: openedEntryIdentifierGenerator--, setPhysicsSolidEntries, data })),
+            );
+        });
+    };
+
+    const saveProject = async () => {
+        const selectedFile = await save({
+            defaultPath: await defaultDialogPath(),
+            filters: [{ extensions: ['json'], name: 'Projects' }],
+            title: 'Save Project',
+        });
+
+        if (selectedFile === null) {
+            return;
+        }
+
+        if (await invoke<boolean>('save_project_file', { path: selectedFile, data: createCompilationData() })) {
+            addRecentFile('projects', selectedFile);
+        }
+    };
+
+    const openProject = async (path?: string) => {
+        const selectedFile =
+            path ??
+            (await open({
+                defaultPath: await defaultDialogPath(),
+                directory: false,
+                filters: [{ extensions: ['json'], name: 'Projects' }],
+                multiple: false,
+                title: 'Open Project',
+            }));
+
+        if (selectedFile === null) {
+            return;
+        }
+
+        const openedProject = await invoke<{ project: ImputedCompilationData; missing_files: string[] } | null>('open_project_file', { path: selectedFile });
+
+        if (openedProject === null) {
+            return;
+        }
+
+        addRecentFile('projects', selectedFile);
+        applyProject(openedProject.project);
+        setMissingFiles(openedProject.missing_files);
+    };
+
+    // The export path and game limits are kept, a new project is usually made for the same game.
+    const createProject = (template: ProjectTemplate) => {
+        const project = copyState(emptyProject);
+
+        applyProject({
+            ...project,
+            export_path: modelExportPath(),
+            target_version: targetVersion(),
+            engine_limits: { bones: boneLimit(), materials: materialLimit() },
+            surface_property: template.surface_property,
+            model_flags: { ...loadUserDefaults().model_flags, ...template.model_flags },
+            gibs: copyState(template.gibs),
+            animations: template.sequences.map((name) => ({
+                name,
+                file_source: '',
+                animation_name: '',
+                frame_range: null,
+                fps: loadUserDefaults().animation_fps,
+                retarget: false,
+                modifiers: { reverse: false, speed: 1, hold_start: 0, hold_end: 0 },
+                subtract: null,
+                motion: null,
+                inverse_kinematic_rules: [],
             })),
-            sequences: sequenceEntries.map((sequence) => ({
-                name: sequence.data.name,
-                animations: sequence.data.animations,
+            sequences: template.sequences.map((name) => ({ ...createSequenceData(name), animations: [[name]], weight_list: null })),
+            physics: template.physics_total_mass === null ? null : { total_mass: template.physics_total_mass, solids: [] },
+        });
+        setMissingFiles([]);
+    };
+
+    // Changing the file source makes the entries load the relocated files.
+    const relocateSourceFiles = (relocatedFiles: Record<string, string>) => {
+        const relocate = (path: string) => relocatedFiles[path] ?? path;
+
+        batch(() => {
+            for (const bodyPart of bodyPartEntries) {
+                for (const model of bodyPart.data.models) {
+                    model.setBodyPartModels((entry) => entry.identifier === model.identifier, 'data', 'file_source', relocate);
+                }
+            }
+
+            setAnimationEntries(() => true, 'data', 'file_source', relocate);
+
+            for (const solid of physicsSolidEntries) {
+                const shape = solid.data.shape;
+                if (shape.type === 'Mesh') {
+                    setPhysicsSolidEntries((entry) => entry.identifier === solid.identifier, 'data', 'shape', {
+                        ...shape,
+                        file_source: relocate(shape.file_source),
+                    });
+                }
+            }
+
+            setMissingFiles((files) => files.filter((file) => relocatedFiles[file] === undefined));
+        });
+    };
+
+    const changeOrigin = (axis: number, value: number) => {
+        const point: [number, number, number] = [...origin()];
+        point[axis] = value || 0;
+        setOrigin(point);
+    };
+
+    // The body parts pick their models by identifier, the compiler picks them by their index.
+    const modelIndex = (models: BodyPartModelEntryProperties[], identifier: number | null) => {
+        const index = models.findIndex((model) => model.identifier === identifier);
+        return index === -1 ? null : index;
+    };
+
+    const createCompilationData = (): ImputedCompilationData => ({
+        model_name: modelName(),
+        export_path: modelExportPath(),
+        target_version: targetVersion(),
+        surface_property: surfaceProperty(),
+        animation_block_size: animationBlockSize(),
+        animation_section_frames: animationSectionFrames(),
+        write_report: writeReport(),
+        write_dx80_mesh: writeDx80Mesh(),
+        write_software_mesh: writeSoftwareMesh(),
+        prefix_animation_names: prefixAnimationNames(),
+        rotation_encoding: rotationEncoding(),
+        origin: origin(),
+        world_model: worldModel(),
+        gibs: gibs(),
+        material_paths: materialPathEntries.map((materialPath) => materialPath.data.path),
+        material_renames: materialRenameEntries.map((materialRename) => ({
+            from: materialRename.data.from,
+            to: materialRename.data.to,
+        })),
+        material_normalization: materialNormalization(),
+        bone_attachments: attachmentEntries.map((attachment) => ({
+            name: attachment.data.name,
+            bone: attachment.data.bone,
+        })),
+        procedural_bones: proceduralBoneEntries.map((proceduralBone) => ({
+            bone: proceduralBone.data.bone,
+            control_bone: proceduralBone.data.control_bone,
+            procedural: proceduralBone.data.procedural,
+        })),
+        mouths: mouthEntries.map((mouth) => ({
+            bone: mouth.data.bone,
+            forward: mouth.data.forward,
+            flex: mouth.data.flex,
+            flex_controller: mouth.data.flex_controller,
+        })),
+        bone_flex_drivers: boneFlexDriverEntries.map((boneFlexDriver) => ({
+            bone: boneFlexDriver.data.bone,
+            controls: boneFlexDriver.data.controls,
+        })),
+        inverse_kinematic_chains: inverseKinematicChainEntries.map((chain) => ({
+            name: chain.data.name,
+            bone: chain.data.bone,
+            knee_direction: chain.data.knee_direction,
+        })),
+        jiggle_chains: jiggleChainEntries.map((chain) => ({
+            start_bone: chain.data.start_bone,
+            end_bone: chain.data.end_bone,
+            tip_mass: chain.data.tip_mass,
+            stiffness: chain.data.stiffness,
+            damping: chain.data.damping,
+            angle_limit: chain.data.angle_limit,
+        })),
+        weight_lists: weightListEntries.map((weightList) => ({
+            name: weightList.data.name,
+            weights: weightList.data.weights,
+        })),
+        bone_conflicts: boneConflicts(),
+        bone_merge_bones: boneMergeBones(),
+        mesh_limits: {
+            hardware_bones_per_strip: hardwareBonesPerStrip(),
+            weights_per_vertex: weightsPerVertex(),
+            vertex_cache_size: vertexCacheSize(),
+            optimize_overdraw: optimizeOverdraw(),
+            overdraw_threshold: overdrawThreshold(),
+            mikktspace_tangents: mikktspaceTangents(),
+        },
+        engine_limits: {
+            bones: boneLimit(),
+            materials: materialLimit(),
+        },
+        weld_tolerances: {
+            position: weldPositionTolerance(),
+            normal_angle: weldNormalAngle(),
+            texture_coordinate: weldTextureCoordinateTolerance(),
+        },
+        texture_coordinates: {
+            flip_v: flipTextureV(),
+            normalize: normalizeTextureCoordinates(),
+            reject_udim: rejectUdimTextureCoordinates(),
+        },
+        polygon_limits: {
+            maximum_vertices: maximumPolygonVertices(),
+            strict: strictPolygonLimit(),
+        },
+        model_flags: modelFlags(),
+        max_eye_deflection: maxEyeDeflection(),
+        illumination_attachment: illuminationAttachment(),
+        normal_smoothing_angle: normalSmoothingAngle(),
+        seam_normal_tolerance: seamNormalTolerance(),
+        bounding_box: boundingBox(),
+        clipping_box: clippingBox(),
+        body_parts: bodyPartEntries.map((bodyPart) => ({
+            name: bodyPart.data.name,
+            // Picked models that were removed fall back to the first model and to every model.
+            default_model: modelIndex(bodyPart.data.models, bodyPart.data.default_model) ?? 0,
+            preview_model: modelIndex(bodyPart.data.models, bodyPart.data.preview_model),
+            models: bodyPart.data.models.map((model) => ({
+                name: model.data.name,
+                is_blank: model.data.blank,
+                file_source: model.data.file_source,
+                part_names: model.data.part_names.filter((part) => part !== null),
+                materials: model.data.materials,
+                mirror: model.data.mirror,
+                flex_file_source: model.data.flex_file_source,
             })),
-        };
+        })),
+        animations: animationEntries.map((animation) => ({
+            name: animation.data.name,
+            file_source: animation.data.file_source,
+            animation_name: animation.data.source_animation,
+            frame_range: animation.data.frame_range,
+            fps: animation.data.fps,
+            retarget: animation.data.retarget,
+            modifiers: {
+                reverse: animation.data.reverse,
+                speed: animation.data.speed,
+                hold_start: animation.data.hold_start,
+                hold_end: animation.data.hold_end,
+            },
+            subtract:
+                animation.data.subtract_animation === '' ?
+                    null
+                :   {
+                        animation: animation.data.subtract_animation,
+                        frame: animation.data.subtract_frame,
+                    },
+            motion: animation.data.motion,
+            inverse_kinematic_rules: animation.data.inverse_kinematic_rules,
+        })),
+        sequences: sequenceEntries.map((sequence) => ({
+            name: sequence.data.name,
+            animations: sequence.data.animations,
+            weight_list: sequence.data.weight_list === '' ? null : sequence.data.weight_list,
+            auto_layers: sequence.data.auto_layers,
+            bounding_box: sequence.data.bounding_box,
+            transition: sequence.data.transition,
+            inverse_kinematic_locks: sequence.data.inverse_kinematic_locks,
+            fade_in_time: sequence.data.fade_in_time,
+            fade_out_time: sequence.data.fade_out_time,
+            phase: sequence.data.phase,
+            column_blend: sequence.data.column_blend,
+            row_blend: sequence.data.row_blend,
+            activity_modifiers: sequence.data.activity_modifiers,
+        })),
+        transition_nodes: transitionNodes(),
+        aim_matrices: aimMatrixEntries.map((aimMatrix) => ({ ...aimMatrix.data })),
+        physics:
+            physicsSolidEntries.length > 0
+                ? {
+                      total_mass: physicsTotalMass(),
+                      solids: physicsSolidEntries.map((solid) => ({
+                          bone: solid.data.bone,
+                          shape: solid.data.shape,
+                          limits: solid.data.limits,
+                      })),
+                  }
+                : null,
+    });
+
+    // Taken before anything is edited, so new projects start from the defaults of the user.
+    const emptyProject = createCompilationData();
+
+    const currentData = createMemo(() => createCompilationData());
+
+    // Fields are compared as text, the data only holds plain values.
+    const isFieldModified = (field: keyof ImputedCompilationData) => {
+        const compiled = compiledData();
+        return compiled !== null && JSON.stringify(compiled[field]) !== JSON.stringify(currentData()[field]);
+    };
 
-        await invoke('compile_model', { data });
+    const isMenuModified = (menu: string) => {
+        if (menu !== 'Compile-Menu') {
+            return (menuFields[menu] ?? []).some(isFieldModified);
+        }
+
+        const menuFieldNames = Object.values(menuFields).flat();
+        return (Object.keys(currentData()) as (keyof ImputedCompilationData)[]).some((field) => !menuFieldNames.includes(field) && isFieldModified(field));
+    };
+
+    // Until a model is compiled there is no output that matches the current setup.
+    const isOutputStale = () => compiledData() === null || JSON.stringify(compiledData()) !== JSON.stringify(currentData());
+
+    const inspectSkeleton = async () => {
+        const bones = await invoke<SkeletonBone[] | null>('inspect_skeleton', { data: createCompilationData() });
+        setSkeletonBones(bones);
+        setPendingBoneConflicts(bones?.filter((bone) => bone.conflicting_sources.length > 0) ?? []);
+    };
+
+    const checkMaterials = async () => {
+        setMissingMaterials(await invoke<string[] | null>('find_missing_materials', { data: createCompilationData() }));
+    };
+
+    // Resolved bones are not reported by the backend, so every bone returned still needs a resolution.
+    onModelFileLoaded(async () => {
+        setPendingBoneConflicts(await invoke<SkeletonBone[]>('find_bone_conflicts', { data: createCompilationData() }));
+    });
+
+    // A dry run compiles without overwriting the files on disk and reports what would change.
+    const compileModel = async (dryRun = false) => {
+        setModelCompiling(true);
+
+        const data = createCompilationData();
+
+        const report = await invoke<CompileReport | null>('compile_model', { data, dryRun });
+        setCompileReport(report);
+
+        // A dry run does not write the files, so the files on disk still match the last compile.
+        if (report !== null && !dryRun) {
+            setCompiledData(data);
+        }
 
         setModelCompiling(false);
     };
 
+    onModelFilesReloaded(async () => {
+        if (recompileOnChange() && !modelCompiling()) {
+            await compileModel();
+        }
+    });
+
+    const changeWatchSourceFiles = async (watch: boolean) => {
+        setWatchSourceFiles(watch);
+        await invoke('set_file_watching', { enabled: watch });
+    };
+
+    // The checkbox is cleared again when the port could not be used, a new token is made every time the server starts.
+    const changeAcceptCompileJobs = async (accept: boolean) => {
+        const token = await invoke<string | null>('set_job_server', { enabled: accept, port: compileJobPort() });
+        setAcceptCompileJobs(accept && token !== null);
+        setCompileJobToken(token ?? '');
+    };
+
     return (
         <>
             <header>
                 <nav>
                     <ul>
                         <li>
-                            <a href="#Compile-Menu">Compilation</a>
+                            <a href="#Compile-Menu">
+                                Compilation
+                                {isMenuModified('Compile-Menu') ? ' *' : ''}
+                            </a>
+                        </li>
+                        <li>
+                            <a href="#Body-Part-Menu">
+                                Body Parts
+                                {isMenuModified('Body-Part-Menu') ? ' *' : ''}
+                            </a>
+                        </li>
+                        <li>
+                            <a href="#Material-Menu">
+                                Materials
+                                {isMenuModified('Material-Menu') ? ' *' : ''}
+                            </a>
+                        </li>
+                        <li>
+                            <a href="#Animation-Menu">
+                                Animations
+                                {isMenuModified('Animation-Menu') ? ' *' : ''}
+                            </a>
+                        </li>
+                        <li>
+                            <a href="#Sequence-Menu">
+                                Sequences
+                                {isMenuModified('Sequence-Menu') ? ' *' : ''}
+                            </a>
+                        </li>
+                        <li>
+                            <a href="#Weight-List-Menu">
+                                Weight Lists
+                                {isMenuModified('Weight-List-Menu') ? ' *' : ''}
+                            </a>
+                        </li>
+                        <li>
+                            <a href="#Aim-Matrix-Menu">
+                                Aim Matrices
+                                {isMenuModified('Aim-Matrix-Menu') ? ' *' : ''}
+                            </a>
+                        </li>
+                        <li>
+                            <a href="#Attachment-Menu">
+                                Attachments
+                                {isMenuModified('Attachment-Menu') ? ' *' : ''}
+                            </a>
                         </li>
                         <li>
-                            <a href="#Body-Part-Menu">Body Parts</a>
+                            <a href="#Procedural-Bone-Menu">
+                                Procedural Bones
+                                {isMenuModified('Procedural-Bone-Menu') ? ' *' : ''}
+                            </a>
                         </li>
                         <li>
-                            <a href="#Animation-Menu">Animations</a>
+                            <a href="#Mouth-Menu">
+                                Mouths
+                                {isMenuModified('Mouth-Menu') ? ' *' : ''}
+                            </a>
                         </li>
                         <li>
-                            <a href="Sequence-Menu">Sequences</a>
+                            <a href="#Bone-Flex-Driver-Menu">
+                                Bone Flex Drivers
+                                {isMenuModified('Bone-Flex-Driver-Menu') ? ' *' : ''}
+                            </a>
+                        </li>
+                        <li>
+                            <a href="#Inverse-Kinematic-Chain-Menu">
+                                Inverse Kinematic Chains
+                                {isMenuModified('Inverse-Kinematic-Chain-Menu') ? ' *' : ''}
+                            </a>
+                        </li>
+                        <li>
+                            <a href="#Files-Menu">Source Files</a>
+                        </li>
+                        <li>
+                            <a href="#Skeleton-Menu">
+                                Skeleton
+                                {isMenuModified('Skeleton-Menu') ? ' *' : ''}
+                            </a>
+                        </li>
+                        <li>
+                            <a href="#Report-Menu">Report</a>
                         </li>
                     </ul>
                 </nav>
@@ -96,45 +1112,539 @@ const App: Component = () => {
             <main>
                 <h1>Source Wrench</h1>
                 <section id="Compile-Menu">
+                    <NewProject createProject={createProject} />
+                    <button onClick={async () => await openProject()}>Open Project</button>
+                    <button onClick={async () => await saveProject()}>Save Project</button>
+                    <button onClick={async () => await decompileModel()}>Decompile Model</button>
+                    <button onClick={async () => await dumpModel()}>Dump Model</button>
+                    <RecentFiles
+                        openProject={async (path) => (path.toLowerCase().endsWith('.mdl') ? await decompileModel(path) : await openProject(path))}
+                    />
+                    <MissingFiles files={missingFiles()} relocate={relocateSourceFiles} dismiss={() => setMissingFiles([])} />
+                    <button disabled={!editHistory.canUndo()} onClick={() => editHistory.undo()}>
+                        Undo
+                    </button>
+                    <button disabled={!editHistory.canRedo()} onClick={() => editHistory.redo()}>
+                        Redo
+                    </button>
+                    <br />
+                    <GameProfiles
+                        exportPath={modelExportPath()}
+                        targetVersion={targetVersion()}
+                        engineLimits={{ bones: boneLimit(), materials: materialLimit() }}
+                        selectProfile={(profile) => {
+                            setModelExportPath(profile.export_path);
+                            setTargetVersion(profile.target_version);
+                            if (profile.engine_limits !== undefined) {
+                                setBoneLimit(profile.engine_limits.bones);
+                                setMaterialLimit(profile.engine_limits.materials);
+                            }
+                        }}
+                    />
+                    <br />
                     <label>
                         Export Path
-                        <input
-                            name="ExportPath"
-                            type="text"
-                            readonly
-                            value={modelExportPath()}
-                            onClick={async () => {
-                                const selectedFile = await open({
-                                    defaultPath: await documentDir(),
-                                    directory: true,
-                                    title: 'Model Export Path',
-                                });
-
-                                if (selectedFile === null) {
-                                    setModelExportPath('');
-                                    return;
-                                }
-
-                                setModelExportPath(selectedFile);
-                            }}
-                        />
+                        <input name="ExportPath" type="text" value={modelExportPath()} onChange={(event) => setModelExportPath(event.target.value)} />
                     </label>
+                    <button
+                        onClick={async () => {
+                            const selectedFile = await open({
+                                defaultPath: await defaultDialogPath(),
+                                directory: true,
+                                title: 'Model Export Path',
+                            });
+
+                            if (selectedFile === null) {
+                                return;
+                            }
+
+                            setModelExportPath(selectedFile);
+                        }}
+                    >
+                        Browse
+                    </button>
+                    <p>
+                        The export path can use $STEAMAPPS for the steamapps folder of the Steam library the game is in, $GAMEDIR for the game set in
+                        VPROJECT or the game the source files are in and $NAME for any environment variable, they are replaced when compiling.
+                    </p>
                     <Show when={modelExportPath()}>
                         <br />
                         <label>
                             Model Name
-                            <input name="ModelName" type="text" onChange={(event) => setModelName(event.target.value)} />
+                            <input name="ModelName" type="text" value={modelName()} onChange={(event) => setModelName(event.target.value)} />
+                        </label>
+                        <br />
+                        <label>
+                            Target Version
+                            <select name="TargetVersion" value={targetVersion()} onChange={(event) => setTargetVersion(Number(event.target.value))}>
+                                <option value="48">48 (Source 2013)</option>
+                                <option value="49">49 (Portal 2, CS:GO)</option>
+                            </select>
+                        </label>
+                        <br />
+                        <label>
+                            Surface Property
+                            <input
+                                name="SurfaceProperty"
+                                type="text"
+                                value={surfaceProperty()}
+                                onChange={(event) => setSurfaceProperty(event.target.value)}
+                            />
+                        </label>
+                        <br />
+                        <label>
+                            Prefix Animation Names With @
+                            <input
+                                name="PrefixAnimationNames"
+                                type="checkbox"
+                                checked={prefixAnimationNames()}
+                                onChange={(event) => setPrefixAnimationNames(event.target.checked)}
+                            />
+                        </label>
+                        <br />
+                        <label>
+                            Constant Rotation Encoding
+                            <select
+                                name="RotationEncoding"
+                                value={rotationEncoding()}
+                                onChange={(event) => setRotationEncoding(event.target.value as RotationEncoding)}
+                            >
+                                <option value="Quaternion64">Quaternion64 (Precise)</option>
+                                <option value="Quaternion48">Quaternion48 (Compact)</option>
+                            </select>
+                        </label>
+                        <br />
+                        <label>
+                            Bone Limit
+                            <input
+                                name="BoneLimit"
+                                type="number"
+                                min="1"
+                                max="256"
+                                value={boneLimit()}
+                                onChange={(event) => setBoneLimit(Math.floor(event.target.valueAsNumber || 0))}
+                            />
+                        </label>
+                        <br />
+                        <label>
+                            Material Limit
+                            <input
+                                name="MaterialLimit"
+                                type="number"
+                                min="1"
+                                max="32768"
+                                value={materialLimit()}
+                                onChange={(event) => setMaterialLimit(Math.floor(event.target.valueAsNumber || 0))}
+                            />
+                        </label>
+                        <br />
+                        <label>
+                            Hardware Bones Per Strip
+                            <input
+                                name="HardwareBonesPerStrip"
+                                type="number"
+                                min={weightsPerVertex() * 3}
+                                max="53"
+                                value={hardwareBonesPerStrip()}
+                                onChange={(event) => setHardwareBonesPerStrip(Math.floor(event.target.valueAsNumber || 0))}
+                            />
+                        </label>
+                        <br />
+                        <label>
+                            Weights Per Vertex
+                            <input
+                                name="WeightsPerVertex"
+                                type="number"
+                                min="1"
+                                max="3"
+                                value={weightsPerVertex()}
+                                onChange={(event) => setWeightsPerVertex(Math.floor(event.target.valueAsNumber || 0))}
+                            />
+                        </label>
+                        <br />
+                        <label>
+                            Vertex Cache Size
+                            <input
+                                name="VertexCacheSize"
+                                type="number"
+                                min="3"
+                                max="16"
+                                value={vertexCacheSize()}
+                                onChange={(event) => setVertexCacheSize(Math.floor(event.target.valueAsNumber || 0))}
+                            />
+                        </label>
+                        <br />
+                        <label>
+                            Optimize Overdraw
+                            <input
+                                name="OptimizeOverdraw"
+                                type="checkbox"
+                                checked={optimizeOverdraw()}
+                                onChange={(event) => setOptimizeOverdraw(event.target.checked)}
+                            />
+                        </label>
+                        <Show when={optimizeOverdraw()}>
+                            <label>
+                                Overdraw Threshold
+                                <input
+                                    name="OverdrawThreshold"
+                                    type="number"
+                                    min="1"
+                                    step="0.01"
+                                    value={overdrawThreshold()}
+                                    onChange={(event) => setOverdrawThreshold(event.target.valueAsNumber || 1)}
+                                />
+                            </label>
+                        </Show>
+                        <br />
+                        <label>
+                            MikkTSpace Tangents
+                            <input
+                                name="MikktspaceTangents"
+                                type="checkbox"
+                                checked={mikktspaceTangents()}
+                                onChange={(event) => setMikktspaceTangents(event.target.checked)}
+                            />
+                        </label>
+                        <br />
+                        <label>
+                            Weld Position Tolerance
+                            <input
+                                name="WeldPositionTolerance"
+                                type="number"
+                                min="0"
+                                step="any"
+                                value={weldPositionTolerance()}
+                                onChange={(event) => setWeldPositionTolerance(event.target.valueAsNumber || 0)}
+                            />
+                        </label>
+                        <label>
+                            Weld Normal Angle
+                            <input
+                                name="WeldNormalAngle"
+                                type="number"
+                                min="0"
+                                max="180"
+                                step="any"
+                                value={weldNormalAngle()}
+                                onChange={(event) => setWeldNormalAngle(event.target.valueAsNumber || 0)}
+                            />
+                        </label>
+                        <label>
+                            Weld UV Tolerance
+                            <input
+                                name="WeldTextureCoordinateTolerance"
+                                type="number"
+                                min="0"
+                                step="any"
+                                value={weldTextureCoordinateTolerance()}
+                                onChange={(event) => setWeldTextureCoordinateTolerance(event.target.valueAsNumber || 0)}
+                            />
+                        </label>
+                        <br />
+                        <label>
+                            Flip Texture V
+                            <input name="FlipTextureV" type="checkbox" checked={flipTextureV()} onChange={(event) => setFlipTextureV(event.target.checked)} />
+                        </label>
+                        <label>
+                            Move Texture Coordinates Into First Tile
+                            <input
+                                name="NormalizeTextureCoordinates"
+                                type="checkbox"
+                                checked={normalizeTextureCoordinates()}
+                                onChange={(event) => setNormalizeTextureCoordinates(event.target.checked)}
+                            />
+                        </label>
+                        <label>
+                            Reject UDIM Texture Coordinates
+                            <input
+                                name="RejectUdimTextureCoordinates"
+                                type="checkbox"
+                                checked={rejectUdimTextureCoordinates()}
+                                onChange={(event) => setRejectUdimTextureCoordinates(event.target.checked)}
+                            />
+                        </label>
+                        <br />
+                        <label>
+                            Maximum Polygon Vertices
+                            <input
+                                name="MaximumPolygonVertices"
+                                type="number"
+                                min="3"
+                                value={maximumPolygonVertices()}
+                                onChange={(event) => setMaximumPolygonVertices(Math.floor(event.target.valueAsNumber || 0))}
+                            />
+                        </label>
+                        <label>
+                            Reject Larger Polygons
+                            <input
+                                name="StrictPolygonLimit"
+                                type="checkbox"
+                                checked={strictPolygonLimit()}
+                                onChange={(event) => setStrictPolygonLimit(event.target.checked)}
+                            />
                         </label>
                         <br />
+                        <ModelFlagsInput value={modelFlags()} onChange={setModelFlags} />
+                        <br />
+                        <label>
+                            Max Eye Deflection (Degrees)
+                            <input
+                                name="MaxEyeDeflection"
+                                type="number"
+                                min="1"
+                                max="89"
+                                value={maxEyeDeflection()}
+                                onChange={(event) => setMaxEyeDeflection(Math.min(89, Math.max(1, event.target.valueAsNumber || 30)))}
+                            />
+                        </label>
+                        <label>
+                            Illumination Attachment
+                            <select
+                                name="IlluminationAttachment"
+                                value={illuminationAttachment() ?? ''}
+                                onChange={(event) => setIlluminationAttachment(event.target.value === '' ? null : event.target.value)}
+                            >
+                                <option value="">Center Of Bounds</option>
+                                <For each={attachmentEntries}>{(attachment) => <option value={attachment.data.name}>{attachment.data.name}</option>}</For>
+                            </select>
+                        </label>
+                        <br />
+                        <label>
+                            Recalculate Normals
+                            <input
+                                name="RecalculateNormals"
+                                type="checkbox"
+                                checked={normalSmoothingAngle() !== null}
+                                onChange={(event) => setNormalSmoothingAngle(event.target.checked ? 60 : null)}
+                            />
+                        </label>
+                        <Show when={normalSmoothingAngle() !== null}>
+                            <label>
+                                Smoothing Angle
+                                <input
+                                    name="NormalSmoothingAngle"
+                                    type="number"
+                                    min="0"
+                                    max="180"
+                                    value={normalSmoothingAngle()!}
+                                    onChange={(event) => setNormalSmoothingAngle(event.target.valueAsNumber || 0)}
+                                />
+                            </label>
+                        </Show>
+                        <br />
+                        <label>
+                            Smooth Seam Normals
+                            <input
+                                name="SmoothSeamNormals"
+                                type="checkbox"
+                                checked={seamNormalTolerance() !== null}
+                                onChange={(event) => setSeamNormalTolerance(event.target.checked ? 0.01 : null)}
+                            />
+                        </label>
+                        <Show when={seamNormalTolerance() !== null}>
+                            <label>
+                                Seam Tolerance
+                                <input
+                                    name="SeamNormalTolerance"
+                                    type="number"
+                                    min="0"
+                                    step="0.001"
+                                    value={seamNormalTolerance()!}
+                                    onChange={(event) => setSeamNormalTolerance(Math.max(0, event.target.valueAsNumber || 0))}
+                                />
+                            </label>
+                        </Show>
+                        <br />
+                        <BoundingBoxInput name="BoundingBox" label="Bounding Box" value={boundingBox()} onChange={setBoundingBox} />
+                        <br />
+                        <BoundingBoxInput name="ClippingBox" label="Clipping Box" value={clippingBox()} onChange={setClippingBox} />
+                        <br />
+                        <For each={['X', 'Y', 'Z']}>
+                            {(axis, axisIndex) => (
+                                <label>
+                                    Origin {axis}:
+                                    <input
+                                        name={`Origin${axis}`}
+                                        type="number"
+                                        value={origin()[axisIndex()]}
+                                        onChange={(event) => changeOrigin(axisIndex(), event.target.valueAsNumber)}
+                                    />
+                                </label>
+                            )}
+                        </For>
+                        <br />
+                        <WorldModelInput value={worldModel()} onChange={setWorldModel} />
+                        <br />
+                        <GibsInput value={gibs()} bodyParts={bodyPartEntries.map((bodyPart) => bodyPart.data.name)} onChange={setGibs} />
+                        <br />
+                        <label>
+                            Animation Block Size (KB, 0 Disables .ani)
+                            <input
+                                name="AnimationBlockSize"
+                                type="number"
+                                min="0"
+                                value={animationBlockSize()}
+                                onChange={(event) => setAnimationBlockSize(Math.floor(event.target.valueAsNumber || 0))}
+                            />
+                        </label>
+                        <br />
+                        <label>
+                            Frames Per Animation Section
+                            <input
+                                name="AnimationSectionFrames"
+                                type="number"
+                                min="2"
+                                value={animationSectionFrames()}
+                                onChange={(event) => setAnimationSectionFrames(Math.max(Math.floor(event.target.valueAsNumber || 0), 2))}
+                            />
+                        </label>
+                        <br />
+                        <label>
+                            Write Report File
+                            <input name="WriteReport" type="checkbox" checked={writeReport()} onChange={(event) => setWriteReport(event.target.checked)} />
+                        </label>
+                        <br />
+                        <label>
+                            Write DirectX 8 Mesh (.dx80.vtx)
+                            <input
+                                name="WriteDx80Mesh"
+                                type="checkbox"
+                                checked={writeDx80Mesh()}
+                                onChange={(event) => setWriteDx80Mesh(event.target.checked)}
+                            />
+                        </label>
+                        <label>
+                            Write Software Mesh (.sw.vtx)
+                            <input
+                                name="WriteSoftwareMesh"
+                                type="checkbox"
+                                checked={writeSoftwareMesh()}
+                                onChange={(event) => setWriteSoftwareMesh(event.target.checked)}
+                            />
+                        </label>
+                        <br />
+                        <button onClick={() => saveCompilationDefaults()}>Save As Defaults</button>
+                        <button onClick={() => resetCompilationDefaults()}>Reset Defaults</button>
+                        <br />
+                        <Show when={isOutputStale()}>
+                            <span title="The compiled files do not match the current setup">Stale Output</span>
+                        </Show>
                         <button disabled={modelCompiling()} onclick={async () => await compileModel()}>
                             Compile Model
                         </button>
+                        <button disabled={modelCompiling()} onClick={async () => await compileModel(true)}>
+                            Dry Run
+                        </button>
+                        <button disabled={!modelCompiling()} onClick={async () => await invoke('cancel_compile')}>
+                            Cancel
+                        </button>
+                        <br />
+                        <label>
+                            Watch Source Files
+                            <input
+                                name="WatchSourceFiles"
+                                type="checkbox"
+                                checked={watchSourceFiles()}
+                                onChange={async (event) => await changeWatchSourceFiles(event.target.checked)}
+                            />
+                        </label>
+                        <label>
+                            Recompile On Change
+                            <input
+                                name="RecompileOnChange"
+                                type="checkbox"
+                                disabled={!watchSourceFiles()}
+                                checked={recompileOnChange()}
+                                onChange={(event) => setRecompileOnChange(event.target.checked)}
+                            />
+                        </label>
+                        <br />
+                        <label>
+                            Accept Compile Jobs From Other Programs
+                            <input
+                                name="AcceptCompileJobs"
+                                type="checkbox"
+                                checked={acceptCompileJobs()}
+                                onChange={async (event) => await changeAcceptCompileJobs(event.target.checked)}
+                            />
+                        </label>
+                        <label>
+                            Port
+                            <input
+                                name="CompileJobPort"
+                                type="number"
+                                min={1}
+                                max={65535}
+                                disabled={acceptCompileJobs()}
+                                value={compileJobPort()}
+                                onChange={(event) => setCompileJobPort(Math.floor(event.target.valueAsNumber || 27150))}
+                            />
+                        </label>
+                        <Show when={acceptCompileJobs()}>
+                            <label>
+                                Token
+                                <input name="CompileJobToken" type="text" readOnly value={compileJobToken()} />
+                            </label>
+                        </Show>
                     </Show>
                 </section>
                 <Logging />
                 <BodyPartMenu bodyPartEntries={bodyPartEntries} setBodyPartEntries={setBodyPartEntries} />
+                <MaterialMenu
+                    materialPathEntries={materialPathEntries}
+                    setMaterialPathEntries={setMaterialPathEntries}
+                    materialRenameEntries={materialRenameEntries}
+                    setMaterialRenameEntries={setMaterialRenameEntries}
+                    materialNormalization={materialNormalization()}
+                    setMaterialNormalization={setMaterialNormalization}
+                    missingMaterials={missingMaterials()}
+                    checkMaterials={checkMaterials}
+                />
                 <AnimationMenu animationEntries={animationEntries} setAnimationEntries={setAnimationEntries} />
-                <SequenceMenu sequenceEntries={sequenceEntries} setSequenceEntries={setSequenceEntries} />
+                <SequenceMenu
+                    sequenceEntries={sequenceEntries}
+                    setSequenceEntries={setSequenceEntries}
+                    transitionNodes={transitionNodes()}
+                    setTransitionNodes={setTransitionNodes}
+                />
+                <WeightListMenu weightListEntries={weightListEntries} setWeightListEntries={setWeightListEntries} />
+                <AimMatrixMenu aimMatrixEntries={aimMatrixEntries} setAimMatrixEntries={setAimMatrixEntries} />
+                <AttachmentMenu attachmentEntries={attachmentEntries} setAttachmentEntries={setAttachmentEntries} />
+                <ProceduralBoneMenu proceduralBoneEntries={proceduralBoneEntries} setProceduralBoneEntries={setProceduralBoneEntries} />
+                <MouthMenu mouthEntries={mouthEntries} setMouthEntries={setMouthEntries} />
+                <BoneFlexDriverMenu boneFlexDriverEntries={boneFlexDriverEntries} setBoneFlexDriverEntries={setBoneFlexDriverEntries} />
+                <InverseKinematicChainMenu
+                    inverseKinematicChainEntries={inverseKinematicChainEntries}
+                    setInverseKinematicChainEntries={setInverseKinematicChainEntries}
+                />
+                <JiggleChainMenu jiggleChainEntries={jiggleChainEntries} setJiggleChainEntries={setJiggleChainEntries} />
+                <PhysicsMenu
+                    physicsSolidEntries={physicsSolidEntries}
+                    setPhysicsSolidEntries={setPhysicsSolidEntries}
+                    totalMass={physicsTotalMass()}
+                    setTotalMass={setPhysicsTotalMass}
+                    fileSources={[...new Set(bodyPartEntries.flatMap((bodyPart) => bodyPart.data.models.map((model) => model.data.file_source)))].filter(
+                        (fileSource) => fileSource !== '',
+                    )}
+                />
+                <SourceFiles />
+                <Skeleton
+                    bones={skeletonBones()}
+                    inspectSkeleton={inspectSkeleton}
+                    boneMergeBones={boneMergeBones()}
+                    setBoneMergeBones={setBoneMergeBones}
+                />
+                <BoneConflicts
+                    conflicts={pendingBoneConflicts()}
+                    resolutions={boneConflicts()}
+                    setResolutions={setBoneConflicts}
+                    dismiss={() => setPendingBoneConflicts([])}
+                />
+                <Report
+                    report={compileReport()}
+                    applyFlagChanges={(flagChanges) => setModelFlags({ ...modelFlags(), ...Object.fromEntries(flagChanges) })}
+                />
             </main>
         </>
     );
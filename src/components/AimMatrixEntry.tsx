@@ -0,0 +1,117 @@
+import { type Component } from 'solid-js';
+import { type SetStoreFunction } from 'solid-js/store';
+
+type AimMatrixEntryProperties = {
+    readonly identifier: number;
+    readonly setAimMatrixEntries: SetStoreFunction<AimMatrixEntryProperties[]>;
+    readonly data: {
+        name: string;
+        animation: string;
+        frame: number;
+        weight_list: string;
+        yaw_parameter: string;
+        yaw_range: number;
+        pitch_parameter: string;
+        pitch_range: number;
+    };
+};
+
+export type { AimMatrixEntryProperties };
+
+const AimMatrixEntry: Component<AimMatrixEntryProperties> = (properties) => {
+    const removeAimMatrix = () => {
+        properties.setAimMatrixEntries((aimMatrices) => aimMatrices.filter((aimMatrix) => aimMatrix.identifier !== properties.identifier));
+    };
+
+    const changeAimMatrix = <K extends keyof AimMatrixEntryProperties['data']>(key: K, value: AimMatrixEntryProperties['data'][K]) => {
+        properties.setAimMatrixEntries((aimMatrix) => aimMatrix.identifier == properties.identifier, 'data', key, value);
+    };
+
+    return (
+        <div class="Aim-Matrix-Entry">
+            <h3>Aim Matrix: {properties.data.name}</h3>
+            <label>
+                Sequence Name:
+                <input name="AimMatrixName" type="text" value={properties.data.name} onChange={(event) => changeAimMatrix('name', event.target.value)} />
+            </label>
+            <br />
+            <label>
+                Center Animation:
+                <input
+                    name="AimMatrixAnimation"
+                    type="text"
+                    list="Animation-Names"
+                    value={properties.data.animation}
+                    onChange={(event) => changeAimMatrix('animation', event.target.value)}
+                />
+            </label>
+            <label>
+                Frame:
+                <input
+                    name="AimMatrixFrame"
+                    type="number"
+                    min="0"
+                    value={properties.data.frame}
+                    onChange={(event) => changeAimMatrix('frame', Math.max(0, Math.floor(event.target.valueAsNumber) || 0))}
+                />
+            </label>
+            <br />
+            <label>
+                Aim Bone Weights:
+                <input
+                    name="AimMatrixWeightList"
+                    type="text"
+                    list="Weight-List-Names"
+                    value={properties.data.weight_list}
+                    onChange={(event) => changeAimMatrix('weight_list', event.target.value)}
+                />
+            </label>
+            <br />
+            <label>
+                Yaw Pose Parameter:
+                <input
+                    name="AimMatrixYawParameter"
+                    type="text"
+                    value={properties.data.yaw_parameter}
+                    onChange={(event) => changeAimMatrix('yaw_parameter', event.target.value)}
+                />
+            </label>
+            <label>
+                Yaw Range:
+                <input
+                    name="AimMatrixYawRange"
+                    type="number"
+                    min="0"
+                    max="180"
+                    value={properties.data.yaw_range}
+                    onChange={(event) => changeAimMatrix('yaw_range', event.target.valueAsNumber || 0)}
+                />
+            </label>
+            <br />
+            <label>
+                Pitch Pose Parameter:
+                <input
+                    name="AimMatrixPitchParameter"
+                    type="text"
+                    value={properties.data.pitch_parameter}
+                    onChange={(event) => changeAimMatrix('pitch_parameter', event.target.value)}
+                />
+            </label>
+            <label>
+                Pitch Range:
+                <input
+                    name="AimMatrixPitchRange"
+                    type="number"
+                    min="0"
+                    max="180"
+                    value={properties.data.pitch_range}
+                    onChange={(event) => changeAimMatrix('pitch_range', event.target.valueAsNumber || 0)}
+                />
+            </label>
+            <br />
+            <button onClick={() => removeAimMatrix()}>Remove</button>
+        </div>
+    );
+};
+
+export default AimMatrixEntry;
@@ -0,0 +1,52 @@
+import { For, type Component } from 'solid-js';
+import { type SetStoreFunction } from 'solid-js/store';
+import AimMatrixEntry, { type AimMatrixEntryProperties } from './AimMatrixEntry';
+
+type AimMatrixMenuProperties = {
+    aimMatrixEntries: AimMatrixEntryProperties[];
+    setAimMatrixEntries: SetStoreFunction<AimMatrixEntryProperties[]>;
+};
+
+export type { AimMatrixMenuProperties };
+
+const AimMatrixMenu: Component<AimMatrixMenuProperties> = (properties) => {
+    const addAimMatrix = () => {
+        properties.setAimMatrixEntries([...properties.aimMatrixEntries, createNewAimMatrix()]);
+    };
+
+    let aimMatrixEntryIdentifierGenerator = 0;
+    const createNewAimMatrix = (): AimMatrixEntryProperties => {
+        return {
+            identifier: aimMatrixEntryIdentifierGenerator++,
+            setAimMatrixEntries: properties.setAimMatrixEntries,
+            data: {
+                name: 'New Aim Matrix',
+                animation: '',
+                frame: 0,
+                weight_list: '',
+                yaw_parameter: 'aim_yaw',
+                yaw_range: 45,
+                pitch_parameter: 'aim_pitch',
+                pitch_range: 45,
+            },
+        };
+    };
+
+    return (
+        <section id="Aim-Matrix-Menu">
+            <h2>Aim Matrices</h2>
+            <p>
+                Each aim matrix generates a delta sequence of nine poses that turn the weighted bones from the center animation across the yaw and pitch ranges.
+                Layer it onto other sequences by its name.
+            </p>
+            <button onClick={() => addAimMatrix()}>Add Aim Matrix</button>
+            <For each={properties.aimMatrixEntries}>
+                {({ identifier, setAimMatrixEntries, data }) => (
+                    <AimMatrixEntry identifier={identifier} setAimMatrixEntries={setAimMatrixEntries} data={data} />
+                )}
+            </For>
+        </section>
+    );
+};
+
+export default AimMatrixMenu;
@@ -1,6 +1,30 @@
-import { createSignal, For, Show, type Component } from 'solid-js';
+import { createEffect, createSignal, For, on, onCleanup, Show, type Component } from 'solid-js';
 import { type SetStoreFunction } from 'solid-js/store';
-import { loadModelFile, unloadModelFile } from './FileOperations';
+import AnimationScrubber from './AnimationScrubber';
+import { type EntryActions } from './Clipboard';
+import { type ImportProgress, loadModelFile, onModelFilesReloaded, prioritizeModelFile, restoreModelFile, unloadModelFile } from './FileOperations';
+import { RecentSourceFileSelect } from './RecentFiles';
+import { loadUserDefaults, saveUserDefaults } from './UserDefaults';
+
+type AnimationMotion = {
+    x: boolean;
+    y: boolean;
+    z: boolean;
+    pieces: number;
+    subtract: boolean;
+};
+
+type AnimationInverseKinematicRule = {
+    chain: string;
+    rule: { type: 'Touch'; bone: string } | { type: 'Footstep' } | { type: 'Release' };
+    start: number;
+    peak: number;
+    tail: number;
+    end: number;
+    contact: number;
+    height: number;
+    radius: number;
+};
 
 type AnimationEntryProperties = {
     readonly identifier: number;
@@ -9,17 +33,28 @@ type AnimationEntryProperties = {
         name: string;
         file_source: string;
         source_animation: string;
+        frame_range: [number, number] | null;
+        fps: number;
+        retarget: boolean;
+        reverse: boolean;
+        speed: number;
+        hold_start: number;
+        hold_end: number;
+        subtract_animation: string;
+        subtract_frame: number;
+        motion: AnimationMotion | null;
+        inverse_kinematic_rules: AnimationInverseKinematicRule[];
     };
 };
 
-export type { AnimationEntryProperties };
+export type { AnimationEntryProperties, AnimationInverseKinematicRule, AnimationMotion };
 
-const AnimationEntry: Component<AnimationEntryProperties> = (properties) => {
+const AnimationEntry: Component<AnimationEntryProperties & EntryActions> = (properties) => {
     const [selectedFile, setSelectedFile] = createSignal('');
+    const [importProgress, setImportProgress] = createSignal<ImportProgress | null>(null);
     const [availableAnimations, setAvailableAnimations] = createSignal<string[]>([]);
 
     const removeAnimation = () => {
-        unloadModelFile(selectedFile());
         properties.setAnimationEntries((animations) => animations.filter((animation) => animation.identifier !== properties.identifier));
     };
 
@@ -35,8 +70,129 @@ const AnimationEntry: Component<AnimationEntryProperties> = (properties) => {
         properties.setAnimationEntries((animation) => animation.identifier == properties.identifier, 'data', 'source_animation', sourceAnimation);
     };
 
+    const changeAnimationFrameRange = (frameRange: [number, number] | null) => {
+        properties.setAnimationEntries((animation) => animation.identifier == properties.identifier, 'data', 'frame_range', frameRange);
+    };
+
+    const changeAnimationFps = (fps: number) => {
+        properties.setAnimationEntries((animation) => animation.identifier == properties.identifier, 'data', 'fps', fps);
+    };
+
+    const changeAnimationRetarget = (retarget: boolean) => {
+        properties.setAnimationEntries((animation) => animation.identifier == properties.identifier, 'data', 'retarget', retarget);
+    };
+
+    const changeAnimationModifier = (modifier: 'reverse' | 'speed' | 'hold_start' | 'hold_end', value: boolean | number) => {
+        properties.setAnimationEntries((animation) => animation.identifier == properties.identifier, 'data', modifier, value);
+    };
+
+    const changeAnimationSubtractAnimation = (subtractAnimation: string) => {
+        properties.setAnimationEntries((animation) => animation.identifier == properties.identifier, 'data', 'subtract_animation', subtractAnimation);
+    };
+
+    const changeAnimationSubtractFrame = (subtractFrame: number) => {
+        properties.setAnimationEntries((animation) => animation.identifier == properties.identifier, 'data', 'subtract_frame', subtractFrame);
+    };
+
+    const changeAnimationMotion = (motion: AnimationMotion | null) => {
+        properties.setAnimationEntries((animation) => animation.identifier == properties.identifier, 'data', 'motion', motion);
+    };
+
+    const addInverseKinematicRule = () => {
+        const rule: AnimationInverseKinematicRule = {
+            chain: '',
+            rule: { type: 'Footstep' },
+            start: 0,
+            peak: 0,
+            tail: 0,
+            end: 0,
+            contact: 0,
+            height: 0,
+            radius: 0,
+        };
+        properties.setAnimationEntries(
+            (animation) => animation.identifier == properties.identifier,
+            'data',
+            'inverse_kinematic_rules',
+            (rules) => [...rules, rule],
+        );
+    };
+
+    const removeInverseKinematicRule = (ruleIndex: number) => {
+        properties.setAnimationEntries(
+            (animation) => animation.identifier == properties.identifier,
+            'data',
+            'inverse_kinematic_rules',
+            (rules) => rules.filter((_, index) => index !== ruleIndex),
+        );
+    };
+
+    const changeInverseKinematicRule = <Key extends keyof AnimationInverseKinematicRule>(
+        ruleIndex: number,
+        key: Key,
+        value: AnimationInverseKinematicRule[Key],
+    ) => {
+        properties.setAnimationEntries((animation) => animation.identifier == properties.identifier, 'data', 'inverse_kinematic_rules', ruleIndex, key, value);
+    };
+
+    const changeInverseKinematicRuleFrame = (ruleIndex: number, key: 'start' | 'peak' | 'tail' | 'end' | 'contact', value: number) => {
+        changeInverseKinematicRule(ruleIndex, key, Math.max(0, Math.floor(value || 0)));
+    };
+
+    const selectFile = async (path?: string) => {
+        if (path !== undefined) {
+            await prioritizeModelFile(path);
+        }
+
+        const loadedFile = await loadModelFile(selectedFile(), setImportProgress, path);
+
+        if (loadedFile === null) {
+            return;
+        }
+
+        setSelectedFile(() => loadedFile.path);
+        changeAnimationFileSource(loadedFile.path);
+
+        const animations = loadedFile.animations.map((animation) => animation.name);
+        changeAnimationSourceAnimation(animations[0]!);
+        setAvailableAnimations(animations);
+    };
+
+    // The file source changes without the file being selected when an edit is undone or redone.
+    createEffect(
+        on(
+            () => properties.data.file_source,
+            async (fileSource) => {
+                if (fileSource === selectedFile()) {
+                    return;
+                }
+
+                const previousFile = selectedFile();
+                setSelectedFile(fileSource);
+                const restoredFile = await restoreModelFile(previousFile, fileSource);
+                setAvailableAnimations(restoredFile === null ? [] : restoredFile.animations.map((animation) => animation.name));
+            },
+        ),
+    );
+
+    onCleanup(() => unloadModelFile(selectedFile()));
+
+    onModelFilesReloaded((files) => {
+        const reloadedFile = files.find((file) => file.path === selectedFile());
+
+        if (reloadedFile === undefined) {
+            return;
+        }
+
+        const animations = reloadedFile.animations.map((animation) => animation.name);
+        if (!animations.includes(properties.data.source_animation)) {
+            changeAnimationSourceAnimation(animations[0] ?? '');
+        }
+        setAvailableAnimations(animations);
+    });
+
     return (
-        <div class="Animation-Entry">
+        <div class="Animation-Entry" onFocusIn={async () => await prioritizeModelFile(selectedFile())}>
             <h3>Animation</h3>
             <label>
                 Name:
@@ -50,32 +206,276 @@ const AnimationEntry: Component<AnimationEntryProperties> = (properties) => {
                     type="text"
                     value={selectedFile()}
                     readonly
-                    onClick={async () => {
-                        const loadedFile = await loadModelFile(selectedFile());
-
-                        if (loadedFile === null) {
-                            return;
-                        }
-
-                        changeAnimationFileSource(loadedFile.path);
-
-                        const animations = loadedFile.animations.map((animation) => animation.name);
-                        changeAnimationSourceAnimation(animations[0]!);
-                        setAvailableAnimations(animations);
-                        setSelectedFile(() => loadedFile.path);
-                    }}
+                    onClick={async () => await selectFile()}
                 />
             </label>
+            <RecentSourceFileSelect name="AnimationRecentFile" onSelect={async (path) => await selectFile(path)} />
+            <Show when={importProgress()}>
+                {(progress) => <progress max={progress().total} value={progress().completed} />}
+            </Show>
             <br />
             <Show when={availableAnimations().length > 0}>
                 <label>
                     Animation:
-                    <select name="AnimationSourceAnimation" onChange={(event) => changeAnimationFileSource(event.target.value)}>
+                    <select
+                        name="AnimationSourceAnimation"
+                        value={properties.data.source_animation}
+                        onChange={(event) => changeAnimationSourceAnimation(event.target.value)}
+                    >
                         <For each={availableAnimations()}>{(animation) => <option value={animation}>{animation}</option>}</For>
                     </select>
                 </label>
+                <AnimationScrubber fileSource={selectedFile()} animation={properties.data.source_animation} />
+            </Show>
+            <br />
+            <label>
+                Use Frame Range:
+                <input
+                    name="AnimationUseFrameRange"
+                    type="checkbox"
+                    checked={properties.data.frame_range !== null}
+                    onChange={(event) => changeAnimationFrameRange(event.target.checked ? [0, 0] : null)}
+                />
+            </label>
+            <Show when={properties.data.frame_range}>
+                {(frameRange) => (
+                    <>
+                        <label>
+                            First Frame:
+                            <input
+                                name="AnimationFrameRangeStart"
+                                type="number"
+                                min="0"
+                                value={frameRange()[0]}
+                                onChange={(event) => changeAnimationFrameRange([Math.max(0, Math.floor(event.target.valueAsNumber || 0)), frameRange()[1]])}
+                            />
+                        </label>
+                        <label>
+                            Last Frame:
+                            <input
+                                name="AnimationFrameRangeEnd"
+                                type="number"
+                                min={frameRange()[0]}
+                                value={frameRange()[1]}
+                                onChange={(event) => changeAnimationFrameRange([frameRange()[0], Math.max(0, Math.floor(event.target.valueAsNumber || 0))])}
+                            />
+                        </label>
+                    </>
+                )}
+            </Show>
+            <br />
+            <label>
+                FPS:
+                <input
+                    name="AnimationFps"
+                    type="number"
+                    min="1"
+                    value={properties.data.fps}
+                    onChange={(event) => changeAnimationFps(Math.max(1, event.target.valueAsNumber || 0))}
+                />
+            </label>
+            <button onClick={() => saveUserDefaults({ ...loadUserDefaults(), animation_fps: properties.data.fps })}>Set As Default</button>
+            <br />
+            <label>
+                Retarget From Source Rest Pose:
+                <input
+                    name="AnimationRetarget"
+                    type="checkbox"
+                    checked={properties.data.retarget}
+                    onChange={(event) => changeAnimationRetarget(event.target.checked)}
+                />
+            </label>
+            <br />
+            <label>
+                Reverse:
+                <input
+                    name="AnimationReverse"
+                    type="checkbox"
+                    checked={properties.data.reverse}
+                    onChange={(event) => changeAnimationModifier('reverse', event.target.checked)}
+                />
+            </label>
+            <label>
+                Speed:
+                <input
+                    name="AnimationSpeed"
+                    type="number"
+                    min="0.01"
+                    max="100"
+                    step="0.01"
+                    value={properties.data.speed}
+                    onChange={(event) => changeAnimationModifier('speed', Math.min(Math.max(0.01, event.target.valueAsNumber || 1), 100))}
+                />
+            </label>
+            <label>
+                Hold First Frame:
+                <input
+                    name="AnimationHoldStart"
+                    type="number"
+                    min="0"
+                    value={properties.data.hold_start}
+                    onChange={(event) => changeAnimationModifier('hold_start', Math.max(0, Math.floor(event.target.valueAsNumber || 0)))}
+                />
+            </label>
+            <label>
+                Hold Last Frame:
+                <input
+                    name="AnimationHoldEnd"
+                    type="number"
+                    min="0"
+                    value={properties.data.hold_end}
+                    onChange={(event) => changeAnimationModifier('hold_end', Math.max(0, Math.floor(event.target.valueAsNumber || 0)))}
+                />
+            </label>
+            <br />
+            <label>
+                Subtract:
+                <input
+                    name="AnimationSubtractAnimation"
+                    type="text"
+                    list="Animation-Names"
+                    value={properties.data.subtract_animation}
+                    onChange={(event) => changeAnimationSubtractAnimation(event.target.value)}
+                />
+            </label>
+            <Show when={properties.data.subtract_animation !== ''}>
+                <label>
+                    Frame:
+                    <input
+                        name="AnimationSubtractFrame"
+                        type="number"
+                        min="0"
+                        value={properties.data.subtract_frame}
+                        onChange={(event) => changeAnimationSubtractFrame(Math.max(0, Math.floor(event.target.valueAsNumber || 0)))}
+                    />
+                </label>
             </Show>
             <br />
+            <label>
+                Extract Motion:
+                <input
+                    name="AnimationExtractMotion"
+                    type="checkbox"
+                    checked={properties.data.motion !== null}
+                    onChange={(event) => changeAnimationMotion(event.target.checked ? { x: true, y: true, z: false, pieces: 1, subtract: true } : null)}
+                />
+            </label>
+            <Show when={properties.data.motion}>
+                {(motion) => (
+                    <>
+                        <For each={['x', 'y', 'z'] as const}>
+                            {(axis) => (
+                                <label>
+                                    {axis.toUpperCase()}:
+                                    <input
+                                        name={`AnimationMotion${axis.toUpperCase()}`}
+                                        type="checkbox"
+                                        checked={motion()[axis]}
+                                        onChange={(event) => changeAnimationMotion({ ...motion(), [axis]: event.target.checked })}
+                                    />
+                                </label>
+                            )}
+                        </For>
+                        <label>
+                            Pieces:
+                            <input
+                                name="AnimationMotionPieces"
+                                type="number"
+                                min="1"
+                                value={motion().pieces}
+                                onChange={(event) => changeAnimationMotion({ ...motion(), pieces: Math.max(1, Math.floor(event.target.valueAsNumber || 0)) })}
+                            />
+                        </label>
+                        <label>
+                            Remove From Root:
+                            <input
+                                name="AnimationMotionSubtract"
+                                type="checkbox"
+                                checked={motion().subtract}
+                                onChange={(event) => changeAnimationMotion({ ...motion(), subtract: event.target.checked })}
+                            />
+                        </label>
+                    </>
+                )}
+            </Show>
+            <br />
+            <label>
+                Inverse Kinematic Rules:
+                <br />
+                <For each={properties.data.inverse_kinematic_rules}>
+                    {(rule, ruleIndex) => (
+                        <div>
+                            <input
+                                name={`AnimationInverseKinematicRule${ruleIndex()}Chain`}
+                                list="Inverse-Kinematic-Chain-Names"
+                                value={rule.chain}
+                                onChange={(event) => changeInverseKinematicRule(ruleIndex(), 'chain', event.target.value)}
+                            />
+                            <select
+                                name={`AnimationInverseKinematicRule${ruleIndex()}Type`}
+                                value={rule.rule.type}
+                                onChange={(event) =>
+                                    changeInverseKinematicRule(
+                                        ruleIndex(),
+                                        'rule',
+                                        event.target.value === 'Touch' ? { type: 'Touch', bone: '' } : { type: event.target.value as 'Footstep' | 'Release' },
+                                    )
+                                }
+                            >
+                                <option value="Footstep">Footstep</option>
+                                <option value="Touch">Touch</option>
+                                <option value="Release">Release</option>
+                            </select>
+                            <Show when={rule.rule.type === 'Touch' && rule.rule}>
+                                {(touch) => (
+                                    <label>
+                                        Bone:
+                                        <input
+                                            name={`AnimationInverseKinematicRule${ruleIndex()}Bone`}
+                                            type="text"
+                                            value={touch().bone}
+                                            onChange={(event) => changeInverseKinematicRule(ruleIndex(), 'rule', { type: 'Touch', bone: event.target.value })}
+                                        />
+                                    </label>
+                                )}
+                            </Show>
+                            <For each={['start', 'peak', 'tail', 'end', 'contact'] as const}>
+                                {(key) => (
+                                    <label>
+                                        {key[0]!.toUpperCase() + key.slice(1)}:
+                                        <input
+                                            name={`AnimationInverseKinematicRule${ruleIndex()}${key}`}
+                                            type="number"
+                                            min="0"
+                                            value={rule[key]}
+                                            onChange={(event) => changeInverseKinematicRuleFrame(ruleIndex(), key, event.target.valueAsNumber)}
+                                        />
+                                    </label>
+                                )}
+                            </For>
+                            <For each={['height', 'radius'] as const}>
+                                {(key) => (
+                                    <label>
+                                        {key[0]!.toUpperCase() + key.slice(1)}:
+                                        <input
+                                            name={`AnimationInverseKinematicRule${ruleIndex()}${key}`}
+                                            type="number"
+                                            min="0"
+                                            value={rule[key]}
+                                            onChange={(event) => changeInverseKinematicRule(ruleIndex(), key, Math.max(0, event.target.valueAsNumber || 0))}
+                                        />
+                                    </label>
+                                )}
+                            </For>
+                            <button onClick={() => removeInverseKinematicRule(ruleIndex())}>-</button>
+                        </div>
+                    )}
+                </For>
+                <button onClick={() => addInverseKinematicRule()}>+</button>
+            </label>
+            <br />
+            <button onClick={() => properties.duplicate()}>Duplicate</button>
+            <button onClick={() => properties.copy()}>Copy</button>
             <button onClick={() => removeAnimation()}>Remove</button>
         </div>
     );
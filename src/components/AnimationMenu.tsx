@@ -1,6 +1,12 @@
-import { type Component, For } from 'solid-js';
+import { type Component, createSignal, For } from 'solid-js';
 import { type SetStoreFunction } from 'solid-js/store';
 import AnimationEntry, { type AnimationEntryProperties } from './AnimationEntry';
+import { canPasteEntry, copyEntry, pasteEntry } from './Clipboard';
+import EntryFilter, { matchesEntryFilter } from './EntryFilter';
+import { fileStem, loadModelFile, onSourceFilesDropped, unloadModelFile } from './FileOperations';
+import { copyState } from './History';
+import { createEntrySelection, SelectableEntryItem, SelectionActions } from './Selection';
+import { loadUserDefaults } from './UserDefaults';
 
 type AnimationMenuProperties = {
     animationEntries: AnimationEntryProperties[];
@@ -14,16 +20,86 @@ const AnimationMenu: Component<AnimationMenuProperties> = (properties) => {
         properties.setAnimationEntries([...properties.animationEntries, createNewAnimation()]);
     };
 
+    const duplicateAnimation = (identifier: number, data: AnimationEntryProperties['data']) => {
+        const index = properties.animationEntries.findIndex((animation) => animation.identifier === identifier);
+        properties.setAnimationEntries((animations) => [
+            ...animations.slice(0, index + 1),
+            createNewAnimation(copyState(data)),
+            ...animations.slice(index + 1),
+        ]);
+    };
+
+    const pasteAnimation = () => {
+        const data = pasteEntry<AnimationEntryProperties['data']>('animation');
+
+        if (data === null) {
+            return;
+        }
+
+        properties.setAnimationEntries([...properties.animationEntries, createNewAnimation(data)]);
+    };
+
+    const [animationFilter, setAnimationFilter] = createSignal('');
+
+    const sortAnimationsByName = () => {
+        properties.setAnimationEntries([...properties.animationEntries].sort((first, second) => first.data.name.localeCompare(second.data.name)));
+    };
+
+    const animationSelection = createEntrySelection(
+        () => properties.animationEntries,
+        (animations) => properties.setAnimationEntries(animations),
+        (data) => createNewAnimation(data),
+    );
+
+    // Every dropped file becomes an animation that uses the first animation of the file.
+    onSourceFilesDropped('Animation-Menu', async (paths) => {
+        const animations: AnimationEntryProperties[] = [];
+
+        for (const path of paths) {
+            const loadedFile = await loadModelFile('', undefined, path);
+
+            if (loadedFile === null) {
+                continue;
+            }
+
+            const animation = createNewAnimation();
+            animation.data.name = fileStem(path);
+            animation.data.file_source = loadedFile.path;
+            animation.data.source_animation = loadedFile.animations[0]?.name ?? '';
+            animations.push(animation);
+        }
+
+        properties.setAnimationEntries([...properties.animationEntries, ...animations]);
+
+        // The created animations load the files themselves, so the files loaded here are released.
+        for (const animation of animations) {
+            await unloadModelFile(animation.data.file_source);
+        }
+    });
+
     let animationEntryIdentifierGenerator = 0;
-    const createNewAnimation = (): AnimationEntryProperties => {
+    const createNewAnimation = (
+        data: AnimationEntryProperties['data'] = {
+            name: 'New Animation',
+            file_source: '',
+            source_animation: '',
+            frame_range: null,
+            fps: loadUserDefaults().animation_fps,
+            retarget: false,
+            reverse: false,
+            speed: 1,
+            hold_start: 0,
+            hold_end: 0,
+            subtract_animation: '',
+            subtract_frame: 0,
+            motion: null,
+            inverse_kinematic_rules: [],
+        },
+    ): AnimationEntryProperties => {
         return {
             identifier: animationEntryIdentifierGenerator++,
             setAnimationEntries: properties.setAnimationEntries,
-            data: {
-                name: 'New Animation',
-                file_source: '',
-                source_animation: '',
-            },
+            data,
         };
     };
 
@@ -31,9 +107,22 @@ const AnimationMenu: Component<AnimationMenuProperties> = (properties) => {
         <section id="Animation-Menu">
             <h2>Animations</h2>
             <button onClick={() => addAnimation()}>Add Animation</button>
+            <button disabled={!canPasteEntry('animation')} onClick={() => pasteAnimation()}>
+                Paste Animation
+            </button>
+            <EntryFilter name="AnimationFilter" value={animationFilter()} onChange={setAnimationFilter} sortByName={sortAnimationsByName} />
+            <SelectionActions selection={animationSelection} />
             <For each={properties.animationEntries}>
                 {({ identifier, setAnimationEntries, data }) => (
-                    <AnimationEntry identifier={identifier} setAnimationEntries={setAnimationEntries} data={data} />
+                    <SelectableEntryItem selection={animationSelection} identifier={identifier} hidden={!matchesEntryFilter(data.name, animationFilter())}>
+                        <AnimationEntry
+                            identifier={identifier}
+                            setAnimationEntries={setAnimationEntries}
+                            data={data}
+                            duplicate={() => duplicateAnimation(identifier, data)}
+                            copy={() => copyEntry('animation', data)}
+                        />
+                    </SelectableEntryItem>
                 )}
             </For>
             <datalist id="Animation-Names">
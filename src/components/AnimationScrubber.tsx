@@ -0,0 +1,90 @@
+import { invoke } from '@tauri-apps/api/core';
+import { createEffect, createSignal, For, on, Show, type Component } from 'solid-js';
+
+type AnimationBonePose = {
+    name: string;
+    position: [number, number, number];
+    rotation: [number, number, number];
+    animated: boolean;
+};
+
+type AnimationFrame = {
+    frame_count: number;
+    bones: AnimationBonePose[];
+};
+
+type AnimationScrubberProperties = {
+    fileSource: string;
+    animation: string;
+};
+
+export type { AnimationBonePose, AnimationFrame, AnimationScrubberProperties };
+
+// Shows the imported animation as it is in the source file, the modifiers of the entry are only applied when compiling.
+const AnimationScrubber: Component<AnimationScrubberProperties> = (properties) => {
+    const [inspecting, setInspecting] = createSignal(false);
+    const [frame, setFrame] = createSignal(0);
+    const [animationFrame, setAnimationFrame] = createSignal<AnimationFrame | null>(null);
+
+    createEffect(
+        on([inspecting, () => properties.fileSource, () => properties.animation, frame], async ([inspect, fileSource, animation, selectedFrame]) => {
+            if (!inspect || fileSource === '' || animation === '') {
+                setAnimationFrame(null);
+                return;
+            }
+
+            setAnimationFrame(await invoke<AnimationFrame | null>('inspect_animation_frame', { path: fileSource, animation, frame: selectedFrame }));
+        }),
+    );
+
+    const formatValues = (values: [number, number, number]) => values.map((value) => value.toFixed(3)).join(', ');
+
+    return (
+        <div>
+            <label>
+                Inspect Frames
+                <input name="AnimationInspectFrames" type="checkbox" checked={inspecting()} onChange={(event) => setInspecting(event.target.checked)} />
+            </label>
+            <Show when={animationFrame()}>
+                {(currentFrame) => (
+                    <>
+                        <br />
+                        <label>
+                            Frame {Math.min(frame(), currentFrame().frame_count - 1)} Of {currentFrame().frame_count - 1}
+                            <input
+                                name="AnimationInspectFrame"
+                                type="range"
+                                min="0"
+                                max={currentFrame().frame_count - 1}
+                                value={frame()}
+                                onInput={(event) => setFrame(event.target.valueAsNumber || 0)}
+                            />
+                        </label>
+                        <table>
+                            <thead>
+                                <tr>
+                                    <th>Bone</th>
+                                    <th>Position</th>
+                                    <th>Rotation (Roll, Pitch, Yaw)</th>
+                                </tr>
+                            </thead>
+                            <tbody>
+                                <For each={currentFrame().bones}>
+                                    {(bone) => (
+                                        <tr title={bone.animated ? undefined : 'The animation has no keys for this bone, it stays in its bind pose'}>
+                                            <td>{bone.animated ? bone.name : `${bone.name} (Bind Pose)`}</td>
+                                            <td>{formatValues(bone.position)}</td>
+                                            <td>{formatValues(bone.rotation)}</td>
+                                        </tr>
+                                    )}
+                                </For>
+                            </tbody>
+                        </table>
+                    </>
+                )}
+            </Show>
+        </div>
+    );
+};
+
+export default AnimationScrubber;
@@ -0,0 +1,46 @@
+import { type Component } from 'solid-js';
+import { type SetStoreFunction } from 'solid-js/store';
+
+type AttachmentEntryProperties = {
+    readonly identifier: number;
+    readonly setAttachmentEntries: SetStoreFunction<AttachmentEntryProperties[]>;
+    readonly data: {
+        name: string;
+        bone: string;
+    };
+};
+
+export type { AttachmentEntryProperties };
+
+const AttachmentEntry: Component<AttachmentEntryProperties> = (properties) => {
+    const removeAttachment = () => {
+        properties.setAttachmentEntries((attachments) => attachments.filter((attachment) => attachment.identifier !== properties.identifier));
+    };
+
+    const changeAttachmentName = (name: string) => {
+        properties.setAttachmentEntries((attachment) => attachment.identifier == properties.identifier, 'data', 'name', name);
+    };
+
+    const changeAttachmentBone = (bone: string) => {
+        properties.setAttachmentEntries((attachment) => attachment.identifier == properties.identifier, 'data', 'bone', bone);
+    };
+
+    return (
+        <div class="Attachment-Entry">
+            <h3>Attachment</h3>
+            <label>
+                Name:
+                <input name="AttachmentName" type="text" value={properties.data.name} onChange={(event) => changeAttachmentName(event.target.value)} />
+            </label>
+            <br />
+            <label>
+                Bone:
+                <input name="AttachmentBone" type="text" value={properties.data.bone} onChange={(event) => changeAttachmentBone(event.target.value)} />
+            </label>
+            <br />
+            <button onClick={() => removeAttachment()}>Remove</button>
+        </div>
+    );
+};
+
+export default AttachmentEntry;
@@ -0,0 +1,43 @@
+import { For, type Component } from 'solid-js';
+import { type SetStoreFunction } from 'solid-js/store';
+import AttachmentEntry, { type AttachmentEntryProperties } from './AttachmentEntry';
+
+type AttachmentMenuProperties = {
+    attachmentEntries: AttachmentEntryProperties[];
+    setAttachmentEntries: SetStoreFunction<AttachmentEntryProperties[]>;
+};
+
+export type { AttachmentMenuProperties };
+
+const AttachmentMenu: Component<AttachmentMenuProperties> = (properties) => {
+    const addAttachment = () => {
+        properties.setAttachmentEntries([...properties.attachmentEntries, createNewAttachment()]);
+    };
+
+    let attachmentEntryIdentifierGenerator = 0;
+    const createNewAttachment = (): AttachmentEntryProperties => {
+        return {
+            identifier: attachmentEntryIdentifierGenerator++,
+            setAttachmentEntries: properties.setAttachmentEntries,
+            data: {
+                name: 'New Attachment',
+                bone: '',
+            },
+        };
+    };
+
+    return (
+        <section id="Attachment-Menu">
+            <h2>Attachments</h2>
+            <p>Each bone is removed from the skeleton and replaced by an attachment on its parent bone.</p>
+            <button onClick={() => addAttachment()}>Add Attachment</button>
+            <For each={properties.attachmentEntries}>
+                {({ identifier, setAttachmentEntries, data }) => (
+                    <AttachmentEntry identifier={identifier} setAttachmentEntries={setAttachmentEntries} data={data} />
+                )}
+            </For>
+        </section>
+    );
+};
+
+export default AttachmentMenu;
@@ -1,40 +1,79 @@
 import { For, type Component } from 'solid-js';
 import { createStore, type SetStoreFunction } from 'solid-js/store';
 import BodyPartModelEntry, { type BodyPartModelEntryProperties } from './BodyPartModelEntry';
+import { canPasteEntry, copyEntry, type EntryActions, pasteEntry } from './Clipboard';
+import { copyState } from './History';
+import { createEntrySelection, SelectableEntryItem, SelectionActions } from './Selection';
 
 type BodyPartEntryProperties = {
     readonly identifier: number;
     readonly setBodyParts: SetStoreFunction<BodyPartEntryProperties[]>;
     readonly data: {
         name: string;
+        // The identifiers of the models, so the picked models stay picked when the models are reordered. No default model picks the first model.
+        default_model: number | null;
+        preview_model: number | null;
         models: BodyPartModelEntryProperties[];
     };
 };
 
 export type { BodyPartEntryProperties };
 
-const BodyPartEntry: Component<BodyPartEntryProperties> = (properties) => {
-    const [bodyPartModelEntries, setBodyPartModelEntries] = createStore<BodyPartModelEntryProperties[]>([]);
+const BodyPartEntry: Component<BodyPartEntryProperties & EntryActions> = (properties) => {
+    // A body part restored by undo or redo already has models, they are moved over to this entry.
+    const [bodyPartModelEntries, setBodyPartModelEntries] = createStore<BodyPartModelEntryProperties[]>(properties.data.models);
+    setBodyPartModelEntries(() => true, 'setBodyPartModels', () => setBodyPartModelEntries);
 
     const addBodyPartModel = () => {
         setBodyPartModelEntries([...bodyPartModelEntries, createNewBodyPartModel()]);
         properties.setBodyParts((bodyPart) => bodyPart.identifier === properties.identifier, 'data', 'models', bodyPartModelEntries);
     };
 
-    let bodyPartModelIdentifierGenerator = 0;
-    const createNewBodyPartModel = (): BodyPartModelEntryProperties => {
+    const duplicateBodyPartModel = (identifier: number, data: BodyPartModelEntryProperties['data']) => {
+        const index = bodyPartModelEntries.findIndex((model) => model.identifier === identifier);
+        setBodyPartModelEntries((models) => [...models.slice(0, index + 1), createNewBodyPartModel(copyState(data)), ...models.slice(index + 1)]);
+    };
+
+    const pasteBodyPartModel = () => {
+        const data = pasteEntry<BodyPartModelEntryProperties['data']>('body-part-model');
+
+        if (data === null) {
+            return;
+        }
+
+        setBodyPartModelEntries([...bodyPartModelEntries, createNewBodyPartModel(data)]);
+        properties.setBodyParts((bodyPart) => bodyPart.identifier === properties.identifier, 'data', 'models', bodyPartModelEntries);
+    };
+
+    let bodyPartModelIdentifierGenerator = Math.max(-1, ...properties.data.models.map((model) => model.identifier)) + 1;
+    const createNewBodyPartModel = (
+        data: BodyPartModelEntryProperties['data'] = {
+            name: 'New Model',
+            blank: false,
+            file_source: '',
+            part_names: [],
+            materials: [],
+            mirror: null,
+            flex_file_source: '',
+        },
+    ): BodyPartModelEntryProperties => {
         return {
             identifier: bodyPartModelIdentifierGenerator++,
             setBodyPartModels: setBodyPartModelEntries,
-            data: {
-                name: 'New Model',
-                blank: false,
-                file_source: '',
-                part_names: [],
-            },
+            data,
         };
     };
 
+    // The body part is given the models again so the change is recorded like adding a model.
+    const modelSelection = createEntrySelection(
+        () => bodyPartModelEntries,
+        (models) => {
+            setBodyPartModelEntries(models);
+            properties.setBodyParts((bodyPart) => bodyPart.identifier === properties.identifier, 'data', 'models', bodyPartModelEntries);
+        },
+        (data) => createNewBodyPartModel(data),
+    );
+
     const removeBodyPart = () => {
         properties.setBodyParts((bodyParts) => bodyParts.filter((bodyPart) => bodyPart.identifier !== properties.identifier));
     };
@@ -43,6 +82,25 @@ const BodyPartEntry: Component<BodyPartEntryProperties> = (properties) => {
         properties.setBodyParts((bodyPart) => bodyPart.identifier === properties.identifier, 'data', 'name', name);
     };
 
+    const changeDefaultModel = (defaultModel: string) => {
+        properties.setBodyParts(
+            (bodyPart) => bodyPart.identifier === properties.identifier,
+            'data',
+            'default_model',
+            defaultModel === '' ? null : Number(defaultModel),
+        );
+    };
+
+    // The bounding boxes are calculated from every model when no preview model is picked.
+    const changePreviewModel = (previewModel: string) => {
+        properties.setBodyParts(
+            (bodyPart) => bodyPart.identifier === properties.identifier,
+            'data',
+            'preview_model',
+            previewModel === '' ? null : Number(previewModel),
+        );
+    };
+
     return (
         <div class="Body-Part-Entry">
             <h3>Body Part</h3>
@@ -50,12 +108,42 @@ const BodyPartEntry: Component<BodyPartEntryProperties> = (properties) => {
                 Name:
                 <input name="BodyPartName" type="text" value={properties.data.name} onChange={(event) => changeBodyPartName(event.target.value)} />
             </label>
+            <label>
+                Default Model:
+                <select name="BodyPartDefaultModel" value={properties.data.default_model ?? ''} onChange={(event) => changeDefaultModel(event.target.value)}>
+                    <option value="">First Model</option>
+                    <For each={bodyPartModelEntries}>{(model) => <option value={model.identifier}>{model.data.name}</option>}</For>
+                </select>
+            </label>
+            <label>
+                Preview Model:
+                <select name="BodyPartPreviewModel" value={properties.data.preview_model ?? ''} onChange={(event) => changePreviewModel(event.target.value)}>
+                    <option value="">Every Model</option>
+                    <For each={bodyPartModelEntries}>{(model) => <option value={model.identifier}>{model.data.name}</option>}</For>
+                </select>
+            </label>
             <br />
             <button onClick={() => addBodyPartModel()}>Add Model</button>
+            <button disabled={!canPasteEntry('body-part-model')} onClick={() => pasteBodyPartModel()}>
+                Paste Model
+            </button>
+            <button onClick={() => properties.duplicate()}>Duplicate</button>
+            <button onClick={() => properties.copy()}>Copy</button>
             <button onClick={() => removeBodyPart()}>Remove</button>
             <h4>Models</h4>
+            <SelectionActions selection={modelSelection} />
             <For each={bodyPartModelEntries}>
-                {({ identifier, setBodyPartModels, data }) => <BodyPartModelEntry identifier={identifier} setBodyPartModels={setBodyPartModels} data={data} />}
+                {({ identifier, setBodyPartModels, data }) => (
+                    <SelectableEntryItem selection={modelSelection} identifier={identifier}>
+                        <BodyPartModelEntry
+                            identifier={identifier}
+                            setBodyPartModels={setBodyPartModels}
+                            data={data}
+                            duplicate={() => duplicateBodyPartModel(identifier, data)}
+                            copy={() => copyEntry('body-part-model', data)}
+                        />
+                    </SelectableEntryItem>
+                )}
             </For>
         </div>
     );
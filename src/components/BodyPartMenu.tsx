@@ -1,28 +1,195 @@
-import { type Component, For } from 'solid-js';
-import { type SetStoreFunction } from 'solid-js/store';
+import { type Component, createSignal, For, Show } from 'solid-js';
+import { createStore, type SetStoreFunction } from 'solid-js/store';
 import BodyPartEntry, { type BodyPartEntryProperties } from './BodyPartEntry';
+import { type BodyPartModelEntryProperties } from './BodyPartModelEntry';
+import { canPasteEntry, copyEntry, pasteEntry } from './Clipboard';
+import { fileStem, type ImportProgress, type LoadedPart, loadModelFile, onSourceFilesDropped, unloadModelFile } from './FileOperations';
+import { copyState } from './History';
+import { createEntrySelection, SelectableEntryItem, SelectionActions } from './Selection';
 
 type BodyPartMenuProperties = {
     bodyPartEntries: BodyPartEntryProperties[];
     setBodyPartEntries: SetStoreFunction<BodyPartEntryProperties[]>;
 };
 
+type SplitGroup = {
+    partNames: Set<string>;
+    materials: string[];
+};
+
 export type { BodyPartMenuProperties };
 
+const groupPartsByMaterial = (parts: LoadedPart[]): Map<string, SplitGroup> => {
+    const groups = new Map<string, SplitGroup>();
+
+    for (const part of parts) {
+        for (const material of Object.keys(part.triangle_counts)) {
+            const group = groups.get(material) ?? { partNames: new Set(), materials: [material] };
+            group.partNames.add(part.name);
+            groups.set(material, group);
+        }
+    }
+
+    return groups;
+};
+
+// Parts are grouped by the first capture group of the pattern, or by the whole match without one.
+const groupPartsByPattern = (parts: LoadedPart[], pattern: RegExp): Map<string, SplitGroup> => {
+    const groups = new Map<string, SplitGroup>();
+
+    for (const part of parts) {
+        const match = pattern.exec(part.name);
+
+        if (match === null) {
+            continue;
+        }
+
+        const name = match[1] ?? match[0];
+        const group = groups.get(name) ?? { partNames: new Set(), materials: [] };
+        group.partNames.add(part.name);
+        groups.set(name, group);
+    }
+
+    return groups;
+};
+
 const BodyPartMenu: Component<BodyPartMenuProperties> = (properties) => {
     const addBodyPart = () => {
         properties.setBodyPartEntries([...properties.bodyPartEntries, createNewBodyPart()]);
     };
 
+    // The copy is placed right after the body part it was made from.
+    const duplicateBodyPart = (identifier: number, data: BodyPartEntryProperties['data']) => {
+        const index = properties.bodyPartEntries.findIndex((bodyPart) => bodyPart.identifier === identifier);
+        properties.setBodyPartEntries((bodyParts) => [...bodyParts.slice(0, index + 1), createNewBodyPart(copyState(data)), ...bodyParts.slice(index + 1)]);
+    };
+
+    const pasteBodyPart = () => {
+        const data = pasteEntry<BodyPartEntryProperties['data']>('body-part');
+
+        if (data === null) {
+            return;
+        }
+
+        properties.setBodyPartEntries([...properties.bodyPartEntries, createNewBodyPart(data)]);
+    };
+
+    const [splitMode, setSplitMode] = createSignal<'material' | 'pattern'>('material');
+    const [splitPattern, setSplitPattern] = createSignal('');
+    const [addBlankModels, setAddBlankModels] = createSignal(true);
+    const [splitImportProgress, setSplitImportProgress] = createSignal<ImportProgress | null>(null);
+
+    const isSplitPatternValid = () => {
+        try {
+            new RegExp(splitPattern());
+            return true;
+        } catch {
+            return false;
+        }
+    };
+
+    const createSplitModels = (fileSource: string, parts: LoadedPart[], name: string, group: SplitGroup) => {
+        const [models, setModels] = createStore<BodyPartModelEntryProperties[]>([]);
+
+        const modelData: BodyPartModelEntryProperties['data'][] = [
+            {
+                name,
+                blank: false,
+                file_source: fileSource,
+                part_names: parts.map((part) => (group.partNames.has(part.name) ? part.name : null)),
+                materials: group.materials,
+                mirror: null,
+                flex_file_source: '',
+            },
+        ];
+
+        if (addBlankModels()) {
+            modelData.push({ name: 'Blank', blank: true, file_source: '', part_names: [], materials: [], mirror: null, flex_file_source: '' });
+        }
+
+        setModels(modelData.map((data, identifier) => ({ identifier, setBodyPartModels: setModels, data })));
+        return models;
+    };
+
+    // Every group of the file becomes its own body part, so the pieces of a file can be switched on their own.
+    const splitFile = async () => {
+        const loadedFile = await loadModelFile('', setSplitImportProgress);
+
+        if (loadedFile === null) {
+            return;
+        }
+
+        const groups = splitMode() === 'material' ? groupPartsByMaterial(loadedFile.parts) : groupPartsByPattern(loadedFile.parts, new RegExp(splitPattern()));
+
+        const bodyParts = [...groups].map(([groupName, group]) => {
+            // Materials are paths, only the last part of the path is used for the name.
+            const name = splitMode() === 'material' ? (groupName.split(/[\\/]/).pop() ?? groupName) : groupName;
+            return createNewBodyPart({
+                name,
+                default_model: null,
+                preview_model: null,
+                models: createSplitModels(loadedFile.path, loadedFile.parts, name, group),
+            });
+        });
+
+        properties.setBodyPartEntries([...properties.bodyPartEntries, ...bodyParts]);
+
+        // The created models load the file themselves, so the file loaded for the split is released.
+        await unloadModelFile(loadedFile.path);
+    };
+
+    const bodyPartSelection = createEntrySelection(
+        () => properties.bodyPartEntries,
+        (bodyParts) => properties.setBodyPartEntries(bodyParts),
+        (data) => createNewBodyPart(data),
+    );
+
+    // Every dropped file becomes a body part with a single model that uses every part of the file.
+    onSourceFilesDropped('Body-Part-Menu', async (paths) => {
+        const bodyParts: BodyPartEntryProperties[] = [];
+
+        for (const path of paths) {
+            const loadedFile = await loadModelFile('', setSplitImportProgress, path);
+
+            if (loadedFile === null) {
+                continue;
+            }
+
+            const [models, setModels] = createStore<BodyPartModelEntryProperties[]>([]);
+            setModels([
+                {
+                    identifier: 0,
+                    setBodyPartModels: setModels,
+                    data: {
+                        name: fileStem(path),
+                        blank: false,
+                        file_source: loadedFile.path,
+                        part_names: loadedFile.parts.map((part) => part.name),
+                        materials: [],
+                        mirror: null,
+                        flex_file_source: '',
+                    },
+                },
+            ]);
+            bodyParts.push(createNewBodyPart({ name: fileStem(path), default_model: null, preview_model: null, models }));
+        }
+
+        properties.setBodyPartEntries([...properties.bodyPartEntries, ...bodyParts]);
+
+        // The created models load the files themselves, so the files loaded here are released.
+        for (const bodyPart of bodyParts) {
+            await unloadModelFile(bodyPart.data.models[0]!.data.file_source);
+        }
+    });
+
     let bodyPartIdentifierGenerator = 0;
-    const createNewBodyPart = (): BodyPartEntryProperties => {
+    const createNewBodyPart = (
+        data: BodyPartEntryProperties['data'] = { name: 'New Body Part', default_model: null, preview_model: null, models: [] },
+    ): BodyPartEntryProperties => {
         return {
             identifier: bodyPartIdentifierGenerator++,
             setBodyParts: properties.setBodyPartEntries,
-            data: {
-                name: 'New Body Part',
-                models: [],
-            },
+            data,
         };
     };
 
@@ -30,8 +197,46 @@ const BodyPartMenu: Component<BodyPartMenuProperties> = (properties) => {
         <section id="Body-Part-Menu">
             <h2>Body Parts</h2>
             <button onClick={() => addBodyPart()}>Add Part</button>
+            <button disabled={!canPasteEntry('body-part')} onClick={() => pasteBodyPart()}>
+                Paste Part
+            </button>
+            <br />
+            <label>
+                Split File By
+                <select name="SplitMode" value={splitMode()} onChange={(event) => setSplitMode(event.target.value as 'material' | 'pattern')}>
+                    <option value="material">Material</option>
+                    <option value="pattern">Part Name Pattern</option>
+                </select>
+            </label>
+            <Show when={splitMode() === 'pattern'}>
+                <label>
+                    Pattern
+                    <input name="SplitPattern" type="text" value={splitPattern()} onChange={(event) => setSplitPattern(event.target.value)} />
+                </label>
+            </Show>
+            <label>
+                Add Blank Models
+                <input name="SplitAddBlankModels" type="checkbox" checked={addBlankModels()} onChange={(event) => setAddBlankModels(event.target.checked)} />
+            </label>
+            <button disabled={splitMode() === 'pattern' && !isSplitPatternValid()} onClick={async () => await splitFile()}>
+                Split File Into Body Parts
+            </button>
+            <Show when={splitImportProgress()}>
+                {(progress) => <progress max={progress().total} value={progress().completed} />}
+            </Show>
+            <SelectionActions selection={bodyPartSelection} />
             <For each={properties.bodyPartEntries}>
-                {({ identifier, setBodyParts, data }) => <BodyPartEntry identifier={identifier} setBodyParts={setBodyParts} data={data} />}
+                {({ identifier, setBodyParts, data }) => (
+                    <SelectableEntryItem selection={bodyPartSelection} identifier={identifier}>
+                        <BodyPartEntry
+                            identifier={identifier}
+                            setBodyParts={setBodyParts}
+                            data={data}
+                            duplicate={() => duplicateBodyPart(identifier, data)}
+                            copy={() => copyEntry('body-part', data)}
+                        />
+                    </SelectableEntryItem>
+                )}
             </For>
         </section>
     );
@@ -1,6 +1,16 @@
-import { type Component, createSignal, For, Show } from 'solid-js';
+import { type Component, createEffect, createSignal, For, on, onCleanup, Show } from 'solid-js';
 import { type SetStoreFunction } from 'solid-js/store';
-import { loadModelFile, unloadModelFile } from './FileOperations';
+import { type EntryActions } from './Clipboard';
+import {
+    type ImportProgress,
+    type LoadedPart,
+    loadModelFile,
+    onModelFilesReloaded,
+    prioritizeModelFile,
+    restoreModelFile,
+    unloadModelFile,
+} from './FileOperations';
+import { RecentSourceFileSelect } from './RecentFiles';
 
 type BodyPartModelEntryProperties = {
     readonly identifier: number;
@@ -10,18 +20,30 @@ type BodyPartModelEntryProperties = {
         blank: boolean;
         file_source: string;
         part_names: (string | null)[];
+        materials: string[];
+        mirror: ModelMirror | null;
+        flex_file_source: string;
     };
 };
 
-export type { BodyPartModelEntryProperties };
+type ModelMirror = {
+    axis: 'X' | 'Y' | 'Z';
+    mirror_bones: boolean;
+};
+
+export type { BodyPartModelEntryProperties, ModelMirror };
 
-const BodyPartModelEntry: Component<BodyPartModelEntryProperties> = (properties) => {
-    const [isBlank, setIsBlank] = createSignal(properties.data.blank);
-    const [availableParts, setAvailableParts] = createSignal<string[]>([]);
+// The engine limits of a single model.
+const maximumModelVertices = 65536;
+const maximumModelTriangles = 65536;
+
+const BodyPartModelEntry: Component<BodyPartModelEntryProperties & EntryActions> = (properties) => {
+    const [availableParts, setAvailableParts] = createSignal<LoadedPart[]>([]);
     const [selectedFile, setSelectedFile] = createSignal('');
+    const [importProgress, setImportProgress] = createSignal<ImportProgress | null>(null);
+    const [selectedFlexFile, setSelectedFlexFile] = createSignal('');
 
     const removeBodyPartModel = () => {
-        unloadModelFile(selectedFile());
         properties.setBodyPartModels((bodyPartModel) => bodyPartModel.filter((model) => model.identifier !== properties.identifier));
     };
 
@@ -31,14 +53,13 @@ const BodyPartModelEntry: Component<BodyPartModelEntryProperties> = (properties)
 
     const changeBodyPartModelBlank = (blank: boolean) => {
         properties.setBodyPartModels((bodyPartModel) => bodyPartModel.identifier === properties.identifier, 'data', 'blank', blank);
-        setIsBlank(blank);
     };
 
     const changeBodyPartModelFileSource = (fileSource: string) => {
         properties.setBodyPartModels((bodyPartModel) => bodyPartModel.identifier === properties.identifier, 'data', 'file_source', fileSource);
     };
 
-    const setBodyPartModelParts = (parts: string[]) => {
+    const setBodyPartModelParts = (parts: (string | null)[]) => {
         properties.setBodyPartModels((bodyPartModel) => bodyPartModel.identifier === properties.identifier, 'data', 'part_names', parts);
     };
 
@@ -46,14 +67,141 @@ const BodyPartModelEntry: Component<BodyPartModelEntryProperties> = (properties)
         properties.setBodyPartModels((bodyPartModel) => bodyPartModel.identifier === properties.identifier, 'data', 'part_names', partIndex, value);
     };
 
+    const clearBodyPartModelMaterials = () => {
+        properties.setBodyPartModels((bodyPartModel) => bodyPartModel.identifier === properties.identifier, 'data', 'materials', []);
+    };
+
+    const changeBodyPartModelMirror = (mirror: ModelMirror | null) => {
+        properties.setBodyPartModels((bodyPartModel) => bodyPartModel.identifier === properties.identifier, 'data', 'mirror', mirror);
+    };
+
+    const changeBodyPartModelFlexFileSource = (flexFileSource: string) => {
+        properties.setBodyPartModels((bodyPartModel) => bodyPartModel.identifier === properties.identifier, 'data', 'flex_file_source', flexFileSource);
+    };
+
+    const isUsedMaterial = (material: string) => properties.data.materials.length === 0 || properties.data.materials.includes(material);
+
+    const countTriangles = (part: LoadedPart, isCounted: (material: string) => boolean = () => true) =>
+        Object.entries(part.triangle_counts).reduce((total, [material, count]) => (isCounted(material) ? total + count : total), 0);
+
+    // Parts of the same file can share materials, so materials are counted once.
+    const selectedPartStatistics = () => {
+        const selectedParts = availableParts().filter((_, index) => properties.data.part_names[index] !== null);
+
+        return {
+            vertices: selectedParts.reduce((total, part) => total + part.vertex_count, 0),
+            triangles: selectedParts.reduce((total, part) => total + countTriangles(part, isUsedMaterial), 0),
+            materials: new Set(selectedParts.flatMap((part) => Object.keys(part.triangle_counts)).filter(isUsedMaterial)).size,
+        };
+    };
+
+    const selectFile = async (path?: string) => {
+        if (path !== undefined) {
+            await prioritizeModelFile(path);
+        }
+
+        const loadedFile = await loadModelFile(selectedFile(), setImportProgress, path);
+
+        if (loadedFile === null) {
+            return;
+        }
+
+        setSelectedFile(() => loadedFile.path);
+        changeBodyPartModelFileSource(loadedFile.path);
+
+        setBodyPartModelParts(loadedFile.parts.map((part) => part.name));
+        setAvailableParts(loadedFile.parts);
+    };
+
+    // The file source changes without the file being selected when an edit is undone or redone.
+    createEffect(
+        on(
+            () => properties.data.file_source,
+            async (fileSource) => {
+                if (fileSource === selectedFile()) {
+                    return;
+                }
+
+                const previousFile = selectedFile();
+                setSelectedFile(fileSource);
+                const restoredFile = await restoreModelFile(previousFile, fileSource);
+                setAvailableParts(restoredFile === null ? [] : restoredFile.parts);
+
+                // Opened projects only store the selected parts, so they are matched to the parts of the file by name.
+                if (restoredFile !== null && restoredFile.parts.length !== properties.data.part_names.length) {
+                    const selectedParts = properties.data.part_names;
+                    setBodyPartModelParts(restoredFile.parts.map((part) => (selectedParts.includes(part.name) ? part.name : null)));
+                }
+            },
+        ),
+    );
+
+    const selectFlexFile = async () => {
+        const loadedFile = await loadModelFile(selectedFlexFile());
+
+        if (loadedFile === null) {
+            return;
+        }
+
+        setSelectedFlexFile(() => loadedFile.path);
+        changeBodyPartModelFlexFileSource(loadedFile.path);
+    };
+
+    const clearFlexFile = async () => {
+        await unloadModelFile(selectedFlexFile());
+        setSelectedFlexFile('');
+        changeBodyPartModelFlexFileSource('');
+    };
+
+    createEffect(
+        on(
+            () => properties.data.flex_file_source,
+            async (flexFileSource) => {
+                if (flexFileSource === selectedFlexFile()) {
+                    return;
+                }
+
+                const previousFile = selectedFlexFile();
+                setSelectedFlexFile(flexFileSource);
+                await restoreModelFile(previousFile, flexFileSource);
+            },
+        ),
+    );
+
+    onCleanup(() => unloadModelFile(selectedFile()));
+    onCleanup(() => unloadModelFile(selectedFlexFile()));
+
+    // Parts keep their selection when the file changes, parts that were added to the file are selected.
+    onModelFilesReloaded((files) => {
+        const reloadedFile = files.find((file) => file.path === selectedFile());
+
+        if (reloadedFile === undefined) {
+            return;
+        }
+
+        const previousParts = availableParts().map((part) => part.name);
+        setBodyPartModelParts(
+            reloadedFile.parts.map((part) => {
+                const previousIndex = previousParts.indexOf(part.name);
+                return previousIndex === -1 ? part.name : properties.data.part_names[previousIndex];
+            }),
+        );
+        setAvailableParts(reloadedFile.parts);
+    });
+
     return (
-        <div class="Body-Part-Model-Entry">
-            <h5>Model</h5>
+        <div class="Body-Part-Model-Entry" onFocusIn={async () => await prioritizeModelFile(selectedFile())}>
+            <h5>{properties.data.blank ? 'Blank Model' : `Model: ${properties.data.name}`}</h5>
             <label>
                 Blank
-                <input name="BodyPartModelBlank" type="checkbox" onChange={() => changeBodyPartModelBlank(!isBlank())} checked={isBlank()}></input>
+                <input
+                    name="BodyPartModelBlank"
+                    type="checkbox"
+                    onChange={() => changeBodyPartModelBlank(!properties.data.blank)}
+                    checked={properties.data.blank}
+                ></input>
             </label>
-            <Show when={!isBlank()}>
+            <Show when={!properties.data.blank}>
                 <br />
                 <label>
                     Name:
@@ -72,42 +220,96 @@ const BodyPartModelEntry: Component<BodyPartModelEntryProperties> = (properties)
                         type="text"
                         value={selectedFile()}
                         readonly
-                        onClick={async () => {
-                            const loadedFile = await loadModelFile(selectedFile());
-
-                            if (loadedFile === null) {
-                                return;
-                            }
-
-                            changeBodyPartModelFileSource(loadedFile.path);
-
-                            const parts = loadedFile.parts.map((part) => part.name);
-                            setBodyPartModelParts(parts);
-                            setAvailableParts(parts);
-                            setSelectedFile(() => loadedFile.path);
-                        }}
+                        onClick={async () => await selectFile()}
                     />
                 </label>
+                <RecentSourceFileSelect name="BodyPartModelRecentFile" onSelect={async (path) => await selectFile(path)} />
+                <Show when={importProgress()}>
+                    {(progress) => <progress max={progress().total} value={progress().completed} />}
+                </Show>
                 <br />
                 <For each={availableParts()}>
-                    {(partName, index) => (
+                    {(part, index) => (
                         <div>
                             <label>
-                                {partName}:
+                                {part.name} ({part.vertex_count} Vertices, {countTriangles(part)} Triangles):
                                 <input
                                     name={`BodyPartModelPart${index()}`}
                                     type="checkbox"
                                     checked={properties.data.part_names[index()] === null ? false : true}
                                     onChange={(event) => {
-                                        changeBodyPartModelPart(index(), event.target.checked ? partName : null);
+                                        changeBodyPartModelPart(index(), event.target.checked ? part.name : null);
                                     }}
                                 ></input>
                             </label>
                         </div>
                     )}
                 </For>
+                <Show when={properties.data.materials.length > 0}>
+                    <p>Only Materials: {properties.data.materials.join(', ')}</p>
+                    <button onClick={() => clearBodyPartModelMaterials()}>Use All Materials</button>
+                </Show>
+                <label>
+                    Mirror
+                    <select
+                        name="BodyPartModelMirrorAxis"
+                        value={properties.data.mirror?.axis ?? ''}
+                        onChange={(event) =>
+                            changeBodyPartModelMirror(
+                                event.target.value === ''
+                                    ? null
+                                    : { axis: event.target.value as ModelMirror['axis'], mirror_bones: properties.data.mirror?.mirror_bones ?? false },
+                            )
+                        }
+                    >
+                        <option value="">None</option>
+                        <option value="X">X Axis</option>
+                        <option value="Y">Y Axis</option>
+                        <option value="Z">Z Axis</option>
+                    </select>
+                </label>
+                <Show when={properties.data.mirror}>
+                    {(mirror) => (
+                        <label>
+                            Swap Left And Right Bones
+                            <input
+                                name="BodyPartModelMirrorBones"
+                                type="checkbox"
+                                checked={mirror().mirror_bones}
+                                onChange={(event) => changeBodyPartModelMirror({ ...mirror(), mirror_bones: event.target.checked })}
+                            />
+                        </label>
+                    )}
+                </Show>
+                <label>
+                    Flex File:
+                    <input
+                        name="BodyPartModelFlexFileSource"
+                        type="text"
+                        value={selectedFlexFile()}
+                        readonly
+                        onClick={async () => await selectFlexFile()}
+                    />
+                </label>
+                <Show when={selectedFlexFile() !== ''}>
+                    <button onClick={async () => await clearFlexFile()}>Clear</button>
+                </Show>
+                <Show when={availableParts().length > 0}>
+                    <p>
+                        Selected: {selectedPartStatistics().vertices} Vertices, {selectedPartStatistics().triangles} Triangles,{' '}
+                        {selectedPartStatistics().materials} Materials
+                    </p>
+                    <Show when={selectedPartStatistics().vertices > maximumModelVertices}>
+                        <p>Warning: The selected parts have more vertices than the {maximumModelVertices} the engine supports in a model!</p>
+                    </Show>
+                    <Show when={selectedPartStatistics().triangles > maximumModelTriangles}>
+                        <p>Warning: The selected parts have more triangles than the {maximumModelTriangles} the engine supports in a model!</p>
+                    </Show>
+                </Show>
             </Show>
             <br />
+            <button onClick={() => properties.duplicate()}>Duplicate</button>
+            <button onClick={() => properties.copy()}>Copy</button>
             <button onClick={() => removeBodyPartModel()}>Remove</button>
         </div>
     );
@@ -0,0 +1,77 @@
+import { For, Show, type Component } from 'solid-js';
+import { type SkeletonBone } from './Skeleton';
+
+type BoneResolution = 'UseModel' | 'UseAnimation' | 'Ignore';
+
+type BoneConflict = {
+    bone: string;
+    resolution: BoneResolution;
+};
+
+type BoneConflictsProperties = {
+    conflicts: SkeletonBone[];
+    resolutions: BoneConflict[];
+    setResolutions: (resolutions: BoneConflict[]) => void;
+    dismiss: () => void;
+};
+
+export type { BoneConflict, BoneConflictsProperties, BoneResolution };
+
+const resolutionNames: [BoneResolution, string][] = [
+    ['UseModel', "Use Model's"],
+    ['UseAnimation', "Use Animation's"],
+    ['Ignore', 'Ignore Bone'],
+];
+
+// Shown when a loaded file disagrees with the merged skeleton, the conflicts that are resolved are removed from the dialog.
+const BoneConflicts: Component<BoneConflictsProperties> = (properties) => {
+    const unresolvedConflicts = () =>
+        properties.conflicts.filter((conflict) => !properties.resolutions.some((resolution) => resolution.bone === conflict.name));
+
+    const resolveConflict = (bone: string, resolution: BoneResolution) => {
+        properties.setResolutions([...properties.resolutions.filter((existing) => existing.bone !== bone), { bone, resolution }]);
+    };
+
+    return (
+        <>
+            <dialog open={unresolvedConflicts().length > 0}>
+                <h3>Skeleton Conflicts</h3>
+                <p>These bones have a different parent or bind pose than the first source file with them.</p>
+                <For each={unresolvedConflicts()}>
+                    {(conflict) => (
+                        <div>
+                            <h4>{conflict.name}</h4>
+                            <For each={conflict.conflicting_sources}>{(source) => <p>Differs In: {source}</p>}</For>
+                            <For each={resolutionNames}>
+                                {([resolution, name]) => <button onClick={() => resolveConflict(conflict.name, resolution)}>{name}</button>}
+                            </For>
+                        </div>
+                    )}
+                </For>
+                <button onClick={() => properties.dismiss()}>Decide Later</button>
+            </dialog>
+            <Show when={properties.resolutions.length > 0}>
+                <h3>Bone Conflict Resolutions</h3>
+                <For each={properties.resolutions}>
+                    {(resolution) => (
+                        <div>
+                            {resolution.bone}
+                            <select
+                                name="BoneResolution"
+                                value={resolution.resolution}
+                                onChange={(event) => resolveConflict(resolution.bone, event.target.value as BoneResolution)}
+                            >
+                                <For each={resolutionNames}>{([value, name]) => <option value={value}>{name}</option>}</For>
+                            </select>
+                            <button onClick={() => properties.setResolutions(properties.resolutions.filter((existing) => existing !== resolution))}>
+                                Remove
+                            </button>
+                        </div>
+                    )}
+                </For>
+            </Show>
+        </>
+    );
+};
+
+export default BoneConflicts;
@@ -0,0 +1,128 @@
+import { For, type Component } from 'solid-js';
+import { type SetStoreFunction } from 'solid-js/store';
+
+type BoneFlexControl = {
+    component: 'X' | 'Y' | 'Z';
+    flex_controller: string;
+    minimum: number;
+    maximum: number;
+};
+
+type BoneFlexDriverEntryProperties = {
+    readonly identifier: number;
+    readonly setBoneFlexDriverEntries: SetStoreFunction<BoneFlexDriverEntryProperties[]>;
+    readonly data: {
+        bone: string;
+        controls: BoneFlexControl[];
+    };
+};
+
+export type { BoneFlexControl, BoneFlexDriverEntryProperties };
+
+const BoneFlexDriverEntry: Component<BoneFlexDriverEntryProperties> = (properties) => {
+    const removeBoneFlexDriver = () => {
+        properties.setBoneFlexDriverEntries((boneFlexDrivers) =>
+            boneFlexDrivers.filter((boneFlexDriver) => boneFlexDriver.identifier !== properties.identifier),
+        );
+    };
+
+    const changeBoneFlexDriverBone = (bone: string) => {
+        properties.setBoneFlexDriverEntries((boneFlexDriver) => boneFlexDriver.identifier == properties.identifier, 'data', 'bone', bone);
+    };
+
+    const addControl = () => {
+        const control: BoneFlexControl = { component: 'X', flex_controller: '', minimum: 0, maximum: 1 };
+        properties.setBoneFlexDriverEntries(
+            (boneFlexDriver) => boneFlexDriver.identifier == properties.identifier,
+            'data',
+            'controls',
+            (controls) => [...controls, control],
+        );
+    };
+
+    const removeControl = (controlIndex: number) => {
+        properties.setBoneFlexDriverEntries(
+            (boneFlexDriver) => boneFlexDriver.identifier == properties.identifier,
+            'data',
+            'controls',
+            (controls) => controls.filter((_, index) => index !== controlIndex),
+        );
+    };
+
+    const changeControl = <Key extends keyof BoneFlexControl>(controlIndex: number, key: Key, value: BoneFlexControl[Key]) => {
+        properties.setBoneFlexDriverEntries(
+            (boneFlexDriver) => boneFlexDriver.identifier == properties.identifier,
+            'data',
+            'controls',
+            controlIndex,
+            key,
+            value,
+        );
+    };
+
+    return (
+        <div class="Bone-Flex-Driver-Entry">
+            <h3>Bone Flex Driver</h3>
+            <label>
+                Bone:
+                <input name="BoneFlexDriverBone" type="text" value={properties.data.bone} onChange={(event) => changeBoneFlexDriverBone(event.target.value)} />
+            </label>
+            <br />
+            <label>
+                Controls:
+                <br />
+                <For each={properties.data.controls}>
+                    {(control, controlIndex) => (
+                        <div>
+                            <label>
+                                Position:
+                                <select
+                                    name={`BoneFlexDriverControl${controlIndex()}Component`}
+                                    value={control.component}
+                                    onChange={(event) => changeControl(controlIndex(), 'component', event.target.value as BoneFlexControl['component'])}
+                                >
+                                    <option value="X">X</option>
+                                    <option value="Y">Y</option>
+                                    <option value="Z">Z</option>
+                                </select>
+                            </label>
+                            <label>
+                                Flex Controller:
+                                <input
+                                    name={`BoneFlexDriverControl${controlIndex()}FlexController`}
+                                    type="text"
+                                    value={control.flex_controller}
+                                    onChange={(event) => changeControl(controlIndex(), 'flex_controller', event.target.value)}
+                                />
+                            </label>
+                            <label>
+                                Minimum:
+                                <input
+                                    name={`BoneFlexDriverControl${controlIndex()}Minimum`}
+                                    type="number"
+                                    value={control.minimum}
+                                    onChange={(event) => changeControl(controlIndex(), 'minimum', event.target.valueAsNumber || 0)}
+                                />
+                            </label>
+                            <label>
+                                Maximum:
+                                <input
+                                    name={`BoneFlexDriverControl${controlIndex()}Maximum`}
+                                    type="number"
+                                    value={control.maximum}
+                                    onChange={(event) => changeControl(controlIndex(), 'maximum', event.target.valueAsNumber || 0)}
+                                />
+                            </label>
+                            <button onClick={() => removeControl(controlIndex())}>-</button>
+                        </div>
+                    )}
+                </For>
+                <button onClick={() => addControl()}>+</button>
+            </label>
+            <br />
+            <button onClick={() => removeBoneFlexDriver()}>Remove</button>
+        </div>
+    );
+};
+
+export default BoneFlexDriverEntry;
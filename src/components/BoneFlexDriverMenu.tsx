@@ -0,0 +1,46 @@
+import { For, type Component } from 'solid-js';
+import { type SetStoreFunction } from 'solid-js/store';
+import BoneFlexDriverEntry, { type BoneFlexDriverEntryProperties } from './BoneFlexDriverEntry';
+
+type BoneFlexDriverMenuProperties = {
+    boneFlexDriverEntries: BoneFlexDriverEntryProperties[];
+    setBoneFlexDriverEntries: SetStoreFunction<BoneFlexDriverEntryProperties[]>;
+};
+
+export type { BoneFlexDriverMenuProperties };
+
+const BoneFlexDriverMenu: Component<BoneFlexDriverMenuProperties> = (properties) => {
+    const addBoneFlexDriver = () => {
+        properties.setBoneFlexDriverEntries([...properties.boneFlexDriverEntries, createNewBoneFlexDriver()]);
+    };
+
+    let boneFlexDriverEntryIdentifierGenerator = 0;
+    const createNewBoneFlexDriver = (): BoneFlexDriverEntryProperties => {
+        return {
+            identifier: boneFlexDriverEntryIdentifierGenerator++,
+            setBoneFlexDriverEntries: properties.setBoneFlexDriverEntries,
+            data: {
+                bone: '',
+                controls: [],
+            },
+        };
+    };
+
+    return (
+        <section id="Bone-Flex-Driver-Menu">
+            <h2>Bone Flex Drivers</h2>
+            <p>
+                Each control sets a flex controller from the position of the bone along an axis of its parent, the minimum sets the controller to 0 and the
+                maximum to 1. Only some engine branches use bone flex drivers.
+            </p>
+            <button onClick={() => addBoneFlexDriver()}>Add Bone Flex Driver</button>
+            <For each={properties.boneFlexDriverEntries}>
+                {({ identifier, setBoneFlexDriverEntries, data }) => (
+                    <BoneFlexDriverEntry identifier={identifier} setBoneFlexDriverEntries={setBoneFlexDriverEntries} data={data} />
+                )}
+            </For>
+        </section>
+    );
+};
+
+export default BoneFlexDriverMenu;
@@ -0,0 +1,69 @@
+import { For, Show, type Component } from 'solid-js';
+
+type BoundingBox = {
+    minimum: [number, number, number];
+    maximum: [number, number, number];
+};
+
+type BoundingBoxInputProperties = {
+    name: string;
+    label: string;
+    value: BoundingBox | null;
+    onChange: (value: BoundingBox | null) => void;
+};
+
+export type { BoundingBox, BoundingBoxInputProperties };
+
+const axes = ['X', 'Y', 'Z'] as const;
+
+// Without an override the compiler calculates the box, so the override starts empty.
+const BoundingBoxInput: Component<BoundingBoxInputProperties> = (properties) => {
+    const changeCorner = (corner: 'minimum' | 'maximum', axis: number, value: number) => {
+        if (properties.value === null) {
+            return;
+        }
+
+        const point: [number, number, number] = [...properties.value[corner]];
+        point[axis] = value || 0;
+        properties.onChange({ ...properties.value, [corner]: point });
+    };
+
+    return (
+        <>
+            <label>
+                Override {properties.label}
+                <input
+                    name={`${properties.name}Override`}
+                    type="checkbox"
+                    checked={properties.value !== null}
+                    onChange={(event) => properties.onChange(event.target.checked ? { minimum: [0, 0, 0], maximum: [0, 0, 0] } : null)}
+                />
+            </label>
+            <Show when={properties.value}>
+                {(boundingBox) => (
+                    <For each={['minimum', 'maximum'] as const}>
+                        {(corner) => (
+                            <div>
+                                <For each={axes}>
+                                    {(axis, axisIndex) => (
+                                        <label>
+                                            {corner[0]!.toUpperCase() + corner.slice(1)} {axis}:
+                                            <input
+                                                name={`${properties.name}${corner}${axis}`}
+                                                type="number"
+                                                value={boundingBox()[corner][axisIndex()]}
+                                                onChange={(event) => changeCorner(corner, axisIndex(), event.target.valueAsNumber)}
+                                            />
+                                        </label>
+                                    )}
+                                </For>
+                            </div>
+                        )}
+                    </For>
+                )}
+            </Show>
+        </>
+    );
+};
+
+export default BoundingBoxInput;
@@ -0,0 +1,32 @@
+import { createSignal } from 'solid-js';
+import { copyState } from './History';
+
+type ClipboardKind = 'body-part' | 'body-part-model' | 'animation' | 'sequence';
+
+type EntryActions = {
+    readonly duplicate: () => void;
+    readonly copy: () => void;
+};
+
+export type { ClipboardKind, EntryActions };
+
+const [copiedEntry, setCopiedEntry] = createSignal<{ kind: ClipboardKind; data: unknown } | null>(null);
+
+const copyEntry = <T,>(kind: ClipboardKind, data: T) => {
+    setCopiedEntry({ kind, data: copyState(data) });
+};
+
+// Every paste gets its own copy so pasting twice does not share data between entries.
+const pasteEntry = <T,>(kind: ClipboardKind): T | null => {
+    const entry = copiedEntry();
+
+    if (entry === null || entry.kind !== kind) {
+        return null;
+    }
+
+    return copyState(entry.data as T);
+};
+
+const canPasteEntry = (kind: ClipboardKind): boolean => copiedEntry()?.kind === kind;
+
+export { canPasteEntry, copyEntry, pasteEntry };
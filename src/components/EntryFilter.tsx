@@ -0,0 +1,27 @@
+import { type Component } from 'solid-js';
+
+type EntryFilterProperties = {
+    name: string;
+    value: string;
+    onChange: (value: string) => void;
+    sortByName: () => void;
+};
+
+export type { EntryFilterProperties };
+
+// Case is ignored so typing part of a name finds it however the source file wrote it.
+const matchesEntryFilter = (name: string, filter: string): boolean => name.toLowerCase().includes(filter.trim().toLowerCase());
+
+const EntryFilter: Component<EntryFilterProperties> = (properties) => (
+    <div>
+        <label>
+            Filter
+            <input name={properties.name} type="search" value={properties.value} onInput={(event) => properties.onChange(event.target.value)} />
+        </label>
+        <button onClick={() => properties.sortByName()}>Sort By Name</button>
+    </div>
+);
+
+export { matchesEntryFilter };
+
+export default EntryFilter;
@@ -1,11 +1,21 @@
 import { invoke } from '@tauri-apps/api/core';
-import { documentDir } from '@tauri-apps/api/path';
+import { listen } from '@tauri-apps/api/event';
+import { documentDir, homeDir } from '@tauri-apps/api/path';
+import { getCurrentWebview } from '@tauri-apps/api/webview';
 import { open } from '@tauri-apps/plugin-dialog';
+import { onCleanup } from 'solid-js';
+import { addRecentFile } from './RecentFiles';
 
 type LoadedFile = LoadedFileData & {
     path: string;
 };
 
+type LoadedPart = {
+    name: string;
+    vertex_count: number;
+    triangle_counts: Record<string, number>;
+};
+
 type LoadedFileData = {
     skeleton: {
         name: string;
@@ -14,38 +24,79 @@ type LoadedFileData = {
     animations: {
         name: string;
     }[];
-    parts: {
-        name: string;
-    }[];
+    parts: LoadedPart[];
+};
+
+const supportedExtensions = ['smd', 'vta', 'obj', 'mdl'];
+
+const modelFileLoadedCallbacks: Set<(path: string) => void> = new Set();
+
+// Not every Linux desktop defines a documents directory, so fall back to the home directory.
+const defaultDialogPath = async (): Promise<string> => {
+    try {
+        return await documentDir();
+    } catch {
+        return await homeDir();
+    }
 };
 
-const loadedModelFiles: Map<string, number> = new Map();
+type ImportProgress = {
+    completed: number;
+    total: number;
+};
 
-const loadModelFile = async (previousPath: string): Promise<LoadedFile | null> => {
-    const selectedFile = await open({
-        defaultPath: await documentDir(),
+// A path skips the file dialog, which is used to open recent files again.
+const loadModelFile = async (
+    previousPath: string,
+    onProgress?: (progress: ImportProgress | null) => void,
+    path?: string,
+): Promise<LoadedFile | null> => {
+    const selectedFile =
+        path ??
+        (await open({
+        defaultPath: await defaultDialogPath(),
         directory: false,
         filters: [
             {
-                extensions: ['smd', 'obj'],
+                // GTK file filters are case sensitive.
+                extensions: [...supportedExtensions, ...supportedExtensions.map((extension) => extension.toUpperCase())],
                 name: 'Supported Files',
             },
         ],
         multiple: false,
         title: 'Select Source File',
-    });
+    }));
 
     if (selectedFile === null) {
         return null;
     }
 
+    // Every import reports its progress on the same event, so only the progress of the selected file is passed on.
+    const unlisten = await listen<{ stage: string; completed: number; total: number }>('source-wrench-import-progress', (event) => {
+        if (event.payload.stage === selectedFile) {
+            onProgress?.({ completed: event.payload.completed, total: event.payload.total });
+        }
+    });
+
+    // Files picked in the dialog are the files the user is setting up, so they are imported before files loaded for other entries.
+    if (path === undefined) {
+        await prioritizeModelFile(selectedFile);
+    }
+
     const loadedFiles: LoadedFileData | null = await invoke('load_file', { path: selectedFile });
 
+    unlisten();
+    onProgress?.(null);
+
     if (loadedFiles === null) {
         return null;
     }
 
-    await manageLoadedModelFiles(previousPath, selectedFile);
+    await unloadModelFile(previousPath);
+    addRecentFile('source_files', selectedFile);
+
+    // The callbacks run once the entry that loaded the file has used it, so the file is part of the project.
+    setTimeout(() => modelFileLoadedCallbacks.forEach((callback) => callback(selectedFile)));
 
     return {
         path: selectedFile,
@@ -53,53 +104,103 @@ const loadModelFile = async (previousPath: string): Promise<LoadedFile | null> =
     };
 };
 
-const manageLoadedModelFiles = async (previousPath: string, path: string) => {
-    if (previousPath === path) {
-        return;
+// Entries that are restored by undo or redo load their file again without asking for it.
+// The backend counts every load, so the file of the entry is released even when the restored file fails to load.
+const restoreModelFile = async (previousPath: string, path: string): Promise<LoadedFile | null> => {
+    if (path === '') {
+        await unloadModelFile(previousPath);
+        return null;
     }
 
-    const filePathCount = loadedModelFiles.get(path) ?? 0;
-    loadedModelFiles.set(path, filePathCount + 1);
-
-    const previousCount = loadedModelFiles.get(previousPath);
+    const loadedFiles: LoadedFileData | null = await invoke('load_file', { path });
+    await unloadModelFile(previousPath);
 
-    if (previousCount === undefined) {
-        return;
+    if (loadedFiles === null) {
+        return null;
     }
 
-    const currentCount = previousCount - 1;
+    return {
+        path,
+        ...loadedFiles,
+    };
+};
 
-    if (currentCount <= 0) {
-        loadedModelFiles.delete(previousPath);
-        await invoke('unload_file', { path: previousPath });
+// Moves the file ahead of the files waiting to be imported, files that are already imported are left as they are.
+const prioritizeModelFile = async (path: string) => {
+    if (path === '') {
         return;
     }
 
-    loadedModelFiles.set(previousPath, currentCount);
+    await invoke('prioritize_file', { path });
 };
 
+// Every load has to be unloaded once, the backend keeps the file until everyone that loaded it has unloaded it.
 const unloadModelFile = async (path: string) => {
     if (path === '') {
         return;
     }
 
-    const filePathCount = loadedModelFiles.get(path) ?? 1;
-    const newCount = filePathCount - 1;
-    if (newCount === 0) {
-        loadedModelFiles.delete(path);
-        await invoke('unload_file', { path });
-        return;
-    }
+    await invoke('unload_file', { path });
+};
 
-    loadedModelFiles.set(path, newCount);
+// Only files picked by the user are passed on, files that entries load again on their own are not.
+const onModelFileLoaded = (callback: (path: string) => void) => {
+    modelFileLoadedCallbacks.add(callback);
+    onCleanup(() => modelFileLoadedCallbacks.delete(callback));
 };
 
-addEventListener('beforeunload', async () => {
-    for (const [path] of loadedModelFiles) {
-        await invoke('unload_file', { path });
-    }
+// Unloads the file no matter how many entries use it, for files the user wants gone from the backend.
+const releaseModelFile = async (path: string) => {
+    await invoke('release_file', { path });
+};
+
+// Files are imported again by the backend when they change on disk while file watching is enabled.
+const onModelFilesReloaded = (callback: (files: LoadedFile[]) => void) => {
+    const unlisten = listen<{ path: string; file: LoadedFileData }[]>('source-wrench-files-reloaded', (event) => {
+        callback(event.payload.map((reloadedFile) => ({ path: reloadedFile.path, ...reloadedFile.file })));
+    });
+
+    onCleanup(async () => (await unlisten)());
+};
+
+// The name of the file without its folders and extension, used to name entries created from a file.
+const fileStem = (path: string): string => (path.split(/[\\/]/).pop() ?? path).replace(/\.[^.]*$/, '');
+
+// Dropped files go to the menu they are dropped on, files that can not be imported are left out.
+const onSourceFilesDropped = (menu: string, callback: (paths: string[]) => void) => {
+    const unlisten = getCurrentWebview().onDragDropEvent((event) => {
+        if (event.payload.type !== 'drop') {
+            return;
+        }
+
+        const paths = event.payload.paths.filter((path) => supportedExtensions.includes(path.split('.').pop()?.toLowerCase() ?? ''));
+        const position = event.payload.position.toLogical(window.devicePixelRatio);
+        const droppedMenu = document.elementFromPoint(position.x, position.y)?.closest('section')?.id;
 
-    loadedModelFiles.clear();
+        if (droppedMenu === menu && paths.length > 0) {
+            callback(paths);
+        }
+    });
+
+    onCleanup(async () => (await unlisten)());
+};
+
+// Reloading the window creates every entry again, so the loads of the previous entries are dropped.
+addEventListener('beforeunload', async () => {
+    await invoke('release_all_files');
 });
 
-export { loadModelFile, unloadModelFile };
+export type { ImportProgress, LoadedPart };
+
+export {
+    defaultDialogPath,
+    fileStem,
+    loadModelFile,
+    onModelFileLoaded,
+    onModelFilesReloaded,
+    onSourceFilesDropped,
+    prioritizeModelFile,
+    releaseModelFile,
+    restoreModelFile,
+    unloadModelFile,
+};
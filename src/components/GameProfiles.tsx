@@ -0,0 +1,146 @@
+import { invoke } from '@tauri-apps/api/core';
+import { open } from '@tauri-apps/plugin-dialog';
+import { createSignal, For, onMount, Show, type Component } from 'solid-js';
+import { defaultDialogPath } from './FileOperations';
+import { loadUserDefaults, saveUserDefaults } from './UserDefaults';
+
+type EngineLimits = {
+    bones: number;
+    materials: number;
+};
+
+type GameProfile = {
+    name: string;
+    export_path: string;
+    target_version: number;
+    // Profiles saved before engine limits were added do not have them.
+    engine_limits?: EngineLimits;
+};
+
+type GameProfilesProperties = {
+    exportPath: string;
+    targetVersion: number;
+    engineLimits: EngineLimits;
+    selectProfile: (profile: GameProfile) => void;
+};
+
+export type { EngineLimits, GameProfile, GameProfilesProperties };
+
+const gameProfilesKey = 'source-wrench-game-profiles';
+
+const loadGameProfiles = (): GameProfile[] => {
+    const savedProfiles = localStorage.getItem(gameProfilesKey);
+
+    if (savedProfiles === null) {
+        return [];
+    }
+
+    try {
+        return JSON.parse(savedProfiles) as GameProfile[];
+    } catch {
+        return [];
+    }
+};
+
+const saveGameProfiles = (profiles: GameProfile[]) => {
+    localStorage.setItem(gameProfilesKey, JSON.stringify(profiles));
+};
+
+const GameProfiles: Component<GameProfilesProperties> = (properties) => {
+    const [profiles, setProfiles] = createSignal(loadGameProfiles());
+    const [selectedProfile, setSelectedProfile] = createSignal('');
+    const [newProfileName, setNewProfileName] = createSignal('');
+
+    // Profiles are found by name, so adding a profile with an existing name replaces it.
+    const addProfile = (profile: GameProfile) => {
+        const updatedProfiles = [...profiles().filter((existingProfile) => existingProfile.name !== profile.name), profile];
+        setProfiles(updatedProfiles);
+        saveGameProfiles(updatedProfiles);
+        selectProfile(profile.name);
+    };
+
+    const selectProfile = (name: string) => {
+        setSelectedProfile(name);
+
+        const profile = profiles().find((profile) => profile.name === name);
+        if (profile !== undefined) {
+            properties.selectProfile(profile);
+        }
+    };
+
+    // The default profile is selected when the app starts, so new projects export to the game the user usually works on.
+    onMount(() => {
+        const defaultProfile = loadUserDefaults().game_profile;
+        if (profiles().some((profile) => profile.name === defaultProfile)) {
+            selectProfile(defaultProfile);
+        }
+    });
+
+    const removeProfile = () => {
+        const updatedProfiles = profiles().filter((profile) => profile.name !== selectedProfile());
+        setProfiles(updatedProfiles);
+        saveGameProfiles(updatedProfiles);
+        setSelectedProfile('');
+    };
+
+    const detectProfile = async () => {
+        const selectedDirectory = await open({
+            defaultPath: await defaultDialogPath(),
+            directory: true,
+            title: 'Select Game Directory With gameinfo.txt',
+        });
+
+        if (selectedDirectory === null) {
+            return;
+        }
+
+        const profile = await invoke<GameProfile | null>('detect_game', { path: selectedDirectory });
+
+        if (profile === null) {
+            return;
+        }
+
+        addProfile(profile);
+    };
+
+    const saveCurrentProfile = () => {
+        if (newProfileName() === '' || properties.exportPath === '') {
+            return;
+        }
+
+        addProfile({
+            name: newProfileName(),
+            export_path: properties.exportPath,
+            target_version: properties.targetVersion,
+            engine_limits: properties.engineLimits,
+        });
+        setNewProfileName('');
+    };
+
+    return (
+        <>
+            <label>
+                Game Profile
+                <select name="GameProfile" value={selectedProfile()} onChange={(event) => selectProfile(event.target.value)}>
+                    <option value="">Custom Export Path</option>
+                    <For each={profiles()}>{(profile) => <option value={profile.name}>{profile.name}</option>}</For>
+                </select>
+            </label>
+            <button onClick={async () => await detectProfile()}>Detect Game</button>
+            <Show when={selectedProfile() !== ''}>
+                <button onClick={() => removeProfile()}>Remove Profile</button>
+                <button onClick={() => saveUserDefaults({ ...loadUserDefaults(), game_profile: selectedProfile() })}>Set As Default</button>
+            </Show>
+            <br />
+            <label>
+                New Profile Name
+                <input name="NewProfileName" type="text" value={newProfileName()} onChange={(event) => setNewProfileName(event.target.value)} />
+            </label>
+            <button disabled={properties.exportPath === ''} onClick={() => saveCurrentProfile()}>
+                Save Export Path As Profile
+            </button>
+        </>
+    );
+};
+
+export default GameProfiles;
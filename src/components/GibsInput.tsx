@@ -0,0 +1,197 @@
+import { For, Show, type Component } from 'solid-js';
+
+type GibModel = {
+    model: string;
+    fade_time: number;
+};
+
+type Gibs = {
+    base: string;
+    health: number;
+    damage_table: string;
+    breakable_model: string;
+    breakable_count: number;
+    breakable_skin: number;
+    gib_models: GibModel[];
+    body_part: string;
+    separate_models: boolean;
+};
+
+type GibsInputProperties = {
+    value: Gibs | null;
+    bodyParts: string[];
+    onChange: (value: Gibs | null) => void;
+};
+
+export type { GibModel, Gibs, GibsInputProperties };
+
+// The physics impact damage tables the engine has, props use the table of their base when none is set.
+const damageTables = ['player', 'player_vehicle', 'npc', 'glass'];
+
+// The gibs are the models of a body part, so they are set up with the same menu as the rest of the model.
+const GibsInput: Component<GibsInputProperties> = (properties) => {
+    const change = <K extends keyof Gibs>(key: K, value: Gibs[K]) => {
+        if (properties.value === null) {
+            return;
+        }
+
+        properties.onChange({ ...properties.value, [key]: value });
+    };
+
+    const changeGibModel = (gibModelIndex: number, gibModel: Partial<GibModel>) => {
+        if (properties.value === null) {
+            return;
+        }
+
+        change(
+            'gib_models',
+            properties.value.gib_models.map((existingGibModel, index) => (index === gibModelIndex ? { ...existingGibModel, ...gibModel } : existingGibModel)),
+        );
+    };
+
+    return (
+        <>
+            <label>
+                Breakable Prop
+                <input
+                    name="BreakableProp"
+                    type="checkbox"
+                    checked={properties.value !== null}
+                    onChange={(event) =>
+                        properties.onChange(
+                            event.target.checked
+                                ? {
+                                      base: 'Wooden.Medium',
+                                      health: 0,
+                                      damage_table: '',
+                                      breakable_model: '',
+                                      breakable_count: 0,
+                                      breakable_skin: 0,
+                                      gib_models: [],
+                                      body_part: '',
+                                      separate_models: true,
+                                  }
+                                : null,
+                        )
+                    }
+                />
+            </label>
+            <Show when={properties.value}>
+                {(gibs) => (
+                    <div>
+                        <label>
+                            Prop Data Base
+                            <input name="GibsBase" type="text" value={gibs().base} onChange={(event) => change('base', event.target.value)} />
+                        </label>
+                        <label>
+                            Health (0 Uses Base)
+                            <input
+                                name="GibsHealth"
+                                type="number"
+                                min="0"
+                                value={gibs().health}
+                                onChange={(event) => change('health', Math.max(Math.floor(event.target.valueAsNumber || 0), 0))}
+                            />
+                        </label>
+                        <label>
+                            Impact Damage Table
+                            <input
+                                name="GibsDamageTable"
+                                type="text"
+                                list="Damage-Table-Names"
+                                value={gibs().damage_table}
+                                onChange={(event) => change('damage_table', event.target.value)}
+                            />
+                            <datalist id="Damage-Table-Names">
+                                <For each={damageTables}>{(damageTable) => <option value={damageTable} />}</For>
+                            </datalist>
+                        </label>
+                        <br />
+                        <label>
+                            Gib Set (From propdata.txt)
+                            <input
+                                name="GibsBreakableModel"
+                                type="text"
+                                value={gibs().breakable_model}
+                                onChange={(event) => change('breakable_model', event.target.value)}
+                            />
+                        </label>
+                        <Show when={gibs().breakable_model !== ''}>
+                            <label>
+                                Gib Count (0 Uses Base)
+                                <input
+                                    name="GibsBreakableCount"
+                                    type="number"
+                                    min="0"
+                                    value={gibs().breakable_count}
+                                    onChange={(event) => change('breakable_count', Math.max(Math.floor(event.target.valueAsNumber || 0), 0))}
+                                />
+                            </label>
+                            <label>
+                                Gib Skin
+                                <input
+                                    name="GibsBreakableSkin"
+                                    type="number"
+                                    min="0"
+                                    value={gibs().breakable_skin}
+                                    onChange={(event) => change('breakable_skin', Math.max(Math.floor(event.target.valueAsNumber || 0), 0))}
+                                />
+                            </label>
+                        </Show>
+                        <br />
+                        <h4>Gib Models</h4>
+                        <p>The models the prop breaks into, the gibs compiled as separate models are added to these.</p>
+                        <For each={gibs().gib_models}>
+                            {(gibModel, gibModelIndex) => (
+                                <div>
+                                    <label>
+                                        Model
+                                        <input
+                                            name={`GibModel${gibModelIndex()}`}
+                                            type="text"
+                                            value={gibModel.model}
+                                            onChange={(event) => changeGibModel(gibModelIndex(), { model: event.target.value })}
+                                        />
+                                    </label>
+                                    <label>
+                                        Fade Time (0 Never Fades)
+                                        <input
+                                            name={`GibModelFadeTime${gibModelIndex()}`}
+                                            type="number"
+                                            min="0"
+                                            value={gibModel.fade_time}
+                                            onChange={(event) => changeGibModel(gibModelIndex(), { fade_time: Math.max(event.target.valueAsNumber || 0, 0) })}
+                                        />
+                                    </label>
+                                    <button onClick={() => change('gib_models', gibs().gib_models.filter((_, index) => index !== gibModelIndex()))}>
+                                        Remove
+                                    </button>
+                                </div>
+                            )}
+                        </For>
+                        <button onClick={() => change('gib_models', [...gibs().gib_models, { model: '', fade_time: 0 }])}>Add Gib Model</button>
+                        <br />
+                        <label>
+                            Gib Body Part
+                            <select name="GibsBodyPart" value={gibs().body_part} onChange={(event) => change('body_part', event.target.value)}>
+                                <option value="">None</option>
+                                <For each={properties.bodyParts}>{(bodyPart) => <option value={bodyPart}>{bodyPart}</option>}</For>
+                            </select>
+                        </label>
+                        <label>
+                            Compile Gibs As Separate Models
+                            <input
+                                name="GibsSeparateModels"
+                                type="checkbox"
+                                checked={gibs().separate_models}
+                                onChange={(event) => change('separate_models', event.target.checked)}
+                            />
+                        </label>
+                    </div>
+                )}
+            </Show>
+        </>
+    );
+};
+
+export default GibsInput;
@@ -0,0 +1,115 @@
+import { batch, createEffect, createSignal, onCleanup } from 'solid-js';
+
+type History = {
+    undo: () => void;
+    redo: () => void;
+    canUndo: () => boolean;
+    canRedo: () => boolean;
+};
+
+export type { History };
+
+const maximumHistoryLength = 100;
+
+// Copies the values of a store so later edits do not change the copy, functions like entry setters are kept as is.
+const copyState = <T,>(value: T): T => {
+    if (Array.isArray(value)) {
+        return value.map((item) => copyState(item)) as T;
+    }
+
+    if (value !== null && typeof value === 'object') {
+        return Object.fromEntries(Object.entries(value).map(([key, item]) => [key, copyState(item)])) as T;
+    }
+
+    return value;
+};
+
+// Text fields keep their own undo while they are being edited.
+const isEditingText = (target: EventTarget | null): boolean => {
+    if (target instanceof HTMLTextAreaElement) {
+        return true;
+    }
+
+    return target instanceof HTMLInputElement && target.type !== 'checkbox';
+};
+
+// Every change to what capture reads is recorded, changes made in the same task are undone together.
+const createHistory = <T,>(capture: () => T, restore: (state: T) => void): History => {
+    const [undoStates, setUndoStates] = createSignal<T[]>([]);
+    const [redoStates, setRedoStates] = createSignal<T[]>([]);
+    let currentState: T | undefined;
+    let restoring = false;
+    let recordingChange = false;
+
+    createEffect(() => {
+        const state = capture();
+
+        if (currentState !== undefined && !restoring && !recordingChange) {
+            const previousState = currentState;
+            setUndoStates((states) => [...states, previousState].slice(-maximumHistoryLength));
+            setRedoStates([]);
+            recordingChange = true;
+            queueMicrotask(() => (recordingChange = false));
+        }
+
+        currentState = state;
+    });
+
+    const restoreState = (state: T) => {
+        restoring = true;
+        batch(() => restore(state));
+        restoring = false;
+    };
+
+    const undo = () => {
+        const state = undoStates().at(-1);
+
+        if (state === undefined || currentState === undefined) {
+            return;
+        }
+
+        setUndoStates((states) => states.slice(0, -1));
+        setRedoStates((states) => [...states, currentState!]);
+        restoreState(state);
+    };
+
+    const redo = () => {
+        const state = redoStates().at(-1);
+
+        if (state === undefined || currentState === undefined) {
+            return;
+        }
+
+        setRedoStates((states) => states.slice(0, -1));
+        setUndoStates((states) => [...states, currentState!]);
+        restoreState(state);
+    };
+
+    const onKeyDown = (event: KeyboardEvent) => {
+        if (!(event.ctrlKey || event.metaKey) || isEditingText(event.target)) {
+            return;
+        }
+
+        const key = event.key.toLowerCase();
+
+        if (key === 'z' && !event.shiftKey) {
+            event.preventDefault();
+            undo();
+        } else if (key === 'y' || (key === 'z' && event.shiftKey)) {
+            event.preventDefault();
+            redo();
+        }
+    };
+
+    document.addEventListener('keydown', onKeyDown);
+    onCleanup(() => document.removeEventListener('keydown', onKeyDown));
+
+    return {
+        undo,
+        redo,
+        canUndo: () => undoStates().length > 0,
+        canRedo: () => redoStates().length > 0,
+    };
+};
+
+export { copyState, createHistory };
@@ -0,0 +1,76 @@
+import { For, type Component } from 'solid-js';
+import { type SetStoreFunction } from 'solid-js/store';
+
+type InverseKinematicChainEntryProperties = {
+    readonly identifier: number;
+    readonly setInverseKinematicChainEntries: SetStoreFunction<InverseKinematicChainEntryProperties[]>;
+    readonly data: {
+        name: string;
+        bone: string;
+        knee_direction: [number, number, number];
+    };
+};
+
+export type { InverseKinematicChainEntryProperties };
+
+const InverseKinematicChainEntry: Component<InverseKinematicChainEntryProperties> = (properties) => {
+    const removeInverseKinematicChain = () => {
+        properties.setInverseKinematicChainEntries((chains) => chains.filter((chain) => chain.identifier !== properties.identifier));
+    };
+
+    const changeInverseKinematicChainName = (name: string) => {
+        properties.setInverseKinematicChainEntries((chain) => chain.identifier == properties.identifier, 'data', 'name', name);
+    };
+
+    const changeInverseKinematicChainBone = (bone: string) => {
+        properties.setInverseKinematicChainEntries((chain) => chain.identifier == properties.identifier, 'data', 'bone', bone);
+    };
+
+    const changeInverseKinematicChainKneeDirection = (axis: number, value: number) => {
+        properties.setInverseKinematicChainEntries((chain) => chain.identifier == properties.identifier, 'data', 'knee_direction', axis, value);
+    };
+
+    return (
+        <div class="Inverse-Kinematic-Chain-Entry">
+            <h3>Inverse Kinematic Chain</h3>
+            <label>
+                Name:
+                <input
+                    name="InverseKinematicChainName"
+                    type="text"
+                    value={properties.data.name}
+                    onChange={(event) => changeInverseKinematicChainName(event.target.value)}
+                />
+            </label>
+            <br />
+            <label>
+                Bone:
+                <input
+                    name="InverseKinematicChainBone"
+                    type="text"
+                    value={properties.data.bone}
+                    onChange={(event) => changeInverseKinematicChainBone(event.target.value)}
+                />
+            </label>
+            <br />
+            Knee Direction:
+            <For each={['X', 'Y', 'Z']}>
+                {(axis, axisIndex) => (
+                    <label>
+                        {axis}:
+                        <input
+                            name={`InverseKinematicChainKneeDirection${axis}`}
+                            type="number"
+                            value={properties.data.knee_direction[axisIndex()]}
+                            onChange={(event) => changeInverseKinematicChainKneeDirection(axisIndex(), event.target.valueAsNumber || 0)}
+                        />
+                    </label>
+                )}
+            </For>
+            <br />
+            <button onClick={() => removeInverseKinematicChain()}>Remove</button>
+        </div>
+    );
+};
+
+export default InverseKinematicChainEntry;
@@ -0,0 +1,47 @@
+import { For, type Component } from 'solid-js';
+import { type SetStoreFunction } from 'solid-js/store';
+import InverseKinematicChainEntry, { type InverseKinematicChainEntryProperties } from './InverseKinematicChainEntry';
+
+type InverseKinematicChainMenuProperties = {
+    inverseKinematicChainEntries: InverseKinematicChainEntryProperties[];
+    setInverseKinematicChainEntries: SetStoreFunction<InverseKinematicChainEntryProperties[]>;
+};
+
+export type { InverseKinematicChainMenuProperties };
+
+const InverseKinematicChainMenu: Component<InverseKinematicChainMenuProperties> = (properties) => {
+    const addInverseKinematicChain = () => {
+        properties.setInverseKinematicChainEntries([...properties.inverseKinematicChainEntries, createNewInverseKinematicChain()]);
+    };
+
+    let inverseKinematicChainEntryIdentifierGenerator = 0;
+    const createNewInverseKinematicChain = (): InverseKinematicChainEntryProperties => {
+        return {
+            identifier: inverseKinematicChainEntryIdentifierGenerator++,
+            setInverseKinematicChainEntries: properties.setInverseKinematicChainEntries,
+            data: {
+                name: '',
+                bone: '',
+                knee_direction: [0, 0, 0],
+            },
+        };
+    };
+
+    return (
+        <section id="Inverse-Kinematic-Chain-Menu">
+            <h2>Inverse Kinematic Chains</h2>
+            <p>Each chain is the bone and its two parents, the knee direction is in model space and is left at zero to let the chain bend freely.</p>
+            <button onClick={() => addInverseKinematicChain()}>Add Inverse Kinematic Chain</button>
+            <For each={properties.inverseKinematicChainEntries}>
+                {({ identifier, setInverseKinematicChainEntries, data }) => (
+                    <InverseKinematicChainEntry identifier={identifier} setInverseKinematicChainEntries={setInverseKinematicChainEntries} data={data} />
+                )}
+            </For>
+            <datalist id="Inverse-Kinematic-Chain-Names">
+                <For each={properties.inverseKinematicChainEntries}>{({ data }) => <option value={data.name}></option>}</For>
+            </datalist>
+        </section>
+    );
+};
+
+export default InverseKinematicChainMenu;
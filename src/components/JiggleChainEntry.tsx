@@ -0,0 +1,105 @@
+import { For, type Component } from 'solid-js';
+import { type SetStoreFunction } from 'solid-js/store';
+
+type JiggleChainEntryProperties = {
+    readonly identifier: number;
+    readonly setJiggleChainEntries: SetStoreFunction<JiggleChainEntryProperties[]>;
+    readonly data: {
+        start_bone: string;
+        end_bone: string;
+        tip_mass: number;
+        stiffness: [number, number];
+        damping: [number, number];
+        angle_limit: number;
+    };
+};
+
+export type { JiggleChainEntryProperties };
+
+const JiggleChainEntry: Component<JiggleChainEntryProperties> = (properties) => {
+    const removeJiggleChain = () => {
+        properties.setJiggleChainEntries((chains) => chains.filter((chain) => chain.identifier !== properties.identifier));
+    };
+
+    const changeJiggleChainBone = (key: 'start_bone' | 'end_bone', bone: string) => {
+        properties.setJiggleChainEntries((chain) => chain.identifier == properties.identifier, 'data', key, bone);
+    };
+
+    const changeJiggleChainSetting = (key: 'tip_mass' | 'angle_limit', value: number) => {
+        properties.setJiggleChainEntries((chain) => chain.identifier == properties.identifier, 'data', key, Math.max(0, value || 0));
+    };
+
+    const changeJiggleChainGradient = (key: 'stiffness' | 'damping', end: number, value: number) => {
+        properties.setJiggleChainEntries((chain) => chain.identifier == properties.identifier, 'data', key, end, Math.max(0, value || 0));
+    };
+
+    return (
+        <div class="Jiggle-Chain-Entry">
+            <h3>Jiggle Chain</h3>
+            <label>
+                Start Bone:
+                <input
+                    name="JiggleChainStartBone"
+                    type="text"
+                    value={properties.data.start_bone}
+                    onChange={(event) => changeJiggleChainBone('start_bone', event.target.value)}
+                />
+            </label>
+            <label>
+                End Bone:
+                <input
+                    name="JiggleChainEndBone"
+                    type="text"
+                    value={properties.data.end_bone}
+                    onChange={(event) => changeJiggleChainBone('end_bone', event.target.value)}
+                />
+            </label>
+            <br />
+            <label>
+                Tip Mass:
+                <input
+                    name="JiggleChainTipMass"
+                    type="number"
+                    min="0"
+                    value={properties.data.tip_mass}
+                    onChange={(event) => changeJiggleChainSetting('tip_mass', event.target.valueAsNumber)}
+                />
+            </label>
+            <label>
+                Angle Limit:
+                <input
+                    name="JiggleChainAngleLimit"
+                    type="number"
+                    min="0"
+                    max="180"
+                    value={properties.data.angle_limit}
+                    onChange={(event) => changeJiggleChainSetting('angle_limit', Math.min(180, event.target.valueAsNumber))}
+                />
+            </label>
+            <br />
+            <For each={[['stiffness', 'Stiffness'] as const, ['damping', 'Damping'] as const]}>
+                {([key, label]) => (
+                    <div>
+                        <For each={['Start', 'Tip']}>
+                            {(end, endIndex) => (
+                                <label>
+                                    {label} At {end}:
+                                    <input
+                                        name={`JiggleChain${label}${end}`}
+                                        type="number"
+                                        min="0"
+                                        value={properties.data[key][endIndex()]}
+                                        onChange={(event) => changeJiggleChainGradient(key, endIndex(), event.target.valueAsNumber)}
+                                    />
+                                </label>
+                            )}
+                        </For>
+                    </div>
+                )}
+            </For>
+            <button onClick={() => removeJiggleChain()}>Remove</button>
+        </div>
+    );
+};
+
+export default JiggleChainEntry;
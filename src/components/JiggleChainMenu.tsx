@@ -0,0 +1,47 @@
+import { For, type Component } from 'solid-js';
+import { type SetStoreFunction } from 'solid-js/store';
+import JiggleChainEntry, { type JiggleChainEntryProperties } from './JiggleChainEntry';
+
+type JiggleChainMenuProperties = {
+    jiggleChainEntries: JiggleChainEntryProperties[];
+    setJiggleChainEntries: SetStoreFunction<JiggleChainEntryProperties[]>;
+};
+
+export type { JiggleChainMenuProperties };
+
+const JiggleChainMenu: Component<JiggleChainMenuProperties> = (properties) => {
+    const addJiggleChain = () => {
+        properties.setJiggleChainEntries([...properties.jiggleChainEntries, createNewJiggleChain()]);
+    };
+
+    let jiggleChainEntryIdentifierGenerator = 0;
+    const createNewJiggleChain = (): JiggleChainEntryProperties => {
+        return {
+            identifier: jiggleChainEntryIdentifierGenerator++,
+            setJiggleChainEntries: properties.setJiggleChainEntries,
+            data: {
+                start_bone: '',
+                end_bone: '',
+                tip_mass: 0,
+                stiffness: [100, 100],
+                damping: [0, 0],
+                angle_limit: 0,
+            },
+        };
+    };
+
+    return (
+        <section id="Jiggle-Chain-Menu">
+            <h2>Jiggle Chains</h2>
+            <p>Every bone from the start bone to the bone above the end bone jiggles, the settings blend from the start of the chain to its tip.</p>
+            <button onClick={() => addJiggleChain()}>Add Jiggle Chain</button>
+            <For each={properties.jiggleChainEntries}>
+                {({ identifier, setJiggleChainEntries, data }) => (
+                    <JiggleChainEntry identifier={identifier} setJiggleChainEntries={setJiggleChainEntries} data={data} />
+                )}
+            </For>
+        </section>
+    );
+};
+
+export default JiggleChainMenu;
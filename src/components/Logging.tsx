@@ -1,15 +1,28 @@
+import { invoke } from '@tauri-apps/api/core';
 import { listen } from '@tauri-apps/api/event';
-import { For, createSignal, type Component } from 'solid-js';
+import { save } from '@tauri-apps/plugin-dialog';
+import { For, Show, createSignal, type Component } from 'solid-js';
+import { defaultDialogPath } from './FileOperations';
 
 type LogEvent = {
+    timestamp: number;
     level: 'Log' | 'Info' | 'Verbose' | 'Debug' | 'Warn' | 'Error';
+    source: 'Application' | 'Import' | 'Process' | 'Write';
     message: string;
 };
 
+type ProgressEvent = {
+    stage: string;
+    completed: number;
+    total: number;
+};
+
 const SourceFileSelector: Component = () => {
     const [emitVerbose, setEmitVerbose] = createSignal(true);
     const [emitDebug, setEmitDebug] = createSignal(true);
     const [logs, setLogs] = createSignal<string[]>([]);
+    const [stage, setStage] = createSignal<ProgressEvent | null>(null);
+    const [progress, setProgress] = createSignal<ProgressEvent | null>(null);
 
     listen('source-wrench-log', (event) => {
         const logEvent = event.payload as LogEvent;
@@ -19,12 +32,57 @@ const SourceFileSelector: Component = () => {
         if (!emitDebug() && logEvent.level === 'Debug') {
             return;
         }
-        setLogs([...logs(), `[${logEvent.level.toUpperCase()}] ${logEvent.message}`]);
+        const time = new Date(logEvent.timestamp).toLocaleTimeString();
+        setLogs([...logs(), `${time} [${logEvent.level.toUpperCase()}] [${logEvent.source.toUpperCase()}] ${logEvent.message}`]);
+    });
+
+    listen('source-wrench-stage', (event) => {
+        setStage(event.payload as ProgressEvent);
+        setProgress(null);
+    });
+
+    listen('source-wrench-progress', (event) => {
+        setProgress(event.payload as ProgressEvent);
     });
 
+    // The backend keeps the latest logs, so the export also has the logs that are hidden here.
+    const exportLog = async () => {
+        const selectedFile = await save({
+            defaultPath: await defaultDialogPath(),
+            filters: [
+                { extensions: ['json'], name: 'JSON Log' },
+                { extensions: ['txt', 'log'], name: 'Text Log' },
+            ],
+            title: 'Export Log',
+        });
+
+        if (selectedFile === null) {
+            return;
+        }
+
+        await invoke('write_log_file', { path: selectedFile });
+    };
+
     return (
         <section id="Logging-Menu">
             <h2>Log</h2>
+            <Show when={stage()}>
+                {(stage) => (
+                    <label>
+                        {stage().stage} (Stage {stage().completed + 1}/{stage().total})
+                        <progress max={stage().total} value={stage().completed} />
+                    </label>
+                )}
+            </Show>
+            <br />
+            <Show when={progress()}>
+                {(progress) => (
+                    <label>
+                        {progress().stage} ({progress().completed}/{progress().total})
+                        <progress max={progress().total} value={progress().completed} />
+                    </label>
+                )}
+            </Show>
             <label>
                 Verbose
                 <input name="Verbose" type="checkbox" checked={true} onChange={(event) => setEmitVerbose(event.target.checked)}></input>
@@ -33,6 +91,7 @@ const SourceFileSelector: Component = () => {
                 Debug
                 <input name="Debug" type="checkbox" checked={true} onChange={(event) => setEmitDebug(event.target.checked)}></input>
             </label>
+            <button onClick={async () => await exportLog()}>Export Log</button>
             <ul>
                 <For each={logs()}>{(log) => <li>{log}</li>}</For>
             </ul>
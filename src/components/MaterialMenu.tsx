@@ -0,0 +1,115 @@
+import { For, Show, type Component } from 'solid-js';
+import { type SetStoreFunction } from 'solid-js/store';
+import MaterialPathEntry, { type MaterialPathEntryProperties } from './MaterialPathEntry';
+import MaterialRenameEntry, { type MaterialRenameEntryProperties } from './MaterialRenameEntry';
+
+type MaterialMenuProperties = {
+    materialPathEntries: MaterialPathEntryProperties[];
+    setMaterialPathEntries: SetStoreFunction<MaterialPathEntryProperties[]>;
+    materialRenameEntries: MaterialRenameEntryProperties[];
+    setMaterialRenameEntries: SetStoreFunction<MaterialRenameEntryProperties[]>;
+    materialNormalization: MaterialNormalization;
+    setMaterialNormalization: (normalization: MaterialNormalization) => void;
+    missingMaterials: string[] | null;
+    checkMaterials: () => Promise<void>;
+};
+
+type MaterialNormalization = {
+    strip_directories: boolean;
+    merge_similar: boolean;
+};
+
+export type { MaterialMenuProperties, MaterialNormalization };
+
+let materialPathEntryIdentifierGenerator = 0;
+const createNewMaterialPath = (setMaterialPathEntries: SetStoreFunction<MaterialPathEntryProperties[]>, path: string = ''): MaterialPathEntryProperties => {
+    return {
+        identifier: materialPathEntryIdentifierGenerator++,
+        setMaterialPathEntries,
+        data: {
+            path,
+        },
+    };
+};
+
+export { createNewMaterialPath };
+
+const MaterialMenu: Component<MaterialMenuProperties> = (properties) => {
+    const addMaterialPath = () => {
+        properties.setMaterialPathEntries([...properties.materialPathEntries, createNewMaterialPath(properties.setMaterialPathEntries)]);
+    };
+
+    const addMaterialRename = () => {
+        properties.setMaterialRenameEntries([...properties.materialRenameEntries, createNewMaterialRename()]);
+    };
+
+    let materialRenameEntryIdentifierGenerator = 0;
+    const createNewMaterialRename = (): MaterialRenameEntryProperties => {
+        return {
+            identifier: materialRenameEntryIdentifierGenerator++,
+            setMaterialRenameEntries: properties.setMaterialRenameEntries,
+            data: {
+                from: '',
+                to: '',
+            },
+        };
+    };
+
+    return (
+        <section id="Material-Menu">
+            <h2>Materials</h2>
+            <p>The engine searches each path under the materials folder in order. Without any paths materials are found from the materials folder itself.</p>
+            <button onClick={() => addMaterialPath()}>Add Material Path</button>
+            <For each={properties.materialPathEntries}>
+                {({ identifier, setMaterialPathEntries, data }) => (
+                    <MaterialPathEntry identifier={identifier} setMaterialPathEntries={setMaterialPathEntries} data={data} />
+                )}
+            </For>
+            <label>
+                Strip Directories From Source Materials
+                <input
+                    name="MaterialStripDirectories"
+                    type="checkbox"
+                    checked={properties.materialNormalization.strip_directories}
+                    onChange={(event) => properties.setMaterialNormalization({ ...properties.materialNormalization, strip_directories: event.target.checked })}
+                />
+            </label>
+            <label>
+                Merge Materials That Only Differ By Case Or Extension
+                <input
+                    name="MaterialMergeSimilar"
+                    type="checkbox"
+                    checked={properties.materialNormalization.merge_similar}
+                    onChange={(event) => properties.setMaterialNormalization({ ...properties.materialNormalization, merge_similar: event.target.checked })}
+                />
+            </label>
+            <p>
+                Renames replace a material name from the source files with the name written to the model. A * in the source material matches any text, which
+                replaces the * in the compiled material.
+            </p>
+            <button onClick={() => addMaterialRename()}>Add Material Rename</button>
+            <For each={properties.materialRenameEntries}>
+                {({ identifier, setMaterialRenameEntries, data }) => (
+                    <MaterialRenameEntry identifier={identifier} setMaterialRenameEntries={setMaterialRenameEntries} data={data} />
+                )}
+            </For>
+            <p>
+                Checking searches for the VMT of every material in the materials folder next to the models folder of the export path. Materials packed in
+                VPK files are not searched, so they are listed as missing.
+            </p>
+            <button onClick={async () => await properties.checkMaterials()}>Check Materials In Game</button>
+            <Show when={properties.missingMaterials}>
+                {(missingMaterials) => (
+                    <Show when={missingMaterials().length > 0} fallback={<p>Every material was found.</p>}>
+                        <p>Missing Materials:</p>
+                        <ul>
+                            <For each={missingMaterials()}>{(material) => <li>{material}</li>}</For>
+                        </ul>
+                    </Show>
+                )}
+            </Show>
+        </section>
+    );
+};
+
+export default MaterialMenu;
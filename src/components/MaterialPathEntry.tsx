@@ -0,0 +1,34 @@
+import { type Component } from 'solid-js';
+import { type SetStoreFunction } from 'solid-js/store';
+
+type MaterialPathEntryProperties = {
+    readonly identifier: number;
+    readonly setMaterialPathEntries: SetStoreFunction<MaterialPathEntryProperties[]>;
+    readonly data: {
+        path: string;
+    };
+};
+
+export type { MaterialPathEntryProperties };
+
+const MaterialPathEntry: Component<MaterialPathEntryProperties> = (properties) => {
+    const removeMaterialPath = () => {
+        properties.setMaterialPathEntries((materialPaths) => materialPaths.filter((materialPath) => materialPath.identifier !== properties.identifier));
+    };
+
+    const changeMaterialPath = (path: string) => {
+        properties.setMaterialPathEntries((materialPath) => materialPath.identifier == properties.identifier, 'data', 'path', path);
+    };
+
+    return (
+        <div class="Material-Path-Entry">
+            <label>
+                Path:
+                <input name="MaterialPath" type="text" value={properties.data.path} onChange={(event) => changeMaterialPath(event.target.value)} />
+            </label>
+            <button onClick={() => removeMaterialPath()}>Remove</button>
+        </div>
+    );
+};
+
+export default MaterialPathEntry;
@@ -0,0 +1,45 @@
+import { type Component } from 'solid-js';
+import { type SetStoreFunction } from 'solid-js/store';
+
+type MaterialRenameEntryProperties = {
+    readonly identifier: number;
+    readonly setMaterialRenameEntries: SetStoreFunction<MaterialRenameEntryProperties[]>;
+    readonly data: {
+        from: string;
+        to: string;
+    };
+};
+
+export type { MaterialRenameEntryProperties };
+
+const MaterialRenameEntry: Component<MaterialRenameEntryProperties> = (properties) => {
+    const removeMaterialRename = () => {
+        properties.setMaterialRenameEntries((materialRenames) =>
+            materialRenames.filter((materialRename) => materialRename.identifier !== properties.identifier),
+        );
+    };
+
+    const changeMaterialRenameFrom = (from: string) => {
+        properties.setMaterialRenameEntries((materialRename) => materialRename.identifier == properties.identifier, 'data', 'from', from);
+    };
+
+    const changeMaterialRenameTo = (to: string) => {
+        properties.setMaterialRenameEntries((materialRename) => materialRename.identifier == properties.identifier, 'data', 'to', to);
+    };
+
+    return (
+        <div class="Material-Rename-Entry">
+            <label>
+                Source Material:
+                <input name="MaterialRenameFrom" type="text" value={properties.data.from} onChange={(event) => changeMaterialRenameFrom(event.target.value)} />
+            </label>
+            <label>
+                Compiled Material:
+                <input name="MaterialRenameTo" type="text" value={properties.data.to} onChange={(event) => changeMaterialRenameTo(event.target.value)} />
+            </label>
+            <button onClick={() => removeMaterialRename()}>Remove</button>
+        </div>
+    );
+};
+
+export default MaterialRenameEntry;
@@ -0,0 +1,49 @@
+import { invoke } from '@tauri-apps/api/core';
+import { open } from '@tauri-apps/plugin-dialog';
+import { For, Show, type Component } from 'solid-js';
+import { defaultDialogPath } from './FileOperations';
+
+type MissingFilesProperties = {
+    files: string[];
+    relocate: (relocatedFiles: Record<string, string>) => void;
+    dismiss: () => void;
+};
+
+export type { MissingFilesProperties };
+
+// Files are found again by name in the selected folder, so a project moved with its sources only needs one folder picked.
+const MissingFiles: Component<MissingFilesProperties> = (properties) => {
+    const searchFolder = async () => {
+        const selectedDirectory = await open({
+            defaultPath: await defaultDialogPath(),
+            directory: true,
+            title: 'Select Folder With Missing Files',
+        });
+
+        if (selectedDirectory === null) {
+            return;
+        }
+
+        const relocatedFiles = await invoke<Record<string, string> | null>('relocate_files', {
+            directory: selectedDirectory,
+            missingFiles: properties.files,
+        });
+
+        if (relocatedFiles !== null) {
+            properties.relocate(relocatedFiles);
+        }
+    };
+
+    return (
+        <Show when={properties.files.length > 0}>
+            <div>
+                <h3>Missing Source Files</h3>
+                <For each={properties.files}>{(file) => <p>{file}</p>}</For>
+                <button onClick={async () => await searchFolder()}>Search Folder</button>
+                <button onClick={() => properties.dismiss()}>Dismiss</button>
+            </div>
+        </Show>
+    );
+};
+
+export default MissingFiles;
@@ -0,0 +1,71 @@
+import { For, type Component } from 'solid-js';
+
+type ModelFlags = {
+    static_prop: boolean;
+    force_opaque: boolean;
+    translucent_two_pass: boolean;
+    ambient_boost: boolean;
+    do_not_cast_shadows: boolean;
+    cast_texture_shadows: boolean;
+};
+
+type ModelFlagsInputProperties = {
+    value: ModelFlags;
+    onChange: (value: ModelFlags) => void;
+};
+
+export type { ModelFlags, ModelFlagsInputProperties };
+
+const createModelFlags = (): ModelFlags => ({
+    static_prop: false,
+    force_opaque: true,
+    translucent_two_pass: false,
+    ambient_boost: false,
+    do_not_cast_shadows: false,
+    cast_texture_shadows: false,
+});
+
+const flags: [keyof ModelFlags, string, string][] = [
+    ['static_prop', 'Static Prop', 'StaticProp'],
+    ['force_opaque', 'Force Opaque', 'ForceOpaque'],
+    ['translucent_two_pass', 'Translucent Two Pass', 'TranslucentTwoPass'],
+    ['ambient_boost', 'Ambient Boost', 'AmbientBoost'],
+    ['do_not_cast_shadows', 'Do Not Cast Shadows', 'DoNotCastShadows'],
+    ['cast_texture_shadows', 'Cast Texture Shadows', 'CastTextureShadows'],
+];
+
+// The engine can only use one flag of each pair, so enabling one disables the other.
+const conflictingFlags: Partial<Record<keyof ModelFlags, keyof ModelFlags>> = {
+    force_opaque: 'translucent_two_pass',
+    translucent_two_pass: 'force_opaque',
+    do_not_cast_shadows: 'cast_texture_shadows',
+    cast_texture_shadows: 'do_not_cast_shadows',
+};
+
+const ModelFlagsInput: Component<ModelFlagsInputProperties> = (properties) => {
+    const changeFlag = (flag: keyof ModelFlags, enabled: boolean) => {
+        const modelFlags = { ...properties.value, [flag]: enabled };
+        const conflictingFlag = conflictingFlags[flag];
+
+        if (enabled && conflictingFlag !== undefined) {
+            modelFlags[conflictingFlag] = false;
+        }
+
+        properties.onChange(modelFlags);
+    };
+
+    return (
+        <For each={flags}>
+            {([flag, label, name]) => (
+                <label>
+                    {label}
+                    <input name={name} type="checkbox" checked={properties.value[flag]} onChange={(event) => changeFlag(flag, event.target.checked)} />
+                </label>
+            )}
+        </For>
+    );
+};
+
+export { createModelFlags };
+
+export default ModelFlagsInput;
@@ -0,0 +1,82 @@
+import { For, type Component } from 'solid-js';
+import { type SetStoreFunction } from 'solid-js/store';
+
+type MouthEntryProperties = {
+    readonly identifier: number;
+    readonly setMouthEntries: SetStoreFunction<MouthEntryProperties[]>;
+    readonly data: {
+        bone: string;
+        forward: [number, number, number];
+        flex: string;
+        flex_controller: string;
+    };
+};
+
+export type { MouthEntryProperties };
+
+const MouthEntry: Component<MouthEntryProperties> = (properties) => {
+    const removeMouth = () => {
+        properties.setMouthEntries((mouths) => mouths.filter((mouth) => mouth.identifier !== properties.identifier));
+    };
+
+    const changeMouthBone = (bone: string) => {
+        properties.setMouthEntries((mouth) => mouth.identifier == properties.identifier, 'data', 'bone', bone);
+    };
+
+    const changeMouthForward = (axis: number, value: number) => {
+        properties.setMouthEntries((mouth) => mouth.identifier == properties.identifier, 'data', 'forward', axis, value);
+    };
+
+    const changeMouthFlex = (flex: string) => {
+        properties.setMouthEntries((mouth) => mouth.identifier == properties.identifier, 'data', 'flex', flex);
+    };
+
+    const changeMouthFlexController = (flexController: string) => {
+        properties.setMouthEntries((mouth) => mouth.identifier == properties.identifier, 'data', 'flex_controller', flexController);
+    };
+
+    return (
+        <div class="Mouth-Entry">
+            <h3>Mouth</h3>
+            <label>
+                Bone:
+                <input name="MouthBone" type="text" value={properties.data.bone} onChange={(event) => changeMouthBone(event.target.value)} />
+            </label>
+            <br />
+            Forward:
+            <For each={['X', 'Y', 'Z']}>
+                {(axis, axisIndex) => (
+                    <label>
+                        {axis}:
+                        <input
+                            name={`MouthForward${axis}`}
+                            type="number"
+                            value={properties.data.forward[axisIndex()]}
+                            onChange={(event) => changeMouthForward(axisIndex(), event.target.valueAsNumber || 0)}
+                        />
+                    </label>
+                )}
+            </For>
+            <br />
+            <label>
+                Flex:
+                <input name="MouthFlex" type="text" value={properties.data.flex} onChange={(event) => changeMouthFlex(event.target.value)} />
+            </label>
+            <br />
+            <label>
+                Flex Controller:
+                <input
+                    name="MouthFlexController"
+                    type="text"
+                    placeholder={properties.data.flex}
+                    value={properties.data.flex_controller}
+                    onChange={(event) => changeMouthFlexController(event.target.value)}
+                />
+            </label>
+            <br />
+            <button onClick={() => removeMouth()}>Remove</button>
+        </div>
+    );
+};
+
+export default MouthEntry;
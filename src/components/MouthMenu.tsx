@@ -0,0 +1,43 @@
+import { For, type Component } from 'solid-js';
+import { type SetStoreFunction } from 'solid-js/store';
+import MouthEntry, { type MouthEntryProperties } from './MouthEntry';
+
+type MouthMenuProperties = {
+    mouthEntries: MouthEntryProperties[];
+    setMouthEntries: SetStoreFunction<MouthEntryProperties[]>;
+};
+
+export type { MouthMenuProperties };
+
+const MouthMenu: Component<MouthMenuProperties> = (properties) => {
+    const addMouth = () => {
+        properties.setMouthEntries([...properties.mouthEntries, createNewMouth()]);
+    };
+
+    let mouthEntryIdentifierGenerator = 0;
+    const createNewMouth = (): MouthEntryProperties => {
+        return {
+            identifier: mouthEntryIdentifierGenerator++,
+            setMouthEntries: properties.setMouthEntries,
+            data: {
+                bone: '',
+                forward: [0, 1, 0],
+                flex: 'mouth',
+                flex_controller: '',
+            },
+        };
+    };
+
+    return (
+        <section id="Mouth-Menu">
+            <h2>Mouths</h2>
+            <p>Lip sync opens each mouth with its flex through the flex controller, the forward direction is in the space of the bone and is used to light the inside of the mouth.</p>
+            <button onClick={() => addMouth()}>Add Mouth</button>
+            <For each={properties.mouthEntries}>
+                {({ identifier, setMouthEntries, data }) => <MouthEntry identifier={identifier} setMouthEntries={setMouthEntries} data={data} />}
+            </For>
+        </section>
+    );
+};
+
+export default MouthMenu;
@@ -0,0 +1,62 @@
+import { For, type Component } from 'solid-js';
+import { type SetStoreFunction } from 'solid-js/store';
+import PhysicsSolidEntry, { createJointLimit, createShape, type PhysicsSolidEntryProperties } from './PhysicsSolidEntry';
+
+type PhysicsMenuProperties = {
+    physicsSolidEntries: PhysicsSolidEntryProperties[];
+    setPhysicsSolidEntries: SetStoreFunction<PhysicsSolidEntryProperties[]>;
+    totalMass: number;
+    setTotalMass: (totalMass: number) => void;
+    fileSources: string[];
+};
+
+export type { PhysicsMenuProperties };
+
+const PhysicsMenu: Component<PhysicsMenuProperties> = (properties) => {
+    const addPhysicsSolid = () => {
+        properties.setPhysicsSolidEntries([...properties.physicsSolidEntries, createNewPhysicsSolid()]);
+    };
+
+    let physicsSolidEntryIdentifierGenerator = 0;
+    const createNewPhysicsSolid = (): PhysicsSolidEntryProperties => {
+        return {
+            identifier: physicsSolidEntryIdentifierGenerator++,
+            setPhysicsSolidEntries: properties.setPhysicsSolidEntries,
+            data: {
+                bone: '',
+                shape: createShape('Capsule'),
+                limits: [createJointLimit(), createJointLimit(), createJointLimit()],
+            },
+        };
+    };
+
+    return (
+        <section id="Physics-Menu">
+            <h2>Physics</h2>
+            <p>Each solid is jointed to the solid of its closest parent bone, a single solid makes a prop. No physics file is written without solids.</p>
+            <label>
+                Total Mass:
+                <input
+                    name="PhysicsTotalMass"
+                    type="number"
+                    min="0"
+                    value={properties.totalMass}
+                    onChange={(event) => properties.setTotalMass(Math.max(0, event.target.valueAsNumber || 0))}
+                />
+            </label>
+            <button onClick={() => addPhysicsSolid()}>Add Solid</button>
+            <For each={properties.physicsSolidEntries}>
+                {({ identifier, setPhysicsSolidEntries, data }) => (
+                    <PhysicsSolidEntry
+                        identifier={identifier}
+                        setPhysicsSolidEntries={setPhysicsSolidEntries}
+                        data={data}
+                        fileSources={properties.fileSources}
+                    />
+                )}
+            </For>
+        </section>
+    );
+};
+
+export default PhysicsMenu;
@@ -0,0 +1,269 @@
+import { For, Show, type Component } from 'solid-js';
+import { type SetStoreFunction } from 'solid-js/store';
+
+type Vector = [number, number, number];
+
+type PhysicsShape =
+    | {
+          type: 'Box';
+          minimum: Vector;
+          maximum: Vector;
+      }
+    | {
+          type: 'Capsule';
+          radius: number;
+          length: number;
+      }
+    | {
+          type: 'Mesh';
+          file_source: string;
+          decomposition: ConvexDecomposition | null;
+      };
+
+type ConvexDecomposition = {
+    maximum_hulls: number;
+    maximum_vertices: number;
+};
+
+type JointLimit = {
+    minimum: number;
+    maximum: number;
+    friction: number;
+};
+
+type PhysicsSolidEntryProperties = {
+    readonly identifier: number;
+    readonly setPhysicsSolidEntries: SetStoreFunction<PhysicsSolidEntryProperties[]>;
+    readonly data: {
+        bone: string;
+        shape: PhysicsShape;
+        limits: [JointLimit, JointLimit, JointLimit];
+    };
+};
+
+export type { PhysicsShape, PhysicsSolidEntryProperties };
+
+const createShape = (type: PhysicsShape['type']): PhysicsShape => {
+    if (type === 'Box') {
+        return { type, minimum: [-4, -4, -4], maximum: [4, 4, 4] };
+    }
+
+    if (type === 'Capsule') {
+        return { type, radius: 4, length: 12 };
+    }
+
+    return { type, file_source: '', decomposition: null };
+};
+
+const createJointLimit = (): JointLimit => ({ minimum: -30, maximum: 30, friction: 0 });
+
+const axes = ['X', 'Y', 'Z'];
+
+const PhysicsSolidEntry: Component<PhysicsSolidEntryProperties & { fileSources: string[] }> = (properties) => {
+    const removePhysicsSolid = () => {
+        properties.setPhysicsSolidEntries((solids) => solids.filter((solid) => solid.identifier !== properties.identifier));
+    };
+
+    const changePhysicsSolidBone = (bone: string) => {
+        properties.setPhysicsSolidEntries((solid) => solid.identifier === properties.identifier, 'data', 'bone', bone);
+    };
+
+    const changeShape = (shape: PhysicsShape) => {
+        properties.setPhysicsSolidEntries((solid) => solid.identifier === properties.identifier, 'data', 'shape', shape);
+    };
+
+    const changeBoxCorner = (corner: 'minimum' | 'maximum', axisIndex: number, value: number) => {
+        const shape = properties.data.shape;
+
+        if (shape.type !== 'Box') {
+            return;
+        }
+
+        const vector: Vector = [...shape[corner]];
+        vector[axisIndex] = value || 0;
+        changeShape({ ...shape, [corner]: vector });
+    };
+
+    const changeJointLimit = (axisIndex: number, key: keyof JointLimit, value: number) => {
+        properties.setPhysicsSolidEntries(
+            (solid) => solid.identifier === properties.identifier,
+            'data',
+            'limits',
+            axisIndex,
+            key,
+            key === 'friction' ? Math.max(0, value || 0) : Math.min(180, Math.max(-180, value || 0)),
+        );
+    };
+
+    const box = () => (properties.data.shape.type === 'Box' ? properties.data.shape : undefined);
+    const capsule = () => (properties.data.shape.type === 'Capsule' ? properties.data.shape : undefined);
+    const mesh = () => (properties.data.shape.type === 'Mesh' ? properties.data.shape : undefined);
+
+    return (
+        <div class="Physics-Solid-Entry">
+            <h3>Physics Solid: {properties.data.bone}</h3>
+            <label>
+                Bone:
+                <input name="PhysicsSolidBone" type="text" value={properties.data.bone} onChange={(event) => changePhysicsSolidBone(event.target.value)} />
+            </label>
+            <label>
+                Shape:
+                <select
+                    name="PhysicsSolidShape"
+                    value={properties.data.shape.type}
+                    onChange={(event) => changeShape(createShape(event.target.value as PhysicsShape['type']))}
+                >
+                    <option value="Box">Box</option>
+                    <option value="Capsule">Capsule</option>
+                    <option value="Mesh">Mesh</option>
+                </select>
+            </label>
+            <Show when={box()}>
+                {(shape) => (
+                    <For each={[['minimum', 'Minimum'] as const, ['maximum', 'Maximum'] as const]}>
+                        {([corner, label]) => (
+                            <div>
+                                {label}
+                                <For each={axes}>
+                                    {(axis, axisIndex) => (
+                                        <label>
+                                            {axis}:
+                                            <input
+                                                name={`PhysicsSolidBox${label}${axis}`}
+                                                type="number"
+                                                value={shape()[corner][axisIndex()]}
+                                                onChange={(event) => changeBoxCorner(corner, axisIndex(), event.target.valueAsNumber)}
+                                            />
+                                        </label>
+                                    )}
+                                </For>
+                            </div>
+                        )}
+                    </For>
+                )}
+            </Show>
+            <Show when={capsule()}>
+                {(shape) => (
+                    <div>
+                        <label>
+                            Radius:
+                            <input
+                                name="PhysicsSolidCapsuleRadius"
+                                type="number"
+                                min="0"
+                                value={shape().radius}
+                                onChange={(event) => changeShape({ ...shape(), radius: Math.max(0, event.target.valueAsNumber || 0) })}
+                            />
+                        </label>
+                        <label>
+                            Length Along X:
+                            <input
+                                name="PhysicsSolidCapsuleLength"
+                                type="number"
+                                min="0"
+                                value={shape().length}
+                                onChange={(event) => changeShape({ ...shape(), length: Math.max(0, event.target.valueAsNumber || 0) })}
+                            />
+                        </label>
+                    </div>
+                )}
+            </Show>
+            <Show when={mesh()}>
+                {(shape) => (
+                    <div>
+                        <label>
+                            Source File:
+                            <select
+                                name="PhysicsSolidMeshFile"
+                                value={shape().file_source}
+                                onChange={(event) => changeShape({ ...shape(), file_source: event.target.value })}
+                            >
+                                <option value="">None</option>
+                                <For each={properties.fileSources}>{(fileSource) => <option value={fileSource}>{fileSource}</option>}</For>
+                            </select>
+                        </label>
+                        <p>Uses the vertices of the file that are mostly weighted to the bone.</p>
+                        <label>
+                            Decompose Into Convex Hulls:
+                            <input
+                                name="PhysicsSolidMeshDecompose"
+                                type="checkbox"
+                                checked={shape().decomposition !== null}
+                                onChange={(event) =>
+                                    changeShape({ ...shape(), decomposition: event.target.checked ? { maximum_hulls: 8, maximum_vertices: 32 } : null })
+                                }
+                            />
+                        </label>
+                        <Show when={shape().decomposition}>
+                            {(decomposition) => (
+                                <>
+                                    <label>
+                                        Maximum Hulls:
+                                        <input
+                                            name="PhysicsSolidMeshMaximumHulls"
+                                            type="number"
+                                            min="1"
+                                            value={decomposition().maximum_hulls}
+                                            onChange={(event) =>
+                                                changeShape({
+                                                    ...shape(),
+                                                    decomposition: { ...decomposition(), maximum_hulls: Math.max(1, Math.floor(event.target.valueAsNumber || 0)) },
+                                                })
+                                            }
+                                        />
+                                    </label>
+                                    <label>
+                                        Maximum Vertices Per Hull:
+                                        <input
+                                            name="PhysicsSolidMeshMaximumVertices"
+                                            type="number"
+                                            min="4"
+                                            value={decomposition().maximum_vertices}
+                                            onChange={(event) =>
+                                                changeShape({
+                                                    ...shape(),
+                                                    decomposition: { ...decomposition(), maximum_vertices: Math.max(4, Math.floor(event.target.valueAsNumber || 0)) },
+                                                })
+                                            }
+                                        />
+                                    </label>
+                                    <p>
+                                        Each connected piece of the mesh becomes a hull, the closest pieces are merged until there are few enough hulls. Pieces
+                                        that are still concave are then cut in two while there are hulls left.
+                                    </p>
+                                </>
+                            )}
+                        </Show>
+                    </div>
+                )}
+            </Show>
+            <h4>Joint Limits</h4>
+            <For each={axes}>
+                {(axis, axisIndex) => (
+                    <div>
+                        <For each={[['minimum', 'Minimum'] as const, ['maximum', 'Maximum'] as const, ['friction', 'Friction'] as const]}>
+                            {([key, label]) => (
+                                <label>
+                                    {axis} {label}:
+                                    <input
+                                        name={`PhysicsSolidLimit${axis}${label}`}
+                                        type="number"
+                                        min={key === 'friction' ? '0' : '-180'}
+                                        max={key === 'friction' ? undefined : '180'}
+                                        value={properties.data.limits[axisIndex()]![key]}
+                                        onChange={(event) => changeJointLimit(axisIndex(), key, event.target.valueAsNumber)}
+                                    />
+                                </label>
+                            )}
+                        </For>
+                    </div>
+                )}
+            </For>
+            <button onClick={() => removePhysicsSolid()}>Remove</button>
+        </div>
+    );
+};
+
+export { createJointLimit, createShape };
+
+export default PhysicsSolidEntry;
@@ -0,0 +1,266 @@
+import { For, Show, type Component } from 'solid-js';
+import { type SetStoreFunction } from 'solid-js/store';
+
+type Vector = [number, number, number];
+
+type QuaternionTrigger = {
+    tolerance: number;
+    trigger: Vector;
+    position: Vector;
+    rotation: Vector;
+};
+
+type Procedural =
+    | {
+          type: 'AxisInterpolation';
+          axis: 'X' | 'Y' | 'Z';
+          positions: Vector[];
+          rotations: Vector[];
+      }
+    | {
+          type: 'QuaternionInterpolation';
+          triggers: QuaternionTrigger[];
+      };
+
+type ProceduralBoneEntryProperties = {
+    readonly identifier: number;
+    readonly setProceduralBoneEntries: SetStoreFunction<ProceduralBoneEntryProperties[]>;
+    readonly data: {
+        bone: string;
+        control_bone: string;
+        procedural: Procedural;
+    };
+};
+
+export type { Procedural, ProceduralBoneEntryProperties };
+
+const axisDirections = ['X+', 'X-', 'Y+', 'Y-', 'Z+', 'Z-'];
+
+const createTrigger = (): QuaternionTrigger => ({ tolerance: 90, trigger: [0, 0, 0], position: [0, 0, 0], rotation: [0, 0, 0] });
+
+const createProcedural = (type: Procedural['type']): Procedural => {
+    if (type === 'AxisInterpolation') {
+        return {
+            type,
+            axis: 'X',
+            positions: axisDirections.map(() => [0, 0, 0]),
+            rotations: axisDirections.map(() => [0, 0, 0]),
+        };
+    }
+
+    return { type, triggers: [createTrigger()] };
+};
+
+const VectorInput: Component<{ name: string; value: Vector; onChange: (value: Vector) => void }> = (properties) => {
+    return (
+        <For each={['X', 'Y', 'Z']}>
+            {(axis, axisIndex) => (
+                <label>
+                    {axis}:
+                    <input
+                        name={`${properties.name}${axis}`}
+                        type="number"
+                        value={properties.value[axisIndex()]}
+                        onChange={(event) => {
+                            const vector: Vector = [...properties.value];
+                            vector[axisIndex()] = event.target.valueAsNumber || 0;
+                            properties.onChange(vector);
+                        }}
+                    />
+                </label>
+            )}
+        </For>
+    );
+};
+
+const ProceduralBoneEntry: Component<ProceduralBoneEntryProperties> = (properties) => {
+    const removeProceduralBone = () => {
+        properties.setProceduralBoneEntries((proceduralBones) =>
+            proceduralBones.filter((proceduralBone) => proceduralBone.identifier !== properties.identifier),
+        );
+    };
+
+    const changeProceduralBoneBone = (bone: string) => {
+        properties.setProceduralBoneEntries((proceduralBone) => proceduralBone.identifier === properties.identifier, 'data', 'bone', bone);
+    };
+
+    const changeProceduralBoneControlBone = (bone: string) => {
+        properties.setProceduralBoneEntries((proceduralBone) => proceduralBone.identifier === properties.identifier, 'data', 'control_bone', bone);
+    };
+
+    const changeProcedural = (procedural: Procedural) => {
+        properties.setProceduralBoneEntries((proceduralBone) => proceduralBone.identifier === properties.identifier, 'data', 'procedural', procedural);
+    };
+
+    const changeTrigger = (triggerIndex: number, trigger: Partial<QuaternionTrigger>) => {
+        const procedural = properties.data.procedural;
+
+        if (procedural.type !== 'QuaternionInterpolation') {
+            return;
+        }
+
+        changeProcedural({
+            ...procedural,
+            triggers: procedural.triggers.map((existing, index) => (index === triggerIndex ? { ...existing, ...trigger } : existing)),
+        });
+    };
+
+    const addTrigger = () => {
+        const procedural = properties.data.procedural;
+
+        if (procedural.type !== 'QuaternionInterpolation') {
+            return;
+        }
+
+        changeProcedural({ ...procedural, triggers: [...procedural.triggers, createTrigger()] });
+    };
+
+    const removeTrigger = (triggerIndex: number) => {
+        const procedural = properties.data.procedural;
+
+        if (procedural.type !== 'QuaternionInterpolation') {
+            return;
+        }
+
+        changeProcedural({ ...procedural, triggers: procedural.triggers.filter((_, index) => index !== triggerIndex) });
+    };
+
+    const changeAxisTransform = (kind: 'positions' | 'rotations', directionIndex: number, value: Vector) => {
+        const procedural = properties.data.procedural;
+
+        if (procedural.type !== 'AxisInterpolation') {
+            return;
+        }
+
+        changeProcedural({ ...procedural, [kind]: procedural[kind].map((existing, index) => (index === directionIndex ? value : existing)) });
+    };
+
+    const axisInterpolation = () => (properties.data.procedural.type === 'AxisInterpolation' ? properties.data.procedural : undefined);
+    const quaternionInterpolation = () => (properties.data.procedural.type === 'QuaternionInterpolation' ? properties.data.procedural : undefined);
+
+    return (
+        <div class="Procedural-Bone-Entry">
+            <h3>Procedural Bone: {properties.data.bone}</h3>
+            <label>
+                Bone:
+                <input name="ProceduralBoneBone" type="text" value={properties.data.bone} onChange={(event) => changeProceduralBoneBone(event.target.value)} />
+            </label>
+            <br />
+            <label>
+                Control Bone:
+                <input
+                    name="ProceduralBoneControlBone"
+                    type="text"
+                    value={properties.data.control_bone}
+                    onChange={(event) => changeProceduralBoneControlBone(event.target.value)}
+                />
+            </label>
+            <br />
+            <label>
+                Type:
+                <select
+                    name="ProceduralBoneType"
+                    value={properties.data.procedural.type}
+                    onChange={(event) => changeProcedural(createProcedural(event.target.value as Procedural['type']))}
+                >
+                    <option value="AxisInterpolation">Axis Interpolation</option>
+                    <option value="QuaternionInterpolation">Quaternion Interpolation</option>
+                </select>
+            </label>
+            <Show when={axisInterpolation()}>
+                {(procedural) => (
+                    <>
+                        <br />
+                        <label>
+                            Control Axis:
+                            <select
+                                name="ProceduralBoneAxis"
+                                value={procedural().axis}
+                                onChange={(event) => changeProcedural({ ...procedural(), axis: event.target.value as 'X' | 'Y' | 'Z' })}
+                            >
+                                <option value="X">X</option>
+                                <option value="Y">Y</option>
+                                <option value="Z">Z</option>
+                            </select>
+                        </label>
+                        <For each={axisDirections}>
+                            {(direction, directionIndex) => (
+                                <div>
+                                    <h4>Control Axis Pointing {direction}</h4>
+                                    Position Offset
+                                    <VectorInput
+                                        name={`ProceduralBonePosition${directionIndex()}`}
+                                        value={procedural().positions[directionIndex()]!}
+                                        onChange={(value) => changeAxisTransform('positions', directionIndex(), value)}
+                                    />
+                                    <br />
+                                    Rotation
+                                    <VectorInput
+                                        name={`ProceduralBoneRotation${directionIndex()}`}
+                                        value={procedural().rotations[directionIndex()]!}
+                                        onChange={(value) => changeAxisTransform('rotations', directionIndex(), value)}
+                                    />
+                                </div>
+                            )}
+                        </For>
+                    </>
+                )}
+            </Show>
+            <Show when={quaternionInterpolation()}>
+                {(procedural) => (
+                    <>
+                        <For each={procedural().triggers}>
+                            {(trigger, triggerIndex) => (
+                                <div>
+                                    <h4>Trigger {triggerIndex() + 1}</h4>
+                                    <label>
+                                        Tolerance:
+                                        <input
+                                            name={`ProceduralBoneTolerance${triggerIndex()}`}
+                                            type="number"
+                                            min="0"
+                                            value={trigger.tolerance}
+                                            onChange={(event) => changeTrigger(triggerIndex(), { tolerance: event.target.valueAsNumber || 0 })}
+                                        />
+                                    </label>
+                                    <br />
+                                    Control Rotation
+                                    <VectorInput
+                                        name={`ProceduralBoneTrigger${triggerIndex()}`}
+                                        value={trigger.trigger}
+                                        onChange={(value) => changeTrigger(triggerIndex(), { trigger: value })}
+                                    />
+                                    <br />
+                                    Position Offset
+                                    <VectorInput
+                                        name={`ProceduralBoneTriggerPosition${triggerIndex()}`}
+                                        value={trigger.position}
+                                        onChange={(value) => changeTrigger(triggerIndex(), { position: value })}
+                                    />
+                                    <br />
+                                    Rotation
+                                    <VectorInput
+                                        name={`ProceduralBoneTriggerRotation${triggerIndex()}`}
+                                        value={trigger.rotation}
+                                        onChange={(value) => changeTrigger(triggerIndex(), { rotation: value })}
+                                    />
+                                    <br />
+                                    <button disabled={procedural().triggers.length === 1} onClick={() => removeTrigger(triggerIndex())}>
+                                        Remove Trigger
+                                    </button>
+                                </div>
+                            )}
+                        </For>
+                        <button onClick={() => addTrigger()}>Add Trigger</button>
+                    </>
+                )}
+            </Show>
+            <br />
+            <button onClick={() => removeProceduralBone()}>Remove</button>
+        </div>
+    );
+};
+
+export { createProcedural };
+
+export default ProceduralBoneEntry;
@@ -0,0 +1,44 @@
+import { For, type Component } from 'solid-js';
+import { type SetStoreFunction } from 'solid-js/store';
+import ProceduralBoneEntry, { createProcedural, type ProceduralBoneEntryProperties } from './ProceduralBoneEntry';
+
+type ProceduralBoneMenuProperties = {
+    proceduralBoneEntries: ProceduralBoneEntryProperties[];
+    setProceduralBoneEntries: SetStoreFunction<ProceduralBoneEntryProperties[]>;
+};
+
+export type { ProceduralBoneMenuProperties };
+
+const ProceduralBoneMenu: Component<ProceduralBoneMenuProperties> = (properties) => {
+    const addProceduralBone = () => {
+        properties.setProceduralBoneEntries([...properties.proceduralBoneEntries, createNewProceduralBone()]);
+    };
+
+    let proceduralBoneEntryIdentifierGenerator = 0;
+    const createNewProceduralBone = (): ProceduralBoneEntryProperties => {
+        return {
+            identifier: proceduralBoneEntryIdentifierGenerator++,
+            setProceduralBoneEntries: properties.setProceduralBoneEntries,
+            data: {
+                bone: '',
+                control_bone: '',
+                procedural: createProcedural('QuaternionInterpolation'),
+            },
+        };
+    };
+
+    return (
+        <section id="Procedural-Bone-Menu">
+            <h2>Procedural Bones</h2>
+            <p>Each bone is posed from the rotation of its control bone instead of its animations. Angles are in degrees.</p>
+            <button onClick={() => addProceduralBone()}>Add Procedural Bone</button>
+            <For each={properties.proceduralBoneEntries}>
+                {({ identifier, setProceduralBoneEntries, data }) => (
+                    <ProceduralBoneEntry identifier={identifier} setProceduralBoneEntries={setProceduralBoneEntries} data={data} />
+                )}
+            </For>
+        </section>
+    );
+};
+
+export default ProceduralBoneMenu;
@@ -0,0 +1,110 @@
+import { createSignal, For, type Component } from 'solid-js';
+import { type Gibs } from './GibsInput';
+import { type ModelFlags } from './ModelFlagsInput';
+
+type ProjectTemplate = {
+    name: string;
+    description: string;
+    surface_property: string;
+    model_flags: Partial<ModelFlags>;
+    sequences: string[];
+    gibs: Gibs | null;
+    physics_total_mass: number | null;
+};
+
+type NewProjectProperties = {
+    createProject: (template: ProjectTemplate) => void;
+};
+
+export type { NewProjectProperties, ProjectTemplate };
+
+// Templates only set up the settings, the source files, hitboxes and physics shapes still have to be added to the project.
+// Every sequence plays an animation of the same name, so only the file of the animation has to be picked.
+const projectTemplates: ProjectTemplate[] = [
+    {
+        name: 'Empty',
+        description: 'Every setting uses the defaults.',
+        surface_property: 'default',
+        model_flags: {},
+        sequences: [],
+        gibs: null,
+        physics_total_mass: null,
+    },
+    {
+        name: 'Static Prop',
+        description: 'A prop that does not move, with a single idle sequence.',
+        surface_property: 'default',
+        model_flags: { static_prop: true },
+        sequences: ['idle'],
+        gibs: null,
+        physics_total_mass: null,
+    },
+    {
+        name: 'Physics Prop',
+        description: 'A prop that is simulated and breaks into gibs.',
+        surface_property: 'wood',
+        model_flags: {},
+        sequences: ['idle'],
+        gibs: {
+            base: 'Wooden.Medium',
+            health: 0,
+            damage_table: '',
+            breakable_model: '',
+            breakable_count: 0,
+            breakable_skin: 0,
+            gib_models: [],
+            body_part: '',
+            separate_models: true,
+        },
+        physics_total_mass: 50,
+    },
+    {
+        name: 'View Model',
+        description: 'A first person weapon, which does not cast shadows and has the sequences weapons play.',
+        surface_property: 'metal',
+        model_flags: { do_not_cast_shadows: true },
+        sequences: ['idle', 'draw', 'holster', 'fire', 'reload'],
+        gibs: null,
+        physics_total_mass: null,
+    },
+    {
+        name: 'NPC / Ragdoll',
+        description: 'A character with the sequences of a ragdoll and the mass of a person.',
+        surface_property: 'flesh',
+        model_flags: {},
+        sequences: ['idle', 'ragdoll'],
+        gibs: null,
+        physics_total_mass: 90,
+    },
+];
+
+const NewProject: Component<NewProjectProperties> = (properties) => {
+    const [choosingTemplate, setChoosingTemplate] = createSignal(false);
+
+    const createProject = (template: ProjectTemplate) => {
+        setChoosingTemplate(false);
+        properties.createProject(template);
+    };
+
+    return (
+        <>
+            <button onClick={() => setChoosingTemplate(true)}>New Project</button>
+            <dialog open={choosingTemplate()}>
+                <h3>New Project</h3>
+                <p>The current project is replaced, the export path and game limits are kept.</p>
+                <For each={projectTemplates}>
+                    {(template) => (
+                        <div>
+                            <h4>{template.name}</h4>
+                            <p>{template.description}</p>
+                            <button onClick={() => createProject(template)}>Create</button>
+                        </div>
+                    )}
+                </For>
+                <button onClick={() => setChoosingTemplate(false)}>Cancel</button>
+            </dialog>
+        </>
+    );
+};
+
+export default NewProject;
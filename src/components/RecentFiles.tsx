@@ -0,0 +1,98 @@
+import { createSignal, For, Show, type Component } from 'solid-js';
+
+type RecentFileLists = {
+    projects: string[];
+    source_files: string[];
+};
+
+type RecentFilesProperties = {
+    openProject: (path: string) => void;
+};
+
+export type { RecentFileLists, RecentFilesProperties };
+
+const recentFilesKey = 'source-wrench-recent-files';
+
+const maximumRecentFiles = 10;
+
+const loadRecentFiles = (): RecentFileLists => {
+    const savedFiles = localStorage.getItem(recentFilesKey);
+
+    if (savedFiles === null) {
+        return { projects: [], source_files: [] };
+    }
+
+    try {
+        return { projects: [], source_files: [], ...(JSON.parse(savedFiles) as Partial<RecentFileLists>) };
+    } catch {
+        return { projects: [], source_files: [] };
+    }
+};
+
+// Every entry reads the same lists, so a file opened in one entry can be picked in the others.
+const [recentFiles, setRecentFiles] = createSignal(loadRecentFiles());
+
+const saveRecentFiles = (files: RecentFileLists) => {
+    setRecentFiles(files);
+    localStorage.setItem(recentFilesKey, JSON.stringify(files));
+};
+
+// The most recent file is first, opening a file again moves it back to the top.
+const addRecentFile = (list: keyof RecentFileLists, path: string) => {
+    const files = recentFiles();
+    saveRecentFiles({ ...files, [list]: [path, ...files[list].filter((file) => file !== path)].slice(0, maximumRecentFiles) });
+};
+
+const removeRecentFile = (list: keyof RecentFileLists, path: string) => {
+    const files = recentFiles();
+    saveRecentFiles({ ...files, [list]: files[list].filter((file) => file !== path) });
+};
+
+// Picking a file loads it again through the file manager, which also watches it again when watching is enabled.
+const RecentSourceFileSelect: Component<{ name: string; onSelect: (path: string) => void }> = (properties) => (
+    <Show when={recentFiles().source_files.length > 0}>
+        <select
+            name={properties.name}
+            value=""
+            onChange={(event) => {
+                const path = event.target.value;
+                event.target.value = '';
+
+                if (path !== '') {
+                    properties.onSelect(path);
+                }
+            }}
+        >
+            <option value="">Recent Files</option>
+            <For each={recentFiles().source_files}>{(path) => <option value={path}>{path}</option>}</For>
+        </select>
+    </Show>
+);
+
+const RecentFiles: Component<RecentFilesProperties> = (properties) => (
+    <div>
+        <h3>Recent Projects</h3>
+        <For each={recentFiles().projects} fallback={<p>No Recent Projects</p>}>
+            {(path) => (
+                <div>
+                    <button onClick={() => properties.openProject(path)}>{path}</button>
+                    <button onClick={() => removeRecentFile('projects', path)}>Remove</button>
+                </div>
+            )}
+        </For>
+        <h3>Recent Source Files</h3>
+        <For each={recentFiles().source_files} fallback={<p>No Recent Source Files</p>}>
+            {(path) => (
+                <div>
+                    {path}
+                    <button onClick={() => removeRecentFile('source_files', path)}>Remove</button>
+                </div>
+            )}
+        </For>
+        <button onClick={() => saveRecentFiles({ projects: [], source_files: [] })}>Clear Recent Files</button>
+    </div>
+);
+
+export { addRecentFile, RecentSourceFileSelect };
+
+export default RecentFiles;
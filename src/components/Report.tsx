@@ -0,0 +1,219 @@
+import { invoke } from '@tauri-apps/api/core';
+import { createSignal, For, Show, type Component } from 'solid-js';
+import { type ModelFlags } from './ModelFlagsInput';
+
+type CompileReport = {
+    model_name: string;
+    export_path: string;
+    checksum: number;
+    bone_count: number;
+    body_parts: {
+        name: string;
+        models: {
+            name: string;
+            levels_of_detail: {
+                vertex_count: number;
+                triangle_count: number;
+            }[];
+        }[];
+    }[];
+    default_body: number;
+    materials: string[];
+    animations: {
+        name: string;
+        frame_count: number;
+        data_size: number;
+    }[];
+    files: {
+        name: string;
+        size: number;
+    }[];
+    mesh_splits: {
+        model: string;
+        material: string;
+        mesh_count: number;
+        vertex_count: number;
+        triangle_count: number;
+        vertex_limit_splits: number;
+        hardware_bone_limit_splits: number;
+    }[];
+    warnings: string[];
+    flag_issues: {
+        message: string;
+        fix: string;
+        flag_changes: [keyof ModelFlags, boolean][];
+    }[];
+    dry_run: boolean;
+    differences: string[];
+    additional_models: CompileReport[];
+};
+
+type ReportProperties = {
+    report: CompileReport | null;
+    applyFlagChanges: (flagChanges: [keyof ModelFlags, boolean][]) => void;
+};
+
+export type { CompileReport, ReportProperties };
+
+const Report: Component<ReportProperties> = (properties) => {
+    const [verifying, setVerifying] = createSignal(false);
+
+    const verifyOutput = async (report: CompileReport) => {
+        setVerifying(true);
+        await invoke('verify_model', { report });
+        setVerifying(false);
+    };
+
+    return (
+        <section id="Report-Menu">
+            <h2>Report</h2>
+            <Show when={properties.report} fallback={<p>Compile a model to see its report.</p>}>
+                {(report) => (
+                    <>
+                        <h3>
+                            {report().model_name}
+                            {report().dry_run ? ' (Dry Run)' : ''}
+                        </h3>
+                        <p>Exported To: {report().export_path}</p>
+                        <p>Checksum: {(report().checksum >>> 0).toString(16).toUpperCase().padStart(8, '0')}</p>
+                        <button disabled={verifying() || report().dry_run} onClick={async () => await verifyOutput(report())}>
+                            Verify Output
+                        </button>
+                        <p>Bones: {report().bone_count}</p>
+                        <h4>Body Parts</h4>
+                        <p>Default Body: {report().default_body}</p>
+                        <ul>
+                            <For each={report().body_parts}>
+                                {(bodyPart) => (
+                                    <li>
+                                        {bodyPart.name}
+                                        <ul>
+                                            <For each={bodyPart.models}>
+                                                {(model) => (
+                                                    <li>
+                                                        {model.name}
+                                                        <ul>
+                                                            <For each={model.levels_of_detail}>
+                                                                {(levelOfDetail, index) => (
+                                                                    <li>
+                                                                        LOD {index()}: {levelOfDetail.vertex_count} Vertices,{' '}
+                                                                        {levelOfDetail.triangle_count} Triangles
+                                                                    </li>
+                                                                )}
+                                                            </For>
+                                                        </ul>
+                                                    </li>
+                                                )}
+                                            </For>
+                                        </ul>
+                                    </li>
+                                )}
+                            </For>
+                        </ul>
+                        <h4>Materials</h4>
+                        <ul>
+                            <For each={report().materials}>{(material) => <li>{material}</li>}</For>
+                        </ul>
+                        <h4>Animations</h4>
+                        <ul>
+                            <For each={report().animations}>
+                                {(animation) => (
+                                    <li>
+                                        {animation.name}: {animation.frame_count} Frames, {animation.data_size} Bytes
+                                    </li>
+                                )}
+                            </For>
+                        </ul>
+                        <h4>Files</h4>
+                        <ul>
+                            <For each={report().files}>
+                                {(file) => (
+                                    <li>
+                                        {file.name}: {file.size} Bytes
+                                    </li>
+                                )}
+                            </For>
+                        </ul>
+                        <Show when={report().mesh_splits.length > 0}>
+                            <h4>Split Meshes</h4>
+                            <p>These materials did not fit into a single mesh, splitting them in the source file keeps every mesh under the limits.</p>
+                            <ul>
+                                <For each={report().mesh_splits}>
+                                    {(meshSplit) => (
+                                        <li>
+                                            {meshSplit.model} "{meshSplit.material}": {meshSplit.mesh_count} Meshes, {meshSplit.vertex_count} Vertices,{' '}
+                                            {meshSplit.triangle_count} Triangles ({meshSplit.vertex_limit_splits} Vertex Limit Splits,{' '}
+                                            {meshSplit.hardware_bone_limit_splits} Bone Limit Splits)
+                                        </li>
+                                    )}
+                                </For>
+                            </ul>
+                        </Show>
+                        <Show when={report().flag_issues.length > 0}>
+                            <h4>Flag Issues</h4>
+                            <ul>
+                                <For each={report().flag_issues}>
+                                    {(flagIssue) => (
+                                        <li>
+                                            {flagIssue.message} {flagIssue.fix}{' '}
+                                            <Show when={flagIssue.flag_changes.length > 0}>
+                                                <button onClick={() => properties.applyFlagChanges(flagIssue.flag_changes)}>Fix</button>
+                                            </Show>
+                                        </li>
+                                    )}
+                                </For>
+                            </ul>
+                        </Show>
+                        <h4>Warnings</h4>
+                        <ul>
+                            <For each={report().warnings}>{(warning) => <li>{warning}</li>}</For>
+                        </ul>
+                        <Show when={report().dry_run}>
+                            <h4>Differences To Files On Disk</h4>
+                            <ul>
+                                <For each={report().differences} fallback={<li>No differences found.</li>}>
+                                    {(difference) => <li>{difference}</li>}
+                                </For>
+                            </ul>
+                        </Show>
+                        <Show when={report().additional_models.length > 0}>
+                            <h4>Additional Models</h4>
+                            <p>The world model and the gibs are compiled with the model, their files are only replaced when every model compiled.</p>
+                            <For each={report().additional_models}>
+                                {(additionalModel) => (
+                                    <div>
+                                        <h5>{additionalModel.model_name}</h5>
+                                        <button disabled={verifying() || additionalModel.dry_run} onClick={async () => await verifyOutput(additionalModel)}>
+                                            Verify Output
+                                        </button>
+                                        <ul>
+                                            <For each={additionalModel.files}>
+                                                {(file) => (
+                                                    <li>
+                                                        {file.name}: {file.size} Bytes
+                                                    </li>
+                                                )}
+                                            </For>
+                                        </ul>
+                                        <Show when={additionalModel.dry_run}>
+                                            <ul>
+                                                <For each={additionalModel.differences} fallback={<li>No differences found.</li>}>
+                                                    {(difference) => <li>{difference}</li>}
+                                                </For>
+                                            </ul>
+                                        </Show>
+                                        <ul>
+                                            <For each={additionalModel.warnings}>{(warning) => <li>{warning}</li>}</For>
+                                        </ul>
+                                    </div>
+                                )}
+                            </For>
+                        </Show>
+                    </>
+                )}
+            </Show>
+        </section>
+    );
+};
+
+export default Report;
@@ -0,0 +1,129 @@
+import { createSignal, Show, type Component, type ParentComponent } from 'solid-js';
+import { copyState } from './History';
+
+type SelectableEntry<T> = {
+    readonly identifier: number;
+    readonly data: T;
+};
+
+type EntrySelection = {
+    readonly isSelected: (identifier: number) => boolean;
+    readonly setSelected: (identifier: number, selected: boolean) => void;
+    readonly selectedCount: () => number;
+    readonly selectAll: () => void;
+    readonly clearSelection: () => void;
+    readonly duplicateSelected: () => void;
+    readonly removeSelected: () => void;
+    readonly startDrag: (identifier: number) => void;
+    readonly dropOn: (identifier: number) => void;
+};
+
+export type { EntrySelection, SelectableEntry };
+
+// Entries are removed and duplicated through the list, so the entries themselves do not need to know about the selection.
+const createEntrySelection = <T, E extends SelectableEntry<T>>(
+    entries: () => E[],
+    setEntries: (entries: E[]) => void,
+    createEntry: (data: T) => E,
+): EntrySelection => {
+    const [selectedIdentifiers, setSelectedIdentifiers] = createSignal<number[]>([]);
+    let draggedIdentifier: number | null = null;
+
+    const isSelected = (identifier: number) => selectedIdentifiers().includes(identifier);
+
+    const setSelected = (identifier: number, selected: boolean) => {
+        setSelectedIdentifiers((identifiers) => [
+            ...identifiers.filter((selectedIdentifier) => selectedIdentifier !== identifier),
+            ...(selected ? [identifier] : []),
+        ]);
+    };
+
+    // Entries removed one at a time stay in the selection, so only entries that still exist are counted.
+    const selectedCount = () => entries().filter((entry) => isSelected(entry.identifier)).length;
+
+    const selectAll = () => setSelectedIdentifiers(entries().map((entry) => entry.identifier));
+
+    const clearSelection = () => setSelectedIdentifiers([]);
+
+    // Every copy is placed right after the entry it was made from, the copies are not selected.
+    const duplicateSelected = () => {
+        setEntries(entries().flatMap((entry) => (isSelected(entry.identifier) ? [entry, createEntry(copyState(entry.data))] : [entry])));
+    };
+
+    const removeSelected = () => {
+        setEntries(entries().filter((entry) => !isSelected(entry.identifier)));
+        clearSelection();
+    };
+
+    // Releasing the pointer outside of the list cancels the move.
+    const startDrag = (identifier: number) => {
+        draggedIdentifier = identifier;
+        addEventListener('pointerup', () => (draggedIdentifier = null), { once: true });
+    };
+
+    // Dragging a selected entry moves every selected entry, they are placed next to the entry they are dropped on in their current order.
+    const dropOn = (identifier: number) => {
+        const dragged = draggedIdentifier;
+        draggedIdentifier = null;
+
+        if (dragged === null) {
+            return;
+        }
+
+        const currentEntries = entries();
+        const isMoved = (entry: E) => (isSelected(dragged) ? isSelected(entry.identifier) : entry.identifier === dragged);
+
+        if (currentEntries.some((entry) => entry.identifier === identifier && isMoved(entry))) {
+            return;
+        }
+
+        const movedEntries = currentEntries.filter(isMoved);
+        const remainingEntries = currentEntries.filter((entry) => !isMoved(entry));
+        const draggedIndex = currentEntries.findIndex((entry) => entry.identifier === dragged);
+        const targetIndex = currentEntries.findIndex((entry) => entry.identifier === identifier);
+
+        if (draggedIndex === -1 || targetIndex === -1) {
+            return;
+        }
+
+        // Entries dragged down are placed after the entry they are dropped on, entries dragged up before it.
+        const insertIndex = remainingEntries.findIndex((entry) => entry.identifier === identifier) + (draggedIndex < targetIndex ? 1 : 0);
+        setEntries([...remainingEntries.slice(0, insertIndex), ...movedEntries, ...remainingEntries.slice(insertIndex)]);
+    };
+
+    return { isSelected, setSelected, selectedCount, selectAll, clearSelection, duplicateSelected, removeSelected, startDrag, dropOn };
+};
+
+const SelectionActions: Component<{ selection: EntrySelection }> = (properties) => (
+    <div>
+        <button onClick={() => properties.selection.selectAll()}>Select All</button>
+        <Show when={properties.selection.selectedCount() > 0}>
+            <span>{properties.selection.selectedCount()} Selected</span>
+            <button onClick={() => properties.selection.clearSelection()}>Clear Selection</button>
+            <button onClick={() => properties.selection.duplicateSelected()}>Duplicate Selected</button>
+            <button onClick={() => properties.selection.removeSelected()}>Remove Selected</button>
+        </Show>
+    </div>
+);
+
+// Entries are moved with the pointer instead of HTML drag and drop, the window uses drag and drop to load dropped source files.
+// Hidden entries stay mounted so filtering a list does not unload the files of the entries.
+const SelectableEntryItem: ParentComponent<{ selection: EntrySelection; identifier: number; hidden?: boolean }> = (properties) => (
+    <div hidden={properties.hidden} onPointerUp={() => properties.selection.dropOn(properties.identifier)}>
+        <span style={{ cursor: 'grab', 'user-select': 'none' }} onPointerDown={() => properties.selection.startDrag(properties.identifier)}>
+            Drag To Move
+        </span>
+        <label>
+            Selected
+            <input
+                name="EntrySelected"
+                type="checkbox"
+                checked={properties.selection.isSelected(properties.identifier)}
+                onChange={(event) => properties.selection.setSelected(properties.identifier, event.target.checked)}
+            />
+        </label>
+        {properties.children}
+    </div>
+);
+
+export { createEntrySelection, SelectableEntryItem, SelectionActions };
@@ -1,5 +1,40 @@
-import { createSignal, For, type Component } from 'solid-js';
+import { createEffect, createSignal, For, on, Show, type Component } from 'solid-js';
 import { type SetStoreFunction } from 'solid-js/store';
+import BoundingBoxInput, { type BoundingBox } from './BoundingBoxInput';
+import { type EntryActions } from './Clipboard';
+
+type SequenceAutoLayer = {
+    sequence: string;
+    start: number;
+    peak: number;
+    tail: number;
+    end: number;
+    spline: boolean;
+    cross_fade: boolean;
+    no_blend: boolean;
+};
+
+type SequenceTransition = {
+    entry: string;
+    exit: string;
+    reverse: boolean;
+};
+
+type SequencePhase = {
+    entry: number;
+    exit: number;
+};
+
+type SequenceBlend = {
+    parameter: string;
+    keys: number[];
+};
+
+type SequenceInverseKinematicLock = {
+    chain: string;
+    position_weight: number;
+    local_rotation_weight: number;
+};
 
 type SequenceEntryProperties = {
     readonly identifier: number;
@@ -7,14 +42,65 @@ type SequenceEntryProperties = {
     readonly data: {
         name: string;
         animations: string[][];
+        weight_list: string;
+        auto_layers: SequenceAutoLayer[];
+        bounding_box: BoundingBox | null;
+        transition: SequenceTransition | null;
+        inverse_kinematic_locks: SequenceInverseKinematicLock[];
+        fade_in_time: number;
+        fade_out_time: number;
+        phase: SequencePhase | null;
+        column_blend: SequenceBlend | null;
+        row_blend: SequenceBlend | null;
+        activity_modifiers: string[];
     };
 };
 
-export type { SequenceEntryProperties };
+export type { SequenceAutoLayer, SequenceBlend, SequenceEntryProperties, SequenceInverseKinematicLock, SequencePhase, SequenceTransition };
+
+/** Keeps a key for every column or row, new keys continue one past the last key. */
+const fitBlendKeys = (keys: number[], count: number) => {
+    const fitted = keys.slice(0, count);
+    while (fitted.length < count) {
+        fitted.push(fitted.length > 0 ? fitted[fitted.length - 1]! + 1 : 0);
+    }
+    return fitted;
+};
+
+const CURVE_WIDTH = 160;
+const CURVE_HEIGHT = 40;
+const CURVE_SAMPLES = 16;
+
+/** The points of the weight the sequence blends with over time, the sequence is held at full weight for a second between the fades. */
+const createBlendCurve = (fadeInTime: number, fadeOutTime: number) => {
+    const duration = fadeInTime + 1 + fadeOutTime;
+    const spline = (value: number) => value * value * (3 - 2 * value);
+    const points: [number, number][] = [];
+
+    for (let sample = 0; sample <= CURVE_SAMPLES; sample++) {
+        const fraction = sample / CURVE_SAMPLES;
+        points.push([fraction * fadeInTime, spline(fraction)]);
+    }
+    for (let sample = 0; sample <= CURVE_SAMPLES; sample++) {
+        const fraction = sample / CURVE_SAMPLES;
+        points.push([fadeInTime + 1 + fraction * fadeOutTime, spline(1 - fraction)]);
+    }
+
+    return points.map(([time, weight]) => `${(time / duration) * CURVE_WIDTH},${(1 - weight) * CURVE_HEIGHT}`).join(' ');
+};
+
 // TODO: This code is bad and should be refactored.
-const SequenceEntry: Component<SequenceEntryProperties> = (properties) => {
+const SequenceEntry: Component<SequenceEntryProperties & EntryActions> = (properties) => {
     const [grid, setGrid] = createSignal([['']]);
 
+    // Every cell is read so the grid follows animations that are changed by undo or redo.
+    createEffect(
+        on(
+            () => properties.data.animations.map((row) => [...row]),
+            (animations) => setGrid(animations.length > 0 ? animations : [['']]),
+        ),
+    );
+
     const removeSequence = () => {
         properties.setSequenceEntries((sequences) => sequences.filter((sequence) => sequence.identifier !== properties.identifier));
     };
@@ -23,27 +109,53 @@ const SequenceEntry: Component<SequenceEntryProperties> = (properties) => {
         properties.setSequenceEntries((sequence) => sequence.identifier == properties.identifier, 'data', 'name', name);
     };
 
+    const changeSequenceBlend = (key: 'column_blend' | 'row_blend', blend: SequenceBlend | null) => {
+        properties.setSequenceEntries((sequence) => sequence.identifier == properties.identifier, 'data', key, blend);
+    };
+
+    const changeSequenceBlendKey = (key: 'column_blend' | 'row_blend', keyIndex: number, value: number) => {
+        const blend = properties.data[key];
+        if (blend) {
+            changeSequenceBlend(key, { ...blend, keys: blend.keys.map((poseKey, index) => (index === keyIndex ? value : poseKey)) });
+        }
+    };
+
+    const fitSequenceBlends = () => {
+        const columnBlend = properties.data.column_blend;
+        if (columnBlend) {
+            changeSequenceBlend('column_blend', { ...columnBlend, keys: fitBlendKeys(columnBlend.keys, grid()[0]?.length ?? 1) });
+        }
+        const rowBlend = properties.data.row_blend;
+        if (rowBlend) {
+            changeSequenceBlend('row_blend', { ...rowBlend, keys: fitBlendKeys(rowBlend.keys, grid().length) });
+        }
+    };
+
     const addRow = () => {
         setGrid([...grid(), new Array(grid()[0]?.length).fill('')]);
         properties.setSequenceEntries((sequence) => sequence.identifier == properties.identifier, 'data', 'animations', grid());
+        fitSequenceBlends();
     };
 
     const removeRow = () => {
         if (grid().length > 1) {
             setGrid(grid().slice(0, -1));
             properties.setSequenceEntries((sequence) => sequence.identifier == properties.identifier, 'data', 'animations', grid());
+            fitSequenceBlends();
         }
     };
 
     const addColumn = () => {
         setGrid(grid().map((row) => [...row, '']));
         properties.setSequenceEntries((sequence) => sequence.identifier == properties.identifier, 'data', 'animations', grid());
+        fitSequenceBlends();
     };
 
     const removeColumn = () => {
         if (grid()[0]?.length! > 1) {
             setGrid(grid().map((row) => row.slice(0, -1)));
             properties.setSequenceEntries((sequence) => sequence.identifier == properties.identifier, 'data', 'animations', grid());
+            fitSequenceBlends();
         }
     };
 
@@ -52,6 +164,112 @@ const SequenceEntry: Component<SequenceEntryProperties> = (properties) => {
         properties.setSequenceEntries((sequence) => sequence.identifier == properties.identifier, 'data', 'animations', grid());
     };
 
+    const changeSequenceWeightList = (weightList: string) => {
+        properties.setSequenceEntries((sequence) => sequence.identifier == properties.identifier, 'data', 'weight_list', weightList);
+    };
+
+    const addAutoLayer = () => {
+        const autoLayer: SequenceAutoLayer = { sequence: '', start: 0, peak: 0, tail: 1, end: 1, spline: false, cross_fade: false, no_blend: false };
+        properties.setSequenceEntries(
+            (sequence) => sequence.identifier == properties.identifier,
+            'data',
+            'auto_layers',
+            (autoLayers) => [...autoLayers, autoLayer],
+        );
+    };
+
+    const removeAutoLayer = (layerIndex: number) => {
+        properties.setSequenceEntries(
+            (sequence) => sequence.identifier == properties.identifier,
+            'data',
+            'auto_layers',
+            (autoLayers) => autoLayers.filter((_, index) => index !== layerIndex),
+        );
+    };
+
+    const changeAutoLayer = <Key extends keyof SequenceAutoLayer>(layerIndex: number, key: Key, value: SequenceAutoLayer[Key]) => {
+        properties.setSequenceEntries((sequence) => sequence.identifier == properties.identifier, 'data', 'auto_layers', layerIndex, key, value);
+    };
+
+    const changeSequenceBoundingBox = (boundingBox: BoundingBox | null) => {
+        properties.setSequenceEntries((sequence) => sequence.identifier == properties.identifier, 'data', 'bounding_box', boundingBox);
+    };
+
+    const changeSequenceTransition = (transition: SequenceTransition | null) => {
+        properties.setSequenceEntries((sequence) => sequence.identifier == properties.identifier, 'data', 'transition', transition);
+    };
+
+    const changeSequenceFadeTime = (key: 'fade_in_time' | 'fade_out_time', value: number) => {
+        properties.setSequenceEntries((sequence) => sequence.identifier == properties.identifier, 'data', key, Math.max(0, value || 0));
+    };
+
+    const changeSequencePhase = (phase: SequencePhase | null) => {
+        properties.setSequenceEntries((sequence) => sequence.identifier == properties.identifier, 'data', 'phase', phase);
+    };
+
+    const addInverseKinematicLock = () => {
+        const lock: SequenceInverseKinematicLock = { chain: '', position_weight: 1, local_rotation_weight: 0 };
+        properties.setSequenceEntries(
+            (sequence) => sequence.identifier == properties.identifier,
+            'data',
+            'inverse_kinematic_locks',
+            (locks) => [...locks, lock],
+        );
+    };
+
+    const removeInverseKinematicLock = (lockIndex: number) => {
+        properties.setSequenceEntries(
+            (sequence) => sequence.identifier == properties.identifier,
+            'data',
+            'inverse_kinematic_locks',
+            (locks) => locks.filter((_, index) => index !== lockIndex),
+        );
+    };
+
+    const changeInverseKinematicLockChain = (lockIndex: number, chain: string) => {
+        properties.setSequenceEntries((sequence) => sequence.identifier == properties.identifier, 'data', 'inverse_kinematic_locks', lockIndex, 'chain', chain);
+    };
+
+    const changeInverseKinematicLockWeight = (lockIndex: number, key: 'position_weight' | 'local_rotation_weight', value: number) => {
+        properties.setSequenceEntries(
+            (sequence) => sequence.identifier == properties.identifier,
+            'data',
+            'inverse_kinematic_locks',
+            lockIndex,
+            key,
+            Math.min(1, Math.max(0, value || 0)),
+        );
+    };
+
+    const addActivityModifier = () => {
+        properties.setSequenceEntries((sequence) => sequence.identifier == properties.identifier, 'data', 'activity_modifiers', (modifiers) => [
+            ...modifiers,
+            '',
+        ]);
+    };
+
+    const removeActivityModifier = (modifierIndex: number) => {
+        properties.setSequenceEntries(
+            (sequence) => sequence.identifier == properties.identifier,
+            'data',
+            'activity_modifiers',
+            (modifiers) => modifiers.filter((_, index) => index !== modifierIndex),
+        );
+    };
+
+    const changeActivityModifier = (modifierIndex: number, modifier: string) => {
+        properties.setSequenceEntries((sequence) => sequence.identifier == properties.identifier, 'data', 'activity_modifiers', modifierIndex, modifier);
+    };
+
+    const changeAutoLayerRange = (layerIndex: number, key: 'start' | 'peak' | 'tail' | 'end', value: number) => {
+        changeAutoLayer(layerIndex, key, Math.min(1, Math.max(0, value || 0)));
+    };
+
+    const blendAxes = [
+        { key: 'column_blend', name: 'SequenceColumnBlend', label: 'Column Blend', count: () => grid()[0]?.length ?? 1 },
+        { key: 'row_blend', name: 'SequenceRowBlend', label: 'Row Blend', count: () => grid().length },
+    ] as const;
+
     return (
         <div class="Sequence-Entry">
             <h3>Sequence</h3>
@@ -86,6 +304,312 @@ const SequenceEntry: Component<SequenceEntryProperties> = (properties) => {
                 <br />
             </label>
             <br />
+            <For each={blendAxes}>
+                {({ key, name, label, count }) => (
+                    <div>
+                        <label>
+                            {label}:
+                            <input
+                                name={name}
+                                type="checkbox"
+                                checked={properties.data[key] !== null}
+                                onChange={(event) => changeSequenceBlend(key, event.target.checked ? { parameter: '', keys: fitBlendKeys([], count()) } : null)}
+                            />
+                        </label>
+                        <Show when={properties.data[key]}>
+                            {(blend) => (
+                                <>
+                                    <label>
+                                        Pose Parameter:
+                                        <input
+                                            name={`${name}Parameter`}
+                                            type="text"
+                                            value={blend().parameter}
+                                            onChange={(event) => changeSequenceBlend(key, { ...blend(), parameter: event.target.value })}
+                                        />
+                                    </label>
+                                    <label>
+                                        Keys:
+                                        <For each={blend().keys}>
+                                            {(poseKey, keyIndex) => (
+                                                <input
+                                                    name={`${name}Key${keyIndex()}`}
+                                                    type="number"
+                                                    step="any"
+                                                    value={poseKey}
+                                                    onChange={(event) => changeSequenceBlendKey(key, keyIndex(), event.target.valueAsNumber || 0)}
+                                                />
+                                            )}
+                                        </For>
+                                    </label>
+                                </>
+                            )}
+                        </Show>
+                    </div>
+                )}
+            </For>
+            <label>
+                Weight List:
+                <input
+                    name="SequenceWeightList"
+                    type="text"
+                    list="Weight-List-Names"
+                    value={properties.data.weight_list}
+                    onChange={(event) => changeSequenceWeightList(event.target.value)}
+                />
+            </label>
+            <br />
+            <label>
+                Auto Layers:
+                <br />
+                <For each={properties.data.auto_layers}>
+                    {(autoLayer, layerIndex) => (
+                        <div>
+                            <input
+                                name={`SequenceAutoLayer${layerIndex()}`}
+                                list="Sequence-Names"
+                                value={autoLayer.sequence}
+                                onChange={(event) => changeAutoLayer(layerIndex(), 'sequence', event.target.value)}
+                            />
+                            <For each={['start', 'peak', 'tail', 'end'] as const}>
+                                {(key) => (
+                                    <label>
+                                        {key[0]!.toUpperCase() + key.slice(1)}:
+                                        <input
+                                            name={`SequenceAutoLayer${layerIndex()}${key}`}
+                                            type="number"
+                                            min="0"
+                                            max="1"
+                                            step="0.01"
+                                            value={autoLayer[key]}
+                                            onChange={(event) => changeAutoLayerRange(layerIndex(), key, event.target.valueAsNumber)}
+                                        />
+                                    </label>
+                                )}
+                            </For>
+                            <label>
+                                Spline:
+                                <input
+                                    name={`SequenceAutoLayer${layerIndex()}Spline`}
+                                    type="checkbox"
+                                    checked={autoLayer.spline}
+                                    onChange={(event) => changeAutoLayer(layerIndex(), 'spline', event.target.checked)}
+                                />
+                            </label>
+                            <label>
+                                Cross Fade:
+                                <input
+                                    name={`SequenceAutoLayer${layerIndex()}CrossFade`}
+                                    type="checkbox"
+                                    checked={autoLayer.cross_fade}
+                                    onChange={(event) => changeAutoLayer(layerIndex(), 'cross_fade', event.target.checked)}
+                                />
+                            </label>
+                            <label>
+                                No Blend:
+                                <input
+                                    name={`SequenceAutoLayer${layerIndex()}NoBlend`}
+                                    type="checkbox"
+                                    checked={autoLayer.no_blend}
+                                    onChange={(event) => changeAutoLayer(layerIndex(), 'no_blend', event.target.checked)}
+                                />
+                            </label>
+                            <button onClick={() => removeAutoLayer(layerIndex())}>-</button>
+                        </div>
+                    )}
+                </For>
+                <button onClick={() => addAutoLayer()}>+</button>
+            </label>
+            <br />
+            <label>
+                Inverse Kinematic Locks:
+                <br />
+                <For each={properties.data.inverse_kinematic_locks}>
+                    {(lock, lockIndex) => (
+                        <div>
+                            <input
+                                name={`SequenceInverseKinematicLock${lockIndex()}`}
+                                list="Inverse-Kinematic-Chain-Names"
+                                value={lock.chain}
+                                onChange={(event) => changeInverseKinematicLockChain(lockIndex(), event.target.value)}
+                            />
+                            <label>
+                                Position Weight:
+                                <input
+                                    name={`SequenceInverseKinematicLock${lockIndex()}PositionWeight`}
+                                    type="number"
+                                    min="0"
+                                    max="1"
+                                    step="0.01"
+                                    value={lock.position_weight}
+                                    onChange={(event) => changeInverseKinematicLockWeight(lockIndex(), 'position_weight', event.target.valueAsNumber)}
+                                />
+                            </label>
+                            <label>
+                                Local Rotation Weight:
+                                <input
+                                    name={`SequenceInverseKinematicLock${lockIndex()}LocalRotationWeight`}
+                                    type="number"
+                                    min="0"
+                                    max="1"
+                                    step="0.01"
+                                    value={lock.local_rotation_weight}
+                                    onChange={(event) => changeInverseKinematicLockWeight(lockIndex(), 'local_rotation_weight', event.target.valueAsNumber)}
+                                />
+                            </label>
+                            <button onClick={() => removeInverseKinematicLock(lockIndex())}>-</button>
+                        </div>
+                    )}
+                </For>
+                <button onClick={() => addInverseKinematicLock()}>+</button>
+            </label>
+            <br />
+            <label>
+                Activity Modifiers:
+                <br />
+                <For each={properties.data.activity_modifiers}>
+                    {(modifier, modifierIndex) => (
+                        <div>
+                            <input
+                                name={`SequenceActivityModifier${modifierIndex()}`}
+                                type="text"
+                                value={modifier}
+                                onChange={(event) => changeActivityModifier(modifierIndex(), event.target.value)}
+                            />
+                            <button onClick={() => removeActivityModifier(modifierIndex())}>-</button>
+                        </div>
+                    )}
+                </For>
+                <button onClick={() => addActivityModifier()}>+</button>
+            </label>
+            <br />
+            <BoundingBoxInput
+                name="SequenceBoundingBox"
+                label="Bounding Box"
+                value={properties.data.bounding_box}
+                onChange={(boundingBox) => changeSequenceBoundingBox(boundingBox)}
+            />
+            <br />
+            <label>
+                Transition:
+                <input
+                    name="SequenceTransition"
+                    type="checkbox"
+                    checked={properties.data.transition !== null}
+                    onChange={(event) => changeSequenceTransition(event.target.checked ? { entry: '', exit: '', reverse: false } : null)}
+                />
+            </label>
+            <Show when={properties.data.transition}>
+                {(transition) => (
+                    <>
+                        <label>
+                            Entry Node:
+                            <input
+                                name="SequenceTransitionEntry"
+                                list="Transition-Node-Names"
+                                value={transition().entry}
+                                onChange={(event) => changeSequenceTransition({ ...transition(), entry: event.target.value })}
+                            />
+                        </label>
+                        <label>
+                            Exit Node:
+                            <input
+                                name="SequenceTransitionExit"
+                                list="Transition-Node-Names"
+                                value={transition().exit}
+                                onChange={(event) => changeSequenceTransition({ ...transition(), exit: event.target.value })}
+                            />
+                        </label>
+                        <label>
+                            Reverse:
+                            <input
+                                name="SequenceTransitionReverse"
+                                type="checkbox"
+                                checked={transition().reverse}
+                                onChange={(event) => changeSequenceTransition({ ...transition(), reverse: event.target.checked })}
+                            />
+                        </label>
+                    </>
+                )}
+            </Show>
+            <br />
+            <label>
+                Fade In Time:
+                <input
+                    name="SequenceFadeInTime"
+                    type="number"
+                    min="0"
+                    step="0.01"
+                    value={properties.data.fade_in_time}
+                    onChange={(event) => changeSequenceFadeTime('fade_in_time', event.target.valueAsNumber)}
+                />
+            </label>
+            <label>
+                Fade Out Time:
+                <input
+                    name="SequenceFadeOutTime"
+                    type="number"
+                    min="0"
+                    step="0.01"
+                    value={properties.data.fade_out_time}
+                    onChange={(event) => changeSequenceFadeTime('fade_out_time', event.target.valueAsNumber)}
+                />
+            </label>
+            <br />
+            <label>
+                Blend Curve:
+                <svg width={CURVE_WIDTH} height={CURVE_HEIGHT} viewBox={`0 0 ${CURVE_WIDTH} ${CURVE_HEIGHT}`}>
+                    <polyline
+                        points={createBlendCurve(properties.data.fade_in_time, properties.data.fade_out_time)}
+                        fill="none"
+                        stroke="currentColor"
+                        stroke-width="2"
+                    />
+                </svg>
+            </label>
+            <br />
+            <label>
+                Phase:
+                <input
+                    name="SequencePhase"
+                    type="checkbox"
+                    checked={properties.data.phase !== null}
+                    onChange={(event) => changeSequencePhase(event.target.checked ? { entry: 0, exit: 0 } : null)}
+                />
+            </label>
+            <Show when={properties.data.phase}>
+                {(phase) => (
+                    <>
+                        <label>
+                            Entry Phase:
+                            <input
+                                name="SequencePhaseEntry"
+                                type="number"
+                                min="0"
+                                max="1"
+                                step="0.01"
+                                value={phase().entry}
+                                onChange={(event) => changeSequencePhase({ ...phase(), entry: Math.min(1, Math.max(0, event.target.valueAsNumber || 0)) })}
+                            />
+                        </label>
+                        <label>
+                            Exit Phase:
+                            <input
+                                name="SequencePhaseExit"
+                                type="number"
+                                min="0"
+                                max="1"
+                                step="0.01"
+                                value={phase().exit}
+                                onChange={(event) => changeSequencePhase({ ...phase(), exit: Math.min(1, Math.max(0, event.target.valueAsNumber || 0)) })}
+                            />
+                        </label>
+                    </>
+                )}
+            </Show>
+            <br />
+            <button onClick={() => properties.duplicate()}>Duplicate</button>
+            <button onClick={() => properties.copy()}>Copy</button>
             <button onClick={() => removeSequence()}>Remove</button>
         </div>
     );
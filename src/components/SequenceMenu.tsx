@@ -1,28 +1,84 @@
-import { For, type Component } from 'solid-js';
+import { createSignal, For, type Component } from 'solid-js';
 import { type SetStoreFunction } from 'solid-js/store';
+import { canPasteEntry, copyEntry, pasteEntry } from './Clipboard';
+import EntryFilter, { matchesEntryFilter } from './EntryFilter';
+import { copyState } from './History';
+import { createEntrySelection, SelectableEntryItem, SelectionActions } from './Selection';
 import SequenceEntry, { type SequenceEntryProperties } from './SequenceEntry';
 
 type SequenceMenuProperties = {
     sequenceEntries: SequenceEntryProperties[];
     setSequenceEntries: SetStoreFunction<SequenceEntryProperties[]>;
+    transitionNodes: string[];
+    setTransitionNodes: (transitionNodes: string[]) => void;
 };
 
 export type { SequenceMenuProperties };
 
+const createSequenceData = (name: string = 'New Sequence'): SequenceEntryProperties['data'] => ({
+    name,
+    animations: [],
+    weight_list: '',
+    auto_layers: [],
+    bounding_box: null,
+    transition: null,
+    inverse_kinematic_locks: [],
+    fade_in_time: 0.2,
+    fade_out_time: 0.2,
+    phase: null,
+    column_blend: null,
+    row_blend: null,
+    activity_modifiers: [],
+});
+
+export { createSequenceData };
+
 const SequenceMenu: Component<SequenceMenuProperties> = (properties) => {
     const addSequence = () => {
         properties.setSequenceEntries([...properties.sequenceEntries, createNewSequence()]);
     };
 
+    const duplicateSequence = (identifier: number, data: SequenceEntryProperties['data']) => {
+        const index = properties.sequenceEntries.findIndex((sequence) => sequence.identifier === identifier);
+        properties.setSequenceEntries((sequences) => [...sequences.slice(0, index + 1), createNewSequence(copyState(data)), ...sequences.slice(index + 1)]);
+    };
+
+    const pasteSequence = () => {
+        const data = pasteEntry<SequenceEntryProperties['data']>('sequence');
+
+        if (data === null) {
+            return;
+        }
+
+        properties.setSequenceEntries([...properties.sequenceEntries, createNewSequence(data)]);
+    };
+
+    const [sequenceFilter, setSequenceFilter] = createSignal('');
+
+    const sortSequencesByName = () => {
+        properties.setSequenceEntries([...properties.sequenceEntries].sort((first, second) => first.data.name.localeCompare(second.data.name)));
+    };
+
+    const sequenceSelection = createEntrySelection(
+        () => properties.sequenceEntries,
+        (sequences) => properties.setSequenceEntries(sequences),
+        (data) => createNewSequence(data),
+    );
+
+    const changeTransitionNode = (nodeIndex: number, name: string) => {
+        properties.setTransitionNodes(properties.transitionNodes.map((node, index) => (index === nodeIndex ? name : node)));
+    };
+
+    const removeTransitionNode = (nodeIndex: number) => {
+        properties.setTransitionNodes(properties.transitionNodes.filter((_, index) => index !== nodeIndex));
+    };
+
     let animationEntrySequenceGenerator = 0;
-    const createNewSequence = (): SequenceEntryProperties => {
+    const createNewSequence = (data: SequenceEntryProperties['data'] = createSequenceData()): SequenceEntryProperties => {
         return {
             identifier: animationEntrySequenceGenerator++,
             setSequenceEntries: properties.setSequenceEntries,
-            data: {
-                name: 'New Sequence',
-                animations: [],
-            },
+            data,
         };
     };
 
@@ -30,9 +86,46 @@ const SequenceMenu: Component<SequenceMenuProperties> = (properties) => {
         <section id="Sequence-Menu">
             <h2>Sequences</h2>
             <button onClick={() => addSequence()}>Add Sequence</button>
+            <button disabled={!canPasteEntry('sequence')} onClick={() => pasteSequence()}>
+                Paste Sequence
+            </button>
+            <EntryFilter name="SequenceFilter" value={sequenceFilter()} onChange={setSequenceFilter} sortByName={sortSequencesByName} />
+            <SelectionActions selection={sequenceSelection} />
             <For each={properties.sequenceEntries}>
-                {({ identifier, setSequenceEntries, data }) => <SequenceEntry identifier={identifier} setSequenceEntries={setSequenceEntries} data={data} />}
+                {({ identifier, setSequenceEntries, data }) => (
+                    <SelectableEntryItem selection={sequenceSelection} identifier={identifier} hidden={!matchesEntryFilter(data.name, sequenceFilter())}>
+                        <SequenceEntry
+                            identifier={identifier}
+                            setSequenceEntries={setSequenceEntries}
+                            data={data}
+                            duplicate={() => duplicateSequence(identifier, data)}
+                            copy={() => copyEntry('sequence', data)}
+                        />
+                    </SelectableEntryItem>
+                )}
+            </For>
+            <datalist id="Sequence-Names">
+                <For each={properties.sequenceEntries}>{({ data }) => <option value={data.name}></option>}</For>
+            </datalist>
+            <h3>Transition Nodes</h3>
+            <p>Sequences with an entry and exit node move between the nodes, the engine chains them to reach nodes that are not directly connected.</p>
+            <For each={properties.transitionNodes}>
+                {(node, nodeIndex) => (
+                    <div>
+                        <input
+                            name={`TransitionNode${nodeIndex()}`}
+                            type="text"
+                            value={node}
+                            onChange={(event) => changeTransitionNode(nodeIndex(), event.target.value)}
+                        />
+                        <button onClick={() => removeTransitionNode(nodeIndex())}>-</button>
+                    </div>
+                )}
             </For>
+            <button onClick={() => properties.setTransitionNodes([...properties.transitionNodes, ''])}>+</button>
+            <datalist id="Transition-Node-Names">
+                <For each={properties.transitionNodes}>{(node) => <option value={node}></option>}</For>
+            </datalist>
         </section>
     );
 };
@@ -0,0 +1,101 @@
+import { For, Show, type Component } from 'solid-js';
+
+type SkeletonBone = {
+    name: string;
+    parent: number | null;
+    used_by_vertex: boolean;
+    used_by_attachment: boolean;
+    conflicting_sources: string[];
+};
+
+type SkeletonProperties = {
+    bones: SkeletonBone[] | null;
+    inspectSkeleton: () => Promise<void>;
+    boneMergeBones: string[];
+    setBoneMergeBones: (bones: string[]) => void;
+};
+
+export type { SkeletonBone, SkeletonProperties };
+
+type SkeletonNodeProperties = {
+    bones: SkeletonBone[];
+    index: number;
+    boneMergeBones: string[];
+    setBoneMergeBones: (bones: string[]) => void;
+};
+
+const SkeletonNode: Component<SkeletonNodeProperties> = (properties) => {
+    const bone = () => properties.bones[properties.index];
+    const children = () => properties.bones.flatMap((child, index) => (child.parent === properties.index ? [index] : []));
+
+    return (
+        <li>
+            <Show when={bone().conflicting_sources.length > 0} fallback={bone().name}>
+                <strong>{bone().name}</strong>
+            </Show>
+            <Show when={bone().used_by_vertex}> [Vertex]</Show>
+            <Show when={bone().used_by_attachment}> [Attachment]</Show>
+            <label>
+                Bone Merge
+                <input
+                    name="SkeletonBoneMerge"
+                    type="checkbox"
+                    checked={properties.boneMergeBones.includes(bone().name)}
+                    onChange={(event) =>
+                        properties.setBoneMergeBones([
+                            ...properties.boneMergeBones.filter((name) => name !== bone().name),
+                            ...(event.target.checked ? [bone().name] : []),
+                        ])
+                    }
+                />
+            </label>
+            <Show when={bone().conflicting_sources.length > 0}>
+                <ul>
+                    <For each={bone().conflicting_sources}>{(source) => <li>Bind Pose Differs In: {source}</li>}</For>
+                </ul>
+            </Show>
+            <Show when={children().length > 0}>
+                <ul>
+                    <For each={children()}>
+                        {(child) => (
+                            <SkeletonNode
+                                bones={properties.bones}
+                                index={child}
+                                boneMergeBones={properties.boneMergeBones}
+                                setBoneMergeBones={properties.setBoneMergeBones}
+                            />
+                        )}
+                    </For>
+                </ul>
+            </Show>
+        </li>
+    );
+};
+
+const Skeleton: Component<SkeletonProperties> = (properties) => {
+    return (
+        <section id="Skeleton-Menu">
+            <h2>Skeleton</h2>
+            <button onClick={async () => await properties.inspectSkeleton()}>Merge Skeleton</button>
+            <p>Bones marked for bone merge are kept for other models to merge onto, like the hands of a view model, even when no vertex uses them.</p>
+            <Show when={properties.bones} fallback={<p>Merge the skeleton to see the bones from all sources.</p>}>
+                {(bones) => (
+                    <ul>
+                        <For each={bones().flatMap((bone, index) => (bone.parent === null ? [index] : []))}>
+                            {(root) => (
+                                <SkeletonNode
+                                    bones={bones()}
+                                    index={root}
+                                    boneMergeBones={properties.boneMergeBones}
+                                    setBoneMergeBones={properties.setBoneMergeBones}
+                                />
+                            )}
+                        </For>
+                    </ul>
+                )}
+            </Show>
+        </section>
+    );
+};
+
+export default Skeleton;
@@ -0,0 +1,80 @@
+import { invoke } from '@tauri-apps/api/core';
+import { createSignal, For, onMount, Show, type Component } from 'solid-js';
+import { onModelFilesReloaded, releaseModelFile } from './FileOperations';
+
+type SourceFileStatus = {
+    path: string;
+    loaded: boolean;
+    size: number;
+    parse_milliseconds: number;
+    modified: number | null;
+    error: string | null;
+    tolerant: boolean;
+};
+
+export type { SourceFileStatus };
+
+// Every file the backend imported is listed, including files that failed so their error is not only in the log.
+const SourceFiles: Component = () => {
+    const [files, setFiles] = createSignal<SourceFileStatus[]>([]);
+
+    const refreshFiles = async () => {
+        setFiles(await invoke<SourceFileStatus[]>('list_files'));
+    };
+
+    const reloadFile = async (path: string) => {
+        await invoke('reload_file', { path });
+        await refreshFiles();
+    };
+
+    // Tolerant parsing warns about the quirks of old exporters instead of failing, the file is imported again to apply it.
+    const setTolerantParsing = async (path: string, tolerant: boolean) => {
+        await invoke('set_tolerant_parsing', { path, tolerant });
+        await reloadFile(path);
+    };
+
+    // Entries still using the file keep it selected, but compiling fails until it is loaded again.
+    const unloadFile = async (path: string) => {
+        await releaseModelFile(path);
+        await refreshFiles();
+    };
+
+    onMount(refreshFiles);
+
+    onModelFilesReloaded(refreshFiles);
+
+    return (
+        <section id="Files-Menu">
+            <h2>Source Files</h2>
+            <button onClick={async () => await refreshFiles()}>Refresh</button>
+            <For each={files()} fallback={<p>No source files have been loaded.</p>}>
+                {(file) => (
+                    <div>
+                        <h3>
+                            {file.error === null ? '' : '[Failed] '}
+                            {file.path}
+                        </h3>
+                        <p>
+                            {file.loaded ? 'Loaded' : 'Not Loaded'}, {file.size} Bytes, Parsed In {file.parse_milliseconds.toFixed(1)} ms
+                        </p>
+                        <Show when={file.modified}>{(modified) => <p>Last Modified: {new Date(modified() * 1000).toLocaleString()}</p>}</Show>
+                        <Show when={file.error}>{(error) => <p>Error: {error()}</p>}</Show>
+                        <label>
+                            Tolerant Parsing
+                            <input
+                                name="SourceFileTolerant"
+                                type="checkbox"
+                                checked={file.tolerant}
+                                onChange={async (event) => await setTolerantParsing(file.path, event.target.checked)}
+                            />
+                        </label>
+                        <button onClick={async () => await reloadFile(file.path)}>Reload</button>
+                        <button onClick={async () => await unloadFile(file.path)}>Unload</button>
+                    </div>
+                )}
+            </For>
+        </section>
+    );
+};
+
+export default SourceFiles;
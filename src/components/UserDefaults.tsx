@@ -0,0 +1,96 @@
+import { createModelFlags, type ModelFlags } from './ModelFlagsInput';
+
+type RotationEncoding = 'Quaternion64' | 'Quaternion48';
+
+type UserDefaults = {
+    target_version: number;
+    surface_property: string;
+    prefix_animation_names: boolean;
+    rotation_encoding: RotationEncoding;
+    animation_fps: number;
+    hardware_bones_per_strip: number;
+    bone_limit: number;
+    material_limit: number;
+    weights_per_vertex: number;
+    vertex_cache_size: number;
+    optimize_overdraw: boolean;
+    overdraw_threshold: number;
+    mikktspace_tangents: boolean;
+    weld_position_tolerance: number;
+    weld_normal_angle: number;
+    weld_texture_coordinate_tolerance: number;
+    flip_texture_v: boolean;
+    normalize_texture_coordinates: boolean;
+    reject_udim_texture_coordinates: boolean;
+    maximum_polygon_vertices: number;
+    strict_polygon_limit: boolean;
+    animation_block_size: number;
+    animation_section_frames: number;
+    write_report: boolean;
+    write_dx80_mesh: boolean;
+    write_software_mesh: boolean;
+    model_flags: ModelFlags;
+    // The name of the game profile selected when the app starts, none when empty.
+    game_profile: string;
+};
+
+export type { RotationEncoding, UserDefaults };
+
+const userDefaultsKey = 'source-wrench-user-defaults';
+
+const builtInDefaults: UserDefaults = {
+    target_version: 48,
+    surface_property: 'default',
+    prefix_animation_names: true,
+    rotation_encoding: 'Quaternion64',
+    animation_fps: 30,
+    hardware_bones_per_strip: 53,
+    bone_limit: 128,
+    material_limit: 32,
+    weights_per_vertex: 3,
+    vertex_cache_size: 16,
+    optimize_overdraw: true,
+    overdraw_threshold: 1.05,
+    mikktspace_tangents: false,
+    // Vertices within the square root of the float epsilon are welded, their squared distance is within the float tolerance of the compiler.
+    weld_position_tolerance: 0.00034526698,
+    weld_normal_angle: 0.01,
+    weld_texture_coordinate_tolerance: 0.00001,
+    flip_texture_v: true,
+    normalize_texture_coordinates: false,
+    reject_udim_texture_coordinates: true,
+    maximum_polygon_vertices: 64,
+    strict_polygon_limit: false,
+    animation_block_size: 0,
+    animation_section_frames: 30,
+    write_report: false,
+    write_dx80_mesh: false,
+    write_software_mesh: false,
+    model_flags: createModelFlags(),
+    game_profile: '',
+};
+
+// Saved defaults are merged over the built in ones so settings added later still get a value.
+const loadUserDefaults = (): UserDefaults => {
+    const savedDefaults = localStorage.getItem(userDefaultsKey);
+
+    if (savedDefaults === null) {
+        return { ...builtInDefaults };
+    }
+
+    try {
+        return { ...builtInDefaults, ...(JSON.parse(savedDefaults) as Partial<UserDefaults>) };
+    } catch {
+        return { ...builtInDefaults };
+    }
+};
+
+const saveUserDefaults = (defaults: UserDefaults) => {
+    localStorage.setItem(userDefaultsKey, JSON.stringify(defaults));
+};
+
+const resetUserDefaults = () => {
+    localStorage.removeItem(userDefaultsKey);
+};
+
+export { loadUserDefaults, resetUserDefaults, saveUserDefaults };
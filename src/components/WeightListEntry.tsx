@@ -0,0 +1,103 @@
+import { For, type Component } from 'solid-js';
+import { type SetStoreFunction } from 'solid-js/store';
+
+type WeightListBoneWeight = {
+    bone: string;
+    weight: number;
+};
+
+type WeightListEntryProperties = {
+    readonly identifier: number;
+    readonly setWeightListEntries: SetStoreFunction<WeightListEntryProperties[]>;
+    readonly data: {
+        name: string;
+        weights: WeightListBoneWeight[];
+    };
+};
+
+export type { WeightListBoneWeight, WeightListEntryProperties };
+
+const WeightListEntry: Component<WeightListEntryProperties> = (properties) => {
+    const removeWeightList = () => {
+        properties.setWeightListEntries((weightLists) => weightLists.filter((weightList) => weightList.identifier !== properties.identifier));
+    };
+
+    const changeWeightListName = (name: string) => {
+        properties.setWeightListEntries((weightList) => weightList.identifier == properties.identifier, 'data', 'name', name);
+    };
+
+    const addBoneWeight = () => {
+        properties.setWeightListEntries(
+            (weightList) => weightList.identifier == properties.identifier,
+            'data',
+            'weights',
+            (weights) => [...weights, { bone: '', weight: 1 }],
+        );
+    };
+
+    const removeBoneWeight = (weightIndex: number) => {
+        properties.setWeightListEntries(
+            (weightList) => weightList.identifier == properties.identifier,
+            'data',
+            'weights',
+            (weights) => weights.filter((_, index) => index !== weightIndex),
+        );
+    };
+
+    const changeBoneWeightBone = (weightIndex: number, bone: string) => {
+        properties.setWeightListEntries((weightList) => weightList.identifier == properties.identifier, 'data', 'weights', weightIndex, 'bone', bone);
+    };
+
+    const changeBoneWeightWeight = (weightIndex: number, weight: number) => {
+        properties.setWeightListEntries(
+            (weightList) => weightList.identifier == properties.identifier,
+            'data',
+            'weights',
+            weightIndex,
+            'weight',
+            Math.min(1, Math.max(0, weight || 0)),
+        );
+    };
+
+    return (
+        <div class="Weight-List-Entry">
+            <h3>Weight List</h3>
+            <label>
+                Name:
+                <input name="WeightListName" type="text" value={properties.data.name} onChange={(event) => changeWeightListName(event.target.value)} />
+            </label>
+            <br />
+            <label>
+                Bones:
+                <br />
+                <For each={properties.data.weights}>
+                    {(boneWeight, weightIndex) => (
+                        <div>
+                            <input
+                                name={`WeightListBone${weightIndex()}`}
+                                type="text"
+                                value={boneWeight.bone}
+                                onChange={(event) => changeBoneWeightBone(weightIndex(), event.target.value)}
+                            />
+                            <input
+                                name={`WeightListWeight${weightIndex()}`}
+                                type="number"
+                                min="0"
+                                max="1"
+                                step="0.1"
+                                value={boneWeight.weight}
+                                onChange={(event) => changeBoneWeightWeight(weightIndex(), event.target.valueAsNumber)}
+                            />
+                            <button onClick={() => removeBoneWeight(weightIndex())}>-</button>
+                        </div>
+                    )}
+                </For>
+                <button onClick={() => addBoneWeight()}>+</button>
+            </label>
+            <br />
+            <button onClick={() => removeWeightList()}>Remove</button>
+        </div>
+    );
+};
+
+export default WeightListEntry;
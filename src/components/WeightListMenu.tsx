@@ -0,0 +1,46 @@
+import { For, type Component } from 'solid-js';
+import { type SetStoreFunction } from 'solid-js/store';
+import WeightListEntry, { type WeightListEntryProperties } from './WeightListEntry';
+
+type WeightListMenuProperties = {
+    weightListEntries: WeightListEntryProperties[];
+    setWeightListEntries: SetStoreFunction<WeightListEntryProperties[]>;
+};
+
+export type { WeightListMenuProperties };
+
+const WeightListMenu: Component<WeightListMenuProperties> = (properties) => {
+    const addWeightList = () => {
+        properties.setWeightListEntries([...properties.weightListEntries, createNewWeightList()]);
+    };
+
+    let weightListIdentifierGenerator = 0;
+    const createNewWeightList = (): WeightListEntryProperties => {
+        return {
+            identifier: weightListIdentifierGenerator++,
+            setWeightListEntries: properties.setWeightListEntries,
+            data: {
+                name: 'New Weight List',
+                weights: [],
+            },
+        };
+    };
+
+    return (
+        <section id="Weight-List-Menu">
+            <h2>Weight Lists</h2>
+            <p>Bones not listed use the weight of their parent bone, or zero if they have no parent.</p>
+            <button onClick={() => addWeightList()}>Add Weight List</button>
+            <For each={properties.weightListEntries}>
+                {({ identifier, setWeightListEntries, data }) => (
+                    <WeightListEntry identifier={identifier} setWeightListEntries={setWeightListEntries} data={data} />
+                )}
+            </For>
+            <datalist id="Weight-List-Names">
+                <For each={properties.weightListEntries}>{({ data }) => <option value={data.name}></option>}</For>
+            </datalist>
+        </section>
+    );
+};
+
+export default WeightListMenu;
@@ -0,0 +1,114 @@
+import { For, Show, type Component } from 'solid-js';
+
+type WorldModel = {
+    model_name: string;
+    origin: [number, number, number];
+    sequences: string[];
+    body_parts: string[];
+};
+
+type WorldModelInputProperties = {
+    value: WorldModel | null;
+    onChange: (value: WorldModel | null) => void;
+};
+
+export type { WorldModel, WorldModelInputProperties };
+
+const axes = ['X', 'Y', 'Z'] as const;
+
+// The world model shares the sources of the view model, empty lists keep every sequence or body part.
+const WorldModelInput: Component<WorldModelInputProperties> = (properties) => {
+    const change = <K extends keyof WorldModel>(key: K, value: WorldModel[K]) => {
+        if (properties.value === null) {
+            return;
+        }
+
+        properties.onChange({ ...properties.value, [key]: value });
+    };
+
+    const changeOrigin = (axis: number, value: number) => {
+        const origin: [number, number, number] = [...properties.value!.origin];
+        origin[axis] = value || 0;
+        change('origin', origin);
+    };
+
+    const changeName = (key: 'sequences' | 'body_parts', index: number, value: string) => {
+        change(key, properties.value![key].map((name, nameIndex) => (nameIndex === index ? value : name)));
+    };
+
+    return (
+        <>
+            <label>
+                Compile World Model
+                <input
+                    name="CompileWorldModel"
+                    type="checkbox"
+                    checked={properties.value !== null}
+                    onChange={(event) =>
+                        properties.onChange(event.target.checked ? { model_name: '', origin: [0, 0, 0], sequences: [], body_parts: [] } : null)
+                    }
+                />
+            </label>
+            <Show when={properties.value}>
+                {(worldModel) => (
+                    <div>
+                        <label>
+                            World Model Name
+                            <input
+                                name="WorldModelName"
+                                type="text"
+                                value={worldModel().model_name}
+                                onChange={(event) => change('model_name', event.target.value)}
+                            />
+                        </label>
+                        <div>
+                            <For each={axes}>
+                                {(axis, axisIndex) => (
+                                    <label>
+                                        Origin {axis}:
+                                        <input
+                                            name={`WorldModelOrigin${axis}`}
+                                            type="number"
+                                            value={worldModel().origin[axisIndex()]}
+                                            onChange={(event) => changeOrigin(axisIndex(), event.target.valueAsNumber)}
+                                        />
+                                    </label>
+                                )}
+                            </For>
+                        </div>
+                        <For each={['sequences', 'body_parts'] as const}>
+                            {(key) => (
+                                <div>
+                                    <For each={worldModel()[key]}>
+                                        {(name, index) => (
+                                            <div>
+                                                <label>
+                                                    {key === 'sequences' ? 'Sequence' : 'Body Part'}
+                                                    <input
+                                                        name={`WorldModel${key}${index()}`}
+                                                        type="text"
+                                                        list={key === 'sequences' ? 'Sequence-Names' : undefined}
+                                                        value={name}
+                                                        onChange={(event) => changeName(key, index(), event.target.value)}
+                                                    />
+                                                </label>
+                                                <button onClick={() => change(key, worldModel()[key].filter((_, nameIndex) => nameIndex !== index()))}>
+                                                    Remove
+                                                </button>
+                                            </div>
+                                        )}
+                                    </For>
+                                    <button onClick={() => change(key, [...worldModel()[key], ''])}>
+                                        {key === 'sequences' ? 'Keep Sequence' : 'Keep Body Part'}
+                                    </button>
+                                </div>
+                            )}
+                        </For>
+                    </div>
+                )}
+            </Show>
+        </>
+    );
+};
+
+export default WorldModelInput;